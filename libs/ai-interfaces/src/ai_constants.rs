@@ -4,3 +4,12 @@ pub const LLAMA_VAR_NAME: &str = "META_LLAMA_API_KEY";
 pub const MISTRAL_VAR_NAME: &str = "MISTRAL_API_KEY";
 pub const OPENAI_VAR_NAME: &str = "OPENAI_API_KEY";
 pub const PERPLEXITY_VAR_NAME: &str = "PERPLEXITY_API_KEY";
+pub const BLOCKMESH_MANAGER_URL_VAR_NAME: &str = "BLOCKMESH_MANAGER_URL";
+pub const BLOCKMESH_EMAIL_VAR_NAME: &str = "BLOCKMESH_EMAIL";
+pub const BLOCKMESH_API_TOKEN_VAR_NAME: &str = "BLOCKMESH_API_TOKEN";
+pub const ANTHROPIC_BASE_URL: &str = "https://api.anthropic.com";
+pub const GEMINI_BASE_URL: &str = "https://generativelanguage.googleapis.com";
+pub const LLAMA_BASE_URL: &str = "https://api.llama-api.com";
+pub const MISTRAL_BASE_URL: &str = "https://api.mistral.ai";
+pub const OPENAI_BASE_URL: &str = "https://api.openai.com";
+pub const PERPLEXITY_BASE_URL: &str = "https://api.perplexity.ai";
@@ -1,6 +1,16 @@
-use crate::ai_constants::ANTHROPIC_VAR_NAME;
+use crate::ai_constants::{ANTHROPIC_BASE_URL, ANTHROPIC_VAR_NAME};
 use crate::clients::bulk::Role as SuperRole;
 use crate::clients::bulk::{ChatCompletionExt, Message};
+use crate::clients::request::{
+    parse_arguments, Capabilities, CompletionRequest, ContentPart, ToolCall,
+};
+use crate::clients::stream::{
+    completion_stream, CompletionDelta, CompletionStream, SseEvent, SseParser, StopReason,
+    Usage as StreamUsage,
+};
+#[cfg(test)]
+use crate::clients::stream::{replay_fixture, text_of};
+use crate::clients::transport::{HttpRequest, HttpResponse, Transport};
 use crate::models::anthropic::AnthropicModels;
 use crate::models::base::ModelName;
 use anyhow::{anyhow, Context};
@@ -8,81 +18,157 @@ use async_trait::async_trait;
 use dotenv::dotenv;
 use reqwest::Client;
 use serde::{Deserialize, Serialize, Serializer};
+use serde_json::Value;
+use std::collections::HashMap;
 use std::env::VarError;
 use std::fmt::{Display, Formatter};
 
+const CAPABILITIES: Capabilities = Capabilities {
+    images: true,
+    tools: true,
+    stop_sequences: true,
+};
+
 #[async_trait]
 impl ChatCompletionExt for AnthropicClient {
     async fn completion(
         &self,
         model_name: ModelName,
-        messages: Vec<Message>,
+        request: CompletionRequest,
     ) -> anyhow::Result<Message> {
-        let request = ChatRequest {
-            model: model_name.to_string(),
-            max_tokens: 1024,
-            messages: messages
-                .into_iter()
-                .map(|msg| {
-                    if matches!(msg.role, SuperRole::User) {
-                        ChatMessage::user(msg.content)
-                    } else {
-                        ChatMessage::assistant(msg.content)
-                    }
-                })
-                .collect(),
-        };
-        let mut result = self.chat_completion(&request).await?;
+        request.ensure_supported("Anthropic", &CAPABILITIES)?;
+        let request = self.request(model_name, &request, false);
+        let result = self.chat_completion(&request).await?;
         let role = match result.role {
             Role::User => SuperRole::User,
             Role::Assistant => SuperRole::Assistant,
         };
-        let content = result
+        let content: Vec<ContentPart> = result
             .content
-            .pop()
-            .context("Anthropic returned no completion message")?
-            .text;
+            .into_iter()
+            .filter_map(Block::into_part)
+            .collect();
+        if content.is_empty() {
+            return Err(anyhow!("Anthropic returned no completion message"));
+        }
         Ok(Message { role, content })
     }
+
+    async fn stream_completion(
+        &self,
+        model_name: ModelName,
+        request: CompletionRequest,
+    ) -> anyhow::Result<CompletionStream> {
+        request.ensure_supported("Anthropic", &CAPABILITIES)?;
+        let request = self.request(model_name, &request, true);
+        let body = self
+            .transport
+            .stream(&self.client, &self.http_request(&request)?, api_error)
+            .await?;
+        Ok(completion_stream(body, StreamParser::default()))
+    }
 }
+
+pub const DEFAULT_MAX_TOKENS: u32 = 1024;
+
 pub struct AnthropicClient {
     client: Client,
     api_key: String,
+    base_url: String,
+    transport: Transport,
+    max_tokens: u32,
 }
 
 impl AnthropicClient {
     pub fn new(client: Client, api_key: String) -> Self {
-        Self { client, api_key }
+        Self {
+            client,
+            api_key,
+            base_url: ANTHROPIC_BASE_URL.to_string(),
+            max_tokens: DEFAULT_MAX_TOKENS,
+            transport: Transport::default(),
+        }
     }
     pub fn from_env(client: Client, env_var_name: &str) -> Result<Self, VarError> {
         let api_key = std::env::var(env_var_name)?;
         Ok(Self::new(client, api_key))
     }
 
-    async fn chat_completion(&self, chat_request: &ChatRequest) -> anyhow::Result<ChatResponse> {
-        let url = "https://api.anthropic.com/v1/messages";
-        let response = self
-            .client
-            .post(url)
-            .header("x-api-key", &self.api_key)
-            .header("anthropic-version", "2023-06-01")
-            .json(chat_request)
-            .send()
-            .await?;
-        if response.status().is_success() {
-            return Ok(response.json().await?);
-        }
-        if response.status().is_client_error() {
-            let error: Error = response.json().await?;
-            return Err(anyhow!(error));
+    /// Points the client at another deployment, a proxy or a mock server.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn with_transport(mut self, transport: Transport) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = max_tokens;
+        self
+    }
+
+    /// `max_tokens` is required by the API, the client default applies unless the request sets it.
+    fn request(
+        &self,
+        model_name: ModelName,
+        request: &CompletionRequest,
+        stream: bool,
+    ) -> ChatRequest {
+        ChatRequest {
+            model: model_name.to_string(),
+            max_tokens: request.params.max_tokens.unwrap_or(self.max_tokens),
+            system: request.system.clone(),
+            messages: request
+                .messages
+                .iter()
+                .filter(|msg| !msg.content.is_empty())
+                .map(|msg| ChatMessage {
+                    role: match msg.role {
+                        SuperRole::User => Role::User,
+                        SuperRole::Assistant => Role::Assistant,
+                    },
+                    content: msg.content.iter().map(Block::from).collect(),
+                })
+                .collect(),
+            tools: request
+                .tools
+                .iter()
+                .map(|tool| ToolSpec {
+                    name: tool.name.clone(),
+                    description: tool.description.clone(),
+                    input_schema: tool.parameters.clone(),
+                })
+                .collect(),
+            temperature: request.params.temperature,
+            top_p: request.params.top_p,
+            stop_sequences: request.params.stop.clone(),
+            stream,
         }
-        Err(anyhow!(
-            "Unexpected response status code {} for Anthorpic chat completion request",
-            response.status()
-        ))
+    }
+
+    async fn chat_completion(&self, chat_request: &ChatRequest) -> anyhow::Result<ChatResponse> {
+        self.transport
+            .send(&self.client, &self.http_request(chat_request)?, api_error)
+            .await?
+            .json()
+    }
+
+    fn http_request(&self, chat_request: &ChatRequest) -> anyhow::Result<HttpRequest> {
+        Ok(
+            HttpRequest::post(format!("{}/v1/messages", self.base_url), chat_request)?
+                .header("x-api-key", &self.api_key)
+                .header("anthropic-version", "2023-06-01"),
+        )
     }
 }
 
+fn api_error(response: HttpResponse) -> anyhow::Error {
+    response.into_error::<Error>("Anthropic")
+}
+
 #[derive(Deserialize, Debug)]
 struct Error {
     #[serde(rename = "type")]
@@ -114,12 +200,31 @@ enum Role {
 struct ChatRequest {
     model: String,
     max_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system: Option<String>,
     messages: Vec<ChatMessage>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<ToolSpec>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    stop_sequences: Vec<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+}
+
+#[derive(Serialize, Debug)]
+struct ToolSpec {
+    name: String,
+    description: String,
+    input_schema: Value,
 }
 
 #[derive(Deserialize, Debug)]
 struct ChatResponse {
-    content: Vec<Content>,
+    content: Vec<Block>,
     id: String,
     model: String,
     role: Role,
@@ -130,11 +235,75 @@ struct ChatResponse {
     usage: Usage,
 }
 
-#[derive(Deserialize, Debug)]
-struct Content {
-    text: String,
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Block {
+    Text {
+        text: String,
+    },
+    Image {
+        source: ImageSource,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        input: Value,
+    },
+    ToolResult {
+        tool_use_id: String,
+        content: String,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        is_error: bool,
+    },
+    #[serde(other)]
+    Other,
+}
+
+impl From<&ContentPart> for Block {
+    fn from(part: &ContentPart) -> Self {
+        match part {
+            ContentPart::Text(text) => Self::Text { text: text.clone() },
+            ContentPart::Image { mime_type, data } => Self::Image {
+                source: ImageSource {
+                    kind: String::from("base64"),
+                    media_type: mime_type.clone(),
+                    data: data.clone(),
+                },
+            },
+            ContentPart::ToolCall(call) => Self::ToolUse {
+                id: call.id.clone(),
+                name: call.name.clone(),
+                input: call.arguments.clone(),
+            },
+            ContentPart::ToolResult(result) => Self::ToolResult {
+                tool_use_id: result.call_id.clone(),
+                content: result.content.clone(),
+                is_error: result.is_error,
+            },
+        }
+    }
+}
+
+impl Block {
+    fn into_part(self) -> Option<ContentPart> {
+        match self {
+            Self::Text { text } => Some(ContentPart::Text(text)),
+            Self::ToolUse { id, name, input } => Some(ContentPart::ToolCall(ToolCall {
+                id,
+                name,
+                arguments: input,
+            })),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct ImageSource {
     #[serde(rename = "type")]
     kind: String,
+    media_type: String,
+    data: String,
 }
 
 #[derive(Deserialize, Debug)]
@@ -143,39 +312,301 @@ struct Usage {
     output_tokens: u32,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-struct ChatMessage {
-    role: Role,
-    content: String,
+#[derive(Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum StreamEvent {
+    MessageStart {
+        message: StreamMessage,
+    },
+    ContentBlockStart {
+        index: usize,
+        content_block: Block,
+    },
+    ContentBlockDelta {
+        index: usize,
+        delta: BlockDelta,
+    },
+    ContentBlockStop {
+        index: usize,
+    },
+    MessageDelta {
+        delta: StopDelta,
+        usage: OutputUsage,
+    },
+    Error {
+        error: InnerError,
+    },
+    #[serde(other)]
+    Other,
 }
 
-impl ChatMessage {
-    fn user(content: String) -> Self {
-        Self {
-            role: Role::User,
-            content,
-        }
-    }
+#[derive(Deserialize, Debug)]
+struct StreamMessage {
+    usage: Usage,
+}
 
-    fn assistant(content: String) -> Self {
-        Self {
-            role: Role::Assistant,
-            content,
-        }
+#[derive(Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum BlockDelta {
+    TextDelta {
+        text: String,
+    },
+    InputJsonDelta {
+        partial_json: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize, Debug)]
+struct StopDelta {
+    stop_reason: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct OutputUsage {
+    output_tokens: u32,
+}
+
+/// `message_start` carries the input token count, `message_delta` the stop reason
+/// and the final output count, so usage is reported once both are known.
+/// Tool inputs are streamed as JSON fragments and emitted when their block stops.
+#[derive(Default)]
+struct StreamParser {
+    input_tokens: u32,
+    tool_calls: HashMap<usize, (String, String, String)>,
+}
+
+impl SseParser for StreamParser {
+    fn parse(&mut self, event: &SseEvent) -> anyhow::Result<Vec<CompletionDelta>> {
+        Ok(match serde_json::from_str(&event.data)? {
+            StreamEvent::MessageStart { message } => {
+                self.input_tokens = message.usage.input_tokens;
+                vec![]
+            }
+            StreamEvent::ContentBlockStart {
+                index,
+                content_block: Block::ToolUse { id, name, .. },
+            } => {
+                self.tool_calls.insert(index, (id, name, String::new()));
+                vec![]
+            }
+            StreamEvent::ContentBlockDelta {
+                delta: BlockDelta::TextDelta { text },
+                ..
+            } => vec![CompletionDelta::Text(text)],
+            StreamEvent::ContentBlockDelta {
+                index,
+                delta: BlockDelta::InputJsonDelta { partial_json },
+            } => {
+                if let Some((_, _, input)) = self.tool_calls.get_mut(&index) {
+                    input.push_str(&partial_json);
+                }
+                vec![]
+            }
+            StreamEvent::ContentBlockStop { index } => match self.tool_calls.remove(&index) {
+                Some((id, name, input)) => vec![CompletionDelta::ToolCall(ToolCall {
+                    id,
+                    name,
+                    arguments: parse_arguments(&input)?,
+                })],
+                None => vec![],
+            },
+            StreamEvent::MessageDelta { delta, usage } => {
+                let mut deltas = vec![];
+                if let Some(reason) = delta.stop_reason {
+                    deltas.push(CompletionDelta::Stop(StopReason::from(reason.as_str())));
+                }
+                deltas.push(CompletionDelta::Usage(StreamUsage {
+                    input_tokens: self.input_tokens,
+                    output_tokens: usage.output_tokens,
+                }));
+                deltas
+            }
+            StreamEvent::Error { error } => {
+                return Err(anyhow!("Anthropic stream error: {:#?}", error))
+            }
+            StreamEvent::ContentBlockStart { .. }
+            | StreamEvent::ContentBlockDelta { .. }
+            | StreamEvent::Other => vec![],
+        })
     }
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+struct ChatMessage {
+    role: Role,
+    content: Vec<Block>,
+}
+
 #[ignore = "Needs valid Anthropic token"]
 #[tokio::test]
 async fn anthropic() {
     dotenv().ok();
     let client = AnthropicClient::from_env(Client::new(), ANTHROPIC_VAR_NAME).unwrap();
     let result = client
-        .chat_completion(&ChatRequest {
-            model: ModelName::Anthropic(AnthropicModels::default()).to_string(),
-            max_tokens: 1024,
-            messages: vec![ChatMessage::user(String::from("Introduce yourself"))],
+        .chat_completion(&client.request(
+            ModelName::Anthropic(AnthropicModels::default()),
+            &CompletionRequest::new(vec![Message::user("Introduce yourself")]),
+            false,
+        ))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_stream_fixture() {
+    let deltas = replay_fixture(
+        include_str!("../../fixtures/anthropic_stream.sse"),
+        StreamParser::default(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(text_of(&deltas), "Hello! I'm Claude — nice to meet you.");
+    assert_eq!(
+        deltas[deltas.len() - 2..],
+        [
+            CompletionDelta::Stop(StopReason::EndTurn),
+            CompletionDelta::Usage(StreamUsage {
+                input_tokens: 12,
+                output_tokens: 14,
+            }),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_stream_error_event() {
+    let fixture = "event: error\ndata: {\"type\":\"error\",\"error\":{\"type\":\"overloaded_error\",\"message\":\"Overloaded\"}}\n\n";
+    let error = replay_fixture(fixture, StreamParser::default())
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("Overloaded"));
+}
+
+#[tokio::test]
+async fn test_stream_tool_call_fixture() {
+    let deltas = replay_fixture(
+        include_str!("../../fixtures/anthropic_tool_call_stream.sse"),
+        StreamParser::default(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(text_of(&deltas), "Let me check that node.");
+    assert!(deltas.contains(&CompletionDelta::ToolCall(ToolCall {
+        id: String::from("toolu_01T1x1fJ34qAmk2tNTrN7Up6"),
+        name: String::from("get_uptime"),
+        arguments: serde_json::json!({"node": "home-router", "days": 7}),
+    })));
+    assert!(deltas.contains(&CompletionDelta::Stop(StopReason::ToolUse)));
+}
+
+#[test]
+fn test_request_translation() {
+    use crate::clients::request::{SamplingParams, ToolDefinition, ToolResult};
+    let client = AnthropicClient::new(Client::new(), String::new());
+    let request = CompletionRequest::new(vec![
+        Message::user("Is home-router online?"),
+        Message {
+            role: SuperRole::Assistant,
+            content: vec![ContentPart::ToolCall(ToolCall {
+                id: String::from("toolu_1"),
+                name: String::from("get_uptime"),
+                arguments: serde_json::json!({"node": "home-router"}),
+            })],
+        },
+        Message {
+            role: SuperRole::User,
+            content: vec![ContentPart::ToolResult(ToolResult {
+                call_id: String::from("toolu_1"),
+                name: String::from("get_uptime"),
+                content: String::from("99.2%"),
+                is_error: false,
+            })],
+        },
+    ])
+    .with_system("You are the BlockMesh assistant")
+    .with_tool(ToolDefinition {
+        name: String::from("get_uptime"),
+        description: String::from("Uptime of a node"),
+        parameters: serde_json::json!({"type": "object"}),
+    })
+    .with_params(SamplingParams {
+        temperature: Some(0.5),
+        max_tokens: Some(256),
+        ..SamplingParams::default()
+    });
+    let body = serde_json::to_value(client.request(
+        ModelName::Anthropic(AnthropicModels::default()),
+        &request,
+        false,
+    ))
+    .unwrap();
+    assert_eq!(
+        body,
+        serde_json::json!({
+            "model": "claude-3-5-haiku-latest",
+            "max_tokens": 256,
+            "system": "You are the BlockMesh assistant",
+            "messages": [
+                {"role": "user", "content": [{"type": "text", "text": "Is home-router online?"}]},
+                {"role": "assistant", "content": [
+                    {"type": "tool_use", "id": "toolu_1", "name": "get_uptime", "input": {"node": "home-router"}}
+                ]},
+                {"role": "user", "content": [
+                    {"type": "tool_result", "tool_use_id": "toolu_1", "content": "99.2%"}
+                ]}
+            ],
+            "tools": [{"name": "get_uptime", "description": "Uptime of a node", "input_schema": {"type": "object"}}],
+            "temperature": 0.5
         })
+    );
+}
+
+#[tokio::test]
+async fn test_error_mapping() {
+    use crate::clients::mock::{MockResponse, MockServer};
+    use crate::error::AiInterfaceError;
+    let server = MockServer::start(vec![
+        MockResponse::json(
+            400,
+            serde_json::json!({
+                "type": "error",
+                "error": { "type": "invalid_request_error", "message": "max_tokens is too large" }
+            }),
+        ),
+        MockResponse::text(502, "upstream connect error"),
+    ])
+    .await;
+    let client =
+        AnthropicClient::new(Client::new(), String::from("key")).with_base_url(server.url());
+    let request = || CompletionRequest::new(vec![Message::user("hi")]);
+    let model = ModelName::Anthropic(AnthropicModels::default());
+
+    let error = client
+        .completion(model.clone(), request())
+        .await
+        .unwrap_err();
+    match error.downcast_ref::<AiInterfaceError>() {
+        Some(AiInterfaceError::Provider {
+            provider,
+            status,
+            message,
+        }) => {
+            assert_eq!((provider.as_str(), *status), ("Anthropic", 400));
+            assert!(message.contains("max_tokens is too large"));
+        }
+        other => panic!("unexpected error {:?}", other),
+    }
+    // bodies that are not provider errors are kept as they are
+    let error = client
+        .stream_completion(model, request())
         .await
+        .err()
         .unwrap();
+    assert_eq!(
+        error.to_string(),
+        "Anthropic request failed with status 502: upstream connect error"
+    );
+    assert_eq!(server.received()[0].headers["x-api-key"], "key");
 }
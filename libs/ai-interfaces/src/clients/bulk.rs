@@ -3,12 +3,14 @@ use crate::ai_constants::{
     PERPLEXITY_VAR_NAME,
 };
 use crate::clients::anthropic::AnthropicClient;
-use crate::clients::bulk::ClientKind::Perplexity;
 use crate::clients::google::GeminiClient;
 use crate::clients::meta::LlamaClient;
 use crate::clients::mistral::MistralClient;
 use crate::clients::openai::OpenAiClient;
 use crate::clients::perplexity::PerplexityClient;
+use crate::clients::request::{CompletionRequest, ContentPart, ToolCall};
+use crate::clients::stream::{CompletionDelta, CompletionStream, StopReason, Usage};
+use crate::clients::transport::Transport;
 use crate::models::anthropic::AnthropicModels;
 use crate::models::base::ModelName;
 use crate::models::google::GoogleModels;
@@ -16,25 +18,33 @@ use crate::models::meta::MetaModels;
 use crate::models::mistral::MistralModels;
 use crate::models::open_ai::OpenAiModels;
 use crate::models::perplexity::PerplexityModels;
+use anyhow::anyhow;
 use async_trait::async_trait;
 use dotenv::dotenv;
+use enum_iterator::{all, Sequence};
+use futures::stream::FuturesUnordered;
+use futures::{StreamExt, TryStreamExt};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::env::VarError;
+use std::fmt::{Display, Formatter};
 use std::fs::File;
 use std::io::Write;
+use std::time::{Duration, Instant};
+use tokio::time::timeout;
 
+/// Providers without an API key are left out, see [`AIClient::supports`].
 pub struct AIClient {
-    anthropic: AnthropicClient,
-    google: GeminiClient,
-    meta: LlamaClient,
-    mistral: MistralClient,
-    openai: OpenAiClient,
-    perplexity: PerplexityClient,
+    anthropic: Option<AnthropicClient>,
+    google: Option<GeminiClient>,
+    meta: Option<LlamaClient>,
+    mistral: Option<MistralClient>,
+    openai: Option<OpenAiClient>,
+    perplexity: Option<PerplexityClient>,
 }
 
-#[derive(Hash, PartialEq, Eq, Serialize, Deserialize, Clone, Debug)]
+#[derive(Hash, PartialEq, Eq, Serialize, Deserialize, Clone, Debug, Sequence)]
 pub enum ClientKind {
     Anthropic,
     Google,
@@ -44,138 +54,404 @@ pub enum ClientKind {
     Perplexity,
 }
 
+impl Display for ClientKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Anthropic => write!(f, "Anthropic"),
+            Self::Google => write!(f, "Google"),
+            Self::Meta => write!(f, "Meta"),
+            Self::Mistral => write!(f, "Mistral"),
+            Self::OpenAi => write!(f, "OpenAi"),
+            Self::Perplexity => write!(f, "Perplexity"),
+        }
+    }
+}
+
+impl ClientKind {
+    pub fn default_model(&self) -> ModelName {
+        match self {
+            Self::Anthropic => ModelName::Anthropic(AnthropicModels::default()),
+            Self::Google => ModelName::Google(GoogleModels::default()),
+            Self::Meta => ModelName::Meta(MetaModels::default()),
+            Self::Mistral => ModelName::Mistral(MistralModels::default()),
+            Self::OpenAi => ModelName::OpenAi(OpenAiModels::default()),
+            Self::Perplexity => ModelName::Perplexity(PerplexityModels::default()),
+        }
+    }
+}
+
+/// When [`AIClient::completions`] stops waiting for the remaining providers.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FanOutStrategy {
+    #[default]
+    All,
+    FirstSuccess,
+    Quorum(usize),
+}
+
+impl FanOutStrategy {
+    fn required(&self, requested: usize) -> usize {
+        match self {
+            Self::All => requested,
+            Self::FirstSuccess => 1,
+            Self::Quorum(n) => (*n).min(requested),
+        }
+    }
+}
+
+pub const DEFAULT_COMPLETION_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone)]
+pub struct CompletionOptions {
+    pub strategy: FanOutStrategy,
+    pub timeout: Duration,
+    timeouts: HashMap<ClientKind, Duration>,
+    models: HashMap<ClientKind, ModelName>,
+}
+
+impl Default for CompletionOptions {
+    fn default() -> Self {
+        Self::new(FanOutStrategy::default())
+    }
+}
+
+impl CompletionOptions {
+    pub fn new(strategy: FanOutStrategy) -> Self {
+        Self {
+            strategy,
+            timeout: DEFAULT_COMPLETION_TIMEOUT,
+            timeouts: HashMap::new(),
+            models: HashMap::new(),
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_provider_timeout(mut self, kind: ClientKind, timeout: Duration) -> Self {
+        self.timeouts.insert(kind, timeout);
+        self
+    }
+
+    /// Replaces the provider's default model, the provider is taken from the model.
+    pub fn with_model(mut self, model: ModelName) -> Self {
+        self.models.insert(model.client_kind(), model);
+        self
+    }
+
+    pub fn model(&self, kind: &ClientKind) -> ModelName {
+        self.models
+            .get(kind)
+            .cloned()
+            .unwrap_or_else(|| kind.default_model())
+    }
+
+    pub fn timeout(&self, kind: &ClientKind) -> Duration {
+        self.timeouts.get(kind).copied().unwrap_or(self.timeout)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Completion {
+    pub model: ModelName,
+    pub message: Message,
+    pub stop_reason: Option<StopReason>,
+    pub usage: Option<Usage>,
+    pub cost_usd: Option<f64>,
+    pub latency: Duration,
+}
+
 impl AIClient {
+    /// Requires the API key of every provider.
     pub fn new() -> Result<Self, VarError> {
         let client = reqwest::Client::new();
-        let anthropic = AnthropicClient::from_env(client.clone(), ANTHROPIC_VAR_NAME)?;
-        let google = GeminiClient::from_env(client.clone(), GEMINI_VAR_NAME)?;
-        let meta = LlamaClient::from_env(client.clone(), LLAMA_VAR_NAME)?;
-        let mistral = MistralClient::from_env(client.clone(), MISTRAL_VAR_NAME)?;
-        let openai = OpenAiClient::from_env(client.clone(), OPENAI_VAR_NAME)?;
-        let perplexity = PerplexityClient::from_env(client, PERPLEXITY_VAR_NAME)?;
         Ok(Self {
-            anthropic,
-            google,
-            meta,
-            mistral,
-            openai,
-            perplexity,
+            anthropic: Some(AnthropicClient::from_env(
+                client.clone(),
+                ANTHROPIC_VAR_NAME,
+            )?),
+            google: Some(GeminiClient::from_env(client.clone(), GEMINI_VAR_NAME)?),
+            meta: Some(LlamaClient::from_env(client.clone(), LLAMA_VAR_NAME)?),
+            mistral: Some(MistralClient::from_env(client.clone(), MISTRAL_VAR_NAME)?),
+            openai: Some(OpenAiClient::from_env(client.clone(), OPENAI_VAR_NAME)?),
+            perplexity: Some(PerplexityClient::from_env(client, PERPLEXITY_VAR_NAME)?),
         })
     }
+
+    /// Builds the providers whose API key is set and skips the rest.
+    pub fn from_available_env() -> Self {
+        let client = reqwest::Client::new();
+        Self {
+            anthropic: AnthropicClient::from_env(client.clone(), ANTHROPIC_VAR_NAME).ok(),
+            google: GeminiClient::from_env(client.clone(), GEMINI_VAR_NAME).ok(),
+            meta: LlamaClient::from_env(client.clone(), LLAMA_VAR_NAME).ok(),
+            mistral: MistralClient::from_env(client.clone(), MISTRAL_VAR_NAME).ok(),
+            openai: OpenAiClient::from_env(client.clone(), OPENAI_VAR_NAME).ok(),
+            perplexity: PerplexityClient::from_env(client, PERPLEXITY_VAR_NAME).ok(),
+        }
+    }
+
+    /// Only the OpenAI models, served by an OpenAI-compatible endpoint at `base_url` that takes no
+    /// API key, e.g. a self-hosted server reached through [`Transport::BlockMesh`].
+    pub fn keyless_openai_compatible(base_url: impl Into<String>) -> Self {
+        Self {
+            anthropic: None,
+            google: None,
+            meta: None,
+            mistral: None,
+            openai: Some(
+                OpenAiClient::new(reqwest::Client::new(), String::new()).with_base_url(base_url),
+            ),
+            perplexity: None,
+        }
+    }
+
+    /// Sends every provider request through `transport`, e.g. [`Transport::BlockMesh`] to run
+    /// them from BlockMesh nodes.
+    pub fn with_transport(mut self, transport: Transport) -> Self {
+        self.anthropic = self.anthropic.map(|c| c.with_transport(transport.clone()));
+        self.google = self.google.map(|c| c.with_transport(transport.clone()));
+        self.meta = self.meta.map(|c| c.with_transport(transport.clone()));
+        self.mistral = self.mistral.map(|c| c.with_transport(transport.clone()));
+        self.openai = self.openai.map(|c| c.with_transport(transport.clone()));
+        self.perplexity = self.perplexity.map(|c| c.with_transport(transport));
+        self
+    }
+
+    pub fn supports(&self, kind: &ClientKind) -> bool {
+        self.client(kind).is_ok()
+    }
+
+    pub fn client_kinds(&self) -> Vec<ClientKind> {
+        all::<ClientKind>()
+            .filter(|kind| self.supports(kind))
+            .collect()
+    }
+
+    fn client(&self, kind: &ClientKind) -> anyhow::Result<&(dyn ChatCompletionExt + Send + Sync)> {
+        let client: Option<&(dyn ChatCompletionExt + Send + Sync)> = match kind {
+            ClientKind::Anthropic => self.anthropic.as_ref().map(|c| c as _),
+            ClientKind::Google => self.google.as_ref().map(|c| c as _),
+            ClientKind::Meta => self.meta.as_ref().map(|c| c as _),
+            ClientKind::Mistral => self.mistral.as_ref().map(|c| c as _),
+            ClientKind::OpenAi => self.openai.as_ref().map(|c| c as _),
+            ClientKind::Perplexity => self.perplexity.as_ref().map(|c| c as _),
+        };
+        client.ok_or_else(|| anyhow!("{} is not configured, its API key is missing", kind))
+    }
+
+    /// Single completion from the provider serving `model`.
+    pub async fn completion(
+        &self,
+        model: ModelName,
+        request: CompletionRequest,
+    ) -> anyhow::Result<Completion> {
+        let kind = model.client_kind();
+        self.complete(&kind, model, request).await
+    }
+
+    /// Queries the providers concurrently. Providers still running once the
+    /// strategy is satisfied are cancelled and reported as `None`.
     pub async fn completions(
         &self,
         client_kinds: impl Into<HashSet<ClientKind>>,
-        messages: Vec<Message>,
+        request: CompletionRequest,
+        options: &CompletionOptions,
     ) -> AIClientResponses {
-        let mut responses = AIClientResponses::default();
-        for kind in client_kinds.into().into_iter() {
-            match kind {
-                ClientKind::Perplexity => {
-                    responses.insert(
-                        ClientKind::Perplexity,
-                        Some(
-                            self.perplexity
-                                .completion(
-                                    ModelName::Perplexity(PerplexityModels::default()),
-                                    messages.clone(),
-                                )
-                                .await,
-                        ),
-                    );
-                }
-                ClientKind::Anthropic => {
-                    responses.insert(
-                        ClientKind::Anthropic,
-                        Some(
-                            self.anthropic
-                                .completion(
-                                    ModelName::Anthropic(AnthropicModels::default()),
-                                    messages.clone(),
-                                )
-                                .await,
-                        ),
-                    );
-                }
-                ClientKind::Google => {
-                    responses.insert(
-                        ClientKind::Google,
-                        Some(
-                            self.google
-                                .completion(
-                                    ModelName::Google(GoogleModels::default()),
-                                    messages.clone(),
-                                )
-                                .await,
-                        ),
-                    );
-                }
-                ClientKind::Meta => {
-                    responses.insert(
-                        ClientKind::Meta,
-                        Some(
-                            self.meta
-                                .completion(
-                                    ModelName::Meta(MetaModels::default()),
-                                    messages.clone(),
-                                )
-                                .await,
-                        ),
-                    );
-                }
-                ClientKind::Mistral => {
-                    responses.insert(
-                        ClientKind::Mistral,
-                        Some(
-                            self.mistral
-                                .completion(
-                                    ModelName::Mistral(MistralModels::default()),
-                                    messages.clone(),
-                                )
-                                .await,
-                        ),
-                    );
+        let client_kinds = client_kinds.into();
+        let required = options.strategy.required(client_kinds.len());
+        let mut pending: FuturesUnordered<_> = client_kinds
+            .iter()
+            .map(|kind| {
+                let request = request.clone();
+                async move {
+                    let result = match timeout(
+                        options.timeout(kind),
+                        self.complete(kind, options.model(kind), request),
+                    )
+                    .await
+                    {
+                        Ok(result) => result,
+                        Err(_) => Err(anyhow!(
+                            "{:?} timed out after {:?}",
+                            kind,
+                            options.timeout(kind)
+                        )),
+                    };
+                    (kind.clone(), result)
                 }
-                ClientKind::OpenAi => {
-                    responses.insert(
-                        ClientKind::OpenAi,
-                        Some(
-                            self.openai
-                                .completion(
-                                    ModelName::OpenAi(OpenAiModels::default()),
-                                    messages.clone(),
-                                )
-                                .await,
-                        ),
-                    );
-                }
-            };
+            })
+            .collect();
+        let mut responses = AIClientResponses {
+            strategy: options.strategy,
+            responses: client_kinds
+                .iter()
+                .map(|kind| (kind.clone(), None))
+                .collect(),
+        };
+        let mut successes = 0;
+        while let Some((kind, result)) = pending.next().await {
+            if result.is_ok() {
+                successes += 1;
+            }
+            responses.responses.insert(kind, Some(result));
+            if successes >= required {
+                break;
+            }
         }
-        println!("Question: {:?}, responses: {:?}", messages, responses);
         responses
     }
+
+    async fn complete(
+        &self,
+        kind: &ClientKind,
+        model: ModelName,
+        request: CompletionRequest,
+    ) -> anyhow::Result<Completion> {
+        if model.client_kind() != *kind {
+            return Err(anyhow!("Model {} is not served by {:?}", model, kind));
+        }
+        let started = Instant::now();
+        let mut stream = self
+            .client(kind)?
+            .stream_completion(model.clone(), request)
+            .await?;
+        let mut content = Vec::new();
+        let mut stop_reason = None;
+        let mut usage = None;
+        while let Some(delta) = stream.try_next().await? {
+            match delta {
+                CompletionDelta::Text(text) => match content.last_mut() {
+                    Some(ContentPart::Text(last)) => last.push_str(&text),
+                    _ => content.push(ContentPart::Text(text)),
+                },
+                CompletionDelta::ToolCall(call) => content.push(ContentPart::ToolCall(call)),
+                CompletionDelta::Stop(reason) => stop_reason = Some(reason),
+                CompletionDelta::Usage(u) => usage = Some(u),
+            }
+        }
+        let cost_usd = usage
+            .as_ref()
+            .zip(model.pricing())
+            .map(|(usage, pricing)| pricing.cost_usd(usage));
+        Ok(Completion {
+            model,
+            message: Message {
+                content,
+                role: Role::Assistant,
+            },
+            stop_reason,
+            usage,
+            cost_usd,
+            latency: started.elapsed(),
+        })
+    }
 }
 
-pub type AIClientResponses = HashMap<ClientKind, Option<anyhow::Result<Message>>>;
+#[derive(Debug)]
+pub struct AIClientResponses {
+    pub strategy: FanOutStrategy,
+    pub responses: HashMap<ClientKind, Option<anyhow::Result<Completion>>>,
+}
 
-#[derive(Debug, Clone)]
+impl AIClientResponses {
+    pub fn successes(&self) -> impl Iterator<Item = (&ClientKind, &Completion)> {
+        self.responses
+            .iter()
+            .filter_map(|(kind, response)| match response {
+                Some(Ok(completion)) => Some((kind, completion)),
+                _ => None,
+            })
+    }
+
+    pub fn is_satisfied(&self) -> bool {
+        self.successes().count() >= self.strategy.required(self.responses.len())
+    }
+
+    pub fn total_usage(&self) -> Usage {
+        self.successes()
+            .filter_map(|(_, completion)| completion.usage)
+            .fold(Usage::default(), |total, usage| Usage {
+                input_tokens: total.input_tokens + usage.input_tokens,
+                output_tokens: total.output_tokens + usage.output_tokens,
+            })
+    }
+
+    /// Cost of the completions that were priced, providers without a price list are skipped.
+    pub fn total_cost_usd(&self) -> f64 {
+        self.successes()
+            .filter_map(|(_, completion)| completion.cost_usd)
+            .sum()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Role {
     User,
     Assistant,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Message {
-    pub content: String,
+    pub content: Vec<ContentPart>,
     pub role: Role,
 }
 
+impl Message {
+    pub fn user(text: impl Into<String>) -> Self {
+        Self {
+            content: vec![ContentPart::Text(text.into())],
+            role: Role::User,
+        }
+    }
+
+    pub fn assistant(text: impl Into<String>) -> Self {
+        Self {
+            content: vec![ContentPart::Text(text.into())],
+            role: Role::Assistant,
+        }
+    }
+
+    /// Text parts joined together, tool calls and images are left out.
+    pub fn text(&self) -> String {
+        self.content
+            .iter()
+            .filter_map(|part| match part {
+                ContentPart::Text(text) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn tool_calls(&self) -> impl Iterator<Item = &ToolCall> {
+        self.content.iter().filter_map(|part| match part {
+            ContentPart::ToolCall(call) => Some(call),
+            _ => None,
+        })
+    }
+
+    pub fn has_tool_calls(&self) -> bool {
+        self.tool_calls().next().is_some()
+    }
+}
+
 #[async_trait]
 pub trait ChatCompletionExt {
     async fn completion(
         &self,
         model_name: ModelName,
-        messages: Vec<Message>,
+        request: CompletionRequest,
     ) -> anyhow::Result<Message>;
+
+    /// Same request as [`ChatCompletionExt::completion`] but yields text as it is
+    /// generated, followed by tool calls, the stop reason and token usage.
+    async fn stream_completion(
+        &self,
+        model_name: ModelName,
+        request: CompletionRequest,
+    ) -> anyhow::Result<CompletionStream>;
 }
 
 #[derive(Clone, Serialize, Debug, Deserialize)]
@@ -184,3 +460,148 @@ pub struct Response {
     question: String,
     answer: String,
 }
+
+#[test]
+fn test_fan_out_accounting() {
+    let completion = |model: ModelName, input_tokens, output_tokens| {
+        let usage = Usage {
+            input_tokens,
+            output_tokens,
+        };
+        Completion {
+            cost_usd: model.pricing().map(|pricing| pricing.cost_usd(&usage)),
+            model,
+            message: Message::assistant("answer"),
+            stop_reason: Some(StopReason::EndTurn),
+            usage: Some(usage),
+            latency: Duration::from_millis(10),
+        }
+    };
+    let responses = AIClientResponses {
+        strategy: FanOutStrategy::Quorum(2),
+        responses: HashMap::from([
+            (
+                ClientKind::OpenAi,
+                Some(Ok(completion(
+                    ClientKind::OpenAi.default_model(),
+                    1_000,
+                    500,
+                ))),
+            ),
+            (
+                ClientKind::Meta,
+                Some(Ok(completion(ClientKind::Meta.default_model(), 100, 50))),
+            ),
+            (ClientKind::Mistral, Some(Err(anyhow!("timed out")))),
+            (ClientKind::Google, None),
+        ]),
+    };
+    assert!(responses.is_satisfied());
+    assert_eq!(
+        responses.total_usage(),
+        Usage {
+            input_tokens: 1_100,
+            output_tokens: 550,
+        }
+    );
+    // Meta has no price list, only the gpt-4 answer is counted
+    assert!((responses.total_cost_usd() - 0.06).abs() < 1e-9);
+    assert!(!AIClientResponses {
+        strategy: FanOutStrategy::All,
+        ..responses
+    }
+    .is_satisfied());
+}
+
+#[tokio::test]
+async fn test_unconfigured_provider() {
+    let client = AIClient {
+        anthropic: None,
+        google: None,
+        meta: None,
+        mistral: None,
+        openai: None,
+        perplexity: None,
+    };
+    assert!(client.client_kinds().is_empty());
+    let err = client
+        .completion(
+            ClientKind::Mistral.default_model(),
+            vec![Message::user("hi")].into(),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Mistral is not configured, its API key is missing"
+    );
+}
+
+#[tokio::test]
+async fn test_completions_against_mock_providers() {
+    use crate::clients::mock::{MockResponse, MockServer};
+    let openai = MockServer::start(vec![MockResponse::sse(include_str!(
+        "../../fixtures/openai_stream.sse"
+    ))])
+    .await;
+    let anthropic = MockServer::start(vec![MockResponse::sse(include_str!(
+        "../../fixtures/anthropic_stream.sse"
+    ))])
+    .await;
+    let mistral = MockServer::start(vec![MockResponse::text(503, "overloaded")]).await;
+    let key = || String::from("key");
+    let client = AIClient {
+        anthropic: Some(AnthropicClient::new(Client::new(), key()).with_base_url(anthropic.url())),
+        google: None,
+        meta: None,
+        mistral: Some(MistralClient::new(Client::new(), key()).with_base_url(mistral.url())),
+        openai: Some(OpenAiClient::new(Client::new(), key()).with_base_url(openai.url())),
+        perplexity: None,
+    };
+    let responses = client
+        .completions(
+            client.client_kinds().into_iter().collect::<HashSet<_>>(),
+            vec![Message::user("hi")].into(),
+            &CompletionOptions::default(),
+        )
+        .await;
+    let text = |kind: ClientKind| match &responses.responses[&kind] {
+        Some(Ok(completion)) => completion.message.content.clone(),
+        other => panic!("{} failed: {:?}", kind, other),
+    };
+    assert_eq!(
+        text(ClientKind::OpenAi),
+        vec![ContentPart::Text(String::from(
+            "Hi there — how can I help?"
+        ))]
+    );
+    assert_eq!(
+        text(ClientKind::Anthropic),
+        vec![ContentPart::Text(String::from(
+            "Hello! I'm Claude — nice to meet you."
+        ))]
+    );
+    assert!(matches!(
+        responses.responses[&ClientKind::Mistral],
+        Some(Err(_))
+    ));
+    assert_eq!(
+        responses.total_usage(),
+        Usage {
+            input_tokens: 21,
+            output_tokens: 22,
+        }
+    );
+    let expected_cost: f64 = [(ClientKind::OpenAi, 9, 8), (ClientKind::Anthropic, 12, 14)]
+        .into_iter()
+        .filter_map(|(kind, input_tokens, output_tokens)| {
+            kind.default_model().pricing().map(|pricing| {
+                pricing.cost_usd(&Usage {
+                    input_tokens,
+                    output_tokens,
+                })
+            })
+        })
+        .sum();
+    assert!((responses.total_cost_usd() - expected_cost).abs() < 1e-12);
+}
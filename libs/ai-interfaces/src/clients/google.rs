@@ -1,7 +1,14 @@
-use crate::ai_constants::GEMINI_VAR_NAME;
+use crate::ai_constants::{GEMINI_BASE_URL, GEMINI_VAR_NAME};
 use crate::clients::bulk::Role as SuperRole;
 use crate::clients::bulk::{ChatCompletionExt, Message};
 use crate::clients::google::Role::Model;
+use crate::clients::request::{Capabilities, CompletionRequest, ContentPart, ToolCall};
+use crate::clients::stream::{
+    completion_stream, CompletionDelta, CompletionStream, SseEvent, SseParser, StopReason, Usage,
+};
+#[cfg(test)]
+use crate::clients::stream::{replay_fixture, text_of};
+use crate::clients::transport::{HttpRequest, HttpResponse, Transport};
 use crate::models::base::ModelName;
 use crate::models::google::GoogleModels;
 use anyhow::{anyhow, Context};
@@ -12,55 +19,76 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::env::VarError;
 
+const CAPABILITIES: Capabilities = Capabilities {
+    images: true,
+    tools: true,
+    stop_sequences: true,
+};
+
 #[async_trait]
 impl ChatCompletionExt for GeminiClient {
     async fn completion(
         &self,
         model_name: ModelName,
-        messages: Vec<Message>,
+        request: CompletionRequest,
     ) -> anyhow::Result<Message> {
-        let request = ChatRequest::new(
-            messages
-                .into_iter()
-                .map(|msg| {
-                    if matches!(msg.role, SuperRole::User) {
-                        ChatMessage::user(vec![Part::Text(msg.content)])
-                    } else {
-                        ChatMessage::model(vec![Part::Text(msg.content)])
-                    }
-                })
-                .collect(),
-        );
+        request.ensure_supported("Gemini", &CAPABILITIES)?;
+        let request = ChatRequest::new(&request);
         let mut result = self.chat_completion(model_name, &request).await?;
-        let part = result
+        let message = result
             .candidates
             .pop()
             .context("Gemini returned no completion candidates")?
-            .content
-            .parts
-            .pop()
-            .context("Gemini returned no completion messages")?;
-        let content = match part {
-            Part::Text(text) => text,
-            Part::InlineData { mime_type, .. } => {
-                return Err(anyhow!(
-                    "Unexpected MIME type '{mime_type}' in Gemini completion"
-                ))
-            }
+            .content;
+        let role = match message.role {
+            Role::User => SuperRole::User,
+            Role::Model => SuperRole::Assistant,
         };
-        let role = SuperRole::User;
+        let content: Vec<ContentPart> = message
+            .parts
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, part)| part.into_content(index))
+            .collect();
+        if content.is_empty() {
+            return Err(anyhow!("Gemini returned no completion messages"));
+        }
         Ok(Message { content, role })
-        // Err(anyhow!("aaaa"))
+    }
+
+    async fn stream_completion(
+        &self,
+        model_name: ModelName,
+        request: CompletionRequest,
+    ) -> anyhow::Result<CompletionStream> {
+        request.ensure_supported("Gemini", &CAPABILITIES)?;
+        let request = ChatRequest::new(&request);
+        let url = format!(
+            "{}/v1beta/models/{}:streamGenerateContent?alt=sse&key={}",
+            self.base_url, model_name, self.api_key
+        );
+        let body = self
+            .transport
+            .stream(&self.client, &HttpRequest::post(url, &request)?, api_error)
+            .await?;
+        Ok(completion_stream(body, StreamParser::default()))
     }
 }
 pub struct GeminiClient {
     client: Client,
     api_key: String,
+    base_url: String,
+    transport: Transport,
 }
 
 impl GeminiClient {
     pub fn new(client: Client, api_key: String) -> Self {
-        Self { client, api_key }
+        Self {
+            client,
+            api_key,
+            base_url: GEMINI_BASE_URL.to_string(),
+            transport: Transport::default(),
+        }
     }
 
     pub fn from_env(client: Client, env_var_name: &str) -> Result<Self, VarError> {
@@ -68,41 +96,134 @@ impl GeminiClient {
         Ok(Self::new(client, api_key))
     }
 
+    /// Points the client at another deployment, a proxy or a mock server.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn with_transport(mut self, transport: Transport) -> Self {
+        self.transport = transport;
+        self
+    }
+
     async fn chat_completion(
         &self,
         model_name: ModelName,
         chat_request: &ChatRequest,
     ) -> anyhow::Result<ChatResponse> {
         let url = format!(
-            "https://generativelanguage.googleapis.com/v1beta/models/{}?key={}",
-            model_name, self.api_key
+            "{}/v1beta/models/{}:generateContent?key={}",
+            self.base_url, model_name, self.api_key
         );
-        let response = self.client.post(url).json(chat_request).send().await?;
-        if response.status().is_success() {
-            return Ok(response.json().await?);
-        }
-        if response.status().is_client_error() {
-            let error: Value = response.json().await?;
-            return Err(anyhow!(error));
-        }
-        Err(anyhow!(
-            "Unexpected response status code {} for Google Gemini chat completion request",
-            response.status()
-        ))
+        self.transport
+            .send(
+                &self.client,
+                &HttpRequest::post(url, chat_request)?,
+                api_error,
+            )
+            .await?
+            .json()
     }
 }
 
+fn api_error(response: HttpResponse) -> anyhow::Error {
+    response.into_error::<Value>("Gemini")
+}
+
 #[derive(Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
 struct ChatRequest {
     contents: Vec<ChatMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system_instruction: Option<SystemInstruction>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<Tool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    generation_config: Option<GenerationConfig>,
 }
 
 impl ChatRequest {
-    fn new(messages: Vec<ChatMessage>) -> Self {
-        Self { contents: messages }
+    fn new(request: &CompletionRequest) -> Self {
+        let params = &request.params;
+        let generation_config = (params.temperature.is_some()
+            || params.top_p.is_some()
+            || params.max_tokens.is_some()
+            || !params.stop.is_empty())
+        .then(|| GenerationConfig {
+            temperature: params.temperature,
+            top_p: params.top_p,
+            max_output_tokens: params.max_tokens,
+            stop_sequences: params.stop.clone(),
+        });
+        let tools = if request.tools.is_empty() {
+            vec![]
+        } else {
+            vec![Tool {
+                function_declarations: request
+                    .tools
+                    .iter()
+                    .map(|tool| FunctionDeclaration {
+                        name: tool.name.clone(),
+                        description: tool.description.clone(),
+                        parameters: tool.parameters.clone(),
+                    })
+                    .collect(),
+            }]
+        };
+        Self {
+            contents: request
+                .messages
+                .iter()
+                .filter(|msg| !msg.content.is_empty())
+                .map(|msg| {
+                    let parts = msg.content.iter().map(Part::from).collect();
+                    if matches!(msg.role, SuperRole::User) {
+                        ChatMessage::user(parts)
+                    } else {
+                        ChatMessage::model(parts)
+                    }
+                })
+                .collect(),
+            system_instruction: request.system.as_ref().map(|system| SystemInstruction {
+                parts: vec![Part::Text(system.clone())],
+            }),
+            tools,
+            generation_config,
+        }
     }
 }
+
+#[derive(Serialize, Debug)]
+struct SystemInstruction {
+    parts: Vec<Part>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Tool {
+    function_declarations: Vec<FunctionDeclaration>,
+}
+
+#[derive(Serialize, Debug)]
+struct FunctionDeclaration {
+    name: String,
+    description: String,
+    parameters: Value,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct GenerationConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_output_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    stop_sequences: Vec<String>,
+}
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 struct ChatResponse {
@@ -116,6 +237,7 @@ struct Candidate {
     content: ChatMessage,
     finish_reason: String,
     index: u32,
+    #[serde(default)]
     safety_ratings: Vec<SafetyRating>,
 }
 
@@ -133,6 +255,103 @@ struct UsageMetadata {
     candidates_token_count: u32,
     total_token_count: u32,
 }
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct StreamChunk {
+    #[serde(default)]
+    candidates: Vec<StreamCandidate>,
+    usage_metadata: Option<StreamUsageMetadata>,
+    error: Option<Value>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct StreamCandidate {
+    content: Option<StreamContent>,
+    finish_reason: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct StreamContent {
+    #[serde(default)]
+    parts: Vec<StreamPart>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct StreamPart {
+    text: Option<String>,
+    function_call: Option<StreamFunctionCall>,
+}
+
+#[derive(Deserialize, Debug)]
+struct StreamFunctionCall {
+    name: String,
+    #[serde(default)]
+    args: Value,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct StreamUsageMetadata {
+    #[serde(default)]
+    prompt_token_count: u32,
+    #[serde(default)]
+    candidates_token_count: u32,
+}
+
+/// Every chunk repeats `usageMetadata` with running totals, the last one wins.
+/// Function calls arrive whole, never split across chunks.
+#[derive(Default)]
+struct StreamParser {
+    usage: Option<Usage>,
+    tool_calls: usize,
+}
+
+impl SseParser for StreamParser {
+    fn parse(&mut self, event: &SseEvent) -> anyhow::Result<Vec<CompletionDelta>> {
+        let chunk: StreamChunk = serde_json::from_str(&event.data)?;
+        if let Some(error) = chunk.error {
+            return Err(anyhow!("Gemini stream error: {error}"));
+        }
+        if let Some(usage) = chunk.usage_metadata {
+            self.usage = Some(Usage {
+                input_tokens: usage.prompt_token_count,
+                output_tokens: usage.candidates_token_count,
+            });
+        }
+        let mut deltas = vec![];
+        for candidate in chunk.candidates {
+            let parts = candidate.content.map(|c| c.parts).unwrap_or_default();
+            for part in parts {
+                if let Some(text) = part.text.filter(|text| !text.is_empty()) {
+                    deltas.push(CompletionDelta::Text(text));
+                }
+                if let Some(call) = part.function_call {
+                    deltas.push(CompletionDelta::ToolCall(ToolCall {
+                        id: format!("{}-{}", call.name, self.tool_calls),
+                        name: call.name,
+                        arguments: call.args,
+                    }));
+                    self.tool_calls += 1;
+                }
+            }
+            if let Some(reason) = candidate.finish_reason {
+                deltas.push(CompletionDelta::Stop(StopReason::from(reason.as_str())));
+            }
+        }
+        Ok(deltas)
+    }
+
+    fn finish(&mut self) -> Vec<CompletionDelta> {
+        self.usage
+            .take()
+            .map(CompletionDelta::Usage)
+            .into_iter()
+            .collect()
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 struct ChatMessage {
@@ -159,6 +378,48 @@ impl ChatMessage {
 enum Part {
     Text(String),
     InlineData { mime_type: String, data: String },
+    FunctionCall { name: String, args: Value },
+    FunctionResponse { name: String, response: Value },
+}
+
+impl From<&ContentPart> for Part {
+    fn from(part: &ContentPart) -> Self {
+        match part {
+            ContentPart::Text(text) => Self::Text(text.clone()),
+            ContentPart::Image { mime_type, data } => Self::InlineData {
+                mime_type: mime_type.clone(),
+                data: data.clone(),
+            },
+            ContentPart::ToolCall(call) => Self::FunctionCall {
+                name: call.name.clone(),
+                args: call.arguments.clone(),
+            },
+            ContentPart::ToolResult(result) => Self::FunctionResponse {
+                name: result.name.clone(),
+                response: if result.is_error {
+                    serde_json::json!({ "error": result.content })
+                } else {
+                    serde_json::json!({ "content": result.content })
+                },
+            },
+        }
+    }
+}
+
+impl Part {
+    /// Gemini has no call ids, results are matched by function name,
+    /// so the id only has to be unique within the message.
+    fn into_content(self, index: usize) -> Option<ContentPart> {
+        match self {
+            Self::Text(text) => Some(ContentPart::Text(text)),
+            Self::FunctionCall { name, args } => Some(ContentPart::ToolCall(ToolCall {
+                id: format!("{}-{}", name, index),
+                name,
+                arguments: args,
+            })),
+            _ => None,
+        }
+    }
 }
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -175,9 +436,9 @@ async fn google_gemini() {
     let response = client
         .chat_completion(
             ModelName::Google(GoogleModels::default()),
-            &ChatRequest::new(vec![ChatMessage::user(vec![Part::Text(String::from(
+            &ChatRequest::new(&CompletionRequest::new(vec![Message::user(
                 "Introduce yourself",
-            ))])]),
+            )])),
         )
         .await
         .unwrap();
@@ -196,3 +457,111 @@ fn test_parts_parsing() {
     let s = serde_json::to_string(&p2).unwrap();
     println!("{s}");
 }
+
+#[tokio::test]
+async fn test_stream_fixture() {
+    let deltas = replay_fixture(
+        include_str!("../../fixtures/gemini_stream.sse"),
+        StreamParser::default(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        text_of(&deltas),
+        "I am Gemini, a large language model — trained by Google."
+    );
+    assert_eq!(
+        deltas[deltas.len() - 2..],
+        [
+            CompletionDelta::Stop(StopReason::EndTurn),
+            CompletionDelta::Usage(Usage {
+                input_tokens: 4,
+                output_tokens: 13,
+            }),
+        ]
+    );
+}
+
+#[test]
+fn test_request_translation() {
+    use crate::clients::request::{SamplingParams, ToolResult};
+    let request = CompletionRequest::new(vec![
+        Message {
+            role: SuperRole::User,
+            content: vec![
+                ContentPart::Text(String::from("What is this?")),
+                ContentPart::Image {
+                    mime_type: String::from("image/jpeg"),
+                    data: String::from("/9j/4AAQ"),
+                },
+            ],
+        },
+        Message {
+            role: SuperRole::Assistant,
+            content: vec![ContentPart::ToolCall(ToolCall {
+                id: String::from("lookup-0"),
+                name: String::from("lookup"),
+                arguments: serde_json::json!({"q": "router"}),
+            })],
+        },
+        Message {
+            role: SuperRole::User,
+            content: vec![ContentPart::ToolResult(ToolResult {
+                call_id: String::from("lookup-0"),
+                name: String::from("lookup"),
+                content: String::from("A router"),
+                is_error: false,
+            })],
+        },
+    ])
+    .with_system("Be brief")
+    .with_params(SamplingParams {
+        max_tokens: Some(100),
+        stop: vec![String::from("END")],
+        ..SamplingParams::default()
+    });
+    assert_eq!(
+        serde_json::to_value(ChatRequest::new(&request)).unwrap(),
+        serde_json::json!({
+            "contents": [
+                {"role": "user", "parts": [
+                    {"text": "What is this?"},
+                    {"inlineData": {"mime_type": "image/jpeg", "data": "/9j/4AAQ"}}
+                ]},
+                {"role": "model", "parts": [{"functionCall": {"name": "lookup", "args": {"q": "router"}}}]},
+                {"role": "user", "parts": [
+                    {"functionResponse": {"name": "lookup", "response": {"content": "A router"}}}
+                ]}
+            ],
+            "systemInstruction": {"parts": [{"text": "Be brief"}]},
+            "generationConfig": {"maxOutputTokens": 100, "stopSequences": ["END"]}
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_replay_cassette() {
+    use crate::clients::transport::Cassette;
+    let cassette = Cassette::load(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/fixtures/replay/gemini_completion.json"
+    ))
+    .unwrap();
+    // the recorded key is redacted, any key matches
+    let client = GeminiClient::new(Client::new(), String::from("another-key"))
+        .with_transport(Transport::Replay(cassette.clone()));
+    let message = client
+        .completion(
+            ModelName::Google(GoogleModels::default()),
+            CompletionRequest::new(vec![Message::user("Introduce yourself")]),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        message.content,
+        vec![ContentPart::Text(String::from(
+            "I'm Gemini, a large language model built by Google."
+        ))]
+    );
+    assert!(cassette.exchanges().is_empty());
+}
@@ -1,6 +1,11 @@
-use crate::ai_constants::LLAMA_VAR_NAME;
-use crate::clients::bulk::Role as SuperRole;
+use crate::ai_constants::{LLAMA_BASE_URL, LLAMA_VAR_NAME};
 use crate::clients::bulk::{ChatCompletionExt, Message};
+use crate::clients::openai::{chat_messages, ChatMessage, OpenAiStreamParser, WireParams};
+use crate::clients::request::{Capabilities, CompletionRequest};
+use crate::clients::stream::{completion_stream, CompletionStream};
+#[cfg(test)]
+use crate::clients::stream::{replay_fixture, text_of, CompletionDelta, StopReason, Usage};
+use crate::clients::transport::{HttpRequest, HttpResponse, Transport};
 use crate::models::base::ModelName;
 use anyhow::{anyhow, Context};
 use async_trait::async_trait;
@@ -11,90 +16,113 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::env::VarError;
 
+const CAPABILITIES: Capabilities = Capabilities {
+    images: false,
+    tools: false,
+    stop_sequences: false,
+};
+
 #[async_trait]
 impl ChatCompletionExt for LlamaClient {
     async fn completion(
         &self,
         model_name: ModelName,
-        messages: Vec<Message>,
+        request: CompletionRequest,
     ) -> anyhow::Result<Message> {
-        let request = ChatRequest::new(
-            model_name.to_string(),
-            messages
-                .into_iter()
-                .map(|msg| {
-                    if matches!(msg.role, SuperRole::User) {
-                        ChatMessage::user(msg.content)
-                    } else {
-                        ChatMessage::assistant(msg.content)
-                    }
-                })
-                .collect(),
-        );
+        request.ensure_supported("Llama API", &CAPABILITIES)?;
+        let request = ChatRequest::new(model_name.to_string(), &request);
         let mut result = self.chat_completion(&request).await?;
-        let choice = result
+        result
             .choices
             .pop()
-            .context("Llama returned no completion messages")?;
-        let role = match choice.message.role {
-            Role::User => SuperRole::User,
-            Role::Assistant => SuperRole::Assistant,
-        };
-        let content = choice.message.content;
-        Ok(Message { content, role })
+            .context("Llama returned no completion messages")?
+            .message
+            .into_message()
+    }
+
+    async fn stream_completion(
+        &self,
+        model_name: ModelName,
+        request: CompletionRequest,
+    ) -> anyhow::Result<CompletionStream> {
+        request.ensure_supported("Llama API", &CAPABILITIES)?;
+        let mut request = ChatRequest::new(model_name.to_string(), &request);
+        request.stream = true;
+        let body = self
+            .transport
+            .stream(&self.client, &self.http_request(&request)?, api_error)
+            .await?;
+        Ok(completion_stream(body, OpenAiStreamParser::default()))
     }
 }
 pub struct LlamaClient {
     client: Client,
     api_key: String,
+    base_url: String,
+    transport: Transport,
 }
 
 impl LlamaClient {
     pub fn new(client: Client, api_key: String) -> Self {
-        Self { client, api_key }
+        Self {
+            client,
+            api_key,
+            base_url: LLAMA_BASE_URL.to_string(),
+            transport: Transport::default(),
+        }
     }
     pub fn from_env(client: Client, env_var_name: &str) -> Result<Self, VarError> {
         let api_key = std::env::var(env_var_name)?;
         Ok(Self::new(client, api_key))
     }
 
+    /// Points the client at another deployment, a proxy or a mock server.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn with_transport(mut self, transport: Transport) -> Self {
+        self.transport = transport;
+        self
+    }
+
     async fn chat_completion(&self, chat_request: &ChatRequest) -> anyhow::Result<ChatResponse> {
-        let url = "https://api.llama-api.com/chat/completions";
-        let response = self
-            .client
-            .post(url)
-            .header(AUTHORIZATION, format!("Bearer {}", self.api_key.as_str()))
-            .json(&chat_request)
-            .send()
-            .await?;
-        if response.status().is_success() {
-            return Ok(response.json().await?);
-        }
-        if response.status().is_client_error() {
-            let error: Value = response.json().await?;
-            return Err(anyhow!(error));
-        }
-        Err(anyhow!(
-            "Unexpected response status code {} for Meta Llama chat completion request",
-            response.status()
-        ))
+        self.transport
+            .send(&self.client, &self.http_request(chat_request)?, api_error)
+            .await?
+            .json()
+    }
+
+    fn http_request(&self, chat_request: &ChatRequest) -> anyhow::Result<HttpRequest> {
+        Ok(
+            HttpRequest::post(format!("{}/chat/completions", self.base_url), chat_request)?
+                .header(AUTHORIZATION.as_str(), format!("Bearer {}", self.api_key)),
+        )
     }
 }
 
+fn api_error(response: HttpResponse) -> anyhow::Error {
+    response.into_error::<Value>("Llama API")
+}
+
 #[derive(Serialize, Debug)]
 struct ChatRequest {
     model: String,
     messages: Vec<ChatMessage>,
     // functions: Vec<Function>,
+    #[serde(flatten)]
+    params: WireParams,
     stream: bool,
     function_call: String,
 }
 
 impl ChatRequest {
-    fn new(model: String, messages: Vec<ChatMessage>) -> Self {
+    fn new(model: String, request: &CompletionRequest) -> Self {
         Self {
             model,
-            messages,
+            messages: chat_messages(request),
+            params: WireParams::from(&request.params),
             stream: false,
             function_call: String::from("none"),
         }
@@ -113,34 +141,6 @@ struct Choice {
     finish_reason: String,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-struct ChatMessage {
-    role: Role,
-    content: String,
-}
-
-impl ChatMessage {
-    fn user(content: String) -> Self {
-        Self {
-            role: Role::User,
-            content,
-        }
-    }
-    fn assistant(content: String) -> Self {
-        Self {
-            role: Role::Assistant,
-            content,
-        }
-    }
-}
-
-#[derive(Serialize, Deserialize, Debug)]
-#[serde(rename = "lowercase")]
-enum Role {
-    User,
-    Assistant,
-}
-
 #[ignore = "Needs valid Meta Llama token"]
 #[tokio::test]
 async fn meta() {
@@ -149,8 +149,29 @@ async fn meta() {
     let result = client
         .chat_completion(&ChatRequest::new(
             String::from("llama3.1-405b"),
-            vec![ChatMessage::user(String::from("Introduce yourself"))],
+            &CompletionRequest::new(vec![Message::user("Introduce yourself")]),
         ))
         .await
         .unwrap();
 }
+
+#[tokio::test]
+async fn test_stream_fixture() {
+    let deltas = replay_fixture(
+        include_str!("../../fixtures/meta_stream.sse"),
+        OpenAiStreamParser::default(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(text_of(&deltas), "I'm Llama, a model by Meta.");
+    assert_eq!(
+        deltas[deltas.len() - 2..],
+        [
+            CompletionDelta::Stop(StopReason::EndTurn),
+            CompletionDelta::Usage(Usage {
+                input_tokens: 10,
+                output_tokens: 9,
+            }),
+        ]
+    );
+}
@@ -1,88 +1,113 @@
-use crate::ai_constants::MISTRAL_VAR_NAME;
-use crate::clients::bulk::Role as SuperRole;
+use crate::ai_constants::{MISTRAL_BASE_URL, MISTRAL_VAR_NAME};
 use crate::clients::bulk::{ChatCompletionExt, Message};
+use crate::clients::openai::{
+    chat_messages, tool_specs, ChatMessage, OpenAiStreamParser, ToolSpec, WireParams,
+};
+use crate::clients::request::{Capabilities, CompletionRequest};
+use crate::clients::stream::{completion_stream, CompletionStream};
+#[cfg(test)]
+use crate::clients::stream::{replay_fixture, text_of, CompletionDelta, StopReason, Usage};
+use crate::clients::transport::{HttpRequest, HttpResponse, Transport};
 use crate::models::base::ModelName;
 use anyhow::{anyhow, Context};
 use async_trait::async_trait;
 use dotenv::dotenv;
 use reqwest::header::AUTHORIZATION;
 use reqwest::Client;
-use serde::__private::de::Content;
 use serde::{Deserialize, Serialize};
 use std::env::VarError;
 use std::fmt::{Display, Formatter};
 
+/// Images need a Pixtral model, which isn't in [`crate::models::mistral::MistralModels`].
+const CAPABILITIES: Capabilities = Capabilities {
+    images: false,
+    tools: true,
+    stop_sequences: true,
+};
+
 #[async_trait]
 impl ChatCompletionExt for MistralClient {
     async fn completion(
         &self,
         model_name: ModelName,
-        messages: Vec<Message>,
+        request: CompletionRequest,
     ) -> anyhow::Result<Message> {
-        let request = ChatRequest::new(
-            model_name.to_string(),
-            messages
-                .into_iter()
-                .map(|msg| {
-                    if matches!(msg.role, SuperRole::User) {
-                        ChatMessage::user(msg.content)
-                    } else {
-                        ChatMessage::assistant(msg.content, false)
-                    }
-                })
-                .collect(),
-        );
+        request.ensure_supported("Mistral", &CAPABILITIES)?;
+        let request = ChatRequest::new(model_name.to_string(), &request);
         let mut result = self.chat_completion(&request).await?;
-        let message = result
+        result
             .choices
             .pop()
             .context("Mistral returned no completion messages")?
-            .message;
-        let content = message
-            .content
-            .context("Mistral should have included a non-empty string in the response")?;
-        let role = match message.role {
-            Role::User => SuperRole::User,
-            Role::Assistant => SuperRole::Assistant,
-        };
-        Ok(Message { content, role })
+            .message
+            .into_message()
+    }
+
+    async fn stream_completion(
+        &self,
+        model_name: ModelName,
+        request: CompletionRequest,
+    ) -> anyhow::Result<CompletionStream> {
+        request.ensure_supported("Mistral", &CAPABILITIES)?;
+        let mut request = ChatRequest::new(model_name.to_string(), &request);
+        request.stream = true;
+        let body = self
+            .transport
+            .stream(&self.client, &self.http_request(&request)?, api_error)
+            .await?;
+        Ok(completion_stream(body, OpenAiStreamParser::default()))
     }
 }
 pub struct MistralClient {
     client: Client,
     api_key: String,
+    base_url: String,
+    transport: Transport,
 }
 
 impl MistralClient {
     pub fn new(client: Client, api_key: String) -> Self {
-        Self { client, api_key }
+        Self {
+            client,
+            api_key,
+            base_url: MISTRAL_BASE_URL.to_string(),
+            transport: Transport::default(),
+        }
     }
     pub fn from_env(client: Client, env_var_name: &str) -> Result<Self, VarError> {
         let api_key = std::env::var(env_var_name)?;
         Ok(Self::new(client, api_key))
     }
+
+    /// Points the client at another deployment, a proxy or a mock server.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn with_transport(mut self, transport: Transport) -> Self {
+        self.transport = transport;
+        self
+    }
     async fn chat_completion(&self, chat_request: &ChatRequest) -> anyhow::Result<ChatResponse> {
-        let url = "https://api.mistral.ai/v1/chat/completions";
-        let response = self
-            .client
-            .post(url)
-            .header(AUTHORIZATION, format!("Bearer {}", self.api_key.as_str()))
-            .json(chat_request)
-            .send()
-            .await?;
-        if response.status().is_success() {
-            return Ok(response.json().await?);
-        }
-        if response.status() == 422 {
-            let error: Error = response.json().await?;
-            return Err(anyhow!(error));
-        }
-        Err(anyhow!(
-            "Unexpected response status code {} for Mistral chat completion request",
-            response.status()
-        ))
+        self.transport
+            .send(&self.client, &self.http_request(chat_request)?, api_error)
+            .await?
+            .json()
+    }
+
+    fn http_request(&self, chat_request: &ChatRequest) -> anyhow::Result<HttpRequest> {
+        Ok(HttpRequest::post(
+            format!("{}/v1/chat/completions", self.base_url),
+            chat_request,
+        )?
+        .header(AUTHORIZATION.as_str(), format!("Bearer {}", self.api_key)))
     }
 }
+
+fn api_error(response: HttpResponse) -> anyhow::Error {
+    response.into_error::<Error>("Mistral")
+}
 #[derive(Deserialize, Debug)]
 struct ChatResponse {
     id: String,
@@ -138,47 +163,26 @@ struct Detail {
 struct ChatRequest {
     model: String,
     messages: Vec<ChatMessage>,
-}
-
-impl ChatRequest {
-    fn new(model: String, messages: Vec<ChatMessage>) -> Self {
-        Self { model, messages }
-    }
-}
-
-#[derive(Serialize, Deserialize, Debug)]
-struct ChatMessage {
-    role: Role,
     #[serde(skip_serializing_if = "Option::is_none")]
-    content: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    prefix: Option<bool>,
+    tools: Option<Vec<ToolSpec>>,
+    #[serde(flatten)]
+    params: WireParams,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
 }
 
-impl ChatMessage {
-    fn user(content: String) -> Self {
-        Self {
-            role: Role::User,
-            content: Some(content),
-            prefix: None,
-        }
-    }
-    fn assistant(content: String, prefix: bool) -> Self {
+impl ChatRequest {
+    fn new(model: String, request: &CompletionRequest) -> Self {
         Self {
-            role: Role::Assistant,
-            content: Some(content),
-            prefix: Some(prefix),
+            model,
+            messages: chat_messages(request),
+            tools: tool_specs(&request.tools),
+            params: WireParams::from(&request.params),
+            stream: false,
         }
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-#[serde(rename_all = "lowercase")]
-enum Role {
-    User,
-    Assistant,
-}
-
 #[ignore = "Needs valid Mistral token"]
 #[tokio::test]
 async fn mistral() {
@@ -186,8 +190,29 @@ async fn mistral() {
     let client = MistralClient::from_env(Client::new(), MISTRAL_VAR_NAME).unwrap();
     let request = ChatRequest::new(
         String::from("mistral-small-latest"),
-        vec![ChatMessage::user(String::from("Introduce yourself"))],
+        &CompletionRequest::new(vec![Message::user("Introduce yourself")]),
     );
     let result = client.chat_completion(&request).await.unwrap();
     println!("{result:#?}")
 }
+
+#[tokio::test]
+async fn test_stream_fixture() {
+    let deltas = replay_fixture(
+        include_str!("../../fixtures/mistral_stream.sse"),
+        OpenAiStreamParser::default(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(text_of(&deltas), "Bonjour! Je suis Mistral.");
+    assert_eq!(
+        deltas[deltas.len() - 2..],
+        [
+            CompletionDelta::Stop(StopReason::EndTurn),
+            CompletionDelta::Usage(Usage {
+                input_tokens: 8,
+                output_tokens: 7,
+            }),
+        ]
+    );
+}
@@ -0,0 +1,138 @@
+//! Local HTTP server standing in for a provider, clients reach it through `with_base_url`.
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+#[derive(Debug, Clone)]
+pub(crate) struct MockResponse {
+    status: u16,
+    content_type: &'static str,
+    body: String,
+}
+
+impl MockResponse {
+    pub(crate) fn json(status: u16, body: Value) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: body.to_string(),
+        }
+    }
+
+    pub(crate) fn text(status: u16, body: &str) -> Self {
+        Self {
+            status,
+            content_type: "text/plain",
+            body: body.to_string(),
+        }
+    }
+
+    pub(crate) fn sse(body: &str) -> Self {
+        Self {
+            status: 200,
+            content_type: "text/event-stream",
+            body: body.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct ReceivedRequest {
+    pub(crate) path: String,
+    pub(crate) headers: HashMap<String, String>,
+    pub(crate) body: Value,
+}
+
+/// Answers with `responses` in order, one per connection, and keeps the requests it received.
+pub(crate) struct MockServer {
+    url: String,
+    received: Arc<Mutex<Vec<ReceivedRequest>>>,
+}
+
+impl MockServer {
+    pub(crate) async fn start(responses: Vec<MockResponse>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let responses = Arc::new(Mutex::new(VecDeque::from(responses)));
+        let log = received.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let (log, responses) = (log.clone(), responses.clone());
+                tokio::spawn(async move {
+                    let _ = serve(stream, log, responses).await;
+                });
+            }
+        });
+        Self { url, received }
+    }
+
+    pub(crate) fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub(crate) fn received(&self) -> Vec<ReceivedRequest> {
+        self.received.lock().unwrap().clone()
+    }
+}
+
+async fn serve(
+    mut stream: TcpStream,
+    log: Arc<Mutex<Vec<ReceivedRequest>>>,
+    responses: Arc<Mutex<VecDeque<MockResponse>>>,
+) -> anyhow::Result<()> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let header_end = loop {
+        let read = stream.read(&mut chunk).await?;
+        anyhow::ensure!(read > 0, "Connection closed before the headers");
+        buffer.extend_from_slice(&chunk[..read]);
+        if let Some(end) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break end + 4;
+        }
+    };
+    let head = String::from_utf8_lossy(&buffer[..header_end]).to_string();
+    let mut lines = head.lines();
+    let path = lines
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .unwrap_or_default()
+        .to_string();
+    let headers: HashMap<String, String> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_string()))
+        .collect();
+    let length: usize = headers
+        .get("content-length")
+        .and_then(|length| length.parse().ok())
+        .unwrap_or(0);
+    while buffer.len() < header_end + length {
+        let read = stream.read(&mut chunk).await?;
+        anyhow::ensure!(read > 0, "Connection closed before the body");
+        buffer.extend_from_slice(&chunk[..read]);
+    }
+    let body =
+        serde_json::from_slice(&buffer[header_end..header_end + length]).unwrap_or(Value::Null);
+    log.lock().unwrap().push(ReceivedRequest {
+        path,
+        headers,
+        body,
+    });
+    let response = responses
+        .lock()
+        .unwrap()
+        .pop_front()
+        .unwrap_or_else(|| MockResponse::text(500, "No mock response left"));
+    let reply = format!(
+        "HTTP/1.1 {} Mock\r\ncontent-type: {}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+        response.status,
+        response.content_type,
+        response.body.len(),
+        response.body
+    );
+    stream.write_all(reply.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
@@ -3,5 +3,10 @@ pub mod bulk;
 pub mod google;
 pub mod meta;
 pub mod mistral;
+#[cfg(test)]
+pub(crate) mod mock;
 pub mod openai;
 pub mod perplexity;
+pub mod request;
+pub mod stream;
+pub mod transport;
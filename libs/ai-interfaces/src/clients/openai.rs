@@ -1,6 +1,16 @@
-use crate::ai_constants::OPENAI_VAR_NAME;
+use crate::ai_constants::{OPENAI_BASE_URL, OPENAI_VAR_NAME};
 use crate::clients::bulk::Role as SuperRole;
 use crate::clients::bulk::{ChatCompletionExt, Message};
+use crate::clients::request::{
+    parse_arguments, Capabilities, CompletionRequest, ContentPart, SamplingParams,
+    ToolCall as SuperToolCall, ToolDefinition,
+};
+use crate::clients::stream::{
+    completion_stream, CompletionDelta, CompletionStream, SseEvent, SseParser, StopReason, Usage,
+};
+#[cfg(test)]
+use crate::clients::stream::{replay_fixture, text_of};
+use crate::clients::transport::{HttpRequest, HttpResponse, Transport};
 use crate::models::base::ModelName;
 use anyhow::{anyhow, Context};
 use async_trait::async_trait;
@@ -8,51 +18,67 @@ use dotenv::dotenv;
 use reqwest::header::AUTHORIZATION;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
 use std::env::VarError;
 use std::fmt::{Display, Formatter};
 
+const CAPABILITIES: Capabilities = Capabilities {
+    images: true,
+    tools: true,
+    stop_sequences: true,
+};
+
 #[async_trait]
 impl ChatCompletionExt for OpenAiClient {
     async fn completion(
         &self,
         model_name: ModelName,
-        messages: Vec<Message>,
+        request: CompletionRequest,
     ) -> anyhow::Result<Message> {
-        let request = ChatRequest::new(
-            model_name.to_string(),
-            messages
-                .into_iter()
-                .map(|msg| {
-                    if matches!(msg.role, SuperRole::User) {
-                        ChatMessage::user(msg.content)
-                    } else {
-                        ChatMessage::assistant(msg.content)
-                    }
-                })
-                .collect(),
-        );
+        request.ensure_supported("OpenAI", &CAPABILITIES)?;
+        let request = ChatRequest::new(model_name.to_string(), &request);
         let mut response = self.chat_completion(&request).await?;
         let message = response
             .choices
             .pop()
             .context("GPT returned no completion message")?;
-        let content = message.message.content;
-        let role = match message.message.role {
-            Role::User => SuperRole::User,
-            Role::Assistant => SuperRole::Assistant,
-            other => return Err(anyhow!("Unimplemented GPT role {other}")),
-        };
-        Ok(Message { content, role })
+        message.message.into_message()
+    }
+
+    async fn stream_completion(
+        &self,
+        model_name: ModelName,
+        request: CompletionRequest,
+    ) -> anyhow::Result<CompletionStream> {
+        request.ensure_supported("OpenAI", &CAPABILITIES)?;
+        let mut request = ChatRequest::new(model_name.to_string(), &request);
+        request.stream = true;
+        request.stream_options = Some(StreamOptions {
+            include_usage: true,
+        });
+        let body = self
+            .transport
+            .stream(&self.client, &self.http_request(&request)?, api_error)
+            .await?;
+        Ok(completion_stream(body, OpenAiStreamParser::default()))
     }
 }
 pub struct OpenAiClient {
     client: Client,
     api_key: String,
+    base_url: String,
+    transport: Transport,
 }
 
 impl OpenAiClient {
     pub fn new(client: Client, api_key: String) -> Self {
-        Self { client, api_key }
+        Self {
+            client,
+            api_key,
+            base_url: OPENAI_BASE_URL.to_string(),
+            transport: Transport::default(),
+        }
     }
 
     pub fn from_env(client: Client, env_var_name: &str) -> Result<Self, VarError> {
@@ -60,41 +86,68 @@ impl OpenAiClient {
         Ok(Self::new(client, api_key))
     }
 
+    /// Points the client at another deployment, a proxy or a mock server.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn with_transport(mut self, transport: Transport) -> Self {
+        self.transport = transport;
+        self
+    }
+
     async fn chat_completion(&self, request: &ChatRequest) -> anyhow::Result<ChatResponse> {
-        let url = "https://api.openai.com/v1/chat/completions";
-        let response = self
-            .client
-            .post(url)
-            .header(AUTHORIZATION, format!("Bearer {}", self.api_key.as_str()))
-            .json(&request)
-            .send()
-            .await?;
-        if response.status().is_success() {
-            return Ok(response.json().await?);
-        }
-        if response.status().is_client_error() {
-            let error: Error = response.json().await?;
-            return Err(anyhow!(error));
-        }
-        Err(anyhow!(
-            "Unexpected response status code {} for OpenAI chat completion request",
-            response.status()
-        ))
+        self.transport
+            .send(&self.client, &self.http_request(request)?, api_error)
+            .await?
+            .json()
+    }
+
+    fn http_request(&self, request: &ChatRequest) -> anyhow::Result<HttpRequest> {
+        Ok(
+            HttpRequest::post(format!("{}/v1/chat/completions", self.base_url), request)?
+                .header(AUTHORIZATION.as_str(), format!("Bearer {}", self.api_key)),
+        )
     }
 }
 
+fn api_error(response: HttpResponse) -> anyhow::Error {
+    response.into_error::<Error>("OpenAI")
+}
+
 #[derive(Serialize)]
 struct ChatRequest {
     model: String,
     messages: Vec<ChatMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<Vec<ToolSpec>>,
+    #[serde(flatten)]
+    params: WireParams,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<StreamOptions>,
 }
 
 impl ChatRequest {
-    fn new(model: String, messages: Vec<ChatMessage>) -> Self {
-        Self { model, messages }
+    fn new(model: String, request: &CompletionRequest) -> Self {
+        Self {
+            model,
+            messages: chat_messages(request),
+            tools: tool_specs(&request.tools),
+            params: WireParams::from(&request.params),
+            stream: false,
+            stream_options: None,
+        }
     }
 }
 
+#[derive(Serialize)]
+struct StreamOptions {
+    include_usage: bool,
+}
+
 #[derive(Deserialize)]
 struct ChatResponse {
     pub(crate) choices: Vec<Choice>,
@@ -105,13 +158,38 @@ struct Choice {
     pub(crate) message: ChatMessage,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// Sampling fields shared by every OpenAI compatible API, flattened into their requests.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub(crate) struct WireParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_tokens: Option<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    stop: Vec<String>,
+}
+
+impl From<&SamplingParams> for WireParams {
+    fn from(params: &SamplingParams) -> Self {
+        Self {
+            temperature: params.temperature,
+            top_p: params.top_p,
+            max_tokens: params.max_tokens,
+            stop: params.stop.clone(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
 #[serde(rename_all = "lowercase")]
-enum Role {
+pub(crate) enum Role {
     User,
     Assistant,
     System,
     Function,
+    Tool,
 }
 
 impl Display for Role {
@@ -120,27 +198,202 @@ impl Display for Role {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-struct ChatMessage {
+/// Message in the OpenAI chat format, Mistral, Perplexity and Llama API accept the same shape.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub(crate) struct ChatMessage {
     pub(crate) role: Role,
-    pub(crate) content: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) content: Option<MessageContent>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) tool_calls: Option<Vec<WireToolCall>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) tool_call_id: Option<String>,
 }
 
 impl ChatMessage {
-    fn user(content: String) -> Self {
+    pub(crate) fn text(role: Role, text: String) -> Self {
         Self {
-            role: Role::User,
-            content,
+            role,
+            content: Some(MessageContent::Text(text)),
+            tool_calls: None,
+            tool_call_id: None,
         }
     }
-    fn assistant(content: String) -> Self {
-        Self {
-            role: Role::Assistant,
-            content,
+
+    pub(crate) fn into_message(self) -> anyhow::Result<Message> {
+        let role = match self.role {
+            Role::User => SuperRole::User,
+            Role::Assistant => SuperRole::Assistant,
+            other => return Err(anyhow!("Unimplemented GPT role {other}")),
+        };
+        let mut content = vec![];
+        match self.content {
+            Some(MessageContent::Text(text)) if !text.is_empty() => {
+                content.push(ContentPart::Text(text))
+            }
+            Some(MessageContent::Parts(parts)) => {
+                content.extend(parts.into_iter().filter_map(|part| match part {
+                    WirePart::Text { text } => Some(ContentPart::Text(text)),
+                    WirePart::ImageUrl { .. } => None,
+                }))
+            }
+            _ => {}
         }
+        for call in self.tool_calls.unwrap_or_default() {
+            content.push(ContentPart::ToolCall(SuperToolCall {
+                id: call.id,
+                name: call.function.name,
+                arguments: parse_arguments(&call.function.arguments)?,
+            }));
+        }
+        Ok(Message { content, role })
     }
 }
 
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(untagged)]
+pub(crate) enum MessageContent {
+    Text(String),
+    Parts(Vec<WirePart>),
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum WirePart {
+    Text { text: String },
+    ImageUrl { image_url: ImageUrl },
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub(crate) struct ImageUrl {
+    url: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub(crate) struct WireToolCall {
+    id: String,
+    #[serde(rename = "type", default = "function_kind")]
+    kind: String,
+    function: WireFunctionCall,
+}
+
+fn function_kind() -> String {
+    String::from("function")
+}
+
+/// `arguments` is JSON encoded as a string.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub(crate) struct WireFunctionCall {
+    name: String,
+    arguments: String,
+}
+
+#[derive(Serialize, Debug)]
+pub(crate) struct ToolSpec {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    function: FunctionSpec,
+}
+
+#[derive(Serialize, Debug)]
+struct FunctionSpec {
+    name: String,
+    description: String,
+    parameters: Value,
+}
+
+pub(crate) fn tool_specs(tools: &[ToolDefinition]) -> Option<Vec<ToolSpec>> {
+    if tools.is_empty() {
+        return None;
+    }
+    Some(
+        tools
+            .iter()
+            .map(|tool| ToolSpec {
+                kind: "function",
+                function: FunctionSpec {
+                    name: tool.name.clone(),
+                    description: tool.description.clone(),
+                    parameters: tool.parameters.clone(),
+                },
+            })
+            .collect(),
+    )
+}
+
+/// Tool results become their own `tool` messages, placed before whatever else
+/// the user message carries so they directly follow the assistant's calls.
+pub(crate) fn chat_messages(request: &CompletionRequest) -> Vec<ChatMessage> {
+    let mut messages = vec![];
+    if let Some(system) = &request.system {
+        messages.push(ChatMessage::text(Role::System, system.clone()));
+    }
+    for message in &request.messages {
+        let mut parts = vec![];
+        let mut tool_calls = vec![];
+        for part in &message.content {
+            match part {
+                ContentPart::Text(text) => parts.push(WirePart::Text { text: text.clone() }),
+                ContentPart::Image { mime_type, data } => parts.push(WirePart::ImageUrl {
+                    image_url: ImageUrl {
+                        url: format!("data:{};base64,{}", mime_type, data),
+                    },
+                }),
+                ContentPart::ToolCall(call) => tool_calls.push(WireToolCall {
+                    id: call.id.clone(),
+                    kind: function_kind(),
+                    function: WireFunctionCall {
+                        name: call.name.clone(),
+                        arguments: call.arguments.to_string(),
+                    },
+                }),
+                ContentPart::ToolResult(result) => messages.push(ChatMessage {
+                    tool_call_id: Some(result.call_id.clone()),
+                    ..ChatMessage::text(
+                        Role::Tool,
+                        if result.is_error {
+                            format!("Error: {}", result.content)
+                        } else {
+                            result.content.clone()
+                        },
+                    )
+                }),
+            }
+        }
+        if parts.is_empty() && tool_calls.is_empty() {
+            continue;
+        }
+        let content = if parts.is_empty() {
+            None
+        } else if parts
+            .iter()
+            .all(|part| matches!(part, WirePart::Text { .. }))
+        {
+            Some(MessageContent::Text(
+                parts
+                    .into_iter()
+                    .filter_map(|part| match part {
+                        WirePart::Text { text } => Some(text),
+                        WirePart::ImageUrl { .. } => None,
+                    })
+                    .collect(),
+            ))
+        } else {
+            Some(MessageContent::Parts(parts))
+        };
+        messages.push(ChatMessage {
+            role: match message.role {
+                SuperRole::User => Role::User,
+                SuperRole::Assistant => Role::Assistant,
+            },
+            content,
+            tool_calls: (!tool_calls.is_empty()).then_some(tool_calls),
+            tool_call_id: None,
+        });
+    }
+    messages
+}
+
 #[derive(Deserialize, Debug)]
 struct Error {
     error: InnerError,
@@ -158,7 +411,132 @@ struct InnerError {
     #[serde(rename = "type")]
     kind: String,
     param: Option<String>,
-    code: String,
+    code: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct StreamChunk {
+    #[serde(default)]
+    choices: Vec<StreamChoice>,
+    usage: Option<ChunkUsage>,
+    error: Option<InnerError>,
+}
+
+#[derive(Deserialize, Debug)]
+struct StreamChoice {
+    delta: StreamChoiceDelta,
+    finish_reason: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct StreamChoiceDelta {
+    content: Option<String>,
+    tool_calls: Option<Vec<StreamToolCall>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct StreamToolCall {
+    index: Option<usize>,
+    id: Option<String>,
+    function: Option<StreamFunctionCall>,
+}
+
+#[derive(Deserialize, Debug)]
+struct StreamFunctionCall {
+    name: Option<String>,
+    arguments: Option<String>,
+}
+
+#[derive(Default)]
+struct PartialToolCall {
+    id: String,
+    name: String,
+    arguments: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct ChunkUsage {
+    prompt_tokens: u32,
+    completion_tokens: u32,
+}
+
+/// Parser for the `chat.completion.chunk` format, which Mistral, Perplexity and
+/// Llama API reuse. Some of them repeat cumulative usage on every chunk, so only
+/// the last one seen is reported when the body ends.
+/// Tool call arguments arrive in fragments keyed by index and are emitted
+/// whole once the choice finishes.
+#[derive(Default)]
+pub(crate) struct OpenAiStreamParser {
+    usage: Option<Usage>,
+    tool_calls: BTreeMap<usize, PartialToolCall>,
+}
+
+impl OpenAiStreamParser {
+    fn flush_tool_calls(&mut self) -> anyhow::Result<Vec<CompletionDelta>> {
+        std::mem::take(&mut self.tool_calls)
+            .into_values()
+            .map(|call| {
+                Ok(CompletionDelta::ToolCall(SuperToolCall {
+                    id: call.id,
+                    name: call.name,
+                    arguments: parse_arguments(&call.arguments)?,
+                }))
+            })
+            .collect()
+    }
+}
+
+impl SseParser for OpenAiStreamParser {
+    fn parse(&mut self, event: &SseEvent) -> anyhow::Result<Vec<CompletionDelta>> {
+        if event.data == "[DONE]" {
+            return Ok(vec![]);
+        }
+        let chunk: StreamChunk = serde_json::from_str(&event.data)?;
+        if let Some(error) = chunk.error {
+            return Err(anyhow!("Chat completion stream error: {:#?}", error));
+        }
+        if let Some(usage) = chunk.usage {
+            self.usage = Some(Usage {
+                input_tokens: usage.prompt_tokens,
+                output_tokens: usage.completion_tokens,
+            });
+        }
+        let mut deltas = vec![];
+        for choice in chunk.choices {
+            if let Some(text) = choice.delta.content.filter(|text| !text.is_empty()) {
+                deltas.push(CompletionDelta::Text(text));
+            }
+            for (position, call) in choice.delta.tool_calls.into_iter().flatten().enumerate() {
+                let partial = self
+                    .tool_calls
+                    .entry(call.index.unwrap_or(position))
+                    .or_default();
+                if let Some(id) = call.id {
+                    partial.id = id;
+                }
+                if let Some(function) = call.function {
+                    if let Some(name) = function.name {
+                        partial.name = name;
+                    }
+                    partial
+                        .arguments
+                        .push_str(&function.arguments.unwrap_or_default());
+                }
+            }
+            if let Some(reason) = choice.finish_reason {
+                deltas.extend(self.flush_tool_calls()?);
+                deltas.push(CompletionDelta::Stop(StopReason::from(reason.as_str())));
+            }
+        }
+        Ok(deltas)
+    }
+
+    fn finish(&mut self) -> Vec<CompletionDelta> {
+        // a body cut short mid-arguments leaves nothing worth emitting
+        let mut deltas = self.flush_tool_calls().unwrap_or_default();
+        deltas.extend(self.usage.take().map(CompletionDelta::Usage));
+        deltas
+    }
 }
 
 struct Metadata {}
@@ -196,8 +574,129 @@ async fn openai() {
     client
         .chat_completion(&ChatRequest::new(
             String::from("gpt-4o"),
-            vec![ChatMessage::user(String::from("Introduce yourself"))],
+            &CompletionRequest::new(vec![Message::user("Introduce yourself")]),
         ))
         .await
         .unwrap();
 }
+
+#[tokio::test]
+async fn test_stream_fixture() {
+    let deltas = replay_fixture(
+        include_str!("../../fixtures/openai_stream.sse"),
+        OpenAiStreamParser::default(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(text_of(&deltas), "Hi there — how can I help?");
+    assert_eq!(
+        deltas[deltas.len() - 2..],
+        [
+            CompletionDelta::Stop(StopReason::EndTurn),
+            CompletionDelta::Usage(Usage {
+                input_tokens: 9,
+                output_tokens: 8,
+            }),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_stream_tool_call_fixture() {
+    let deltas = replay_fixture(
+        include_str!("../../fixtures/openai_tool_call_stream.sse"),
+        OpenAiStreamParser::default(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        deltas[..2],
+        [
+            CompletionDelta::ToolCall(SuperToolCall {
+                id: String::from("call_9pGx2nUQd0Ih6HqbQRSz1ZcA"),
+                name: String::from("get_uptime"),
+                arguments: serde_json::json!({"node": "home-router", "days": 7}),
+            }),
+            CompletionDelta::Stop(StopReason::ToolUse),
+        ]
+    );
+}
+
+#[test]
+fn test_chat_messages() {
+    use crate::clients::request::{ToolCall, ToolResult};
+    let request = CompletionRequest::new(vec![
+        Message {
+            role: SuperRole::User,
+            content: vec![
+                ContentPart::Text(String::from("What is in this picture?")),
+                ContentPart::Image {
+                    mime_type: String::from("image/png"),
+                    data: String::from("iVBORw0KGgo="),
+                },
+            ],
+        },
+        Message {
+            role: SuperRole::Assistant,
+            content: vec![ContentPart::ToolCall(ToolCall {
+                id: String::from("call_1"),
+                name: String::from("describe"),
+                arguments: serde_json::json!({"detail": "high"}),
+            })],
+        },
+        Message {
+            role: SuperRole::User,
+            content: vec![ContentPart::ToolResult(ToolResult {
+                call_id: String::from("call_1"),
+                name: String::from("describe"),
+                content: String::from("A cat"),
+                is_error: false,
+            })],
+        },
+    ])
+    .with_system("Answer in one word");
+    let messages = serde_json::to_value(chat_messages(&request)).unwrap();
+    assert_eq!(
+        messages,
+        serde_json::json!([
+            {"role": "system", "content": "Answer in one word"},
+            {"role": "user", "content": [
+                {"type": "text", "text": "What is in this picture?"},
+                {"type": "image_url", "image_url": {"url": "data:image/png;base64,iVBORw0KGgo="}}
+            ]},
+            {"role": "assistant", "tool_calls": [
+                {"id": "call_1", "type": "function", "function": {"name": "describe", "arguments": "{\"detail\":\"high\"}"}}
+            ]},
+            {"role": "tool", "content": "A cat", "tool_call_id": "call_1"}
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_completion_against_mock() {
+    use crate::clients::mock::{MockResponse, MockServer};
+    use crate::models::open_ai::OpenAiModels;
+    let server = MockServer::start(vec![MockResponse::json(
+        200,
+        serde_json::json!({
+            "choices": [{ "message": { "role": "assistant", "content": "Hi there" } }]
+        }),
+    )])
+    .await;
+    let client = OpenAiClient::new(Client::new(), String::from("key")).with_base_url(server.url());
+    let message = client
+        .completion(
+            ModelName::OpenAi(OpenAiModels::Gpt4oMini),
+            CompletionRequest::new(vec![Message::user("hi")]),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        message.content,
+        vec![ContentPart::Text(String::from("Hi there"))]
+    );
+    let received = &server.received()[0];
+    assert_eq!(received.path, "/v1/chat/completions");
+    assert_eq!(received.headers["authorization"], "Bearer key");
+    assert_eq!(received.body["model"], "gpt-4o-mini");
+}
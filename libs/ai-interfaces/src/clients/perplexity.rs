@@ -1,7 +1,14 @@
-use crate::ai_constants::{OPENAI_VAR_NAME, PERPLEXITY_VAR_NAME};
-use crate::clients::bulk::Role as SuperRole;
+use crate::ai_constants::{OPENAI_VAR_NAME, PERPLEXITY_BASE_URL, PERPLEXITY_VAR_NAME};
 use crate::clients::bulk::{ChatCompletionExt, Message};
 use crate::clients::openai::OpenAiClient;
+use crate::clients::openai::{chat_messages, ChatMessage, OpenAiStreamParser, WireParams};
+#[cfg(test)]
+use crate::clients::request::ToolDefinition;
+use crate::clients::request::{Capabilities, CompletionRequest};
+use crate::clients::stream::{completion_stream, CompletionStream};
+#[cfg(test)]
+use crate::clients::stream::{replay_fixture, text_of, CompletionDelta, StopReason, Usage};
+use crate::clients::transport::{HttpRequest, HttpResponse, Transport};
 use crate::models::base::ModelName;
 use anyhow::{anyhow, Context};
 use async_trait::async_trait;
@@ -16,43 +23,57 @@ use std::fmt::{Display, Formatter};
 pub struct PerplexityClient {
     client: Client,
     api_key: String,
+    base_url: String,
+    transport: Transport,
 }
 
 impl PerplexityClient {
     pub fn new(client: Client, api_key: String) -> Self {
-        Self { client, api_key }
+        Self {
+            client,
+            api_key,
+            base_url: PERPLEXITY_BASE_URL.to_string(),
+            transport: Transport::default(),
+        }
     }
 
     pub fn from_env(client: Client, env_var_name: &str) -> Result<Self, VarError> {
         let api_key = std::env::var(env_var_name)?;
         Ok(Self::new(client, api_key))
     }
+
+    /// Points the client at another deployment, a proxy or a mock server.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn with_transport(mut self, transport: Transport) -> Self {
+        self.transport = transport;
+        self
+    }
     async fn chat_completion(
         &self,
         request: &ChatCompletionRequest,
     ) -> anyhow::Result<ChatCompletionResponse> {
-        let url = "https://api.perplexity.ai/chat/completions";
-        let response = self
-            .client
-            .post(url)
-            .header(AUTHORIZATION, format!("Bearer {}", self.api_key.as_str()))
-            .json(&request)
-            .send()
-            .await?;
-        if response.status().is_success() {
-            return Ok(response.json().await?);
-        }
-        if response.status().is_client_error() {
-            let error: Error = response.json().await?;
-            return Err(anyhow!(error));
-        }
-        Err(anyhow!(
-            "Unexpected response status code {} for OpenAI chat completion request",
-            response.status()
-        ))
+        self.transport
+            .send(&self.client, &self.http_request(request)?, api_error)
+            .await?
+            .json()
+    }
+
+    fn http_request(&self, request: &ChatCompletionRequest) -> anyhow::Result<HttpRequest> {
+        Ok(
+            HttpRequest::post(format!("{}/chat/completions", self.base_url), request)?
+                .header(AUTHORIZATION.as_str(), format!("Bearer {}", self.api_key)),
+        )
     }
 }
 
+fn api_error(response: HttpResponse) -> anyhow::Error {
+    response.into_error::<Error>("Perplexity")
+}
+
 #[derive(Deserialize, Debug)]
 struct InnerError {
     message: String,
@@ -73,52 +94,24 @@ impl Display for Error {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-#[serde(rename_all = "lowercase")]
-enum Role {
-    User,
-    Assistant,
-    System,
-    Function,
-}
-
-impl Display for Role {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self)
-    }
-}
-
-#[derive(Serialize, Deserialize, Debug)]
-struct ChatMessage {
-    role: Role,
-    content: String,
-}
-
-impl ChatMessage {
-    fn user(content: String) -> Self {
-        Self {
-            role: Role::User,
-            content,
-        }
-    }
-
-    fn assistant(content: String) -> Self {
-        Self {
-            role: Role::Assistant,
-            content,
-        }
-    }
-}
-
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ChatCompletionRequest {
     model: String,
     messages: Vec<ChatMessage>,
+    #[serde(flatten)]
+    params: WireParams,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
 }
 
 impl ChatCompletionRequest {
-    fn new(model: String, messages: Vec<ChatMessage>) -> Self {
-        Self { model, messages }
+    fn new(model: String, request: &CompletionRequest) -> Self {
+        Self {
+            model,
+            messages: chat_messages(request),
+            params: WireParams::from(&request.params),
+            stream: false,
+        }
     }
 }
 
@@ -132,38 +125,42 @@ pub struct ChatCompletionResponse {
     choices: Vec<Choice>,
 }
 
+const CAPABILITIES: Capabilities = Capabilities {
+    images: false,
+    tools: false,
+    stop_sequences: false,
+};
+
 #[async_trait]
 impl ChatCompletionExt for PerplexityClient {
     async fn completion(
         &self,
         model_name: ModelName,
-        messages: Vec<Message>,
+        request: CompletionRequest,
     ) -> anyhow::Result<Message> {
-        let request = ChatCompletionRequest::new(
-            model_name.to_string(),
-            messages
-                .into_iter()
-                .map(|msg| {
-                    if matches!(msg.role, SuperRole::User) {
-                        ChatMessage::user(msg.content)
-                    } else {
-                        ChatMessage::assistant(msg.content)
-                    }
-                })
-                .collect(),
-        );
+        request.ensure_supported("Perplexity", &CAPABILITIES)?;
+        let request = ChatCompletionRequest::new(model_name.to_string(), &request);
         let mut response = self.chat_completion(&request).await?;
         let message = response
             .choices
             .pop()
             .context("GPT returned no completion message")?;
-        let content = message.message.content;
-        let role = match message.message.role {
-            Role::User => SuperRole::User,
-            Role::Assistant => SuperRole::Assistant,
-            other => return Err(anyhow!("Unimplemented GPT role {other}")),
-        };
-        Ok(Message { content, role })
+        message.message.into_message()
+    }
+
+    async fn stream_completion(
+        &self,
+        model_name: ModelName,
+        request: CompletionRequest,
+    ) -> anyhow::Result<CompletionStream> {
+        request.ensure_supported("Perplexity", &CAPABILITIES)?;
+        let mut request = ChatCompletionRequest::new(model_name.to_string(), &request);
+        request.stream = true;
+        let body = self
+            .transport
+            .stream(&self.client, &self.http_request(&request)?, api_error)
+            .await?;
+        Ok(completion_stream(body, OpenAiStreamParser::default()))
     }
 }
 
@@ -175,9 +172,47 @@ async fn perplexity() {
     let resp = client
         .chat_completion(&ChatCompletionRequest::new(
             String::from("llama-3.1-sonar-small-128k-online"),
-            vec![ChatMessage::user(String::from("Introduce yourself"))],
+            &CompletionRequest::new(vec![Message::user("Introduce yourself")]),
         ))
         .await
         .unwrap();
     println!("resp = {:#?}", resp);
 }
+
+#[tokio::test]
+async fn test_stream_fixture() {
+    let deltas = replay_fixture(
+        include_str!("../../fixtures/perplexity_stream.sse"),
+        OpenAiStreamParser::default(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(text_of(&deltas), "BlockMesh is a DePIN network.");
+    assert_eq!(
+        deltas[deltas.len() - 2..],
+        [
+            CompletionDelta::Stop(StopReason::EndTurn),
+            CompletionDelta::Usage(Usage {
+                input_tokens: 6,
+                output_tokens: 9,
+            }),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_unsupported_tools() {
+    let client = PerplexityClient::new(Client::new(), String::new());
+    let request = CompletionRequest::new(vec![Message::user("Is my node online?")]).with_tool(
+        ToolDefinition {
+            name: String::from("get_uptime"),
+            description: String::from("Uptime of a node"),
+            parameters: serde_json::json!({"type": "object", "properties": {}}),
+        },
+    );
+    let error = client
+        .completion(ModelName::Perplexity(Default::default()), request)
+        .await
+        .unwrap_err();
+    assert_eq!(error.to_string(), "Perplexity does not support tools");
+}
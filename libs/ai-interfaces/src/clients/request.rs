@@ -0,0 +1,184 @@
+use crate::clients::bulk::{Message, Role};
+use crate::error::AiInterfaceError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Provider neutral completion request, each client translates it to its own wire format.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct CompletionRequest {
+    pub system: Option<String>,
+    pub messages: Vec<Message>,
+    pub tools: Vec<ToolDefinition>,
+    pub params: SamplingParams,
+}
+
+impl From<Vec<Message>> for CompletionRequest {
+    fn from(messages: Vec<Message>) -> Self {
+        Self {
+            messages,
+            ..Self::default()
+        }
+    }
+}
+
+impl CompletionRequest {
+    pub fn new(messages: Vec<Message>) -> Self {
+        Self::from(messages)
+    }
+
+    pub fn with_system(mut self, system: impl Into<String>) -> Self {
+        self.system = Some(system.into());
+        self
+    }
+
+    pub fn with_tool(mut self, tool: ToolDefinition) -> Self {
+        self.tools.push(tool);
+        self
+    }
+
+    pub fn with_params(mut self, params: SamplingParams) -> Self {
+        self.params = params;
+        self
+    }
+
+    fn parts(&self) -> impl Iterator<Item = &ContentPart> {
+        self.messages
+            .iter()
+            .flat_map(|message| message.content.iter())
+    }
+
+    /// Fails with [`AiInterfaceError::UnsupportedFeature`] naming the first feature
+    /// the request uses that `provider` can't handle, rather than silently dropping it.
+    pub fn ensure_supported(
+        &self,
+        provider: &str,
+        capabilities: &Capabilities,
+    ) -> Result<(), AiInterfaceError> {
+        let unsupported = |feature: &str| AiInterfaceError::UnsupportedFeature {
+            provider: provider.to_string(),
+            feature: feature.to_string(),
+        };
+        if !capabilities.images
+            && self
+                .parts()
+                .any(|part| matches!(part, ContentPart::Image { .. }))
+        {
+            return Err(unsupported("image inputs"));
+        }
+        if !capabilities.tools
+            && (!self.tools.is_empty()
+                || self.parts().any(|part| {
+                    matches!(part, ContentPart::ToolCall(_) | ContentPart::ToolResult(_))
+                }))
+        {
+            return Err(unsupported("tools"));
+        }
+        if !capabilities.stop_sequences && !self.params.stop.is_empty() {
+            return Err(unsupported("stop sequences"));
+        }
+        if self
+            .messages
+            .iter()
+            .any(|message| matches!(message.role, Role::User) && message.has_tool_calls())
+        {
+            return Err(AiInterfaceError::InvalidRequest(String::from(
+                "tool calls can only appear in assistant messages",
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// What a client can translate, checked before anything is sent.
+#[derive(Debug, Clone, Copy)]
+pub struct Capabilities {
+    pub images: bool,
+    pub tools: bool,
+    pub stop_sequences: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct SamplingParams {
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+    pub max_tokens: Option<u32>,
+    pub stop: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum ContentPart {
+    Text(String),
+    /// Base64 encoded image data.
+    Image {
+        mime_type: String,
+        data: String,
+    },
+    ToolCall(ToolCall),
+    ToolResult(ToolResult),
+}
+
+/// `parameters` is the JSON schema of the arguments.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ToolDefinition {
+    pub name: String,
+    pub description: String,
+    pub parameters: Value,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ToolCall {
+    pub id: String,
+    pub name: String,
+    pub arguments: Value,
+}
+
+/// `name` repeats the tool name since Gemini matches results by name rather than id.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ToolResult {
+    pub call_id: String,
+    pub name: String,
+    pub content: String,
+    pub is_error: bool,
+}
+
+/// Arguments arrive as a JSON encoded string from OpenAI compatible APIs and
+/// as partial JSON fragments from streams, an empty string means no arguments.
+pub(crate) fn parse_arguments(arguments: &str) -> anyhow::Result<Value> {
+    if arguments.trim().is_empty() {
+        return Ok(Value::Object(Default::default()));
+    }
+    Ok(serde_json::from_str(arguments)?)
+}
+
+#[test]
+fn test_ensure_supported() {
+    let capabilities = Capabilities {
+        images: false,
+        tools: true,
+        stop_sequences: false,
+    };
+    let request = CompletionRequest::new(vec![Message::user("Hi")]).with_system("Be brief");
+    assert!(request.ensure_supported("Test", &capabilities).is_ok());
+    let image = CompletionRequest::new(vec![Message {
+        role: Role::User,
+        content: vec![ContentPart::Image {
+            mime_type: String::from("image/png"),
+            data: String::from("iVBORw0KGgo="),
+        }],
+    }]);
+    assert_eq!(
+        image
+            .ensure_supported("Test", &capabilities)
+            .unwrap_err()
+            .to_string(),
+        "Test does not support image inputs"
+    );
+    let stop = request.with_params(SamplingParams {
+        stop: vec![String::from("\n")],
+        ..SamplingParams::default()
+    });
+    assert!(matches!(
+        stop.ensure_supported("Test", &capabilities),
+        Err(AiInterfaceError::UnsupportedFeature { .. })
+    ));
+}
@@ -0,0 +1,236 @@
+use crate::clients::request::ToolCall;
+use futures::stream::{self, BoxStream, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter};
+
+/// Incremental piece of a streamed completion, in the order the provider sent it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CompletionDelta {
+    Text(String),
+    /// Emitted once the call's arguments are complete.
+    ToolCall(ToolCall),
+    Stop(StopReason),
+    Usage(Usage),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum StopReason {
+    EndTurn,
+    MaxTokens,
+    StopSequence,
+    ToolUse,
+    ContentFilter,
+    Other(String),
+}
+
+impl From<&str> for StopReason {
+    fn from(value: &str) -> Self {
+        match value {
+            "end_turn" | "stop" | "STOP" => Self::EndTurn,
+            "max_tokens" | "length" | "model_length" | "MAX_TOKENS" => Self::MaxTokens,
+            "stop_sequence" => Self::StopSequence,
+            "tool_use" | "tool_calls" | "function_call" => Self::ToolUse,
+            "content_filter" | "SAFETY" | "RECITATION" | "BLOCKLIST" | "PROHIBITED_CONTENT" => {
+                Self::ContentFilter
+            }
+            other => Self::Other(other.to_string()),
+        }
+    }
+}
+
+impl Display for StopReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::EndTurn => write!(f, "end_turn"),
+            Self::MaxTokens => write!(f, "max_tokens"),
+            Self::StopSequence => write!(f, "stop_sequence"),
+            Self::ToolUse => write!(f, "tool_use"),
+            Self::ContentFilter => write!(f, "content_filter"),
+            Self::Other(other) => write!(f, "{}", other),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct Usage {
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+}
+
+pub type CompletionStream = BoxStream<'static, anyhow::Result<CompletionDelta>>;
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct SseEvent {
+    pub event: Option<String>,
+    pub data: String,
+}
+
+/// Splits a `text/event-stream` body into events. Works on bytes so chunk
+/// boundaries inside multi-byte characters are harmless.
+#[derive(Default)]
+pub(crate) struct SseDecoder {
+    buffer: Vec<u8>,
+    current: SseEvent,
+    has_data: bool,
+}
+
+impl SseDecoder {
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buffer.extend_from_slice(chunk);
+        let mut events = Vec::new();
+        while let Some(position) = self.buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=position).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\n', '\r']);
+            if let Some(event) = self.line(line) {
+                events.push(event);
+            }
+        }
+        events
+    }
+
+    /// Flushes an event that was not followed by a blank line before the body ended.
+    pub fn finish(mut self) -> Option<SseEvent> {
+        if !self.buffer.is_empty() {
+            let line = String::from_utf8_lossy(&self.buffer).into_owned();
+            self.line(line.trim_end_matches('\r'));
+        }
+        self.dispatch()
+    }
+
+    fn line(&mut self, line: &str) -> Option<SseEvent> {
+        if line.is_empty() {
+            return self.dispatch();
+        }
+        if line.starts_with(':') {
+            return None;
+        }
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "event" => self.current.event = Some(value.to_string()),
+            "data" => {
+                if self.has_data {
+                    self.current.data.push('\n');
+                }
+                self.current.data.push_str(value);
+                self.has_data = true;
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn dispatch(&mut self) -> Option<SseEvent> {
+        let event = std::mem::take(&mut self.current);
+        if !std::mem::take(&mut self.has_data) {
+            return None;
+        }
+        Some(event)
+    }
+}
+
+/// Provider specific translation of SSE events into deltas.
+pub(crate) trait SseParser: Send + 'static {
+    fn parse(&mut self, event: &SseEvent) -> anyhow::Result<Vec<CompletionDelta>>;
+
+    /// Called once the body ends, for providers that only report usage cumulatively.
+    fn finish(&mut self) -> Vec<CompletionDelta> {
+        Vec::new()
+    }
+}
+
+struct StreamState<B, P> {
+    body: BoxStream<'static, anyhow::Result<B>>,
+    decoder: Option<SseDecoder>,
+    parser: P,
+    pending: VecDeque<CompletionDelta>,
+}
+
+impl<B: AsRef<[u8]>, P: SseParser> StreamState<B, P> {
+    async fn next_delta(mut self) -> anyhow::Result<Option<(CompletionDelta, Self)>> {
+        loop {
+            if let Some(delta) = self.pending.pop_front() {
+                return Ok(Some((delta, self)));
+            }
+            let Some(decoder) = self.decoder.as_mut() else {
+                return Ok(None);
+            };
+            match self.body.next().await {
+                Some(chunk) => {
+                    for event in decoder.push(chunk?.as_ref()) {
+                        self.pending.extend(self.parser.parse(&event)?);
+                    }
+                }
+                None => {
+                    if let Some(event) = self.decoder.take().and_then(SseDecoder::finish) {
+                        self.pending.extend(self.parser.parse(&event)?);
+                    }
+                    self.pending.extend(self.parser.finish());
+                }
+            }
+        }
+    }
+}
+
+pub(crate) fn completion_stream<S, B, E, P>(body: S, parser: P) -> CompletionStream
+where
+    S: Stream<Item = Result<B, E>> + Send + 'static,
+    B: AsRef<[u8]> + Send + 'static,
+    E: Into<anyhow::Error>,
+    P: SseParser,
+{
+    let state = StreamState {
+        body: body.map(|chunk| chunk.map_err(Into::into)).boxed(),
+        decoder: Some(SseDecoder::default()),
+        parser,
+        pending: VecDeque::new(),
+    };
+    stream::try_unfold(state, StreamState::next_delta).boxed()
+}
+
+#[cfg(test)]
+pub(crate) async fn replay_fixture<P: SseParser>(
+    fixture: &'static str,
+    parser: P,
+) -> anyhow::Result<Vec<CompletionDelta>> {
+    use futures::TryStreamExt;
+    // odd sized chunks so events and characters get split across reads
+    let chunks: Vec<Result<Vec<u8>, anyhow::Error>> = fixture
+        .as_bytes()
+        .chunks(7)
+        .map(|chunk| Ok(chunk.to_vec()))
+        .collect();
+    completion_stream(stream::iter(chunks), parser)
+        .try_collect()
+        .await
+}
+
+#[cfg(test)]
+pub(crate) fn text_of(deltas: &[CompletionDelta]) -> String {
+    deltas
+        .iter()
+        .filter_map(|delta| match delta {
+            CompletionDelta::Text(text) => Some(text.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_sse_decoder_multiline_and_comments() {
+    let mut decoder = SseDecoder::default();
+    let mut events =
+        decoder.push(b": keep-alive\r\nevent: ping\r\ndata: a\r\ndata: b\r\n\r\ndata: tail");
+    assert_eq!(
+        events,
+        vec![SseEvent {
+            event: Some(String::from("ping")),
+            data: String::from("a\nb"),
+        }]
+    );
+    events.extend(decoder.finish());
+    assert_eq!(events[1].data, "tail");
+    assert_eq!(events[1].event, None);
+}
@@ -0,0 +1,511 @@
+use crate::ai_constants::{
+    BLOCKMESH_API_TOKEN_VAR_NAME, BLOCKMESH_EMAIL_VAR_NAME, BLOCKMESH_MANAGER_URL_VAR_NAME,
+};
+use crate::error::AiInterfaceError;
+use anyhow::{anyhow, Context};
+use block_mesh_common::constants::BLOCK_MESH_APP_SERVER;
+use block_mesh_common::interfaces::server_api::{
+    CreateTaskRequest, CreateTaskResponse, GetTaskResultRequest, GetTaskResultResponse,
+};
+use block_mesh_common::routes_enum::RoutesEnum;
+use futures::stream::{self, BoxStream};
+use futures::StreamExt;
+use reqwest::{Client, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use uuid::Uuid;
+
+pub const DEFAULT_NODE_TIMEOUT: Duration = Duration::from_secs(120);
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// Status nodes submit when they could not run the task at all.
+const NODE_FAILURE_CODE: i32 = 520;
+/// Headers carrying API keys, never written to a cassette or handed to a BlockMesh node.
+const SECRET_HEADERS: [&str; 3] = ["authorization", "x-api-key", "x-goog-api-key"];
+const REDACTED: &str = "REDACTED";
+
+/// Body of a successful response, streamed when sent directly, a single chunk when a node ran it.
+pub(crate) type BodyStream = BoxStream<'static, anyhow::Result<Vec<u8>>>;
+
+/// Provider request built by each client, independent of how it is sent.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HttpRequest {
+    pub url: String,
+    pub headers: BTreeMap<String, String>,
+    pub body: Value,
+}
+
+impl HttpRequest {
+    pub fn post(url: impl Into<String>, body: &impl Serialize) -> anyhow::Result<Self> {
+        Ok(Self {
+            url: url.into(),
+            headers: BTreeMap::new(),
+            body: serde_json::to_value(body)?,
+        })
+    }
+
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.insert(name.into(), value.into());
+        self
+    }
+
+    /// Same request with API keys replaced, the form it is stored and matched in.
+    pub fn redacted(&self) -> Self {
+        let mut request = self.clone();
+        for (name, value) in request.headers.iter_mut() {
+            if SECRET_HEADERS.contains(&name.to_lowercase().as_str()) {
+                *value = REDACTED.to_string();
+            }
+        }
+        request.url = self.map_query(|param| match param.split_once('=') {
+            Some(("key", _)) => Some(format!("key={}", REDACTED)),
+            _ => Some(param.to_string()),
+        });
+        request
+    }
+
+    /// Same request with API keys removed, the form a BlockMesh node gets.
+    pub fn without_secrets(&self) -> Self {
+        let mut request = self.clone();
+        request
+            .headers
+            .retain(|name, _| !SECRET_HEADERS.contains(&name.to_lowercase().as_str()));
+        request.url = self.map_query(|param| match param.split_once('=') {
+            Some(("key", _)) => None,
+            _ => Some(param.to_string()),
+        });
+        request
+    }
+
+    fn map_query(&self, map: impl Fn(&str) -> Option<String>) -> String {
+        match self.url.split_once('?') {
+            Some((path, query)) => {
+                let query: Vec<String> = query.split('&').filter_map(map).collect();
+                if query.is_empty() {
+                    path.to_string()
+                } else {
+                    format!("{}?{}", path, query.join("&"))
+                }
+            }
+            None => self.url.clone(),
+        }
+    }
+
+    async fn send_direct(&self, client: &Client) -> anyhow::Result<reqwest::Response> {
+        let mut builder = client.post(&self.url).json(&self.body);
+        for (name, value) in &self.headers {
+            builder = builder.header(name, value);
+        }
+        Ok(builder.send().await?)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+impl HttpResponse {
+    async fn read(response: reqwest::Response) -> anyhow::Result<Self> {
+        Ok(Self {
+            status: response.status().as_u16(),
+            body: response.text().await?,
+        })
+    }
+
+    pub fn status(&self) -> StatusCode {
+        StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    pub fn json<T: DeserializeOwned>(&self) -> anyhow::Result<T> {
+        Ok(serde_json::from_str(&self.body)?)
+    }
+
+    /// Maps a non success response to [`AiInterfaceError::Provider`], `E` is the provider's
+    /// error body and the raw body is kept when it doesn't match.
+    pub(crate) fn into_error<E: DeserializeOwned + Display>(self, provider: &str) -> anyhow::Error {
+        let message = match self.json::<E>() {
+            Ok(error) => error.to_string(),
+            Err(_) => self.body,
+        };
+        AiInterfaceError::Provider {
+            provider: provider.to_string(),
+            status: self.status,
+            message,
+        }
+        .into()
+    }
+}
+
+/// How provider requests leave the process, see [`crate::clients::bulk::AIClient::with_transport`].
+#[derive(Clone, Default)]
+pub enum Transport {
+    #[default]
+    Direct,
+    BlockMesh(BlockMeshTransport),
+    /// Sends directly and saves every exchange to the cassette.
+    Record(Cassette),
+    /// Answers from the cassette without touching the network.
+    Replay(Cassette),
+}
+
+impl Transport {
+    async fn exchange(&self, client: &Client, request: &HttpRequest) -> anyhow::Result<Response> {
+        match self {
+            Self::Direct => {
+                let response = request.send_direct(client).await?;
+                if response.status().is_success() {
+                    return Ok(Response::Streaming(response));
+                }
+                Ok(Response::Buffered(HttpResponse::read(response).await?))
+            }
+            Self::BlockMesh(transport) => {
+                Ok(Response::Buffered(transport.send(client, request).await?))
+            }
+            Self::Record(cassette) => {
+                let response = HttpResponse::read(request.send_direct(client).await?).await?;
+                cassette.push(request, response.clone())?;
+                Ok(Response::Buffered(response))
+            }
+            Self::Replay(cassette) => Ok(Response::Buffered(cassette.take(request)?)),
+        }
+    }
+
+    /// Successful responses only, anything else goes through the client's `api_error`.
+    pub(crate) async fn send(
+        &self,
+        client: &Client,
+        request: &HttpRequest,
+        api_error: fn(HttpResponse) -> anyhow::Error,
+    ) -> anyhow::Result<HttpResponse> {
+        let response = match self.exchange(client, request).await? {
+            Response::Streaming(response) => HttpResponse::read(response).await?,
+            Response::Buffered(response) => response,
+        };
+        if !response.status().is_success() {
+            return Err(api_error(response));
+        }
+        Ok(response)
+    }
+
+    /// A node returns the whole event stream at once, it is parsed the same way.
+    pub(crate) async fn stream(
+        &self,
+        client: &Client,
+        request: &HttpRequest,
+        api_error: fn(HttpResponse) -> anyhow::Error,
+    ) -> anyhow::Result<BodyStream> {
+        match self.exchange(client, request).await? {
+            Response::Streaming(response) => Ok(response
+                .bytes_stream()
+                .map(|chunk| {
+                    chunk
+                        .map(|bytes| bytes.to_vec())
+                        .map_err(anyhow::Error::from)
+                })
+                .boxed()),
+            Response::Buffered(response) if response.status().is_success() => {
+                Ok(stream::iter([Ok(response.body.into_bytes())]).boxed())
+            }
+            Response::Buffered(response) => Err(api_error(response)),
+        }
+    }
+}
+
+enum Response {
+    Streaming(reqwest::Response),
+    Buffered(HttpResponse),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Exchange {
+    pub request: HttpRequest,
+    pub response: HttpResponse,
+}
+
+/// Provider exchanges kept in a JSON fixture file, written by [`Transport::Record`] and read by
+/// [`Transport::Replay`] for tests that don't need API keys or network.
+///
+/// Requests are stored with their API keys redacted, see [`HttpRequest::redacted`].
+#[derive(Clone)]
+pub struct Cassette {
+    path: PathBuf,
+    exchanges: Arc<Mutex<Vec<Exchange>>>,
+}
+
+impl Cassette {
+    /// Starts an empty cassette, `path` is overwritten on the first exchange.
+    pub fn record(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            exchanges: Arc::default(),
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let file = std::fs::read_to_string(path)
+            .with_context(|| format!("Cannot read cassette {}", path.display()))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            exchanges: Arc::new(Mutex::new(serde_json::from_str(&file)?)),
+        })
+    }
+
+    pub fn exchanges(&self) -> Vec<Exchange> {
+        self.exchanges.lock().unwrap().clone()
+    }
+
+    fn push(&self, request: &HttpRequest, response: HttpResponse) -> anyhow::Result<()> {
+        let mut exchanges = self.exchanges.lock().unwrap();
+        exchanges.push(Exchange {
+            request: request.redacted(),
+            response,
+        });
+        std::fs::write(&self.path, serde_json::to_string_pretty(&*exchanges)?)?;
+        Ok(())
+    }
+
+    /// Each recorded exchange answers once, in recording order for identical requests.
+    fn take(&self, request: &HttpRequest) -> anyhow::Result<HttpResponse> {
+        let request = request.redacted();
+        let mut exchanges = self.exchanges.lock().unwrap();
+        let position = exchanges
+            .iter()
+            .position(|exchange| exchange.request == request)
+            .ok_or_else(|| AiInterfaceError::NotRecorded(request.url.clone()))?;
+        Ok(exchanges.remove(position).response)
+    }
+}
+
+/// Runs provider requests from BlockMesh nodes: each request becomes a task created with
+/// `create_task_with_token` and its response is read back once a node submits it.
+///
+/// This is not private: the node is a third party and sees the prompt and the provider's answer
+/// in plaintext. API keys are stripped before the task is created, so only providers that accept
+/// unauthenticated requests work through it, see `AIClient::keyless_openai_compatible`. Tasks are
+/// answered in one piece, streamed completions arrive when the node is done.
+#[derive(Clone)]
+pub struct BlockMeshTransport {
+    base_url: String,
+    email: String,
+    api_token: Uuid,
+    timeout: Duration,
+    poll_interval: Duration,
+}
+
+impl BlockMeshTransport {
+    pub fn new(base_url: impl Into<String>, email: impl Into<String>, api_token: Uuid) -> Self {
+        Self {
+            base_url: base_url.into(),
+            email: email.into(),
+            api_token,
+            timeout: DEFAULT_NODE_TIMEOUT,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+        }
+    }
+
+    /// The api token needs the `task-create` scope.
+    pub fn from_env() -> anyhow::Result<Self> {
+        let base_url = std::env::var(BLOCKMESH_MANAGER_URL_VAR_NAME)
+            .unwrap_or(BLOCK_MESH_APP_SERVER.to_string());
+        let email = std::env::var(BLOCKMESH_EMAIL_VAR_NAME)
+            .with_context(|| format!("{} is not set", BLOCKMESH_EMAIL_VAR_NAME))?;
+        let api_token = std::env::var(BLOCKMESH_API_TOKEN_VAR_NAME)
+            .with_context(|| format!("{} is not set", BLOCKMESH_API_TOKEN_VAR_NAME))?;
+        Ok(Self::new(base_url, email, Uuid::from_str(&api_token)?))
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    fn url(&self, route: RoutesEnum) -> String {
+        format!("{}/api{}", self.base_url.trim_end_matches('/'), route)
+    }
+
+    async fn send(&self, client: &Client, request: &HttpRequest) -> anyhow::Result<HttpResponse> {
+        let request = request.without_secrets();
+        let task: CreateTaskResponse = client
+            .post(self.url(RoutesEnum::Api_CreateTaskWithToken))
+            .json(&CreateTaskRequest {
+                url: request.url.clone(),
+                method: String::from("POST"),
+                headers: Some(serde_json::to_value(&request.headers)?),
+                body: Some(request.body.clone()),
+                api_token: self.api_token,
+                email: self.email.clone(),
+            })
+            .send()
+            .await?
+            .error_for_status()
+            .context("BlockMesh rejected the task")?
+            .json()
+            .await?;
+        match tokio::time::timeout(self.timeout, self.wait_for(client, task.task_id)).await {
+            Ok(result) => result,
+            Err(_) => Err(anyhow!(
+                "No BlockMesh node answered task {} within {:?}",
+                task.task_id,
+                self.timeout
+            )),
+        }
+    }
+
+    async fn wait_for(&self, client: &Client, task_id: Uuid) -> anyhow::Result<HttpResponse> {
+        let request = GetTaskResultRequest {
+            task_id,
+            api_token: self.api_token,
+            email: self.email.clone(),
+        };
+        loop {
+            tokio::time::sleep(self.poll_interval).await;
+            let result: GetTaskResultResponse = client
+                .post(self.url(RoutesEnum::Api_GetTaskResult))
+                .json(&request)
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+            match (result.response_code, result.response_raw) {
+                (Some(NODE_FAILURE_CODE), raw) => {
+                    return Err(anyhow!(
+                        "BlockMesh node could not run task {}: {}",
+                        task_id,
+                        raw.unwrap_or_default()
+                    ))
+                }
+                (Some(status), Some(body)) => {
+                    return Ok(HttpResponse {
+                        status: u16::try_from(status)?,
+                        body,
+                    })
+                }
+                _ => continue,
+            }
+        }
+    }
+}
+
+#[test]
+fn test_block_mesh_task_urls() {
+    let transport =
+        BlockMeshTransport::new("http://localhost:8000/", "node@example.com", Uuid::nil());
+    assert_eq!(
+        transport.url(RoutesEnum::Api_CreateTaskWithToken),
+        "http://localhost:8000/api/create_task_with_token"
+    );
+    assert_eq!(
+        transport.url(RoutesEnum::Api_GetTaskResult),
+        "http://localhost:8000/api/get_task_result"
+    );
+}
+
+#[test]
+fn test_http_request_headers_are_task_headers() {
+    let request = HttpRequest::post("https://api.openai.com/v1/chat/completions", &"{}")
+        .unwrap()
+        .header("content-type", "application/json");
+    // nodes expect a flat object of string values
+    assert_eq!(
+        serde_json::to_value(&request.headers).unwrap(),
+        serde_json::json!({ "content-type": "application/json" })
+    );
+}
+
+#[test]
+fn test_request_without_secrets() {
+    let request = HttpRequest::post(
+        "https://generativelanguage.googleapis.com/v1beta/models/gemini:generateContent?key=secret&alt=sse",
+        &"{}",
+    )
+    .unwrap()
+    .header("Authorization", "Bearer secret")
+    .header("x-goog-api-key", "secret")
+    .header("content-type", "application/json")
+    .without_secrets();
+    assert_eq!(
+        request.url,
+        "https://generativelanguage.googleapis.com/v1beta/models/gemini:generateContent?alt=sse"
+    );
+    assert_eq!(
+        serde_json::to_value(&request.headers).unwrap(),
+        serde_json::json!({ "content-type": "application/json" })
+    );
+    let request = HttpRequest::post("https://example.com/v1?key=secret", &"{}")
+        .unwrap()
+        .without_secrets();
+    assert_eq!(request.url, "https://example.com/v1");
+}
+
+#[test]
+fn test_redacted_request() {
+    let request = HttpRequest::post(
+        "https://generativelanguage.googleapis.com/v1beta/models/gemini:generateContent?key=secret&alt=sse",
+        &"{}",
+    )
+    .unwrap()
+    .header("x-api-key", "secret")
+    .header("content-type", "application/json")
+    .redacted();
+    assert_eq!(
+        request.url,
+        "https://generativelanguage.googleapis.com/v1beta/models/gemini:generateContent?key=REDACTED&alt=sse"
+    );
+    assert_eq!(request.headers["x-api-key"], REDACTED);
+    assert_eq!(request.headers["content-type"], "application/json");
+}
+
+#[tokio::test]
+async fn test_record_then_replay() {
+    use crate::clients::mock::{MockResponse, MockServer};
+    let server = MockServer::start(vec![MockResponse::json(
+        200,
+        serde_json::json!({ "answer": 42 }),
+    )])
+    .await;
+    let path = std::env::temp_dir().join(format!("cassette-{}.json", Uuid::new_v4()));
+    let request = HttpRequest::post(format!("{}/v1/answer", server.url()), &"question")
+        .unwrap()
+        .header("authorization", "Bearer secret");
+    let api_error = |response: HttpResponse| anyhow!("status {}", response.status);
+    let client = Client::new();
+
+    let recorded = Transport::Record(Cassette::record(&path))
+        .send(&client, &request, api_error)
+        .await
+        .unwrap();
+    assert_eq!(
+        server.received()[0].headers["authorization"],
+        "Bearer secret"
+    );
+    let file = std::fs::read_to_string(&path).unwrap();
+    assert!(!file.contains("secret"));
+
+    let replay = Transport::Replay(Cassette::load(&path).unwrap());
+    let replayed = replay.send(&client, &request, api_error).await.unwrap();
+    assert_eq!(replayed, recorded);
+    assert_eq!(replayed.json::<Value>().unwrap()["answer"], 42);
+    // every exchange answers once and the server is not asked again
+    let error = replay.send(&client, &request, api_error).await.unwrap_err();
+    assert!(matches!(
+        error.downcast_ref::<AiInterfaceError>(),
+        Some(AiInterfaceError::NotRecorded(_))
+    ));
+    assert_eq!(server.received().len(), 1);
+    std::fs::remove_file(path).unwrap();
+}
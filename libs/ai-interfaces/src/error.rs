@@ -2,4 +2,17 @@
 pub enum AiInterfaceError {
     #[error("DB Error: {0}")]
     DBError(String),
+    #[error("{provider} does not support {feature}")]
+    UnsupportedFeature { provider: String, feature: String },
+    #[error("Invalid completion request: {0}")]
+    InvalidRequest(String),
+    /// Provider answered with a non success status, `message` is its error body.
+    #[error("{provider} request failed with status {status}: {message}")]
+    Provider {
+        provider: String,
+        status: u16,
+        message: String,
+    },
+    #[error("No recorded response for {0}")]
+    NotRecorded(String),
 }
@@ -1,3 +1,4 @@
+use crate::clients::bulk::ClientKind;
 use crate::error::AiInterfaceError;
 use crate::models::anthropic::AnthropicModels;
 use crate::models::google::GoogleModels;
@@ -57,6 +58,17 @@ impl ModelName {
             value
         )))
     }
+
+    pub fn client_kind(&self) -> ClientKind {
+        match self {
+            Self::OpenAi(_) => ClientKind::OpenAi,
+            Self::Anthropic(_) => ClientKind::Anthropic,
+            Self::Google(_) => ClientKind::Google,
+            Self::Perplexity(_) => ClientKind::Perplexity,
+            Self::Meta(_) => ClientKind::Meta,
+            Self::Mistral(_) => ClientKind::Mistral,
+        }
+    }
 }
 
 impl Display for ModelName {
@@ -66,7 +78,8 @@ impl Display for ModelName {
             Self::Anthropic(x) => write!(f, "{}", x),
             Self::Google(x) => write!(f, "{}", x),
             Self::Perplexity(x) => write!(f, "{}", x),
-            _ => write!(f, "{}", Self::default()),
+            Self::Meta(x) => write!(f, "{}", x),
+            Self::Mistral(x) => write!(f, "{}", x),
         }
     }
 }
@@ -104,3 +117,10 @@ impl sqlx::Decode<'_, Postgres> for ModelName {
         }
     }
 }
+
+#[test]
+fn test_model_name_round_trip() {
+    for model in all::<ModelName>() {
+        assert_eq!(ModelName::from(model.to_string()), model);
+    }
+}
@@ -5,3 +5,4 @@ pub mod meta;
 pub mod mistral;
 pub mod open_ai;
 pub mod perplexity;
+pub mod pricing;
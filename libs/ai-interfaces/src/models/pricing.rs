@@ -0,0 +1,78 @@
+use crate::clients::stream::Usage;
+use crate::models::anthropic::AnthropicModels;
+use crate::models::base::ModelName;
+use crate::models::google::GoogleModels;
+use crate::models::mistral::MistralModels;
+use crate::models::open_ai::OpenAiModels;
+use crate::models::perplexity::PerplexityModels;
+use serde::{Deserialize, Serialize};
+
+/// List prices in USD per million tokens.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct ModelPricing {
+    pub input_per_million: f64,
+    pub output_per_million: f64,
+}
+
+impl ModelPricing {
+    const fn new(input_per_million: f64, output_per_million: f64) -> Self {
+        Self {
+            input_per_million,
+            output_per_million,
+        }
+    }
+
+    pub fn cost_usd(&self, usage: &Usage) -> f64 {
+        (usage.input_tokens as f64 * self.input_per_million
+            + usage.output_tokens as f64 * self.output_per_million)
+            / 1_000_000.0
+    }
+}
+
+impl ModelName {
+    /// `None` when the provider doesn't publish per-token prices for the model.
+    pub fn pricing(&self) -> Option<ModelPricing> {
+        Some(match self {
+            Self::OpenAi(model) => match model {
+                OpenAiModels::Gpt4o => ModelPricing::new(2.5, 10.0),
+                OpenAiModels::Gpt4oLatest => ModelPricing::new(5.0, 15.0),
+                OpenAiModels::Gpt4oMini => ModelPricing::new(0.15, 0.6),
+                OpenAiModels::Gpt4Turbo => ModelPricing::new(10.0, 30.0),
+                OpenAiModels::Gpt4 => ModelPricing::new(30.0, 60.0),
+                OpenAiModels::Gpt35Turbo => ModelPricing::new(0.5, 1.5),
+            },
+            Self::Anthropic(model) => match model {
+                AnthropicModels::Claude35HaikuLatest => ModelPricing::new(1.0, 5.0),
+                AnthropicModels::Claude35SonnetLatest => ModelPricing::new(3.0, 15.0),
+                AnthropicModels::Claude3OpusLatest => ModelPricing::new(15.0, 75.0),
+            },
+            Self::Google(model) => match model {
+                GoogleModels::Gemini15FlashLatest => ModelPricing::new(0.075, 0.3),
+                GoogleModels::Gemini15ProLatest => ModelPricing::new(1.25, 5.0),
+            },
+            Self::Perplexity(model) => match model {
+                PerplexityModels::Llama31SonarSmall128KOnline
+                | PerplexityModels::Llama31SonarSmall128KChat
+                | PerplexityModels::Llama318BInstruct => ModelPricing::new(0.2, 0.2),
+                PerplexityModels::Llama31SonarLarge128KOnline
+                | PerplexityModels::Llama31SonarLarge128KChat
+                | PerplexityModels::Llama3170BInstruct => ModelPricing::new(1.0, 1.0),
+                PerplexityModels::Llama31SonarHuge128KOnline => ModelPricing::new(5.0, 5.0),
+            },
+            Self::Mistral(MistralModels::MistralSmallLatest) => ModelPricing::new(0.2, 0.6),
+            Self::Meta(_) => return None,
+        })
+    }
+}
+
+#[test]
+fn test_cost_usd() {
+    let pricing = ModelName::Anthropic(AnthropicModels::Claude35SonnetLatest)
+        .pricing()
+        .unwrap();
+    let usage = Usage {
+        input_tokens: 2_000,
+        output_tokens: 1_000,
+    };
+    assert!((pricing.cost_usd(&usage) - 0.021).abs() < 1e-9);
+}
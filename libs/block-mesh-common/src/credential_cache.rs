@@ -0,0 +1,342 @@
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::env;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Salted HMAC of the credentials, the plaintext secret never becomes part of the cache.
+pub type CredentialKey = [u8; 64];
+
+#[derive(Debug, Clone, Copy)]
+pub struct CredentialCacheConfig {
+    pub capacity: usize,
+    pub ttl: Duration,
+    /// Negative results (wrong password, unknown user) expire much faster than successes.
+    pub negative_ttl: Duration,
+}
+
+impl Default for CredentialCacheConfig {
+    fn default() -> Self {
+        Self {
+            capacity: env::var("CREDENTIAL_CACHE_CAPACITY")
+                .unwrap_or("100000".to_string())
+                .parse()
+                .unwrap_or(100_000),
+            ttl: Duration::from_secs(
+                env::var("CREDENTIAL_CACHE_TTL_SECS")
+                    .unwrap_or("3600".to_string())
+                    .parse()
+                    .unwrap_or(3600),
+            ),
+            negative_ttl: Duration::from_secs(
+                env::var("CREDENTIAL_CACHE_NEGATIVE_TTL_SECS")
+                    .unwrap_or("30".to_string())
+                    .parse()
+                    .unwrap_or(30),
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CredentialCacheStats {
+    pub entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    pub hit_ratio: f64,
+}
+
+/// Returned by a cache miss, hands the key and the tick it was issued at to [`CredentialCache::insert`]
+/// so a result computed before an invalidation is not stored after it.
+#[derive(Debug, Clone)]
+pub struct CredentialTicket {
+    key: CredentialKey,
+    email: String,
+    issued_at: u64,
+}
+
+struct Entry<V> {
+    value: V,
+    email: String,
+    expires_at: Instant,
+    tick: u64,
+}
+
+struct Inner<V> {
+    entries: HashMap<CredentialKey, Entry<V>>,
+    /// Least recently used first.
+    order: BTreeMap<u64, CredentialKey>,
+    by_email: HashMap<String, HashSet<CredentialKey>>,
+    /// Tick of the latest invalidation per email, bounded by the capacity.
+    invalidated_at: HashMap<String, u64>,
+    /// Oldest invalidation first.
+    invalidation_order: BTreeMap<u64, String>,
+    /// Tickets issued before this tick are rejected, it covers the invalidations that were dropped.
+    forgotten_before: u64,
+    tick: u64,
+}
+
+impl<V> Inner<V> {
+    fn remove(&mut self, key: &CredentialKey) -> Option<Entry<V>> {
+        let entry = self.entries.remove(key)?;
+        self.order.remove(&entry.tick);
+        if let Some(keys) = self.by_email.get_mut(&entry.email) {
+            keys.remove(key);
+            if keys.is_empty() {
+                self.by_email.remove(&entry.email);
+            }
+        }
+        Some(entry)
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+}
+
+/// Bounded LRU cache with TTLs for credential checks, keyed by a salted hash of
+/// `(email, secret)` and indexed by email so invalidating a user is O(entries of that user).
+pub struct CredentialCache<V> {
+    salt: [u8; 32],
+    config: CredentialCacheConfig,
+    inner: Mutex<Inner<V>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+}
+
+impl<V: Clone> Default for CredentialCache<V> {
+    fn default() -> Self {
+        Self::new(CredentialCacheConfig::default())
+    }
+}
+
+impl<V: Clone> CredentialCache<V> {
+    pub fn new(config: CredentialCacheConfig) -> Self {
+        let mut salt = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut salt);
+        Self {
+            salt,
+            config,
+            inner: Mutex::new(Inner {
+                entries: HashMap::new(),
+                order: BTreeMap::new(),
+                by_email: HashMap::new(),
+                invalidated_at: HashMap::new(),
+                invalidation_order: BTreeMap::new(),
+                forgotten_before: 0,
+                tick: 0,
+            }),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+        }
+    }
+
+    fn key(&self, email: &str, secret: &[u8]) -> CredentialKey {
+        let mut input = Vec::with_capacity(email.len() + secret.len() + 1);
+        input.extend_from_slice(email.as_bytes());
+        input.push(0);
+        input.extend_from_slice(secret);
+        hmac_sha512::HMAC::mac(input, self.salt)
+    }
+
+    /// `Ok` with the cached value, or `Err` with the ticket to store the freshly computed one.
+    pub fn get(&self, email: &str, secret: &[u8]) -> Result<V, CredentialTicket> {
+        let key = self.key(email, secret);
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let expired = match inner.entries.get(&key) {
+            Some(entry) => entry.expires_at <= now,
+            None => true,
+        };
+        if expired {
+            inner.remove(&key);
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            return Err(CredentialTicket {
+                key,
+                email: email.to_string(),
+                issued_at: inner.next_tick(),
+            });
+        }
+        let tick = inner.next_tick();
+        let entry = inner.entries.get_mut(&key).expect("entry checked above");
+        let old_tick = std::mem::replace(&mut entry.tick, tick);
+        let value = entry.value.clone();
+        inner.order.remove(&old_tick);
+        inner.order.insert(tick, key);
+        self.hits.fetch_add(1, Ordering::Relaxed);
+        Ok(value)
+    }
+
+    /// Stores `value` unless the email was invalidated since the ticket was issued.
+    pub fn insert(&self, ticket: CredentialTicket, value: V, negative: bool) {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let invalidated = ticket.issued_at < inner.forgotten_before
+            || inner
+                .invalidated_at
+                .get(&ticket.email)
+                .is_some_and(|at| *at > ticket.issued_at);
+        if invalidated {
+            return;
+        }
+        inner.remove(&ticket.key);
+        while inner.entries.len() >= self.config.capacity.max(1) {
+            let Some((_, oldest)) = inner.order.pop_first() else {
+                break;
+            };
+            inner.remove(&oldest);
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+        }
+        let ttl = if negative {
+            self.config.negative_ttl
+        } else {
+            self.config.ttl
+        };
+        let tick = inner.next_tick();
+        inner.order.insert(tick, ticket.key);
+        inner
+            .by_email
+            .entry(ticket.email.clone())
+            .or_default()
+            .insert(ticket.key);
+        inner.entries.insert(
+            ticket.key,
+            Entry {
+                value,
+                email: ticket.email,
+                expires_at: Instant::now() + ttl,
+                tick,
+            },
+        );
+    }
+
+    /// Drops every entry of `email` and rejects inserts from lookups that started before this call.
+    /// Only the latest `capacity` invalidations are remembered, once one is dropped every ticket
+    /// issued before it is rejected.
+    pub fn invalidate_email(&self, email: &str) -> usize {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let tick = inner.next_tick();
+        if let Some(previous) = inner.invalidated_at.insert(email.to_string(), tick) {
+            inner.invalidation_order.remove(&previous);
+        }
+        inner.invalidation_order.insert(tick, email.to_string());
+        while inner.invalidated_at.len() > self.config.capacity.max(1) {
+            let Some((at, oldest)) = inner.invalidation_order.pop_first() else {
+                break;
+            };
+            inner.invalidated_at.remove(&oldest);
+            inner.forgotten_before = at + 1;
+        }
+        let keys = inner.by_email.remove(email).unwrap_or_default();
+        keys.iter()
+            .filter(|key| inner.remove(key).is_some())
+            .count()
+    }
+
+    pub fn stats(&self) -> CredentialCacheStats {
+        let entries = self
+            .inner
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entries
+            .len();
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        let total = hits + misses;
+        CredentialCacheStats {
+            entries,
+            hits,
+            misses,
+            evictions: self.evictions.load(Ordering::Relaxed),
+            hit_ratio: if total == 0 {
+                0.0
+            } else {
+                hits as f64 / total as f64
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache(capacity: usize) -> CredentialCache<u32> {
+        CredentialCache::new(CredentialCacheConfig {
+            capacity,
+            ttl: Duration::from_secs(60),
+            negative_ttl: Duration::ZERO,
+        })
+    }
+
+    #[test]
+    fn test_hit_miss_and_negative_ttl() {
+        let cache = cache(10);
+        let ticket = cache.get("a@b.c", b"secret").unwrap_err();
+        cache.insert(ticket, 1, false);
+        assert_eq!(cache.get("a@b.c", b"secret").ok(), Some(1));
+        assert!(cache.get("a@b.c", b"other").is_err());
+
+        let ticket = cache.get("a@b.c", b"wrong").unwrap_err();
+        cache.insert(ticket, 2, true);
+        assert!(cache.get("a@b.c", b"wrong").is_err());
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses), (1, 4));
+        assert_eq!(stats.hit_ratio, 0.2);
+    }
+
+    #[test]
+    fn test_invalidate_email_and_stale_ticket() {
+        let cache = cache(10);
+        for secret in [b"one", b"two"] {
+            let ticket = cache.get("a@b.c", secret).unwrap_err();
+            cache.insert(ticket, 1, false);
+        }
+        let ticket = cache.get("x@y.z", b"one").unwrap_err();
+        cache.insert(ticket, 3, false);
+
+        let stale = cache.get("a@b.c", b"three").unwrap_err();
+        assert_eq!(cache.invalidate_email("a@b.c"), 2);
+        cache.insert(stale, 1, false);
+        assert!(cache.get("a@b.c", b"three").is_err());
+        assert!(cache.get("a@b.c", b"one").is_err());
+        assert_eq!(cache.get("x@y.z", b"one").ok(), Some(3));
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let cache = cache(2);
+        for email in ["a", "b"] {
+            let ticket = cache.get(email, b"s").unwrap_err();
+            cache.insert(ticket, 1, false);
+        }
+        assert!(cache.get("a", b"s").is_ok());
+        let ticket = cache.get("c", b"s").unwrap_err();
+        cache.insert(ticket, 1, false);
+        assert!(cache.get("a", b"s").is_ok());
+        assert!(cache.get("b", b"s").is_err());
+        assert_eq!(cache.stats().evictions, 1);
+    }
+
+    #[test]
+    fn test_invalidations_are_bounded() {
+        let cache = cache(2);
+        let stale = cache.get("a", b"s").unwrap_err();
+        for email in ["a", "b", "c", "d"] {
+            cache.invalidate_email(email);
+        }
+        assert_eq!(cache.inner.lock().unwrap().invalidated_at.len(), 2);
+        cache.insert(stale, 1, false);
+        assert!(cache.get("a", b"s").is_err());
+
+        let ticket = cache.get("a", b"s").unwrap_err();
+        cache.insert(ticket, 1, false);
+        assert_eq!(cache.get("a", b"s").ok(), Some(1));
+    }
+}
@@ -0,0 +1,46 @@
+use crate::email::smtp_transport::build_message;
+use crate::email::transport::{EmailTransport, OutgoingEmail, Sender};
+use async_trait::async_trait;
+use chrono::Utc;
+use std::path::PathBuf;
+
+/// Writes every email as an `.eml` file into a directory instead of sending it.
+pub struct FileTransport {
+    dir: PathBuf,
+    sender: Sender,
+}
+
+impl FileTransport {
+    pub fn new(dir: impl Into<PathBuf>, sender: Sender) -> Self {
+        Self {
+            dir: dir.into(),
+            sender,
+        }
+    }
+}
+
+#[async_trait]
+impl EmailTransport for FileTransport {
+    fn name(&self) -> &'static str {
+        "file"
+    }
+
+    #[tracing::instrument(name = "FileTransport::send", skip_all, err)]
+    async fn send(&self, email: &OutgoingEmail) -> anyhow::Result<()> {
+        let message = build_message(&self.sender, email)?;
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let recipient: String = email
+            .to
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        let path = self.dir.join(format!(
+            "{}-{}.eml",
+            Utc::now().format("%Y%m%dT%H%M%S%.6f"),
+            recipient
+        ));
+        tokio::fs::write(&path, message.formatted()).await?;
+        tracing::info!("Email written to {}", path.display());
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Every transactional email we send, stored as text in the `email_outbox` table.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmailKind {
+    ConfirmEmail,
+    ResetPassword,
+    ChangeEmail,
+    NodeOffline,
+    NewReferral,
+    PerkEarned,
+    DailySummary,
+}
+
+impl Display for EmailKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EmailKind::ConfirmEmail => write!(f, "confirm_email"),
+            EmailKind::ResetPassword => write!(f, "reset_password"),
+            EmailKind::ChangeEmail => write!(f, "change_email"),
+            EmailKind::NodeOffline => write!(f, "node_offline"),
+            EmailKind::NewReferral => write!(f, "new_referral"),
+            EmailKind::PerkEarned => write!(f, "perk_earned"),
+            EmailKind::DailySummary => write!(f, "daily_summary"),
+        }
+    }
+}
+
+impl FromStr for EmailKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "confirm_email" => Ok(EmailKind::ConfirmEmail),
+            "reset_password" => Ok(EmailKind::ResetPassword),
+            "change_email" => Ok(EmailKind::ChangeEmail),
+            "node_offline" => Ok(EmailKind::NodeOffline),
+            "new_referral" => Ok(EmailKind::NewReferral),
+            "perk_earned" => Ok(EmailKind::PerkEarned),
+            "daily_summary" => Ok(EmailKind::DailySummary),
+            _ => Err(anyhow!("Unknown email kind {}", s)),
+        }
+    }
+}
@@ -0,0 +1,55 @@
+pub const DEFAULT_LOCALE: &str = "en";
+pub const SUPPORTED_LOCALES: [&str; 2] = ["en", "es"];
+
+fn find_locale(tag: &str) -> Option<&'static str> {
+    let primary = tag
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    SUPPORTED_LOCALES.iter().find(|l| **l == primary).copied()
+}
+
+/// Maps a stored or requested locale such as `es-AR` to one we have templates for.
+pub fn supported_locale(locale: &str) -> &'static str {
+    find_locale(locale).unwrap_or(DEFAULT_LOCALE)
+}
+
+/// Picks the supported locale with the highest weight from an `Accept-Language` header.
+pub fn negotiate_locale(accept_language: &str) -> &'static str {
+    let mut best: Option<(&'static str, f32)> = None;
+    for part in accept_language.split(',') {
+        let mut pieces = part.split(';');
+        let tag = pieces.next().unwrap_or_default();
+        let weight = pieces
+            .find_map(|p| p.trim().strip_prefix("q="))
+            .and_then(|q| q.parse::<f32>().ok())
+            .unwrap_or(1.0);
+        if weight <= 0.0 {
+            continue;
+        }
+        let Some(locale) = find_locale(tag) else {
+            continue;
+        };
+        if best.map_or(true, |(_, w)| weight > w) {
+            best = Some((locale, weight));
+        }
+    }
+    best.map(|(locale, _)| locale).unwrap_or(DEFAULT_LOCALE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn negotiates_by_weight_and_falls_back() {
+        assert_eq!(negotiate_locale("es-ES,es;q=0.9,en;q=0.8"), "es");
+        assert_eq!(negotiate_locale("fr-FR,en;q=0.5,es;q=0.7"), "es");
+        assert_eq!(negotiate_locale("de,fr"), DEFAULT_LOCALE);
+        assert_eq!(negotiate_locale("es;q=0,en"), "en");
+        assert_eq!(negotiate_locale(""), DEFAULT_LOCALE);
+        assert_eq!(supported_locale("ES_mx"), "es");
+    }
+}
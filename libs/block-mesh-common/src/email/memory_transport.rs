@@ -0,0 +1,30 @@
+use crate::email::transport::{EmailTransport, OutgoingEmail};
+use async_trait::async_trait;
+use std::sync::{Arc, Mutex};
+
+/// Keeps sent emails in memory, for tests.
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryTransport {
+    sent: Arc<Mutex<Vec<OutgoingEmail>>>,
+}
+
+impl InMemoryTransport {
+    pub fn sent(&self) -> Vec<OutgoingEmail> {
+        self.sent.lock().map(|s| s.clone()).unwrap_or_default()
+    }
+}
+
+#[async_trait]
+impl EmailTransport for InMemoryTransport {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    async fn send(&self, email: &OutgoingEmail) -> anyhow::Result<()> {
+        self.sent
+            .lock()
+            .map_err(|_| anyhow::anyhow!("InMemoryTransport lock poisoned"))?
+            .push(email.clone());
+        Ok(())
+    }
+}
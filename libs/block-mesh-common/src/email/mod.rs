@@ -0,0 +1,15 @@
+pub mod kind;
+pub mod locale;
+
+#[cfg(feature = "email")]
+pub mod file_transport;
+#[cfg(feature = "email")]
+pub mod memory_transport;
+#[cfg(feature = "email")]
+pub mod ses_transport;
+#[cfg(feature = "email")]
+pub mod smtp_transport;
+#[cfg(feature = "email")]
+pub mod template;
+#[cfg(feature = "email")]
+pub mod transport;
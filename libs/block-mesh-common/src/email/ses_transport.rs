@@ -0,0 +1,61 @@
+use crate::email::transport::{EmailTransport, OutgoingEmail, Sender};
+use async_trait::async_trait;
+use aws_config::meta::region::RegionProviderChain;
+use aws_sdk_sesv2::config::Region;
+use aws_sdk_sesv2::types::{Body, Content, Destination, EmailContent, Message};
+
+pub struct SesTransport {
+    sender: Sender,
+    client: aws_sdk_sesv2::Client,
+}
+
+impl SesTransport {
+    pub async fn new(sender: Sender) -> Self {
+        let region_provider = RegionProviderChain::first_try(Region::from_static("eu-north-1"))
+            .or_default_provider()
+            .or_else(Region::new("us-west-2"));
+        let shared_config = aws_config::from_env().region(region_provider).load().await;
+        Self {
+            sender,
+            client: aws_sdk_sesv2::Client::new(&shared_config),
+        }
+    }
+}
+
+#[async_trait]
+impl EmailTransport for SesTransport {
+    fn name(&self) -> &'static str {
+        "ses"
+    }
+
+    #[tracing::instrument(name = "SesTransport::send", skip_all, err)]
+    async fn send(&self, email: &OutgoingEmail) -> anyhow::Result<()> {
+        let mut dest: Destination = Destination::builder().build();
+        dest.to_addresses = Some(vec![email.to.clone()]);
+        let subject_content = Content::builder()
+            .data(&email.subject)
+            .charset("UTF-8")
+            .build()?;
+        let body_content = Content::builder()
+            .data(&email.html)
+            .charset("UTF-8")
+            .build()?;
+        let body = Body::builder().html(body_content).build();
+        let msg = Message::builder()
+            .subject(subject_content)
+            .body(body)
+            .build();
+        let email_content = EmailContent::builder().simple(msg).build();
+        let result = self
+            .client
+            .send_email()
+            .from_email_address(&self.sender.from)
+            .reply_to_addresses(&self.sender.reply_to)
+            .destination(dest)
+            .content(email_content)
+            .send()
+            .await?;
+        tracing::info!("Email sent: {:?}", result.message_id);
+        Ok(())
+    }
+}
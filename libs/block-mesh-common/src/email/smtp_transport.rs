@@ -0,0 +1,70 @@
+use crate::email::transport::{EmailTransport, OutgoingEmail, Sender};
+use crate::env::app_env_var::AppEnvVar;
+use async_trait::async_trait;
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+use std::env;
+
+/// SMTP relay, Gmail by default. Point `SMTP_HOST` at a local catcher such as
+/// Mailpit with `SMTP_TLS=false` and `SMTP_PORT=1025` for development.
+pub struct SmtpEmailTransport {
+    sender: Sender,
+    mailer: SmtpTransport,
+}
+
+impl SmtpEmailTransport {
+    pub fn from_env(sender: Sender) -> anyhow::Result<Self> {
+        let host = env::var("SMTP_HOST").unwrap_or("smtp.gmail.com".to_string());
+        let tls = env::var("SMTP_TLS")
+            .unwrap_or("true".to_string())
+            .parse()
+            .unwrap_or(true);
+        let mut builder = if tls {
+            SmtpTransport::relay(&host)?
+        } else {
+            SmtpTransport::builder_dangerous(&host)
+        };
+        if let Some(port) = env::var("SMTP_PORT").ok().and_then(|p| p.parse().ok()) {
+            builder = builder.port(port);
+        }
+        let password = env::var("SMTP_PASSWORD")
+            .or_else(|_| env::var(&*AppEnvVar::GmailAppPassword))
+            .unwrap_or_default();
+        if !password.is_empty() {
+            let username = env::var("SMTP_USERNAME").unwrap_or(sender.from.clone());
+            builder = builder.credentials(Credentials::new(username, password));
+        }
+        Ok(Self {
+            sender,
+            mailer: builder.build(),
+        })
+    }
+}
+
+pub fn build_message(sender: &Sender, email: &OutgoingEmail) -> anyhow::Result<Message> {
+    Ok(Message::builder()
+        .from(sender.from.parse()?)
+        .to(email.to.parse()?)
+        .subject(&email.subject)
+        .reply_to(sender.reply_to.parse()?)
+        .header(ContentType::TEXT_HTML)
+        .body(email.html.clone())?)
+}
+
+#[async_trait]
+impl EmailTransport for SmtpEmailTransport {
+    fn name(&self) -> &'static str {
+        "smtp"
+    }
+
+    #[tracing::instrument(name = "SmtpEmailTransport::send", skip_all, err)]
+    async fn send(&self, email: &OutgoingEmail) -> anyhow::Result<()> {
+        let message = build_message(&self.sender, email)?;
+        let mailer = self.mailer.clone();
+        // lettre's transport is blocking, keep it off the runtime threads
+        let result = tokio::task::spawn_blocking(move || mailer.send(&message)).await??;
+        tracing::info!("Email sent: {:?}", result.code());
+        Ok(())
+    }
+}
@@ -0,0 +1,203 @@
+use crate::email::kind::EmailKind;
+use crate::email::locale::supported_locale;
+use anyhow::anyhow;
+use askama::Template;
+use serde::Deserialize;
+use serde_json::Value;
+
+const EN: &str = include_str!("../../templates/emails/locales/en.json");
+const ES: &str = include_str!("../../templates/emails/locales/es.json");
+const DEFAULT_UNSUBSCRIBE_URL: &str = "https://blockmesh.xyz/unsubscribe";
+
+#[derive(Deserialize)]
+struct CommonStrings {
+    greeting: String,
+    support: String,
+    unsubscribe: String,
+}
+
+#[derive(Deserialize)]
+struct EmailStrings {
+    subject: String,
+    title: String,
+    lines: Vec<String>,
+    button: String,
+}
+
+/// Translations for one locale, see `templates/emails/locales`.
+#[derive(Deserialize)]
+struct Catalog {
+    common: CommonStrings,
+    confirm_email: EmailStrings,
+    reset_password: EmailStrings,
+    change_email: EmailStrings,
+    node_offline: EmailStrings,
+    new_referral: EmailStrings,
+    perk_earned: EmailStrings,
+    daily_summary: EmailStrings,
+}
+
+impl Catalog {
+    fn load(locale: &str) -> anyhow::Result<Self> {
+        let raw = match locale {
+            "es" => ES,
+            _ => EN,
+        };
+        serde_json::from_str(raw).map_err(|e| anyhow!("Invalid {} email catalog: {}", locale, e))
+    }
+
+    fn strings(&self, kind: EmailKind) -> &EmailStrings {
+        match kind {
+            EmailKind::ConfirmEmail => &self.confirm_email,
+            EmailKind::ResetPassword => &self.reset_password,
+            EmailKind::ChangeEmail => &self.change_email,
+            EmailKind::NodeOffline => &self.node_offline,
+            EmailKind::NewReferral => &self.new_referral,
+            EmailKind::PerkEarned => &self.perk_earned,
+            EmailKind::DailySummary => &self.daily_summary,
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "emails/action_email.html")]
+struct ActionEmailTemplate<'a> {
+    lang: &'a str,
+    title: &'a str,
+    greeting: &'a str,
+    lines: &'a [String],
+    action_url: &'a str,
+    button: &'a str,
+    support: &'a str,
+    unsubscribe: &'a str,
+    unsubscribe_url: &'a str,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedEmail {
+    pub subject: String,
+    pub html: String,
+}
+
+/// Replaces `{name}` placeholders with the matching string or number from `params`.
+fn fill(text: &str, params: &Value) -> String {
+    let mut text = text.to_string();
+    if let Some(params) = params.as_object() {
+        for (key, value) in params {
+            let value = match value {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            text = text.replace(&format!("{{{}}}", key), &value);
+        }
+    }
+    text
+}
+
+/// Renders `kind` in `locale`, unknown locales fall back to English.
+/// `params` fills the placeholders of the catalog strings, an `unsubscribe_url`
+/// entry replaces the generic unsubscribe link.
+pub fn render_email(
+    kind: EmailKind,
+    locale: &str,
+    action_url: &str,
+    params: &Value,
+) -> anyhow::Result<RenderedEmail> {
+    let locale = supported_locale(locale);
+    let catalog = Catalog::load(locale)?;
+    let strings = catalog.strings(kind);
+    let lines: Vec<String> = strings.lines.iter().map(|l| fill(l, params)).collect();
+    let html = ActionEmailTemplate {
+        lang: locale,
+        title: &strings.title,
+        greeting: &catalog.common.greeting,
+        lines: &lines,
+        action_url,
+        button: &strings.button,
+        support: &catalog.common.support,
+        unsubscribe: &catalog.common.unsubscribe,
+        unsubscribe_url: params
+            .get("unsubscribe_url")
+            .and_then(Value::as_str)
+            .unwrap_or(DEFAULT_UNSUBSCRIBE_URL),
+    }
+    .render()?;
+    Ok(RenderedEmail {
+        subject: fill(&strings.subject, params),
+        html,
+    })
+}
+
+/// Plain text version of the same strings, for chat channels.
+pub fn render_text(
+    kind: EmailKind,
+    locale: &str,
+    action_url: &str,
+    params: &Value,
+) -> anyhow::Result<String> {
+    let catalog = Catalog::load(supported_locale(locale))?;
+    let strings = catalog.strings(kind);
+    let mut text = fill(&strings.subject, params);
+    for line in &strings.lines {
+        text.push('\n');
+        text.push_str(&fill(line, params));
+    }
+    text.push('\n');
+    text.push_str(action_url);
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::email::locale::SUPPORTED_LOCALES;
+
+    #[test]
+    fn renders_every_kind_in_every_locale() {
+        for locale in SUPPORTED_LOCALES {
+            for kind in [
+                EmailKind::ConfirmEmail,
+                EmailKind::ResetPassword,
+                EmailKind::ChangeEmail,
+            ] {
+                let email = render_email(
+                    kind,
+                    locale,
+                    "https://app.blockmesh.xyz/email_confirm?token=abc",
+                )
+                .unwrap();
+                assert!(!email.subject.is_empty());
+                assert!(email.html.contains("email_confirm?token=abc"));
+                assert!(email.html.contains(&format!("lang=\"{}\"", locale)));
+            }
+        }
+        let fallback = render_email(EmailKind::ResetPassword, "de-DE", "x", &Value::Null).unwrap();
+        assert_eq!(
+            fallback.subject,
+            render_email(EmailKind::ResetPassword, "en", "x", &Value::Null)
+                .unwrap()
+                .subject
+        );
+    }
+
+    #[test]
+    fn fills_placeholders_and_unsubscribe_link() {
+        let params = serde_json::json!({
+            "day": "2024-11-20",
+            "points": "12.50",
+            "unsubscribe_url": "https://app.blockmesh.xyz/unsubscribe?token=t",
+        });
+        let email = render_email(EmailKind::DailySummary, "en", "x", &params).unwrap();
+        assert_eq!(email.subject, "Your BlockMesh summary for 2024-11-20");
+        assert!(email.html.contains("you earned 12.50 points"));
+        assert!(email.html.contains("unsubscribe?token=t"));
+        let text = render_text(
+            EmailKind::NodeOffline,
+            "en",
+            "x",
+            &serde_json::json!({"minutes": 30}),
+        )
+        .unwrap();
+        assert!(text.contains("more than 30 minutes"));
+    }
+}
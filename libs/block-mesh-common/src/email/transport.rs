@@ -0,0 +1,60 @@
+use crate::email::file_transport::FileTransport;
+use crate::email::ses_transport::SesTransport;
+use crate::email::smtp_transport::SmtpEmailTransport;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use std::env;
+use std::sync::Arc;
+
+pub const DEFAULT_FROM: &str = "support@blockmesh.xyz";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutgoingEmail {
+    pub to: String,
+    pub subject: String,
+    pub html: String,
+}
+
+/// Address the emails are sent from, `EMAIL_FROM` and `EMAIL_REPLY_TO`.
+#[derive(Debug, Clone)]
+pub struct Sender {
+    pub from: String,
+    pub reply_to: String,
+}
+
+impl Sender {
+    pub fn from_env() -> Self {
+        let from = env::var("EMAIL_FROM").unwrap_or(DEFAULT_FROM.to_string());
+        let reply_to = env::var("EMAIL_REPLY_TO").unwrap_or(from.clone());
+        Self { from, reply_to }
+    }
+}
+
+#[async_trait]
+pub trait EmailTransport: Send + Sync {
+    fn name(&self) -> &'static str;
+    async fn send(&self, email: &OutgoingEmail) -> anyhow::Result<()>;
+}
+
+/// Picks the transport from `EMAIL_TRANSPORT` (`ses`, `smtp` or `file`).
+/// When unset the legacy `EMAIL_MODE=AWS` selects SES and anything else SMTP,
+/// so existing deployments keep their behavior.
+pub async fn transport_from_env() -> anyhow::Result<Arc<dyn EmailTransport>> {
+    let transport = env::var("EMAIL_TRANSPORT").unwrap_or_else(|_| {
+        if env::var("EMAIL_MODE").unwrap_or_default() == "AWS" {
+            "ses".to_string()
+        } else {
+            "smtp".to_string()
+        }
+    });
+    let sender = Sender::from_env();
+    match transport.to_ascii_lowercase().as_str() {
+        "ses" => Ok(Arc::new(SesTransport::new(sender).await)),
+        "smtp" => Ok(Arc::new(SmtpEmailTransport::from_env(sender)?)),
+        "file" => Ok(Arc::new(FileTransport::new(
+            env::var("EMAIL_FILE_DIR").unwrap_or("emails".to_string()),
+            sender,
+        ))),
+        other => Err(anyhow!("Unknown EMAIL_TRANSPORT {}", other)),
+    }
+}
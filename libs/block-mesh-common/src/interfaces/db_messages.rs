@@ -31,6 +31,8 @@ pub struct AnalyticsMessage {
     pub depin_aggregator: String,
     pub device_type: DeviceType,
     pub version: String,
+    #[serde(default)]
+    pub device_id: Option<Uuid>,
     pub msg_type: DBMessageTypes,
 }
 
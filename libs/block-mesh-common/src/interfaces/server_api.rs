@@ -1,11 +1,10 @@
 use crate::constants::DeviceType;
 use crate::interfaces::ws_api::WsServerMessage;
+use crate::tg_bot::TgBotTier;
 use chrono::{DateTime, NaiveDate, Utc};
-use dashmap::DashMap;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::cmp::Ordering;
-use std::sync::Arc;
 use std::time::Duration;
 use typeshare::typeshare;
 use uuid::Uuid;
@@ -23,6 +22,50 @@ pub struct GetTaskResponse {
     pub body: Option<Value>,
 }
 
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CreateTaskRequest {
+    pub url: String,
+    /// `GET` or `POST`, what nodes know how to run
+    pub method: String,
+    #[typeshare(serialized_as = "object")]
+    pub headers: Option<Value>,
+    #[typeshare(serialized_as = "object")]
+    pub body: Option<Value>,
+    #[typeshare(serialized_as = "string")]
+    pub api_token: Uuid,
+    pub email: String,
+}
+
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CreateTaskResponse {
+    #[typeshare(serialized_as = "string")]
+    pub task_id: Uuid,
+}
+
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GetTaskResultRequest {
+    #[typeshare(serialized_as = "string")]
+    pub task_id: Uuid,
+    #[typeshare(serialized_as = "string")]
+    pub api_token: Uuid,
+    pub email: String,
+}
+
+/// `response_code` and `response_raw` are set once a node submitted the task,
+/// a node that could not run it reports 520.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GetTaskResultResponse {
+    #[typeshare(serialized_as = "string")]
+    pub task_id: Uuid,
+    pub status: String,
+    pub response_code: Option<i32>,
+    pub response_raw: Option<String>,
+}
+
 #[typeshare]
 #[derive(Serialize, Deserialize, Debug)]
 pub struct GetTaskRequest {
@@ -34,9 +77,13 @@ pub struct GetTaskRequest {
 #[typeshare]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SubmitTaskRequest {
-    pub email: String,
+    /// Deprecated, send `Authorization: Bearer` instead
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    /// Deprecated, send `Authorization: Bearer` instead
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     #[typeshare(serialized_as = "string")]
-    pub api_token: Uuid,
+    pub api_token: Option<Uuid>,
     #[typeshare(serialized_as = "string")]
     pub task_id: Uuid,
     pub response_code: Option<i32>,
@@ -61,14 +108,21 @@ pub struct ClientsMetadata {
     #[typeshare(serialized_as = "string")]
     pub device_type: DeviceType,
     pub version: Option<String>,
+    #[serde(default)]
+    #[typeshare(serialized_as = "Option<String>")]
+    pub device_id: Option<Uuid>,
 }
 
 #[typeshare]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ReportUptimeRequest {
-    pub email: String,
+    /// Deprecated, send `Authorization: Bearer` instead
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    /// Deprecated, send `Authorization: Bearer` instead
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     #[typeshare(serialized_as = "string")]
-    pub api_token: Uuid,
+    pub api_token: Option<Uuid>,
     pub ip: Option<String>,
 }
 
@@ -95,8 +149,16 @@ pub struct SubmitTaskResponse {
 #[typeshare]
 #[derive(Serialize, Deserialize, Debug)]
 pub struct GetTokenRequest {
+    #[serde(default)]
     pub email: String,
+    #[serde(default)]
     pub password: String,
+    /// Name of the device token to return, created on first login. Defaults to `default`.
+    #[serde(default)]
+    pub token_name: Option<String>,
+    /// Wallet sign in, used instead of email and password when present.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub siws: Option<SiwsSignIn>,
 }
 
 #[typeshare]
@@ -125,6 +187,13 @@ pub struct GetTokenResponse {
     #[typeshare(serialized_as = "string")]
     pub api_token: Option<Uuid>,
     pub message: Option<String>,
+    /// Short lived alternative to the api token for `Authorization: Bearer`,
+    /// absent when session tickets are disabled on the server
+    #[serde(default)]
+    pub session_ticket: Option<String>,
+    #[serde(default)]
+    #[typeshare(serialized_as = "Date")]
+    pub session_ticket_expires_at: Option<DateTime<Utc>>,
 }
 
 #[typeshare]
@@ -274,9 +343,13 @@ pub struct DashboardResponse {
     pub perks: Vec<PerkUI>,
     pub calls_to_action: Vec<CallToActionUI>,
     pub referrals: Vec<Referral>,
+    #[serde(default)]
+    pub referral_points: f64,
     pub verified_email: bool,
     pub user_ips: Vec<UserIpInfo>,
     pub wallet_address: Option<String>,
+    #[serde(default)]
+    pub api_tokens: Vec<ApiTokenInfo>,
 }
 
 #[typeshare]
@@ -339,6 +412,172 @@ pub struct ConnectWalletResponse {
     pub status: i32,
 }
 
+/// Asks for a single use message to sign for linking `pubkey` to the logged in account.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WalletChallengeRequest {
+    pub pubkey: String,
+}
+
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WalletChallengeResponse {
+    pub message: String,
+    #[typeshare(serialized_as = "Date")]
+    pub expires_at: DateTime<Utc>,
+}
+
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SiwsNonceResponse {
+    pub nonce: String,
+    #[typeshare(serialized_as = "Date")]
+    pub expires_at: DateTime<Utc>,
+}
+
+/// A signed Sign In With Solana message, see `block_mesh_common::siws::SiwsMessage`.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SiwsSignIn {
+    pub pubkey: String,
+    pub message: String,
+    pub signature: Vec<u8>,
+}
+
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SiwsLoginRequest {
+    pub pubkey: String,
+    pub message: String,
+    pub signature: Vec<u8>,
+    /// Required when the wallet has no account yet.
+    #[serde(default)]
+    pub invite_code: Option<String>,
+}
+
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SiwsLoginResponse {
+    pub created: bool,
+    pub two_factor_required: bool,
+}
+
+/// Attaches an email and password to an account created by wallet sign in.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LinkEmailRequest {
+    pub email: String,
+    pub password: String,
+}
+
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ChangeEmailRequest {
+    pub new_email: String,
+    pub password: String,
+}
+
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DeleteAccountRequest {
+    /// Not required for accounts created by wallet sign in that never linked an email.
+    #[serde(default)]
+    pub password: String,
+    /// Must be [`DELETE_ACCOUNT_CONFIRMATION`].
+    pub confirmation: String,
+}
+
+pub const DELETE_ACCOUNT_CONFIRMATION: &str = "DELETE";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct AccountExportQuery {
+    /// `json` (default) or `csv`.
+    #[serde(default)]
+    pub format: Option<String>,
+    /// Required for `csv`: `daily_stats`, `referrals`, `perks` or `ip_history`.
+    #[serde(default)]
+    pub dataset: Option<String>,
+}
+
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AccountExport {
+    pub email: String,
+    #[typeshare(serialized_as = "Date")]
+    pub created_at: DateTime<Utc>,
+    pub wallet_address: Option<String>,
+    pub daily_stats: Vec<DailyStatForDashboard>,
+    pub referrals: Vec<Referral>,
+    pub perks: Vec<PerkUI>,
+    pub ip_history: Vec<UserIpInfo>,
+}
+
+/// Which notifications a user receives and through which channels.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct NotificationPreferencesResponse {
+    pub email_enabled: bool,
+    pub telegram_linked: bool,
+    pub node_offline: bool,
+    pub new_referral: bool,
+    pub perk_earned: bool,
+    pub daily_summary: bool,
+    pub offline_minutes: i32,
+}
+
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UpdateNotificationPreferencesRequest {
+    pub email_enabled: bool,
+    pub node_offline: bool,
+    pub new_referral: bool,
+    pub perk_earned: bool,
+    pub daily_summary: bool,
+    pub offline_minutes: i32,
+}
+
+/// Deep link that opens the notifications bot and links the chat to the account.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TelegramLinkResponse {
+    pub url: String,
+}
+
+/// One-time code the user sends to the Telegram privacy bot with `/link <code>`.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TgBotLinkCodeResponse {
+    pub code: String,
+    #[typeshare(serialized_as = "Date")]
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TgBotLinkRequest {
+    pub code: String,
+    pub tg_id: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TgBotAccountRequest {
+    pub tg_id: i64,
+}
+
+/// What the Telegram privacy bot knows about a linked account, newest day first.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TgBotAccountResponse {
+    pub tier: TgBotTier,
+    pub daily_limit: i64,
+    pub points: f64,
+    pub daily_stats: Vec<DailyStatForDashboard>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UnsubscribeQuery {
+    pub token: Option<String>,
+    pub kind: Option<String>,
+}
+
 #[typeshare]
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct Referral {
@@ -346,6 +585,99 @@ pub struct Referral {
     #[typeshare(serialized_as = "Date")]
     pub created_at: DateTime<Utc>,
     pub verified_email: bool,
+    #[serde(default)]
+    pub points_earned: f64,
+}
+
+#[typeshare]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ApiTokenInfo {
+    #[typeshare(serialized_as = "string")]
+    pub id: Uuid,
+    pub name: String,
+    pub scopes: Vec<String>,
+    #[typeshare(serialized_as = "Date")]
+    pub created_at: DateTime<Utc>,
+    #[typeshare(serialized_as = "Option<Date>")]
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub last_used_ip: Option<String>,
+    #[typeshare(serialized_as = "Option<Date>")]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CreateApiTokenRequest {
+    pub name: String,
+    pub scopes: Vec<String>,
+    #[typeshare(serialized_as = "Option<number>")]
+    pub expires_in_days: Option<i64>,
+}
+
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ApiTokenIdRequest {
+    #[typeshare(serialized_as = "string")]
+    pub id: Uuid,
+}
+
+/// The token value is only returned when it is created or rotated.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ApiTokenSecretResponse {
+    #[typeshare(serialized_as = "string")]
+    pub id: Uuid,
+    #[typeshare(serialized_as = "string")]
+    pub api_token: Uuid,
+}
+
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct TwoFactorStatusResponse {
+    pub enabled: bool,
+    pub recovery_codes_left: i64,
+}
+
+/// Secret of a pending enrollment, shown once so it can be added to an authenticator app.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TwoFactorEnrollResponse {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+/// A TOTP code or an unused recovery code.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TwoFactorCodeRequest {
+    pub code: String,
+}
+
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RecoveryCodesResponse {
+    pub recovery_codes: Vec<String>,
+}
+
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UserSessionInfo {
+    #[typeshare(serialized_as = "string")]
+    pub id: Uuid,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    #[typeshare(serialized_as = "Date")]
+    pub created_at: DateTime<Utc>,
+    #[typeshare(serialized_as = "Date")]
+    pub last_seen_at: DateTime<Utc>,
+    pub current: bool,
+}
+
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UserSessionIdRequest {
+    #[typeshare(serialized_as = "string")]
+    pub id: Uuid,
 }
 
 #[typeshare]
@@ -414,7 +746,13 @@ pub enum HandlerMode {
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub enum GetTokenResponseEnum {
-    GetTokenResponse(GetTokenResponse),
+    /// Owner, token and scopes are kept so a fresh session ticket can be minted on every cache hit
+    GetTokenResponse {
+        response: GetTokenResponse,
+        user_id: Uuid,
+        token_id: Uuid,
+        scopes: Vec<String>,
+    },
     UserNotFound,
     PasswordMismatch,
     ApiTokenNotFound,
@@ -422,14 +760,24 @@ pub enum GetTokenResponseEnum {
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub enum CheckTokenResponseEnum {
-    GetTokenResponse(GetTokenResponse),
+    /// Same as [`GetTokenResponseEnum::GetTokenResponse`], the session ticket is minted per hit
+    GetTokenResponse {
+        response: GetTokenResponse,
+        user_id: Uuid,
+        token_id: Uuid,
+        scopes: Vec<String>,
+    },
     UserNotFound,
     ApiTokenMismatch,
     ApiTokenNotFound,
 }
 
-pub type GetTokenResponseMap = Arc<DashMap<(String, String), GetTokenResponseEnum>>;
-pub type CheckTokenResponseMap = Arc<DashMap<(String, Uuid), CheckTokenResponseEnum>>;
+#[cfg(feature = "credential-cache")]
+pub type GetTokenResponseMap =
+    std::sync::Arc<crate::credential_cache::CredentialCache<GetTokenResponseEnum>>;
+#[cfg(feature = "credential-cache")]
+pub type CheckTokenResponseMap =
+    std::sync::Arc<crate::credential_cache::CredentialCache<CheckTokenResponseEnum>>;
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct CronReportSettings {
@@ -492,3 +840,35 @@ impl CronReportAggregateEntry {
         }
     }
 }
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PointsLedgerQuery {
+    pub email: String,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PointsAdjustmentRequest {
+    pub email: String,
+    pub points: f64,
+    pub note: String,
+    pub day: Option<NaiveDate>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PointsReversalRequest {
+    pub id: Uuid,
+    pub note: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SybilFlagsQuery {
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SybilFlagReviewRequest {
+    pub user_id: Uuid,
+    pub status: String,
+}
@@ -3,7 +3,11 @@ pub mod app_config;
 pub mod chrome_storage;
 #[cfg(feature = "cli")]
 pub mod cli;
+pub mod constant_time;
 pub mod constants;
+#[cfg(feature = "credential-cache")]
+pub mod credential_cache;
+pub mod email;
 #[cfg(feature = "env")]
 pub mod env;
 #[cfg(feature = "feature-flag")]
@@ -11,7 +15,13 @@ pub mod feature_flag_client;
 #[cfg(feature = "http")]
 pub mod http;
 pub mod interfaces;
+#[cfg(feature = "rate-limit")]
+pub mod rate_limit;
 #[cfg(feature = "reqwest")]
 pub mod reqwest;
 pub mod routes_enum;
+#[cfg(feature = "session-ticket")]
+pub mod session_ticket;
+pub mod siws;
 pub mod tauri_message_channel;
+pub mod tg_bot;
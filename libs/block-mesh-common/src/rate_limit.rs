@@ -0,0 +1,613 @@
+//! Sliding window rate limiting shared through Redis, so the limits hold across replicas.
+//!
+//! Policies are declared per [`RoutesEnum`] entry (or raw path) and per principal, every
+//! matching policy is counted and the tightest one is reported back with the
+//! `RateLimit-Limit`, `RateLimit-Remaining`, `RateLimit-Reset` and `RateLimit-Policy` headers.
+use crate::routes_enum::RoutesEnum;
+use axum::extract::{ConnectInfo, Request};
+use axum::http::{Extensions, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use futures::future::BoxFuture;
+use redis::aio::MultiplexedConnection;
+use redis::Script;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tower::{Layer, Service};
+use uuid::Uuid;
+
+const KEY_PREFIX: &str = "rate-limit";
+
+/// Counts the request only when the weighted estimate of the sliding window is under the limit,
+/// so rejected requests do not extend a ban.
+const SLIDING_WINDOW_SCRIPT: &str = r#"
+local current = tonumber(redis.call('GET', KEYS[1]) or '0')
+local previous = tonumber(redis.call('GET', KEYS[2]) or '0')
+local estimate = math.floor(previous * tonumber(ARGV[2])) + current
+if estimate >= tonumber(ARGV[3]) then
+    return {0, estimate}
+end
+current = redis.call('INCR', KEYS[1])
+if current == 1 then
+    redis.call('PEXPIRE', KEYS[1], ARGV[1])
+end
+return {1, estimate + 1}
+"#;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PrincipalKind {
+    Ip,
+    User,
+    ApiToken,
+}
+
+impl Display for PrincipalKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PrincipalKind::Ip => write!(f, "ip"),
+            PrincipalKind::User => write!(f, "user"),
+            PrincipalKind::ApiToken => write!(f, "api-token"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Principal {
+    Ip(String),
+    User(Uuid),
+    ApiToken(String),
+}
+
+impl Principal {
+    pub fn kind(&self) -> PrincipalKind {
+        match self {
+            Principal::Ip(_) => PrincipalKind::Ip,
+            Principal::User(_) => PrincipalKind::User,
+            Principal::ApiToken(_) => PrincipalKind::ApiToken,
+        }
+    }
+
+    /// Raw api tokens never end up in Redis keys.
+    fn key_part(&self) -> String {
+        match self {
+            Principal::Ip(ip) => ip.clone(),
+            Principal::User(id) => id.to_string(),
+            Principal::ApiToken(token) => {
+                bs58::encode(&hmac_sha512::Hash::hash(token.as_bytes())[..16]).into_string()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimitPolicy {
+    pub limit: u64,
+    pub window: Duration,
+}
+
+impl RateLimitPolicy {
+    pub fn new(limit: u64, window: Duration) -> Self {
+        Self { limit, window }
+    }
+
+    pub fn per_second(limit: u64) -> Self {
+        Self::new(limit, Duration::from_secs(1))
+    }
+
+    pub fn per_minute(limit: u64) -> Self {
+        Self::new(limit, Duration::from_secs(60))
+    }
+
+    pub fn per_hour(limit: u64) -> Self {
+        Self::new(limit, Duration::from_secs(3600))
+    }
+}
+
+impl Display for RateLimitPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{};w={}", self.limit, self.window.as_secs().max(1))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Rule {
+    principal: PrincipalKind,
+    policy: RateLimitPolicy,
+}
+
+/// Policies per path, requests to paths without policies fall back to the default ones.
+#[derive(Debug, Clone, Default)]
+pub struct RateLimitRules {
+    paths: HashMap<String, Vec<Rule>>,
+    fallback: Vec<Rule>,
+}
+
+impl RateLimitRules {
+    /// Applies to the route both as a page and nested under `/api`.
+    pub fn route(
+        self,
+        route: RoutesEnum,
+        principal: PrincipalKind,
+        policy: RateLimitPolicy,
+    ) -> Self {
+        let path = route.to_string();
+        self.path(&format!("/api{}", path), principal, policy)
+            .path(&path, principal, policy)
+    }
+
+    pub fn path(mut self, path: &str, principal: PrincipalKind, policy: RateLimitPolicy) -> Self {
+        self.paths
+            .entry(path.to_string())
+            .or_default()
+            .push(Rule { principal, policy });
+        self
+    }
+
+    pub fn fallback(mut self, principal: PrincipalKind, policy: RateLimitPolicy) -> Self {
+        self.fallback.push(Rule { principal, policy });
+        self
+    }
+
+    fn rules_for<'a>(&'a self, path: &'a str) -> (&'a str, &'a [Rule]) {
+        match self.paths.get(path) {
+            Some(rules) => (path, rules),
+            None => ("*", &self.fallback),
+        }
+    }
+}
+
+/// Where `now` falls in the fixed window grid, and how much of the previous window still
+/// overlaps the sliding window ending at `now`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindowPosition {
+    pub index: u64,
+    pub previous_weight: f64,
+    pub reset_after: Duration,
+}
+
+impl WindowPosition {
+    pub fn at(now: Duration, window: Duration) -> Self {
+        let now_ms = now.as_millis() as u64;
+        let window_ms = (window.as_millis() as u64).max(1);
+        let elapsed = now_ms % window_ms;
+        Self {
+            index: now_ms / window_ms,
+            previous_weight: (window_ms - elapsed) as f64 / window_ms as f64,
+            reset_after: Duration::from_millis(window_ms - elapsed),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimitDecision {
+    pub allowed: bool,
+    pub policy: RateLimitPolicy,
+    pub remaining: u64,
+    pub reset_after: Duration,
+}
+
+impl RateLimitDecision {
+    /// Rejections win, otherwise the policy closest to its limit is the one reported.
+    fn tighter(self, other: Self) -> Self {
+        match (self.allowed, other.allowed) {
+            (true, false) => other,
+            (false, true) => self,
+            _ if other.remaining < self.remaining => other,
+            _ => self,
+        }
+    }
+
+    pub fn apply_headers(&self, headers: &mut HeaderMap) {
+        let reset = self.reset_after.as_secs() + u64::from(self.reset_after.subsec_nanos() > 0);
+        let values = [
+            ("ratelimit-limit", self.policy.limit.to_string()),
+            ("ratelimit-remaining", self.remaining.to_string()),
+            ("ratelimit-reset", reset.to_string()),
+            ("ratelimit-policy", self.policy.to_string()),
+        ];
+        for (name, value) in values {
+            if let Ok(value) = HeaderValue::from_str(&value) {
+                headers.insert(name, value);
+            }
+        }
+        if !self.allowed {
+            headers.insert("retry-after", HeaderValue::from(reset));
+        }
+    }
+}
+
+impl IntoResponse for RateLimitDecision {
+    fn into_response(self) -> Response {
+        let mut response = (StatusCode::TOO_MANY_REQUESTS, "Too Many Requests").into_response();
+        self.apply_headers(response.headers_mut());
+        response
+    }
+}
+
+/// Policies shared by every service, so a route served by several of them has one budget.
+/// Credential endpoints are limited per IP, node reports and the websocket handshake per token,
+/// everything else shares a generous per IP and per user budget. A route's rules replace the
+/// fallback, so routes reachable without a session or bearer token need a per IP rule.
+pub fn default_rules() -> RateLimitRules {
+    let default_per_minute = std::env::var("RATE_LIMIT_PER_MINUTE")
+        .ok()
+        .and_then(|limit| limit.parse().ok())
+        .unwrap_or(600);
+    RateLimitRules::default()
+        .route(
+            RoutesEnum::Static_UnAuth_Login,
+            PrincipalKind::Ip,
+            RateLimitPolicy::per_minute(20),
+        )
+        .route(
+            RoutesEnum::Static_UnAuth_Register,
+            PrincipalKind::Ip,
+            RateLimitPolicy::per_hour(20),
+        )
+        .route(
+            RoutesEnum::Static_UnAuth_RegisterApi,
+            PrincipalKind::Ip,
+            RateLimitPolicy::per_hour(20),
+        )
+        .route(
+            RoutesEnum::Static_UnAuth_ResetPassword,
+            PrincipalKind::Ip,
+            RateLimitPolicy::per_hour(10),
+        )
+        .route(
+            RoutesEnum::Static_UnAuth_ResendConfirmationEmail,
+            PrincipalKind::Ip,
+            RateLimitPolicy::per_hour(10),
+        )
+        .route(
+            RoutesEnum::Static_UnAuth_TwoFactor,
+            PrincipalKind::Ip,
+            RateLimitPolicy::per_minute(10),
+        )
+        .route(
+            RoutesEnum::Api_GetToken,
+            PrincipalKind::Ip,
+            RateLimitPolicy::per_minute(30),
+        )
+        .route(
+            RoutesEnum::Api_CheckToken,
+            PrincipalKind::Ip,
+            RateLimitPolicy::per_minute(120),
+        )
+        .route(
+            RoutesEnum::Api_SiwsNonce,
+            PrincipalKind::Ip,
+            RateLimitPolicy::per_minute(30),
+        )
+        .route(
+            RoutesEnum::Api_SiwsLogin,
+            PrincipalKind::Ip,
+            RateLimitPolicy::per_minute(20),
+        )
+        .route(
+            RoutesEnum::Api_ChangeEmail,
+            PrincipalKind::User,
+            RateLimitPolicy::per_hour(5),
+        )
+        .route(
+            RoutesEnum::Api_WalletChallenge,
+            PrincipalKind::User,
+            RateLimitPolicy::per_minute(10),
+        )
+        .route(
+            RoutesEnum::Api_TgBotLinkCode,
+            PrincipalKind::User,
+            RateLimitPolicy::per_minute(10),
+        )
+        .route(
+            RoutesEnum::Api_ReportUptime,
+            PrincipalKind::Ip,
+            RateLimitPolicy::per_minute(120),
+        )
+        .route(
+            RoutesEnum::Api_ReportUptime,
+            PrincipalKind::ApiToken,
+            RateLimitPolicy::per_minute(30),
+        )
+        .route(
+            RoutesEnum::Api_ReportUptime,
+            PrincipalKind::User,
+            RateLimitPolicy::per_minute(60),
+        )
+        .route(
+            RoutesEnum::Api_SubmitBandwidth,
+            PrincipalKind::Ip,
+            RateLimitPolicy::per_minute(120),
+        )
+        .route(
+            RoutesEnum::Api_SubmitBandwidth,
+            PrincipalKind::ApiToken,
+            RateLimitPolicy::per_minute(30),
+        )
+        .route(
+            RoutesEnum::Api_SubmitBandwidth,
+            PrincipalKind::User,
+            RateLimitPolicy::per_minute(60),
+        )
+        .path("/ws", PrincipalKind::Ip, RateLimitPolicy::per_minute(30))
+        .path(
+            "/ws",
+            PrincipalKind::ApiToken,
+            RateLimitPolicy::per_minute(10),
+        )
+        .fallback(
+            PrincipalKind::Ip,
+            RateLimitPolicy::per_minute(default_per_minute),
+        )
+        .fallback(
+            PrincipalKind::User,
+            RateLimitPolicy::per_minute(default_per_minute),
+        )
+}
+
+#[derive(Clone)]
+pub struct RateLimiter {
+    redis: MultiplexedConnection,
+    rules: Arc<RateLimitRules>,
+    script: Arc<Script>,
+}
+
+impl RateLimiter {
+    pub fn new(redis: MultiplexedConnection, rules: RateLimitRules) -> Self {
+        Self {
+            redis,
+            rules: Arc::new(rules),
+            script: Arc::new(Script::new(SLIDING_WINDOW_SCRIPT)),
+        }
+    }
+
+    /// `None` when no policy applies to the path and principals.
+    #[tracing::instrument(name = "rate_limit_check", skip(self, principals))]
+    pub async fn check(
+        &self,
+        path: &str,
+        principals: &[Principal],
+    ) -> anyhow::Result<Option<RateLimitDecision>> {
+        let (scope, rules) = self.rules.rules_for(path);
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?;
+        let mut redis = self.redis.clone();
+        let mut decision: Option<RateLimitDecision> = None;
+        for rule in rules {
+            for principal in principals.iter().filter(|p| p.kind() == rule.principal) {
+                let position = WindowPosition::at(now, rule.policy.window);
+                let key = format!(
+                    "{}:{}:{}:{}:{}",
+                    KEY_PREFIX,
+                    scope,
+                    rule.principal,
+                    principal.key_part(),
+                    rule.policy.window.as_millis()
+                );
+                let (allowed, count): (u8, u64) = self
+                    .script
+                    .key(format!("{}:{}", key, position.index))
+                    .key(format!("{}:{}", key, position.index.saturating_sub(1)))
+                    .arg(2 * rule.policy.window.as_millis() as u64)
+                    .arg(position.previous_weight)
+                    .arg(rule.policy.limit)
+                    .invoke_async(&mut redis)
+                    .await?;
+                let current = RateLimitDecision {
+                    allowed: allowed == 1,
+                    policy: rule.policy,
+                    remaining: rule.policy.limit.saturating_sub(count),
+                    reset_after: position.reset_after,
+                };
+                decision = Some(match decision {
+                    Some(decision) => decision.tighter(current),
+                    None => current,
+                });
+            }
+        }
+        Ok(decision)
+    }
+}
+
+pub type UserResolver = Arc<dyn Fn(&Extensions) -> Option<Uuid> + Send + Sync>;
+
+/// Client IP as seen by Cloudflare, falling back to the socket peer.
+pub fn client_ip(headers: &HeaderMap, extensions: &Extensions) -> Option<String> {
+    headers
+        .get("cf-connecting-ip")
+        .and_then(|ip| ip.to_str().ok())
+        .map(str::to_string)
+        .or_else(|| {
+            extensions
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(addr)| addr.ip().to_string())
+        })
+}
+
+pub fn bearer_token(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|token| token.trim().to_string())
+        .filter(|token| !token.is_empty())
+}
+
+#[derive(Clone)]
+pub struct RateLimitLayer {
+    limiter: RateLimiter,
+    user_resolver: Option<UserResolver>,
+}
+
+impl RateLimitLayer {
+    pub fn new(limiter: RateLimiter) -> Self {
+        Self {
+            limiter,
+            user_resolver: None,
+        }
+    }
+
+    /// Enables the [`PrincipalKind::User`] policies, the resolver reads whatever the
+    /// authentication layer put in the request extensions.
+    pub fn with_user_resolver(mut self, resolver: UserResolver) -> Self {
+        self.user_resolver = Some(resolver);
+        self
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimitService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimitService {
+            inner,
+            limiter: self.limiter.clone(),
+            user_resolver: self.user_resolver.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RateLimitService<S> {
+    inner: S,
+    limiter: RateLimiter,
+    user_resolver: Option<UserResolver>,
+}
+
+impl<S> RateLimitService<S> {
+    fn principals(&self, request: &Request) -> Vec<Principal> {
+        let headers = request.headers();
+        let extensions = request.extensions();
+        let mut principals = Vec::with_capacity(3);
+        if let Some(ip) = client_ip(headers, extensions) {
+            principals.push(Principal::Ip(ip));
+        }
+        if let Some(user_id) = self
+            .user_resolver
+            .as_ref()
+            .and_then(|resolver| resolver(extensions))
+        {
+            principals.push(Principal::User(user_id));
+        }
+        if let Some(token) = bearer_token(headers) {
+            principals.push(Principal::ApiToken(token));
+        }
+        principals
+    }
+}
+
+impl<S> Service<Request> for RateLimitService<S>
+where
+    S: Service<Request, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let principals = self.principals(&request);
+        let limiter = self.limiter.clone();
+        // the clone may not be ready, keep the one poll_ready was called on
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(async move {
+            let path = request.uri().path().to_string();
+            match limiter.check(&path, &principals).await {
+                Ok(Some(decision)) if !decision.allowed => Ok(decision.into_response()),
+                Ok(Some(decision)) => {
+                    let mut response = inner.call(request).await?;
+                    decision.apply_headers(response.headers_mut());
+                    Ok(response)
+                }
+                Ok(None) => inner.call(request).await,
+                // a Redis outage must not take the service down with it
+                Err(e) => {
+                    tracing::error!("Rate limit check failed: {:?}", e);
+                    inner.call(request).await
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window_position() {
+        let window = Duration::from_secs(60);
+        let position = WindowPosition::at(Duration::from_secs(120), window);
+        assert_eq!(position.index, 2);
+        assert_eq!(position.previous_weight, 1.0);
+        assert_eq!(position.reset_after, window);
+
+        let position = WindowPosition::at(Duration::from_secs(165), window);
+        assert_eq!(position.index, 2);
+        assert_eq!(position.previous_weight, 0.25);
+        assert_eq!(position.reset_after, Duration::from_secs(15));
+    }
+
+    #[test]
+    fn test_rules_and_headers() {
+        let rules = RateLimitRules::default()
+            .route(
+                RoutesEnum::Api_GetToken,
+                PrincipalKind::Ip,
+                RateLimitPolicy::per_minute(10),
+            )
+            .fallback(PrincipalKind::Ip, RateLimitPolicy::per_second(5));
+        assert_eq!(rules.rules_for("/api/get_token").0, "/api/get_token");
+        assert_eq!(rules.rules_for("/get_token").1.len(), 1);
+        assert_eq!(rules.rules_for("/dashboard").0, "*");
+
+        let allowed = RateLimitDecision {
+            allowed: true,
+            policy: RateLimitPolicy::per_minute(10),
+            remaining: 3,
+            reset_after: Duration::from_millis(1500),
+        };
+        let rejected = RateLimitDecision {
+            allowed: false,
+            remaining: 0,
+            ..allowed
+        };
+        assert_eq!(allowed.tighter(rejected), rejected);
+        let mut headers = HeaderMap::new();
+        rejected.apply_headers(&mut headers);
+        assert_eq!(headers["ratelimit-policy"], "10;w=60");
+        assert_eq!(headers["ratelimit-remaining"], "0");
+        assert_eq!(headers["ratelimit-reset"], "2");
+        assert_eq!(headers["retry-after"], "2");
+        assert!(!Principal::ApiToken("secret".to_string())
+            .key_part()
+            .contains("secret"));
+    }
+
+    #[test]
+    fn test_token_routes_are_limited_per_ip() {
+        // requests without a bearer token only match the per IP rules
+        let rules = default_rules();
+        for (path, path_rules) in &rules.paths {
+            if path_rules
+                .iter()
+                .any(|rule| rule.principal == PrincipalKind::ApiToken)
+            {
+                assert!(
+                    path_rules
+                        .iter()
+                        .any(|rule| rule.principal == PrincipalKind::Ip),
+                    "{} has no per IP rule",
+                    path
+                );
+            }
+        }
+    }
+}
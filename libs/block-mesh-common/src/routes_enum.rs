@@ -20,6 +20,7 @@ pub enum RoutesEnum {
     Static_UnAuth_Login,
     Static_UnAuth_HealthCheck,
     Static_UnAuth_Health,
+    Static_UnAuth_TwoFactor,
     Static_Auth_Twitter_Login,
     Static_Auth_Edit_Invite,
     Static_Auth_Call_To_Action,
@@ -36,10 +37,41 @@ pub enum RoutesEnum {
     Api_GetStats,
     Api_GetLatestInviteCode,
     Api_CreateTaskWithToken,
+    Api_GetTaskResult,
     Api_CheckToken,
     Api_EMailViaToken,
     Api_Dashboard,
     Api_ReportsQueue,
+    Api_AdminPointsLedger,
+    Api_AdminPointsReversal,
+    Api_AdminSybilFlags,
+    Api_ApiTokens,
+    Api_ApiTokensRevoke,
+    Api_ApiTokensRotate,
+    Api_TwoFactor,
+    Api_TwoFactorEnroll,
+    Api_TwoFactorConfirm,
+    Api_TwoFactorDisable,
+    Api_TwoFactorRecoveryCodes,
+    Api_Sessions,
+    Api_SessionsRevoke,
+    Api_SiwsNonce,
+    Api_SiwsLogin,
+    Api_LinkEmail,
+    Api_WalletChallenge,
+    Api_WalletUnlink,
+    Api_ChangeEmail,
+    Api_DeleteAccount,
+    Api_ExportAccount,
+    Static_UnAuth_EmailChangeConfirm,
+    Api_NotificationPreferences,
+    Api_NotificationTelegramLink,
+    Api_NotificationTelegramUnlink,
+    Static_UnAuth_TelegramWebhook,
+    Api_TgBotLinkCode,
+    Api_TgBotUnlink,
+    Api_TgBotLink,
+    Api_TgBotAccount,
 }
 
 impl Display for RoutesEnum {
@@ -67,6 +99,7 @@ impl Display for RoutesEnum {
             RoutesEnum::Static_UnAuth_Login => write!(f, "/login"),
             RoutesEnum::Static_UnAuth_HealthCheck => write!(f, "/health_check"),
             RoutesEnum::Static_UnAuth_Health => write!(f, "/health"),
+            RoutesEnum::Static_UnAuth_TwoFactor => write!(f, "/two_factor"),
             RoutesEnum::Static_Auth_Logout => write!(f, "/logout"),
             RoutesEnum::Static_Auth_Dashboard => write!(f, "/dashboard"),
             RoutesEnum::Static_Auth_Edit_Invite => write!(f, "/edit_invite_code"),
@@ -80,11 +113,46 @@ impl Display for RoutesEnum {
             RoutesEnum::Api_GetStats => write!(f, "/get_stats"),
             RoutesEnum::Api_GetLatestInviteCode => write!(f, "/get_latest_invite_code"),
             RoutesEnum::Api_CreateTaskWithToken => write!(f, "/create_task_with_token"),
+            RoutesEnum::Api_GetTaskResult => write!(f, "/get_task_result"),
             RoutesEnum::Api_CheckToken => write!(f, "/check_token"),
             RoutesEnum::Api_EMailViaToken => write!(f, "/get_email_via_token"),
             RoutesEnum::Api_Dashboard => write!(f, "/dashboard"),
             RoutesEnum::Api_ReportsQueue => write!(f, "/admin/reports_queue"),
+            RoutesEnum::Api_AdminPointsLedger => write!(f, "/admin/points_ledger"),
+            RoutesEnum::Api_AdminPointsReversal => write!(f, "/admin/points_reversal"),
+            RoutesEnum::Api_AdminSybilFlags => write!(f, "/admin/sybil_flags"),
+            RoutesEnum::Api_ApiTokens => write!(f, "/api_tokens"),
+            RoutesEnum::Api_ApiTokensRevoke => write!(f, "/api_tokens/revoke"),
+            RoutesEnum::Api_ApiTokensRotate => write!(f, "/api_tokens/rotate"),
+            RoutesEnum::Api_TwoFactor => write!(f, "/two_factor"),
+            RoutesEnum::Api_TwoFactorEnroll => write!(f, "/two_factor/enroll"),
+            RoutesEnum::Api_TwoFactorConfirm => write!(f, "/two_factor/confirm"),
+            RoutesEnum::Api_TwoFactorDisable => write!(f, "/two_factor/disable"),
+            RoutesEnum::Api_TwoFactorRecoveryCodes => write!(f, "/two_factor/recovery_codes"),
+            RoutesEnum::Api_Sessions => write!(f, "/sessions"),
+            RoutesEnum::Api_SessionsRevoke => write!(f, "/sessions/revoke"),
+            RoutesEnum::Api_SiwsNonce => write!(f, "/siws/nonce"),
+            RoutesEnum::Api_SiwsLogin => write!(f, "/siws/login"),
+            RoutesEnum::Api_LinkEmail => write!(f, "/link_email"),
+            RoutesEnum::Api_WalletChallenge => write!(f, "/wallet/challenge"),
+            RoutesEnum::Api_WalletUnlink => write!(f, "/wallet/unlink"),
+            RoutesEnum::Api_ChangeEmail => write!(f, "/account/change_email"),
+            RoutesEnum::Api_DeleteAccount => write!(f, "/account/delete"),
+            RoutesEnum::Api_ExportAccount => write!(f, "/account/export"),
+            RoutesEnum::Static_UnAuth_EmailChangeConfirm => write!(f, "/email_change_confirm"),
+            RoutesEnum::Api_NotificationPreferences => write!(f, "/notifications/preferences"),
+            RoutesEnum::Api_NotificationTelegramLink => write!(f, "/notifications/telegram_link"),
+            RoutesEnum::Api_NotificationTelegramUnlink => {
+                write!(f, "/notifications/telegram_unlink")
+            }
+            RoutesEnum::Static_UnAuth_TelegramWebhook => {
+                write!(f, "/telegram/notifications_webhook")
+            }
             RoutesEnum::Static_UnAuth_Unsubscribe => write!(f, "/unsubscribe"),
+            RoutesEnum::Api_TgBotLinkCode => write!(f, "/tg_bot/link_code"),
+            RoutesEnum::Api_TgBotUnlink => write!(f, "/tg_bot/unlink"),
+            RoutesEnum::Api_TgBotLink => write!(f, "/tg_bot/link"),
+            RoutesEnum::Api_TgBotAccount => write!(f, "/tg_bot/account"),
         }
     }
 }
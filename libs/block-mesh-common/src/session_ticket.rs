@@ -0,0 +1,191 @@
+use crate::constant_time::constant_time_eq;
+use chrono::{DateTime, Duration, Utc};
+use std::env;
+use std::str::FromStr;
+use uuid::Uuid;
+
+const VERSION: &str = "v2";
+const MAC_LEN: usize = 32;
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum SessionTicketError {
+    #[error("Malformed session ticket")]
+    Malformed,
+    #[error("Invalid session ticket signature")]
+    InvalidSignature,
+    #[error("Session ticket expired")]
+    Expired,
+    #[error("Session tickets are disabled")]
+    Disabled,
+}
+
+/// Short lived, HMAC signed credential handed out by `/api/get_token` so nodes don't have
+/// to send their long lived api token on every request. The ticket names the api token it was
+/// issued for, so it stops working once that token is revoked or rotated away.
+///
+/// Wire format: `v2.<user_id>.<token_id>.<expires_at unix>.<comma separated scopes>.<bs58 mac>`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionTicket {
+    pub user_id: Uuid,
+    pub token_id: Uuid,
+    pub scopes: Vec<String>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl SessionTicket {
+    pub fn new(user_id: Uuid, token_id: Uuid, scopes: Vec<String>, ttl: Duration) -> Self {
+        Self {
+            user_id,
+            token_id,
+            scopes,
+            expires_at: Utc::now() + ttl,
+        }
+    }
+
+    /// Secret shared by every service that issues or accepts tickets, unset disables tickets.
+    pub fn secret_from_env() -> Option<String> {
+        env::var("SESSION_TICKET_SECRET")
+            .ok()
+            .filter(|secret| !secret.is_empty())
+    }
+
+    pub fn ttl_from_env() -> Duration {
+        Duration::seconds(
+            env::var("SESSION_TICKET_TTL_SECS")
+                .unwrap_or("900".to_string())
+                .parse()
+                .unwrap_or(900),
+        )
+    }
+
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes.iter().any(|s| s == scope)
+    }
+
+    fn payload(&self) -> String {
+        format!(
+            "{}.{}.{}.{}.{}",
+            VERSION,
+            self.user_id,
+            self.token_id,
+            self.expires_at.timestamp(),
+            self.scopes.join(",")
+        )
+    }
+
+    fn mac(payload: &str, secret: &str) -> [u8; MAC_LEN] {
+        let mac = hmac_sha512::HMAC::mac(payload.as_bytes(), secret.as_bytes());
+        let mut truncated = [0u8; MAC_LEN];
+        truncated.copy_from_slice(&mac[..MAC_LEN]);
+        truncated
+    }
+
+    pub fn sign(&self, secret: &str) -> String {
+        let payload = self.payload();
+        let mac = bs58::encode(Self::mac(&payload, secret)).into_string();
+        format!("{}.{}", payload, mac)
+    }
+
+    pub fn verify(
+        ticket: &str,
+        secret: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Self, SessionTicketError> {
+        let (payload, mac) = ticket
+            .rsplit_once('.')
+            .ok_or(SessionTicketError::Malformed)?;
+        let mac = bs58::decode(mac)
+            .into_vec()
+            .map_err(|_| SessionTicketError::Malformed)?;
+        let expected = Self::mac(payload, secret);
+        if !constant_time_eq(&mac, &expected) {
+            return Err(SessionTicketError::InvalidSignature);
+        }
+        let mut parts = payload.splitn(5, '.');
+        if parts.next() != Some(VERSION) {
+            return Err(SessionTicketError::Malformed);
+        }
+        let user_id = parts
+            .next()
+            .and_then(|s| Uuid::from_str(s).ok())
+            .ok_or(SessionTicketError::Malformed)?;
+        let token_id = parts
+            .next()
+            .and_then(|s| Uuid::from_str(s).ok())
+            .ok_or(SessionTicketError::Malformed)?;
+        let expires_at = parts
+            .next()
+            .and_then(|s| s.parse::<i64>().ok())
+            .and_then(|ts| DateTime::from_timestamp(ts, 0))
+            .ok_or(SessionTicketError::Malformed)?;
+        let scopes = parts
+            .next()
+            .ok_or(SessionTicketError::Malformed)?
+            .split(',')
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .collect();
+        if expires_at <= now {
+            return Err(SessionTicketError::Expired);
+        }
+        Ok(Self {
+            user_id,
+            token_id,
+            scopes,
+            expires_at,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ticket() -> SessionTicket {
+        SessionTicket::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            vec!["node-report".to_string(), "read-stats".to_string()],
+            Duration::minutes(15),
+        )
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let ticket = ticket();
+        let signed = ticket.sign("secret");
+        let verified = SessionTicket::verify(&signed, "secret", Utc::now()).unwrap();
+        assert_eq!(verified.user_id, ticket.user_id);
+        assert_eq!(verified.token_id, ticket.token_id);
+        assert_eq!(verified.scopes, ticket.scopes);
+        assert_eq!(
+            verified.expires_at.timestamp(),
+            ticket.expires_at.timestamp()
+        );
+        assert!(verified.has_scope("node-report"));
+        assert!(!verified.has_scope("task-create"));
+    }
+
+    #[test]
+    fn test_rejects_tampered_and_expired() {
+        let ticket = ticket();
+        let signed = ticket.sign("secret");
+        assert_eq!(
+            SessionTicket::verify(&signed, "other", Utc::now()),
+            Err(SessionTicketError::InvalidSignature)
+        );
+        let tampered = signed.replacen("node-report", "task-create", 1);
+        assert_eq!(
+            SessionTicket::verify(&tampered, "secret", Utc::now()),
+            Err(SessionTicketError::InvalidSignature)
+        );
+        assert_eq!(
+            SessionTicket::verify(&signed, "secret", ticket.expires_at + Duration::seconds(1)),
+            Err(SessionTicketError::Expired)
+        );
+        assert_eq!(
+            SessionTicket::verify("garbage", "secret", Utc::now()),
+            Err(SessionTicketError::Malformed)
+        );
+    }
+}
@@ -0,0 +1,272 @@
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use std::fmt::{Display, Formatter};
+
+const HEADER_SUFFIX: &str = " wants you to sign in with your Solana account:";
+pub const SIWS_VERSION: &str = "1";
+pub const SIWS_CHAIN_ID: &str = "mainnet";
+pub const SIWS_STATEMENT: &str = "Sign in to BlockMesh";
+/// How long a signed message stays valid, independently of the nonce expiry.
+pub const SIWS_MESSAGE_TTL_MINUTES: i64 = 10;
+/// Reserved (RFC 2606) domain of the stand-in email of accounts created by wallet sign in,
+/// replaced once the user links a real email.
+pub const WALLET_EMAIL_DOMAIN: &str = "wallet.blockmesh.invalid";
+
+pub fn wallet_placeholder_email(pubkey: &str) -> String {
+    format!("{}@{}", pubkey.to_ascii_lowercase(), WALLET_EMAIL_DOMAIN)
+}
+
+pub fn is_wallet_placeholder_email(email: &str) -> bool {
+    email.ends_with(&format!("@{}", WALLET_EMAIL_DOMAIN))
+}
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum SiwsError {
+    #[error("Malformed sign in message")]
+    Malformed,
+    #[error("Sign in message is for another domain")]
+    DomainMismatch,
+    #[error("Sign in message is for another address")]
+    AddressMismatch,
+    #[error("Sign in message expired")]
+    Expired,
+    #[error("Invalid sign in signature")]
+    InvalidSignature,
+    #[error("Unknown or already used sign in nonce")]
+    InvalidNonce,
+}
+
+/// Sign In With Solana message, the Solana flavour of EIP-4361 that wallets render natively.
+///
+/// ```text
+/// app.blockmesh.xyz wants you to sign in with your Solana account:
+/// <base58 pubkey>
+///
+/// Sign in to BlockMesh
+///
+/// URI: https://app.blockmesh.xyz
+/// Version: 1
+/// Chain ID: mainnet
+/// Nonce: <nonce>
+/// Issued At: <rfc3339>
+/// Expiration Time: <rfc3339>
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SiwsMessage {
+    pub domain: String,
+    pub address: String,
+    pub statement: Option<String>,
+    pub uri: String,
+    pub version: String,
+    pub chain_id: String,
+    pub nonce: String,
+    pub issued_at: DateTime<Utc>,
+    pub expiration_time: Option<DateTime<Utc>>,
+}
+
+impl SiwsMessage {
+    /// `origin` is the full origin the user signs into, e.g. `https://app.blockmesh.xyz`.
+    pub fn new(origin: &str, address: &str, nonce: &str) -> Self {
+        let now = Utc::now();
+        let domain = origin
+            .split("://")
+            .last()
+            .unwrap_or(origin)
+            .trim_end_matches('/')
+            .to_string();
+        Self {
+            domain,
+            address: address.to_string(),
+            statement: Some(SIWS_STATEMENT.to_string()),
+            uri: origin.trim_end_matches('/').to_string(),
+            version: SIWS_VERSION.to_string(),
+            chain_id: SIWS_CHAIN_ID.to_string(),
+            nonce: nonce.to_string(),
+            issued_at: now,
+            expiration_time: Some(now + Duration::minutes(SIWS_MESSAGE_TTL_MINUTES)),
+        }
+    }
+
+    pub fn parse(message: &str) -> Result<Self, SiwsError> {
+        let mut lines = message.lines();
+        let domain = lines
+            .next()
+            .and_then(|line| line.strip_suffix(HEADER_SUFFIX))
+            .filter(|domain| !domain.is_empty())
+            .ok_or(SiwsError::Malformed)?
+            .to_string();
+        let address = lines
+            .next()
+            .filter(|address| !address.is_empty())
+            .ok_or(SiwsError::Malformed)?
+            .to_string();
+        if lines.next() != Some("") {
+            return Err(SiwsError::Malformed);
+        }
+        let mut next = lines.next().ok_or(SiwsError::Malformed)?;
+        let statement = if next.starts_with("URI: ") {
+            None
+        } else {
+            let statement = next.to_string();
+            if lines.next() != Some("") {
+                return Err(SiwsError::Malformed);
+            }
+            next = lines.next().ok_or(SiwsError::Malformed)?;
+            Some(statement)
+        };
+        let field = |line: Option<&str>, name: &str| -> Result<String, SiwsError> {
+            line.and_then(|line| line.strip_prefix(name))
+                .and_then(|line| line.strip_prefix(": "))
+                .map(str::to_string)
+                .ok_or(SiwsError::Malformed)
+        };
+        let timestamp = |value: String| -> Result<DateTime<Utc>, SiwsError> {
+            DateTime::parse_from_rfc3339(&value)
+                .map(|t| t.with_timezone(&Utc))
+                .map_err(|_| SiwsError::Malformed)
+        };
+        let uri = field(Some(next), "URI")?;
+        let version = field(lines.next(), "Version")?;
+        let chain_id = field(lines.next(), "Chain ID")?;
+        let nonce = field(lines.next(), "Nonce")?;
+        let issued_at = timestamp(field(lines.next(), "Issued At")?)?;
+        let expiration_time = match lines.next() {
+            Some(line) => Some(timestamp(field(Some(line), "Expiration Time")?)?),
+            None => None,
+        };
+        if lines.next().is_some() || version != SIWS_VERSION {
+            return Err(SiwsError::Malformed);
+        }
+        Ok(Self {
+            domain,
+            address,
+            statement,
+            uri,
+            version,
+            chain_id,
+            nonce,
+            issued_at,
+            expiration_time,
+        })
+    }
+
+    /// Checks everything but the nonce and the signature, which need server state and crypto.
+    pub fn validate(
+        &self,
+        allowed_domains: &[String],
+        address: &str,
+        now: DateTime<Utc>,
+    ) -> Result<(), SiwsError> {
+        if !allowed_domains.iter().any(|domain| *domain == self.domain) {
+            return Err(SiwsError::DomainMismatch);
+        }
+        if self.address != address {
+            return Err(SiwsError::AddressMismatch);
+        }
+        let expiration_time = self
+            .expiration_time
+            .unwrap_or(self.issued_at + Duration::minutes(SIWS_MESSAGE_TTL_MINUTES));
+        // small allowance for clients whose clock runs ahead
+        if expiration_time <= now || self.issued_at > now + Duration::minutes(1) {
+            return Err(SiwsError::Expired);
+        }
+        Ok(())
+    }
+}
+
+impl Display for SiwsMessage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}{}", self.domain, HEADER_SUFFIX)?;
+        writeln!(f, "{}", self.address)?;
+        writeln!(f)?;
+        if let Some(statement) = &self.statement {
+            writeln!(f, "{}", statement)?;
+            writeln!(f)?;
+        }
+        writeln!(f, "URI: {}", self.uri)?;
+        writeln!(f, "Version: {}", self.version)?;
+        writeln!(f, "Chain ID: {}", self.chain_id)?;
+        writeln!(f, "Nonce: {}", self.nonce)?;
+        write!(
+            f,
+            "Issued At: {}",
+            self.issued_at.to_rfc3339_opts(SecondsFormat::Millis, true)
+        )?;
+        if let Some(expiration_time) = &self.expiration_time {
+            write!(
+                f,
+                "\nExpiration Time: {}",
+                expiration_time.to_rfc3339_opts(SecondsFormat::Millis, true)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Verifies the ed25519 signature of `message` by the base58 `pubkey`.
+#[cfg(feature = "siws")]
+pub fn verify_signature(pubkey: &str, message: &str, signature: &[u8]) -> Result<(), SiwsError> {
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::Signature;
+    use std::str::FromStr;
+
+    let pubkey = Pubkey::from_str(pubkey).map_err(|_| SiwsError::Malformed)?;
+    let signature = Signature::try_from(signature).map_err(|_| SiwsError::Malformed)?;
+    if signature.verify(pubkey.as_ref(), message.as_bytes()) {
+        Ok(())
+    } else {
+        Err(SiwsError::InvalidSignature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDRESS: &str = "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM";
+
+    #[test]
+    fn test_roundtrip() {
+        let message = SiwsMessage::new("https://app.blockmesh.xyz/", ADDRESS, "abc123");
+        assert_eq!(message.domain, "app.blockmesh.xyz");
+        let text = message.to_string();
+        assert!(text
+            .starts_with("app.blockmesh.xyz wants you to sign in with your Solana account:\n9WzD"));
+        let parsed = SiwsMessage::parse(&text).unwrap();
+        assert_eq!(parsed.nonce, "abc123");
+        assert_eq!(parsed.statement.as_deref(), Some(SIWS_STATEMENT));
+        assert_eq!(parsed.to_string(), text);
+
+        let mut bare = message.clone();
+        bare.statement = None;
+        bare.expiration_time = None;
+        let parsed = SiwsMessage::parse(&bare.to_string()).unwrap();
+        assert_eq!(parsed.statement, None);
+        assert_eq!(parsed.expiration_time, None);
+        assert_eq!(parsed.to_string(), bare.to_string());
+    }
+
+    #[test]
+    fn test_validate() {
+        let message = SiwsMessage::new("https://app.blockmesh.xyz", ADDRESS, "abc123");
+        let domains = vec!["app.blockmesh.xyz".to_string()];
+        let now = Utc::now();
+        assert_eq!(message.validate(&domains, ADDRESS, now), Ok(()));
+        assert_eq!(
+            message.validate(&["evil.xyz".to_string()], ADDRESS, now),
+            Err(SiwsError::DomainMismatch)
+        );
+        assert_eq!(
+            message.validate(&domains, "other", now),
+            Err(SiwsError::AddressMismatch)
+        );
+        assert_eq!(
+            message.validate(&domains, ADDRESS, now + Duration::hours(1)),
+            Err(SiwsError::Expired)
+        );
+        assert_eq!(SiwsMessage::parse("hello"), Err(SiwsError::Malformed));
+        assert!(is_wallet_placeholder_email(&wallet_placeholder_email(
+            ADDRESS
+        )));
+        assert!(!is_wallet_placeholder_email("user@blockmesh.xyz"));
+    }
+}
@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use typeshare::typeshare;
+
+/// Header carrying `TG_BOT_API_SECRET` on the calls the Telegram privacy bot makes to the manager.
+pub const TG_BOT_SECRET_HEADER: &str = "X-Tg-Bot-Secret";
+pub const TG_BOT_LINK_CODE_LENGTH: usize = 8;
+pub const TG_BOT_LINK_CODE_TTL_MINUTES: i64 = 15;
+/// Days of uptime averaged to pick the tier.
+pub const TG_BOT_TIER_WINDOW_DAYS: i64 = 7;
+
+const HOUR: f64 = 3_600.0;
+
+/// Daily AI quota of a linked Telegram account, based on how much the account contributes to the network.
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TgBotTier {
+    #[default]
+    Free,
+    Bronze,
+    Silver,
+    Gold,
+}
+
+impl TgBotTier {
+    /// `points` is the account total, `average_uptime` the mean daily uptime in seconds over
+    /// the last [`TG_BOT_TIER_WINDOW_DAYS`].
+    pub fn from_activity(points: f64, average_uptime: f64) -> Self {
+        if points >= 10_000.0 && average_uptime >= 20.0 * HOUR {
+            Self::Gold
+        } else if points >= 2_500.0 && average_uptime >= 12.0 * HOUR {
+            Self::Silver
+        } else if average_uptime >= HOUR {
+            Self::Bronze
+        } else {
+            Self::Free
+        }
+    }
+
+    pub fn daily_limit(&self) -> i64 {
+        match self {
+            Self::Free => 10,
+            Self::Bronze => 25,
+            Self::Silver => 50,
+            Self::Gold => 100,
+        }
+    }
+}
+
+impl Display for TgBotTier {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Free => write!(f, "Free"),
+            Self::Bronze => write!(f, "Bronze"),
+            Self::Silver => write!(f, "Silver"),
+            Self::Gold => write!(f, "Gold"),
+        }
+    }
+}
+
+impl From<String> for TgBotTier {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "Bronze" => Self::Bronze,
+            "Silver" => Self::Silver,
+            "Gold" => Self::Gold,
+            _ => Self::Free,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_activity() {
+        assert_eq!(TgBotTier::from_activity(0.0, 0.0), TgBotTier::Free);
+        assert_eq!(TgBotTier::from_activity(0.0, 2.0 * HOUR), TgBotTier::Bronze);
+        // points alone don't lift an inactive account
+        assert_eq!(TgBotTier::from_activity(50_000.0, 0.0), TgBotTier::Free);
+        assert_eq!(
+            TgBotTier::from_activity(5_000.0, 23.0 * HOUR),
+            TgBotTier::Silver
+        );
+        assert_eq!(
+            TgBotTier::from_activity(12_000.0, 23.0 * HOUR),
+            TgBotTier::Gold
+        );
+        for tier in [TgBotTier::Free, TgBotTier::Gold] {
+            assert_eq!(TgBotTier::from(tier.to_string()), tier);
+        }
+    }
+}
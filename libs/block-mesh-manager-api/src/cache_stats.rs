@@ -0,0 +1,23 @@
+use block_mesh_common::interfaces::server_api::{CheckTokenResponseMap, GetTokenResponseMap};
+use std::env;
+use std::time::Duration;
+
+/// Logs the credential cache counters periodically, they are not exposed over HTTP.
+pub async fn log_cache_stats(
+    check_token_map: CheckTokenResponseMap,
+    get_token_map: GetTokenResponseMap,
+) {
+    let period = env::var("CACHE_STATS_INTERVAL_SECS")
+        .unwrap_or("300".to_string())
+        .parse()
+        .unwrap_or(300);
+    let mut interval = tokio::time::interval(Duration::from_secs(period));
+    loop {
+        interval.tick().await;
+        tracing::info!(
+            get_token = ?get_token_map.stats(),
+            check_token = ?check_token_map.stats(),
+            "credential cache stats"
+        );
+    }
+}
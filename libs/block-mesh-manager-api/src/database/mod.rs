@@ -1,2 +1 @@
-pub mod get_api_token_by_usr_and_status;
 pub mod get_nonce_by_user_id;
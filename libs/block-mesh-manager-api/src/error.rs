@@ -16,6 +16,8 @@ pub enum Error {
     ApiTokenMismatch,
     #[error("Password Mismatch")]
     PasswordMismatch,
+    #[error("Wallet sign in failed")]
+    WalletSignInFailed,
 }
 
 impl IntoResponse for Error {
@@ -34,6 +36,9 @@ impl IntoResponse for Error {
             Error::ApiTokenMismatch => {
                 (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error.").into_response()
             }
+            Error::WalletSignInFailed => {
+                (StatusCode::UNAUTHORIZED, "Wallet sign in failed.").into_response()
+            }
             Error::Anyhow(_) => {
                 (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error.").into_response()
             }
@@ -50,6 +55,7 @@ impl From<Error> for StatusCode {
         match error {
             Error::PasswordMismatch => StatusCode::INTERNAL_SERVER_ERROR,
             Error::ApiTokenMismatch => StatusCode::INTERNAL_SERVER_ERROR,
+            Error::WalletSignInFailed => StatusCode::UNAUTHORIZED,
             Error::UserNotFound => StatusCode::INTERNAL_SERVER_ERROR,
             Error::ApiTokenNotFound => StatusCode::INTERNAL_SERVER_ERROR,
             Error::Sql(_) => StatusCode::INTERNAL_SERVER_ERROR,
@@ -2,7 +2,6 @@ use crate::routes::router::get_router;
 use axum::extract::Request;
 use axum::{Extension, Router};
 use block_mesh_common::env::load_dotenv::load_dotenv;
-use dashmap::DashMap;
 use logger_general::tracing::setup_tracing_stdout_only_with_sentry;
 use sentry_tower::NewSentryLayer;
 use std::net::SocketAddr;
@@ -11,14 +10,18 @@ use std::time::Duration;
 use std::{env, mem, process};
 use tokio::net::TcpListener;
 
+mod cache_stats;
 mod database;
 mod error;
 mod pg_listener;
 mod routes;
 
+use crate::cache_stats::log_cache_stats;
 use crate::pg_listener::start_listening;
 use block_mesh_common::constants::BLOCKMESH_PG_NOTIFY_API;
+use block_mesh_common::credential_cache::CredentialCache;
 use block_mesh_common::interfaces::server_api::{CheckTokenResponseMap, GetTokenResponseMap};
+use block_mesh_common::rate_limit::{default_rules, RateLimitLayer, RateLimiter};
 use database_utils::utils::connection::get_pg_pool;
 use tower_http::cors::CorsLayer;
 use tower_http::timeout::TimeoutLayer;
@@ -59,9 +62,10 @@ fn main() -> anyhow::Result<()> {
 
 async fn run(is_with_sentry: bool) {
     let db_pool = get_pg_pool(None).await;
+    let rate_limiter = rate_limiter().await;
     let router = get_router();
-    let check_token_map: CheckTokenResponseMap = Arc::new(DashMap::new());
-    let get_token_map: GetTokenResponseMap = Arc::new(DashMap::new());
+    let check_token_map: CheckTokenResponseMap = Arc::new(CredentialCache::default());
+    let get_token_map: GetTokenResponseMap = Arc::new(CredentialCache::default());
     let cors = CorsLayer::permissive();
 
     let timeout_layer = env::var("TIMEOUT_LAYER")
@@ -69,8 +73,12 @@ async fn run(is_with_sentry: bool) {
         .parse()
         .unwrap_or(false);
 
-    let app = Router::new()
-        .nest("/", router)
+    let app = Router::new().nest("/", router);
+    let app = match rate_limiter {
+        Some(rate_limiter) => app.layer(RateLimitLayer::new(rate_limiter)),
+        None => app,
+    };
+    let app = app
         .layer(Extension(db_pool.clone()))
         .layer(Extension(check_token_map.clone()))
         .layer(Extension(get_token_map.clone()))
@@ -97,6 +105,10 @@ async fn run(is_with_sentry: bool) {
         .await
         .unwrap();
     tracing::info!("Listening on {}", listener.local_addr().unwrap());
+    tokio::spawn(log_cache_stats(
+        check_token_map.clone(),
+        get_token_map.clone(),
+    ));
     let db_listen_task = tokio::spawn(start_listening(
         db_pool.clone(),
         vec![BLOCKMESH_PG_NOTIFY_API],
@@ -110,6 +122,25 @@ async fn run(is_with_sentry: bool) {
     }
 }
 
+/// `None` when `REDIS_URL` is not set, the api then runs without rate limits.
+async fn rate_limiter() -> Option<RateLimiter> {
+    let Ok(redis_url) = env::var("REDIS_URL") else {
+        tracing::warn!("REDIS_URL is not set, rate limiting is disabled");
+        return None;
+    };
+    let redis_url = if redis_url.ends_with("#insecure") {
+        redis_url
+    } else {
+        format!("{}#insecure", redis_url)
+    };
+    let redis = redis::Client::open(redis_url)
+        .expect("REDIS_URL is not a valid Redis URL")
+        .get_multiplexed_async_connection()
+        .await
+        .expect("Unable to connect to REDIS_URL");
+    Some(RateLimiter::new(redis, default_rules()))
+}
+
 pub async fn run_server(listener: TcpListener, app: Router<()>) -> std::io::Result<()> {
     axum::serve(
         listener,
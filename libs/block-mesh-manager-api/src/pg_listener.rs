@@ -5,7 +5,6 @@ use sqlx::postgres::PgListener;
 use sqlx::Pool;
 use sqlx::Postgres;
 use tracing::error;
-use uuid::Uuid;
 
 #[allow(dead_code)]
 #[tracing::instrument(name = "start_listening", skip_all, err)]
@@ -21,39 +20,12 @@ pub async fn start_listening(
         while let Ok(Some(notification)) = listener.try_recv().await {
             let string = notification.payload().to_owned();
             if let Ok(payload) = serde_json::from_str::<InvalidateApiCache>(&string) {
-                let found = find_check_token_map_key(&check_token_map, &payload.email);
-                if let Some(found) = found {
-                    check_token_map.remove(&found);
-                }
-                let found = find_get_token_map_key(&get_token_map, &payload.email);
-                if let Some(found) = found {
-                    get_token_map.remove(&found);
-                }
+                let email = payload.email.to_ascii_lowercase();
+                check_token_map.invalidate_email(&email);
+                get_token_map.invalidate_email(&email);
             } else {
                 error!("Failed to deserialize {:?}", string);
             }
         }
     }
 }
-
-#[tracing::instrument(name = "find_check_token_map_key", skip_all)]
-pub fn find_check_token_map_key(
-    check_token_map: &CheckTokenResponseMap,
-    email: &str,
-) -> Option<(String, Uuid)> {
-    check_token_map
-        .iter()
-        .find(|i| i.key().0 == email)
-        .map(|found| found.key().clone())
-}
-
-#[tracing::instrument(name = "find_get_token_map_key", skip_all)]
-pub fn find_get_token_map_key(
-    get_token_map: &GetTokenResponseMap,
-    email: &str,
-) -> Option<(String, String)> {
-    get_token_map
-        .iter()
-        .find(|i| i.key().0 == email)
-        .map(|found| found.key().clone())
-}
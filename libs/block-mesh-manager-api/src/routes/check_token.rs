@@ -1,11 +1,12 @@
-use crate::database::get_api_token_by_usr_and_status::get_api_token_by_usr_and_status;
 use crate::error::Error;
 use axum::{Extension, Json};
 use block_mesh_common::interfaces::server_api::{
     CheckTokenRequest, CheckTokenResponseEnum, CheckTokenResponseMap, GetTokenResponse,
 };
-use block_mesh_manager_database_domain::domain::api_token::ApiTokenStatus;
+use block_mesh_manager_database_domain::domain::api_token::ApiTokenScope;
+use block_mesh_manager_database_domain::domain::find_token::find_token;
 use block_mesh_manager_database_domain::domain::get_user_opt_by_email::get_user_opt_by_email;
+use block_mesh_manager_database_domain::domain::node_auth::issue_session_ticket;
 use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
 use sqlx::PgPool;
 
@@ -16,16 +17,26 @@ pub async fn check_token(
     Json(body): Json<CheckTokenRequest>,
 ) -> Result<Json<GetTokenResponse>, Error> {
     let email = body.email.clone().to_ascii_lowercase();
-    let key = (email.clone(), body.api_token);
-
-    if let Some(entry) = check_token_map.get(&key) {
-        return match entry.value() {
-            CheckTokenResponseEnum::ApiTokenMismatch => Err(Error::ApiTokenMismatch),
-            CheckTokenResponseEnum::UserNotFound => Err(Error::UserNotFound),
-            CheckTokenResponseEnum::ApiTokenNotFound => Err(Error::ApiTokenNotFound),
-            CheckTokenResponseEnum::GetTokenResponse(r) => Ok(Json(r.clone())),
-        };
-    }
+    let ticket = match check_token_map.get(&email, body.api_token.as_bytes()) {
+        Ok(cached) => {
+            return match cached {
+                CheckTokenResponseEnum::ApiTokenMismatch => Err(Error::ApiTokenMismatch),
+                CheckTokenResponseEnum::UserNotFound => Err(Error::UserNotFound),
+                CheckTokenResponseEnum::ApiTokenNotFound => Err(Error::ApiTokenNotFound),
+                CheckTokenResponseEnum::GetTokenResponse {
+                    mut response,
+                    user_id,
+                    token_id,
+                    scopes,
+                } => {
+                    (response.session_ticket, response.session_ticket_expires_at) =
+                        issue_session_ticket(user_id, token_id, &scopes).unzip();
+                    Ok(Json(response))
+                }
+            }
+        }
+        Err(ticket) => ticket,
+    };
 
     let mut transaction = create_txn(&pool).await?;
 
@@ -33,48 +44,56 @@ pub async fn check_token(
         Ok(user) => match user {
             Some(user) => user,
             None => {
-                check_token_map.insert(key, CheckTokenResponseEnum::UserNotFound);
+                check_token_map.insert(ticket, CheckTokenResponseEnum::UserNotFound, true);
                 commit_txn(transaction).await?;
                 return Err(Error::UserNotFound);
             }
         },
         Err(_) => {
-            check_token_map.insert(key, CheckTokenResponseEnum::UserNotFound);
             commit_txn(transaction).await?;
             return Err(Error::UserNotFound);
         }
     };
     let api_token =
-        match get_api_token_by_usr_and_status(&pool, &user.id, ApiTokenStatus::Active).await {
+        match find_token(&mut transaction, &body.api_token, ApiTokenScope::NodeReport).await {
             Ok(api_token) => match api_token {
                 Some(api_token) => api_token,
                 None => {
-                    check_token_map.insert(key, CheckTokenResponseEnum::ApiTokenNotFound);
+                    check_token_map.insert(ticket, CheckTokenResponseEnum::ApiTokenNotFound, true);
                     commit_txn(transaction).await?;
                     return Err(Error::ApiTokenNotFound);
                 }
             },
             Err(_) => {
-                check_token_map.insert(key, CheckTokenResponseEnum::ApiTokenNotFound);
                 commit_txn(transaction).await?;
                 return Err(Error::ApiTokenNotFound);
             }
         };
 
-    if *api_token.token.as_ref() != body.api_token {
-        check_token_map.insert(key, CheckTokenResponseEnum::ApiTokenMismatch);
+    if api_token.user_id != user.id {
+        check_token_map.insert(ticket, CheckTokenResponseEnum::ApiTokenMismatch, true);
         commit_txn(transaction).await?;
         return Err(Error::ApiTokenMismatch);
     }
 
-    let response = GetTokenResponse {
+    let mut response = GetTokenResponse {
         api_token: Some(*api_token.token.as_ref()),
         message: None,
+        session_ticket: None,
+        session_ticket_expires_at: None,
     };
     check_token_map.insert(
-        key,
-        CheckTokenResponseEnum::GetTokenResponse(response.clone()),
+        ticket,
+        CheckTokenResponseEnum::GetTokenResponse {
+            response: response.clone(),
+            user_id: user.id,
+            token_id: api_token.id,
+            scopes: api_token.scopes.clone(),
+        },
+        false,
     );
     commit_txn(transaction).await?;
+    (response.session_ticket, response.session_ticket_expires_at) =
+        issue_session_ticket(user.id, api_token.id, &api_token.scopes).unzip();
     Ok(Json(response))
 }
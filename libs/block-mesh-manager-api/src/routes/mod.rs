@@ -3,4 +3,5 @@ pub mod get_token;
 pub mod health;
 pub mod ok;
 pub mod router;
+pub mod siws_nonce;
 pub mod version;
@@ -2,6 +2,7 @@ use crate::routes::check_token::check_token;
 use crate::routes::get_token::get_token;
 use crate::routes::health::health;
 use crate::routes::ok::ok_handler;
+use crate::routes::siws_nonce::siws_nonce;
 use crate::routes::version::version;
 use axum::routing::{get, post};
 use axum::Router;
@@ -13,4 +14,5 @@ pub fn get_router() -> Router {
         .route("/version", get(version))
         .route("/api/check_token", post(check_token).get(ok_handler))
         .route("/api/get_token", post(get_token).get(ok_handler))
+        .route("/api/siws/nonce", get(siws_nonce))
 }
@@ -0,0 +1,13 @@
+use crate::error::Error;
+use axum::{Extension, Json};
+use block_mesh_common::interfaces::server_api::SiwsNonceResponse;
+use block_mesh_manager_database_domain::domain::siws_nonce::create_siws_nonce;
+use sqlx::PgPool;
+
+#[tracing::instrument(name = "siws_nonce", skip_all)]
+pub async fn siws_nonce(
+    Extension(pool): Extension<PgPool>,
+) -> Result<Json<SiwsNonceResponse>, Error> {
+    let (nonce, expires_at) = create_siws_nonce(&pool).await?;
+    Ok(Json(SiwsNonceResponse { nonce, expires_at }))
+}
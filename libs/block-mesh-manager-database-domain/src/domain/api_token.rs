@@ -1,13 +1,27 @@
+use std::env;
 use std::error::Error;
 use std::fmt::Display;
+use std::str::FromStr;
 
-use chrono::{DateTime, Utc};
+use block_mesh_common::interfaces::server_api::ApiTokenInfo;
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::{Decode, Postgres};
 use uuid::Uuid;
 
 use secret::Secret;
 
+/// Name of the token created on registration and returned on login when no device name is given.
+pub const DEFAULT_API_TOKEN_NAME: &str = "default";
+
+/// Upper bound of active tokens per user, `MAX_ACTIVE_API_TOKENS` overrides it.
+pub fn max_active_api_tokens() -> i64 {
+    env::var("MAX_ACTIVE_API_TOKENS")
+        .unwrap_or("20".to_string())
+        .parse()
+        .unwrap_or(20)
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum ApiTokenStatus {
     Inactive,
@@ -58,11 +72,147 @@ impl sqlx::Decode<'_, Postgres> for ApiTokenStatus {
     }
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiTokenScope {
+    NodeReport,
+    ReadStats,
+    TaskCreate,
+}
+
+impl ApiTokenScope {
+    pub fn all() -> Vec<ApiTokenScope> {
+        vec![
+            ApiTokenScope::NodeReport,
+            ApiTokenScope::ReadStats,
+            ApiTokenScope::TaskCreate,
+        ]
+    }
+
+    /// Scopes of tokens minted on login, enough for a device to run a node and show its stats.
+    pub fn device() -> Vec<ApiTokenScope> {
+        vec![ApiTokenScope::NodeReport, ApiTokenScope::ReadStats]
+    }
+}
+
+impl Display for ApiTokenScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiTokenScope::NodeReport => write!(f, "node-report"),
+            ApiTokenScope::ReadStats => write!(f, "read-stats"),
+            ApiTokenScope::TaskCreate => write!(f, "task-create"),
+        }
+    }
+}
+
+impl FromStr for ApiTokenScope {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "node-report" => Ok(ApiTokenScope::NodeReport),
+            "read-stats" => Ok(ApiTokenScope::ReadStats),
+            "task-create" => Ok(ApiTokenScope::TaskCreate),
+            _ => Err(anyhow::anyhow!("Unknown api token scope {}", s)),
+        }
+    }
+}
+
 #[derive(sqlx::FromRow, Debug, Serialize, Deserialize, Clone)]
 pub struct ApiToken {
     pub id: Uuid,
     pub user_id: Uuid,
     pub token: Secret<Uuid>,
     pub status: ApiTokenStatus,
+    pub name: String,
+    pub scopes: Vec<String>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub last_used_ip: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
 }
+
+impl ApiToken {
+    pub fn has_scope(&self, scope: ApiTokenScope) -> bool {
+        let scope = scope.to_string();
+        self.scopes.iter().any(|s| *s == scope)
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+
+    /// Last used is only written when the IP changes or the previous write is older than
+    /// `API_TOKEN_TOUCH_INTERVAL` seconds, so hot paths do not update the row on every request.
+    pub fn should_touch(&self, now: DateTime<Utc>, ip: &str) -> bool {
+        let interval = env::var("API_TOKEN_TOUCH_INTERVAL")
+            .unwrap_or("300".to_string())
+            .parse()
+            .unwrap_or(300);
+        match self.last_used_at {
+            None => true,
+            Some(last_used_at) => {
+                self.last_used_ip.as_deref() != Some(ip)
+                    || now - last_used_at > Duration::seconds(interval)
+            }
+        }
+    }
+}
+
+impl From<ApiToken> for ApiTokenInfo {
+    fn from(api_token: ApiToken) -> Self {
+        Self {
+            id: api_token.id,
+            name: api_token.name,
+            scopes: api_token.scopes,
+            created_at: api_token.created_at,
+            last_used_at: api_token.last_used_at,
+            last_used_ip: api_token.last_used_ip,
+            expires_at: api_token.expires_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scopes_expiry_and_touch() {
+        let now = Utc::now();
+        let mut api_token = ApiToken {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            token: Secret::from(Uuid::new_v4()),
+            status: ApiTokenStatus::Active,
+            name: DEFAULT_API_TOKEN_NAME.to_string(),
+            scopes: ApiTokenScope::device()
+                .iter()
+                .map(|scope| scope.to_string())
+                .collect(),
+            last_used_at: None,
+            last_used_ip: None,
+            expires_at: None,
+            revoked_at: None,
+            created_at: now,
+        };
+        assert!(api_token.has_scope(ApiTokenScope::NodeReport));
+        assert!(!api_token.has_scope(ApiTokenScope::TaskCreate));
+        assert_eq!(
+            ApiTokenScope::from_str("task-create").unwrap(),
+            ApiTokenScope::TaskCreate
+        );
+        assert!(ApiTokenScope::from_str("admin").is_err());
+
+        assert!(!api_token.is_expired(now));
+        api_token.expires_at = Some(now);
+        assert!(api_token.is_expired(now));
+
+        assert!(api_token.should_touch(now, "1.1.1.1"));
+        api_token.last_used_at = Some(now);
+        api_token.last_used_ip = Some("1.1.1.1".to_string());
+        assert!(!api_token.should_touch(now, "1.1.1.1"));
+        assert!(api_token.should_touch(now, "2.2.2.2"));
+        assert!(api_token.should_touch(now + Duration::hours(1), "1.1.1.1"));
+    }
+}
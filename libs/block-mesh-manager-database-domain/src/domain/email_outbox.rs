@@ -0,0 +1,109 @@
+use block_mesh_common::email::kind::EmailKind;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::types::Json;
+use sqlx::{Decode, PgExecutor, Postgres};
+use std::error::Error;
+use std::fmt::Display;
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum EmailOutboxStatus {
+    Pending,
+    Sent,
+    Failed,
+}
+
+impl Display for EmailOutboxStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EmailOutboxStatus::Pending => write!(f, "Pending"),
+            EmailOutboxStatus::Sent => write!(f, "Sent"),
+            EmailOutboxStatus::Failed => write!(f, "Failed"),
+        }
+    }
+}
+
+impl From<String> for EmailOutboxStatus {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "Pending" => EmailOutboxStatus::Pending,
+            "Sent" => EmailOutboxStatus::Sent,
+            "Failed" => EmailOutboxStatus::Failed,
+            _ => EmailOutboxStatus::Pending,
+        }
+    }
+}
+
+impl sqlx::Type<Postgres> for EmailOutboxStatus {
+    fn type_info() -> sqlx::postgres::PgTypeInfo {
+        <String as sqlx::Type<Postgres>>::type_info()
+    }
+}
+
+impl sqlx::Encode<'_, Postgres> for EmailOutboxStatus {
+    fn encode_by_ref(
+        &self,
+        buf: &mut <Postgres as sqlx::database::HasArguments<'_>>::ArgumentBuffer,
+    ) -> sqlx::encode::IsNull {
+        <String as sqlx::Encode<Postgres>>::encode(self.to_string(), buf)
+    }
+}
+
+impl sqlx::Decode<'_, Postgres> for EmailOutboxStatus {
+    fn decode(
+        value: <Postgres as sqlx::database::HasValueRef<'_>>::ValueRef,
+    ) -> Result<Self, Box<dyn Error + 'static + Send + Sync>> {
+        let value = <&str as Decode<Postgres>>::decode(value)?;
+        let value = value.to_string();
+        Ok(Self::from(value))
+    }
+}
+
+/// An email waiting for the worker to render and deliver it.
+/// `attempts` counts delivery tries, a claimed row is leased by pushing
+/// `next_attempt_at` forward so a crashed worker's rows are picked up again.
+#[derive(sqlx::FromRow, Debug, Serialize, Deserialize, Clone)]
+pub struct EmailOutbox {
+    pub id: Uuid,
+    pub kind: String,
+    pub to_address: String,
+    pub locale: String,
+    pub action_url: String,
+    pub params: Json<Value>,
+    pub status: EmailOutboxStatus,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub next_attempt_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub sent_at: Option<DateTime<Utc>>,
+}
+
+#[tracing::instrument(name = "enqueue_email", skip_all, err)]
+pub async fn enqueue_email(
+    executor: impl PgExecutor<'_>,
+    kind: EmailKind,
+    to_address: &str,
+    locale: &str,
+    action_url: &str,
+    params: &Value,
+) -> anyhow::Result<Uuid> {
+    let id = Uuid::new_v4();
+    sqlx::query!(
+        r#"
+        INSERT INTO email_outbox (id, kind, to_address, locale, action_url, params, status, attempts, next_attempt_at, created_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, 0, now(), now())
+        "#,
+        id,
+        kind.to_string(),
+        to_address,
+        locale,
+        action_url,
+        params,
+        EmailOutboxStatus::Pending.to_string()
+    )
+    .execute(executor)
+    .await?;
+    Ok(id)
+}
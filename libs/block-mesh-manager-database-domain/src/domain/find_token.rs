@@ -1,24 +1,42 @@
-use crate::domain::api_token::{ApiToken, ApiTokenStatus};
+use crate::domain::api_token::{ApiToken, ApiTokenScope, ApiTokenStatus};
+use chrono::Utc;
 use secret::Secret;
 use sqlx::{Postgres, Transaction};
 use uuid::Uuid;
 
+/// Active, unexpired token carrying `scope`. A token without the scope is treated as not found.
 #[tracing::instrument(name = "find_token", skip_all)]
 pub async fn find_token(
     transaction: &mut Transaction<'_, Postgres>,
     token: &Uuid,
+    scope: ApiTokenScope,
 ) -> anyhow::Result<Option<ApiToken>> {
     Ok(sqlx::query_as!(
         ApiToken,
-        r#"SELECT
-        id,
-        created_at,
-        user_id,
-        token as "token: Secret<Uuid>",
-        status as "status: ApiTokenStatus"
-        FROM api_tokens WHERE token = $1 and status = $2 LIMIT 1"#,
+        r#"
+        SELECT
+            id,
+            created_at,
+            user_id,
+            token AS "token: Secret<Uuid>",
+            status AS "status: ApiTokenStatus",
+            name,
+            scopes,
+            last_used_at,
+            last_used_ip,
+            expires_at,
+            revoked_at
+        FROM api_tokens
+        WHERE token = $1
+        AND status = $2
+        AND $3 = ANY(scopes)
+        AND (expires_at IS NULL OR expires_at > $4)
+        LIMIT 1
+        "#,
         token,
-        ApiTokenStatus::Active.to_string()
+        ApiTokenStatus::Active.to_string(),
+        scope.to_string(),
+        Utc::now()
     )
     .fetch_optional(&mut **transaction)
     .await?)
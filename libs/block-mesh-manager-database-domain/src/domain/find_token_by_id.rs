@@ -0,0 +1,49 @@
+use crate::domain::api_token::{ApiToken, ApiTokenScope, ApiTokenStatus};
+use chrono::Utc;
+use secret::Secret;
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+/// Same as [`crate::domain::find_token::find_token`] by row id, used to check that the token a
+/// session ticket was issued for is still active and its owner was not deleted.
+#[tracing::instrument(name = "find_token_by_id", skip(transaction))]
+pub async fn find_token_by_id(
+    transaction: &mut Transaction<'_, Postgres>,
+    id: &Uuid,
+    user_id: &Uuid,
+    scope: ApiTokenScope,
+) -> anyhow::Result<Option<ApiToken>> {
+    Ok(sqlx::query_as!(
+        ApiToken,
+        r#"
+        SELECT
+            api_tokens.id,
+            api_tokens.created_at,
+            api_tokens.user_id,
+            api_tokens.token AS "token: Secret<Uuid>",
+            api_tokens.status AS "status: ApiTokenStatus",
+            api_tokens.name,
+            api_tokens.scopes,
+            api_tokens.last_used_at,
+            api_tokens.last_used_ip,
+            api_tokens.expires_at,
+            api_tokens.revoked_at
+        FROM api_tokens
+        JOIN users ON users.id = api_tokens.user_id
+        WHERE api_tokens.id = $1
+        AND api_tokens.user_id = $2
+        AND api_tokens.status = $3
+        AND $4 = ANY(api_tokens.scopes)
+        AND (api_tokens.expires_at IS NULL OR api_tokens.expires_at > $5)
+        AND users.deleted_at IS NULL
+        LIMIT 1
+        "#,
+        id,
+        user_id,
+        ApiTokenStatus::Active.to_string(),
+        scope.to_string(),
+        Utc::now()
+    )
+    .fetch_optional(&mut **transaction)
+    .await?)
+}
@@ -27,7 +27,9 @@ pub async fn finish_task(
         ip = $5,
         asn = $6,
         colo = $7,
-        response_time = $8
+        response_time = $8,
+        headers = NULL,
+        body = NULL
         WHERE id = $9"#,
         response_code,
         response_raw,
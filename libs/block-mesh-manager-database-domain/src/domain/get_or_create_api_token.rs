@@ -0,0 +1,101 @@
+use crate::domain::api_token::{max_active_api_tokens, ApiToken, ApiTokenScope, ApiTokenStatus};
+use chrono::Utc;
+use secret::Secret;
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+/// Active, unexpired token named `name`, minted with the device scopes when the user has none,
+/// so every device logging in with its own name gets its own revocable token.
+/// Fails instead of minting once the user holds [`max_active_api_tokens`] active tokens.
+#[tracing::instrument(name = "get_or_create_api_token", skip(transaction))]
+pub async fn get_or_create_api_token(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+    name: &str,
+) -> anyhow::Result<ApiToken> {
+    let now = Utc::now();
+    let existing = sqlx::query_as!(
+        ApiToken,
+        r#"
+        SELECT
+            id,
+            created_at,
+            user_id,
+            token AS "token: Secret<Uuid>",
+            status AS "status: ApiTokenStatus",
+            name,
+            scopes,
+            last_used_at,
+            last_used_ip,
+            expires_at,
+            revoked_at
+        FROM api_tokens
+        WHERE user_id = $1
+        AND name = $2
+        AND status = $3
+        AND (expires_at IS NULL OR expires_at > $4)
+        ORDER BY created_at DESC
+        LIMIT 1
+        "#,
+        user_id,
+        name,
+        ApiTokenStatus::Active.to_string(),
+        now
+    )
+    .fetch_optional(&mut **transaction)
+    .await?;
+    if let Some(existing) = existing {
+        return Ok(existing);
+    }
+    let active = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) AS "count!"
+        FROM api_tokens
+        WHERE user_id = $1
+        AND status = $2
+        AND (expires_at IS NULL OR expires_at > $3)
+        "#,
+        user_id,
+        ApiTokenStatus::Active.to_string(),
+        now
+    )
+    .fetch_one(&mut **transaction)
+    .await?;
+    anyhow::ensure!(
+        active < max_active_api_tokens(),
+        "User {} reached the limit of active api tokens",
+        user_id
+    );
+    let scopes: Vec<String> = ApiTokenScope::device()
+        .iter()
+        .map(|scope| scope.to_string())
+        .collect();
+    Ok(sqlx::query_as!(
+        ApiToken,
+        r#"
+        INSERT INTO api_tokens (id, created_at, token, status, user_id, name, scopes)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING
+            id,
+            created_at,
+            user_id,
+            token AS "token: Secret<Uuid>",
+            status AS "status: ApiTokenStatus",
+            name,
+            scopes,
+            last_used_at,
+            last_used_ip,
+            expires_at,
+            revoked_at
+        "#,
+        Uuid::new_v4(),
+        now,
+        Uuid::new_v4(),
+        ApiTokenStatus::Active.to_string(),
+        user_id,
+        name,
+        &scopes
+    )
+    .fetch_one(&mut **transaction)
+    .await?)
+}
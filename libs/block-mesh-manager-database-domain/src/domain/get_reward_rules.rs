@@ -0,0 +1,35 @@
+use crate::domain::reward_rules::{PerkRule, RewardCampaign, RewardRuleSet, RewardRules};
+use sqlx::types::Json;
+use sqlx::{Postgres, Transaction};
+use std::collections::HashMap;
+
+#[tracing::instrument(name = "get_reward_rules", skip_all, err)]
+pub async fn get_reward_rules(
+    transaction: &mut Transaction<'_, Postgres>,
+) -> anyhow::Result<RewardRules> {
+    let rule_sets = sqlx::query_as!(
+        RewardRuleSet,
+        r#"
+        SELECT
+        id, version, effective_from, uptime_factor, tasks_factor,
+        perks AS "perks: Json<HashMap<String, PerkRule>>", daily_cap, ip_daily_cap,
+        referral_rates, referral_min_uptime, referral_requires_verified_email, created_at
+        FROM reward_rules
+        ORDER BY effective_from, version
+        "#,
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+    let campaigns = sqlx::query_as!(
+        RewardCampaign,
+        r#"
+        SELECT
+        id, name, multiplier, starts_at, ends_at, created_at
+        FROM reward_campaigns
+        ORDER BY starts_at
+        "#,
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+    Ok(RewardRules::new(rule_sets, campaigns))
+}
@@ -0,0 +1,20 @@
+use crate::domain::get_user_opt_by_id::get_user_opt_by_id;
+use crate::domain::user::User;
+use sqlx::{Postgres, Transaction};
+
+#[tracing::instrument(name = "get_user_opt_by_wallet", skip_all)]
+pub async fn get_user_opt_by_wallet(
+    transaction: &mut Transaction<'_, Postgres>,
+    wallet_address: &str,
+) -> anyhow::Result<Option<User>> {
+    let user_id = sqlx::query_scalar!(
+        "SELECT id FROM users WHERE wallet_address = $1 LIMIT 1",
+        wallet_address
+    )
+    .fetch_optional(&mut **transaction)
+    .await?;
+    match user_id {
+        Some(user_id) => get_user_opt_by_id(transaction, &user_id).await,
+        None => Ok(None),
+    }
+}
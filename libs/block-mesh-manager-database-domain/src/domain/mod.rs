@@ -2,27 +2,40 @@ pub mod aggregate;
 pub mod api_token;
 pub mod create_daily_stat;
 pub mod daily_stat;
+pub mod email_outbox;
 pub mod fetch_latest_cron_settings;
 pub mod find_pending_tasks_with_limit;
 pub mod find_task_by_task_id_and_status;
 pub mod find_token;
+pub mod find_token_by_id;
 pub mod finish_task;
 pub mod get_daily_stat_of_user;
 pub mod get_or_create_aggregate_by_user_and_name;
+pub mod get_or_create_api_token;
+pub mod get_reward_rules;
 pub mod get_user_opt_by_email;
 pub mod get_user_opt_by_id;
+pub mod get_user_opt_by_wallet;
 pub mod increment_tasks_count;
 pub mod increment_uptime;
+pub mod node_auth;
 pub mod nonce;
+pub mod notification_preferences;
 pub mod notify_api;
 pub mod notify_worker;
 pub mod option_uuid;
+pub mod points_ledger;
 pub mod prep_user;
 pub mod report_uptime_content;
+pub mod reward_rules;
+pub mod siws_auth;
+pub mod siws_nonce;
 pub mod submit_bandwidth_content;
 pub mod submit_task_content;
+pub mod sybil_flag;
 pub mod task;
 pub mod task_limit;
+pub mod touch_api_token;
 pub mod update_aggregate;
 pub mod update_task_assigned;
 pub mod user;
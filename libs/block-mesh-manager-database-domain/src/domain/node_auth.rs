@@ -0,0 +1,181 @@
+use crate::domain::api_token::{ApiToken, ApiTokenScope};
+use crate::domain::find_token::find_token;
+use crate::domain::find_token_by_id::find_token_by_id;
+use crate::domain::get_user_opt_by_id::get_user_opt_by_id;
+use anyhow::anyhow;
+use axum::async_trait;
+use axum::extract::{FromRequestParts, Query};
+use block_mesh_common::session_ticket::{SessionTicket, SessionTicketError};
+use chrono::{DateTime, Utc};
+use http::header::AUTHORIZATION;
+use http::request::Parts;
+use http::StatusCode;
+use serde::Deserialize;
+use sqlx::{Postgres, Transaction};
+use std::env;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// Credentials of a node (CLI, extension, app) calling the node APIs or opening the websocket.
+#[derive(Debug, Clone)]
+pub enum NodeCredentials {
+    /// `Authorization: Bearer <api_token>`
+    ApiToken(Uuid),
+    /// `Authorization: Bearer <session ticket>` as returned by `/api/get_token` and
+    /// `/api/check_token`, or `?ticket=<session ticket>` where headers can't be set (browser websockets).
+    SessionTicket(SessionTicket),
+    /// Deprecated `?email=...&api_token=...`, ends up in access and CDN logs.
+    /// Only accepted while `ALLOW_QUERY_AUTH` is enabled.
+    Query { email: String, api_token: Uuid },
+}
+
+#[derive(Deserialize)]
+struct QueryCredentials {
+    email: String,
+    api_token: Uuid,
+}
+
+#[derive(Deserialize)]
+struct QueryTicket {
+    ticket: String,
+}
+
+pub fn query_auth_allowed() -> bool {
+    env::var("ALLOW_QUERY_AUTH")
+        .unwrap_or("false".to_string())
+        .parse()
+        .unwrap_or(false)
+}
+
+impl NodeCredentials {
+    pub fn from_authorization(value: &str) -> Result<Self, String> {
+        let value = value
+            .strip_prefix("Bearer ")
+            .ok_or("Unsupported authorization scheme")?
+            .trim();
+        if let Ok(api_token) = Uuid::from_str(value) {
+            return Ok(Self::ApiToken(api_token));
+        }
+        Self::from_ticket(value)
+    }
+
+    pub fn from_ticket(value: &str) -> Result<Self, String> {
+        let secret =
+            SessionTicket::secret_from_env().ok_or(SessionTicketError::Disabled.to_string())?;
+        SessionTicket::verify(value, &secret, Utc::now())
+            .map(Self::SessionTicket)
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for NodeCredentials
+where
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, String);
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        if let Some(value) = parts.headers.get(AUTHORIZATION) {
+            let value = value
+                .to_str()
+                .map_err(|_| (StatusCode::UNAUTHORIZED, "Invalid header".to_string()))?;
+            return Self::from_authorization(value).map_err(|e| (StatusCode::UNAUTHORIZED, e));
+        }
+        if let Ok(Query(query)) = Query::<QueryTicket>::try_from_uri(&parts.uri) {
+            return Self::from_ticket(&query.ticket).map_err(|e| (StatusCode::UNAUTHORIZED, e));
+        }
+        if query_auth_allowed() {
+            if let Ok(Query(query)) = Query::<QueryCredentials>::try_from_uri(&parts.uri) {
+                tracing::warn!("Deprecated query string authentication used");
+                return Ok(Self::Query {
+                    email: query.email,
+                    api_token: query.api_token,
+                });
+            }
+        }
+        Err((
+            StatusCode::UNAUTHORIZED,
+            "Missing authorization".to_string(),
+        ))
+    }
+}
+
+/// Node that passed [`authenticate_node`], with the api token it authenticated with or
+/// the session ticket was issued for.
+#[derive(Debug, Clone)]
+pub struct NodeIdentity {
+    pub user_id: Uuid,
+    pub api_token: ApiToken,
+}
+
+#[tracing::instrument(name = "authenticate_node", skip_all)]
+pub async fn authenticate_node(
+    transaction: &mut Transaction<'_, Postgres>,
+    credentials: &NodeCredentials,
+    scope: ApiTokenScope,
+) -> anyhow::Result<NodeIdentity> {
+    match credentials {
+        NodeCredentials::ApiToken(token) => {
+            let api_token = find_token(transaction, token, scope)
+                .await?
+                .ok_or(anyhow!("Api Token Not Found"))?;
+            Ok(NodeIdentity {
+                user_id: api_token.user_id,
+                api_token,
+            })
+        }
+        NodeCredentials::SessionTicket(ticket) => {
+            if !ticket.has_scope(&scope.to_string()) {
+                return Err(anyhow!("Session Ticket Missing Scope"));
+            }
+            // the signature only proves the ticket was issued, revocations and deleted accounts
+            // are only visible in the database
+            let api_token = find_token_by_id(transaction, &ticket.token_id, &ticket.user_id, scope)
+                .await?
+                .ok_or(anyhow!("Session Ticket Revoked"))?;
+            Ok(NodeIdentity {
+                user_id: api_token.user_id,
+                api_token,
+            })
+        }
+        NodeCredentials::Query { email, api_token } => {
+            let api_token = find_token(transaction, api_token, scope)
+                .await?
+                .ok_or(anyhow!("Api Token Not Found"))?;
+            let user = get_user_opt_by_id(transaction, &api_token.user_id)
+                .await?
+                .ok_or_else(|| anyhow!("User Not Found"))?;
+            if user.email.to_ascii_lowercase() != email.to_ascii_lowercase() {
+                return Err(anyhow!("User Not Found"));
+            }
+            Ok(NodeIdentity {
+                user_id: user.id,
+                api_token,
+            })
+        }
+    }
+}
+
+/// Signed session ticket and its expiry for a token with the `node-report` scope,
+/// `None` when the token can't report or tickets are disabled.
+pub fn issue_session_ticket(
+    user_id: Uuid,
+    token_id: Uuid,
+    scopes: &[String],
+) -> Option<(String, DateTime<Utc>)> {
+    if !scopes
+        .iter()
+        .any(|scope| scope == &ApiTokenScope::NodeReport.to_string())
+    {
+        return None;
+    }
+    let secret = SessionTicket::secret_from_env()?;
+    let ticket = SessionTicket::new(
+        user_id,
+        token_id,
+        scopes.to_vec(),
+        SessionTicket::ttl_from_env(),
+    );
+    Some((ticket.sign(&secret), ticket.expires_at))
+}
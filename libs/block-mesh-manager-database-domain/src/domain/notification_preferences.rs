@@ -0,0 +1,115 @@
+use crate::domain::nonce::Nonce;
+use anyhow::anyhow;
+use block_mesh_common::email::kind::EmailKind;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgExecutor;
+use std::fmt::Display;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// Events users can be notified about, each has its own opt-in column.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationKind {
+    NodeOffline,
+    NewReferral,
+    PerkEarned,
+    DailySummary,
+}
+
+impl NotificationKind {
+    pub fn email_kind(&self) -> EmailKind {
+        match self {
+            NotificationKind::NodeOffline => EmailKind::NodeOffline,
+            NotificationKind::NewReferral => EmailKind::NewReferral,
+            NotificationKind::PerkEarned => EmailKind::PerkEarned,
+            NotificationKind::DailySummary => EmailKind::DailySummary,
+        }
+    }
+
+    /// Preference column toggling this kind, only ever one of a fixed set of names.
+    pub fn column(&self) -> &'static str {
+        match self {
+            NotificationKind::NodeOffline => "node_offline",
+            NotificationKind::NewReferral => "new_referral",
+            NotificationKind::PerkEarned => "perk_earned",
+            NotificationKind::DailySummary => "daily_summary",
+        }
+    }
+}
+
+impl Display for NotificationKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.column())
+    }
+}
+
+impl FromStr for NotificationKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "node_offline" => Ok(NotificationKind::NodeOffline),
+            "new_referral" => Ok(NotificationKind::NewReferral),
+            "perk_earned" => Ok(NotificationKind::PerkEarned),
+            "daily_summary" => Ok(NotificationKind::DailySummary),
+            _ => Err(anyhow!("Unknown notification kind {}", s)),
+        }
+    }
+}
+
+/// `unsubscribe_token` authenticates the links in notification emails,
+/// `telegram_link_token` is the short lived start parameter of the bot deep link.
+#[derive(sqlx::FromRow, Debug, Serialize, Deserialize, Clone)]
+pub struct NotificationPreferences {
+    pub user_id: Uuid,
+    pub email_enabled: bool,
+    pub telegram_chat_id: Option<i64>,
+    pub node_offline: bool,
+    pub new_referral: bool,
+    pub perk_earned: bool,
+    pub daily_summary: bool,
+    pub offline_minutes: i32,
+    pub locale: String,
+    pub unsubscribe_token: String,
+    pub telegram_link_token: Option<String>,
+    pub telegram_link_expires_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl NotificationPreferences {
+    pub fn is_enabled(&self, kind: NotificationKind) -> bool {
+        match kind {
+            NotificationKind::NodeOffline => self.node_offline,
+            NotificationKind::NewReferral => self.new_referral,
+            NotificationKind::PerkEarned => self.perk_earned,
+            NotificationKind::DailySummary => self.daily_summary,
+        }
+    }
+}
+
+/// Rows are created lazily the first time they are needed, with every kind off
+/// until the user opts in from the notification settings.
+#[tracing::instrument(name = "get_or_create_notification_preferences", skip_all, err)]
+pub async fn get_or_create_notification_preferences(
+    executor: impl PgExecutor<'_>,
+    user_id: &Uuid,
+) -> anyhow::Result<NotificationPreferences> {
+    let preferences = sqlx::query_as!(
+        NotificationPreferences,
+        r#"
+        INSERT INTO notification_preferences (user_id, unsubscribe_token, created_at, updated_at)
+        VALUES ($1, $2, now(), now())
+        ON CONFLICT (user_id) DO UPDATE SET user_id = EXCLUDED.user_id
+        RETURNING user_id, email_enabled, telegram_chat_id, node_offline, new_referral, perk_earned,
+                  daily_summary, offline_minutes, locale, unsubscribe_token, telegram_link_token,
+                  telegram_link_expires_at, created_at, updated_at
+        "#,
+        user_id,
+        Nonce::generate_nonce(48)
+    )
+    .fetch_one(executor)
+    .await?;
+    Ok(preferences)
+}
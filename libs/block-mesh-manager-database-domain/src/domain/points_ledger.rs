@@ -0,0 +1,107 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Decode, Postgres};
+use std::error::Error;
+use std::fmt::Display;
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum PointsSource {
+    Uptime,
+    Tasks,
+    PerkMultiplier,
+    PerkOneTimeBonus,
+    CampaignBoost,
+    DailyCap,
+    SybilPenalty,
+    Referral,
+    ManualAdjustment,
+    Reversal,
+    /// One-off credit for what the old dashboard total showed beyond the backfilled days.
+    Legacy,
+    Invalid,
+}
+
+impl Display for PointsSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PointsSource::Uptime => write!(f, "Uptime"),
+            PointsSource::Tasks => write!(f, "Tasks"),
+            PointsSource::PerkMultiplier => write!(f, "PerkMultiplier"),
+            PointsSource::PerkOneTimeBonus => write!(f, "PerkOneTimeBonus"),
+            PointsSource::CampaignBoost => write!(f, "CampaignBoost"),
+            PointsSource::DailyCap => write!(f, "DailyCap"),
+            PointsSource::SybilPenalty => write!(f, "SybilPenalty"),
+            PointsSource::Referral => write!(f, "Referral"),
+            PointsSource::ManualAdjustment => write!(f, "ManualAdjustment"),
+            PointsSource::Reversal => write!(f, "Reversal"),
+            PointsSource::Legacy => write!(f, "Legacy"),
+            PointsSource::Invalid => write!(f, "Invalid"),
+        }
+    }
+}
+
+impl From<String> for PointsSource {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "Uptime" => PointsSource::Uptime,
+            "Tasks" => PointsSource::Tasks,
+            "PerkMultiplier" => PointsSource::PerkMultiplier,
+            "PerkOneTimeBonus" => PointsSource::PerkOneTimeBonus,
+            "CampaignBoost" => PointsSource::CampaignBoost,
+            "DailyCap" => PointsSource::DailyCap,
+            "SybilPenalty" => PointsSource::SybilPenalty,
+            "Referral" => PointsSource::Referral,
+            "ManualAdjustment" => PointsSource::ManualAdjustment,
+            "Reversal" => PointsSource::Reversal,
+            "Legacy" => PointsSource::Legacy,
+            _ => PointsSource::Invalid,
+        }
+    }
+}
+
+impl sqlx::Type<Postgres> for PointsSource {
+    fn type_info() -> sqlx::postgres::PgTypeInfo {
+        <String as sqlx::Type<Postgres>>::type_info()
+    }
+}
+
+impl sqlx::Encode<'_, Postgres> for PointsSource {
+    fn encode_by_ref(
+        &self,
+        buf: &mut <Postgres as sqlx::database::HasArguments<'_>>::ArgumentBuffer,
+    ) -> sqlx::encode::IsNull {
+        <String as sqlx::Encode<Postgres>>::encode(self.to_string(), buf)
+    }
+}
+
+impl sqlx::Decode<'_, Postgres> for PointsSource {
+    fn decode(
+        value: <Postgres as sqlx::database::HasValueRef<'_>>::ValueRef,
+    ) -> Result<Self, Box<dyn Error + 'static + Send + Sync>> {
+        let value = <&str as Decode<Postgres>>::decode(value)?;
+        let value = value.to_string();
+        Ok(Self::from(value))
+    }
+}
+
+/// A single append-only credit (or debit) of points.
+/// `points` is what counts towards the user's balance, `base_points` and `multiplier`
+/// record how it was derived: perk, campaign and sybil entries adjust by
+/// `base_points * (multiplier - 1)`, every other entry is `base_points * multiplier`.
+/// Reversals point back at the entry they cancel via `reverses_id`.
+#[derive(sqlx::FromRow, Debug, Serialize, Deserialize, Clone)]
+pub struct PointsLedgerEntry {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub day: NaiveDate,
+    pub source: PointsSource,
+    pub source_id: Option<Uuid>,
+    pub base_points: f64,
+    pub multiplier: f64,
+    pub points: f64,
+    pub reverses_id: Option<Uuid>,
+    pub note: Option<String>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
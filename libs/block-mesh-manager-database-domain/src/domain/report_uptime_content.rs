@@ -1,10 +1,10 @@
 use crate::domain::aggregate::AggregateName;
 use crate::domain::create_daily_stat::create_daily_stat;
-use crate::domain::find_token::find_token;
 use crate::domain::get_daily_stat_of_user::get_daily_stat_of_user;
 use crate::domain::get_or_create_aggregate_by_user_and_name::get_or_create_aggregate_by_user_and_name;
-use crate::domain::get_user_opt_by_id::get_user_opt_by_id;
+use crate::domain::node_auth::NodeIdentity;
 use crate::domain::notify_worker::notify_worker;
+use crate::domain::touch_api_token::touch_api_token;
 use anyhow::{anyhow, Error};
 use axum::extract::Request;
 use axum::Json;
@@ -12,7 +12,7 @@ use block_mesh_common::interfaces::db_messages::{
     AggregateMessage, AnalyticsMessage, DBMessageTypes, DailyStatMessage, UsersIpMessage,
 };
 use block_mesh_common::interfaces::server_api::{
-    ClientsMetadata, HandlerMode, ReportUptimeRequest, ReportUptimeResponse,
+    ClientsMetadata, HandlerMode, ReportUptimeResponse,
 };
 use chrono::Utc;
 use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
@@ -61,6 +61,7 @@ async fn send_analytics(
                         depin_aggregator: metadata.depin_aggregator.unwrap_or_default(),
                         version: metadata.version.unwrap_or_default(),
                         device_type: metadata.device_type,
+                        device_id: metadata.device_id,
                     },
                 )
                 .await;
@@ -87,31 +88,23 @@ async fn send_message_to_touch_users_ip(pool: &PgPool, ip: String, user_id: &Uui
 pub async fn report_uptime_content(
     pool: &PgPool,
     ip: String,
-    query: ReportUptimeRequest,
+    identity: &NodeIdentity,
     request: Option<Request>,
     mode: HandlerMode,
     polling_interval: f64,
     interval_factor: f64,
 ) -> Result<Json<ReportUptimeResponse>, Error> {
     let mut transaction = create_txn(pool).await?;
-    let api_token = find_token(&mut transaction, &query.api_token)
-        .await?
-        .ok_or(anyhow!("Api Token Not Found"))?;
-    let user = get_user_opt_by_id(&mut transaction, &api_token.user_id)
-        .await?
-        .ok_or_else(|| anyhow!("User Not Found"))?;
+    let user_id = identity.user_id;
+    touch_api_token(&mut transaction, &identity.api_token, &ip).await?;
 
-    if user.email.to_ascii_lowercase() != query.email.to_ascii_lowercase() {
-        return Err(anyhow!("User Not Found"));
-    }
-
-    let _ = create_daily_stat(&mut transaction, &user.id).await;
-    let daily_stat = get_daily_stat_of_user(&mut transaction, user.id).await?;
-    let _ = send_analytics(pool, request, &user.id).await;
-    send_message_to_touch_users_ip(pool, ip.clone(), &user.id).await;
+    let _ = create_daily_stat(&mut transaction, &user_id).await;
+    let daily_stat = get_daily_stat_of_user(&mut transaction, user_id).await?;
+    let _ = send_analytics(pool, request, &user_id).await;
+    send_message_to_touch_users_ip(pool, ip.clone(), &user_id).await;
 
     let uptime =
-        get_or_create_aggregate_by_user_and_name(&mut transaction, AggregateName::Uptime, &user.id)
+        get_or_create_aggregate_by_user_and_name(&mut transaction, AggregateName::Uptime, &user_id)
             .await
             .map_err(Error::from)?;
     commit_txn(transaction).await?;
@@ -0,0 +1,293 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PerkRule {
+    pub multiplier: f64,
+    pub one_time_bonus: f64,
+}
+
+/// A versioned set of reward rules, active from `effective_from` until the next version.
+#[derive(sqlx::FromRow, Debug, Serialize, Deserialize, Clone)]
+pub struct RewardRuleSet {
+    pub id: Uuid,
+    pub version: i32,
+    pub effective_from: NaiveDate,
+    pub uptime_factor: f64,
+    pub tasks_factor: f64,
+    pub perks: Json<HashMap<String, PerkRule>>,
+    pub daily_cap: Option<f64>,
+    /// Points per day that can be earned from a single IP, split across the accounts seen on it.
+    pub ip_daily_cap: Option<f64>,
+    /// Share of a referral's daily points credited to the referrer, index 0 is a direct referral.
+    pub referral_rates: Vec<f64>,
+    /// Seconds of uptime a referral needs on a day for the day to earn commission.
+    pub referral_min_uptime: f64,
+    pub referral_requires_verified_email: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A time boxed multiplier applied on top of perks for every day overlapping the window.
+#[derive(sqlx::FromRow, Debug, Serialize, Deserialize, Clone)]
+pub struct RewardCampaign {
+    pub id: Uuid,
+    pub name: String,
+    pub multiplier: f64,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl RewardCampaign {
+    pub fn is_active_on(&self, day: NaiveDate) -> bool {
+        self.starts_at.date_naive() <= day && day <= self.ends_at.date_naive()
+    }
+}
+
+/// How a day's points were derived, each part maps to one ledger entry.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PointsBreakdown {
+    pub uptime_points: f64,
+    pub tasks_points: f64,
+    pub perk_multiplier: f64,
+    pub perk_bonus: f64,
+    pub campaign_multiplier: f64,
+    pub campaign_bonus: f64,
+    /// Zero or negative, the amount removed by the daily or IP caps.
+    pub cap_adjustment: f64,
+}
+
+impl PointsBreakdown {
+    pub fn base(&self) -> f64 {
+        self.uptime_points + self.tasks_points
+    }
+
+    pub fn total(&self) -> f64 {
+        self.base() + self.perk_bonus + self.campaign_bonus + self.cap_adjustment
+    }
+}
+
+impl RewardRuleSet {
+    /// Multiplier for a perk, the rule set wins over the value stored on the perk itself.
+    pub fn perk_multiplier(&self, name: &str, stored: f64) -> f64 {
+        self.perks
+            .get(name)
+            .map(|rule| rule.multiplier)
+            .unwrap_or(stored)
+    }
+
+    pub fn perk_rule(&self, name: &str) -> PerkRule {
+        self.perks.get(name).cloned().unwrap_or(PerkRule {
+            multiplier: 1.0,
+            one_time_bonus: 0.0,
+        })
+    }
+
+    /// Commission for the referrer `level` steps above the account that earned `points`,
+    /// `None` when that level is not rewarded or the referral's day does not qualify.
+    pub fn referral_commission(
+        &self,
+        level: usize,
+        points: f64,
+        uptime: f64,
+        verified_email: bool,
+    ) -> Option<f64> {
+        let rate = *self.referral_rates.get(level.checked_sub(1)?)?;
+        if rate <= 0.0
+            || points <= 0.0
+            || uptime < self.referral_min_uptime
+            || (self.referral_requires_verified_email && !verified_email)
+        {
+            return None;
+        }
+        Some(points * rate)
+    }
+
+    /// `perks` are `(name, stored multiplier)` pairs, `accounts_on_ip` is the largest number
+    /// of accounts seen on any of the user's IPs that day.
+    pub fn evaluate<'a>(
+        &self,
+        uptime: f64,
+        tasks_count: i64,
+        perks: impl IntoIterator<Item = (&'a str, f64)>,
+        campaigns: &[&RewardCampaign],
+        accounts_on_ip: i64,
+    ) -> PointsBreakdown {
+        let uptime_points = uptime * self.uptime_factor;
+        let tasks_points = tasks_count as f64 * self.tasks_factor;
+        let base = uptime_points + tasks_points;
+        let perk_multiplier: f64 = perks
+            .into_iter()
+            .map(|(name, stored)| self.perk_multiplier(name, stored))
+            .product();
+        let campaign_multiplier: f64 = campaigns.iter().map(|c| c.multiplier).product();
+        let perk_bonus = base * (perk_multiplier - 1.0);
+        let campaign_bonus = base * perk_multiplier * (campaign_multiplier - 1.0);
+        let uncapped = base + perk_bonus + campaign_bonus;
+        let mut cap = f64::INFINITY;
+        if let Some(daily_cap) = self.daily_cap {
+            cap = cap.min(daily_cap);
+        }
+        if let Some(ip_daily_cap) = self.ip_daily_cap {
+            cap = cap.min(ip_daily_cap / accounts_on_ip.max(1) as f64);
+        }
+        PointsBreakdown {
+            uptime_points,
+            tasks_points,
+            perk_multiplier,
+            perk_bonus,
+            campaign_multiplier,
+            campaign_bonus,
+            cap_adjustment: (cap - uncapped).min(0.0),
+        }
+    }
+}
+
+/// All rule set versions and campaigns, used to evaluate any day with the rules active on it.
+#[derive(Debug, Clone, Default)]
+pub struct RewardRules {
+    pub rule_sets: Vec<RewardRuleSet>,
+    pub campaigns: Vec<RewardCampaign>,
+}
+
+impl RewardRules {
+    pub fn new(mut rule_sets: Vec<RewardRuleSet>, campaigns: Vec<RewardCampaign>) -> Self {
+        rule_sets.sort_by_key(|rule_set| (rule_set.effective_from, rule_set.version));
+        Self {
+            rule_sets,
+            campaigns,
+        }
+    }
+
+    pub fn for_day(&self, day: NaiveDate) -> Option<&RewardRuleSet> {
+        self.rule_sets
+            .iter()
+            .rev()
+            .find(|rule_set| rule_set.effective_from <= day)
+    }
+
+    pub fn campaigns_for_day(&self, day: NaiveDate) -> Vec<&RewardCampaign> {
+        self.campaigns
+            .iter()
+            .filter(|campaign| campaign.is_active_on(day))
+            .collect()
+    }
+
+    /// Perk values to award on `day`, a perk without a rule is neutral.
+    pub fn perk_rule(&self, day: NaiveDate, name: &str) -> PerkRule {
+        self.for_day(day)
+            .map(|rule_set| rule_set.perk_rule(name))
+            .unwrap_or(PerkRule {
+                multiplier: 1.0,
+                one_time_bonus: 0.0,
+            })
+    }
+
+    pub fn evaluate<'a>(
+        &self,
+        day: NaiveDate,
+        uptime: f64,
+        tasks_count: i64,
+        perks: impl IntoIterator<Item = (&'a str, f64)>,
+        accounts_on_ip: i64,
+    ) -> Option<PointsBreakdown> {
+        let rule_set = self.for_day(day)?;
+        Some(rule_set.evaluate(
+            uptime,
+            tasks_count,
+            perks,
+            &self.campaigns_for_day(day),
+            accounts_on_ip,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn rule_set(version: i32, effective_from: NaiveDate, tasks_factor: f64) -> RewardRuleSet {
+        RewardRuleSet {
+            id: Uuid::new_v4(),
+            version,
+            effective_from,
+            uptime_factor: 1.0,
+            tasks_factor,
+            perks: Json(HashMap::from([(
+                "wallet".to_string(),
+                PerkRule {
+                    multiplier: 2.0,
+                    one_time_bonus: 0.0,
+                },
+            )])),
+            daily_cap: None,
+            ip_daily_cap: None,
+            referral_rates: vec![0.1, 0.05],
+            referral_min_uptime: 3600.0,
+            referral_requires_verified_email: true,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_rule_set_for_day() {
+        let d1 = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let d2 = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+        let rules = RewardRules::new(vec![rule_set(2, d2, 20.0), rule_set(1, d1, 10.0)], vec![]);
+        assert_eq!(
+            rules
+                .for_day(NaiveDate::from_ymd_opt(2024, 3, 1).unwrap())
+                .unwrap()
+                .version,
+            1
+        );
+        assert_eq!(rules.for_day(d2).unwrap().version, 2);
+        assert!(rules
+            .for_day(NaiveDate::from_ymd_opt(2023, 1, 1).unwrap())
+            .is_none());
+    }
+
+    #[test]
+    fn test_evaluate_perks_campaign_and_caps() {
+        let day = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+        let mut set = rule_set(1, day, 10.0);
+        let campaign = RewardCampaign {
+            id: Uuid::new_v4(),
+            name: "launch".to_string(),
+            multiplier: 1.5,
+            starts_at: Utc.with_ymd_and_hms(2024, 5, 30, 0, 0, 0).unwrap(),
+            ends_at: Utc.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap(),
+            created_at: Utc::now(),
+        };
+        // base 100 + 10, wallet rule overrides the stored 1.1 with 2.0
+        let breakdown = set.evaluate(100.0, 1, [("wallet", 1.1)], &[&campaign], 1);
+        assert_eq!(breakdown.base(), 110.0);
+        assert_eq!(breakdown.perk_bonus, 110.0);
+        assert_eq!(breakdown.campaign_bonus, 110.0);
+        assert_eq!(breakdown.total(), 330.0);
+
+        set.daily_cap = Some(300.0);
+        set.ip_daily_cap = Some(400.0);
+        let breakdown = set.evaluate(100.0, 1, [("wallet", 1.1)], &[&campaign], 2);
+        assert_eq!(breakdown.cap_adjustment, -130.0);
+        assert_eq!(breakdown.total(), 200.0);
+    }
+
+    #[test]
+    fn test_referral_commission() {
+        let set = rule_set(1, NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(), 10.0);
+        assert_eq!(
+            set.referral_commission(1, 1000.0, 7200.0, true),
+            Some(100.0)
+        );
+        assert_eq!(set.referral_commission(2, 1000.0, 7200.0, true), Some(50.0));
+        assert_eq!(set.referral_commission(3, 1000.0, 7200.0, true), None);
+        assert_eq!(set.referral_commission(0, 1000.0, 7200.0, true), None);
+        assert_eq!(set.referral_commission(1, 1000.0, 60.0, true), None);
+        assert_eq!(set.referral_commission(1, 1000.0, 7200.0, false), None);
+    }
+}
@@ -0,0 +1,80 @@
+use crate::domain::api_token::ApiToken;
+use crate::domain::get_or_create_api_token::get_or_create_api_token;
+use crate::domain::get_user_opt_by_wallet::get_user_opt_by_wallet;
+use crate::domain::siws_nonce::consume_siws_nonce;
+use block_mesh_common::constants::BLOCK_MESH_APP_SERVER;
+use block_mesh_common::interfaces::server_api::SiwsSignIn;
+use block_mesh_common::siws::{verify_signature, SiwsError, SiwsMessage};
+use chrono::Utc;
+use sqlx::{Postgres, Transaction};
+use std::env;
+use uuid::Uuid;
+
+/// Domains a sign in message may be addressed to, `SIWS_DOMAINS` is a comma separated list.
+pub fn siws_domains() -> Vec<String> {
+    env::var("SIWS_DOMAINS")
+        .unwrap_or_else(|_| {
+            BLOCK_MESH_APP_SERVER
+                .split("://")
+                .last()
+                .unwrap_or_default()
+                .to_string()
+        })
+        .split(',')
+        .map(|domain| domain.trim().to_string())
+        .filter(|domain| !domain.is_empty())
+        .collect()
+}
+
+/// Verifies a signed sign in message and burns its nonce, returns the message on success.
+///
+/// The nonce is only consumed once the signature checks out, so a forged request can't burn
+/// the nonce of a legitimate one.
+#[tracing::instrument(name = "verify_siws", skip_all, fields(pubkey = pubkey))]
+pub async fn verify_siws(
+    transaction: &mut Transaction<'_, Postgres>,
+    pubkey: &str,
+    message: &str,
+    signature: &[u8],
+) -> anyhow::Result<Result<SiwsMessage, SiwsError>> {
+    let parsed = match SiwsMessage::parse(message) {
+        Ok(parsed) => parsed,
+        Err(e) => return Ok(Err(e)),
+    };
+    if let Err(e) = parsed.validate(&siws_domains(), pubkey, Utc::now()) {
+        return Ok(Err(e));
+    }
+    if let Err(e) = verify_signature(pubkey, message, signature) {
+        return Ok(Err(e));
+    }
+    if !consume_siws_nonce(&mut **transaction, &parsed.nonce).await? {
+        return Ok(Err(SiwsError::InvalidNonce));
+    }
+    Ok(Ok(parsed))
+}
+
+/// `/api/get_token` for nodes holding a wallet instead of a password, returns `None` when the
+/// sign in is invalid or no account is bound to the wallet.
+#[tracing::instrument(name = "get_token_with_siws", skip_all)]
+pub async fn get_token_with_siws(
+    transaction: &mut Transaction<'_, Postgres>,
+    sign_in: &SiwsSignIn,
+    token_name: &str,
+) -> anyhow::Result<Option<(Uuid, ApiToken)>> {
+    if let Err(e) = verify_siws(
+        transaction,
+        &sign_in.pubkey,
+        &sign_in.message,
+        &sign_in.signature,
+    )
+    .await?
+    {
+        tracing::warn!("Wallet sign in rejected for {}: {}", sign_in.pubkey, e);
+        return Ok(None);
+    }
+    let Some(user) = get_user_opt_by_wallet(transaction, &sign_in.pubkey).await? else {
+        return Ok(None);
+    };
+    let api_token = get_or_create_api_token(transaction, &user.id, token_name).await?;
+    Ok(Some((user.id, api_token)))
+}
@@ -0,0 +1,42 @@
+use crate::domain::nonce::Nonce;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::PgExecutor;
+
+const SIWS_NONCE_LEN: usize = 32;
+/// Time a client has to get the message signed after asking for a nonce.
+const SIWS_NONCE_TTL_MINUTES: i64 = 10;
+
+/// Issues a single use nonce for a Sign In With Solana message.
+#[tracing::instrument(name = "create_siws_nonce", skip_all)]
+pub async fn create_siws_nonce(
+    executor: impl PgExecutor<'_>,
+) -> sqlx::Result<(String, DateTime<Utc>)> {
+    let nonce = Nonce::generate_nonce(SIWS_NONCE_LEN);
+    let now = Utc::now();
+    let expires_at = now + Duration::minutes(SIWS_NONCE_TTL_MINUTES);
+    sqlx::query!(
+        r#"
+        WITH expired AS (DELETE FROM siws_nonces WHERE expires_at < $2)
+        INSERT INTO siws_nonces (nonce, created_at, expires_at) VALUES ($1, $2, $3)
+        "#,
+        &nonce,
+        now,
+        expires_at
+    )
+    .execute(executor)
+    .await?;
+    Ok((nonce, expires_at))
+}
+
+/// Burns the nonce, returns false when it was never issued, already used or expired.
+#[tracing::instrument(name = "consume_siws_nonce", skip_all)]
+pub async fn consume_siws_nonce(executor: impl PgExecutor<'_>, nonce: &str) -> sqlx::Result<bool> {
+    let result = sqlx::query!(
+        "DELETE FROM siws_nonces WHERE nonce = $1 AND expires_at > $2",
+        nonce,
+        Utc::now()
+    )
+    .execute(executor)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
@@ -1,4 +1,5 @@
 use crate::domain::aggregate::AggregateName;
+use crate::domain::api_token::ApiTokenScope;
 use crate::domain::find_token::find_token;
 use crate::domain::get_or_create_aggregate_by_user_and_name::get_or_create_aggregate_by_user_and_name;
 use crate::domain::get_user_opt_by_id::get_user_opt_by_id;
@@ -17,7 +18,7 @@ pub async fn submit_bandwidth_content(
     body: ReportBandwidthRequest,
 ) -> Result<Json<ReportBandwidthResponse>, Error> {
     let mut transaction = create_txn(pool).await?;
-    let api_token = find_token(&mut transaction, &body.api_token)
+    let api_token = find_token(&mut transaction, &body.api_token, ApiTokenScope::NodeReport)
         .await?
         .ok_or(anyhow!("Token Not Found"))?;
     let user = get_user_opt_by_id(&mut transaction, &api_token.user_id)
@@ -1,12 +1,11 @@
 use crate::domain::aggregate::AggregateName;
 use crate::domain::create_daily_stat::create_daily_stat;
 use crate::domain::find_task_by_task_id_and_status::find_task_by_task_id_and_status;
-use crate::domain::find_token::find_token;
 use crate::domain::finish_task::finish_task;
 use crate::domain::get_daily_stat_of_user::get_daily_stat_of_user;
 use crate::domain::get_or_create_aggregate_by_user_and_name::get_or_create_aggregate_by_user_and_name;
-use crate::domain::get_user_opt_by_id::get_user_opt_by_id;
 use crate::domain::increment_tasks_count::increment_tasks_count;
+use crate::domain::node_auth::NodeIdentity;
 use crate::domain::notify_worker::notify_worker;
 use crate::domain::task::TaskStatus;
 use anyhow::{anyhow, Error};
@@ -38,26 +37,18 @@ pub async fn extract_body(request: Request) -> anyhow::Result<String> {
 #[tracing::instrument(name = "submit_task_content", skip_all)]
 pub async fn submit_task_content(
     pool: &PgPool,
+    identity: &NodeIdentity,
     query: SubmitTaskRequest,
     request: Option<Request>,
     mode: HandlerMode,
 ) -> Result<Json<SubmitTaskResponse>, Error> {
     let mut transaction = create_txn(pool).await?;
-    let api_token = find_token(&mut transaction, &query.api_token)
-        .await?
-        .ok_or(anyhow!("Api Token Not Found".to_string()))?;
-    let user = get_user_opt_by_id(&mut transaction, &api_token.user_id)
-        .await?
-        .ok_or_else(|| anyhow!("User Not Found".to_string()))?;
-    if user.email.to_ascii_lowercase() != query.email.to_ascii_lowercase() {
-        commit_txn(transaction).await?;
-        return Err(anyhow!("User Not Found".to_string()));
-    }
+    let user_id = identity.user_id;
     let task =
         find_task_by_task_id_and_status(&mut transaction, &query.task_id, TaskStatus::Assigned)
             .await?
             .ok_or(anyhow!("Token Not Found".to_string()))?;
-    if task.assigned_user_id.is_some() && task.assigned_user_id.unwrap() != user.id {
+    if task.assigned_user_id.is_some() && task.assigned_user_id.unwrap() != user_id {
         commit_txn(transaction).await?;
         return Err(anyhow!("Task Assigned To Another User".to_string(),));
     }
@@ -95,8 +86,8 @@ pub async fn submit_task_content(
         query.response_time.unwrap_or_default(),
     )
     .await?;
-    let _ = create_daily_stat(&mut transaction, &user.id).await;
-    let daily_stat = get_daily_stat_of_user(&mut transaction, user.id).await?;
+    let _ = create_daily_stat(&mut transaction, &user_id).await;
+    let daily_stat = get_daily_stat_of_user(&mut transaction, user_id).await?;
     increment_tasks_count(&mut transaction, daily_stat.id).await?;
     commit_txn(transaction).await?;
 
@@ -105,7 +96,7 @@ pub async fn submit_task_content(
         let tasks = get_or_create_aggregate_by_user_and_name(
             &mut transaction,
             AggregateName::Tasks,
-            &user.id,
+            &user_id,
         )
         .await?;
         commit_txn(transaction).await?;
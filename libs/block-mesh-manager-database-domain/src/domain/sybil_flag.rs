@@ -0,0 +1,104 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::{Decode, Postgres};
+use std::error::Error;
+use std::fmt::Display;
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum SybilFlagStatus {
+    Pending,
+    Confirmed,
+    Cleared,
+}
+
+impl Display for SybilFlagStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SybilFlagStatus::Pending => write!(f, "Pending"),
+            SybilFlagStatus::Confirmed => write!(f, "Confirmed"),
+            SybilFlagStatus::Cleared => write!(f, "Cleared"),
+        }
+    }
+}
+
+impl From<String> for SybilFlagStatus {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "Pending" => SybilFlagStatus::Pending,
+            "Confirmed" => SybilFlagStatus::Confirmed,
+            "Cleared" => SybilFlagStatus::Cleared,
+            _ => SybilFlagStatus::Pending,
+        }
+    }
+}
+
+impl sqlx::Type<Postgres> for SybilFlagStatus {
+    fn type_info() -> sqlx::postgres::PgTypeInfo {
+        <String as sqlx::Type<Postgres>>::type_info()
+    }
+}
+
+impl sqlx::Encode<'_, Postgres> for SybilFlagStatus {
+    fn encode_by_ref(
+        &self,
+        buf: &mut <Postgres as sqlx::database::HasArguments<'_>>::ArgumentBuffer,
+    ) -> sqlx::encode::IsNull {
+        <String as sqlx::Encode<Postgres>>::encode(self.to_string(), buf)
+    }
+}
+
+impl sqlx::Decode<'_, Postgres> for SybilFlagStatus {
+    fn decode(
+        value: <Postgres as sqlx::database::HasValueRef<'_>>::ValueRef,
+    ) -> Result<Self, Box<dyn Error + 'static + Send + Sync>> {
+        let value = <&str as Decode<Postgres>>::decode(value)?;
+        let value = value.to_string();
+        Ok(Self::from(value))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SybilReason {
+    SharedIp,
+    SharedDevice,
+    ReferralChain,
+    DatacenterIp,
+    AnonymizedIp,
+    AbuserIp,
+}
+
+/// Result of the sybil scoring job for a single account.
+/// `weight` is the points multiplier while the flag waits for review,
+/// a confirmed flag removes the account's points and a cleared flag restores them.
+#[derive(sqlx::FromRow, Debug, Serialize, Deserialize, Clone)]
+pub struct SybilFlag {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub cluster_id: Option<Uuid>,
+    pub score: f64,
+    pub weight: f64,
+    pub reasons: Json<Vec<SybilReason>>,
+    pub status: SybilFlagStatus,
+    pub reviewed_by: Option<Uuid>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl SybilFlagStatus {
+    pub fn effective_weight(&self, weight: f64) -> f64 {
+        match self {
+            SybilFlagStatus::Pending => weight.clamp(0.0, 1.0),
+            SybilFlagStatus::Confirmed => 0.0,
+            SybilFlagStatus::Cleared => 1.0,
+        }
+    }
+}
+
+impl SybilFlag {
+    pub fn effective_weight(&self) -> f64 {
+        self.status.effective_weight(self.weight)
+    }
+}
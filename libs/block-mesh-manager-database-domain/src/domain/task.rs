@@ -133,6 +133,33 @@ pub struct Task {
     pub response_time: f64,
 }
 
+/// Headers that carry the submitter's credentials and must never reach a node
+/// or be rendered back from the tasks table.
+pub const CREDENTIAL_HEADERS: [&str; 6] = [
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "x-api-key",
+    "x-goog-api-key",
+    "api-key",
+];
+
+/// Drops [`CREDENTIAL_HEADERS`] (case-insensitively) from a task's headers object.
+pub fn without_credential_headers(headers: Option<Value>) -> Option<Value> {
+    match headers {
+        Some(Value::Object(map)) => Some(Value::Object(
+            map.into_iter()
+                .filter(|(name, _)| {
+                    !CREDENTIAL_HEADERS
+                        .iter()
+                        .any(|secret| name.eq_ignore_ascii_case(secret))
+                })
+                .collect(),
+        )),
+        other => other,
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct GetTask {
     pub id: Uuid,
@@ -141,3 +168,28 @@ pub struct GetTask {
     pub headers: Option<Value>,
     pub body: Option<Value>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_without_credential_headers() {
+        let headers = json!({
+            "Authorization": "Bearer sk-secret",
+            "x-api-key": "secret",
+            "X-Goog-Api-Key": "secret",
+            "content-type": "application/json"
+        });
+        assert_eq!(
+            without_credential_headers(Some(headers)),
+            Some(json!({ "content-type": "application/json" }))
+        );
+        assert_eq!(without_credential_headers(None), None);
+        assert_eq!(
+            without_credential_headers(Some(Value::Null)),
+            Some(Value::Null)
+        );
+    }
+}
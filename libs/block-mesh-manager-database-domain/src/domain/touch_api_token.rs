@@ -0,0 +1,28 @@
+use crate::domain::api_token::ApiToken;
+use chrono::Utc;
+use sqlx::{Postgres, Transaction};
+
+#[tracing::instrument(name = "touch_api_token", skip_all)]
+pub async fn touch_api_token(
+    transaction: &mut Transaction<'_, Postgres>,
+    api_token: &ApiToken,
+    ip: &str,
+) -> anyhow::Result<()> {
+    let now = Utc::now();
+    if !api_token.should_touch(now, ip) {
+        return Ok(());
+    }
+    sqlx::query!(
+        r#"
+        UPDATE api_tokens
+        SET last_used_at = $2, last_used_ip = $3
+        WHERE id = $1
+        "#,
+        api_token.id,
+        now,
+        ip
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
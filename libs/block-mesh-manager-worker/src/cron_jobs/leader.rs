@@ -0,0 +1,37 @@
+use sqlx::{PgConnection, PgPool};
+
+/// Session level Postgres advisory lock, held for as long as the connection lives.
+/// Only one worker replica can hold the lock for a given job name at a time.
+/// The connection is detached from the pool, dropping it closes the session and releases the lock.
+pub struct LeaderLock {
+    name: &'static str,
+    connection: PgConnection,
+}
+
+impl LeaderLock {
+    #[tracing::instrument(name = "LeaderLock::try_acquire", skip(pool), err)]
+    pub async fn try_acquire(pool: &PgPool, name: &'static str) -> anyhow::Result<Option<Self>> {
+        let mut connection = pool.acquire().await?;
+        let acquired = sqlx::query_scalar!(
+            r#"SELECT pg_try_advisory_lock(hashtext($1)) AS "acquired!""#,
+            name
+        )
+        .fetch_one(&mut *connection)
+        .await?;
+        if !acquired {
+            return Ok(None);
+        }
+        let connection = connection.detach();
+        tracing::info!("acquired leadership for {}", name);
+        Ok(Some(Self { name, connection }))
+    }
+
+    /// Checks that the session holding the lock is still alive.
+    #[tracing::instrument(name = "LeaderLock::is_alive", skip(self), fields(name = self.name))]
+    pub async fn is_alive(&mut self) -> bool {
+        sqlx::query!("SELECT 1 AS alive")
+            .fetch_one(&mut self.connection)
+            .await
+            .is_ok()
+    }
+}
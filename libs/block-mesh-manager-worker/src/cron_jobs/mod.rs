@@ -1,4 +1,9 @@
 pub mod clean_old_tasks;
 pub mod finalize_daily_cron;
+pub mod leader;
+pub mod notifications_cron;
 pub mod rpc_cron;
+pub mod runner;
+pub mod schedule;
 pub mod special_task_cron;
+pub mod sybil_scoring_cron;
@@ -0,0 +1,57 @@
+use crate::db_calls::find_notification_candidates::{
+    find_daily_summaries, find_new_perks, find_new_referrals, find_offline_nodes,
+};
+use crate::notifier::Notifier;
+use block_mesh_manager_database_domain::domain::notification_preferences::NotificationKind;
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use std::sync::Arc;
+
+/// Looks for state changes since the last runs, the notifications table
+/// makes overlapping lookback windows safe.
+#[tracing::instrument(name = "notifications_cron", skip_all, err)]
+pub async fn notifications_cron(pool: PgPool, notifier: Arc<Notifier>) -> anyhow::Result<()> {
+    let lookback = notifier.config.lookback_minutes;
+    let detected = [
+        (
+            NotificationKind::NodeOffline,
+            find_offline_nodes(&pool, lookback).await?,
+        ),
+        (
+            NotificationKind::NewReferral,
+            find_new_referrals(&pool, lookback).await?,
+        ),
+        (
+            NotificationKind::PerkEarned,
+            find_new_perks(&pool, lookback).await?,
+        ),
+    ];
+    for (kind, candidates) in detected {
+        for candidate in candidates {
+            if let Err(e) = notifier.notify(&pool, kind, &candidate).await {
+                tracing::error!(
+                    "{} notification for {} failed: {}",
+                    kind,
+                    candidate.user_id,
+                    e
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Sends yesterday's ledger total to users who opted in.
+#[tracing::instrument(name = "daily_summary_cron", skip_all, err)]
+pub async fn daily_summary_cron(pool: PgPool, notifier: Arc<Notifier>) -> anyhow::Result<()> {
+    let day = (Utc::now() - Duration::days(1)).date_naive();
+    for candidate in find_daily_summaries(&pool, day).await? {
+        if let Err(e) = notifier
+            .notify(&pool, NotificationKind::DailySummary, &candidate)
+            .await
+        {
+            tracing::error!("daily summary for {} failed: {}", candidate.user_id, e);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use crate::cron_jobs::leader::LeaderLock;
+use crate::cron_jobs::schedule::CronSchedule;
+use crate::supervisor::{wait_for_shutdown, ShutdownRx};
+use anyhow::anyhow;
+use chrono::Utc;
+use rand::Rng;
+use sqlx::PgPool;
+use std::env;
+use std::future::Future;
+use std::time::Duration;
+
+pub fn schedule_from_env(envar: &str, default: &str) -> anyhow::Result<CronSchedule> {
+    env::var(envar)
+        .unwrap_or(default.to_string())
+        .parse()
+        .map_err(|e| anyhow!("{}: {}", envar, e))
+}
+
+/// Runs `job` on `schedule`, on a single replica at a time.
+/// Each tick is delayed by a random jitter of up to `CRON_MAX_JITTER_MS`
+/// so replicas don't hit the database at the exact same instant.
+#[tracing::instrument(name = "run_cron_job", skip(schedule, pool, shutdown, job))]
+pub async fn run_cron_job<F, Fut>(
+    name: &'static str,
+    schedule: CronSchedule,
+    pool: PgPool,
+    mut shutdown: ShutdownRx,
+    job: F,
+) -> anyhow::Result<()>
+where
+    F: Fn(PgPool) -> Fut,
+    Fut: Future<Output = anyhow::Result<()>>,
+{
+    let max_jitter: u64 = env::var("CRON_MAX_JITTER_MS")
+        .unwrap_or("5000".to_string())
+        .parse()
+        .unwrap_or(5_000);
+    let mut leader: Option<LeaderLock> = None;
+    loop {
+        let now = Utc::now();
+        let next = schedule
+            .next_after(now)
+            .ok_or_else(|| anyhow!("{} schedule never fires", name))?;
+        let jitter = Duration::from_millis(rand::thread_rng().gen_range(0..=max_jitter));
+        let wait = (next - now).to_std().unwrap_or_default() + jitter;
+        tokio::select! {
+            _ = tokio::time::sleep(wait) => {}
+            _ = wait_for_shutdown(&mut shutdown) => return Ok(()),
+        }
+        if let Some(lock) = leader.as_mut() {
+            if !lock.is_alive().await {
+                tracing::warn!("{} lost leadership", name);
+                leader = None;
+            }
+        }
+        if leader.is_none() {
+            leader = LeaderLock::try_acquire(&pool, name).await.unwrap_or(None);
+        }
+        if leader.is_none() {
+            tracing::trace!("{} skipped, not the leader", name);
+            continue;
+        }
+        if let Err(e) = job(pool.clone()).await {
+            tracing::error!("{} failed: {}", name, e);
+        }
+    }
+}
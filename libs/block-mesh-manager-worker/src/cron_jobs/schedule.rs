@@ -0,0 +1,172 @@
+use anyhow::anyhow;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Timelike, Utc};
+use std::str::FromStr;
+
+/// Cron expression evaluated in UTC.
+///
+/// Accepts the classic five fields (`min hour dom month dow`) or six fields with
+/// a leading seconds field. Each field supports `*`, values, `a-b` ranges, `/step`
+/// and comma separated lists. Day-of-week is `0-7` where both `0` and `7` are Sunday.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    seconds: u64,
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    dom_any: bool,
+    dow_any: bool,
+}
+
+fn parse_field(field: &str, min: u32, max: u32) -> anyhow::Result<u64> {
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>()?),
+            None => (part, 1),
+        };
+        if step == 0 {
+            return Err(anyhow!("invalid step in cron field '{}'", field));
+        }
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (start.parse::<u32>()?, end.parse::<u32>()?)
+        } else {
+            let value = range.parse::<u32>()?;
+            // `5/10` means starting at 5 every 10 until the end of the range
+            (value, if part.contains('/') { max } else { value })
+        };
+        if start < min || end > max || start > end {
+            return Err(anyhow!(
+                "cron field '{}' out of range {}-{}",
+                field,
+                min,
+                max
+            ));
+        }
+        let mut value = start;
+        while value <= end {
+            mask |= 1 << value;
+            value += step;
+        }
+    }
+    Ok(mask)
+}
+
+fn has(mask: u64, value: u32) -> bool {
+    mask & (1 << value) != 0
+}
+
+impl FromStr for CronSchedule {
+    type Err = anyhow::Error;
+
+    fn from_str(expression: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let fields = match fields.len() {
+            5 => [vec!["0"], fields].concat(),
+            6 => fields,
+            _ => return Err(anyhow!("invalid cron expression '{}'", expression)),
+        };
+        let mut days_of_week = parse_field(fields[5], 0, 7)?;
+        if has(days_of_week, 7) {
+            days_of_week = (days_of_week & !(1 << 7)) | 1;
+        }
+        Ok(Self {
+            seconds: parse_field(fields[0], 0, 59)?,
+            minutes: parse_field(fields[1], 0, 59)?,
+            hours: parse_field(fields[2], 0, 23)?,
+            days_of_month: parse_field(fields[3], 1, 31)?,
+            months: parse_field(fields[4], 1, 12)?,
+            days_of_week,
+            dom_any: fields[3] == "*",
+            dow_any: fields[5] == "*",
+        })
+    }
+}
+
+impl CronSchedule {
+    fn matches_day(&self, date: NaiveDate) -> bool {
+        if !has(self.months, date.month()) {
+            return false;
+        }
+        let dom = has(self.days_of_month, date.day());
+        let dow = has(self.days_of_week, date.weekday().num_days_from_sunday());
+        match (self.dom_any, self.dow_any) {
+            (true, true) => true,
+            (true, false) => dow,
+            (false, true) => dom,
+            (false, false) => dom || dow,
+        }
+    }
+
+    /// First firing time strictly after `after`, looking at most five years ahead.
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let start = after.with_nanosecond(0)? + Duration::seconds(1);
+        let mut date = start.date_naive();
+        for _ in 0..(366 * 5) {
+            if self.matches_day(date) {
+                for hour in (0..24).filter(|h| has(self.hours, *h)) {
+                    if date == start.date_naive() && hour < start.hour() {
+                        continue;
+                    }
+                    for minute in (0..60).filter(|m| has(self.minutes, *m)) {
+                        for second in (0..60).filter(|s| has(self.seconds, *s)) {
+                            let candidate = date.and_hms_opt(hour, minute, second)?.and_utc();
+                            if candidate >= start {
+                                return Some(candidate);
+                            }
+                        }
+                    }
+                }
+            }
+            date = date.succ_opt()?;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_every_hour() {
+        let schedule: CronSchedule = "0 * * * *".parse().unwrap();
+        let now = Utc.with_ymd_and_hms(2024, 11, 5, 10, 15, 30).unwrap();
+        assert_eq!(
+            schedule.next_after(now),
+            Some(Utc.with_ymd_and_hms(2024, 11, 5, 11, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_seconds_step() {
+        let schedule: CronSchedule = "*/30 * * * * *".parse().unwrap();
+        let now = Utc.with_ymd_and_hms(2024, 11, 5, 10, 15, 30).unwrap();
+        assert_eq!(
+            schedule.next_after(now),
+            Some(Utc.with_ymd_and_hms(2024, 11, 5, 10, 16, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_day_of_week_and_rollover() {
+        // Sundays at 00:05, 2024-11-05 is a Tuesday
+        let schedule: CronSchedule = "5 0 * * 7".parse().unwrap();
+        let now = Utc.with_ymd_and_hms(2024, 11, 5, 10, 15, 30).unwrap();
+        assert_eq!(
+            schedule.next_after(now),
+            Some(Utc.with_ymd_and_hms(2024, 11, 10, 0, 5, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!("* * *".parse::<CronSchedule>().is_err());
+        assert!("61 * * * *".parse::<CronSchedule>().is_err());
+        assert!("*/0 * * * *".parse::<CronSchedule>().is_err());
+    }
+}
@@ -0,0 +1,17 @@
+use crate::db_calls::enrich_ip_addresses::enrich_ip_addresses;
+use crate::db_calls::score_sybil_accounts::score_sybil_accounts;
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+use sqlx::PgPool;
+
+#[tracing::instrument(name = "enrich_ip_cron", level = "trace", skip(pool), err)]
+pub async fn enrich_ip_cron(pool: PgPool) -> Result<(), anyhow::Error> {
+    enrich_ip_addresses(&pool).await?;
+    Ok(())
+}
+
+#[tracing::instrument(name = "sybil_scoring_cron", level = "trace", skip(pool), err)]
+pub async fn sybil_scoring_cron(pool: PgPool) -> Result<(), anyhow::Error> {
+    let mut transaction = create_txn(&pool).await?;
+    score_sybil_accounts(&mut transaction).await?;
+    commit_txn(transaction).await
+}
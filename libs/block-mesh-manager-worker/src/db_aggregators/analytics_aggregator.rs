@@ -1,4 +1,5 @@
 use crate::db_calls::get_or_create_analytics::get_or_create_analytics;
+use crate::supervisor::{wait_for_shutdown, ShutdownRx};
 use anyhow::anyhow;
 use block_mesh_common::interfaces::db_messages::AnalyticsMessage;
 use chrono::Utc;
@@ -8,6 +9,29 @@ use sqlx::PgPool;
 use std::collections::HashMap;
 use tokio::sync::broadcast::error::RecvError;
 use tokio::sync::broadcast::Receiver;
+use uuid::Uuid;
+
+#[tracing::instrument(name = "analytics_flush", skip_all)]
+pub async fn analytics_flush(pool: &PgPool, calls: &HashMap<Uuid, AnalyticsMessage>) -> bool {
+    tracing::info!("analytics_aggregator starting txn");
+    if let Ok(mut transaction) = create_txn(pool).await {
+        for pair in calls.iter() {
+            let _ = get_or_create_analytics(
+                &mut transaction,
+                pair.0,
+                &pair.1.depin_aggregator,
+                &pair.1.device_type,
+                &pair.1.version,
+                pair.1.device_id,
+            )
+            .await;
+        }
+        let _ = commit_txn(transaction).await;
+        tracing::info!("analytics_aggregator finished txn");
+        return true;
+    }
+    false
+}
 
 #[tracing::instrument(name = "analytics_aggregator", skip_all, err)]
 pub async fn analytics_aggregator(
@@ -15,50 +39,48 @@ pub async fn analytics_aggregator(
     mut rx: Receiver<Value>,
     agg_size: i32,
     time_limit: i64,
+    mut shutdown: ShutdownRx,
 ) -> Result<(), anyhow::Error> {
     let mut calls: HashMap<_, _> = HashMap::new();
     let mut count = 0;
     let mut prev = Utc::now();
     loop {
-        match rx.recv().await {
-            Ok(message) => {
-                if let Ok(message) = serde_json::from_value::<AnalyticsMessage>(message) {
-                    calls.insert(message.user_id, message.clone());
-                    count += 1;
-                    let now = Utc::now();
-                    let diff = now - prev;
-                    let run = diff.num_seconds() > time_limit || count >= agg_size;
-                    prev = Utc::now();
-                    if run {
-                        tracing::info!("analytics_aggregator starting txn");
-                        if let Ok(mut transaction) = create_txn(&pool).await {
-                            for pair in calls.iter() {
-                                let _ = get_or_create_analytics(
-                                    &mut transaction,
-                                    pair.0,
-                                    &pair.1.depin_aggregator,
-                                    &pair.1.device_type,
-                                    &pair.1.version,
-                                )
-                                .await;
-                            }
-                            let _ = commit_txn(transaction).await;
+        tokio::select! {
+            message = rx.recv() => match message {
+                Ok(message) => {
+                    if let Ok(message) = serde_json::from_value::<AnalyticsMessage>(message) {
+                        calls.insert(message.user_id, message.clone());
+                        count += 1;
+                        let now = Utc::now();
+                        let diff = now - prev;
+                        let run = diff.num_seconds() > time_limit || count >= agg_size;
+                        prev = Utc::now();
+                        if run && analytics_flush(&pool, &calls).await {
                             count = 0;
                             calls.clear();
-                            tracing::info!("analytics_aggregator finished txn");
                         }
                     }
                 }
-            }
-            Err(e) => match e {
-                RecvError::Closed => {
-                    tracing::error!("analytics_aggregator error recv: {:?}", e);
-                    return Err(anyhow!("analytics_aggregator error recv: {:?}", e));
-                }
-                RecvError::Lagged(_) => {
-                    tracing::error!("analytics_aggregator error recv: {:?}", e);
-                }
+                Err(e) => match e {
+                    RecvError::Closed => {
+                        tracing::error!("analytics_aggregator error recv: {:?}", e);
+                        return Err(anyhow!("analytics_aggregator error recv: {:?}", e));
+                    }
+                    RecvError::Lagged(_) => {
+                        tracing::error!("analytics_aggregator error recv: {:?}", e);
+                    }
+                },
             },
+            _ = wait_for_shutdown(&mut shutdown) => {
+                while let Ok(message) = rx.try_recv() {
+                    if let Ok(message) = serde_json::from_value::<AnalyticsMessage>(message) {
+                        calls.insert(message.user_id, message);
+                    }
+                }
+                tracing::info!("analytics_aggregator draining {} calls", calls.len());
+                analytics_flush(&pool, &calls).await;
+                return Ok(());
+            }
         }
     }
 }
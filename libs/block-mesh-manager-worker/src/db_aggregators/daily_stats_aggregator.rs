@@ -1,3 +1,4 @@
+use crate::supervisor::{wait_for_shutdown, ShutdownRx};
 use anyhow::anyhow;
 use block_mesh_common::interfaces::db_messages::DailyStatMessage;
 use chrono::Utc;
@@ -35,6 +36,28 @@ WHERE daily_stats.id = updates.id;
     )
 }
 
+#[tracing::instrument(name = "daily_stats_flush", skip_all)]
+pub async fn daily_stats_flush(pool: PgPool, calls: HashMap<Uuid, f64>) {
+    tracing::info!("daily_stats_create_bulk_query starting txn");
+    if let Ok(mut transaction) = create_txn(&pool).await {
+        let query = daily_stats_create_bulk_query(calls);
+        let r = sqlx::query(&query)
+            .execute(&mut *transaction)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to execute query: {} , with error {:?}", query, e);
+            });
+        if let Ok(r) = r {
+            tracing::info!(
+                "daily_stats_create_bulk_query rows_affected : {}",
+                r.rows_affected()
+            );
+        }
+        let _ = commit_txn(transaction).await;
+        tracing::info!("daily_stats_create_bulk_query finished txn");
+    }
+}
+
 #[tracing::instrument(name = "daily_stats_aggregator", skip_all, err)]
 pub async fn daily_stats_aggregator(
     joiner_tx: Sender<JoinHandle<()>>,
@@ -42,62 +65,53 @@ pub async fn daily_stats_aggregator(
     mut rx: Receiver<Value>,
     agg_size: i32,
     time_limit: i64,
+    mut shutdown: ShutdownRx,
 ) -> Result<(), anyhow::Error> {
     let mut calls: HashMap<_, _> = HashMap::new();
     let mut count = 0;
     let mut prev = Utc::now();
     loop {
-        match rx.recv().await {
-            Ok(message) => {
-                if let Ok(message) = serde_json::from_value::<DailyStatMessage>(message) {
-                    calls.insert(message.id, message.uptime);
-                    count += 1;
-                    let now = Utc::now();
-                    let diff = now - prev;
-                    let run = diff.num_seconds() > time_limit || count >= agg_size;
-                    prev = Utc::now();
-                    if run {
-                        let calls_clone = calls.clone();
-                        let poll_clone = pool.clone();
-                        let handle = tokio::spawn(async move {
-                            tracing::info!("daily_stats_create_bulk_query starting txn");
-                            if let Ok(mut transaction) = create_txn(&poll_clone).await {
-                                let query = daily_stats_create_bulk_query(calls_clone);
-                                let r = sqlx::query(&query)
-                                    .execute(&mut *transaction)
-                                    .await
-                                    .map_err(|e| {
-                                        tracing::error!(
-                                            "Failed to execute query: {} , with error {:?}",
-                                            query,
-                                            e
-                                        );
-                                    });
-                                if let Ok(r) = r {
-                                    tracing::info!(
-                                        "daily_stats_create_bulk_query rows_affected : {}",
-                                        r.rows_affected()
-                                    );
-                                }
-                                let _ = commit_txn(transaction).await;
-                                tracing::info!("daily_stats_create_bulk_query finished txn");
-                            }
-                        });
-                        let _ = joiner_tx.send_async(handle).await;
-                        count = 0;
-                        calls.clear();
+        tokio::select! {
+            message = rx.recv() => match message {
+                Ok(message) => {
+                    if let Ok(message) = serde_json::from_value::<DailyStatMessage>(message) {
+                        calls.insert(message.id, message.uptime);
+                        count += 1;
+                        let now = Utc::now();
+                        let diff = now - prev;
+                        let run = diff.num_seconds() > time_limit || count >= agg_size;
+                        prev = Utc::now();
+                        if run {
+                            let handle = tokio::spawn(daily_stats_flush(pool.clone(), calls.clone()));
+                            let _ = joiner_tx.send_async(handle).await;
+                            count = 0;
+                            calls.clear();
+                        }
                     }
                 }
-            }
-            Err(e) => match e {
-                RecvError::Closed => {
-                    tracing::error!("daily_stats_aggregator error recv: {:?}", e);
-                    return Err(anyhow!("daily_stats_aggregator error recv: {:?}", e));
+                Err(e) => match e {
+                    RecvError::Closed => {
+                        tracing::error!("daily_stats_aggregator error recv: {:?}", e);
+                        return Err(anyhow!("daily_stats_aggregator error recv: {:?}", e));
+                    }
+                    RecvError::Lagged(_) => {
+                        tracing::error!("daily_stats_aggregator error recv: {:?}", e);
+                    }
+                },
+            },
+            _ = wait_for_shutdown(&mut shutdown) => {
+                while let Ok(message) = rx.try_recv() {
+                    if let Ok(message) = serde_json::from_value::<DailyStatMessage>(message) {
+                        calls.insert(message.id, message.uptime);
+                    }
                 }
-                RecvError::Lagged(_) => {
-                    tracing::error!("daily_stats_aggregator error recv: {:?}", e);
+                tracing::info!("daily_stats_aggregator draining {} calls", calls.len());
+                if !calls.is_empty() {
+                    let handle = tokio::spawn(daily_stats_flush(pool.clone(), calls));
+                    let _ = joiner_tx.send_async(handle).await;
                 }
-            },
+                return Ok(());
+            }
         }
     }
 }
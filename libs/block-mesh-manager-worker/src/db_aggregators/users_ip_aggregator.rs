@@ -1,3 +1,4 @@
+use crate::supervisor::{wait_for_shutdown, ShutdownRx};
 use anyhow::anyhow;
 use block_mesh_common::interfaces::db_messages::UsersIpMessage;
 use chrono::Utc;
@@ -118,42 +119,55 @@ pub async fn users_ip_aggregator(
     mut rx: Receiver<Value>,
     agg_size: i32,
     time_limit: i64,
+    mut shutdown: ShutdownRx,
 ) -> Result<(), anyhow::Error> {
     let mut calls: HashMap<_, _> = HashMap::new();
     let mut count = 0;
     let mut prev = Utc::now();
     loop {
-        match rx.recv().await {
-            Ok(message) => {
-                if let Ok(message) = serde_json::from_value::<UsersIpMessage>(message) {
-                    calls.insert(message.id, message.ip);
-                    count += 1;
-                    let now = Utc::now();
-                    let diff = now - prev;
-                    let run = diff.num_seconds() > time_limit || count >= agg_size;
-                    prev = Utc::now();
-                    if run {
-                        let calls_clone = calls.clone();
-                        let poll_clone = pool.clone();
-                        let handle = tokio::spawn(async move {
-                            let _ =
-                                ip_address_and_users_ip_bulk_query(&poll_clone, calls_clone).await;
-                        });
-                        let _ = joiner_tx.send_async(handle).await;
-                        count = 0;
-                        calls.clear();
+        tokio::select! {
+            message = rx.recv() => match message {
+                Ok(message) => {
+                    if let Ok(message) = serde_json::from_value::<UsersIpMessage>(message) {
+                        calls.insert(message.id, message.ip);
+                        count += 1;
+                        let now = Utc::now();
+                        let diff = now - prev;
+                        let run = diff.num_seconds() > time_limit || count >= agg_size;
+                        prev = Utc::now();
+                        if run {
+                            let calls_clone = calls.clone();
+                            let poll_clone = pool.clone();
+                            let handle = tokio::spawn(async move {
+                                let _ =
+                                    ip_address_and_users_ip_bulk_query(&poll_clone, calls_clone).await;
+                            });
+                            let _ = joiner_tx.send_async(handle).await;
+                            count = 0;
+                            calls.clear();
+                        }
                     }
                 }
-            }
-            Err(e) => match e {
-                RecvError::Closed => {
-                    tracing::error!("users_ip_aggregator error recv: {:?}", e);
-                    return Err(anyhow!("users_ip_aggregator error recv: {:?}", e));
-                }
-                RecvError::Lagged(_) => {
-                    tracing::error!("users_ip_aggregator error recv: {:?}", e);
-                }
+                Err(e) => match e {
+                    RecvError::Closed => {
+                        tracing::error!("users_ip_aggregator error recv: {:?}", e);
+                        return Err(anyhow!("users_ip_aggregator error recv: {:?}", e));
+                    }
+                    RecvError::Lagged(_) => {
+                        tracing::error!("users_ip_aggregator error recv: {:?}", e);
+                    }
+                },
             },
+            _ = wait_for_shutdown(&mut shutdown) => {
+                while let Ok(message) = rx.try_recv() {
+                    if let Ok(message) = serde_json::from_value::<UsersIpMessage>(message) {
+                        calls.insert(message.id, message.ip);
+                    }
+                }
+                tracing::info!("users_ip_aggregator draining {} calls", calls.len());
+                let _ = ip_address_and_users_ip_bulk_query(&pool, calls).await;
+                return Ok(());
+            }
         }
     }
 }
@@ -0,0 +1,92 @@
+use block_mesh_manager_database_domain::domain::task::TaskStatus;
+use chrono::{Duration, Utc};
+use sqlx::{Postgres, Transaction};
+use std::env;
+
+/// Fails tasks no node finished in time and drops their headers and body, so the
+/// submitter's request doesn't sit in the table until the daily cleanup.
+#[tracing::instrument(
+    name = "bulk_expire_tasks",
+    skip(transaction),
+    ret,
+    err,
+    level = "trace"
+)]
+pub async fn bulk_expire_tasks(transaction: &mut Transaction<'_, Postgres>) -> anyhow::Result<()> {
+    let expire_minutes = env::var("TASK_EXPIRE_MINUTES")
+        .unwrap_or("10".to_string())
+        .parse()
+        .unwrap_or(10);
+    let date = Utc::now() - Duration::minutes(expire_minutes);
+    sqlx::query!(
+        r#"
+        UPDATE tasks
+        SET
+        status = $1,
+        response_code = 520,
+        headers = NULL,
+        body = NULL
+        WHERE status IN ($2, $3) AND created_at < $4
+        "#,
+        TaskStatus::Failed.to_string(),
+        TaskStatus::Pending.to_string(),
+        TaskStatus::Assigned.to_string(),
+        date
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db_calls::test_support::insert_user;
+    use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+    use sqlx::PgPool;
+    use uuid::Uuid;
+
+    async fn insert_task(
+        pool: &PgPool,
+        user_id: Uuid,
+        status: TaskStatus,
+        minutes_ago: i64,
+    ) -> anyhow::Result<Uuid> {
+        let id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO tasks (id, user_id, url, method, headers, body, status, created_at) VALUES ($1, $2, 'https://example.com', 'POST', '{\"authorization\": \"Bearer sk\"}', '{\"prompt\": \"hi\"}', $3, $4)",
+        )
+        .bind(id)
+        .bind(user_id)
+        .bind(status.to_string())
+        .bind(Utc::now() - Duration::minutes(minutes_ago))
+        .execute(pool)
+        .await?;
+        Ok(id)
+    }
+
+    #[sqlx::test(migrations = "../block-mesh-manager/migrations")]
+    async fn test_expires_stale_tasks(pool: PgPool) -> anyhow::Result<()> {
+        let user_id = insert_user(&pool).await?;
+        let stale = insert_task(&pool, user_id, TaskStatus::Assigned, 60).await?;
+        let fresh = insert_task(&pool, user_id, TaskStatus::Pending, 1).await?;
+
+        let mut transaction = create_txn(&pool).await?;
+        bulk_expire_tasks(&mut transaction).await?;
+        commit_txn(transaction).await?;
+
+        let rows: Vec<(Uuid, String, Option<i32>, bool)> = sqlx::query_as(
+            "SELECT id, status, response_code, headers IS NULL AND body IS NULL FROM tasks",
+        )
+        .fetch_all(&pool)
+        .await?;
+        let stale_row = rows.iter().find(|row| row.0 == stale).unwrap();
+        assert_eq!(stale_row.1, TaskStatus::Failed.to_string());
+        assert_eq!(stale_row.2, Some(520));
+        assert!(stale_row.3);
+        let fresh_row = rows.iter().find(|row| row.0 == fresh).unwrap();
+        assert_eq!(fresh_row.1, TaskStatus::Pending.to_string());
+        assert!(!fresh_row.3);
+        Ok(())
+    }
+}
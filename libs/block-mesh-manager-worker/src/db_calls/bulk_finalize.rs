@@ -1,11 +1,89 @@
-use chrono::{Duration, Utc};
-use sqlx::{Postgres, Transaction};
+use block_mesh_manager_database_domain::domain::daily_stat::DailyStatStatus;
+use block_mesh_manager_database_domain::domain::get_reward_rules::get_reward_rules;
+use block_mesh_manager_database_domain::domain::points_ledger::PointsSource;
+use block_mesh_manager_database_domain::domain::sybil_flag::SybilFlagStatus;
+use chrono::{Duration, NaiveDate, Utc};
+use sqlx::{FromRow, Postgres, Transaction};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
 
+#[derive(FromRow, Debug)]
+struct FinalizedDailyStat {
+    id: Uuid,
+    user_id: Uuid,
+    day: NaiveDate,
+    uptime: f64,
+    tasks_count: i64,
+}
+
+#[derive(Default)]
+struct LedgerRows {
+    user_ids: Vec<Uuid>,
+    days: Vec<NaiveDate>,
+    sources: Vec<String>,
+    source_ids: Vec<Uuid>,
+    base_points: Vec<f64>,
+    multipliers: Vec<f64>,
+    points: Vec<f64>,
+    notes: Vec<String>,
+}
+
+impl LedgerRows {
+    fn push(
+        &mut self,
+        stat: &FinalizedDailyStat,
+        source: PointsSource,
+        base_points: f64,
+        multiplier: f64,
+        points: f64,
+        note: &str,
+    ) {
+        self.push_for(
+            stat.user_id,
+            stat,
+            source,
+            base_points,
+            multiplier,
+            points,
+            note,
+        );
+    }
+
+    /// Credits `user_id` for `stat`, used when the points go to someone other than its owner.
+    #[allow(clippy::too_many_arguments)]
+    fn push_for(
+        &mut self,
+        user_id: Uuid,
+        stat: &FinalizedDailyStat,
+        source: PointsSource,
+        base_points: f64,
+        multiplier: f64,
+        points: f64,
+        note: &str,
+    ) {
+        self.user_ids.push(user_id);
+        self.days.push(stat.day);
+        self.sources.push(source.to_string());
+        self.source_ids.push(stat.id);
+        self.base_points.push(base_points);
+        self.multipliers.push(multiplier);
+        self.points.push(points);
+        self.notes.push(note.to_string());
+    }
+}
+
+/// Finalizes past daily stats and credits their points to the ledger in the same transaction,
+/// evaluating each day with the reward rules that were active on it.
+/// Accounts with a sybil flag get a penalty entry scaling the day down to the flag's weight,
+/// and referrers up the invite tree are credited their commission on what remains.
+/// Deleted accounts have their days finalized but earn nothing, neither do they as referrers.
 #[tracing::instrument(name = "bulk_finalize", skip(transaction), ret, err, level = "trace")]
 pub async fn bulk_finalize(transaction: &mut Transaction<'_, Postgres>) -> anyhow::Result<()> {
     let now = Utc::now() - Duration::days(1);
     let day = now.date_naive();
-    sqlx::query!(
+    let rules = get_reward_rules(transaction).await?;
+    let finalized = sqlx::query_as!(
+        FinalizedDailyStat,
         r#"
         UPDATE
         daily_stats
@@ -17,12 +95,397 @@ pub async fn bulk_finalize(transaction: &mut Transaction<'_, Postgres>) -> anyho
             WHERE day < $2 AND status = $3
             LIMIT 10000
         )
+        RETURNING
+            id,
+            user_id,
+            day,
+            CAST(uptime AS DOUBLE PRECISION) AS "uptime!",
+            CAST(tasks_count AS BIGINT) AS "tasks_count!"
         "#,
-        "Finalized".to_string(),
+        DailyStatStatus::Finalized.to_string(),
         day,
-        "OnGoing".to_string()
+        DailyStatStatus::OnGoing.to_string()
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+    if finalized.is_empty() {
+        return Ok(());
+    }
+    let user_ids: Vec<Uuid> = finalized.iter().map(|i| i.user_id).collect();
+    let earliest_day = finalized.iter().map(|i| i.day).min().unwrap_or(day);
+
+    let deleted: HashSet<Uuid> = sqlx::query_scalar!(
+        r#"
+        SELECT id
+        FROM users
+        WHERE id = ANY($1) AND deleted_at IS NOT NULL
+        "#,
+        &user_ids
+    )
+    .fetch_all(&mut **transaction)
+    .await?
+    .into_iter()
+    .collect();
+
+    let perk_rows = sqlx::query!(
+        r#"
+        SELECT user_id, name, multiplier
+        FROM perks
+        WHERE user_id = ANY($1)
+        "#,
+        &user_ids
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+    let mut perks: HashMap<Uuid, Vec<(String, f64)>> = HashMap::new();
+    for perk in perk_rows {
+        perks
+            .entry(perk.user_id)
+            .or_default()
+            .push((perk.name, perk.multiplier));
+    }
+
+    // Largest number of accounts seen on any of the user's IPs since the finalized day
+    let accounts_on_ip: HashMap<Uuid, i64> = sqlx::query!(
+        r#"
+        SELECT mine.user_id, MAX(shared.accounts) AS "accounts!"
+        FROM users_ip mine
+        JOIN (
+            SELECT ip_id, COUNT(DISTINCT user_id) AS accounts
+            FROM users_ip
+            WHERE updated_at >= $2::date
+            GROUP BY ip_id
+        ) shared ON shared.ip_id = mine.ip_id
+        WHERE mine.user_id = ANY($1) AND mine.updated_at >= $2::date
+        GROUP BY mine.user_id
+        "#,
+        &user_ids,
+        earliest_day
+    )
+    .fetch_all(&mut **transaction)
+    .await?
+    .into_iter()
+    .map(|row| (row.user_id, row.accounts))
+    .collect();
+
+    let sybil_weights: HashMap<Uuid, f64> = sqlx::query!(
+        r#"
+        SELECT user_id, weight, status
+        FROM sybil_flags
+        WHERE user_id = ANY($1)
+        "#,
+        &user_ids
+    )
+    .fetch_all(&mut **transaction)
+    .await?
+    .into_iter()
+    .map(|row| {
+        let status = SybilFlagStatus::from(row.status);
+        (row.user_id, status.effective_weight(row.weight))
+    })
+    .collect();
+
+    let mut rows = LedgerRows::default();
+    let mut earned = Vec::with_capacity(finalized.len());
+    for stat in finalized
+        .iter()
+        .filter(|stat| !deleted.contains(&stat.user_id))
+    {
+        let Some(rule_set) = rules.for_day(stat.day) else {
+            tracing::error!(
+                "no reward rules active on {}, skipping {}",
+                stat.day,
+                stat.id
+            );
+            continue;
+        };
+        let user_perks = perks.get(&stat.user_id).cloned().unwrap_or_default();
+        let breakdown = rule_set.evaluate(
+            stat.uptime,
+            stat.tasks_count,
+            user_perks.iter().map(|(name, m)| (name.as_str(), *m)),
+            &rules.campaigns_for_day(stat.day),
+            accounts_on_ip.get(&stat.user_id).copied().unwrap_or(1),
+        );
+        let note = format!("rules v{}", rule_set.version);
+        rows.push(
+            stat,
+            PointsSource::Uptime,
+            breakdown.uptime_points,
+            1.0,
+            breakdown.uptime_points,
+            &note,
+        );
+        rows.push(
+            stat,
+            PointsSource::Tasks,
+            breakdown.tasks_points,
+            1.0,
+            breakdown.tasks_points,
+            &note,
+        );
+        if breakdown.perk_multiplier != 1.0 {
+            rows.push(
+                stat,
+                PointsSource::PerkMultiplier,
+                breakdown.base(),
+                breakdown.perk_multiplier,
+                breakdown.perk_bonus,
+                &note,
+            );
+        }
+        if breakdown.campaign_multiplier != 1.0 {
+            rows.push(
+                stat,
+                PointsSource::CampaignBoost,
+                breakdown.base() * breakdown.perk_multiplier,
+                breakdown.campaign_multiplier,
+                breakdown.campaign_bonus,
+                &note,
+            );
+        }
+        if breakdown.cap_adjustment < 0.0 {
+            rows.push(
+                stat,
+                PointsSource::DailyCap,
+                breakdown.cap_adjustment,
+                1.0,
+                breakdown.cap_adjustment,
+                &note,
+            );
+        }
+        let sybil_weight = sybil_weights.get(&stat.user_id).copied().unwrap_or(1.0);
+        if sybil_weight < 1.0 {
+            rows.push(
+                stat,
+                PointsSource::SybilPenalty,
+                breakdown.total(),
+                sybil_weight,
+                breakdown.total() * (sybil_weight - 1.0),
+                &note,
+            );
+        }
+        earned.push((stat, rule_set, breakdown.total() * sybil_weight));
+    }
+
+    // Referrers up the invite tree get a share of each finalized day that qualifies
+    let max_level = rules
+        .rule_sets
+        .iter()
+        .map(|rule_set| rule_set.referral_rates.len())
+        .max()
+        .unwrap_or_default() as i32;
+    if max_level > 0 {
+        let chain = sqlx::query!(
+            r#"
+            WITH RECURSIVE chain(user_id, referrer_id, level) AS (
+                SELECT id, invited_by, 1
+                FROM users
+                WHERE id = ANY($1) AND invited_by IS NOT NULL
+                UNION ALL
+                SELECT chain.user_id, users.invited_by, chain.level + 1
+                FROM chain
+                JOIN users ON users.id = chain.referrer_id
+                WHERE users.invited_by IS NOT NULL AND chain.level < $2
+            )
+            SELECT
+                chain.user_id AS "user_id!",
+                chain.referrer_id AS "referrer_id!",
+                chain.level AS "level!",
+                users.verified_email
+            FROM chain
+            JOIN users ON users.id = chain.user_id
+            JOIN users referrers ON referrers.id = chain.referrer_id
+            WHERE chain.referrer_id <> chain.user_id AND referrers.deleted_at IS NULL
+            "#,
+            &user_ids,
+            max_level
+        )
+        .fetch_all(&mut **transaction)
+        .await?;
+        let mut referrers: HashMap<Uuid, Vec<(Uuid, usize, bool)>> = HashMap::new();
+        for link in chain {
+            referrers.entry(link.user_id).or_default().push((
+                link.referrer_id,
+                link.level as usize,
+                link.verified_email,
+            ));
+        }
+        for (stat, rule_set, points) in earned {
+            let Some(chain) = referrers.get(&stat.user_id) else {
+                continue;
+            };
+            for (referrer_id, level, verified_email) in chain {
+                if let Some(commission) =
+                    rule_set.referral_commission(*level, points, stat.uptime, *verified_email)
+                {
+                    rows.push_for(
+                        *referrer_id,
+                        stat,
+                        PointsSource::Referral,
+                        points,
+                        rule_set.referral_rates[level - 1],
+                        commission,
+                        &format!("level {} referral, rules v{}", level, rule_set.version),
+                    );
+                }
+            }
+        }
+    }
+
+    sqlx::query!(
+        r#"
+        INSERT INTO points_ledger
+        (id, user_id, day, source, source_id, base_points, multiplier, points, note, created_at)
+        SELECT
+            gen_random_uuid(),
+            rows.user_id,
+            rows.day,
+            rows.source,
+            rows.source_id,
+            rows.base_points,
+            rows.multiplier,
+            rows.points,
+            rows.note,
+            now()
+        FROM UNNEST($1::uuid[], $2::date[], $3::text[], $4::uuid[], $5::float8[], $6::float8[], $7::float8[], $8::text[])
+        AS rows(user_id, day, source, source_id, base_points, multiplier, points, note)
+        ON CONFLICT DO NOTHING
+        "#,
+        &rows.user_ids,
+        &rows.days,
+        &rows.sources,
+        &rows.source_ids,
+        &rows.base_points,
+        &rows.multipliers,
+        &rows.points,
+        &rows.notes
     )
     .execute(&mut **transaction)
     .await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db_calls::test_support::insert_user;
+    use sqlx::PgPool;
+
+    async fn insert_daily_stat(
+        pool: &PgPool,
+        user_id: Uuid,
+        uptime: f64,
+        tasks_count: i32,
+    ) -> anyhow::Result<Uuid> {
+        let id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO daily_stats (id, user_id, tasks_count, status, day, created_at, uptime) VALUES ($1, $2, $3, $4, $5, now(), $6)",
+        )
+        .bind(id)
+        .bind(user_id)
+        .bind(tasks_count)
+        .bind(DailyStatStatus::OnGoing.to_string())
+        .bind((Utc::now() - Duration::days(3)).date_naive())
+        .bind(uptime)
+        .execute(pool)
+        .await?;
+        Ok(id)
+    }
+
+    async fn ledger(pool: &PgPool, user_id: Uuid) -> anyhow::Result<HashMap<String, f64>> {
+        let rows: Vec<(String, f64)> =
+            sqlx::query_as("SELECT source, points FROM points_ledger WHERE user_id = $1")
+                .bind(user_id)
+                .fetch_all(pool)
+                .await?;
+        let mut ledger = HashMap::new();
+        for (source, points) in rows {
+            *ledger.entry(source).or_default() += points;
+        }
+        Ok(ledger)
+    }
+
+    async fn finalize(pool: &PgPool) -> anyhow::Result<()> {
+        let mut transaction = pool.begin().await?;
+        bulk_finalize(&mut transaction).await?;
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "../block-mesh-manager/migrations")]
+    async fn test_finalized_day_is_credited(pool: PgPool) -> anyhow::Result<()> {
+        let user_id = insert_user(&pool).await?;
+        sqlx::query(
+            "INSERT INTO perks (id, user_id, created_at, name, multiplier) VALUES ($1, $2, now(), 'wallet', 1.1)",
+        )
+        .bind(Uuid::new_v4())
+        .bind(user_id)
+        .execute(&pool)
+        .await?;
+        insert_daily_stat(&pool, user_id, 24.0 * 60.0 * 60.0, 3).await?;
+        finalize(&pool).await?;
+
+        let ledger = ledger(&pool, user_id).await?;
+        assert_eq!(ledger.len(), 3);
+        assert!((ledger["Uptime"] - 100.0).abs() < 1e-6);
+        assert!((ledger["Tasks"] - 30.0).abs() < 1e-6);
+        assert!((ledger["PerkMultiplier"] - 13.0).abs() < 1e-6);
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "../block-mesh-manager/migrations")]
+    async fn test_day_is_credited_once(pool: PgPool) -> anyhow::Result<()> {
+        let user_id = insert_user(&pool).await?;
+        let stat_id = insert_daily_stat(&pool, user_id, 3600.0, 1).await?;
+        finalize(&pool).await?;
+        let credited = ledger(&pool, user_id).await?;
+
+        // finalizing the same day again must not credit it twice
+        sqlx::query("UPDATE daily_stats SET status = $1 WHERE id = $2")
+            .bind(DailyStatStatus::OnGoing.to_string())
+            .bind(stat_id)
+            .execute(&pool)
+            .await?;
+        finalize(&pool).await?;
+        assert_eq!(ledger(&pool, user_id).await?, credited);
+        let entries: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM points_ledger WHERE user_id = $1 AND source_id = $2",
+        )
+        .bind(user_id)
+        .bind(stat_id)
+        .fetch_one(&pool)
+        .await?;
+        assert_eq!(entries, 2);
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "../block-mesh-manager/migrations")]
+    async fn test_deleted_accounts_earn_nothing(pool: PgPool) -> anyhow::Result<()> {
+        let referrer_id = insert_user(&pool).await?;
+        let user_id = insert_user(&pool).await?;
+        let deleted_id = insert_user(&pool).await?;
+        sqlx::query("UPDATE users SET invited_by = $1 WHERE id = $2")
+            .bind(referrer_id)
+            .bind(user_id)
+            .execute(&pool)
+            .await?;
+        sqlx::query("UPDATE users SET deleted_at = now() WHERE id = ANY($1)")
+            .bind(vec![referrer_id, deleted_id])
+            .execute(&pool)
+            .await?;
+        insert_daily_stat(&pool, user_id, 24.0 * 60.0 * 60.0, 1).await?;
+        insert_daily_stat(&pool, deleted_id, 24.0 * 60.0 * 60.0, 1).await?;
+        finalize(&pool).await?;
+
+        assert!(!ledger(&pool, user_id).await?.is_empty());
+        assert!(ledger(&pool, referrer_id).await?.is_empty());
+        assert!(ledger(&pool, deleted_id).await?.is_empty());
+        let ongoing: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM daily_stats WHERE status = $1")
+            .bind(DailyStatStatus::OnGoing.to_string())
+            .fetch_one(&pool)
+            .await?;
+        assert_eq!(ongoing, 0);
+        Ok(())
+    }
+}
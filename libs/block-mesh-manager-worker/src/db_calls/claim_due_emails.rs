@@ -0,0 +1,38 @@
+use block_mesh_manager_database_domain::domain::email_outbox::{EmailOutbox, EmailOutboxStatus};
+use serde_json::Value;
+use sqlx::types::Json;
+use sqlx::PgPool;
+use std::time::Duration;
+
+/// Leases up to `limit` due emails by pushing their `next_attempt_at` past `lease`,
+/// concurrent workers skip the locked rows instead of sending them twice.
+#[tracing::instrument(name = "claim_due_emails", skip(pool), err, level = "trace")]
+pub async fn claim_due_emails(
+    pool: &PgPool,
+    limit: i64,
+    lease: Duration,
+) -> anyhow::Result<Vec<EmailOutbox>> {
+    let emails = sqlx::query_as!(
+        EmailOutbox,
+        r#"
+        UPDATE email_outbox
+        SET attempts = attempts + 1, next_attempt_at = now() + make_interval(secs => $3)
+        WHERE id IN (
+            SELECT id FROM email_outbox
+            WHERE status = $1 AND next_attempt_at <= now()
+            ORDER BY next_attempt_at
+            LIMIT $2
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING id, kind, to_address, locale, action_url, params AS "params: Json<Value>",
+                  status AS "status: EmailOutboxStatus", attempts, last_error, next_attempt_at,
+                  created_at, sent_at
+        "#,
+        EmailOutboxStatus::Pending.to_string(),
+        limit,
+        lease.as_secs_f64()
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(emails)
+}
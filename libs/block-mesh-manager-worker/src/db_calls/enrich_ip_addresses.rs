@@ -0,0 +1,187 @@
+use block_mesh_common::interfaces::ip_data::{get_ip_info, IpApiIsResponse};
+use chrono::{DateTime, Utc};
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+use sqlx::{PgPool, Postgres, Transaction};
+use std::env;
+use uuid::Uuid;
+
+/// Looks up unenriched IPs on ipapi.is and stores their ASN and risk flags,
+/// the lookups happen outside of any transaction.
+#[tracing::instrument(name = "enrich_ip_addresses", skip(pool), ret, err)]
+pub async fn enrich_ip_addresses(pool: &PgPool) -> anyhow::Result<usize> {
+    let batch: i64 = env::var("ENRICH_IP_BATCH")
+        .unwrap_or("50".to_string())
+        .parse()
+        .unwrap_or(50);
+    let max_attempts: i32 = env::var("ENRICH_IP_MAX_ATTEMPTS")
+        .unwrap_or("5".to_string())
+        .parse()
+        .unwrap_or(5);
+    let backoff_minutes: f64 = env::var("ENRICH_IP_BACKOFF_MINUTES")
+        .unwrap_or("10".to_string())
+        .parse()
+        .unwrap_or(10.0);
+    let pending =
+        find_ips_to_enrich(pool, batch, max_attempts, backoff_minutes, Utc::now()).await?;
+    let mut enriched = 0;
+    for (id, ip) in pending {
+        let Ok(info) = get_ip_info(&ip).await else {
+            record_enrich_failure(pool, &id, Utc::now()).await?;
+            continue;
+        };
+        let mut transaction = create_txn(pool).await?;
+        update_ip_address(&mut transaction, &id, &info).await?;
+        commit_txn(transaction).await?;
+        enriched += 1;
+    }
+    Ok(enriched)
+}
+
+/// IPs never tried come first, newest first, then failed ones whose backoff is over, the one
+/// that failed longest ago first. The backoff doubles with every attempt and an IP is given up
+/// on after `max_attempts`, so permanent failures never block the rest.
+async fn find_ips_to_enrich(
+    pool: &PgPool,
+    batch: i64,
+    max_attempts: i32,
+    backoff_minutes: f64,
+    now: DateTime<Utc>,
+) -> anyhow::Result<Vec<(Uuid, String)>> {
+    let pending = sqlx::query!(
+        r#"
+        SELECT id, ip
+        FROM ip_addresses
+        WHERE enriched = false
+            AND enrich_attempts < $2
+            AND (
+                enrich_failed_at IS NULL
+                OR enrich_failed_at < $4::timestamptz - interval '1 minute' * $3 * POWER(2, enrich_attempts - 1)
+            )
+        ORDER BY enrich_failed_at ASC NULLS FIRST, created_at DESC
+        LIMIT $1
+        "#,
+        batch,
+        max_attempts,
+        backoff_minutes,
+        now
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(pending.into_iter().map(|row| (row.id, row.ip)).collect())
+}
+
+async fn record_enrich_failure(
+    pool: &PgPool,
+    id: &Uuid,
+    failed_at: DateTime<Utc>,
+) -> anyhow::Result<()> {
+    sqlx::query!(
+        r#"
+        UPDATE ip_addresses SET
+            enrich_attempts = enrich_attempts + 1,
+            enrich_failed_at = $2
+        WHERE id = $1
+        "#,
+        id,
+        failed_at
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn update_ip_address(
+    transaction: &mut Transaction<'_, Postgres>,
+    id: &Uuid,
+    info: &IpApiIsResponse,
+) -> anyhow::Result<()> {
+    sqlx::query!(
+        r#"
+        UPDATE ip_addresses SET
+            asn = $2,
+            country = COALESCE(country, $3),
+            isp = COALESCE(isp, $4),
+            is_datacenter = $5,
+            is_vpn = $6,
+            is_proxy = $7,
+            is_tor = $8,
+            is_abuser = $9,
+            enriched = true
+        WHERE id = $1
+        "#,
+        id,
+        info.asn.as_ref().map(|asn| asn.asn as i32),
+        info.location.as_ref().map(|l| l.country_code.clone()),
+        info.company.as_ref().map(|c| c.name.clone()),
+        info.is_datacenter,
+        info.is_vpn,
+        info.is_proxy,
+        info.is_tor,
+        info.is_abuser
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    async fn insert_ip(pool: &PgPool, ip: &str, minutes_ago: i64) -> anyhow::Result<Uuid> {
+        let id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO ip_addresses (id, ip, created_at, enriched) VALUES ($1, $2, $3, false)",
+        )
+        .bind(id)
+        .bind(ip)
+        .bind(Utc::now() - Duration::minutes(minutes_ago))
+        .execute(pool)
+        .await?;
+        Ok(id)
+    }
+
+    #[sqlx::test(migrations = "../block-mesh-manager/migrations")]
+    async fn test_failed_ips_do_not_block_older_ones(pool: PgPool) -> anyhow::Result<()> {
+        let broken = insert_ip(&pool, "10.0.0.1", 1).await?;
+        let older = insert_ip(&pool, "1.1.1.1", 60).await?;
+        let now = Utc::now();
+        let ids = |rows: Vec<(Uuid, String)>| rows.into_iter().map(|row| row.0).collect::<Vec<_>>();
+
+        assert_eq!(
+            ids(find_ips_to_enrich(&pool, 1, 3, 10.0, now).await?),
+            vec![broken]
+        );
+        record_enrich_failure(&pool, &broken, now).await?;
+        // backing off, the older IP gets its turn
+        assert_eq!(
+            ids(find_ips_to_enrich(&pool, 1, 3, 10.0, now).await?),
+            vec![older]
+        );
+        assert_eq!(
+            ids(find_ips_to_enrich(&pool, 2, 3, 10.0, now + Duration::minutes(11)).await?),
+            vec![older, broken]
+        );
+        record_enrich_failure(&pool, &broken, now + Duration::minutes(11)).await?;
+        // the second backoff is twice as long
+        let later = now + Duration::minutes(11 + 15);
+        assert_eq!(
+            ids(find_ips_to_enrich(&pool, 2, 3, 10.0, later).await?),
+            vec![older]
+        );
+        let later = now + Duration::minutes(11 + 21);
+        assert_eq!(
+            ids(find_ips_to_enrich(&pool, 2, 3, 10.0, later).await?),
+            vec![older, broken]
+        );
+        record_enrich_failure(&pool, &broken, later).await?;
+        // out of attempts
+        let much_later = later + Duration::days(1);
+        assert_eq!(
+            ids(find_ips_to_enrich(&pool, 2, 3, 10.0, much_later).await?),
+            vec![older]
+        );
+        Ok(())
+    }
+}
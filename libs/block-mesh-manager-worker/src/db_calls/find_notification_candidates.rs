@@ -0,0 +1,167 @@
+use crate::domain::notification::NotificationCandidate;
+use chrono::NaiveDate;
+use serde_json::Value;
+use sqlx::types::Json;
+use sqlx::PgPool;
+
+/// Nodes that went quiet for longer than the user's `offline_minutes`.
+/// Only recent transitions are picked up and accounts that never reported uptime are skipped,
+/// so a new deployment doesn't alert every long dead node at once.
+#[tracing::instrument(name = "find_offline_nodes", skip(pool), err, level = "trace")]
+pub async fn find_offline_nodes(
+    pool: &PgPool,
+    lookback_minutes: i32,
+) -> anyhow::Result<Vec<NotificationCandidate>> {
+    let candidates = sqlx::query_as!(
+        NotificationCandidate,
+        r#"
+        SELECT u.id AS user_id, u.email, a.updated_at::text AS "dedupe_key!",
+               jsonb_build_object('minutes', p.offline_minutes) AS "params!: Json<Value>"
+        FROM aggregates a
+        JOIN users u ON u.id = a.user_id
+        JOIN notification_preferences p ON p.user_id = a.user_id
+        WHERE a.name = 'Uptime'
+          AND a.updated_at > now() - make_interval(mins => 1440 + $1)
+          AND a.updated_at < now() - make_interval(mins => p.offline_minutes)
+          AND a.updated_at > now() - make_interval(mins => p.offline_minutes + $1)
+          AND (a.value #>> '{}')::double precision > 0
+          AND u.deleted_at IS NULL
+          AND p.node_offline
+        "#,
+        lookback_minutes
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(candidates)
+}
+
+/// Inviters of accounts registered in the lookback window, like every detector here
+/// it only returns users who opted in to the kind.
+#[tracing::instrument(name = "find_new_referrals", skip(pool), err, level = "trace")]
+pub async fn find_new_referrals(
+    pool: &PgPool,
+    lookback_minutes: i32,
+) -> anyhow::Result<Vec<NotificationCandidate>> {
+    let candidates = sqlx::query_as!(
+        NotificationCandidate,
+        r#"
+        SELECT u.id AS user_id, u.email, r.id::text AS "dedupe_key!",
+               jsonb_build_object(
+                   'referrals', (SELECT COUNT(*) FROM users c WHERE c.invited_by = u.id)
+               ) AS "params!: Json<Value>"
+        FROM users r
+        JOIN users u ON u.id = r.invited_by
+        JOIN notification_preferences p ON p.user_id = u.id
+        WHERE r.created_at > now() - make_interval(mins => $1)
+          AND u.deleted_at IS NULL
+          AND p.new_referral
+        "#,
+        lookback_minutes
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(candidates)
+}
+
+#[tracing::instrument(name = "find_new_perks", skip(pool), err, level = "trace")]
+pub async fn find_new_perks(
+    pool: &PgPool,
+    lookback_minutes: i32,
+) -> anyhow::Result<Vec<NotificationCandidate>> {
+    let candidates = sqlx::query_as!(
+        NotificationCandidate,
+        r#"
+        SELECT u.id AS user_id, u.email, k.id::text AS "dedupe_key!",
+               jsonb_build_object('perk', COALESCE(k.name, '')) AS "params!: Json<Value>"
+        FROM perks k
+        JOIN users u ON u.id = k.user_id
+        JOIN notification_preferences p ON p.user_id = k.user_id
+        WHERE k.created_at > now() - make_interval(mins => $1)
+          AND u.deleted_at IS NULL
+          AND p.perk_earned
+        "#,
+        lookback_minutes
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(candidates)
+}
+
+/// Ledger totals of `day` for users who opted in to the daily summary.
+#[tracing::instrument(name = "find_daily_summaries", skip(pool), err, level = "trace")]
+pub async fn find_daily_summaries(
+    pool: &PgPool,
+    day: NaiveDate,
+) -> anyhow::Result<Vec<NotificationCandidate>> {
+    let candidates = sqlx::query_as!(
+        NotificationCandidate,
+        r#"
+        SELECT u.id AS user_id, u.email, $2::text AS "dedupe_key!",
+               jsonb_build_object('day', $2::text, 'points', to_char(SUM(l.points), 'FM999999990.00')) AS "params!: Json<Value>"
+        FROM notification_preferences p
+        JOIN users u ON u.id = p.user_id
+        JOIN points_ledger l ON l.user_id = p.user_id AND l.day = $1
+        WHERE p.daily_summary
+          AND u.deleted_at IS NULL
+        GROUP BY u.id, u.email
+        "#,
+        day,
+        day.to_string()
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(candidates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db_calls::test_support::insert_user;
+    use uuid::Uuid;
+
+    async fn insert_user_with_perk(pool: &PgPool) -> anyhow::Result<Uuid> {
+        let id = insert_user(pool).await?;
+        sqlx::query(
+            "INSERT INTO perks (id, user_id, created_at, name, multiplier) VALUES ($1, $2, now(), 'wallet', 1.1)",
+        )
+        .bind(Uuid::new_v4())
+        .bind(id)
+        .execute(pool)
+        .await?;
+        Ok(id)
+    }
+
+    #[sqlx::test(migrations = "../block-mesh-manager/migrations")]
+    async fn test_only_opted_in_users_are_candidates(pool: PgPool) -> anyhow::Result<()> {
+        let without_preferences = insert_user_with_perk(&pool).await?;
+        let opted_out = insert_user_with_perk(&pool).await?;
+        let opted_in = insert_user_with_perk(&pool).await?;
+        for (user_id, perk_earned) in [(opted_out, None), (opted_in, Some(true))] {
+            sqlx::query(
+                "INSERT INTO notification_preferences (user_id, unsubscribe_token, created_at, updated_at) VALUES ($1, $2, now(), now())",
+            )
+            .bind(user_id)
+            .bind(user_id.to_string())
+            .execute(&pool)
+            .await?;
+            if let Some(perk_earned) = perk_earned {
+                sqlx::query(
+                    "UPDATE notification_preferences SET perk_earned = $2 WHERE user_id = $1",
+                )
+                .bind(user_id)
+                .bind(perk_earned)
+                .execute(&pool)
+                .await?;
+            }
+        }
+
+        let candidates: Vec<Uuid> = find_new_perks(&pool, 60)
+            .await?
+            .into_iter()
+            .map(|candidate| candidate.user_id)
+            .collect();
+        assert_eq!(candidates, vec![opted_in]);
+        assert!(!candidates.contains(&without_preferences));
+        Ok(())
+    }
+}
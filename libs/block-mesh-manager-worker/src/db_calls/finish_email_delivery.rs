@@ -0,0 +1,60 @@
+use block_mesh_manager_database_domain::domain::email_outbox::EmailOutboxStatus;
+use sqlx::PgPool;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// The link carries a token, it is cleared once the email is out.
+#[tracing::instrument(name = "mark_email_sent", skip(pool), err, level = "trace")]
+pub async fn mark_email_sent(pool: &PgPool, id: &Uuid) -> anyhow::Result<()> {
+    sqlx::query!(
+        r#"
+        UPDATE email_outbox
+        SET status = $2, sent_at = now(), action_url = '', last_error = NULL
+        WHERE id = $1
+        "#,
+        id,
+        EmailOutboxStatus::Sent.to_string()
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+#[tracing::instrument(name = "mark_email_retry", skip(pool, error), err, level = "trace")]
+pub async fn mark_email_retry(
+    pool: &PgPool,
+    id: &Uuid,
+    error: &str,
+    retry_in: Duration,
+) -> anyhow::Result<()> {
+    sqlx::query!(
+        r#"
+        UPDATE email_outbox
+        SET last_error = $2, next_attempt_at = now() + make_interval(secs => $3)
+        WHERE id = $1
+        "#,
+        id,
+        error,
+        retry_in.as_secs_f64()
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+#[tracing::instrument(name = "mark_email_failed", skip(pool, error), err, level = "trace")]
+pub async fn mark_email_failed(pool: &PgPool, id: &Uuid, error: &str) -> anyhow::Result<()> {
+    sqlx::query!(
+        r#"
+        UPDATE email_outbox
+        SET status = $2, last_error = $3, action_url = ''
+        WHERE id = $1
+        "#,
+        id,
+        EmailOutboxStatus::Failed.to_string(),
+        error
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
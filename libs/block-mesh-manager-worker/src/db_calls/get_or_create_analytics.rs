@@ -10,6 +10,7 @@ pub async fn get_or_create_analytics(
     depin_aggregator: &str,
     device_type: &DeviceType,
     version: &str,
+    device_id: Option<Uuid>,
 ) -> anyhow::Result<Uuid> {
     let now = Utc::now();
     let id = Uuid::new_v4();
@@ -17,18 +18,21 @@ pub async fn get_or_create_analytics(
         r#"
         INSERT
         INTO analytics
-        (user_id, depin_aggregator, device_type, created_at, updated_at, id, version)
+        (user_id, depin_aggregator, device_type, created_at, updated_at, id, version, device_id)
         VALUES
-        ($1, $2, $3, $4, $5, $6, $7)
-        ON CONFLICT (user_id, depin_aggregator) DO UPDATE SET updated_at = $5
+        ($1, $2, $3, $4, $5, $6, $7, $8)
+        ON CONFLICT (user_id, depin_aggregator) DO UPDATE SET
+            updated_at = $5,
+            device_id = COALESCE($8, analytics.device_id)
     "#,
         user_id,
         depin_aggregator,
         device_type.to_string(),
-        now.clone(),
+        now,
         now,
         id,
-        version
+        version,
+        device_id
     )
     .execute(&mut **transaction)
     .await?;
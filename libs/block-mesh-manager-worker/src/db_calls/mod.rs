@@ -1,7 +1,16 @@
 pub mod bulk_delete_old_tasks;
+pub mod bulk_expire_tasks;
 pub mod bulk_finalize;
+pub mod claim_due_emails;
 pub mod create_server_user;
 pub mod create_task;
+pub mod enrich_ip_addresses;
+pub mod find_notification_candidates;
+pub mod finish_email_delivery;
 pub mod get_all_rpcs;
 pub mod get_or_create_analytics;
+pub mod record_notification;
+pub mod score_sybil_accounts;
+#[cfg(test)]
+pub mod test_support;
 pub mod touch_users_ip;
@@ -0,0 +1,35 @@
+use block_mesh_manager_database_domain::domain::notification_preferences::NotificationKind;
+use serde_json::Value;
+use sqlx::PgExecutor;
+use uuid::Uuid;
+
+/// Returns false when this event was already recorded, by an earlier run or another replica.
+#[tracing::instrument(
+    name = "record_notification",
+    skip(executor, params),
+    err,
+    level = "trace"
+)]
+pub async fn record_notification(
+    executor: impl PgExecutor<'_>,
+    user_id: &Uuid,
+    kind: NotificationKind,
+    dedupe_key: &str,
+    params: &Value,
+) -> anyhow::Result<bool> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO notifications (id, user_id, kind, dedupe_key, params, created_at)
+        VALUES ($1, $2, $3, $4, $5, now())
+        ON CONFLICT (user_id, kind, dedupe_key) DO NOTHING
+        "#,
+        Uuid::new_v4(),
+        user_id,
+        kind.to_string(),
+        dedupe_key,
+        params
+    )
+    .execute(executor)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
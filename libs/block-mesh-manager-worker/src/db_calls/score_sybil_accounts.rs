@@ -0,0 +1,386 @@
+use crate::domain::sybil::{score_accounts, SybilConfig, UserSignals};
+use block_mesh_manager_database_domain::domain::sybil_flag::{SybilFlagStatus, SybilReason};
+use chrono::{DateTime, Duration, Utc};
+use serde_json::Value;
+use sqlx::types::Json;
+use sqlx::{Postgres, Transaction};
+use std::env;
+
+/// Rebuilds the sybil flags from the IPs, devices and referrals seen in the lookback window.
+/// Admin decisions are kept, only the score of reviewed flags is refreshed, and pending flags
+/// that no longer score above the threshold are dropped.
+#[tracing::instrument(name = "score_sybil_accounts", skip(transaction), ret, err)]
+pub async fn score_sybil_accounts(
+    transaction: &mut Transaction<'_, Postgres>,
+) -> anyhow::Result<usize> {
+    let lookback_days: i64 = env::var("SYBIL_LOOKBACK_DAYS")
+        .unwrap_or("7".to_string())
+        .parse()
+        .unwrap_or(7);
+    let now = Utc::now();
+    let since = now - Duration::days(lookback_days);
+
+    let signals = find_user_signals(transaction, since).await?;
+    let verdicts = score_accounts(&signals, &SybilConfig::default());
+
+    let mut flagged_ids = Vec::with_capacity(verdicts.len());
+    let mut cluster_ids = Vec::with_capacity(verdicts.len());
+    let mut scores = Vec::with_capacity(verdicts.len());
+    let mut weights = Vec::with_capacity(verdicts.len());
+    let mut reasons = Vec::with_capacity(verdicts.len());
+    for verdict in &verdicts {
+        flagged_ids.push(verdict.user_id);
+        cluster_ids.push(verdict.cluster_id);
+        scores.push(verdict.score);
+        weights.push(verdict.weight);
+        reasons.push(serde_json::to_value(&verdict.reasons).unwrap_or(Value::Array(vec![])));
+    }
+    sqlx::query!(
+        r#"
+        INSERT INTO sybil_flags
+        (id, user_id, cluster_id, score, weight, reasons, status, created_at, updated_at)
+        SELECT
+            gen_random_uuid(),
+            flags.user_id,
+            flags.cluster_id,
+            flags.score,
+            flags.weight,
+            flags.reasons,
+            $6,
+            $7,
+            $7
+        FROM UNNEST($1::uuid[], $2::uuid[], $3::float8[], $4::float8[], $5::jsonb[])
+        AS flags(user_id, cluster_id, score, weight, reasons)
+        ON CONFLICT (user_id) DO UPDATE SET
+            cluster_id = EXCLUDED.cluster_id,
+            score = EXCLUDED.score,
+            weight = EXCLUDED.weight,
+            reasons = EXCLUDED.reasons,
+            updated_at = EXCLUDED.updated_at
+        "#,
+        &flagged_ids,
+        &cluster_ids as _,
+        &scores,
+        &weights,
+        &reasons,
+        SybilFlagStatus::Pending.to_string(),
+        now
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        DELETE FROM sybil_flags
+        WHERE status = $1 AND updated_at < $2
+        "#,
+        SybilFlagStatus::Pending.to_string(),
+        now
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(verdicts.len())
+}
+
+/// Clusters the accounts seen since `since` by shared IPs, shared devices and referrals on the
+/// same ASN, and aggregates their IP signals. Accounts that share nothing and were only seen on
+/// clean IPs are left out since they cannot score.
+pub async fn find_user_signals(
+    transaction: &mut Transaction<'_, Postgres>,
+    since: DateTime<Utc>,
+) -> anyhow::Result<Vec<UserSignals>> {
+    // the scratch tables are shared, a second run waits for the first one to commit
+    sqlx::query!("LOCK TABLE sybil_clusters, sybil_cluster_edges IN EXCLUSIVE MODE")
+        .execute(&mut **transaction)
+        .await?;
+    sqlx::query!("DELETE FROM sybil_cluster_edges")
+        .execute(&mut **transaction)
+        .await?;
+    sqlx::query!("DELETE FROM sybil_clusters")
+        .execute(&mut **transaction)
+        .await?;
+    sqlx::query!(
+        r#"
+        INSERT INTO sybil_clusters (user_id, cluster_id)
+        SELECT user_id, user_id FROM users_ip WHERE updated_at >= $1
+        UNION
+        SELECT user_id, user_id FROM analytics WHERE device_id IS NOT NULL AND updated_at >= $1
+        "#,
+        since
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    // reasons are stored with the serde names of `SybilReason`
+    sqlx::query!(
+        r#"
+        WITH window_ips AS (
+            SELECT DISTINCT user_id, ip_id FROM users_ip WHERE updated_at >= $1
+        )
+        INSERT INTO sybil_cluster_edges (user_id, other_id, reason)
+        SELECT window_ips.user_id, anchors.anchor_id, 'SharedIp'
+        FROM
+            window_ips
+            JOIN (
+                SELECT ip_id, (ARRAY_AGG(user_id ORDER BY user_id))[1] AS anchor_id
+                FROM window_ips
+                GROUP BY ip_id
+                HAVING COUNT(*) > 1
+            ) anchors ON anchors.ip_id = window_ips.ip_id
+        WHERE window_ips.user_id <> anchors.anchor_id
+        "#,
+        since
+    )
+    .execute(&mut **transaction)
+    .await?;
+    sqlx::query!(
+        r#"
+        WITH window_devices AS (
+            SELECT DISTINCT user_id, device_id
+            FROM analytics
+            WHERE device_id IS NOT NULL AND updated_at >= $1
+        )
+        INSERT INTO sybil_cluster_edges (user_id, other_id, reason)
+        SELECT window_devices.user_id, anchors.anchor_id, 'SharedDevice'
+        FROM
+            window_devices
+            JOIN (
+                SELECT device_id, (ARRAY_AGG(user_id ORDER BY user_id))[1] AS anchor_id
+                FROM window_devices
+                GROUP BY device_id
+                HAVING COUNT(*) > 1
+            ) anchors ON anchors.device_id = window_devices.device_id
+        WHERE window_devices.user_id <> anchors.anchor_id
+        "#,
+        since
+    )
+    .execute(&mut **transaction)
+    .await?;
+    sqlx::query!(
+        r#"
+        INSERT INTO sybil_cluster_edges (user_id, other_id, reason)
+        SELECT users.id, users.invited_by, 'ReferralChain'
+        FROM
+            users
+            JOIN sybil_clusters ON sybil_clusters.user_id = users.id
+        WHERE users.invited_by IS NOT NULL
+            AND EXISTS (
+                SELECT 1
+                FROM
+                    users_ip invitee_ip
+                    JOIN ip_addresses invitee_address ON invitee_address.id = invitee_ip.ip_id
+                    JOIN ip_addresses inviter_address ON inviter_address.asn = invitee_address.asn
+                    JOIN users_ip inviter_ip ON inviter_ip.ip_id = inviter_address.id
+                WHERE invitee_ip.user_id = users.id
+                    AND inviter_ip.user_id = users.invited_by
+                    AND invitee_ip.updated_at >= $1
+                    AND inviter_ip.updated_at >= $1
+            )
+        "#,
+        since
+    )
+    .execute(&mut **transaction)
+    .await?;
+
+    // every pass moves the smallest id one edge further, until no cluster id changes
+    loop {
+        let updated = sqlx::query!(
+            r#"
+            UPDATE sybil_clusters
+            SET cluster_id = smallest.cluster_id
+            FROM (
+                SELECT DISTINCT ON (edges.user_id) edges.user_id, sybil_clusters.cluster_id
+                FROM
+                    (
+                        SELECT user_id, other_id FROM sybil_cluster_edges
+                        UNION ALL
+                        SELECT other_id, user_id FROM sybil_cluster_edges
+                    ) edges
+                    JOIN sybil_clusters ON sybil_clusters.user_id = edges.other_id
+                ORDER BY edges.user_id, sybil_clusters.cluster_id
+            ) smallest
+            WHERE sybil_clusters.user_id = smallest.user_id
+                AND smallest.cluster_id < sybil_clusters.cluster_id
+            "#
+        )
+        .execute(&mut **transaction)
+        .await?;
+        if updated.rows_affected() == 0 {
+            break;
+        }
+    }
+
+    let signals = sqlx::query!(
+        r#"
+        WITH
+            reasons AS (
+                SELECT user_id, jsonb_agg(DISTINCT reason) AS reasons
+                FROM (
+                    SELECT user_id, reason FROM sybil_cluster_edges
+                    UNION
+                    SELECT other_id, reason FROM sybil_cluster_edges
+                ) edges
+                GROUP BY user_id
+            ),
+            ips AS (
+                SELECT
+                    window_ips.user_id,
+                    COUNT(*) AS total,
+                    COUNT(*) FILTER (WHERE ip_addresses.is_datacenter) AS datacenter,
+                    COUNT(*) FILTER (WHERE ip_addresses.is_vpn OR ip_addresses.is_proxy OR ip_addresses.is_tor) AS anonymized,
+                    COUNT(*) FILTER (WHERE ip_addresses.is_abuser) AS abuser
+                FROM
+                    (SELECT DISTINCT user_id, ip_id FROM users_ip WHERE updated_at >= $1) window_ips
+                    JOIN ip_addresses ON ip_addresses.id = window_ips.ip_id
+                GROUP BY window_ips.user_id
+            ),
+            clusters AS (
+                SELECT user_id, cluster_id, COUNT(*) OVER (PARTITION BY cluster_id) AS cluster_size
+                FROM sybil_clusters
+            )
+        SELECT
+            clusters.user_id,
+            clusters.cluster_id,
+            clusters.cluster_size AS "cluster_size!",
+            COALESCE(reasons.reasons, '[]'::jsonb) AS "shared_reasons!: Json<Vec<SybilReason>>",
+            COALESCE(ips.total, 0) AS "ips!",
+            COALESCE(ips.datacenter, 0) AS "datacenter_ips!",
+            COALESCE(ips.anonymized, 0) AS "anonymized_ips!",
+            COALESCE(ips.abuser, 0) AS "abuser_ips!"
+        FROM
+            clusters
+            LEFT JOIN reasons ON reasons.user_id = clusters.user_id
+            LEFT JOIN ips ON ips.user_id = clusters.user_id
+        WHERE clusters.cluster_size > 1
+            OR ips.datacenter > 0
+            OR ips.anonymized > 0
+            OR ips.abuser > 0
+        "#,
+        since
+    )
+    .fetch_all(&mut **transaction)
+    .await?
+    .into_iter()
+    .map(|row| UserSignals {
+        user_id: row.user_id,
+        cluster_id: row.cluster_id,
+        cluster_size: row.cluster_size as usize,
+        shared_reasons: row.shared_reasons.0,
+        ips: row.ips as usize,
+        datacenter_ips: row.datacenter_ips as usize,
+        anonymized_ips: row.anonymized_ips as usize,
+        abuser_ips: row.abuser_ips as usize,
+    })
+    .collect();
+    Ok(signals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db_calls::test_support::insert_user;
+    use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+    use sqlx::PgPool;
+    use uuid::Uuid;
+
+    async fn insert_ip(pool: &PgPool, asn: i32, is_datacenter: bool) -> anyhow::Result<Uuid> {
+        let id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO ip_addresses (id, ip, created_at, enriched, asn, is_datacenter) VALUES ($1, $2, now(), true, $3, $4)",
+        )
+        .bind(id)
+        .bind(id.to_string())
+        .bind(asn)
+        .bind(is_datacenter)
+        .execute(pool)
+        .await?;
+        Ok(id)
+    }
+
+    async fn insert_user_ip(pool: &PgPool, user_id: Uuid, ip_id: Uuid) -> anyhow::Result<()> {
+        sqlx::query("INSERT INTO users_ip (id, user_id, ip_id) VALUES ($1, $2, $3)")
+            .bind(Uuid::new_v4())
+            .bind(user_id)
+            .bind(ip_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn insert_device(pool: &PgPool, user_id: Uuid, device_id: Uuid) -> anyhow::Result<()> {
+        sqlx::query(
+            "INSERT INTO analytics (id, user_id, depin_aggregator, device_type, device_id, created_at, updated_at) VALUES ($1, $2, 'none', 'extension', $3, now(), now())",
+        )
+        .bind(Uuid::new_v4())
+        .bind(user_id)
+        .bind(device_id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = "../block-mesh-manager/migrations")]
+    async fn test_clusters_are_built_in_sql(pool: PgPool) -> anyhow::Result<()> {
+        // a and b share one IP, b and c another: a single cluster of three
+        let first_ip = insert_ip(&pool, 1, false).await?;
+        let second_ip = insert_ip(&pool, 1, false).await?;
+        let mut chain = Vec::new();
+        for _ in 0..3 {
+            chain.push(insert_user(&pool).await?);
+        }
+        insert_user_ip(&pool, chain[0], first_ip).await?;
+        insert_user_ip(&pool, chain[1], first_ip).await?;
+        insert_user_ip(&pool, chain[1], second_ip).await?;
+        insert_user_ip(&pool, chain[2], second_ip).await?;
+
+        let device_id = Uuid::new_v4();
+        let device_users = [insert_user(&pool).await?, insert_user(&pool).await?];
+        for user_id in device_users {
+            insert_device(&pool, user_id, device_id).await?;
+        }
+
+        let inviter = insert_user(&pool).await?;
+        let invitee = insert_user(&pool).await?;
+        sqlx::query("UPDATE users SET invited_by = $1 WHERE id = $2")
+            .bind(inviter)
+            .bind(invitee)
+            .execute(&pool)
+            .await?;
+        insert_user_ip(&pool, inviter, insert_ip(&pool, 2, false).await?).await?;
+        insert_user_ip(&pool, invitee, insert_ip(&pool, 2, false).await?).await?;
+
+        let datacenter_user = insert_user(&pool).await?;
+        insert_user_ip(&pool, datacenter_user, insert_ip(&pool, 3, true).await?).await?;
+        let clean_user = insert_user(&pool).await?;
+        insert_user_ip(&pool, clean_user, insert_ip(&pool, 4, false).await?).await?;
+
+        let mut transaction = create_txn(&pool).await?;
+        let signals = find_user_signals(&mut transaction, Utc::now() - Duration::days(1)).await?;
+        commit_txn(transaction).await?;
+
+        let find = |user_id: Uuid| signals.iter().find(|s| s.user_id == user_id);
+        let chain_cluster = *chain.iter().min().unwrap();
+        for user_id in &chain {
+            let s = find(*user_id).unwrap();
+            assert_eq!(s.cluster_id, chain_cluster);
+            assert_eq!(s.cluster_size, 3);
+            assert_eq!(s.shared_reasons, vec![SybilReason::SharedIp]);
+        }
+        assert_eq!(find(chain[1]).unwrap().ips, 2);
+        for user_id in device_users {
+            let s = find(user_id).unwrap();
+            assert_eq!(s.cluster_size, 2);
+            assert_eq!(s.shared_reasons, vec![SybilReason::SharedDevice]);
+        }
+        for user_id in [inviter, invitee] {
+            let s = find(user_id).unwrap();
+            assert_eq!(s.cluster_id, inviter.min(invitee));
+            assert_eq!(s.shared_reasons, vec![SybilReason::ReferralChain]);
+        }
+        let s = find(datacenter_user).unwrap();
+        assert_eq!((s.cluster_size, s.datacenter_ips), (1, 1));
+        assert!(find(clean_user).is_none());
+        assert_eq!(signals.len(), 8);
+        Ok(())
+    }
+}
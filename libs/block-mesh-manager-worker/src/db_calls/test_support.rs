@@ -0,0 +1,15 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Verified user with an empty password, the bare row the database tests build on.
+pub async fn insert_user(pool: &PgPool) -> anyhow::Result<Uuid> {
+    let id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO users (id, email, password, created_at, verified_email) VALUES ($1, $2, '', now(), true)",
+    )
+    .bind(id)
+    .bind(format!("{}@example.com", id))
+    .execute(pool)
+    .await?;
+    Ok(id)
+}
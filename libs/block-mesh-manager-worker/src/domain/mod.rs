@@ -1 +1,3 @@
+pub mod notification;
 pub mod rpc;
+pub mod sybil;
@@ -0,0 +1,46 @@
+use block_mesh_manager_database_domain::domain::notification_preferences::NotificationKind;
+use serde_json::Value;
+use sqlx::types::Json;
+use std::env;
+use uuid::Uuid;
+
+/// A detected event for one user. `dedupe_key` identifies the state change,
+/// e.g. the last heartbeat before going offline, so it is announced once.
+#[derive(sqlx::FromRow, Debug, Clone)]
+pub struct NotificationCandidate {
+    pub user_id: Uuid,
+    pub email: String,
+    pub dedupe_key: String,
+    pub params: Json<Value>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NotificationConfig {
+    /// Public URL of the dashboard, used for links in notifications.
+    pub base_url: String,
+    /// How far back the detectors look for events, covers worker downtime.
+    pub lookback_minutes: i32,
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        Self {
+            base_url: env::var("APP_BASE_URL").unwrap_or("https://app.blockmesh.xyz".to_string()),
+            lookback_minutes: env::var("NOTIFICATIONS_LOOKBACK_MINUTES")
+                .unwrap_or("60".to_string())
+                .parse()
+                .unwrap_or(60),
+        }
+    }
+}
+
+impl NotificationConfig {
+    pub fn action_url(&self, kind: NotificationKind) -> String {
+        let path = match kind {
+            NotificationKind::NodeOffline | NotificationKind::DailySummary => "/ui/dashboard",
+            NotificationKind::NewReferral => "/ui/referrals",
+            NotificationKind::PerkEarned => "/ui/perks",
+        };
+        format!("{}{}", self.base_url, path)
+    }
+}
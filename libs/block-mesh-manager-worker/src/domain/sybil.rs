@@ -0,0 +1,152 @@
+use block_mesh_manager_database_domain::domain::sybil_flag::SybilReason;
+use std::collections::HashSet;
+use std::env;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy)]
+pub struct SybilConfig {
+    /// Cluster size at which the shared IP / device signal is at its maximum.
+    pub cluster_threshold: usize,
+    /// Minimum score for an account to be flagged.
+    pub flag_threshold: f64,
+}
+
+impl Default for SybilConfig {
+    fn default() -> Self {
+        Self {
+            cluster_threshold: env::var("SYBIL_CLUSTER_THRESHOLD")
+                .unwrap_or("5".to_string())
+                .parse()
+                .unwrap_or(5),
+            flag_threshold: env::var("SYBIL_FLAG_THRESHOLD")
+                .unwrap_or("0.5".to_string())
+                .parse()
+                .unwrap_or(0.5),
+        }
+    }
+}
+
+/// One account's signals over the lookback window, aggregated by `score_sybil_accounts`.
+#[derive(Debug, Clone, Default)]
+pub struct UserSignals {
+    pub user_id: Uuid,
+    /// Smallest user id in the account's cluster, the account itself when it shares nothing.
+    pub cluster_id: Uuid,
+    pub cluster_size: usize,
+    /// `SharedIp`, `SharedDevice` and `ReferralChain`, whichever linked the account to its cluster.
+    pub shared_reasons: Vec<SybilReason>,
+    pub ips: usize,
+    pub datacenter_ips: usize,
+    pub anonymized_ips: usize,
+    pub abuser_ips: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SybilVerdict {
+    pub user_id: Uuid,
+    /// Smallest user id in the cluster, stable across runs while the cluster holds.
+    pub cluster_id: Option<Uuid>,
+    pub score: f64,
+    pub weight: f64,
+    pub reasons: Vec<SybilReason>,
+}
+
+/// Scores each account by its cluster size and by how much of its traffic comes from
+/// datacenter, VPN/proxy/Tor or known abuser IPs. Only accounts at or above the flag threshold
+/// are returned.
+pub fn score_accounts(signals: &[UserSignals], config: &SybilConfig) -> Vec<SybilVerdict> {
+    let threshold = config.cluster_threshold.max(2);
+    let mut verdicts = Vec::new();
+    for s in signals {
+        let cluster_size = s.cluster_size.max(1);
+        let cluster_signal = ((cluster_size - 1) as f64 / (threshold - 1) as f64).min(1.0);
+        let total_ips = s.ips.max(1) as f64;
+        let risky_ips = s.datacenter_ips.max(s.anonymized_ips) as f64;
+        let mut reasons: HashSet<SybilReason> = s.shared_reasons.iter().copied().collect();
+        if s.datacenter_ips > 0 {
+            reasons.insert(SybilReason::DatacenterIp);
+        }
+        if s.anonymized_ips > 0 {
+            reasons.insert(SybilReason::AnonymizedIp);
+        }
+        if s.abuser_ips > 0 {
+            reasons.insert(SybilReason::AbuserIp);
+        }
+        let score = (0.6 * cluster_signal
+            + 0.3 * (risky_ips / total_ips).min(1.0)
+            + if s.abuser_ips > 0 { 0.1 } else { 0.0 })
+        .min(1.0);
+        if score < config.flag_threshold {
+            continue;
+        }
+        let mut reasons: Vec<SybilReason> = reasons.into_iter().collect();
+        reasons.sort_by_key(|r| *r as u8);
+        verdicts.push(SybilVerdict {
+            user_id: s.user_id,
+            cluster_id: (cluster_size > 1).then_some(s.cluster_id),
+            score,
+            weight: 1.0 - score,
+            reasons,
+        });
+    }
+    verdicts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clustered(cluster_id: Uuid, cluster_size: usize, reason: SybilReason) -> UserSignals {
+        UserSignals {
+            user_id: Uuid::new_v4(),
+            cluster_id,
+            cluster_size,
+            shared_reasons: vec![reason],
+            ips: 1,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_cluster_size_drives_the_score() {
+        let config = SybilConfig {
+            cluster_threshold: 3,
+            flag_threshold: 0.5,
+        };
+        let cluster_id = Uuid::new_v4();
+        let signals = vec![
+            clustered(cluster_id, 3, SybilReason::SharedIp),
+            clustered(cluster_id, 3, SybilReason::SharedIp),
+            clustered(cluster_id, 3, SybilReason::SharedIp),
+            clustered(Uuid::new_v4(), 2, SybilReason::SharedDevice),
+        ];
+        let verdicts = score_accounts(&signals, &config);
+        assert_eq!(verdicts.len(), 3);
+        for verdict in &verdicts {
+            assert_eq!(verdict.cluster_id, Some(cluster_id));
+            assert_eq!(verdict.reasons, vec![SybilReason::SharedIp]);
+            assert!((verdict.weight - 0.4).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_datacenter_ips_without_a_cluster() {
+        let config = SybilConfig {
+            cluster_threshold: 2,
+            flag_threshold: 0.3,
+        };
+        let user_id = Uuid::new_v4();
+        let signals = vec![UserSignals {
+            user_id,
+            cluster_id: user_id,
+            cluster_size: 1,
+            ips: 2,
+            datacenter_ips: 2,
+            ..Default::default()
+        }];
+        let verdicts = score_accounts(&signals, &config);
+        assert_eq!(verdicts[0].reasons, vec![SybilReason::DatacenterIp]);
+        assert_eq!(verdicts[0].cluster_id, None);
+        assert!((verdicts[0].score - 0.3).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,107 @@
+use crate::db_calls::claim_due_emails::claim_due_emails;
+use crate::db_calls::finish_email_delivery::{
+    mark_email_failed, mark_email_retry, mark_email_sent,
+};
+use crate::supervisor::{is_shutting_down, wait_for_shutdown, Backoff, ShutdownRx};
+use anyhow::anyhow;
+use block_mesh_common::email::kind::EmailKind;
+use block_mesh_common::email::template::render_email;
+use block_mesh_common::email::transport::{EmailTransport, OutgoingEmail};
+use block_mesh_manager_database_domain::domain::email_outbox::EmailOutbox;
+use futures::StreamExt;
+use sqlx::PgPool;
+use std::env;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::timeout;
+
+fn env_or<T: FromStr>(name: &str, default: T) -> T {
+    env::var(name)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+/// Delivers queued emails from `email_outbox` through `transport`.
+/// A failed send is retried with exponential backoff until `EMAIL_MAX_ATTEMPTS`,
+/// after which the row is marked `Failed` and kept for inspection.
+#[tracing::instrument(name = "email_sender", skip_all, err)]
+pub async fn email_sender(
+    pool: PgPool,
+    transport: Arc<dyn EmailTransport>,
+    mut shutdown: ShutdownRx,
+) -> anyhow::Result<()> {
+    let poll = Duration::from_millis(env_or("EMAIL_POLL_MS", 2_000));
+    let batch: i64 = env_or("EMAIL_BATCH_SIZE", 20);
+    let concurrency: usize = env_or("EMAIL_CONCURRENCY", 4);
+    let max_attempts: i32 = env_or("EMAIL_MAX_ATTEMPTS", 8);
+    let send_timeout = Duration::from_secs(env_or("EMAIL_SEND_TIMEOUT_SECS", 30));
+    // a claimed row becomes due again after the lease, in case this worker dies mid-send
+    let lease = send_timeout * 4;
+    let retry = Backoff {
+        initial: Duration::from_secs(env_or("EMAIL_RETRY_INITIAL_SECS", 30)),
+        max: Duration::from_secs(env_or("EMAIL_RETRY_MAX_SECS", 3_600)),
+        healthy_after: Duration::ZERO,
+    };
+    tracing::info!("Delivering emails with the {} transport", transport.name());
+    loop {
+        let due = claim_due_emails(&pool, batch, lease).await?;
+        let claimed = due.len() as i64;
+        futures::stream::iter(due)
+            .for_each_concurrent(concurrency, |email| {
+                let pool = pool.clone();
+                let transport = transport.clone();
+                async move {
+                    let outcome = deliver(transport.as_ref(), &email, send_timeout).await;
+                    let result = match outcome {
+                        Ok(()) => mark_email_sent(&pool, &email.id).await,
+                        Err(e) if email.attempts >= max_attempts => {
+                            tracing::error!("email {} failed for good: {}", email.id, e);
+                            mark_email_failed(&pool, &email.id, &e.to_string()).await
+                        }
+                        Err(e) => {
+                            tracing::warn!(
+                                "email {} attempt {} failed: {}",
+                                email.id,
+                                email.attempts,
+                                e
+                            );
+                            let retry_in = retry.delay(email.attempts.saturating_sub(1) as u32);
+                            mark_email_retry(&pool, &email.id, &e.to_string(), retry_in).await
+                        }
+                    };
+                    if let Err(e) = result {
+                        tracing::error!("failed to record delivery of email {}: {}", email.id, e);
+                    }
+                }
+            })
+            .await;
+        if is_shutting_down(&shutdown) {
+            return Ok(());
+        }
+        if claimed < batch {
+            tokio::select! {
+                _ = tokio::time::sleep(poll) => {}
+                _ = wait_for_shutdown(&mut shutdown) => return Ok(()),
+            }
+        }
+    }
+}
+
+async fn deliver(
+    transport: &dyn EmailTransport,
+    email: &EmailOutbox,
+    send_timeout: Duration,
+) -> anyhow::Result<()> {
+    let kind = EmailKind::from_str(&email.kind)?;
+    let rendered = render_email(kind, &email.locale, &email.action_url, &email.params)?;
+    let outgoing = OutgoingEmail {
+        to: email.to_address.clone(),
+        subject: rendered.subject,
+        html: rendered.html,
+    };
+    timeout(send_timeout, transport.send(&outgoing))
+        .await
+        .map_err(|_| anyhow!("{} send timed out", transport.name()))?
+}
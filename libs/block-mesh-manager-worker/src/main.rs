@@ -1,15 +1,23 @@
 use crate::db_aggregators::users_ip_aggregator::users_ip_aggregator;
 use crate::pg_listener::start_listening;
+use anyhow::anyhow;
 use axum::{Extension, Router};
 use block_mesh_common::constants::BLOCKMESH_PG_NOTIFY_WORKER;
+use block_mesh_common::email::transport::transport_from_env;
 use block_mesh_common::env::load_dotenv::load_dotenv;
 use database_utils::utils::connection::get_pg_pool;
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
 use logger_general::tracing::setup_tracing_stdout_only_with_sentry;
 use serde_json::Value;
+use sqlx::PgPool;
 use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
 use std::{env, mem, process};
 use tokio::net::TcpListener;
+use tokio::sync::watch;
 use tokio::task::JoinHandle;
+use tokio::time::timeout;
 use tower_http::cors::CorsLayer;
 
 mod call_backs;
@@ -17,27 +25,42 @@ mod cron_jobs;
 mod db_aggregators;
 mod db_calls;
 mod domain;
+mod email_sender;
 mod errors;
+mod notifier;
 mod pg_listener;
 mod routes;
+mod supervisor;
 mod utils;
 
 use crate::call_backs::send_to_rx::send_to_rx;
 use crate::cron_jobs::clean_old_tasks::clean_old_tasks;
 use crate::cron_jobs::finalize_daily_cron::finalize_daily_cron;
-use crate::cron_jobs::rpc_cron::rpc_worker_loop;
-use crate::cron_jobs::special_task_cron::special_worker_loop;
+use crate::cron_jobs::notifications_cron::{daily_summary_cron, notifications_cron};
+use crate::cron_jobs::rpc_cron::create_rpc_tasks;
+use crate::cron_jobs::runner::{run_cron_job, schedule_from_env};
+use crate::cron_jobs::special_task_cron::create_special_task_cron;
+use crate::cron_jobs::sybil_scoring_cron::{enrich_ip_cron, sybil_scoring_cron};
 use crate::db_aggregators::aggregates_aggregator::aggregates_aggregator;
 use crate::db_aggregators::analytics_aggregator::analytics_aggregator;
 use crate::db_aggregators::daily_stats_aggregator::daily_stats_aggregator;
 use crate::db_aggregators::joiner_loop::joiner_loop;
+use crate::db_calls::create_server_user::create_server_user;
+use crate::email_sender::email_sender;
+use crate::notifier::Notifier;
 use crate::routes::get_router;
+use crate::supervisor::{shutdown_signal, supervise, wait_for_shutdown, Backoff, ShutdownRx};
 
-pub async fn run_server(listener: TcpListener, app: Router<()>) -> std::io::Result<()> {
+pub async fn run_server(
+    listener: TcpListener,
+    app: Router<()>,
+    mut shutdown: ShutdownRx,
+) -> std::io::Result<()> {
     axum::serve(
         listener,
         app.into_make_service_with_connect_info::<SocketAddr>(),
     )
+    .with_graceful_shutdown(async move { wait_for_shutdown(&mut shutdown).await })
     .await
 }
 
@@ -65,13 +88,16 @@ fn main() {
         mem::forget(_guard);
     }
 
-    let _ = tokio::runtime::Builder::new_multi_thread()
+    let result = tokio::runtime::Builder::new_multi_thread()
         .enable_all()
         .build()
         .unwrap()
         .block_on(async { run().await });
-    tracing::error!("block mesh manager worker stopped, exiting with exit code 1");
-    process::exit(1);
+    if result.is_err() {
+        tracing::error!("block mesh manager worker stopped, exiting with exit code 1");
+        process::exit(1);
+    }
+    tracing::info!("block mesh manager worker stopped gracefully");
 }
 
 #[tracing::instrument(name = "run", skip_all, ret, err)]
@@ -89,58 +115,261 @@ async fn run() -> anyhow::Result<()> {
             .parse()
             .unwrap_or(5000),
     );
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    // The listener stops first so nothing reaches the aggregators once they drain
+    let (listener_shutdown_tx, listener_shutdown_rx) = watch::channel(false);
+    let backoff = Backoff::default();
+    let agg_size: i32 = env::var("AGG_SIZE")
+        .unwrap_or("300".to_string())
+        .parse()
+        .unwrap_or(300);
 
-    let joiner_task = tokio::spawn(joiner_loop(joiner_rx));
-    let rpc_worker_task = tokio::spawn(rpc_worker_loop(db_pool.clone()));
-    let finalize_daily_stats_task = tokio::spawn(finalize_daily_cron(db_pool.clone()));
-    let delete_old_tasks_task = tokio::spawn(clean_old_tasks(db_pool.clone()));
+    if let Ok(mut transaction) = create_txn(&db_pool).await {
+        _ = create_server_user(&mut transaction).await;
+        _ = commit_txn(transaction).await;
+    }
 
-    let db_listen_task = tokio::spawn(start_listening(
-        db_pool.clone(),
-        vec![BLOCKMESH_PG_NOTIFY_WORKER],
-        tx.clone(),
-        send_to_rx,
-    ));
-    let db_aggregator_users_ip_task = tokio::spawn(users_ip_aggregator(
-        joiner_tx.clone(),
-        db_pool.clone(),
-        tx.subscribe(),
-        env::var("AGG_SIZE")
-            .unwrap_or("300".to_string())
-            .parse()
-            .unwrap_or(300),
-        5,
-    ));
-    let db_aggregates_aggregator_task = tokio::spawn(aggregates_aggregator(
-        joiner_tx.clone(),
-        db_pool.clone(),
-        tx.subscribe(),
-        env::var("AGG_SIZE")
-            .unwrap_or("300".to_string())
-            .parse()
-            .unwrap_or(300),
-        5,
-    ));
-    let db_analytics_aggregator_task = tokio::spawn(analytics_aggregator(
-        db_pool.clone(),
-        tx.subscribe(),
-        env::var("AGG_SIZE")
-            .unwrap_or("300".to_string())
-            .parse()
-            .unwrap_or(300),
-        5,
-    ));
-    let db_daily_stats_aggregator_task = tokio::spawn(daily_stats_aggregator(
-        joiner_tx.clone(),
-        db_pool.clone(),
-        tx.subscribe(),
-        env::var("AGG_SIZE")
-            .unwrap_or("300".to_string())
-            .parse()
-            .unwrap_or(300),
-        5,
-    ));
-    let db_special_task = tokio::spawn(special_worker_loop(db_pool.clone()));
+    // Exits once every aggregator dropped its sender, after they drained
+    let joiner_task = tokio::spawn(supervise("joiner_loop", shutdown_rx.clone(), backoff, {
+        let joiner_rx = joiner_rx.clone();
+        move |_| joiner_loop(joiner_rx.clone())
+    }));
+    drop(joiner_rx);
+    let email_transport = transport_from_env().await?;
+
+    let rpc_schedule = schedule_from_env("RPC_CRON", "*/30 * * * * *")?;
+    let finalize_daily_schedule = schedule_from_env("FINALIZE_DAILY_CRON", "0 5 * * * *")?;
+    let clean_old_tasks_schedule = schedule_from_env("CLEAN_OLD_TASKS_CRON", "0 * * * * *")?;
+    let special_schedule = schedule_from_env("SPECIAL_CRON", "*/30 * * * * *")?;
+    let enrich_ip_schedule = schedule_from_env("ENRICH_IP_CRON", "0 * * * * *")?;
+    let sybil_scoring_schedule = schedule_from_env("SYBIL_SCORING_CRON", "0 15 * * * *")?;
+    let notifications_schedule = schedule_from_env("NOTIFICATIONS_CRON", "0 * * * * *")?;
+    let daily_summary_schedule = schedule_from_env("DAILY_SUMMARY_CRON", "0 0 8 * * *")?;
+    let notifier = Arc::new(Notifier::from_env());
+
+    let supervised = vec![
+        tokio::spawn(supervise("rpc_cron", shutdown_rx.clone(), backoff, {
+            let pool = db_pool.clone();
+            move |shutdown| {
+                run_cron_job(
+                    "rpc_cron",
+                    rpc_schedule.clone(),
+                    pool.clone(),
+                    shutdown,
+                    create_rpc_tasks,
+                )
+            }
+        })),
+        tokio::spawn(supervise(
+            "finalize_daily_cron",
+            shutdown_rx.clone(),
+            backoff,
+            {
+                let pool = db_pool.clone();
+                move |shutdown| {
+                    run_cron_job(
+                        "finalize_daily_cron",
+                        finalize_daily_schedule.clone(),
+                        pool.clone(),
+                        shutdown,
+                        finalize_daily_cron,
+                    )
+                }
+            },
+        )),
+        tokio::spawn(supervise(
+            "clean_old_tasks",
+            shutdown_rx.clone(),
+            backoff,
+            {
+                let pool = db_pool.clone();
+                move |shutdown| {
+                    run_cron_job(
+                        "clean_old_tasks",
+                        clean_old_tasks_schedule.clone(),
+                        pool.clone(),
+                        shutdown,
+                        clean_old_tasks,
+                    )
+                }
+            },
+        )),
+        tokio::spawn(supervise("special_cron", shutdown_rx.clone(), backoff, {
+            let pool = db_pool.clone();
+            move |shutdown| {
+                run_cron_job(
+                    "special_cron",
+                    special_schedule.clone(),
+                    pool.clone(),
+                    shutdown,
+                    |pool: PgPool| async move { create_special_task_cron(&pool).await },
+                )
+            }
+        })),
+        tokio::spawn(supervise("enrich_ip_cron", shutdown_rx.clone(), backoff, {
+            let pool = db_pool.clone();
+            move |shutdown| {
+                run_cron_job(
+                    "enrich_ip_cron",
+                    enrich_ip_schedule.clone(),
+                    pool.clone(),
+                    shutdown,
+                    enrich_ip_cron,
+                )
+            }
+        })),
+        tokio::spawn(supervise(
+            "sybil_scoring_cron",
+            shutdown_rx.clone(),
+            backoff,
+            {
+                let pool = db_pool.clone();
+                move |shutdown| {
+                    run_cron_job(
+                        "sybil_scoring_cron",
+                        sybil_scoring_schedule.clone(),
+                        pool.clone(),
+                        shutdown,
+                        sybil_scoring_cron,
+                    )
+                }
+            },
+        )),
+        tokio::spawn(supervise(
+            "notifications_cron",
+            shutdown_rx.clone(),
+            backoff,
+            {
+                let pool = db_pool.clone();
+                let notifier = notifier.clone();
+                move |shutdown| {
+                    let notifier = notifier.clone();
+                    run_cron_job(
+                        "notifications_cron",
+                        notifications_schedule.clone(),
+                        pool.clone(),
+                        shutdown,
+                        move |pool: PgPool| notifications_cron(pool, notifier.clone()),
+                    )
+                }
+            },
+        )),
+        tokio::spawn(supervise(
+            "daily_summary_cron",
+            shutdown_rx.clone(),
+            backoff,
+            {
+                let pool = db_pool.clone();
+                let notifier = notifier.clone();
+                move |shutdown| {
+                    let notifier = notifier.clone();
+                    run_cron_job(
+                        "daily_summary_cron",
+                        daily_summary_schedule.clone(),
+                        pool.clone(),
+                        shutdown,
+                        move |pool: PgPool| daily_summary_cron(pool, notifier.clone()),
+                    )
+                }
+            },
+        )),
+        tokio::spawn(supervise("email_sender", shutdown_rx.clone(), backoff, {
+            let pool = db_pool.clone();
+            move |shutdown| email_sender(pool.clone(), email_transport.clone(), shutdown)
+        })),
+        tokio::spawn(supervise(
+            "db_aggregator_users_ip",
+            shutdown_rx.clone(),
+            backoff,
+            {
+                let joiner_tx = joiner_tx.clone();
+                let pool = db_pool.clone();
+                let tx = tx.clone();
+                move |shutdown| {
+                    users_ip_aggregator(
+                        joiner_tx.clone(),
+                        pool.clone(),
+                        tx.subscribe(),
+                        agg_size,
+                        5,
+                        shutdown,
+                    )
+                }
+            },
+        )),
+        tokio::spawn(supervise(
+            "db_aggregates_aggregator",
+            shutdown_rx.clone(),
+            backoff,
+            {
+                let joiner_tx = joiner_tx.clone();
+                let pool = db_pool.clone();
+                let tx = tx.clone();
+                move |shutdown| {
+                    aggregates_aggregator(
+                        joiner_tx.clone(),
+                        pool.clone(),
+                        tx.subscribe(),
+                        agg_size,
+                        5,
+                        shutdown,
+                    )
+                }
+            },
+        )),
+        tokio::spawn(supervise(
+            "db_analytics_aggregator",
+            shutdown_rx.clone(),
+            backoff,
+            {
+                let pool = db_pool.clone();
+                let tx = tx.clone();
+                move |shutdown| {
+                    analytics_aggregator(pool.clone(), tx.subscribe(), agg_size, 5, shutdown)
+                }
+            },
+        )),
+        tokio::spawn(supervise(
+            "db_daily_stats_aggregator",
+            shutdown_rx.clone(),
+            backoff,
+            {
+                let joiner_tx = joiner_tx.clone();
+                let pool = db_pool.clone();
+                let tx = tx.clone();
+                move |shutdown| {
+                    daily_stats_aggregator(
+                        joiner_tx.clone(),
+                        pool.clone(),
+                        tx.subscribe(),
+                        agg_size,
+                        5,
+                        shutdown,
+                    )
+                }
+            },
+        )),
+    ];
+
+    let listener_task = tokio::spawn(supervise("db_listen", listener_shutdown_rx, backoff, {
+        let pool = db_pool.clone();
+        let tx = tx.clone();
+        move |shutdown| {
+            let pool = pool.clone();
+            let tx = tx.clone();
+            async move {
+                start_listening(
+                    pool,
+                    vec![BLOCKMESH_PG_NOTIFY_WORKER],
+                    tx,
+                    send_to_rx,
+                    shutdown,
+                )
+                .await?;
+                Ok(())
+            }
+        }
+    }));
 
     let router = get_router();
     let cors = CorsLayer::permissive();
@@ -151,19 +380,36 @@ async fn run() -> anyhow::Result<()> {
     let port = env::var("PORT").unwrap_or("8001".to_string());
     let listener = TcpListener::bind(format!("0.0.0.0:{}", port)).await?;
     tracing::info!("Listening on {}", listener.local_addr()?);
-    let server_task = run_server(listener, app);
+    let mut server_task = tokio::spawn(run_server(listener, app, shutdown_rx.clone()));
+
+    let outcome = tokio::select! {
+        _ = shutdown_signal() => Ok(()),
+        o = &mut server_task => Err(anyhow!("server task exit {:?}", o)),
+    };
 
-    tokio::select! {
-        o = db_special_task => panic!("db_special_task exit {:?}", o),
-        o = delete_old_tasks_task => panic!("delete_old_tasks_task exit {:?}", o),
-        o = joiner_task => panic!("joiner_task exit {:?}", o),
-        o = server_task => panic!("server task exit {:?}", o),
-        o = finalize_daily_stats_task => panic!("finalize_daily_stats_task exit {:?}", o),
-        o = rpc_worker_task => panic!("rpc_worker_task exit {:?}", o),
-        o = db_listen_task => panic!("db_listen_task exit {:?}", o),
-        o = db_aggregator_users_ip_task => panic!("db_aggregator_users_ip_task exit {:?}", o),
-        o = db_aggregates_aggregator_task => panic!("db_aggregates_aggregator_task exit {:?}", o),
-        o = db_analytics_aggregator_task => panic!("db_analytics_aggregator_task exit {:?}", o),
-        o = db_daily_stats_aggregator_task => panic!("db_daily_stats_aggregator_task exit {:?}", o)
+    tracing::info!("Shutting down worker, stopping the pg listener");
+    let _ = listener_shutdown_tx.send(true);
+    let grace = Duration::from_secs(
+        env::var("SHUTDOWN_GRACE_SECS")
+            .unwrap_or("30".to_string())
+            .parse()
+            .unwrap_or(30),
+    );
+    let drain = async {
+        let _ = listener_task.await;
+        tracing::info!("pg listener stopped, draining aggregators");
+        let _ = shutdown_tx.send(true);
+        for task in supervised {
+            let _ = task.await;
+        }
+        if !server_task.is_finished() {
+            let _ = server_task.await;
+        }
+        drop(joiner_tx);
+        let _ = joiner_task.await;
+    };
+    if timeout(grace, drain).await.is_err() {
+        tracing::error!("worker did not drain within {:?}", grace);
     }
+    outcome
 }
@@ -0,0 +1,94 @@
+use crate::db_calls::record_notification::record_notification;
+use crate::domain::notification::{NotificationCandidate, NotificationConfig};
+use block_mesh_common::email::template::render_text;
+use block_mesh_common::siws::is_wallet_placeholder_email;
+use block_mesh_manager_database_domain::domain::email_outbox::enqueue_email;
+use block_mesh_manager_database_domain::domain::notification_preferences::{
+    get_or_create_notification_preferences, NotificationKind,
+};
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+use sqlx::PgPool;
+use std::env;
+use teloxide::requests::Requester;
+use teloxide::types::ChatId;
+use teloxide::Bot;
+
+/// Delivers detected events through the channels each user picked.
+/// Email goes through the outbox so it gets the same retries as transactional mail,
+/// Telegram is sent right away when `TELEGRAM_NOTIFICATIONS_BOT_TOKEN` is set.
+pub struct Notifier {
+    pub config: NotificationConfig,
+    bot: Option<Bot>,
+}
+
+impl Notifier {
+    pub fn from_env() -> Self {
+        let bot = env::var("TELEGRAM_NOTIFICATIONS_BOT_TOKEN")
+            .ok()
+            .filter(|token| !token.is_empty())
+            .map(Bot::new);
+        Self {
+            config: NotificationConfig::default(),
+            bot,
+        }
+    }
+
+    #[tracing::instrument(name = "notify", skip(self, pool, candidate), fields(user_id = %candidate.user_id), err)]
+    pub async fn notify(
+        &self,
+        pool: &PgPool,
+        kind: NotificationKind,
+        candidate: &NotificationCandidate,
+    ) -> anyhow::Result<()> {
+        let mut transaction = create_txn(pool).await?;
+        let preferences =
+            get_or_create_notification_preferences(&mut *transaction, &candidate.user_id).await?;
+        if !preferences.is_enabled(kind) {
+            return Ok(());
+        }
+        // recorded together with the email, a failed enqueue must not mark the event delivered
+        if !record_notification(
+            &mut *transaction,
+            &candidate.user_id,
+            kind,
+            &candidate.dedupe_key,
+            &candidate.params.0,
+        )
+        .await?
+        {
+            return Ok(());
+        }
+        let action_url = self.config.action_url(kind);
+        if preferences.email_enabled && !is_wallet_placeholder_email(&candidate.email) {
+            let mut params = candidate.params.0.clone();
+            params["unsubscribe_url"] = format!(
+                "{}/unsubscribe?token={}&kind={}",
+                self.config.base_url, preferences.unsubscribe_token, kind
+            )
+            .into();
+            enqueue_email(
+                &mut *transaction,
+                kind.email_kind(),
+                &candidate.email,
+                &preferences.locale,
+                &action_url,
+                &params,
+            )
+            .await?;
+        }
+        commit_txn(transaction).await?;
+        if let (Some(bot), Some(chat_id)) = (&self.bot, preferences.telegram_chat_id) {
+            let text = render_text(
+                kind.email_kind(),
+                &preferences.locale,
+                &action_url,
+                &candidate.params.0,
+            )?;
+            // a blocked bot or deleted chat shouldn't keep the email from going out
+            if let Err(e) = bot.send_message(ChatId(chat_id), text).await {
+                tracing::warn!("telegram notification to {} failed: {}", chat_id, e);
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,3 +1,4 @@
+use crate::supervisor::{wait_for_shutdown, ShutdownRx};
 use serde::de::DeserializeOwned;
 use serde_json::Value;
 use sqlx::error::Error;
@@ -17,6 +18,7 @@ pub async fn start_listening<T, F, R, Fut>(
     channels: Vec<&str>,
     tx: Sender<Value>,
     call_back: F,
+    mut shutdown: ShutdownRx,
 ) -> Result<(), Error>
 where
     T: DeserializeOwned + Sized + Debug,
@@ -27,14 +29,19 @@ where
     listener.listen_all(channels).await?;
     let tx = Arc::new(tx);
     loop {
-        while let Ok(Some(notification)) = listener.try_recv().await {
-            let tx = tx.clone();
-            let string = notification.payload().to_owned();
-            if let Ok(payload) = serde_json::from_str::<T>(&string) {
-                call_back(payload, tx).await;
-            } else {
-                error!("Failed to deserialize {:?}", string);
+        tokio::select! {
+            notification = listener.try_recv() => {
+                if let Ok(Some(notification)) = notification {
+                    let tx = tx.clone();
+                    let string = notification.payload().to_owned();
+                    if let Ok(payload) = serde_json::from_str::<T>(&string) {
+                        call_back(payload, tx).await;
+                    } else {
+                        error!("Failed to deserialize {:?}", string);
+                    }
+                }
             }
+            _ = wait_for_shutdown(&mut shutdown) => return Ok(()),
         }
     }
 }
@@ -0,0 +1,126 @@
+use rand::Rng;
+use std::env;
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+pub type ShutdownRx = watch::Receiver<bool>;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Backoff {
+    pub initial: Duration,
+    pub max: Duration,
+    /// A run that lasted at least this long resets the backoff to `initial`.
+    pub healthy_after: Duration,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_millis(
+                env::var("SUPERVISOR_INITIAL_BACKOFF_MS")
+                    .unwrap_or("1000".to_string())
+                    .parse()
+                    .unwrap_or(1_000),
+            ),
+            max: Duration::from_millis(
+                env::var("SUPERVISOR_MAX_BACKOFF_MS")
+                    .unwrap_or("60000".to_string())
+                    .parse()
+                    .unwrap_or(60_000),
+            ),
+            healthy_after: Duration::from_secs(60),
+        }
+    }
+}
+
+impl Backoff {
+    /// Exponential delay for the given attempt with +/- 20% jitter.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let base = self
+            .initial
+            .saturating_mul(2u32.saturating_pow(attempt.min(16)))
+            .min(self.max);
+        let jitter = rand::thread_rng().gen_range(0.8..1.2);
+        base.mul_f64(jitter)
+    }
+}
+
+pub fn is_shutting_down(shutdown: &ShutdownRx) -> bool {
+    *shutdown.borrow()
+}
+
+/// Resolves once shutdown was requested, or the sender was dropped.
+pub async fn wait_for_shutdown(shutdown: &mut ShutdownRx) {
+    while !*shutdown.borrow_and_update() {
+        if shutdown.changed().await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Resolves on SIGTERM or Ctrl-C.
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("failed to install SIGTERM handler: {:?}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+    tokio::select! {
+        _ = ctrl_c => tracing::info!("received Ctrl-C"),
+        _ = terminate => tracing::info!("received SIGTERM"),
+    }
+}
+
+/// Runs the task produced by `factory` and restarts it with backoff whenever it
+/// exits, errors or panics. The task receives the shutdown receiver and is expected
+/// to return on its own once shutdown is requested, so it can drain buffered work.
+#[tracing::instrument(name = "supervise", skip(shutdown, factory))]
+pub async fn supervise<F, Fut>(
+    name: &'static str,
+    shutdown: ShutdownRx,
+    backoff: Backoff,
+    factory: F,
+) -> anyhow::Result<()>
+where
+    F: Fn(ShutdownRx) -> Fut,
+    Fut: Future<Output = anyhow::Result<()>> + Send + 'static,
+{
+    let mut attempt = 0;
+    loop {
+        let started = Instant::now();
+        let result = tokio::spawn(factory(shutdown.clone())).await;
+        if is_shutting_down(&shutdown) {
+            tracing::info!("{} stopped for shutdown: {:?}", name, result);
+            return Ok(());
+        }
+        match result {
+            Ok(Ok(())) => tracing::error!("{} exited unexpectedly", name),
+            Ok(Err(e)) => tracing::error!("{} failed: {:?}", name, e),
+            Err(e) => tracing::error!("{} panicked: {:?}", name, e),
+        }
+        if started.elapsed() >= backoff.healthy_after {
+            attempt = 0;
+        }
+        let delay = backoff.delay(attempt);
+        attempt = attempt.saturating_add(1);
+        tracing::warn!("restarting {} in {:?} (attempt {})", name, delay, attempt);
+        let mut shutdown_wait = shutdown.clone();
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = wait_for_shutdown(&mut shutdown_wait) => return Ok(()),
+        }
+    }
+}
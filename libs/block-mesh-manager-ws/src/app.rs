@@ -6,6 +6,7 @@ use axum::http::StatusCode;
 use axum::response::IntoResponse;
 use axum::routing::get;
 use axum::{Json, Router};
+use block_mesh_common::rate_limit::{default_rules, RateLimitLayer, RateLimiter};
 use block_mesh_manager_database_domain::domain::task_limit::TaskLimit;
 use database_utils::utils::health_check::health_check;
 use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
@@ -103,6 +104,8 @@ pub async fn stats(State(state): State<Arc<AppState>>) -> Json<StatsResponse> {
 }
 
 pub async fn app(listener: TcpListener, state: Arc<AppState>) {
+    // limits the handshake only, messages on an open socket are not counted
+    let rate_limit = RateLimitLayer::new(RateLimiter::new(state.redis.clone(), default_rules()));
     let router = Router::new()
         .route("/", get(health))
         .route("/health", get(health))
@@ -111,7 +114,7 @@ pub async fn app(listener: TcpListener, state: Arc<AppState>) {
         .route("/stats", get(stats))
         .route("/summary", get(summary))
         .route("/detailed_summary", get(detailed_summary))
-        .route("/ws", get(ws_handler))
+        .route("/ws", get(ws_handler).layer(rate_limit))
         .with_state(state);
 
     axum::serve(
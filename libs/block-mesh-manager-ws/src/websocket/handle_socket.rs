@@ -2,14 +2,20 @@ use crate::state::AppState;
 use crate::websocket::messenger::messenger;
 use crate::websocket::receiver::receiver;
 use axum::extract::ws::WebSocket;
+use block_mesh_manager_database_domain::domain::node_auth::NodeIdentity;
 use futures::StreamExt;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tokio::sync::Notify;
-use uuid::Uuid;
 
 /// Actual websocket statemachine (one will be spawned per connection)
-pub async fn handle_socket(socket: WebSocket, ip: String, state: Arc<AppState>, user_id: Uuid) {
+pub async fn handle_socket(
+    socket: WebSocket,
+    ip: String,
+    state: Arc<AppState>,
+    identity: NodeIdentity,
+) {
+    let user_id = identity.user_id;
     let is_closing = Arc::new(AtomicBool::new(false));
     let (ws_sink, ws_stream) = socket.split();
     let (sink_task, sink_tx) = messenger(ws_sink, is_closing.clone());
@@ -19,6 +25,7 @@ pub async fn handle_socket(socket: WebSocket, ip: String, state: Arc<AppState>,
         ws_stream,
         is_closing.clone(),
         ip.clone(),
+        identity,
         task_scheduler_notifier.clone(),
         state.clone(),
     )
@@ -2,6 +2,7 @@ use crate::state::AppState;
 use axum::extract::ws::Message;
 use block_mesh_common::interfaces::server_api::HandlerMode;
 use block_mesh_common::interfaces::ws_api::WsClientMessage;
+use block_mesh_manager_database_domain::domain::node_auth::NodeIdentity;
 use block_mesh_manager_database_domain::domain::report_uptime_content::report_uptime_content;
 use block_mesh_manager_database_domain::domain::submit_bandwidth_content::submit_bandwidth_content;
 use block_mesh_manager_database_domain::domain::submit_task_content::submit_task_content;
@@ -13,11 +14,12 @@ use std::sync::Arc;
 pub async fn process_message(
     msg: Message,
     ip: String,
+    identity: &NodeIdentity,
     state: Arc<AppState>,
 ) -> ControlFlow<(), Option<WsClientMessage>> {
     match msg {
         Message::Text(text) => {
-            let ws_client_message = process_client_message(&text, ip, state).await;
+            let ws_client_message = process_client_message(&text, ip, identity, state).await;
             return ControlFlow::Continue(ws_client_message);
         }
         Message::Binary(bytes) => {
@@ -53,6 +55,7 @@ pub async fn process_message(
 async fn process_client_message(
     text: &str,
     ip: String,
+    identity: &NodeIdentity,
     state: Arc<AppState>,
 ) -> Option<WsClientMessage> {
     if text == "pong" {
@@ -62,8 +65,10 @@ async fn process_client_message(
         Ok(message) => {
             match &message {
                 WsClientMessage::CompleteTask(query) => {
+                    // the connection was authenticated on upgrade, credentials in the message are ignored
                     let _ = submit_task_content(
                         &state.pool,
+                        identity,
                         query.clone(),
                         None,
                         HandlerMode::WebSocket,
@@ -73,11 +78,11 @@ async fn process_client_message(
                 WsClientMessage::ReportBandwidth(body) => {
                     let _ = submit_bandwidth_content(&state.pool, body.clone()).await;
                 }
-                WsClientMessage::ReportUptime(query) => {
+                WsClientMessage::ReportUptime(_) => {
                     let _ = report_uptime_content(
                         &state.pool,
                         ip.clone(),
-                        query.clone(),
+                        identity,
                         None,
                         HandlerMode::WebSocket,
                         env::var("POLLING_INTERVAL")
@@ -1,46 +1,29 @@
 use crate::errors::Error;
 use crate::state::AppState;
 use crate::websocket::handle_socket::handle_socket;
-use anyhow::{anyhow, Context};
-use axum::extract::{Query, State, WebSocketUpgrade};
+use anyhow::Context;
+use axum::extract::{State, WebSocketUpgrade};
 use axum::response::IntoResponse;
-use block_mesh_manager_database_domain::domain::find_token::find_token;
-use block_mesh_manager_database_domain::domain::get_user_opt_by_email::get_user_opt_by_email;
+use block_mesh_manager_database_domain::domain::api_token::ApiTokenScope;
+use block_mesh_manager_database_domain::domain::node_auth::{authenticate_node, NodeCredentials};
+use block_mesh_manager_database_domain::domain::touch_api_token::touch_api_token;
 use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
 use http::HeaderMap;
-use std::collections::HashMap;
 use std::env;
-use std::str::FromStr;
 use std::sync::Arc;
-use uuid::Uuid;
 
 #[tracing::instrument(name = "ws_handler", skip_all)]
 pub async fn ws_handler(
     headers: HeaderMap,
     ws: WebSocketUpgrade,
     State(state): State<Arc<AppState>>,
-    Query(query): Query<HashMap<String, String>>,
+    credentials: NodeCredentials,
 ) -> Result<impl IntoResponse, Error> {
-    let email = query
-        .get("email")
-        .ok_or(anyhow!("Missing email".to_string()))?
-        .clone();
-    let api_token = query
-        .get("api_token")
-        .ok_or(anyhow!("Missing token".to_string()))?;
-    let api_token = Uuid::from_str(api_token).context("Cannot deserialize UUID")?;
     let follower_pool = state.follower_pool.clone();
     let mut transaction = create_txn(&follower_pool).await?;
-    let user = get_user_opt_by_email(&mut *transaction, &email)
-        .await?
-        .ok_or(anyhow!(String::from("User email is not present in DB")))?;
-    let api_token = find_token(&mut transaction, &api_token)
-        .await?
-        .ok_or(anyhow!("Api Token Not Found"))?;
+    let identity =
+        authenticate_node(&mut transaction, &credentials, ApiTokenScope::NodeReport).await?;
     commit_txn(transaction).await?;
-    if user.id != api_token.user_id {
-        return Err(Error::from(anyhow!("User Not Found")));
-    }
     // let pool = state.pool.clone();
     // if let Ok(mut transaction) = create_txn(&pool).await {
     //     let _ = prep_user(&mut transaction, &user.id).await;
@@ -57,6 +40,14 @@ pub async fn ws_handler(
         "127.0.0.1"
     }
     .to_string();
+    if let Ok(mut transaction) = create_txn(&state.pool).await {
+        if touch_api_token(&mut transaction, &identity.api_token, &header_ip)
+            .await
+            .is_ok()
+        {
+            let _ = commit_txn(transaction).await;
+        }
+    }
 
-    Ok(ws.on_upgrade(move |socket| handle_socket(socket, header_ip, state, user.id)))
+    Ok(ws.on_upgrade(move |socket| handle_socket(socket, header_ip, state, identity)))
 }
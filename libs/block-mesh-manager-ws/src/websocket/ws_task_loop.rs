@@ -4,7 +4,9 @@ use block_mesh_common::interfaces::server_api::GetTaskResponse;
 use block_mesh_common::interfaces::ws_api::WsServerMessage;
 use block_mesh_manager_database_domain::domain::fetch_latest_cron_settings::fetch_latest_cron_settings;
 use block_mesh_manager_database_domain::domain::find_pending_tasks_with_limit::find_pending_tasks_with_limit;
-use block_mesh_manager_database_domain::domain::task::{GetTask, TaskStatus};
+use block_mesh_manager_database_domain::domain::task::{
+    without_credential_headers, GetTask, TaskStatus,
+};
 use block_mesh_manager_database_domain::domain::task_limit::TaskLimit;
 use block_mesh_manager_database_domain::domain::update_task_assigned::update_task_assigned;
 use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
@@ -55,7 +57,7 @@ pub async fn assign_tasks_to_users(
                     id: task.id,
                     url: task.url,
                     method: task.method.to_string(),
-                    headers: task.headers,
+                    headers: without_credential_headers(task.headers),
                     body: task.body,
                 })],
                 queue,
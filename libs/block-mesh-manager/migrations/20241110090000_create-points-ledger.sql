@@ -0,0 +1,147 @@
+CREATE TABLE points_ledger
+(
+    id          uuid PRIMARY KEY,
+    user_id     uuid             NOT NULL,
+    day         DATE             NOT NULL,
+    source      TEXT             NOT NULL,
+    source_id   uuid,
+    base_points DOUBLE PRECISION NOT NULL,
+    multiplier  DOUBLE PRECISION NOT NULL DEFAULT 1.0,
+    points      DOUBLE PRECISION NOT NULL,
+    reverses_id uuid,
+    note        TEXT,
+    created_by  uuid,
+    created_at  timestamptz      NOT NULL,
+    CONSTRAINT fk_user FOREIGN KEY (user_id) REFERENCES users (id),
+    CONSTRAINT fk_reverses FOREIGN KEY (reverses_id) REFERENCES points_ledger (id)
+);
+
+CREATE INDEX points_ledger_user_id_day ON points_ledger (user_id, day);
+CREATE INDEX points_ledger_day ON points_ledger (day);
+CREATE INDEX points_ledger_source ON points_ledger (source);
+-- a credit can only be reversed once
+CREATE UNIQUE INDEX points_ledger_reverses_id ON points_ledger (reverses_id) WHERE reverses_id IS NOT NULL;
+-- the same source record can't be credited twice
+CREATE UNIQUE INDEX points_ledger_user_id_source_source_id ON points_ledger (user_id, source, source_id) WHERE source_id IS NOT NULL;
+
+-- -- -----
+CREATE OR REPLACE FUNCTION points_ledger_append_only() RETURNS TRIGGER AS
+$$
+BEGIN
+    RAISE EXCEPTION 'points_ledger is append-only, insert a reversal instead';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trg_points_ledger_append_only
+    BEFORE UPDATE OR DELETE
+    ON points_ledger
+    FOR EACH ROW
+EXECUTE FUNCTION points_ledger_append_only();
+
+-- -- -----
+-- Backfill finalized days and one time bonuses. Past perk multipliers were never recorded,
+-- so every finalized day is credited with the user's current perks.
+WITH multipliers AS (SELECT user_id, EXP(SUM(LN(multiplier))) AS multiplier
+                     FROM perks
+                     WHERE multiplier > 0
+                     GROUP BY user_id),
+     finalized AS (SELECT daily_stats.id,
+                          daily_stats.user_id,
+                          daily_stats.day,
+                          daily_stats.uptime * (100.0 / (24.0 * 60.0 * 60.0))           AS uptime_points,
+                          CAST(daily_stats.tasks_count AS DOUBLE PRECISION) * 10.0      AS tasks_points,
+                          COALESCE(multipliers.multiplier, 1.0)                         AS multiplier
+                   FROM daily_stats
+                            LEFT JOIN multipliers ON multipliers.user_id = daily_stats.user_id
+                   WHERE daily_stats.status = 'Finalized')
+INSERT
+INTO points_ledger (id, user_id, day, source, source_id, base_points, multiplier, points, created_at)
+SELECT gen_random_uuid(), user_id, day, 'Uptime', id, uptime_points, 1.0, uptime_points, now()
+FROM finalized
+UNION ALL
+SELECT gen_random_uuid(), user_id, day, 'Tasks', id, tasks_points, 1.0, tasks_points, now()
+FROM finalized
+UNION ALL
+SELECT gen_random_uuid(),
+       user_id,
+       day,
+       'PerkMultiplier',
+       id,
+       uptime_points + tasks_points,
+       multiplier,
+       (uptime_points + tasks_points) * (multiplier - 1.0),
+       now()
+FROM finalized
+WHERE multiplier <> 1.0;
+
+INSERT
+INTO points_ledger (id, user_id, day, source, source_id, base_points, multiplier, points, created_at)
+SELECT gen_random_uuid(), user_id, created_at::date, 'PerkOneTimeBonus', id, one_time_bonus, 1.0, one_time_bonus, now()
+FROM perks
+WHERE one_time_bonus <> 0;
+
+-- -- -----
+-- The dashboard used to show max(lifetime uptime and tasks with the current perks + bonuses,
+-- uptime and tasks + bonuses + every daily stat with the current perks), lifetime values taken
+-- from the aggregates when they exceed the daily stats. Whatever the backfill above does not
+-- cover is credited once as Legacy, so no total shrinks. The OnGoing day is left out, it is
+-- still counted live until it is finalized. The entry is not tied to a day.
+WITH multipliers AS (SELECT user_id, EXP(SUM(LN(multiplier))) AS multiplier
+                     FROM perks
+                     WHERE multiplier > 0
+                     GROUP BY user_id),
+     bonuses AS (SELECT user_id, SUM(one_time_bonus) AS bonus
+                 FROM perks
+                 GROUP BY user_id),
+     days AS (SELECT user_id,
+                     SUM(uptime)                                                  AS uptime,
+                     SUM(tasks_count)                                             AS tasks_count,
+                     SUM(uptime * (100.0 / (24.0 * 60.0 * 60.0))
+                         + CAST(tasks_count AS DOUBLE PRECISION) * 10.0)          AS raw_points,
+                     SUM(CASE
+                             WHEN status = 'OnGoing' THEN uptime * (100.0 / (24.0 * 60.0 * 60.0))
+                                 + CAST(tasks_count AS DOUBLE PRECISION) * 10.0
+                             ELSE 0.0 END)                                        AS ongoing_raw_points
+              FROM daily_stats
+              GROUP BY user_id),
+     lifetime AS (SELECT user_id,
+                         MAX(CASE
+                                 WHEN name = 'Uptime' AND jsonb_typeof(value) = 'number'
+                                     THEN (value #>> '{}')::DOUBLE PRECISION END) AS uptime,
+                         MAX(CASE
+                                 WHEN name = 'Tasks' AND (value #>> '{}') ~ '^-?[0-9]+$'
+                                     THEN (value #>> '{}')::BIGINT END)           AS tasks_count
+                  FROM aggregates
+                  WHERE name IN ('Uptime', 'Tasks')
+                  GROUP BY user_id),
+     backfilled AS (SELECT user_id, SUM(points) AS points
+                    FROM points_ledger
+                    GROUP BY user_id),
+     totals AS (SELECT users.id                                                   AS user_id,
+                       COALESCE(multipliers.multiplier, 1.0)                      AS multiplier,
+                       COALESCE(bonuses.bonus, 0.0)                               AS bonus,
+                       COALESCE(days.raw_points, 0.0)                             AS days_raw_points,
+                       COALESCE(days.ongoing_raw_points, 0.0)                     AS ongoing_raw_points,
+                       GREATEST(FLOOR(COALESCE(lifetime.uptime, 0.0)), FLOOR(COALESCE(days.uptime, 0.0)))
+                           * (100.0 / (24.0 * 60.0 * 60.0))
+                           + CAST(GREATEST(COALESCE(lifetime.tasks_count, 0), COALESCE(days.tasks_count, 0))
+                                  AS DOUBLE PRECISION) * 10.0                     AS lifetime_raw_points,
+                       COALESCE(backfilled.points, 0.0)                           AS backfilled
+                FROM users
+                         LEFT JOIN multipliers ON multipliers.user_id = users.id
+                         LEFT JOIN bonuses ON bonuses.user_id = users.id
+                         LEFT JOIN days ON days.user_id = users.id
+                         LEFT JOIN lifetime ON lifetime.user_id = users.id
+                         LEFT JOIN backfilled ON backfilled.user_id = users.id),
+     legacy AS (SELECT user_id,
+                       GREATEST(
+                               FLOOR(GREATEST(lifetime_raw_points * multiplier + bonus, 0.0)),
+                               FLOOR(GREATEST(lifetime_raw_points + bonus, 0.0))
+                                   + FLOOR(GREATEST(days_raw_points * multiplier, 0.0))
+                       ) - backfilled - ongoing_raw_points * multiplier AS points
+                FROM totals)
+INSERT
+INTO points_ledger (id, user_id, day, source, base_points, multiplier, points, note, created_at)
+SELECT gen_random_uuid(), user_id, '1970-01-01', 'Legacy', points, 1.0, points, 'total shown before the ledger', now()
+FROM legacy
+WHERE points > 0;
@@ -0,0 +1,40 @@
+CREATE TABLE reward_rules
+(
+    id             uuid PRIMARY KEY,
+    version        INTEGER          NOT NULL UNIQUE,
+    effective_from DATE             NOT NULL,
+    uptime_factor  DOUBLE PRECISION NOT NULL,
+    tasks_factor   DOUBLE PRECISION NOT NULL,
+    perks          jsonb            NOT NULL DEFAULT '{}'::jsonb,
+    daily_cap      DOUBLE PRECISION,
+    ip_daily_cap   DOUBLE PRECISION,
+    created_at     timestamptz      NOT NULL
+);
+
+CREATE INDEX reward_rules_effective_from ON reward_rules (effective_from);
+
+CREATE TABLE reward_campaigns
+(
+    id         uuid PRIMARY KEY,
+    name       TEXT             NOT NULL,
+    multiplier DOUBLE PRECISION NOT NULL,
+    starts_at  timestamptz      NOT NULL,
+    ends_at    timestamptz      NOT NULL,
+    created_at timestamptz      NOT NULL,
+    CONSTRAINT reward_campaigns_window CHECK (starts_at < ends_at)
+);
+
+CREATE INDEX reward_campaigns_starts_at_ends_at ON reward_campaigns (starts_at, ends_at);
+
+-- -- -----
+-- Version 1 keeps the values that used to be hard-coded
+INSERT INTO reward_rules (id, version, effective_from, uptime_factor, tasks_factor, perks, daily_cap, ip_daily_cap, created_at)
+VALUES (gen_random_uuid(),
+        1,
+        '1970-01-01',
+        100.0 / (24.0 * 60.0 * 60.0),
+        10.0,
+        '{"wallet": {"multiplier": 1.1, "one_time_bonus": 0.0}, "twitter": {"multiplier": 1.0, "one_time_bonus": 500.0}}'::jsonb,
+        NULL,
+        NULL,
+        now());
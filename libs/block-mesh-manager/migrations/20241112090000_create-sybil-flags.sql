@@ -0,0 +1,34 @@
+ALTER TABLE ip_addresses
+    ADD COLUMN asn           INTEGER,
+    ADD COLUMN is_datacenter BOOLEAN NOT NULL DEFAULT false,
+    ADD COLUMN is_vpn        BOOLEAN NOT NULL DEFAULT false,
+    ADD COLUMN is_proxy      BOOLEAN NOT NULL DEFAULT false,
+    ADD COLUMN is_tor        BOOLEAN NOT NULL DEFAULT false,
+    ADD COLUMN is_abuser     BOOLEAN NOT NULL DEFAULT false;
+
+CREATE INDEX ip_addresses_asn ON ip_addresses (asn);
+
+ALTER TABLE analytics
+    ADD COLUMN device_id uuid;
+
+CREATE INDEX analytics_device_id ON analytics (device_id);
+
+CREATE TABLE sybil_flags
+(
+    id          uuid PRIMARY KEY,
+    user_id     uuid             NOT NULL UNIQUE,
+    cluster_id  uuid,
+    score       DOUBLE PRECISION NOT NULL,
+    weight      DOUBLE PRECISION NOT NULL,
+    reasons     jsonb            NOT NULL DEFAULT '[]'::jsonb,
+    status      TEXT             NOT NULL,
+    reviewed_by uuid,
+    reviewed_at timestamptz,
+    created_at  timestamptz      NOT NULL,
+    updated_at  timestamptz      NOT NULL,
+    CONSTRAINT fk_user FOREIGN KEY (user_id) REFERENCES users (id),
+    CONSTRAINT fk_reviewed_by FOREIGN KEY (reviewed_by) REFERENCES users (id)
+);
+
+CREATE INDEX sybil_flags_status_score ON sybil_flags (status, score DESC);
+CREATE INDEX sybil_flags_cluster_id ON sybil_flags (cluster_id);
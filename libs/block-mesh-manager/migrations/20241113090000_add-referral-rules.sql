@@ -0,0 +1,13 @@
+ALTER TABLE reward_rules
+    ADD COLUMN referral_rates        DOUBLE PRECISION[] NOT NULL DEFAULT '{}',
+    ADD COLUMN referral_min_uptime   DOUBLE PRECISION   NOT NULL DEFAULT 0,
+    ADD COLUMN referral_requires_verified_email BOOLEAN NOT NULL DEFAULT true;
+
+-- -- -----
+-- 10% of a direct referral's daily points, 5% of a second level referral's,
+-- only for days with at least an hour of uptime
+UPDATE reward_rules
+SET referral_rates      = '{0.1, 0.05}',
+    referral_min_uptime = 3600
+WHERE version = 1;
+
@@ -0,0 +1,9 @@
+ALTER TABLE api_tokens
+    ADD COLUMN name         TEXT        NOT NULL DEFAULT 'default',
+    ADD COLUMN scopes       TEXT[]      NOT NULL DEFAULT '{node-report,read-stats,task-create}',
+    ADD COLUMN last_used_at timestamptz,
+    ADD COLUMN last_used_ip TEXT,
+    ADD COLUMN expires_at   timestamptz,
+    ADD COLUMN revoked_at   timestamptz;
+
+CREATE INDEX api_tokens_user_id_status_name ON api_tokens (user_id, status, name);
@@ -0,0 +1,34 @@
+CREATE TABLE user_totp
+(
+    user_id        UUID PRIMARY KEY REFERENCES users (id) ON DELETE CASCADE,
+    secret         TEXT        NOT NULL,
+    enabled        BOOLEAN     NOT NULL DEFAULT FALSE,
+    last_used_step BIGINT,
+    created_at     timestamptz NOT NULL,
+    enabled_at     timestamptz
+);
+
+CREATE TABLE recovery_codes
+(
+    id         UUID PRIMARY KEY,
+    user_id    UUID        NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    code_hash  TEXT        NOT NULL,
+    used_at    timestamptz,
+    created_at timestamptz NOT NULL
+);
+
+CREATE INDEX recovery_codes_user_id_code_hash ON recovery_codes (user_id, code_hash);
+
+CREATE TABLE user_sessions
+(
+    id           UUID PRIMARY KEY,
+    user_id      UUID        NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    session_id   TEXT,
+    ip           TEXT,
+    user_agent   TEXT,
+    created_at   timestamptz NOT NULL,
+    last_seen_at timestamptz NOT NULL,
+    revoked_at   timestamptz
+);
+
+CREATE INDEX user_sessions_user_id_revoked_at ON user_sessions (user_id, revoked_at);
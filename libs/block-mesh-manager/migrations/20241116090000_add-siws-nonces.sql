@@ -0,0 +1,8 @@
+CREATE TABLE siws_nonces
+(
+    nonce      TEXT PRIMARY KEY,
+    created_at timestamptz NOT NULL,
+    expires_at timestamptz NOT NULL
+);
+
+CREATE INDEX siws_nonces_expires_at ON siws_nonces (expires_at);
@@ -0,0 +1,60 @@
+CREATE TABLE wallet_challenges
+(
+    id             uuid PRIMARY KEY,
+    user_id        uuid        NOT NULL,
+    wallet_address TEXT        NOT NULL,
+    message        TEXT        NOT NULL,
+    created_at     timestamptz NOT NULL,
+    expires_at     timestamptz NOT NULL,
+    used_at        timestamptz NULL,
+    CONSTRAINT fk_user FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+);
+
+CREATE INDEX wallet_challenges_user_id ON wallet_challenges (user_id);
+CREATE INDEX wallet_challenges_expires_at ON wallet_challenges (expires_at);
+
+CREATE TABLE wallet_link_events
+(
+    id             uuid PRIMARY KEY,
+    user_id        uuid        NOT NULL,
+    wallet_address TEXT        NOT NULL,
+    action         TEXT        NOT NULL,
+    ip             TEXT        NULL,
+    created_at     timestamptz NOT NULL,
+    CONSTRAINT fk_user FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+);
+
+CREATE INDEX wallet_link_events_user_id ON wallet_link_events (user_id);
+CREATE INDEX wallet_link_events_wallet_address ON wallet_link_events (wallet_address);
+
+-- seed the audit trail with the wallets linked before it existed
+INSERT INTO wallet_link_events (id, user_id, wallet_address, action, ip, created_at)
+SELECT gen_random_uuid(), id, wallet_address, 'link', NULL, now()
+FROM users
+WHERE wallet_address IS NOT NULL;
+
+-- keep the oldest account of any wallet linked more than once, the newer ones are unlinked the
+-- way unlink_user_wallet does it: audited, and without the wallet perk multiplier
+CREATE TEMP TABLE duplicate_wallet_links ON COMMIT DROP AS
+SELECT id AS user_id, wallet_address
+FROM users
+WHERE wallet_address IS NOT NULL
+  AND id NOT IN (SELECT DISTINCT ON (wallet_address) id
+                 FROM users
+                 WHERE wallet_address IS NOT NULL
+                 ORDER BY wallet_address, created_at);
+
+INSERT INTO wallet_link_events (id, user_id, wallet_address, action, ip, created_at)
+SELECT gen_random_uuid(), user_id, wallet_address, 'unlink', NULL, now()
+FROM duplicate_wallet_links;
+
+DELETE FROM perks
+WHERE name = 'wallet'
+  AND user_id IN (SELECT user_id FROM duplicate_wallet_links);
+
+UPDATE users
+SET wallet_address = NULL
+WHERE id IN (SELECT user_id FROM duplicate_wallet_links);
+
+CREATE UNIQUE INDEX IF NOT EXISTS users_wallet_address_unique ON users (wallet_address)
+    WHERE wallet_address IS NOT NULL;
@@ -0,0 +1,15 @@
+ALTER TABLE users ADD COLUMN deleted_at timestamptz NULL;
+
+CREATE TABLE email_change_requests
+(
+    id           uuid PRIMARY KEY,
+    user_id      uuid        NOT NULL,
+    new_email    TEXT        NOT NULL,
+    token        TEXT        NOT NULL UNIQUE,
+    created_at   timestamptz NOT NULL,
+    expires_at   timestamptz NOT NULL,
+    confirmed_at timestamptz NULL,
+    CONSTRAINT fk_user FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+);
+
+CREATE INDEX email_change_requests_user_id ON email_change_requests (user_id);
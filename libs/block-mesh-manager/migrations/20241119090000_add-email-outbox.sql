@@ -0,0 +1,17 @@
+CREATE TABLE email_outbox
+(
+    id              uuid PRIMARY KEY,
+    kind            TEXT        NOT NULL,
+    to_address      TEXT        NOT NULL,
+    locale          TEXT        NOT NULL DEFAULT 'en',
+    action_url      TEXT        NOT NULL,
+    status          TEXT        NOT NULL DEFAULT 'Pending',
+    attempts        INTEGER     NOT NULL DEFAULT 0,
+    last_error      TEXT        NULL,
+    next_attempt_at timestamptz NOT NULL,
+    created_at      timestamptz NOT NULL,
+    sent_at         timestamptz NULL
+);
+
+CREATE INDEX email_outbox_due ON email_outbox (next_attempt_at) WHERE status = 'Pending';
+CREATE INDEX email_outbox_to_address ON email_outbox (to_address);
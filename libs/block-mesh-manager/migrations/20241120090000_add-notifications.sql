@@ -0,0 +1,38 @@
+ALTER TABLE email_outbox ADD COLUMN params JSONB NOT NULL DEFAULT '{}';
+
+CREATE TABLE notification_preferences
+(
+    user_id                  uuid PRIMARY KEY,
+    email_enabled            BOOLEAN     NOT NULL DEFAULT TRUE,
+    telegram_chat_id         BIGINT      NULL,
+    node_offline             BOOLEAN     NOT NULL DEFAULT TRUE,
+    new_referral             BOOLEAN     NOT NULL DEFAULT TRUE,
+    perk_earned              BOOLEAN     NOT NULL DEFAULT TRUE,
+    daily_summary            BOOLEAN     NOT NULL DEFAULT FALSE,
+    offline_minutes          INTEGER     NOT NULL DEFAULT 30,
+    locale                   TEXT        NOT NULL DEFAULT 'en',
+    unsubscribe_token        TEXT        NOT NULL UNIQUE,
+    telegram_link_token      TEXT        NULL UNIQUE,
+    telegram_link_expires_at timestamptz NULL,
+    created_at               timestamptz NOT NULL,
+    updated_at               timestamptz NOT NULL,
+    CONSTRAINT fk_user FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+);
+
+CREATE INDEX notification_preferences_telegram_chat_id ON notification_preferences (telegram_chat_id);
+
+-- one row per delivered event, the unique key keeps a state change from being announced twice
+CREATE TABLE notifications
+(
+    id         uuid PRIMARY KEY,
+    user_id    uuid        NOT NULL,
+    kind       TEXT        NOT NULL,
+    dedupe_key TEXT        NOT NULL,
+    params     JSONB       NOT NULL,
+    created_at timestamptz NOT NULL,
+    CONSTRAINT fk_user FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX notifications_user_id_kind_dedupe_key ON notifications (user_id, kind, dedupe_key);
+CREATE INDEX notifications_created_at ON notifications (created_at);
+CREATE INDEX aggregates_uptime_updated_at ON aggregates (updated_at) WHERE name = 'Uptime';
@@ -0,0 +1,13 @@
+-- links an account to a Telegram user of the privacy bot, the code is shown on the dashboard
+-- and consumed by the bot's /link command
+CREATE TABLE tg_bot_links
+(
+    user_id              uuid PRIMARY KEY,
+    tg_id                BIGINT      NULL UNIQUE,
+    link_code            TEXT        NULL UNIQUE,
+    link_code_expires_at timestamptz NULL,
+    linked_at            timestamptz NULL,
+    created_at           timestamptz NOT NULL,
+    updated_at           timestamptz NOT NULL,
+    CONSTRAINT fk_user FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+);
@@ -0,0 +1,3 @@
+-- failed second factor attempts per user, the session counter alone does not survive a new login
+ALTER TABLE user_totp ADD COLUMN failed_attempts INT NOT NULL DEFAULT 0;
+ALTER TABLE user_totp ADD COLUMN locked_until timestamptz NULL;
@@ -0,0 +1,8 @@
+-- notifications are opt-in, a row created before the user saved their choices enables nothing
+ALTER TABLE notification_preferences ALTER COLUMN node_offline SET DEFAULT FALSE;
+ALTER TABLE notification_preferences ALTER COLUMN new_referral SET DEFAULT FALSE;
+ALTER TABLE notification_preferences ALTER COLUMN perk_earned SET DEFAULT FALSE;
+
+UPDATE notification_preferences
+SET node_offline = FALSE, new_referral = FALSE, perk_earned = FALSE, daily_summary = FALSE
+WHERE updated_at = created_at;
@@ -0,0 +1,6 @@
+-- failed lookups are retried with a backoff and given up on after a few attempts
+ALTER TABLE ip_addresses
+    ADD COLUMN enrich_attempts  INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN enrich_failed_at timestamptz;
+
+CREATE INDEX ip_addresses_pending_enrichment ON ip_addresses (enrich_failed_at NULLS FIRST, created_at DESC) WHERE enriched = false;
@@ -0,0 +1,19 @@
+-- Scratch tables the sybil scoring job rebuilds inside its transaction on every run.
+-- Edges link an account to the first account (by id) it shares an IP or a device with,
+-- or to its inviter when both were seen on the same ASN.
+CREATE UNLOGGED TABLE sybil_cluster_edges
+(
+    user_id  uuid NOT NULL,
+    other_id uuid NOT NULL,
+    reason   TEXT NOT NULL
+);
+
+CREATE INDEX sybil_cluster_edges_user_id ON sybil_cluster_edges (user_id);
+CREATE INDEX sybil_cluster_edges_other_id ON sybil_cluster_edges (other_id);
+
+-- every account seen in the lookback window, cluster_id converges to the smallest id in its cluster
+CREATE UNLOGGED TABLE sybil_clusters
+(
+    user_id    uuid PRIMARY KEY,
+    cluster_id uuid NOT NULL
+);
@@ -0,0 +1,284 @@
+use crate::database::two_factor::delete_user_totp::delete_user_totp;
+use block_mesh_manager_database_domain::domain::api_token::ApiTokenStatus;
+use block_mesh_manager_database_domain::domain::nonce::Nonce;
+use chrono::Utc;
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+/// Reserved (RFC 2606) domain of the email left on deleted accounts.
+pub const DELETED_EMAIL_DOMAIN: &str = "deleted.blockmesh.invalid";
+
+/// Strips personal data while keeping the rows other users' referrals, points and tasks point
+/// at, and revokes every credential so nothing can log in as the account again.
+#[tracing::instrument(name = "anonymize_user", skip(transaction, unusable_password))]
+pub async fn anonymize_user(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+    unusable_password: &str,
+) -> anyhow::Result<()> {
+    let now = Utc::now();
+    // queued emails hold the address and a live link, drop them before the email is replaced
+    sqlx::query!(
+        r#"
+        DELETE FROM email_outbox
+        WHERE to_address IN (
+            SELECT email FROM users WHERE id = $1
+            UNION
+            SELECT new_email FROM email_change_requests WHERE user_id = $1
+        )
+        "#,
+        user_id
+    )
+    .execute(&mut **transaction)
+    .await?;
+    sqlx::query!(
+        r#"
+        UPDATE users
+        SET email = $2, password = $3, wallet_address = NULL, verified_email = FALSE, deleted_at = $4
+        WHERE id = $1
+        "#,
+        user_id,
+        format!("{}@{}", user_id, DELETED_EMAIL_DOMAIN),
+        unusable_password,
+        now
+    )
+    .execute(&mut **transaction)
+    .await?;
+    let users_ip = sqlx::query_scalar!(
+        "DELETE FROM users_ip WHERE user_id = $1 RETURNING id",
+        user_id
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+    sqlx::query!(
+        r#"
+        UPDATE uptime_reports
+        SET ip = NULL, latitude = NULL, longitude = NULL, country = NULL, city = NULL,
+            region = NULL, timezone = NULL, isp = NULL
+        WHERE user_id = $1
+        "#,
+        user_id
+    )
+    .execute(&mut **transaction)
+    .await?;
+    // tasks the account ran as a node, the response belongs to whoever submitted the task
+    sqlx::query!(
+        r#"
+        UPDATE tasks
+        SET ip = '', asn = '', colo = '', country = ''
+        WHERE assigned_user_id = $1
+        "#,
+        user_id
+    )
+    .execute(&mut **transaction)
+    .await?;
+    sqlx::query!(
+        "UPDATE tasks SET headers = NULL, body = NULL WHERE user_id = $1",
+        user_id
+    )
+    .execute(&mut **transaction)
+    .await?;
+    // the change history trigger keeps full row snapshots
+    let mut record_ids: Vec<Uuid> = users_ip;
+    record_ids.push(*user_id);
+    sqlx::query!(
+        "DELETE FROM archives WHERE record_id = ANY($1)",
+        &record_ids
+    )
+    .execute(&mut **transaction)
+    .await?;
+    sqlx::query!(
+        r#"
+        UPDATE api_tokens
+        SET status = $2, revoked_at = $3
+        WHERE user_id = $1 AND status = $4
+        "#,
+        user_id,
+        ApiTokenStatus::Inactive.to_string(),
+        now,
+        ApiTokenStatus::Active.to_string()
+    )
+    .execute(&mut **transaction)
+    .await?;
+    let session_ids: Vec<String> = sqlx::query_scalar!(
+        r#"
+        UPDATE user_sessions
+        SET revoked_at = $2
+        WHERE user_id = $1 AND revoked_at IS NULL
+        RETURNING session_id
+        "#,
+        user_id,
+        now
+    )
+    .fetch_all(&mut **transaction)
+    .await?
+    .into_iter()
+    .flatten()
+    .collect();
+    // the schema is created by the session store at startup, not by the migrations
+    sqlx::query("DELETE FROM tower_sessions.session WHERE id = ANY($1)")
+        .bind(session_ids)
+        .execute(&mut **transaction)
+        .await?;
+    delete_user_totp(transaction, user_id).await?;
+    // outstanding confirmation and reset links carry the nonce
+    sqlx::query!(
+        "UPDATE nonces SET nonce = $2 WHERE user_id = $1",
+        user_id,
+        Nonce::generate_nonce(16)
+    )
+    .execute(&mut **transaction)
+    .await?;
+    sqlx::query!(
+        "DELETE FROM email_change_requests WHERE user_id = $1",
+        user_id
+    )
+    .execute(&mut **transaction)
+    .await?;
+    sqlx::query!(
+        "DELETE FROM notification_preferences WHERE user_id = $1",
+        user_id
+    )
+    .execute(&mut **transaction)
+    .await?;
+    sqlx::query!("DELETE FROM notifications WHERE user_id = $1", user_id)
+        .execute(&mut **transaction)
+        .await?;
+    sqlx::query!("DELETE FROM tg_bot_links WHERE user_id = $1", user_id)
+        .execute(&mut **transaction)
+        .await?;
+    // wallet_link_events stay, they keep a wallet from earning the link bonus twice
+    sqlx::query!("DELETE FROM wallet_challenges WHERE user_id = $1", user_id)
+        .execute(&mut **transaction)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::test_support::insert_user;
+    use sqlx::PgPool;
+
+    async fn insert_task(pool: &PgPool, user_id: Uuid, node_id: Uuid) -> anyhow::Result<Uuid> {
+        let id = Uuid::new_v4();
+        sqlx::query(
+            r#"
+            INSERT INTO tasks (id, user_id, url, method, headers, body, assigned_user_id, status,
+                response_code, response_raw, created_at, ip, asn, colo, country)
+            VALUES ($1, $2, 'https://example.com', 'GET', '{"a": "b"}', '{"c": "d"}', $3, 'Completed',
+                200, 'response', now(), '1.2.3.4', '13335', 'TLV', 'IL')
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .bind(node_id)
+        .execute(pool)
+        .await?;
+        Ok(id)
+    }
+
+    #[sqlx::test]
+    async fn test_anonymize_user(pool: PgPool) -> anyhow::Result<()> {
+        // the schema is created by the session store at startup, not by the migrations
+        sqlx::query("CREATE SCHEMA IF NOT EXISTS tower_sessions")
+            .execute(&pool)
+            .await?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS tower_sessions.session (id TEXT PRIMARY KEY, data BYTEA NOT NULL, expiry_date timestamptz NOT NULL)",
+        )
+        .execute(&pool)
+        .await?;
+        let user_id = insert_user(&pool).await?;
+        let other_id = insert_user(&pool).await?;
+        sqlx::query(
+            "INSERT INTO email_outbox (id, kind, to_address, action_url, next_attempt_at, created_at) VALUES ($1, 'Confirm', $2, 'https://example.com', now(), now())",
+        )
+        .bind(Uuid::new_v4())
+        .bind(format!("{}@example.com", user_id))
+        .execute(&pool)
+        .await?;
+        sqlx::query(
+            "INSERT INTO api_tokens (id, user_id, token, status, created_at) VALUES ($1, $2, $3, $4, now())",
+        )
+        .bind(Uuid::new_v4())
+        .bind(user_id)
+        .bind(Uuid::new_v4())
+        .bind(ApiTokenStatus::Active.to_string())
+        .execute(&pool)
+        .await?;
+        sqlx::query(
+            "INSERT INTO user_sessions (id, user_id, session_id, created_at, last_seen_at) VALUES ($1, $2, 'session', now(), now())",
+        )
+        .bind(Uuid::new_v4())
+        .bind(user_id)
+        .execute(&pool)
+        .await?;
+        sqlx::query(
+            "INSERT INTO tower_sessions.session (id, data, expiry_date) VALUES ('session', '', now() + interval '1 day')",
+        )
+        .execute(&pool)
+        .await?;
+        let ran_by_user = insert_task(&pool, other_id, user_id).await?;
+        let submitted_by_user = insert_task(&pool, user_id, other_id).await?;
+
+        let mut transaction = pool.begin().await?;
+        anonymize_user(&mut transaction, &user_id, "unusable").await?;
+        transaction.commit().await?;
+
+        let (email, wallet_address, deleted): (String, Option<String>, bool) = sqlx::query_as(
+            "SELECT email, wallet_address, deleted_at IS NOT NULL FROM users WHERE id = $1",
+        )
+        .bind(user_id)
+        .fetch_one(&pool)
+        .await?;
+        assert_eq!(email, format!("{}@{}", user_id, DELETED_EMAIL_DOMAIN));
+        assert_eq!(wallet_address, None);
+        assert!(deleted);
+        let outbox: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM email_outbox")
+            .fetch_one(&pool)
+            .await?;
+        assert_eq!(outbox, 0);
+        let active_tokens: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM api_tokens WHERE user_id = $1 AND revoked_at IS NULL",
+        )
+        .bind(user_id)
+        .fetch_one(&pool)
+        .await?;
+        assert_eq!(active_tokens, 0);
+        let sessions: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM tower_sessions.session")
+            .fetch_one(&pool)
+            .await?;
+        assert_eq!(sessions, 0);
+
+        // the node's location goes, the submitter keeps the response
+        let (ip, asn, colo, country, response_raw): (
+            String,
+            String,
+            String,
+            String,
+            Option<String>,
+        ) = sqlx::query_as("SELECT ip, asn, colo, country, response_raw FROM tasks WHERE id = $1")
+            .bind(ran_by_user)
+            .fetch_one(&pool)
+            .await?;
+        assert_eq!(
+            (ip.as_str(), asn.as_str(), colo.as_str(), country.as_str()),
+            ("", "", "", "")
+        );
+        assert_eq!(response_raw.as_deref(), Some("response"));
+        let (headers, body, ip, response_raw): (
+            Option<serde_json::Value>,
+            Option<serde_json::Value>,
+            String,
+            Option<String>,
+        ) = sqlx::query_as("SELECT headers, body, ip, response_raw FROM tasks WHERE id = $1")
+            .bind(submitted_by_user)
+            .fetch_one(&pool)
+            .await?;
+        assert_eq!((headers, body), (None, None));
+        assert_eq!(ip, "1.2.3.4");
+        assert_eq!(response_raw.as_deref(), Some("response"));
+        Ok(())
+    }
+}
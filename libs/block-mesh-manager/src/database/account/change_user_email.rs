@@ -0,0 +1,19 @@
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+/// The new address was proven by the confirmation link, so it is verified right away.
+#[tracing::instrument(name = "change_user_email", skip(transaction))]
+pub async fn change_user_email(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+    email: &str,
+) -> anyhow::Result<()> {
+    sqlx::query!(
+        "UPDATE users SET email = $2, verified_email = TRUE WHERE id = $1",
+        user_id,
+        email
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
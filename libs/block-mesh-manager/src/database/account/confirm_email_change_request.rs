@@ -0,0 +1,26 @@
+use chrono::Utc;
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+/// Burns a pending, unexpired request and returns the user and the email to switch to.
+#[tracing::instrument(name = "confirm_email_change_request", skip_all)]
+pub async fn confirm_email_change_request(
+    transaction: &mut Transaction<'_, Postgres>,
+    token: &str,
+) -> anyhow::Result<Option<(Uuid, String)>> {
+    let now = Utc::now();
+    let request = sqlx::query!(
+        r#"
+        UPDATE email_change_requests
+        SET confirmed_at = $2
+        WHERE token = $1 AND confirmed_at IS NULL AND expires_at > $2
+        RETURNING user_id, new_email
+        "#,
+        token,
+        now
+    )
+    .fetch_optional(&mut **transaction)
+    .await?
+    .map(|row| (row.user_id, row.new_email));
+    Ok(request)
+}
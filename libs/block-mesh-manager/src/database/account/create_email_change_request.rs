@@ -0,0 +1,35 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+/// Replaces any pending request of the user, only the latest link works.
+#[tracing::instrument(name = "create_email_change_request", skip(transaction, token))]
+pub async fn create_email_change_request(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+    new_email: &str,
+    token: &str,
+    expires_at: DateTime<Utc>,
+) -> anyhow::Result<()> {
+    sqlx::query!(
+        "DELETE FROM email_change_requests WHERE user_id = $1 AND confirmed_at IS NULL",
+        user_id
+    )
+    .execute(&mut **transaction)
+    .await?;
+    sqlx::query!(
+        r#"
+        INSERT INTO email_change_requests (id, user_id, new_email, token, created_at, expires_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+        Uuid::new_v4(),
+        user_id,
+        new_email,
+        token,
+        Utc::now(),
+        expires_at
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,4 @@
+pub mod anonymize_user;
+pub mod change_user_email;
+pub mod confirm_email_change_request;
+pub mod create_email_change_request;
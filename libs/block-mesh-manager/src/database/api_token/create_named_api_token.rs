@@ -0,0 +1,35 @@
+use block_mesh_manager_database_domain::domain::api_token::{ApiTokenScope, ApiTokenStatus};
+use chrono::{DateTime, Utc};
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+/// Returns the id and the value of the new token.
+#[tracing::instrument(name = "create_named_api_token", skip(transaction))]
+pub async fn create_named_api_token(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+    name: &str,
+    scopes: &[ApiTokenScope],
+    expires_at: Option<DateTime<Utc>>,
+) -> anyhow::Result<(Uuid, Uuid)> {
+    let id = Uuid::new_v4();
+    let token = Uuid::new_v4();
+    let scopes: Vec<String> = scopes.iter().map(|scope| scope.to_string()).collect();
+    sqlx::query!(
+        r#"
+        INSERT INTO api_tokens (id, created_at, token, status, user_id, name, scopes, expires_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        "#,
+        id,
+        Utc::now(),
+        token,
+        ApiTokenStatus::Active.to_string(),
+        user_id,
+        name,
+        &scopes,
+        expires_at
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok((id, token))
+}
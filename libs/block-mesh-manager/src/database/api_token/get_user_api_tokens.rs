@@ -0,0 +1,35 @@
+use block_mesh_manager_database_domain::domain::api_token::{ApiToken, ApiTokenStatus};
+use secret::Secret;
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+#[tracing::instrument(name = "get_user_api_tokens", skip_all)]
+pub async fn get_user_api_tokens(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+) -> anyhow::Result<Vec<ApiToken>> {
+    Ok(sqlx::query_as!(
+        ApiToken,
+        r#"
+        SELECT
+            id,
+            created_at,
+            user_id,
+            token AS "token: Secret<Uuid>",
+            status AS "status: ApiTokenStatus",
+            name,
+            scopes,
+            last_used_at,
+            last_used_ip,
+            expires_at,
+            revoked_at
+        FROM api_tokens
+        WHERE user_id = $1 AND status = $2
+        ORDER BY created_at
+        "#,
+        user_id,
+        ApiTokenStatus::Active.to_string()
+    )
+    .fetch_all(&mut **transaction)
+    .await?)
+}
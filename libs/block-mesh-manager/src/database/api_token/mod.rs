@@ -1,4 +1,7 @@
 pub mod create_api_token;
-pub mod get_api_token_by_user_id_and_status;
+pub mod create_named_api_token;
+pub mod get_user_api_tokens;
+pub mod revoke_api_token;
+pub mod rotate_api_token;
 pub mod update_api_token;
 pub mod update_api_token_status;
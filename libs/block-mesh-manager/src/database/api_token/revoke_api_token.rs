@@ -0,0 +1,28 @@
+use block_mesh_manager_database_domain::domain::api_token::ApiTokenStatus;
+use chrono::Utc;
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+/// Returns false when the user has no active token with this id.
+#[tracing::instrument(name = "revoke_api_token", skip(transaction))]
+pub async fn revoke_api_token(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+    id: &Uuid,
+) -> anyhow::Result<bool> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE api_tokens
+        SET status = $3, revoked_at = $4
+        WHERE id = $1 AND user_id = $2 AND status = $5
+        "#,
+        id,
+        user_id,
+        ApiTokenStatus::Inactive.to_string(),
+        Utc::now(),
+        ApiTokenStatus::Active.to_string()
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,28 @@
+use block_mesh_manager_database_domain::domain::api_token::ApiTokenStatus;
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+/// Replaces the token value, keeping its name, scopes and expiry.
+/// Returns `None` when the user has no active token with this id.
+#[tracing::instrument(name = "rotate_api_token", skip(transaction))]
+pub async fn rotate_api_token(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+    id: &Uuid,
+) -> anyhow::Result<Option<Uuid>> {
+    let token = Uuid::new_v4();
+    let result = sqlx::query!(
+        r#"
+        UPDATE api_tokens
+        SET token = $3
+        WHERE id = $1 AND user_id = $2 AND status = $4
+        "#,
+        id,
+        user_id,
+        token,
+        ApiTokenStatus::Active.to_string()
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok((result.rows_affected() > 0).then_some(token))
+}
@@ -1,14 +1,13 @@
 use sqlx::{Postgres, Transaction};
 use uuid::Uuid;
 
+/// Gives every token of the user a fresh value, each token keeps its name and scopes.
 pub async fn update_api_token(
     transaction: &mut Transaction<'_, Postgres>,
     user_id: Uuid,
 ) -> Result<(), sqlx::Error> {
-    let token = Uuid::new_v4();
     sqlx::query!(
-        r#"UPDATE api_tokens SET token = $1 WHERE user_id = $2"#,
-        token,
+        r#"UPDATE api_tokens SET token = gen_random_uuid() WHERE user_id = $1"#,
         user_id
     )
     .execute(&mut **transaction)
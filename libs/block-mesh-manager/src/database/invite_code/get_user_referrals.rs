@@ -1,22 +1,38 @@
 use block_mesh_common::interfaces::server_api::Referral;
+use block_mesh_manager_database_domain::domain::points_ledger::PointsSource;
 use sqlx::{Postgres, Transaction};
 use uuid::Uuid;
 
+/// Direct referrals with the commission `user_id` earned from each of them, net of reversals.
 pub async fn get_user_referrals(
     transaction: &mut Transaction<'_, Postgres>,
     user_id: Uuid,
 ) -> anyhow::Result<Vec<Referral>> {
-    let users = sqlx::query_as!(
+    let referrals = sqlx::query_as!(
         Referral,
-        r#"SELECT
-        created_at, email, verified_email
-        FROM USERS
+        r#"
+        WITH earned AS (
+            SELECT daily_stats.user_id, SUM(entry.points) AS points
+            FROM points_ledger entry
+            JOIN points_ledger original ON original.id = COALESCE(entry.reverses_id, entry.id)
+            JOIN daily_stats ON daily_stats.id = original.source_id
+            WHERE entry.user_id = $1 AND original.source = $2
+            GROUP BY daily_stats.user_id
+        )
+        SELECT
+        users.created_at,
+        users.email,
+        users.verified_email,
+        COALESCE(earned.points, 0.0) AS "points_earned!"
+        FROM users
+        LEFT JOIN earned ON earned.user_id = users.id
         WHERE
-        invited_by = $1
+        users.invited_by = $1
         "#,
-        user_id
+        user_id,
+        PointsSource::Referral.to_string()
     )
     .fetch_all(&mut **transaction)
     .await?;
-    Ok(users)
+    Ok(referrals)
 }
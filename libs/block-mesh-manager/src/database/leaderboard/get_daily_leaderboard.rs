@@ -1,54 +1,234 @@
 use block_mesh_common::interfaces::server_api::LeaderBoardUser;
-use chrono::{Duration, Utc};
+use block_mesh_manager_database_domain::domain::daily_stat::DailyStatStatus;
+use block_mesh_manager_database_domain::domain::sybil_flag::SybilFlagStatus;
+use chrono::NaiveDate;
 use sqlx::{Postgres, Transaction};
 
+/// Finalized days come from the points ledger. A day that is still OnGoing is evaluated with the
+/// reward rules active on it, the same way `calc_live_points` does for the dashboard: rule set
+/// perk multipliers over stored ones, active campaigns, the daily and IP caps and the sybil weight.
+/// Confirmed sybil and deleted accounts are left out.
 pub async fn get_daily_leaderboard(
     transaction: &mut Transaction<'_, Postgres>,
-    uptime_factor: f64,
-    tasks_factor: f64,
+    day: NaiveDate,
     limit: i64,
 ) -> anyhow::Result<Vec<LeaderBoardUser>> {
-    let day = Utc::now().date_naive() - Duration::days(1);
-    let daily_stats = sqlx::query_as!(
+    let leaderboard = sqlx::query_as!(
         LeaderBoardUser,
         r#"
+        WITH
+            rule_set AS (
+                SELECT uptime_factor, tasks_factor, perks, daily_cap, ip_daily_cap
+                FROM reward_rules
+                WHERE effective_from <= $1
+                ORDER BY effective_from DESC, version DESC
+                LIMIT 1
+            ),
+            campaigns AS (
+                SELECT COALESCE(EXP(SUM(LN(multiplier))), 1.0) AS multiplier
+                FROM reward_campaigns
+                WHERE (starts_at AT TIME ZONE 'UTC')::date <= $1
+                    AND (ends_at AT TIME ZONE 'UTC')::date >= $1
+                    AND multiplier > 0
+            ),
+            ongoing AS (
+                SELECT user_id, uptime, tasks_count
+                FROM daily_stats
+                WHERE day = $1 AND status = $2
+            ),
+            multipliers AS (
+                SELECT user_id, EXP(SUM(LN(multiplier))) AS multiplier
+                FROM (
+                    SELECT
+                        perks.user_id,
+                        COALESCE((rule_set.perks -> perks.name ->> 'multiplier')::DOUBLE PRECISION, perks.multiplier) AS multiplier
+                    FROM
+                        perks
+                        JOIN ongoing ON ongoing.user_id = perks.user_id
+                        CROSS JOIN rule_set
+                ) user_perks
+                WHERE multiplier > 0
+                GROUP BY user_id
+            ),
+            live AS (
+                SELECT
+                    ongoing.user_id,
+                    LEAST(
+                        (ongoing.uptime * rule_set.uptime_factor + CAST(ongoing.tasks_count AS DOUBLE PRECISION) * rule_set.tasks_factor)
+                            * COALESCE(multipliers.multiplier, 1.0)
+                            * campaigns.multiplier,
+                        rule_set.daily_cap,
+                        rule_set.ip_daily_cap
+                    ) AS points
+                FROM
+                    ongoing
+                    CROSS JOIN rule_set
+                    CROSS JOIN campaigns
+                    LEFT JOIN multipliers ON multipliers.user_id = ongoing.user_id
+            ),
+            ledger AS (
+                SELECT user_id, SUM(points) AS points
+                FROM points_ledger
+                WHERE day = $1
+                GROUP BY user_id
+            )
         SELECT
             users.email AS email,
-            (uptime * $1 + CAST(tasks_count as DOUBLE PRECISION) * $2) AS points
+            COALESCE(ledger.points, 0.0) + COALESCE(live.points, 0.0) * (
+                CASE
+                    WHEN sybil_flags.status = $3 THEN LEAST(GREATEST(sybil_flags.weight, 0.0), 1.0)
+                    ELSE 1.0
+                END
+            ) AS points
         FROM
-	        daily_stats
-	        JOIN users ON users.id = daily_stats.user_id
-        WHERE day = $3
+            (SELECT user_id FROM ledger UNION SELECT user_id FROM live) day_users
+            JOIN users ON users.id = day_users.user_id
+            LEFT JOIN ledger ON ledger.user_id = day_users.user_id
+            LEFT JOIN live ON live.user_id = day_users.user_id
+            LEFT JOIN sybil_flags ON sybil_flags.user_id = day_users.user_id
+        WHERE sybil_flags.status IS DISTINCT FROM $4
+            AND users.deleted_at IS NULL
         ORDER BY points DESC
-        LIMIT $4
+        LIMIT $5
         "#,
-        // r#"
-        // SELECT
-        // 	users.email AS email,
-        // 	(uptime * $1 + CAST(tasks_count as DOUBLE PRECISION) * $2) AS points,
-        // 	COUNT(users_ip.id) AS ips
-        // FROM
-        // 	daily_stats
-        // 	JOIN users ON users.id = daily_stats.user_id
-        // 	JOIN users_ip ON users.id = users_ip.user_id
-        // WHERE
-        //     day = $3
-        //     AND users_ip.updated_at >= NOW() - INTERVAL '24 hours'
-        // GROUP BY
-        //     users.email,
-        //     points
-        // HAVING
-        //     COUNT(users_ip.id) < 10
-        // ORDER BY
-        // 	points DESC
-        // 	LIMIT $4
-        // "#,
-        uptime_factor,
-        tasks_factor,
         day,
+        DailyStatStatus::OnGoing.to_string(),
+        SybilFlagStatus::Pending.to_string(),
+        SybilFlagStatus::Confirmed.to_string(),
         limit
     )
     .fetch_all(&mut **transaction)
     .await?;
-    Ok(daily_stats)
+    Ok(leaderboard)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::perks::get_users_perks::get_users_perks;
+    use crate::database::test_support::insert_user;
+    use crate::utils::points::calc_live_points;
+    use block_mesh_manager_database_domain::domain::get_reward_rules::get_reward_rules;
+    use chrono::Utc;
+    use sqlx::PgPool;
+    use uuid::Uuid;
+
+    async fn insert_ongoing(pool: &PgPool, day: NaiveDate, uptime: f64) -> anyhow::Result<Uuid> {
+        let user_id = insert_user(pool).await?;
+        sqlx::query(
+            "INSERT INTO daily_stats (id, user_id, tasks_count, status, day, created_at, uptime) VALUES ($1, $2, 3, $3, $4, now(), $5)",
+        )
+        .bind(Uuid::new_v4())
+        .bind(user_id)
+        .bind(DailyStatStatus::OnGoing.to_string())
+        .bind(day)
+        .bind(uptime)
+        .execute(pool)
+        .await?;
+        Ok(user_id)
+    }
+
+    async fn insert_perk(
+        pool: &PgPool,
+        user_id: Uuid,
+        name: &str,
+        multiplier: f64,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            "INSERT INTO perks (id, user_id, created_at, name, multiplier) VALUES ($1, $2, now(), $3, $4)",
+        )
+        .bind(Uuid::new_v4())
+        .bind(user_id)
+        .bind(name)
+        .bind(multiplier)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn insert_sybil_flag(
+        pool: &PgPool,
+        user_id: Uuid,
+        status: SybilFlagStatus,
+        weight: f64,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            "INSERT INTO sybil_flags (id, user_id, score, weight, status, created_at, updated_at) VALUES ($1, $2, 1.0, $3, $4, now(), now())",
+        )
+        .bind(Uuid::new_v4())
+        .bind(user_id)
+        .bind(weight)
+        .bind(status.to_string())
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_live_points_match_the_dashboard(pool: PgPool) -> anyhow::Result<()> {
+        let day = Utc::now().date_naive();
+        sqlx::query(
+            r#"UPDATE reward_rules SET perks = '{"wallet": {"multiplier": 1.5, "one_time_bonus": 0.0}}', daily_cap = 200.0"#,
+        )
+        .execute(&pool)
+        .await?;
+        sqlx::query(
+            "INSERT INTO reward_campaigns (id, name, multiplier, starts_at, ends_at, created_at) VALUES ($1, 'launch', 2.0, now() - interval '1 day', now() + interval '1 day', now())",
+        )
+        .bind(Uuid::new_v4())
+        .execute(&pool)
+        .await?;
+        let with_perks = insert_ongoing(&pool, day, 3600.0).await?;
+        insert_perk(&pool, with_perks, "wallet", 1.1).await?;
+        insert_perk(&pool, with_perks, "twitter", 1.2).await?;
+        let pending = insert_ongoing(&pool, day, 7200.0).await?;
+        insert_sybil_flag(&pool, pending, SybilFlagStatus::Pending, 0.5).await?;
+        let capped = insert_ongoing(&pool, day, 86400.0).await?;
+        let confirmed = insert_ongoing(&pool, day, 86400.0).await?;
+        insert_sybil_flag(&pool, confirmed, SybilFlagStatus::Confirmed, 0.0).await?;
+        let finalized = insert_user(&pool).await?;
+        sqlx::query(
+            "INSERT INTO points_ledger (id, user_id, day, source, base_points, points, created_at) VALUES ($1, $2, $3, 'Uptime', 150.0, 150.0, now())",
+        )
+        .bind(Uuid::new_v4())
+        .bind(finalized)
+        .bind(day)
+        .execute(&pool)
+        .await?;
+
+        let mut transaction = pool.begin().await?;
+        let rules = get_reward_rules(&mut transaction).await?;
+        let perks = get_users_perks(&mut transaction, &[with_perks]).await?;
+        let expected = [
+            (finalized, 150.0),
+            (capped, calc_live_points(&rules, day, 86400.0, 3, &[], 1.0)),
+            (
+                pending,
+                calc_live_points(
+                    &rules,
+                    day,
+                    7200.0,
+                    3,
+                    &[],
+                    SybilFlagStatus::Pending.effective_weight(0.5),
+                ),
+            ),
+            (
+                with_perks,
+                calc_live_points(&rules, day, 3600.0, 3, &perks, 1.0),
+            ),
+        ];
+        assert_eq!(expected[1].1, 200.0);
+        let leaderboard = get_daily_leaderboard(&mut transaction, day, 10).await?;
+        transaction.commit().await?;
+
+        assert_eq!(leaderboard.len(), expected.len());
+        let mut expected = expected.to_vec();
+        expected.sort_by(|a, b| b.1.total_cmp(&a.1));
+        for (row, (user_id, points)) in leaderboard.iter().zip(expected) {
+            assert_eq!(row.email, format!("{}@example.com", user_id));
+            assert!((row.points.unwrap() - points).abs() < 1e-9);
+        }
+        Ok(())
+    }
 }
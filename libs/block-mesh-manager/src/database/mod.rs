@@ -1,3 +1,4 @@
+pub mod account;
 pub mod aggregate;
 pub mod analytics;
 pub mod api_token;
@@ -8,9 +9,18 @@ pub mod invite_code;
 pub mod ip_address;
 pub mod leaderboard;
 pub mod nonce;
+pub mod notifications;
 pub mod perks;
+pub mod points_ledger;
 pub mod proxy_master;
+pub mod sybil_flags;
 pub mod task;
+#[cfg(test)]
+pub mod test_support;
+pub mod tg_bot;
+pub mod two_factor;
 pub mod uptime_report;
 pub mod user;
+pub mod user_session;
 pub mod users_ip;
+pub mod wallet;
@@ -0,0 +1,23 @@
+use sqlx::PgExecutor;
+
+/// Consumes the deep link token, returns false when it is unknown or expired.
+#[tracing::instrument(name = "link_telegram_chat", skip(executor, token))]
+pub async fn link_telegram_chat(
+    executor: impl PgExecutor<'_>,
+    token: &str,
+    chat_id: i64,
+) -> anyhow::Result<bool> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE notification_preferences
+        SET telegram_chat_id = $2, telegram_link_token = NULL, telegram_link_expires_at = NULL,
+            updated_at = now()
+        WHERE telegram_link_token = $1 AND telegram_link_expires_at > now()
+        "#,
+        token,
+        chat_id
+    )
+    .execute(executor)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
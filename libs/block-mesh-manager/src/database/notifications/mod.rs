@@ -0,0 +1,5 @@
+pub mod link_telegram_chat;
+pub mod set_telegram_link_token;
+pub mod unlink_telegram_chat;
+pub mod unsubscribe_notifications;
+pub mod update_notification_preferences;
@@ -0,0 +1,26 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+/// Replaces any previous token, only the latest deep link works.
+#[tracing::instrument(name = "set_telegram_link_token", skip(transaction, token))]
+pub async fn set_telegram_link_token(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+    token: &str,
+    expires_at: DateTime<Utc>,
+) -> anyhow::Result<()> {
+    sqlx::query!(
+        r#"
+        UPDATE notification_preferences
+        SET telegram_link_token = $2, telegram_link_expires_at = $3, updated_at = now()
+        WHERE user_id = $1
+        "#,
+        user_id,
+        token,
+        expires_at
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
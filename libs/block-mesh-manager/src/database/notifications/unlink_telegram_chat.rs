@@ -0,0 +1,31 @@
+use sqlx::PgExecutor;
+use uuid::Uuid;
+
+#[tracing::instrument(name = "unlink_telegram_chat", skip(executor))]
+pub async fn unlink_telegram_chat(
+    executor: impl PgExecutor<'_>,
+    user_id: &Uuid,
+) -> anyhow::Result<()> {
+    sqlx::query!(
+        "UPDATE notification_preferences SET telegram_chat_id = NULL, updated_at = now() WHERE user_id = $1",
+        user_id
+    )
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// Used when the chat itself asks to stop, returns false when no account was linked.
+#[tracing::instrument(name = "unlink_telegram_chat_by_chat_id", skip(executor))]
+pub async fn unlink_telegram_chat_by_chat_id(
+    executor: impl PgExecutor<'_>,
+    chat_id: i64,
+) -> anyhow::Result<bool> {
+    let result = sqlx::query!(
+        "UPDATE notification_preferences SET telegram_chat_id = NULL, updated_at = now() WHERE telegram_chat_id = $1",
+        chat_id
+    )
+    .execute(executor)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,22 @@
+use block_mesh_manager_database_domain::domain::notification_preferences::NotificationKind;
+use sqlx::PgExecutor;
+
+/// Turns off one kind, or every email when `kind` is `None`.
+/// Returns false when the token doesn't match any user.
+#[tracing::instrument(name = "unsubscribe_notifications", skip(executor, token))]
+pub async fn unsubscribe_notifications(
+    executor: impl PgExecutor<'_>,
+    token: &str,
+    kind: Option<NotificationKind>,
+) -> anyhow::Result<bool> {
+    let column = kind.map(|k| k.column()).unwrap_or("email_enabled");
+    // the column name comes from a fixed list, never from the request
+    let result = sqlx::query(&format!(
+        "UPDATE notification_preferences SET {} = FALSE, updated_at = now() WHERE unsubscribe_token = $1",
+        column
+    ))
+    .bind(token)
+    .execute(executor)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
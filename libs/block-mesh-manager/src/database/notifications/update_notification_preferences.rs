@@ -0,0 +1,31 @@
+use block_mesh_common::interfaces::server_api::UpdateNotificationPreferencesRequest;
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+#[tracing::instrument(name = "update_notification_preferences", skip(transaction))]
+pub async fn update_notification_preferences(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+    preferences: &UpdateNotificationPreferencesRequest,
+    locale: &str,
+) -> anyhow::Result<()> {
+    sqlx::query!(
+        r#"
+        UPDATE notification_preferences
+        SET email_enabled = $2, node_offline = $3, new_referral = $4, perk_earned = $5,
+            daily_summary = $6, offline_minutes = $7, locale = $8, updated_at = now()
+        WHERE user_id = $1
+        "#,
+        user_id,
+        preferences.email_enabled,
+        preferences.node_offline,
+        preferences.new_referral,
+        preferences.perk_earned,
+        preferences.daily_summary,
+        preferences.offline_minutes,
+        locale
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
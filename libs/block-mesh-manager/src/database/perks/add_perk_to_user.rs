@@ -1,4 +1,6 @@
+use crate::database::points_ledger::insert_points_ledger_entry::insert_points_ledger_entry;
 use crate::domain::perk::PerkName;
+use block_mesh_manager_database_domain::domain::points_ledger::PointsSource;
 use chrono::Utc;
 use serde_json::Value;
 use sqlx::{Postgres, Transaction};
@@ -14,7 +16,7 @@ pub(crate) async fn add_perk_to_user(
 ) -> anyhow::Result<()> {
     let now = Utc::now();
     let id = Uuid::new_v4();
-    let _ = sqlx::query!(
+    let perk = sqlx::query!(
         r#"
         INSERT INTO perks
         (id, user_id, created_at, name, multiplier, one_time_bonus, data, updated_at)
@@ -34,5 +36,19 @@ pub(crate) async fn add_perk_to_user(
     )
     .fetch_one(&mut **transaction)
     .await?;
+    if one_time_bonus != 0.0 {
+        insert_points_ledger_entry(
+            transaction,
+            &user_id,
+            now.date_naive(),
+            PointsSource::PerkOneTimeBonus,
+            Some(perk.id),
+            one_time_bonus,
+            1.0,
+            Some(name.to_string()),
+            None,
+        )
+        .await?;
+    }
     Ok(())
 }
@@ -0,0 +1,22 @@
+use crate::domain::perk::Perk;
+use sqlx::{query_as, Postgres, Transaction};
+use uuid::Uuid;
+
+pub async fn get_users_perks(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_ids: &[Uuid],
+) -> anyhow::Result<Vec<Perk>> {
+    let perks = query_as!(
+        Perk,
+        r#"
+        SELECT
+        id, user_id, name, created_at, multiplier, one_time_bonus, data
+        FROM perks
+        WHERE user_id = ANY($1)
+        "#,
+        user_ids
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+    Ok(perks)
+}
@@ -1,2 +1,3 @@
 pub mod add_perk_to_user;
 pub mod get_user_perks;
+pub mod get_users_perks;
@@ -0,0 +1,22 @@
+use block_mesh_manager_database_domain::domain::points_ledger::PointsLedgerEntry;
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+pub async fn get_points_ledger_entry(
+    transaction: &mut Transaction<'_, Postgres>,
+    id: &Uuid,
+) -> anyhow::Result<Option<PointsLedgerEntry>> {
+    let entry = sqlx::query_as!(
+        PointsLedgerEntry,
+        r#"
+        SELECT
+        id, user_id, day, source, source_id, base_points, multiplier, points, reverses_id, note, created_by, created_at
+        FROM points_ledger
+        WHERE id = $1
+        "#,
+        id
+    )
+    .fetch_optional(&mut **transaction)
+    .await?;
+    Ok(entry)
+}
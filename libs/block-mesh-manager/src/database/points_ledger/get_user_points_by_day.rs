@@ -0,0 +1,22 @@
+use chrono::NaiveDate;
+use sqlx::{Postgres, Transaction};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+pub async fn get_user_points_by_day(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+) -> anyhow::Result<HashMap<NaiveDate, f64>> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT day, COALESCE(SUM(points), 0.0) AS "points!"
+        FROM points_ledger
+        WHERE user_id = $1
+        GROUP BY day
+        "#,
+        user_id
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+    Ok(rows.into_iter().map(|row| (row.day, row.points)).collect())
+}
@@ -0,0 +1,26 @@
+use block_mesh_manager_database_domain::domain::points_ledger::PointsLedgerEntry;
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+pub async fn get_user_points_ledger(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+    limit: i64,
+) -> anyhow::Result<Vec<PointsLedgerEntry>> {
+    let entries = sqlx::query_as!(
+        PointsLedgerEntry,
+        r#"
+        SELECT
+        id, user_id, day, source, source_id, base_points, multiplier, points, reverses_id, note, created_by, created_at
+        FROM points_ledger
+        WHERE user_id = $1
+        ORDER BY created_at DESC
+        LIMIT $2
+        "#,
+        user_id,
+        limit
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+    Ok(entries)
+}
@@ -0,0 +1,149 @@
+use block_mesh_manager_database_domain::domain::points_ledger::PointsSource;
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+pub async fn get_user_points_total(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+) -> anyhow::Result<f64> {
+    let points = sqlx::query_scalar!(
+        r#"
+        SELECT COALESCE(SUM(points), 0.0) AS "points!"
+        FROM points_ledger
+        WHERE user_id = $1
+        "#,
+        user_id
+    )
+    .fetch_one(&mut **transaction)
+    .await?;
+    Ok(points)
+}
+
+/// Points from a single `source`, net of any reversals of those entries.
+pub async fn get_user_points_total_by_source(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+    source: PointsSource,
+) -> anyhow::Result<f64> {
+    let points = sqlx::query_scalar!(
+        r#"
+        SELECT COALESCE(SUM(entry.points), 0.0) AS "points!"
+        FROM points_ledger entry
+        JOIN points_ledger original ON original.id = COALESCE(entry.reverses_id, entry.id)
+        WHERE entry.user_id = $1 AND original.source = $2
+        "#,
+        user_id,
+        source.to_string()
+    )
+    .fetch_one(&mut **transaction)
+    .await?;
+    Ok(points)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::perks::get_users_perks::get_users_perks;
+    use crate::database::test_support::insert_user;
+    use crate::utils::points::calc_live_points;
+    use block_mesh_manager_database_domain::domain::daily_stat::DailyStatStatus;
+    use block_mesh_manager_database_domain::domain::get_reward_rules::get_reward_rules;
+    use chrono::{Duration, Utc};
+    use sqlx::{Executor, PgPool};
+
+    const UPTIME_FACTOR: f64 = 100.0 / (24.0 * 60.0 * 60.0);
+    const TASKS_FACTOR: f64 = 10.0;
+
+    /// The dashboard total before the ledger, `perks` are `(multiplier, one_time_bonus)`.
+    fn old_dashboard_total(lifetime: (f64, i64), days: &[(f64, i64)], perks: &[(f64, f64)]) -> f64 {
+        let raw = |uptime: f64, tasks: i64| uptime * UPTIME_FACTOR + tasks as f64 * TASKS_FACTOR;
+        let multiplier: f64 = perks.iter().map(|(multiplier, _)| multiplier).product();
+        let bonus: f64 = perks.iter().map(|(_, bonus)| bonus).sum();
+        let uptime = (lifetime.0 as u64).max(days.iter().map(|d| d.0).sum::<f64>() as u64) as f64;
+        let tasks = lifetime.1.max(days.iter().map(|d| d.1).sum());
+        let daily: f64 = days.iter().map(|d| raw(d.0, d.1) * multiplier).sum();
+        ((raw(uptime, tasks) * multiplier + bonus) as u64)
+            .max((raw(uptime, tasks) + bonus) as u64 + daily as u64) as f64
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_total_unchanged_by_ledger_migration(pool: PgPool) -> anyhow::Result<()> {
+        const VERSION: i64 = 20241110090000;
+        let migrator = sqlx::migrate!();
+        for migration in migrator.iter().filter(|m| m.version < VERSION) {
+            pool.execute(&*migration.sql).await?;
+        }
+        let user_id = insert_user(&pool).await?;
+        let today = Utc::now().date_naive();
+        // uptime and tasks from before daily stats existed only live in the aggregates
+        let lifetime = (500_000.0, 90);
+        let days = [(80_000.0, 4, 2), (60_000.0, 2, 1), (30_000.0, 1, 0)];
+        for (uptime, tasks_count, days_ago) in days {
+            let status = if days_ago == 0 {
+                DailyStatStatus::OnGoing
+            } else {
+                DailyStatStatus::Finalized
+            };
+            sqlx::query(
+                "INSERT INTO daily_stats (id, user_id, tasks_count, status, day, created_at, uptime) VALUES ($1, $2, $3, $4, $5, now(), $6)",
+            )
+            .bind(Uuid::new_v4())
+            .bind(user_id)
+            .bind(tasks_count)
+            .bind(status.to_string())
+            .bind(today - Duration::days(days_ago))
+            .bind(uptime)
+            .execute(&pool)
+            .await?;
+        }
+        let perks = [("wallet", 1.1, 0.0), ("twitter", 1.0, 500.0)];
+        for (name, multiplier, one_time_bonus) in perks {
+            sqlx::query(
+                "INSERT INTO perks (id, user_id, created_at, name, multiplier, one_time_bonus) VALUES ($1, $2, now(), $3, $4, $5)",
+            )
+            .bind(Uuid::new_v4())
+            .bind(user_id)
+            .bind(name)
+            .bind(multiplier)
+            .bind(one_time_bonus)
+            .execute(&pool)
+            .await?;
+        }
+        for (name, value) in [
+            ("Uptime", serde_json::json!(lifetime.0)),
+            ("Tasks", serde_json::json!(lifetime.1)),
+        ] {
+            sqlx::query(
+                "INSERT INTO aggregates (id, user_id, name, value, created_at) VALUES ($1, $2, $3, $4, now())",
+            )
+            .bind(Uuid::new_v4())
+            .bind(user_id)
+            .bind(name)
+            .bind(value)
+            .execute(&pool)
+            .await?;
+        }
+        let old_total = old_dashboard_total(
+            lifetime,
+            &days.map(|(uptime, tasks_count, _)| (uptime, tasks_count as i64)),
+            &perks.map(|(_, multiplier, bonus)| (multiplier, bonus)),
+        );
+
+        for migration in migrator.iter().filter(|m| m.version >= VERSION) {
+            pool.execute(&*migration.sql).await?;
+        }
+        let mut transaction = pool.begin().await?;
+        let rules = get_reward_rules(&mut transaction).await?;
+        let perks = get_users_perks(&mut transaction, &[user_id]).await?;
+        let ledger = get_user_points_total(&mut transaction, &user_id).await?;
+        let legacy =
+            get_user_points_total_by_source(&mut transaction, &user_id, PointsSource::Legacy)
+                .await?;
+        transaction.commit().await?;
+        let ongoing = calc_live_points(&rules, today, 30_000.0, 1, &perks, 1.0);
+
+        assert!(legacy > 0.0);
+        assert!((ledger + ongoing - old_total).abs() < 1e-6);
+        Ok(())
+    }
+}
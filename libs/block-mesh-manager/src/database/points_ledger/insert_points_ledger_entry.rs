@@ -0,0 +1,45 @@
+use block_mesh_manager_database_domain::domain::points_ledger::PointsSource;
+use chrono::{NaiveDate, Utc};
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+/// Appends a credit to the ledger.
+/// Returns `None` when the same `source_id` was already credited for this source.
+#[allow(clippy::too_many_arguments)]
+#[tracing::instrument(name = "insert_points_ledger_entry", skip(transaction), err)]
+pub async fn insert_points_ledger_entry(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+    day: NaiveDate,
+    source: PointsSource,
+    source_id: Option<Uuid>,
+    base_points: f64,
+    multiplier: f64,
+    note: Option<String>,
+    created_by: Option<Uuid>,
+) -> anyhow::Result<Option<Uuid>> {
+    let id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO points_ledger
+        (id, user_id, day, source, source_id, base_points, multiplier, points, note, created_by, created_at)
+        VALUES
+        ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+        ON CONFLICT DO NOTHING
+        RETURNING id
+        "#,
+        Uuid::new_v4(),
+        user_id,
+        day,
+        source.to_string(),
+        source_id,
+        base_points,
+        multiplier,
+        base_points * multiplier,
+        note,
+        created_by,
+        Utc::now()
+    )
+    .fetch_optional(&mut **transaction)
+    .await?;
+    Ok(id)
+}
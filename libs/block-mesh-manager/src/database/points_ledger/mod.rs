@@ -0,0 +1,6 @@
+pub mod get_points_ledger_entry;
+pub mod get_user_points_by_day;
+pub mod get_user_points_ledger;
+pub mod get_user_points_total;
+pub mod insert_points_ledger_entry;
+pub mod reverse_points_ledger_entry;
@@ -0,0 +1,40 @@
+use block_mesh_manager_database_domain::domain::points_ledger::{PointsLedgerEntry, PointsSource};
+use chrono::Utc;
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+/// Cancels `entry` by appending an opposite entry on the same day, credited as
+/// `-points` at a multiplier of 1 so the reversal stands on its own.
+/// Returns `None` if the entry was already reversed.
+#[tracing::instrument(name = "reverse_points_ledger_entry", skip(transaction), err)]
+pub async fn reverse_points_ledger_entry(
+    transaction: &mut Transaction<'_, Postgres>,
+    entry: &PointsLedgerEntry,
+    note: String,
+    created_by: &Uuid,
+) -> anyhow::Result<Option<Uuid>> {
+    let id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO points_ledger
+        (id, user_id, day, source, source_id, base_points, multiplier, points, reverses_id, note, created_by, created_at)
+        VALUES
+        ($1, $2, $3, $4, NULL, $5, $6, $7, $8, $9, $10, $11)
+        ON CONFLICT DO NOTHING
+        RETURNING id
+        "#,
+        Uuid::new_v4(),
+        entry.user_id,
+        entry.day,
+        PointsSource::Reversal.to_string(),
+        -entry.points,
+        1.0,
+        -entry.points,
+        entry.id,
+        note,
+        created_by,
+        Utc::now()
+    )
+    .fetch_optional(&mut **transaction)
+    .await?;
+    Ok(id)
+}
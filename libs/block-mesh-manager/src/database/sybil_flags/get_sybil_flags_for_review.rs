@@ -0,0 +1,66 @@
+use block_mesh_manager_database_domain::domain::sybil_flag::{
+    SybilFlag, SybilFlagStatus, SybilReason,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::{Postgres, Transaction};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SybilFlagForReview {
+    pub email: String,
+    #[serde(flatten)]
+    pub flag: SybilFlag,
+}
+
+/// Highest scoring flags first, so the review queue starts with the most suspicious accounts.
+pub async fn get_sybil_flags_for_review(
+    transaction: &mut Transaction<'_, Postgres>,
+    status: SybilFlagStatus,
+    limit: i64,
+) -> anyhow::Result<Vec<SybilFlagForReview>> {
+    let flags = sqlx::query!(
+        r#"
+        SELECT
+        users.email,
+        sybil_flags.id,
+        sybil_flags.user_id,
+        sybil_flags.cluster_id,
+        sybil_flags.score,
+        sybil_flags.weight,
+        sybil_flags.reasons AS "reasons: Json<Vec<SybilReason>>",
+        sybil_flags.status,
+        sybil_flags.reviewed_by,
+        sybil_flags.reviewed_at,
+        sybil_flags.created_at,
+        sybil_flags.updated_at
+        FROM sybil_flags
+        JOIN users ON users.id = sybil_flags.user_id
+        WHERE sybil_flags.status = $1
+        ORDER BY sybil_flags.score DESC, sybil_flags.cluster_id
+        LIMIT $2
+        "#,
+        status.to_string(),
+        limit
+    )
+    .fetch_all(&mut **transaction)
+    .await?
+    .into_iter()
+    .map(|row| SybilFlagForReview {
+        email: row.email,
+        flag: SybilFlag {
+            id: row.id,
+            user_id: row.user_id,
+            cluster_id: row.cluster_id,
+            score: row.score,
+            weight: row.weight,
+            reasons: row.reasons,
+            status: SybilFlagStatus::from(row.status),
+            reviewed_by: row.reviewed_by,
+            reviewed_at: row.reviewed_at,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        },
+    })
+    .collect();
+    Ok(flags)
+}
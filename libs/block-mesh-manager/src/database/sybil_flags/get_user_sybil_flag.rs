@@ -0,0 +1,24 @@
+use block_mesh_manager_database_domain::domain::sybil_flag::{SybilFlag, SybilReason};
+use sqlx::types::Json;
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+pub async fn get_user_sybil_flag(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+) -> anyhow::Result<Option<SybilFlag>> {
+    let flag = sqlx::query_as!(
+        SybilFlag,
+        r#"
+        SELECT
+        id, user_id, cluster_id, score, weight, reasons AS "reasons: Json<Vec<SybilReason>>",
+        status, reviewed_by, reviewed_at, created_at, updated_at
+        FROM sybil_flags
+        WHERE user_id = $1
+        "#,
+        user_id
+    )
+    .fetch_optional(&mut **transaction)
+    .await?;
+    Ok(flag)
+}
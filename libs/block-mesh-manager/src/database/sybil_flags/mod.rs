@@ -0,0 +1,3 @@
+pub mod get_sybil_flags_for_review;
+pub mod get_user_sybil_flag;
+pub mod review_sybil_flag;
@@ -0,0 +1,26 @@
+use block_mesh_manager_database_domain::domain::sybil_flag::SybilFlagStatus;
+use chrono::Utc;
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+pub async fn review_sybil_flag(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+    status: SybilFlagStatus,
+    reviewed_by: &Uuid,
+) -> anyhow::Result<bool> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE sybil_flags
+        SET status = $2, reviewed_by = $3, reviewed_at = $4
+        WHERE user_id = $1
+        "#,
+        user_id,
+        status.to_string(),
+        reviewed_by,
+        Utc::now()
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,23 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Verified user with an empty password, the bare row the database tests build on.
+pub async fn insert_user(pool: &PgPool) -> anyhow::Result<Uuid> {
+    insert_user_with_wallet(pool, None).await
+}
+
+pub async fn insert_user_with_wallet(
+    pool: &PgPool,
+    wallet_address: Option<&str>,
+) -> anyhow::Result<Uuid> {
+    let id = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO users (id, email, password, created_at, verified_email, wallet_address) VALUES ($1, $2, '', now(), true, $3)",
+    )
+    .bind(id)
+    .bind(format!("{}@example.com", id))
+    .bind(wallet_address)
+    .execute(pool)
+    .await?;
+    Ok(id)
+}
@@ -0,0 +1,13 @@
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+#[tracing::instrument(name = "get_user_id_by_tg_id", skip(transaction))]
+pub async fn get_user_id_by_tg_id(
+    transaction: &mut Transaction<'_, Postgres>,
+    tg_id: i64,
+) -> anyhow::Result<Option<Uuid>> {
+    let user_id = sqlx::query_scalar!("SELECT user_id FROM tg_bot_links WHERE tg_id = $1", tg_id)
+        .fetch_optional(&mut **transaction)
+        .await?;
+    Ok(user_id)
+}
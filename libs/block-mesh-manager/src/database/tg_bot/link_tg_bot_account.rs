@@ -0,0 +1,35 @@
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+/// Consumes the code and moves the Telegram user over from any account it was linked to before,
+/// returns `None` when the code is unknown or expired.
+#[tracing::instrument(name = "link_tg_bot_account", skip(transaction, code))]
+pub async fn link_tg_bot_account(
+    transaction: &mut Transaction<'_, Postgres>,
+    code: &str,
+    tg_id: i64,
+) -> anyhow::Result<Option<Uuid>> {
+    sqlx::query!(
+        r#"
+        UPDATE tg_bot_links SET tg_id = NULL, linked_at = NULL, updated_at = now()
+        WHERE tg_id = $1
+        "#,
+        tg_id
+    )
+    .execute(&mut **transaction)
+    .await?;
+    let user_id: Option<Uuid> = sqlx::query_scalar!(
+        r#"
+        UPDATE tg_bot_links
+        SET tg_id = $2, linked_at = now(), link_code = NULL, link_code_expires_at = NULL,
+            updated_at = now()
+        WHERE link_code = $1 AND link_code_expires_at > now()
+        RETURNING user_id
+        "#,
+        code,
+        tg_id
+    )
+    .fetch_optional(&mut **transaction)
+    .await?;
+    Ok(user_id)
+}
@@ -0,0 +1,4 @@
+pub mod get_user_id_by_tg_id;
+pub mod link_tg_bot_account;
+pub mod set_tg_bot_link_code;
+pub mod unlink_tg_bot_account;
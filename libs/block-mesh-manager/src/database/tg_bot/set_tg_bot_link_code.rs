@@ -0,0 +1,27 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+/// Replaces any previous code, only the latest one can be used.
+#[tracing::instrument(name = "set_tg_bot_link_code", skip(transaction, code))]
+pub async fn set_tg_bot_link_code(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+    code: &str,
+    expires_at: DateTime<Utc>,
+) -> anyhow::Result<()> {
+    sqlx::query!(
+        r#"
+        INSERT INTO tg_bot_links (user_id, link_code, link_code_expires_at, created_at, updated_at)
+        VALUES ($1, $2, $3, now(), now())
+        ON CONFLICT (user_id) DO UPDATE
+        SET link_code = $2, link_code_expires_at = $3, updated_at = now()
+        "#,
+        user_id,
+        code,
+        expires_at
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,13 @@
+use sqlx::PgExecutor;
+use uuid::Uuid;
+
+#[tracing::instrument(name = "unlink_tg_bot_account", skip(executor))]
+pub async fn unlink_tg_bot_account(
+    executor: impl PgExecutor<'_>,
+    user_id: &Uuid,
+) -> anyhow::Result<()> {
+    sqlx::query!("DELETE FROM tg_bot_links WHERE user_id = $1", user_id)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
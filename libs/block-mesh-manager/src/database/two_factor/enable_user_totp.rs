@@ -0,0 +1,24 @@
+use chrono::Utc;
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+#[tracing::instrument(name = "enable_user_totp", skip_all)]
+pub async fn enable_user_totp(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+    step: i64,
+) -> anyhow::Result<()> {
+    sqlx::query!(
+        r#"
+        UPDATE user_totp
+        SET enabled = TRUE, enabled_at = $2, last_used_step = $3
+        WHERE user_id = $1
+        "#,
+        user_id,
+        Utc::now(),
+        step
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use crate::domain::user_totp::UserTotp;
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+#[tracing::instrument(name = "get_user_totp", skip_all)]
+pub async fn get_user_totp(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+) -> anyhow::Result<Option<UserTotp>> {
+    Ok(sqlx::query_as!(
+        UserTotp,
+        r#"
+        SELECT user_id, secret, enabled, last_used_step, created_at, enabled_at
+        FROM user_totp
+        WHERE user_id = $1
+        "#,
+        user_id
+    )
+    .fetch_optional(&mut **transaction)
+    .await?)
+}
+
+/// Number of unused recovery codes left.
+#[tracing::instrument(name = "count_recovery_codes", skip_all)]
+pub async fn count_recovery_codes(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+) -> anyhow::Result<i64> {
+    Ok(sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) AS "count!"
+        FROM recovery_codes
+        WHERE user_id = $1 AND used_at IS NULL
+        "#,
+        user_id
+    )
+    .fetch_one(&mut **transaction)
+    .await?)
+}
@@ -0,0 +1,9 @@
+pub mod delete_user_totp;
+pub mod enable_user_totp;
+pub mod get_user_totp;
+pub mod record_second_factor_attempt;
+pub mod replace_recovery_codes;
+pub mod update_totp_last_used_step;
+pub mod upsert_user_totp;
+pub mod use_recovery_code;
+pub mod verify_second_factor;
@@ -0,0 +1,48 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+/// Counts an attempt and locks the user out until `locked_until` once `max_attempts` failed in a row.
+/// Returns false while the user is locked out, the row lock serializes parallel attempts.
+#[tracing::instrument(name = "record_second_factor_attempt", skip_all)]
+pub async fn record_second_factor_attempt(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+    max_attempts: i32,
+    locked_until: DateTime<Utc>,
+) -> anyhow::Result<bool> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE user_totp
+        SET
+            failed_attempts = CASE WHEN failed_attempts + 1 >= $2 THEN 0 ELSE failed_attempts + 1 END,
+            locked_until = CASE WHEN failed_attempts + 1 >= $2 THEN $3::timestamptz ELSE NULL END
+        WHERE user_id = $1 AND (locked_until IS NULL OR locked_until <= $4)
+        "#,
+        user_id,
+        max_attempts,
+        locked_until,
+        Utc::now()
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+#[tracing::instrument(name = "reset_second_factor_attempts", skip_all)]
+pub async fn reset_second_factor_attempts(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+) -> anyhow::Result<()> {
+    sqlx::query!(
+        r#"
+        UPDATE user_totp
+        SET failed_attempts = 0, locked_until = NULL
+        WHERE user_id = $1
+        "#,
+        user_id
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
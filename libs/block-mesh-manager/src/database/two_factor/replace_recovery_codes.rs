@@ -0,0 +1,31 @@
+use chrono::Utc;
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+/// Invalidates every previous recovery code of the user.
+#[tracing::instrument(name = "replace_recovery_codes", skip_all)]
+pub async fn replace_recovery_codes(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+    code_hashes: &[String],
+) -> anyhow::Result<()> {
+    sqlx::query!("DELETE FROM recovery_codes WHERE user_id = $1", user_id)
+        .execute(&mut **transaction)
+        .await?;
+    let now = Utc::now();
+    for code_hash in code_hashes {
+        sqlx::query!(
+            r#"
+            INSERT INTO recovery_codes (id, user_id, code_hash, created_at)
+            VALUES ($1, $2, $3, $4)
+            "#,
+            Uuid::new_v4(),
+            user_id,
+            code_hash,
+            now
+        )
+        .execute(&mut **transaction)
+        .await?;
+    }
+    Ok(())
+}
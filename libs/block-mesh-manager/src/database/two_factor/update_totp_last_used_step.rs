@@ -0,0 +1,23 @@
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+/// Returns false when a concurrent request already consumed this or a later step.
+#[tracing::instrument(name = "update_totp_last_used_step", skip_all)]
+pub async fn update_totp_last_used_step(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+    step: i64,
+) -> anyhow::Result<bool> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE user_totp
+        SET last_used_step = $2
+        WHERE user_id = $1 AND (last_used_step IS NULL OR last_used_step < $2)
+        "#,
+        user_id,
+        step
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,27 @@
+use chrono::Utc;
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+/// Starts (or restarts) an enrollment with a fresh secret, never touches an enabled one.
+#[tracing::instrument(name = "upsert_user_totp", skip_all)]
+pub async fn upsert_user_totp(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+    secret: &str,
+) -> anyhow::Result<bool> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO user_totp (user_id, secret, enabled, created_at)
+        VALUES ($1, $2, FALSE, $3)
+        ON CONFLICT (user_id) DO UPDATE
+        SET secret = EXCLUDED.secret, created_at = EXCLUDED.created_at, last_used_step = NULL
+        WHERE user_totp.enabled = FALSE
+        "#,
+        user_id,
+        secret,
+        Utc::now()
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
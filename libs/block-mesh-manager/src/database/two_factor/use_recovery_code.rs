@@ -0,0 +1,25 @@
+use chrono::Utc;
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+/// Burns a recovery code, returns false when it doesn't exist or was already used.
+#[tracing::instrument(name = "use_recovery_code", skip_all)]
+pub async fn use_recovery_code(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+    code_hash: &str,
+) -> anyhow::Result<bool> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE recovery_codes
+        SET used_at = $3
+        WHERE user_id = $1 AND code_hash = $2 AND used_at IS NULL
+        "#,
+        user_id,
+        code_hash,
+        Utc::now()
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,130 @@
+use crate::database::two_factor::get_user_totp::get_user_totp;
+use crate::database::two_factor::record_second_factor_attempt::{
+    record_second_factor_attempt, reset_second_factor_attempts,
+};
+use crate::database::two_factor::update_totp_last_used_step::update_totp_last_used_step;
+use crate::database::two_factor::use_recovery_code::use_recovery_code;
+use crate::utils::totp::{hash_recovery_code, verify_totp};
+use chrono::{Duration, Utc};
+use sqlx::{Postgres, Transaction};
+use std::env;
+use uuid::Uuid;
+
+/// Accepts either a current TOTP code or an unused recovery code, consuming it.
+/// Every attempt is counted per user, after `TOTP_MAX_FAILED_ATTEMPTS` failures in a row every
+/// code is rejected for `TOTP_LOCKOUT_SECS`. Callers must commit even when this returns false.
+#[tracing::instrument(name = "verify_second_factor", skip_all)]
+pub async fn verify_second_factor(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+    code: &str,
+) -> anyhow::Result<bool> {
+    let Some(user_totp) = get_user_totp(transaction, user_id).await? else {
+        return Ok(false);
+    };
+    if !user_totp.enabled {
+        return Ok(false);
+    }
+    let max_attempts = env::var("TOTP_MAX_FAILED_ATTEMPTS")
+        .unwrap_or("5".to_string())
+        .parse()
+        .unwrap_or(5);
+    let lockout = env::var("TOTP_LOCKOUT_SECS")
+        .unwrap_or("900".to_string())
+        .parse()
+        .unwrap_or(900);
+    let locked_until = Utc::now() + Duration::seconds(lockout);
+    if !record_second_factor_attempt(transaction, user_id, max_attempts, locked_until).await? {
+        tracing::warn!("Second factor locked out for user {}", user_id);
+        return Ok(false);
+    }
+    let verified = match verify_totp(
+        &user_totp.secret,
+        code,
+        Utc::now().timestamp(),
+        user_totp.last_used_step,
+    ) {
+        Some(step) => update_totp_last_used_step(transaction, user_id, step).await?,
+        None => {
+            use_recovery_code(
+                transaction,
+                user_id,
+                &hash_recovery_code(code, &user_id.to_string()),
+            )
+            .await?
+        }
+    };
+    if verified {
+        reset_second_factor_attempts(transaction, user_id).await?;
+    }
+    Ok(verified)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::test_support::insert_user;
+    use sqlx::PgPool;
+
+    async fn user_with_recovery_codes(pool: &PgPool, codes: &[&str]) -> anyhow::Result<Uuid> {
+        let user_id = insert_user(pool).await?;
+        sqlx::query(
+            "INSERT INTO user_totp (user_id, secret, enabled, created_at) VALUES ($1, 'GEZDGNBVGY3TQOJQ', true, now())",
+        )
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+        for code in codes {
+            sqlx::query(
+                "INSERT INTO recovery_codes (id, user_id, code_hash, created_at) VALUES ($1, $2, $3, now())",
+            )
+            .bind(Uuid::new_v4())
+            .bind(user_id)
+            .bind(hash_recovery_code(code, &user_id.to_string()))
+            .execute(pool)
+            .await?;
+        }
+        Ok(user_id)
+    }
+
+    async fn verify(pool: &PgPool, user_id: &Uuid, code: &str) -> anyhow::Result<bool> {
+        let mut transaction = pool.begin().await?;
+        let verified = verify_second_factor(&mut transaction, user_id, code).await?;
+        transaction.commit().await?;
+        Ok(verified)
+    }
+
+    #[sqlx::test]
+    async fn test_locks_out_after_failed_attempts(pool: PgPool) -> anyhow::Result<()> {
+        let user_id = user_with_recovery_codes(&pool, &["code-one", "code-two"]).await?;
+        for _ in 0..5 {
+            assert!(!verify(&pool, &user_id, "wrong-code").await?);
+        }
+        // a valid code is rejected while locked out and is not burned
+        assert!(!verify(&pool, &user_id, "code-one").await?);
+
+        sqlx::query(
+            "UPDATE user_totp SET locked_until = now() - interval '1 second' WHERE user_id = $1",
+        )
+        .bind(user_id)
+        .execute(&pool)
+        .await?;
+        assert!(verify(&pool, &user_id, "code-one").await?);
+        assert!(!verify(&pool, &user_id, "code-one").await?);
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_success_resets_failed_attempts(pool: PgPool) -> anyhow::Result<()> {
+        let user_id = user_with_recovery_codes(&pool, &["code-one", "code-two"]).await?;
+        for _ in 0..4 {
+            assert!(!verify(&pool, &user_id, "wrong-code").await?);
+        }
+        assert!(verify(&pool, &user_id, "code-one").await?);
+        for _ in 0..4 {
+            assert!(!verify(&pool, &user_id, "wrong-code").await?);
+        }
+        assert!(verify(&pool, &user_id, "code-two").await?);
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
 pub mod create_test_user;
 pub mod create_user;
 pub mod get_user_by_email;
+pub mod update_user_email;
 pub mod update_user_invited_by;
 pub mod update_user_password;
 pub mod update_user_wallet;
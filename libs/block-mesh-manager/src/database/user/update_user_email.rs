@@ -0,0 +1,20 @@
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+/// Replaces the email and password of an account, the new email has to be confirmed again.
+pub async fn update_user_email(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+    email: &str,
+    password: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"UPDATE users SET email = $1, password = $2, verified_email = FALSE WHERE id = $3"#,
+        email,
+        password,
+        user_id
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,30 @@
+use chrono::Utc;
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+#[tracing::instrument(name = "create_user_session", skip_all)]
+pub async fn create_user_session(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+    session_id: Option<String>,
+    ip: Option<String>,
+    user_agent: Option<String>,
+) -> anyhow::Result<Uuid> {
+    let id = Uuid::new_v4();
+    let now = Utc::now();
+    sqlx::query!(
+        r#"
+        INSERT INTO user_sessions (id, user_id, session_id, ip, user_agent, created_at, last_seen_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $6)
+        "#,
+        id,
+        user_id,
+        session_id,
+        ip,
+        user_agent,
+        now
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(id)
+}
@@ -0,0 +1,21 @@
+use crate::domain::user_session::UserSession;
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+#[tracing::instrument(name = "get_user_session", skip_all)]
+pub async fn get_user_session(
+    transaction: &mut Transaction<'_, Postgres>,
+    id: &Uuid,
+) -> anyhow::Result<Option<UserSession>> {
+    Ok(sqlx::query_as!(
+        UserSession,
+        r#"
+        SELECT id, user_id, session_id, ip, user_agent, created_at, last_seen_at, revoked_at
+        FROM user_sessions
+        WHERE id = $1
+        "#,
+        id
+    )
+    .fetch_optional(&mut **transaction)
+    .await?)
+}
@@ -0,0 +1,26 @@
+use crate::domain::user_session::UserSession;
+use chrono::{DateTime, Utc};
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+/// Sessions that are neither revoked nor idle since before `active_since`.
+#[tracing::instrument(name = "get_user_sessions", skip_all)]
+pub async fn get_user_sessions(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+    active_since: DateTime<Utc>,
+) -> anyhow::Result<Vec<UserSession>> {
+    Ok(sqlx::query_as!(
+        UserSession,
+        r#"
+        SELECT id, user_id, session_id, ip, user_agent, created_at, last_seen_at, revoked_at
+        FROM user_sessions
+        WHERE user_id = $1 AND revoked_at IS NULL AND last_seen_at > $2
+        ORDER BY last_seen_at DESC
+        "#,
+        user_id,
+        active_since
+    )
+    .fetch_all(&mut **transaction)
+    .await?)
+}
@@ -0,0 +1,5 @@
+pub mod create_user_session;
+pub mod get_user_session;
+pub mod get_user_sessions;
+pub mod revoke_user_session;
+pub mod touch_user_session;
@@ -0,0 +1,37 @@
+use chrono::Utc;
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+/// Marks the session revoked and drops it from the `tower_sessions` store,
+/// returns false when the user has no live session with this id.
+#[tracing::instrument(name = "revoke_user_session", skip(transaction))]
+pub async fn revoke_user_session(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+    id: &Uuid,
+) -> anyhow::Result<bool> {
+    let revoked = sqlx::query_scalar!(
+        r#"
+        UPDATE user_sessions
+        SET revoked_at = $3
+        WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL
+        RETURNING session_id
+        "#,
+        id,
+        user_id,
+        Utc::now()
+    )
+    .fetch_optional(&mut **transaction)
+    .await?;
+    let Some(session_id) = revoked else {
+        return Ok(false);
+    };
+    if let Some(session_id) = session_id {
+        // the schema is created by the session store at startup, not by the migrations
+        sqlx::query("DELETE FROM tower_sessions.session WHERE id = $1")
+            .bind(session_id)
+            .execute(&mut **transaction)
+            .await?;
+    }
+    Ok(true)
+}
@@ -0,0 +1,28 @@
+use chrono::Utc;
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+#[tracing::instrument(name = "touch_user_session", skip_all)]
+pub async fn touch_user_session(
+    transaction: &mut Transaction<'_, Postgres>,
+    id: &Uuid,
+    session_id: Option<String>,
+    ip: Option<String>,
+) -> anyhow::Result<()> {
+    sqlx::query!(
+        r#"
+        UPDATE user_sessions
+        SET last_seen_at = $2,
+            session_id = COALESCE($3, session_id),
+            ip = COALESCE($4, ip)
+        WHERE id = $1
+        "#,
+        id,
+        Utc::now(),
+        session_id,
+        ip
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+#[tracing::instrument(name = "create_wallet_challenge", skip(transaction, message))]
+pub async fn create_wallet_challenge(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+    wallet_address: &str,
+    message: &str,
+    expires_at: DateTime<Utc>,
+) -> anyhow::Result<Uuid> {
+    let id = Uuid::new_v4();
+    let now = Utc::now();
+    sqlx::query!("DELETE FROM wallet_challenges WHERE expires_at < $1", now)
+        .execute(&mut **transaction)
+        .await?;
+    sqlx::query!(
+        r#"
+        INSERT INTO wallet_challenges (id, user_id, wallet_address, message, created_at, expires_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+        id,
+        user_id,
+        wallet_address,
+        message,
+        now,
+        expires_at
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(id)
+}
@@ -0,0 +1,150 @@
+use crate::domain::wallet_link_event::WalletLinkAction;
+use chrono::Utc;
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+#[tracing::instrument(name = "insert_wallet_link_event", skip(transaction))]
+pub async fn insert_wallet_link_event(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+    wallet_address: &str,
+    action: WalletLinkAction,
+    ip: Option<String>,
+) -> anyhow::Result<()> {
+    sqlx::query!(
+        r#"
+        INSERT INTO wallet_link_events (id, user_id, wallet_address, action, ip, created_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+        Uuid::new_v4(),
+        user_id,
+        wallet_address,
+        action.to_string(),
+        ip,
+        Utc::now()
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
+
+/// True when the user ever linked a wallet, or the wallet was ever linked to any account,
+/// which means the one time wallet bonus was already paid out.
+#[tracing::instrument(name = "wallet_bonus_already_granted", skip(transaction))]
+pub async fn wallet_bonus_already_granted(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+    wallet_address: &str,
+) -> anyhow::Result<bool> {
+    Ok(sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM wallet_link_events
+            WHERE action = $3 AND (user_id = $1 OR wallet_address = $2)
+        ) AS "exists!"
+        "#,
+        user_id,
+        wallet_address,
+        WalletLinkAction::Link.to_string()
+    )
+    .fetch_one(&mut **transaction)
+    .await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::test_support::{insert_user, insert_user_with_wallet};
+    use sqlx::{Executor, PgPool};
+
+    #[sqlx::test]
+    async fn test_wallet_bonus_already_granted(pool: PgPool) -> anyhow::Result<()> {
+        let (first, second) = (insert_user(&pool).await?, insert_user(&pool).await?);
+        let mut transaction = pool.begin().await?;
+        assert!(!wallet_bonus_already_granted(&mut transaction, &first, "wallet").await?);
+        insert_wallet_link_event(
+            &mut transaction,
+            &first,
+            "wallet",
+            WalletLinkAction::Unlink,
+            None,
+        )
+        .await?;
+        assert!(!wallet_bonus_already_granted(&mut transaction, &first, "wallet").await?);
+        insert_wallet_link_event(
+            &mut transaction,
+            &first,
+            "wallet",
+            WalletLinkAction::Link,
+            None,
+        )
+        .await?;
+        // the same user with another wallet, and another user with the same wallet
+        assert!(wallet_bonus_already_granted(&mut transaction, &first, "other").await?);
+        assert!(wallet_bonus_already_granted(&mut transaction, &second, "wallet").await?);
+        assert!(!wallet_bonus_already_granted(&mut transaction, &second, "other").await?);
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_migration_unlinks_newer_accounts_of_duplicate_wallet(
+        pool: PgPool,
+    ) -> anyhow::Result<()> {
+        const VERSION: i64 = 20241117090000;
+        let migrator = sqlx::migrate!();
+        for migration in migrator.iter().filter(|m| m.version < VERSION) {
+            pool.execute(&*migration.sql).await?;
+        }
+        // databases where the original unique constraint was dropped may hold duplicates
+        pool.execute("ALTER TABLE users DROP CONSTRAINT users_wallet_address_key")
+            .await?;
+        let oldest = insert_user_with_wallet(&pool, Some("wallet")).await?;
+        let newer = insert_user_with_wallet(&pool, Some("wallet")).await?;
+        sqlx::query("UPDATE users SET created_at = now() - interval '1 day' WHERE id = $1")
+            .bind(oldest)
+            .execute(&pool)
+            .await?;
+        for user_id in [oldest, newer] {
+            sqlx::query(
+                "INSERT INTO perks (id, user_id, created_at, name, multiplier) VALUES ($1, $2, now(), 'wallet', 1.1)",
+            )
+            .bind(Uuid::new_v4())
+            .bind(user_id)
+            .execute(&pool)
+            .await?;
+        }
+        let migration = migrator
+            .iter()
+            .find(|m| m.version == VERSION)
+            .expect("wallet challenges migration");
+        pool.execute(&*migration.sql).await?;
+
+        let wallets: Vec<(Uuid, Option<String>)> = sqlx::query_as(
+            "SELECT id, wallet_address FROM users WHERE id = ANY($1) ORDER BY created_at",
+        )
+        .bind(vec![oldest, newer])
+        .fetch_all(&pool)
+        .await?;
+        assert_eq!(
+            wallets,
+            vec![(oldest, Some("wallet".to_string())), (newer, None)]
+        );
+        let events: Vec<(Uuid, String)> = sqlx::query_as(
+            "SELECT user_id, action FROM wallet_link_events WHERE action = 'unlink'",
+        )
+        .fetch_all(&pool)
+        .await?;
+        assert_eq!(events, vec![(newer, "unlink".to_string())]);
+        let wallet_perks: Vec<Uuid> =
+            sqlx::query_scalar("SELECT user_id FROM perks WHERE name = 'wallet'")
+                .fetch_all(&pool)
+                .await?;
+        assert_eq!(wallet_perks, vec![oldest]);
+        // both accounts got the bonus, neither can be paid again
+        let mut transaction = pool.begin().await?;
+        assert!(wallet_bonus_already_granted(&mut transaction, &newer, "other").await?);
+        transaction.commit().await?;
+        Ok(())
+    }
+}
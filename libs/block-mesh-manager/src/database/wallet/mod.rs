@@ -0,0 +1,4 @@
+pub mod create_wallet_challenge;
+pub mod insert_wallet_link_event;
+pub mod unlink_user_wallet;
+pub mod use_wallet_challenge;
@@ -0,0 +1,32 @@
+use crate::domain::perk::PerkName;
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+/// Detaches the wallet and drops the wallet perk multiplier, returns the detached wallet.
+/// Points already credited by the perk stay in the ledger.
+#[tracing::instrument(name = "unlink_user_wallet", skip(transaction))]
+pub async fn unlink_user_wallet(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+) -> anyhow::Result<Option<String>> {
+    let previous = sqlx::query_scalar!(
+        r#"
+        UPDATE users u
+        SET wallet_address = NULL
+        FROM (SELECT id, wallet_address FROM users WHERE id = $1 FOR UPDATE) old
+        WHERE u.id = old.id
+        RETURNING old.wallet_address
+        "#,
+        user_id
+    )
+    .fetch_optional(&mut **transaction)
+    .await?;
+    sqlx::query!(
+        "DELETE FROM perks WHERE user_id = $1 AND name = $2",
+        user_id,
+        PerkName::Wallet.to_string()
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(previous.flatten())
+}
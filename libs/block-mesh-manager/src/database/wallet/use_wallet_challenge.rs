@@ -0,0 +1,71 @@
+use chrono::Utc;
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+/// Burns the challenge the user was issued for exactly this wallet and message, returns false
+/// when there is none, it expired or it was already used.
+#[tracing::instrument(name = "use_wallet_challenge", skip(transaction, message))]
+pub async fn use_wallet_challenge(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+    wallet_address: &str,
+    message: &str,
+) -> anyhow::Result<bool> {
+    let now = Utc::now();
+    let result = sqlx::query!(
+        r#"
+        UPDATE wallet_challenges
+        SET used_at = $5
+        WHERE user_id = $1
+        AND wallet_address = $2
+        AND message = $3
+        AND used_at IS NULL
+        AND expires_at > $4
+        "#,
+        user_id,
+        wallet_address,
+        message,
+        now,
+        now
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::test_support::insert_user;
+    use crate::database::wallet::create_wallet_challenge::create_wallet_challenge;
+    use chrono::Duration;
+    use sqlx::PgPool;
+
+    #[sqlx::test]
+    async fn test_challenge_is_single_use_and_bound(pool: PgPool) -> anyhow::Result<()> {
+        let (user_id, other_id) = (insert_user(&pool).await?, insert_user(&pool).await?);
+        let mut transaction = pool.begin().await?;
+        let expires_at = Utc::now() + Duration::minutes(5);
+        create_wallet_challenge(&mut transaction, &user_id, "wallet", "message", expires_at)
+            .await?;
+        assert!(!use_wallet_challenge(&mut transaction, &other_id, "wallet", "message").await?);
+        assert!(!use_wallet_challenge(&mut transaction, &user_id, "other", "message").await?);
+        assert!(!use_wallet_challenge(&mut transaction, &user_id, "wallet", "other").await?);
+        assert!(use_wallet_challenge(&mut transaction, &user_id, "wallet", "message").await?);
+        assert!(!use_wallet_challenge(&mut transaction, &user_id, "wallet", "message").await?);
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_expired_challenge_is_rejected(pool: PgPool) -> anyhow::Result<()> {
+        let user_id = insert_user(&pool).await?;
+        let mut transaction = pool.begin().await?;
+        let expires_at = Utc::now() - Duration::seconds(1);
+        create_wallet_challenge(&mut transaction, &user_id, "wallet", "message", expires_at)
+            .await?;
+        assert!(!use_wallet_challenge(&mut transaction, &user_id, "wallet", "message").await?);
+        transaction.commit().await?;
+        Ok(())
+    }
+}
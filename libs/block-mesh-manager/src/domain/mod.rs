@@ -7,4 +7,7 @@ pub mod perk;
 pub mod provider_master_status;
 pub mod proxy_master;
 pub mod uptime_report;
+pub mod user_session;
+pub mod user_totp;
 pub mod users_ip;
+pub mod wallet_link_event;
@@ -0,0 +1,17 @@
+use std::fmt::Display;
+
+/// Audit trail entry of a wallet being attached to or detached from an account.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WalletLinkAction {
+    Link,
+    Unlink,
+}
+
+impl Display for WalletLinkAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WalletLinkAction::Link => write!(f, "link"),
+            WalletLinkAction::Unlink => write!(f, "unlink"),
+        }
+    }
+}
@@ -1,3 +1 @@
-mod confirm_email;
 pub mod email_client;
-mod reset_email;
@@ -53,6 +53,42 @@ pub enum Error {
     TokenMismatch,
     #[error("Signature mismatch")]
     SignatureMismatch,
+    #[error("Points ledger entry not found")]
+    PointsLedgerEntryNotFound,
+    #[error("Points already reversed")]
+    PointsAlreadyReversed,
+    #[error("Reward rules not found")]
+    RewardRulesNotFound,
+    #[error("Sybil flag not found")]
+    SybilFlagNotFound,
+    #[error("Invalid api token request")]
+    InvalidApiTokenRequest,
+    #[error("Invalid two factor code")]
+    InvalidTwoFactorCode,
+    #[error("Invalid two factor request")]
+    InvalidTwoFactorRequest,
+    #[error("User session not found")]
+    UserSessionNotFound,
+    #[error("Wallet sign in failed: {0}")]
+    WalletSignIn(String),
+    #[error("Email already linked")]
+    EmailAlreadyLinked,
+    #[error("Wallet is linked to another account")]
+    WalletAlreadyLinked,
+    #[error("Unknown, expired or already used wallet challenge")]
+    InvalidWalletChallenge,
+    #[error("Invalid account request: {0}")]
+    InvalidAccountRequest(String),
+    #[error("Invalid link email request: {0}")]
+    InvalidLinkEmailRequest(String),
+    #[error("Telegram notifications are not available")]
+    TelegramNotificationsDisabled,
+    #[error("Telegram bot linking is not available")]
+    TgBotDisabled,
+    #[error("Unknown or expired Telegram bot link code")]
+    InvalidTgBotLinkCode,
+    #[error("Telegram account is not linked")]
+    TgBotAccountNotLinked,
 }
 
 impl Error {
@@ -82,6 +118,58 @@ impl IntoResponse for Error {
             Error::SignatureMismatch => {
                 (StatusCode::INTERNAL_SERVER_ERROR, "Signature Mismatch").into_response()
             }
+            Error::PointsLedgerEntryNotFound => {
+                (StatusCode::BAD_REQUEST, "Points Ledger Entry Not Found").into_response()
+            }
+            Error::PointsAlreadyReversed => {
+                (StatusCode::BAD_REQUEST, "Points Already Reversed").into_response()
+            }
+            Error::RewardRulesNotFound => {
+                (StatusCode::INTERNAL_SERVER_ERROR, "Reward Rules Not Found").into_response()
+            }
+            Error::SybilFlagNotFound => {
+                (StatusCode::BAD_REQUEST, "Sybil Flag Not Found").into_response()
+            }
+            Error::InvalidApiTokenRequest => {
+                (StatusCode::BAD_REQUEST, "Invalid Api Token Request").into_response()
+            }
+            Error::InvalidTwoFactorCode => {
+                (StatusCode::BAD_REQUEST, "Invalid Two Factor Code").into_response()
+            }
+            Error::InvalidTwoFactorRequest => {
+                (StatusCode::BAD_REQUEST, "Invalid Two Factor Request").into_response()
+            }
+            Error::UserSessionNotFound => {
+                (StatusCode::BAD_REQUEST, "User Session Not Found").into_response()
+            }
+            Error::WalletSignIn(e) => (StatusCode::UNAUTHORIZED, e).into_response(),
+            Error::EmailAlreadyLinked => {
+                (StatusCode::BAD_REQUEST, "Email Already Linked").into_response()
+            }
+            Error::WalletAlreadyLinked => {
+                (StatusCode::BAD_REQUEST, "Wallet Already Linked").into_response()
+            }
+            Error::InvalidWalletChallenge => {
+                (StatusCode::BAD_REQUEST, "Invalid Wallet Challenge").into_response()
+            }
+            Error::InvalidAccountRequest(e) => (StatusCode::BAD_REQUEST, e).into_response(),
+            Error::InvalidLinkEmailRequest(e) => (StatusCode::BAD_REQUEST, e).into_response(),
+            Error::TelegramNotificationsDisabled => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "Telegram notifications are not available",
+            )
+                .into_response(),
+            Error::TgBotDisabled => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "Telegram bot linking is not available",
+            )
+                .into_response(),
+            Error::InvalidTgBotLinkCode => {
+                (StatusCode::BAD_REQUEST, "Invalid Telegram Bot Link Code").into_response()
+            }
+            Error::TgBotAccountNotLinked => {
+                (StatusCode::NOT_FOUND, "Telegram Account Not Linked").into_response()
+            }
             Error::TokenMismatch => {
                 (StatusCode::INTERNAL_SERVER_ERROR, "Token Mismatch").into_response()
             }
@@ -154,6 +242,24 @@ impl From<Error> for StatusCode {
             Error::PleaseLogout => StatusCode::INTERNAL_SERVER_ERROR,
             Error::NotAllowedRateLimit => StatusCode::INTERNAL_SERVER_ERROR,
             Error::SignatureMismatch => StatusCode::INTERNAL_SERVER_ERROR,
+            Error::PointsLedgerEntryNotFound => StatusCode::BAD_REQUEST,
+            Error::PointsAlreadyReversed => StatusCode::BAD_REQUEST,
+            Error::RewardRulesNotFound => StatusCode::INTERNAL_SERVER_ERROR,
+            Error::SybilFlagNotFound => StatusCode::BAD_REQUEST,
+            Error::InvalidApiTokenRequest => StatusCode::BAD_REQUEST,
+            Error::InvalidTwoFactorCode => StatusCode::BAD_REQUEST,
+            Error::InvalidTwoFactorRequest => StatusCode::BAD_REQUEST,
+            Error::UserSessionNotFound => StatusCode::BAD_REQUEST,
+            Error::WalletSignIn(_) => StatusCode::UNAUTHORIZED,
+            Error::EmailAlreadyLinked => StatusCode::BAD_REQUEST,
+            Error::WalletAlreadyLinked => StatusCode::BAD_REQUEST,
+            Error::InvalidWalletChallenge => StatusCode::BAD_REQUEST,
+            Error::InvalidAccountRequest(_) => StatusCode::BAD_REQUEST,
+            Error::InvalidLinkEmailRequest(_) => StatusCode::BAD_REQUEST,
+            Error::TelegramNotificationsDisabled => StatusCode::SERVICE_UNAVAILABLE,
+            Error::TgBotDisabled => StatusCode::SERVICE_UNAVAILABLE,
+            Error::InvalidTgBotLinkCode => StatusCode::BAD_REQUEST,
+            Error::TgBotAccountNotLinked => StatusCode::NOT_FOUND,
             Error::TokenMismatch => StatusCode::INTERNAL_SERVER_ERROR,
             Error::NotYourTask => StatusCode::INTERNAL_SERVER_ERROR,
             Error::TaskResponseNotFound => StatusCode::INTERNAL_SERVER_ERROR,
@@ -1,11 +1,14 @@
+pub mod bell_icon;
 pub mod checkmark_icon;
 pub mod chrome_icon;
 pub mod clipboard_icon;
 pub mod edit_icon;
 pub mod home_icon;
+pub mod key_icon;
 pub mod link_icon;
 pub mod logout_icon;
 pub mod medal_icon;
 pub mod perk_icon;
+pub mod shield_icon;
 pub mod twitter_icon;
 pub mod xmark_icon;
@@ -18,4 +18,5 @@ pub mod reload_button;
 pub mod stat;
 pub mod sub_heading;
 pub mod tables;
+pub mod tg_bot_link;
 pub mod wallet_selector;
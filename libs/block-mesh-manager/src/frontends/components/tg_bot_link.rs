@@ -0,0 +1,83 @@
+use crate::frontends::context::notification_context::NotificationContext;
+use block_mesh_common::interfaces::server_api::TgBotLinkCodeResponse;
+use block_mesh_common::routes_enum::RoutesEnum;
+use leptos::*;
+use reqwest::Client;
+
+/// Shows the one-time code the user sends to the privacy bot with `/link`.
+#[component]
+pub fn TgBotLink() -> impl IntoView {
+    let notifications = expect_context::<NotificationContext>();
+    let link_code = RwSignal::new(None::<TgBotLinkCodeResponse>);
+
+    let get_code = create_action(move |_: &()| async move {
+        let response = Client::new()
+            .post(format!(
+                "{}/api{}",
+                window().origin(),
+                RoutesEnum::Api_TgBotLinkCode
+            ))
+            .send()
+            .await;
+        match response {
+            Ok(res) if res.status().as_u16() == 200 => {
+                link_code.set(res.json::<TgBotLinkCodeResponse>().await.ok())
+            }
+            _ => notifications.set_error("Failed to create a link code"),
+        }
+    });
+
+    let unlink = create_action(move |_: &()| async move {
+        let response = Client::new()
+            .post(format!(
+                "{}/api{}",
+                window().origin(),
+                RoutesEnum::Api_TgBotUnlink
+            ))
+            .send()
+            .await;
+        match response {
+            Ok(res) if res.status().as_u16() == 200 => {
+                link_code.set(None);
+                notifications.set_success("Telegram bot disconnected");
+            }
+            _ => notifications.set_error("Failed to disconnect the Telegram bot"),
+        }
+    });
+
+    view! {
+        <div class="mt-4 flex flex-col text-off-white">
+            "Link the AI privacy bot to your account, its daily quota grows with your uptime and points"
+            {move || {
+                link_code
+                    .get()
+                    .map(|link| {
+                        view! {
+                            <div class="mt-2">
+                                "Send "
+                                <span class="font-mono text-cyan">{format!("/link {}", link.code)}</span>
+                                {format!(
+                                    " to the bot before {}",
+                                    link.expires_at.format("%H:%M UTC"),
+                                )}
+                            </div>
+                        }
+                    })
+            }}
+            <div class="mt-4 flex gap-4">
+                <button
+                    class="hover:text-orange text-off-white py-2 px-4 border border-orange rounded font-bebas-neue"
+                    on:click=move |_| get_code.dispatch(())
+                >
+                    Get link code
+                </button>
+                <button
+                    class="hover:text-orange text-off-white py-2 px-4 border border-orange rounded font-bebas-neue"
+                    on:click=move |_| unlink.dispatch(())
+                >
+                    Disconnect bot
+                </button>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,196 @@
+use crate::frontends::components::heading::Heading;
+use crate::frontends::components::sub_heading::Subheading;
+use crate::frontends::components::tables::table::Table;
+use crate::frontends::components::tables::table_cell::TableCell;
+use crate::frontends::components::tables::table_head::TableHead;
+use crate::frontends::components::tables::table_header::TableHeader;
+use crate::frontends::context::notification_context::NotificationContext;
+use crate::frontends::context::reload_context::ReloadContext;
+use block_mesh_common::interfaces::server_api::{
+    ApiTokenIdRequest, ApiTokenSecretResponse, CreateApiTokenRequest, DashboardResponse,
+};
+use block_mesh_common::routes_enum::RoutesEnum;
+use leptos::*;
+use reqwest::Client;
+use uuid::Uuid;
+
+#[component]
+pub fn ApiTokens() -> impl IntoView {
+    let notifications = expect_context::<NotificationContext>();
+    let reload = expect_context::<ReloadContext>();
+    let async_data = use_context::<DashboardResponse>();
+    let api_tokens = RwSignal::new(vec![]);
+    let new_token_name = RwSignal::new(String::default());
+    let issued_token = RwSignal::new(None::<ApiTokenSecretResponse>);
+    if let Some(data) = async_data {
+        api_tokens.set(data.api_tokens);
+    }
+
+    let create = create_action(move |_: &()| {
+        let reload = reload.clone();
+        async move {
+            let name = new_token_name.get_untracked();
+            if name.trim().is_empty() {
+                notifications.set_error("Please fill a token name");
+                return;
+            }
+            let origin = window().origin();
+            let response = Client::new()
+                .post(format!("{}/api{}", origin, RoutesEnum::Api_ApiTokens))
+                .json(&CreateApiTokenRequest {
+                    name,
+                    scopes: vec!["node-report".to_string(), "read-stats".to_string()],
+                    expires_in_days: None,
+                })
+                .send()
+                .await;
+            match response {
+                Ok(res) if res.status().as_u16() == 200 => {
+                    if let Ok(secret) = res.json::<ApiTokenSecretResponse>().await {
+                        issued_token.set(Some(secret));
+                    }
+                    notifications.set_success("Token created");
+                    reload.trigger_reload();
+                }
+                _ => notifications.set_error("Failed to create token, names must be unique"),
+            }
+        }
+    });
+
+    let revoke = create_action(move |id: &Uuid| {
+        let id = *id;
+        async move {
+            let origin = window().origin();
+            let response = Client::new()
+                .post(format!("{}/api{}", origin, RoutesEnum::Api_ApiTokensRevoke))
+                .json(&ApiTokenIdRequest { id })
+                .send()
+                .await;
+            match response {
+                Ok(res) if res.status().as_u16() == 200 => {
+                    api_tokens.update(|tokens| tokens.retain(|token| token.id != id));
+                    notifications.set_success("Token revoked");
+                }
+                _ => notifications.set_error("Failed to revoke token"),
+            }
+        }
+    });
+
+    let rotate = create_action(move |id: &Uuid| {
+        let id = *id;
+        async move {
+            let origin = window().origin();
+            let response = Client::new()
+                .post(format!("{}/api{}", origin, RoutesEnum::Api_ApiTokensRotate))
+                .json(&ApiTokenIdRequest { id })
+                .send()
+                .await;
+            match response {
+                Ok(res) if res.status().as_u16() == 200 => {
+                    if let Ok(secret) = res.json::<ApiTokenSecretResponse>().await {
+                        issued_token.set(Some(secret));
+                    }
+                    notifications.set_success("Token rotated");
+                }
+                _ => notifications.set_error("Failed to rotate token"),
+            }
+        }
+    });
+
+    view! {
+        <div class="flex items-start justify-start gap-4">
+            <Heading>API Tokens</Heading>
+        </div>
+        <form class="mt-4 flex gap-4" on:submit=|ev| ev.prevent_default()>
+            <input
+                class="appearance-none rounded border px-3 py-2 text-black shadow"
+                type="text"
+                placeholder="Device name"
+                on:change=move |ev| {
+                    let val = event_target_value(&ev);
+                    new_token_name.update(|v| *v = val);
+                }
+            />
+            <button
+                class="hover:text-orange text-off-white py-2 px-4 border border-orange rounded font-bebas-neue focus:outline-none focus:shadow-outline"
+                type="submit"
+                on:click=move |_| create.dispatch(())
+            >
+                Create Token
+            </button>
+        </form>
+        {move || {
+            issued_token
+                .get()
+                .map(|secret| {
+                    view! {
+                        <Subheading class="mt-8">
+                            {format!(
+                                "New token, copy it now as it will not be shown again: {}",
+                                secret.api_token,
+                            )}
+                        </Subheading>
+                    }
+                })
+        }}
+        <Subheading class="mt-14">Tokens List</Subheading>
+        <Table class="mt-4 [--gutter:theme(spacing.6)] lg:[--gutter:theme(spacing.10)]">
+            <TableHead>
+                <tr>
+                    <TableHeader>Name</TableHeader>
+                    <TableHeader>Scopes</TableHeader>
+                    <TableHeader>Last Used</TableHeader>
+                    <TableHeader>Last IP</TableHeader>
+                    <TableHeader>Expires</TableHeader>
+                    <TableHeader class="text-right">Actions</TableHeader>
+                </tr>
+            </TableHead>
+            <tbody>
+                {move || {
+                    api_tokens
+                        .get()
+                        .iter()
+                        .cloned()
+                        .map(|token| {
+                            let id = token.id;
+                            view! {
+                                <tr>
+                                    <TableCell>{token.name}</TableCell>
+                                    <TableCell>{token.scopes.join(", ")}</TableCell>
+                                    <TableCell>
+                                        {token
+                                            .last_used_at
+                                            .map(|i| i.to_string())
+                                            .unwrap_or("Never".to_string())}
+                                    </TableCell>
+                                    <TableCell>{token.last_used_ip.unwrap_or_default()}</TableCell>
+                                    <TableCell>
+                                        {token
+                                            .expires_at
+                                            .map(|i| i.to_string())
+                                            .unwrap_or("Never".to_string())}
+                                    </TableCell>
+                                    <TableCell class="text-right">
+                                        <button
+                                            class="hover:text-orange text-off-white px-2"
+                                            on:click=move |_| rotate.dispatch(id)
+                                        >
+                                            Rotate
+                                        </button>
+                                        <button
+                                            class="hover:text-orange text-off-white px-2"
+                                            on:click=move |_| revoke.dispatch(id)
+                                        >
+                                            Revoke
+                                        </button>
+                                    </TableCell>
+                                </tr>
+                            }
+                        })
+                        .collect_view()
+                }}
+
+            </tbody>
+        </Table>
+    }
+}
@@ -1,10 +1,13 @@
 use crate::frontends::components::avatar::Avatar;
 use crate::frontends::components::conditionals::if_let_some::IfLetSome;
+use crate::frontends::components::icons::bell_icon::BellIcon;
 use crate::frontends::components::icons::home_icon::HomeIcon;
+use crate::frontends::components::icons::key_icon::KeyIcon;
 use crate::frontends::components::icons::link_icon::LinkIcon;
 use crate::frontends::components::icons::logout_icon::LogoutIcon;
 use crate::frontends::components::icons::medal_icon::MedalIcon;
 use crate::frontends::components::icons::perk_icon::PerkIcon;
+use crate::frontends::components::icons::shield_icon::ShieldIcon;
 use crate::frontends::components::navbars::navbar::Navbar;
 use crate::frontends::components::navbars::navbar_section::NavbarSection;
 use crate::frontends::components::navbars::navbar_spacer::NavbarSpacer;
@@ -72,6 +75,18 @@ pub fn ApplicationSidebar() -> impl IntoView {
                         <MedalIcon/>
                         <SidebarLabel>Daily Leaderboard</SidebarLabel>
                     </SidebarItemLink>
+                    <SidebarItemLink href="/ui/api_tokens">
+                        <KeyIcon/>
+                        <SidebarLabel>API Tokens</SidebarLabel>
+                    </SidebarItemLink>
+                    <SidebarItemLink href="/ui/security">
+                        <ShieldIcon/>
+                        <SidebarLabel>Security</SidebarLabel>
+                    </SidebarItemLink>
+                    <SidebarItemLink href="/ui/notifications">
+                        <BellIcon/>
+                        <SidebarLabel>Notifications</SidebarLabel>
+                    </SidebarItemLink>
                 </SidebarSection>
 
                 <SidebarSpacer/>
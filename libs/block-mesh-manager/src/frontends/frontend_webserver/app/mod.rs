@@ -1,7 +1,11 @@
 pub mod admin_dashboard;
+pub mod api_tokens;
 pub mod application_layout;
 pub mod daily_leaderboard;
 pub mod extension;
 pub mod new_dashboard;
+pub mod notifications;
 pub mod perks;
 pub mod referrals;
+pub mod security;
+pub mod wallet_login;
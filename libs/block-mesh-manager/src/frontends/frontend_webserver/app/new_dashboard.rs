@@ -10,6 +10,7 @@ use crate::frontends::components::tables::table::Table;
 use crate::frontends::components::tables::table_cell::TableCell;
 use crate::frontends::components::tables::table_head::TableHead;
 use crate::frontends::components::tables::table_header::TableHeader;
+use crate::frontends::components::tg_bot_link::TgBotLink;
 use crate::frontends::context::notification_context::NotificationContext;
 use block_mesh_common::constants::BLOCK_MESH_CHROME_EXTENSION_LINK;
 use block_mesh_common::interfaces::server_api::{
@@ -203,5 +204,7 @@ pub fn NewDashboard() -> impl IntoView {
         </Table>
         <Subheading>Daily points earnings</Subheading>
         <BarChart/>
+        <Subheading>Telegram AI bot</Subheading>
+        <TgBotLink/>
     }
 }
@@ -0,0 +1,191 @@
+use crate::frontends::components::heading::Heading;
+use crate::frontends::components::sub_heading::Subheading;
+use crate::frontends::context::notification_context::NotificationContext;
+use block_mesh_common::interfaces::server_api::{
+    NotificationPreferencesResponse, TelegramLinkResponse, UpdateNotificationPreferencesRequest,
+};
+use block_mesh_common::routes_enum::RoutesEnum;
+use leptos::*;
+use reqwest::Client;
+
+#[component]
+pub fn Notifications() -> impl IntoView {
+    let notifications = expect_context::<NotificationContext>();
+    let preferences = RwSignal::new(NotificationPreferencesResponse::default());
+    let telegram_url = RwSignal::new(None::<String>);
+
+    let _data_resource = create_local_resource(
+        move || (),
+        move |_| async move {
+            if let Ok(res) = Client::new()
+                .get(format!(
+                    "{}/api{}",
+                    window().origin(),
+                    RoutesEnum::Api_NotificationPreferences
+                ))
+                .send()
+                .await
+            {
+                if let Ok(response) = res.json::<NotificationPreferencesResponse>().await {
+                    preferences.set(response);
+                }
+            }
+        },
+    );
+
+    let save = create_action(move |_: &()| async move {
+        let current = preferences.get_untracked();
+        let response = Client::new()
+            .post(format!(
+                "{}/api{}",
+                window().origin(),
+                RoutesEnum::Api_NotificationPreferences
+            ))
+            .json(&UpdateNotificationPreferencesRequest {
+                email_enabled: current.email_enabled,
+                node_offline: current.node_offline,
+                new_referral: current.new_referral,
+                perk_earned: current.perk_earned,
+                daily_summary: current.daily_summary,
+                offline_minutes: current.offline_minutes,
+            })
+            .send()
+            .await;
+        match response {
+            Ok(res) if res.status().as_u16() == 200 => {
+                if let Ok(response) = res.json::<NotificationPreferencesResponse>().await {
+                    preferences.set(response);
+                }
+                notifications.set_success("Notification settings saved");
+            }
+            _ => notifications.set_error("Failed to save notification settings"),
+        }
+    });
+
+    let link_telegram = create_action(move |_: &()| async move {
+        let response = Client::new()
+            .post(format!(
+                "{}/api{}",
+                window().origin(),
+                RoutesEnum::Api_NotificationTelegramLink
+            ))
+            .send()
+            .await;
+        match response {
+            Ok(res) if res.status().as_u16() == 200 => {
+                telegram_url.set(res.json::<TelegramLinkResponse>().await.ok().map(|r| r.url))
+            }
+            Ok(res) => notifications.set_error(res.text().await.unwrap_or_default()),
+            Err(_) => notifications.set_error("Failed to link Telegram"),
+        }
+    });
+
+    let unlink_telegram = create_action(move |_: &()| async move {
+        let response = Client::new()
+            .post(format!(
+                "{}/api{}",
+                window().origin(),
+                RoutesEnum::Api_NotificationTelegramUnlink
+            ))
+            .send()
+            .await;
+        match response {
+            Ok(res) if res.status().as_u16() == 200 => {
+                preferences.update(|p| p.telegram_linked = false);
+                notifications.set_success("Telegram disconnected");
+            }
+            _ => notifications.set_error("Failed to disconnect Telegram"),
+        }
+    });
+
+    let toggle = move |label: &'static str,
+                       get: fn(&NotificationPreferencesResponse) -> bool,
+                       set: fn(&mut NotificationPreferencesResponse, bool)| {
+        view! {
+            <label class="mt-2 flex items-center gap-3 text-off-white">
+                <input
+                    type="checkbox"
+                    prop:checked=move || get(&preferences.get())
+                    on:change=move |ev| {
+                        let checked = event_target_checked(&ev);
+                        preferences.update(|p| set(p, checked));
+                    }
+                />
+                {label}
+            </label>
+        }
+    };
+
+    view! {
+        <div class="flex items-start justify-start gap-4">
+            <Heading>Notifications</Heading>
+        </div>
+        <Subheading class="mt-8">Events</Subheading>
+        <div class="mt-4 flex flex-col">
+            {toggle("Node offline", |p| p.node_offline, |p, v| p.node_offline = v)}
+            {toggle("New referral", |p| p.new_referral, |p, v| p.new_referral = v)}
+            {toggle("Perk earned", |p| p.perk_earned, |p, v| p.perk_earned = v)}
+            {toggle("Daily points summary", |p| p.daily_summary, |p, v| p.daily_summary = v)}
+            <label class="mt-4 flex items-center gap-3 text-off-white">
+                "Alert after the node is offline for"
+                <input
+                    class="w-24 appearance-none rounded border px-3 py-2 text-black shadow"
+                    type="number"
+                    min="5"
+                    max="1440"
+                    prop:value=move || preferences.get().offline_minutes.to_string()
+                    on:change=move |ev| {
+                        let val = event_target_value(&ev).parse().unwrap_or(30);
+                        preferences.update(|p| p.offline_minutes = val);
+                    }
+                />
+                minutes
+            </label>
+        </div>
+        <Subheading class="mt-8">Channels</Subheading>
+        <div class="mt-4 flex flex-col">
+            {toggle("Email", |p| p.email_enabled, |p, v| p.email_enabled = v)}
+        </div>
+        <button
+            class="mt-4 hover:text-orange text-off-white py-2 px-4 border border-orange rounded font-bebas-neue"
+            on:click=move |_| save.dispatch(())
+        >
+            Save
+        </button>
+        <Subheading class="mt-8">Telegram</Subheading>
+        {move || {
+            if preferences.get().telegram_linked {
+                view! {
+                    <div class="mt-4 text-off-white">Notifications are sent to your Telegram chat</div>
+                    <button
+                        class="mt-4 hover:text-orange text-off-white py-2 px-4 border border-orange rounded font-bebas-neue"
+                        on:click=move |_| unlink_telegram.dispatch(())
+                    >
+                        Disconnect Telegram
+                    </button>
+                }
+                    .into_view()
+            } else if let Some(url) = telegram_url.get() {
+                view! {
+                    <div class="mt-4 text-off-white">
+                        "Open the bot and press Start, then reload this page"
+                    </div>
+                    <a class="mt-2 text-cyan break-all" target="_blank" rel="external" href=url.clone()>
+                        {url.clone()}
+                    </a>
+                }
+                    .into_view()
+            } else {
+                view! {
+                    <button
+                        class="mt-4 hover:text-orange text-off-white py-2 px-4 border border-orange rounded font-bebas-neue"
+                        on:click=move |_| link_telegram.dispatch(())
+                    >
+                        Connect Telegram
+                    </button>
+                }
+                    .into_view()
+            }
+        }}
+    }
+}
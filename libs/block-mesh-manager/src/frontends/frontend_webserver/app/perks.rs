@@ -8,7 +8,7 @@ use crate::frontends::components::tables::table_head::TableHead;
 use crate::frontends::components::tables::table_header::TableHeader;
 use crate::frontends::components::wallet_selector::WalletSelector;
 use crate::frontends::context::notification_context::NotificationContext;
-use crate::frontends::utils::auth::connect_wallet_in_browser;
+use crate::frontends::utils::auth::{connect_wallet_in_browser, unlink_wallet_in_browser};
 use block_mesh_common::interfaces::server_api::DashboardResponse;
 use leptos::*;
 
@@ -43,6 +43,13 @@ pub fn Perks() -> impl IntoView {
         }
     });
 
+    let unlink_action = create_action(move |_: &()| async move {
+        if unlink_wallet_in_browser().await {
+            perks.update(|perks| perks.retain(|perk| perk.name != "wallet"));
+            button_enabled.set(true)
+        }
+    });
+
     view! {
         <Modal show=show_wallet_modal show_close_button=true>
             <WalletSelector show=show_wallet_modal wallet_name=wallet_name connect=connect_action/>
@@ -59,6 +66,14 @@ pub fn Perks() -> impl IntoView {
                 }}
 
             </button>
+            <Show when=move || !button_enabled.get()>
+                <button
+                    on:click=move |_| unlink_action.dispatch(())
+                    class="hover:text-orange text-off-white py-2 px-4 border border-orange rounded font-bebas-neue"
+                >
+                    Disconnect Wallet
+                </button>
+            </Show>
             <a
                 rel="external"
                 href="/twitter/login"
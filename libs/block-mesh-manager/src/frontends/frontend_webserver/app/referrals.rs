@@ -15,10 +15,12 @@ use leptos::*;
 pub fn Referrals() -> impl IntoView {
     let async_data = use_context::<DashboardResponse>();
     let referrals = RwSignal::new(vec![]);
+    let referral_points = RwSignal::new(0.0);
     let invite_code = RwSignal::new("".to_string());
     let show_invite_code = RwSignal::new(false);
     if let Some(data) = async_data {
         referrals.set(data.referrals);
+        referral_points.set(data.referral_points);
         invite_code.set(data.invite_code);
     }
 
@@ -136,12 +138,16 @@ pub fn Referrals() -> impl IntoView {
 
         </div>
 
+        <Subheading class="mt-14">
+            Referral Points: {move || format!("{:.2}", referral_points.get())}
+        </Subheading>
         <Subheading class="mt-14">Referrals List</Subheading>
         <Table class="mt-4 [--gutter:theme(spacing.6)] lg:[--gutter:theme(spacing.10)]">
             <TableHead>
                 <tr>
                     <TableHeader>Email</TableHeader>
                     <TableHeader>Joined Date</TableHeader>
+                    <TableHeader>Points Earned</TableHeader>
                     <TableHeader class="text-right">Verified</TableHeader>
                 </tr>
             </TableHead>
@@ -155,6 +161,7 @@ pub fn Referrals() -> impl IntoView {
                             <tr>
                                 <TableCell>{referral.email}</TableCell>
                                 <TableCell>{referral.created_at.to_string()}</TableCell>
+                                <TableCell>{format!("{:.2}", referral.points_earned)}</TableCell>
                                 <TableCell class="text-right">
                                     {referral.verified_email.to_string()}
                                 </TableCell>
@@ -0,0 +1,433 @@
+use crate::frontends::components::heading::Heading;
+use crate::frontends::components::sub_heading::Subheading;
+use crate::frontends::components::tables::table::Table;
+use crate::frontends::components::tables::table_cell::TableCell;
+use crate::frontends::components::tables::table_head::TableHead;
+use crate::frontends::components::tables::table_header::TableHeader;
+use crate::frontends::context::auth_context::AuthContext;
+use crate::frontends::context::notification_context::NotificationContext;
+use block_mesh_common::interfaces::server_api::{
+    ChangeEmailRequest, DeleteAccountRequest, LinkEmailRequest, RecoveryCodesResponse,
+    TwoFactorCodeRequest, TwoFactorEnrollResponse, TwoFactorStatusResponse, UserSessionIdRequest,
+    UserSessionInfo,
+};
+use block_mesh_common::routes_enum::RoutesEnum;
+use block_mesh_common::siws::is_wallet_placeholder_email;
+use leptos::*;
+use reqwest::Client;
+use uuid::Uuid;
+
+#[component]
+pub fn Security() -> impl IntoView {
+    let notifications = expect_context::<NotificationContext>();
+    let status = RwSignal::new(None::<TwoFactorStatusResponse>);
+    let sessions = RwSignal::new(Vec::<UserSessionInfo>::new());
+    let enrollment = RwSignal::new(None::<TwoFactorEnrollResponse>);
+    let recovery_codes = RwSignal::new(Vec::<String>::new());
+    let code = RwSignal::new(String::default());
+    let auth = expect_context::<AuthContext>();
+    let link_email = RwSignal::new(String::default());
+    let link_password = RwSignal::new(String::default());
+    let new_email = RwSignal::new(String::default());
+    let account_password = RwSignal::new(String::default());
+    let delete_confirmation = RwSignal::new(String::default());
+
+    let data_resource = create_local_resource(
+        move || (),
+        move |_| async move {
+            let origin = window().origin();
+            let client = Client::new();
+            if let Ok(res) = client
+                .get(format!("{}/api{}", origin, RoutesEnum::Api_TwoFactor))
+                .send()
+                .await
+            {
+                status.set(res.json::<TwoFactorStatusResponse>().await.ok());
+            }
+            if let Ok(res) = client
+                .get(format!("{}/api{}", origin, RoutesEnum::Api_Sessions))
+                .send()
+                .await
+            {
+                sessions.set(res.json::<Vec<UserSessionInfo>>().await.unwrap_or_default());
+            }
+        },
+    );
+
+    let enroll = create_action(move |_: &()| async move {
+        let origin = window().origin();
+        let response = Client::new()
+            .post(format!("{}/api{}", origin, RoutesEnum::Api_TwoFactorEnroll))
+            .send()
+            .await;
+        match response {
+            Ok(res) if res.status().as_u16() == 200 => {
+                enrollment.set(res.json::<TwoFactorEnrollResponse>().await.ok());
+                recovery_codes.set(vec![]);
+            }
+            _ => notifications.set_error("Failed to start two-factor enrollment"),
+        }
+    });
+
+    // confirm, disable and regenerate all take the code typed in the same input
+    let submit_code = create_action(move |route: &String| {
+        let route = route.clone();
+        async move {
+            let origin = window().origin();
+            let response = Client::new()
+                .post(format!("{}/api{}", origin, route))
+                .json(&TwoFactorCodeRequest {
+                    code: code.get_untracked(),
+                })
+                .send()
+                .await;
+            match response {
+                Ok(res) if res.status().as_u16() == 200 => {
+                    if let Ok(codes) = res.json::<RecoveryCodesResponse>().await {
+                        recovery_codes.set(codes.recovery_codes);
+                    }
+                    enrollment.set(None);
+                    code.set(String::default());
+                    notifications.set_success("Two-factor settings updated");
+                    data_resource.refetch();
+                }
+                _ => notifications.set_error("Invalid code"),
+            }
+        }
+    });
+
+    let revoke = create_action(move |id: &Uuid| {
+        let id = *id;
+        async move {
+            let origin = window().origin();
+            let response = Client::new()
+                .post(format!("{}/api{}", origin, RoutesEnum::Api_SessionsRevoke))
+                .json(&UserSessionIdRequest { id })
+                .send()
+                .await;
+            match response {
+                Ok(res) if res.status().as_u16() == 200 => {
+                    sessions.update(|sessions| sessions.retain(|session| session.id != id));
+                    notifications.set_success("Session revoked");
+                }
+                _ => notifications.set_error("Failed to revoke session"),
+            }
+        }
+    });
+
+    let link = create_action(move |_: &()| async move {
+        let origin = window().origin();
+        let email = link_email.get_untracked();
+        let response = Client::new()
+            .post(format!("{}/api{}", origin, RoutesEnum::Api_LinkEmail))
+            .json(&LinkEmailRequest {
+                email: email.clone(),
+                password: link_password.get_untracked(),
+            })
+            .send()
+            .await;
+        match response {
+            Ok(res) if res.status().as_u16() == 200 => {
+                auth.email.set(email);
+                notifications.set_success("Email linked, please confirm it from your inbox");
+            }
+            Ok(res) => notifications.set_error(res.text().await.unwrap_or_default()),
+            Err(_) => notifications.set_error("Failed to link email"),
+        }
+    });
+
+    let change_email = create_action(move |_: &()| async move {
+        let origin = window().origin();
+        let response = Client::new()
+            .post(format!("{}/api{}", origin, RoutesEnum::Api_ChangeEmail))
+            .json(&ChangeEmailRequest {
+                new_email: new_email.get_untracked(),
+                password: account_password.get_untracked(),
+            })
+            .send()
+            .await;
+        match response {
+            Ok(res) if res.status().as_u16() == 200 => {
+                notifications.set_success("Please confirm the change from your new inbox")
+            }
+            Ok(res) => notifications.set_error(res.text().await.unwrap_or_default()),
+            Err(_) => notifications.set_error("Failed to change email"),
+        }
+    });
+
+    let delete_account = create_action(move |_: &()| async move {
+        let origin = window().origin();
+        let response = Client::new()
+            .post(format!("{}/api{}", origin, RoutesEnum::Api_DeleteAccount))
+            .json(&DeleteAccountRequest {
+                password: account_password.get_untracked(),
+                confirmation: delete_confirmation.get_untracked(),
+            })
+            .send()
+            .await;
+        match response {
+            Ok(res) if res.status().as_u16() == 200 => {
+                let _ = window()
+                    .location()
+                    .set_href(&RoutesEnum::Static_UnAuth_Login.to_string());
+            }
+            Ok(res) => notifications.set_error(res.text().await.unwrap_or_default()),
+            Err(_) => notifications.set_error("Failed to delete account"),
+        }
+    });
+
+    let code_input = move || {
+        view! {
+            <input
+                class="appearance-none rounded border px-3 py-2 text-black shadow"
+                type="text"
+                placeholder="Code"
+                prop:value=move || code.get()
+                on:change=move |ev| {
+                    let val = event_target_value(&ev);
+                    code.update(|v| *v = val);
+                }
+            />
+        }
+    };
+
+    view! {
+        <div class="flex items-start justify-start gap-4">
+            <Heading>Security</Heading>
+        </div>
+        {move || {
+            is_wallet_placeholder_email(&auth.email.get())
+                .then(|| {
+                    view! {
+                        <Subheading class="mt-8">Link Email</Subheading>
+                        <form class="mt-4 flex gap-4" on:submit=|ev| ev.prevent_default()>
+                            <input
+                                class="appearance-none rounded border px-3 py-2 text-black shadow"
+                                type="text"
+                                placeholder="Email"
+                                on:change=move |ev| {
+                                    let val = event_target_value(&ev);
+                                    link_email.update(|v| *v = val);
+                                }
+                            />
+                            <input
+                                class="appearance-none rounded border px-3 py-2 text-black shadow"
+                                type="password"
+                                placeholder="Password"
+                                on:change=move |ev| {
+                                    let val = event_target_value(&ev);
+                                    link_password.update(|v| *v = val);
+                                }
+                            />
+                            <button
+                                class="hover:text-orange text-off-white py-2 px-4 border border-orange rounded font-bebas-neue"
+                                type="submit"
+                                on:click=move |_| link.dispatch(())
+                            >
+                                Link Email
+                            </button>
+                        </form>
+                    }
+                })
+        }}
+        <Subheading class="mt-8">Two-Factor Authentication</Subheading>
+        {move || {
+            let enabled = status.get().map(|s| s.enabled).unwrap_or(false);
+            if enabled {
+                let left = status.get().map(|s| s.recovery_codes_left).unwrap_or_default();
+                view! {
+                    <div class="mt-4 text-off-white">
+                        {format!("Enabled, {} recovery codes left", left)}
+                    </div>
+                    <form class="mt-4 flex gap-4" on:submit=|ev| ev.prevent_default()>
+                        {code_input}
+                        <button
+                            class="hover:text-orange text-off-white py-2 px-4 border border-orange rounded font-bebas-neue"
+                            type="submit"
+                            on:click=move |_| {
+                                submit_code.dispatch(RoutesEnum::Api_TwoFactorRecoveryCodes.to_string())
+                            }
+                        >
+                            New Recovery Codes
+                        </button>
+                        <button
+                            class="hover:text-orange text-off-white py-2 px-4 border border-orange rounded font-bebas-neue"
+                            type="submit"
+                            on:click=move |_| submit_code.dispatch(RoutesEnum::Api_TwoFactorDisable.to_string())
+                        >
+                            Disable
+                        </button>
+                    </form>
+                }
+                    .into_view()
+            } else if let Some(pending) = enrollment.get() {
+                view! {
+                    <div class="mt-4 text-off-white break-all">
+                        {format!("Add this key to your authenticator app: {}", pending.secret)}
+                    </div>
+                    <a class="mt-2 text-cyan break-all" href=pending.otpauth_uri.clone()>
+                        {pending.otpauth_uri.clone()}
+                    </a>
+                    <form class="mt-4 flex gap-4" on:submit=|ev| ev.prevent_default()>
+                        {code_input}
+                        <button
+                            class="hover:text-orange text-off-white py-2 px-4 border border-orange rounded font-bebas-neue"
+                            type="submit"
+                            on:click=move |_| submit_code.dispatch(RoutesEnum::Api_TwoFactorConfirm.to_string())
+                        >
+                            Confirm
+                        </button>
+                    </form>
+                }
+                    .into_view()
+            } else {
+                view! {
+                    <button
+                        class="mt-4 hover:text-orange text-off-white py-2 px-4 border border-orange rounded font-bebas-neue"
+                        on:click=move |_| enroll.dispatch(())
+                    >
+                        Enable Two-Factor
+                    </button>
+                }
+                    .into_view()
+            }
+        }}
+        {move || {
+            let codes = recovery_codes.get();
+            (!codes.is_empty())
+                .then(|| {
+                    view! {
+                        <Subheading class="mt-8">
+                            "Recovery codes, store them now as they will not be shown again"
+                        </Subheading>
+                        <ul class="mt-2 font-mono text-off-white">
+                            {codes.into_iter().map(|c| view! { <li>{c}</li> }).collect_view()}
+                        </ul>
+                    }
+                })
+        }}
+        <Subheading class="mt-14">Sessions and Devices</Subheading>
+        <Table class="mt-4 [--gutter:theme(spacing.6)] lg:[--gutter:theme(spacing.10)]">
+            <TableHead>
+                <tr>
+                    <TableHeader>Device</TableHeader>
+                    <TableHeader>IP</TableHeader>
+                    <TableHeader>Signed In</TableHeader>
+                    <TableHeader>Last Seen</TableHeader>
+                    <TableHeader class="text-right">Actions</TableHeader>
+                </tr>
+            </TableHead>
+            <tbody>
+                {move || {
+                    sessions
+                        .get()
+                        .iter()
+                        .cloned()
+                        .map(|session| {
+                            let id = session.id;
+                            view! {
+                                <tr>
+                                    <TableCell>{session.user_agent.unwrap_or_default()}</TableCell>
+                                    <TableCell>{session.ip.unwrap_or_default()}</TableCell>
+                                    <TableCell>{session.created_at.to_string()}</TableCell>
+                                    <TableCell>{session.last_seen_at.to_string()}</TableCell>
+                                    <TableCell class="text-right">
+                                        {if session.current {
+                                            view! { <span class="text-off-white px-2">Current</span> }
+                                                .into_view()
+                                        } else {
+                                            view! {
+                                                <button
+                                                    class="hover:text-orange text-off-white px-2"
+                                                    on:click=move |_| revoke.dispatch(id)
+                                                >
+                                                    Revoke
+                                                </button>
+                                            }
+                                                .into_view()
+                                        }}
+                                    </TableCell>
+                                </tr>
+                            }
+                        })
+                        .collect_view()
+                }}
+
+            </tbody>
+        </Table>
+        <Subheading class="mt-14">Account</Subheading>
+        <form class="mt-4 flex gap-4" on:submit=|ev| ev.prevent_default()>
+            <input
+                class="appearance-none rounded border px-3 py-2 text-black shadow"
+                type="password"
+                placeholder="Current Password"
+                on:change=move |ev| {
+                    let val = event_target_value(&ev);
+                    account_password.update(|v| *v = val);
+                }
+            />
+            <input
+                class="appearance-none rounded border px-3 py-2 text-black shadow"
+                type="text"
+                placeholder="New Email"
+                on:change=move |ev| {
+                    let val = event_target_value(&ev);
+                    new_email.update(|v| *v = val);
+                }
+            />
+            <button
+                class="hover:text-orange text-off-white py-2 px-4 border border-orange rounded font-bebas-neue"
+                type="submit"
+                on:click=move |_| change_email.dispatch(())
+            >
+                Change Email
+            </button>
+        </form>
+        <div class="mt-4 flex gap-4 text-off-white">
+            <span>Export your data:</span>
+            <a
+                rel="external"
+                class="text-cyan"
+                href=format!("/api{}?format=json", RoutesEnum::Api_ExportAccount)
+            >
+                JSON
+            </a>
+            {["daily_stats", "referrals", "perks", "ip_history"]
+                .into_iter()
+                .map(|dataset| {
+                    view! {
+                        <a
+                            rel="external"
+                            class="text-cyan"
+                            href=format!(
+                                "/api{}?format=csv&dataset={}",
+                                RoutesEnum::Api_ExportAccount,
+                                dataset,
+                            )
+                        >
+                            {format!("{} CSV", dataset)}
+                        </a>
+                    }
+                })
+                .collect_view()}
+        </div>
+        <form class="mt-4 flex gap-4" on:submit=|ev| ev.prevent_default()>
+            <input
+                class="appearance-none rounded border px-3 py-2 text-black shadow"
+                type="text"
+                placeholder="Type DELETE"
+                on:change=move |ev| {
+                    let val = event_target_value(&ev);
+                    delete_confirmation.update(|v| *v = val);
+                }
+            />
+            <button
+                class="hover:text-orange text-off-white py-2 px-4 border border-orange rounded font-bebas-neue"
+                type="submit"
+                on:click=move |_| delete_account.dispatch(())
+            >
+                Delete Account
+            </button>
+        </form>
+    }
+}
@@ -0,0 +1,90 @@
+use crate::frontends::components::modal::Modal;
+use crate::frontends::components::wallet_selector::WalletSelector;
+use crate::frontends::context::notification_context::NotificationContext;
+use crate::frontends::utils::auth::sign_in_with_wallet_in_browser;
+use block_mesh_common::routes_enum::RoutesEnum;
+use leptos::*;
+
+#[component]
+pub fn WalletLogin() -> impl IntoView {
+    let notifications = expect_context::<NotificationContext>();
+    let show_wallet_modal = RwSignal::new(false);
+    let wallet_name = RwSignal::new("".to_string());
+    let invite_code = RwSignal::new(String::default());
+
+    let sign_in_action = create_action(move |wallet: &String| {
+        let wallet = wallet.clone();
+        async move {
+            let invite_code = Some(invite_code.get_untracked())
+                .map(|code| code.trim().to_string())
+                .filter(|code| !code.is_empty());
+            match sign_in_with_wallet_in_browser(wallet, invite_code).await {
+                Ok(response) => {
+                    // full page loads, both targets are server rendered or need the new cookie
+                    let target = if response.two_factor_required {
+                        RoutesEnum::Static_UnAuth_TwoFactor.to_string()
+                    } else {
+                        "/ui/dashboard".to_string()
+                    };
+                    let _ = window().location().set_href(&target);
+                }
+                Err(e) => {
+                    let message = e.to_string();
+                    if message.contains("Invite Code") {
+                        notifications.set_error(
+                            "New wallet, please add an invite code to create an account",
+                        );
+                    } else {
+                        notifications.set_error("Wallet sign in failed");
+                    }
+                }
+            }
+        }
+    });
+
+    view! {
+        <Modal show=show_wallet_modal show_close_button=true>
+            <WalletSelector show=show_wallet_modal wallet_name=wallet_name connect=sign_in_action/>
+        </Modal>
+        <div class="bg-dark-blue flex justify-center items-center h-screen">
+            <div class="bg-dark-blue border-cyan border-solid border-2 p-8 rounded-lg shadow-md w-80">
+                <h2 class="text-white text-2xl font-semibold text-center mb-6">
+                    Sign in with Wallet
+                </h2>
+                <div class="mb-4">
+                    <label
+                        class="font-bebas-neue block text-off-white text-sm font-bold mb-2"
+                        for="invite_code"
+                    >
+                        Invite Code (new accounts only)
+                    </label>
+                    <input
+                        class="shadow appearance-none border rounded w-full py-2 px-3 text-black leading-tight focus:outline-none focus:shadow-outline"
+                        type="text"
+                        id="invite_code"
+                        placeholder="Invite Code"
+                        on:change=move |ev| {
+                            let val = event_target_value(&ev);
+                            invite_code.update(|v| *v = val);
+                        }
+                    />
+                </div>
+                <div class="flex items-center justify-between">
+                    <button
+                        class="hover:text-orange text-off-white py-2 px-4 border border-orange rounded font-bebas-neue focus:outline-none focus:shadow-outline"
+                        on:click=move |_| show_wallet_modal.set(true)
+                    >
+                        Select Wallet
+                    </button>
+                    <a
+                        rel="external"
+                        class="font-open-sans mb-2 inline-block align-baseline font-bold text-xs text-cyan hover:text-cyan"
+                        href="/login"
+                    >
+                        Login with Email
+                    </a>
+                </div>
+            </div>
+        </div>
+    }
+}
@@ -8,12 +8,13 @@ use leptos_dom::tracing;
 
 use block_mesh_common::interfaces::server_api::{
     ConnectWalletRequest, ConnectWalletResponse, GetTokenResponse, LoginForm, RegisterForm,
-    RegisterResponse,
+    RegisterResponse, SiwsLoginRequest, SiwsLoginResponse, SiwsNonceResponse,
+    WalletChallengeRequest, WalletChallengeResponse,
 };
 use block_mesh_common::routes_enum::RoutesEnum;
+use block_mesh_common::siws::SiwsMessage;
 use js_sys::Uint8Array;
 use leptos::*;
-use uuid::Uuid;
 
 pub async fn register(blockmesh_url: &str, credentials: &RegisterForm) -> anyhow::Result<()> {
     let url = format!("{}/register_api", blockmesh_url);
@@ -68,28 +69,34 @@ pub async fn connect_wallet(
     Ok(response)
 }
 
+/// Links the wallet to the logged in account by signing a one time challenge issued by the server.
 pub async fn connect_wallet_in_browser(wallet: String) -> bool {
     if wallet.is_empty() {
         return false;
     }
-    let msg = Uuid::new_v4().to_string();
-    let key = pubkey(&wallet).await;
-    let sign = sign_message(&msg, &wallet).await;
+    let notifications = expect_context::<NotificationContext>();
+    let origin = window().origin();
+    let Some(pubkey) = pubkey(&wallet).await.as_string() else {
+        notifications.set_error("Wallet did not connect");
+        return false;
+    };
+    let challenge = match wallet_challenge(&origin, &pubkey).await {
+        Ok(challenge) => challenge,
+        Err(e) => {
+            notifications.set_error(format!("Failed to connect - {}", e));
+            return false;
+        }
+    };
+    let sign = sign_message(&challenge.message, &wallet).await;
     let uint8_array = Uint8Array::new(&sign);
     let mut signature = vec![0; uint8_array.length() as usize];
     uint8_array.copy_to(&mut signature[..]);
 
-    let origin = window().origin();
-
-    let pubkey = key.as_string().unwrap();
-
-    let notifications = expect_context::<NotificationContext>();
-
     match connect_wallet(
         origin,
         ConnectWalletRequest {
             pubkey: pubkey.clone(),
-            message: msg.to_string(),
+            message: challenge.message,
             signature,
         },
     )
@@ -107,3 +114,84 @@ pub async fn connect_wallet_in_browser(wallet: String) -> bool {
         }
     }
 }
+
+async fn wallet_challenge(origin: &str, pubkey: &str) -> anyhow::Result<WalletChallengeResponse> {
+    let response = reqwest::Client::new()
+        .post(format!("{}/api{}", origin, RoutesEnum::Api_WalletChallenge))
+        .json(&WalletChallengeRequest {
+            pubkey: pubkey.to_string(),
+        })
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(anyhow!(response.text().await.unwrap_or_default()));
+    }
+    Ok(response.json().await?)
+}
+
+pub async fn unlink_wallet_in_browser() -> bool {
+    let notifications = expect_context::<NotificationContext>();
+    let response = reqwest::Client::new()
+        .post(format!(
+            "{}/api{}",
+            window().origin(),
+            RoutesEnum::Api_WalletUnlink
+        ))
+        .send()
+        .await;
+    match response {
+        Ok(res) if res.status().is_success() => {
+            let auth = expect_context::<AuthContext>();
+            auth.wallet_address.set(None);
+            notifications.set_success("Wallet disconnected");
+            true
+        }
+        _ => {
+            notifications.set_error("Failed to disconnect wallet");
+            false
+        }
+    }
+}
+
+/// Sign In With Solana: asks the server for a nonce, has the wallet sign the standard message
+/// and logs in, creating the account when `invite_code` is given and the wallet is new.
+pub async fn sign_in_with_wallet_in_browser(
+    wallet: String,
+    invite_code: Option<String>,
+) -> anyhow::Result<SiwsLoginResponse> {
+    if wallet.is_empty() {
+        return Err(anyhow!("No wallet selected"));
+    }
+    let origin = window().origin();
+    let client = reqwest::Client::new();
+    let nonce: SiwsNonceResponse = client
+        .get(format!("{}/api{}", origin, RoutesEnum::Api_SiwsNonce))
+        .send()
+        .await?
+        .json()
+        .await?;
+    let pubkey = pubkey(&wallet)
+        .await
+        .as_string()
+        .filter(|key| !key.is_empty())
+        .ok_or_else(|| anyhow!("Wallet did not connect"))?;
+    let message = SiwsMessage::new(&origin, &pubkey, &nonce.nonce).to_string();
+    let sign = sign_message(&message, &wallet).await;
+    let uint8_array = Uint8Array::new(&sign);
+    let mut signature = vec![0; uint8_array.length() as usize];
+    uint8_array.copy_to(&mut signature[..]);
+    let response = client
+        .post(format!("{}/api{}", origin, RoutesEnum::Api_SiwsLogin))
+        .json(&SiwsLoginRequest {
+            pubkey,
+            message,
+            signature,
+            invite_code,
+        })
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(anyhow!(response.text().await.unwrap_or_default()));
+    }
+    Ok(response.json().await?)
+}
@@ -9,7 +9,7 @@ cfg_if! { if #[cfg(feature = "ssr")] {
     use block_mesh_manager::utils::cache_envar::get_envar;
     use database_utils::utils::migrate::migrate;
     use std::process;
-    use dashmap::DashMap;
+    use block_mesh_common::credential_cache::CredentialCache;
     use block_mesh_common::interfaces::server_api::{CheckTokenResponseMap, GetTokenResponseMap};
     use std::mem;
     use logger_general::tracing::setup_tracing_stdout_only_with_sentry;
@@ -79,7 +79,6 @@ async fn run() -> anyhow::Result<()> {
     setup_tracing_stdout_only_with_sentry();
     let configuration = get_configuration().expect("Failed to read configuration");
     tracing::info!("Starting with configuration {:#?}", configuration);
-    let _gmail_password = get_env_var_or_panic(AppEnvVar::GmailAppPassword);
     let database_url = get_env_var_or_panic(AppEnvVar::DatabaseUrl);
     let database_url = <EnvVar as AsRef<Secret<String>>>::as_ref(&database_url);
     let mailgun_token = get_env_var_or_panic(AppEnvVar::MailgunSendKey);
@@ -91,7 +90,10 @@ async fn run() -> anyhow::Result<()> {
         .await
         .expect("Failed to migrate database");
     tracing::info!("Database migration complete");
-    let email_client = Arc::new(EmailClient::new(configuration.application.base_url.clone()).await);
+    let email_client = Arc::new(EmailClient::new(
+        configuration.application.base_url.clone(),
+        db_pool.clone(),
+    ));
     let client = http_client();
     tracing::info!("Starting to get feature flags");
     let flags = Arc::new(get_all_flags(&client).await?);
@@ -110,8 +112,8 @@ async fn run() -> anyhow::Result<()> {
 
     let _ = create_test_user(&db_pool).await;
 
-    let check_token_map: CheckTokenResponseMap = Arc::new(DashMap::new());
-    let get_token_map: GetTokenResponseMap = Arc::new(DashMap::new());
+    let check_token_map: CheckTokenResponseMap = Arc::new(CredentialCache::default());
+    let get_token_map: GetTokenResponseMap = Arc::new(CredentialCache::default());
 
     let app_state = Arc::new(AppState {
         check_token_map,
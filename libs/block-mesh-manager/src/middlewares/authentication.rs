@@ -13,6 +13,7 @@ use axum_login::{
     AuthManagerLayer, AuthManagerLayerBuilder, AuthUser, AuthnBackend, UserId,
 };
 use block_mesh_manager_database_domain::domain::get_user_opt_by_id::get_user_opt_by_id;
+use chrono::{DateTime, Utc};
 use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
 use redis::aio::MultiplexedConnection;
 use redis::{AsyncCommands, RedisResult};
@@ -64,6 +65,34 @@ pub struct SessionUser {
     pub nonce: String,
 }
 
+/// Session key of a password-verified login that still has to pass the TOTP step.
+pub const PENDING_TWO_FACTOR_KEY: &str = "pending_two_factor";
+/// Sessions expire after this long without activity.
+pub const SESSION_INACTIVITY_DAYS: i64 = 2;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingTwoFactor {
+    pub user: SessionUser,
+    pub expires_at: DateTime<Utc>,
+    pub attempts: u32,
+}
+
+impl PendingTwoFactor {
+    pub const MAX_ATTEMPTS: u32 = 5;
+
+    pub fn new(user: SessionUser) -> Self {
+        Self {
+            user,
+            expires_at: Utc::now() + chrono::Duration::minutes(5),
+            attempts: 0,
+        }
+    }
+
+    pub fn is_usable(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at > now && self.attempts < Self::MAX_ATTEMPTS
+    }
+}
+
 #[async_trait]
 impl AuthnBackend for Backend {
     type User = SessionUser;
@@ -179,7 +208,9 @@ pub async fn authentication_layer(
 
     let session_layer = SessionManagerLayer::new(session_store)
         .with_secure(false)
-        .with_expiry(Expiry::OnInactivity(Duration::days(2)));
+        .with_expiry(Expiry::OnInactivity(Duration::days(
+            SESSION_INACTIVITY_DAYS,
+        )));
 
     let backend = Backend::new(pool.clone(), con.clone());
     AuthManagerLayerBuilder::new(backend, session_layer).build()
@@ -1,3 +1,4 @@
 pub mod authentication;
 pub mod rate_limit;
+pub mod user_session;
 // pub mod request_id;
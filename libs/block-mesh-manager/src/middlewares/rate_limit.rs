@@ -1,10 +1,13 @@
 use crate::middlewares::authentication::Backend;
 use crate::utils::cache_envar::get_envar;
+use axum_login::AuthSession;
+use block_mesh_common::rate_limit::{default_rules, RateLimitLayer, RateLimiter};
 use chrono::{DateTime, Duration, Utc};
 use redis::aio::MultiplexedConnection;
 use redis::{AsyncCommands, RedisResult};
 use serde::{Deserialize, Serialize};
 use std::cmp::max;
+use std::sync::Arc;
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -82,3 +85,14 @@ pub async fn filter_request(
     touch_redis_value(con, user_id, ip).await;
     Ok(max(by_user.update_at, by_ip.update_at) < diff)
 }
+
+/// Must sit inside the auth layer, which puts the session in the request extensions.
+pub fn rate_limit_layer(redis: MultiplexedConnection) -> RateLimitLayer {
+    RateLimitLayer::new(RateLimiter::new(redis, default_rules())).with_user_resolver(Arc::new(
+        |extensions| {
+            extensions
+                .get::<AuthSession<Backend>>()
+                .and_then(|auth| auth.user.as_ref().map(|user| user.id))
+        },
+    ))
+}
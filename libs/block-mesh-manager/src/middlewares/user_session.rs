@@ -0,0 +1,142 @@
+use crate::database::user_session::create_user_session::create_user_session;
+use crate::database::user_session::get_user_session::get_user_session;
+use crate::database::user_session::touch_user_session::touch_user_session;
+use crate::middlewares::authentication::{del_from_redis, Backend};
+use crate::startup::application::AppState;
+use crate::utils::cache_envar::get_envar;
+use axum::extract::{Request, State};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Redirect, Response};
+use axum::Extension;
+use axum_login::AuthSession;
+use block_mesh_common::routes_enum::RoutesEnum;
+use chrono::{Duration, Utc};
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+use http::{HeaderMap, StatusCode};
+use redis::aio::MultiplexedConnection;
+use redis::{AsyncCommands, RedisResult};
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Session key holding the `user_sessions` row of the current login.
+pub const USER_SESSION_KEY: &str = "user_session_id";
+
+fn header_value(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.chars().take(512).collect())
+}
+
+/// Marks a live `user_sessions` row in Redis so most requests skip the database. The key sits
+/// under the user id, `forget_cached_user` drops it along with the rest of the user's cache.
+fn user_session_cache_key(user_id: &Uuid, id: &Uuid) -> String {
+    format!("{}-user-session-{}", user_id, id)
+}
+
+/// Call after revoking a session so the next request goes back to the database.
+pub async fn forget_user_session(redis: &MultiplexedConnection, user_id: &Uuid, id: &Uuid) {
+    let mut redis = redis.clone();
+    del_from_redis(&user_session_cache_key(user_id, id), &mut redis).await;
+}
+
+/// Returns false when the session was revoked from the sessions page.
+#[tracing::instrument(name = "check_user_session", skip_all)]
+async fn check_user_session(
+    pool: &PgPool,
+    redis: &MultiplexedConnection,
+    auth: &AuthSession<Backend>,
+    user_id: &Uuid,
+    headers: &HeaderMap,
+) -> anyhow::Result<bool> {
+    let session_id = auth.session.id().map(|id| id.to_string());
+    let ip = header_value(headers, "cf-connecting-ip");
+    let current: Option<Uuid> = auth.session.get(USER_SESSION_KEY).await?;
+    let mut redis = redis.clone();
+    if let Some(id) = current {
+        // the cached value is the tower session id the row was last touched with
+        let cached: RedisResult<Option<String>> =
+            redis.get(user_session_cache_key(user_id, &id)).await;
+        if let Ok(Some(cached)) = cached {
+            if session_id.is_none() || session_id.as_ref() == Some(&cached) {
+                return Ok(true);
+            }
+        }
+    }
+    let mut transaction = create_txn(pool).await?;
+    match current {
+        // logins from before session tracking, or the first request after login
+        None => {
+            let id = create_user_session(
+                &mut transaction,
+                user_id,
+                session_id,
+                ip,
+                header_value(headers, "user-agent"),
+            )
+            .await?;
+            auth.session.insert(USER_SESSION_KEY, id).await?;
+        }
+        Some(id) => {
+            let Some(user_session) = get_user_session(&mut transaction, &id).await? else {
+                commit_txn(transaction).await?;
+                return Ok(false);
+            };
+            if user_session.revoked_at.is_some() || user_session.user_id != *user_id {
+                commit_txn(transaction).await?;
+                return Ok(false);
+            }
+            let interval = Duration::seconds(
+                get_envar("USER_SESSION_TOUCH_INTERVAL")
+                    .await
+                    .parse()
+                    .unwrap_or(300),
+            );
+            if (session_id.is_some() && user_session.session_id != session_id)
+                || Utc::now() - user_session.last_seen_at > interval
+            {
+                touch_user_session(&mut transaction, &id, session_id.clone(), ip).await?;
+            }
+            let ttl: u64 = get_envar("USER_SESSION_CACHE_TTL")
+                .await
+                .parse()
+                .unwrap_or(60);
+            let cached = session_id.or(user_session.session_id).unwrap_or_default();
+            let _: RedisResult<()> = redis
+                .set_ex(user_session_cache_key(user_id, &id), cached, ttl)
+                .await;
+        }
+    }
+    commit_txn(transaction).await?;
+    Ok(true)
+}
+
+#[tracing::instrument(name = "track_user_session", skip_all)]
+pub async fn track_user_session(
+    State(state): State<Arc<AppState>>,
+    Extension(mut auth): Extension<AuthSession<Backend>>,
+    headers: HeaderMap,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(user) = auth.user.clone() else {
+        return next.run(request).await;
+    };
+    match check_user_session(&state.pool, &state.redis, &auth, &user.id, &headers).await {
+        Ok(true) => next.run(request).await,
+        Ok(false) => {
+            let _ = auth.logout().await;
+            if request.uri().path().starts_with("/api") {
+                (StatusCode::UNAUTHORIZED, "Session Revoked").into_response()
+            } else {
+                Redirect::to(RoutesEnum::Static_UnAuth_Login.to_string().as_str()).into_response()
+            }
+        }
+        // tracking is best effort, a database hiccup must not log everyone out
+        Err(e) => {
+            tracing::error!("Failed to check user session: {:?}", e);
+            next.run(request).await
+        }
+    }
+}
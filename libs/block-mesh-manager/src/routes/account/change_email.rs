@@ -0,0 +1,81 @@
+use crate::database::account::create_email_change_request::create_email_change_request;
+use crate::database::user::get_user_by_email::get_user_opt_by_email;
+use crate::errors::error::Error;
+use crate::middlewares::authentication::Backend;
+use crate::startup::application::AppState;
+use crate::utils::email_locale::email_locale;
+use crate::utils::verify_cache::verify_with_cache;
+use axum::extract::State;
+use axum::http::HeaderMap;
+use axum::response::IntoResponse;
+use axum::{Extension, Json};
+use axum_login::AuthSession;
+use block_mesh_common::interfaces::server_api::ChangeEmailRequest;
+use block_mesh_common::siws::is_wallet_placeholder_email;
+use block_mesh_manager_database_domain::domain::get_user_opt_by_id::get_user_opt_by_id;
+use block_mesh_manager_database_domain::domain::nonce::Nonce;
+use chrono::{Duration, Utc};
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+use http::StatusCode;
+use std::sync::Arc;
+use validator::validate_email;
+
+/// How long the link sent to the new address stays valid.
+const EMAIL_CHANGE_TTL_HOURS: i64 = 24;
+
+/// Starts an email change, the current email keeps working until the new one is confirmed.
+#[tracing::instrument(name = "change_email", skip_all)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthSession<Backend>>,
+    headers: HeaderMap,
+    Json(body): Json<ChangeEmailRequest>,
+) -> Result<impl IntoResponse, Error> {
+    let user = auth.user.ok_or(Error::UserNotFound)?;
+    if is_wallet_placeholder_email(&user.email) {
+        return Err(Error::InvalidAccountRequest(
+            "Please link an email to your account first".to_string(),
+        ));
+    }
+    let new_email = body.new_email.trim().to_ascii_lowercase();
+    if !validate_email(new_email.clone()) || is_wallet_placeholder_email(&new_email) {
+        return Err(Error::InvalidAccountRequest(
+            "Please check if email you inserted is correct".to_string(),
+        ));
+    }
+    if new_email == user.email.to_ascii_lowercase() {
+        return Err(Error::InvalidAccountRequest(
+            "This is already your email".to_string(),
+        ));
+    }
+    let mut transaction = create_txn(&state.pool).await?;
+    let db_user = get_user_opt_by_id(&mut transaction, &user.id)
+        .await?
+        .ok_or(Error::UserNotFound)?;
+    if !verify_with_cache(&body.password, db_user.password.as_ref()).await {
+        return Err(Error::PasswordMismatch);
+    }
+    if get_user_opt_by_email(&mut transaction, &new_email)
+        .await?
+        .is_some()
+    {
+        return Err(Error::InvalidAccountRequest(
+            "Email is already in use".to_string(),
+        ));
+    }
+    let token = Nonce::generate_nonce(48);
+    create_email_change_request(
+        &mut transaction,
+        &user.id,
+        &new_email,
+        &token,
+        Utc::now() + Duration::hours(EMAIL_CHANGE_TTL_HOURS),
+    )
+    .await?;
+    commit_txn(transaction).await?;
+    let _ = state
+        .email_client
+        .send_email_change_email(&new_email, &token, email_locale(&headers))
+        .await;
+    Ok(StatusCode::OK)
+}
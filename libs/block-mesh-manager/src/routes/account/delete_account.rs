@@ -0,0 +1,51 @@
+use crate::database::account::anonymize_user::anonymize_user;
+use crate::errors::error::Error;
+use crate::middlewares::authentication::Backend;
+use crate::startup::application::AppState;
+use crate::utils::forget_user::forget_cached_user;
+use crate::utils::verify_cache::verify_with_cache;
+use axum::extract::State;
+use axum::response::IntoResponse;
+use axum::{Extension, Json};
+use axum_login::AuthSession;
+use bcrypt::{hash, DEFAULT_COST};
+use block_mesh_common::interfaces::server_api::{
+    DeleteAccountRequest, DELETE_ACCOUNT_CONFIRMATION,
+};
+use block_mesh_common::siws::is_wallet_placeholder_email;
+use block_mesh_manager_database_domain::domain::get_user_opt_by_id::get_user_opt_by_id;
+use block_mesh_manager_database_domain::domain::nonce::Nonce;
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+use http::StatusCode;
+use std::sync::Arc;
+
+#[tracing::instrument(name = "delete_account", skip_all)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Extension(mut auth): Extension<AuthSession<Backend>>,
+    Json(body): Json<DeleteAccountRequest>,
+) -> Result<impl IntoResponse, Error> {
+    let user = auth.user.clone().ok_or(Error::UserNotFound)?;
+    if body.confirmation != DELETE_ACCOUNT_CONFIRMATION {
+        return Err(Error::InvalidAccountRequest(format!(
+            "Please type {} to confirm",
+            DELETE_ACCOUNT_CONFIRMATION
+        )));
+    }
+    let mut transaction = create_txn(&state.pool).await?;
+    let db_user = get_user_opt_by_id(&mut transaction, &user.id)
+        .await?
+        .ok_or(Error::UserNotFound)?;
+    // wallet-first accounts never had a password they know
+    if !is_wallet_placeholder_email(&db_user.email)
+        && !verify_with_cache(&body.password, db_user.password.as_ref()).await
+    {
+        return Err(Error::PasswordMismatch);
+    }
+    let unusable_password = hash(Nonce::generate_nonce(32), DEFAULT_COST)?;
+    anonymize_user(&mut transaction, &user.id, &unusable_password).await?;
+    commit_txn(transaction).await?;
+    forget_cached_user(&state, &user.id, &db_user.email).await;
+    let _ = auth.logout().await;
+    Ok(StatusCode::OK)
+}
@@ -0,0 +1,55 @@
+use crate::database::account::change_user_email::change_user_email;
+use crate::database::account::confirm_email_change_request::confirm_email_change_request;
+use crate::database::user::get_user_by_email::get_user_opt_by_email;
+use crate::errors::error::Error;
+use crate::notification::notification_redirect::NotificationRedirect;
+use crate::startup::application::AppState;
+use crate::utils::forget_user::forget_cached_user;
+use axum::extract::{Query, State};
+use axum::response::Redirect;
+use block_mesh_common::interfaces::server_api::ConfirmEmailRequest;
+use block_mesh_common::routes_enum::RoutesEnum;
+use block_mesh_manager_database_domain::domain::get_user_opt_by_id::get_user_opt_by_id;
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+use std::sync::Arc;
+
+#[tracing::instrument(name = "email_change_confirm", skip_all)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ConfirmEmailRequest>,
+) -> Result<Redirect, Error> {
+    let mut transaction = create_txn(&state.pool).await?;
+    let Some((user_id, new_email)) =
+        confirm_email_change_request(&mut transaction, &query.token).await?
+    else {
+        return Ok(Error::redirect(
+            400,
+            "Invalid link",
+            "The link expired or was already used, please request the change again",
+            RoutesEnum::Static_UnAuth_Root.to_string().as_str(),
+        ));
+    };
+    // someone may have registered the address after the request was made
+    if get_user_opt_by_email(&mut transaction, &new_email)
+        .await?
+        .is_some()
+    {
+        return Ok(Error::redirect(
+            400,
+            "Email in use",
+            "This email belongs to another account",
+            RoutesEnum::Static_UnAuth_Root.to_string().as_str(),
+        ));
+    }
+    let user = get_user_opt_by_id(&mut transaction, &user_id)
+        .await?
+        .ok_or(Error::UserNotFound)?;
+    change_user_email(&mut transaction, &user_id, &new_email).await?;
+    commit_txn(transaction).await?;
+    forget_cached_user(&state, &user_id, &user.email).await;
+    Ok(NotificationRedirect::redirect(
+        "Email changed",
+        "Please use your new email to login",
+        RoutesEnum::Static_UnAuth_Login.to_string().as_str(),
+    ))
+}
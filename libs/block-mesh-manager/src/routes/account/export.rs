@@ -0,0 +1,249 @@
+use crate::database::daily_stat::get_daily_stats_by_user_id::get_daily_stats_by_user_id;
+use crate::database::invite_code::get_user_referrals::get_user_referrals;
+use crate::database::perks::get_user_perks::get_user_perks;
+use crate::database::points_ledger::get_user_points_by_day::get_user_points_by_day;
+use crate::database::users_ip::get_user_ips::get_user_ips;
+use crate::errors::error::Error;
+use crate::middlewares::authentication::Backend;
+use crate::startup::application::AppState;
+use axum::extract::{Query, State};
+use axum::response::{IntoResponse, Response};
+use axum::{Extension, Json};
+use axum_login::AuthSession;
+use block_mesh_common::interfaces::server_api::{
+    AccountExport, AccountExportQuery, DailyStatForDashboard, PerkUI,
+};
+use block_mesh_manager_database_domain::domain::get_user_opt_by_id::get_user_opt_by_id;
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+use http::header;
+use std::sync::Arc;
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn csv(header: &[&str], rows: Vec<Vec<String>>) -> String {
+    let mut out = header.join(",");
+    out.push('\n');
+    for row in rows {
+        let row: Vec<String> = row.iter().map(|value| csv_field(value)).collect();
+        out.push_str(&row.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+fn export_csv(export: AccountExport, dataset: &str) -> Result<String, Error> {
+    Ok(match dataset {
+        "daily_stats" => csv(
+            &["day", "tasks_count", "uptime", "points"],
+            export
+                .daily_stats
+                .into_iter()
+                .map(|stat| {
+                    vec![
+                        stat.day.to_string(),
+                        stat.tasks_count.to_string(),
+                        stat.uptime.to_string(),
+                        stat.points.to_string(),
+                    ]
+                })
+                .collect(),
+        ),
+        "referrals" => csv(
+            &["email", "created_at", "verified_email", "points_earned"],
+            export
+                .referrals
+                .into_iter()
+                .map(|referral| {
+                    vec![
+                        referral.email,
+                        referral.created_at.to_rfc3339(),
+                        referral.verified_email.to_string(),
+                        referral.points_earned.to_string(),
+                    ]
+                })
+                .collect(),
+        ),
+        "perks" => csv(
+            &["name", "multiplier", "one_time_bonus"],
+            export
+                .perks
+                .into_iter()
+                .map(|perk| {
+                    vec![
+                        perk.name,
+                        perk.multiplier.to_string(),
+                        perk.one_time_bonus.to_string(),
+                    ]
+                })
+                .collect(),
+        ),
+        "ip_history" => csv(
+            &["ip", "country", "last_seen_at"],
+            export
+                .ip_history
+                .into_iter()
+                .map(|ip| {
+                    vec![
+                        ip.ip,
+                        ip.country.unwrap_or_default(),
+                        ip.updated_at.to_rfc3339(),
+                    ]
+                })
+                .collect(),
+        ),
+        _ => {
+            return Err(Error::InvalidAccountRequest(
+                "dataset must be one of daily_stats, referrals, perks, ip_history".to_string(),
+            ))
+        }
+    })
+}
+
+/// Everything the account produced, as JSON or as one CSV per dataset.
+#[tracing::instrument(name = "export_account", skip_all)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthSession<Backend>>,
+    Query(query): Query<AccountExportQuery>,
+) -> Result<Response, Error> {
+    let user = auth.user.ok_or(Error::UserNotFound)?;
+    let mut transaction = create_txn(&state.pool).await?;
+    let db_user = get_user_opt_by_id(&mut transaction, &user.id)
+        .await?
+        .ok_or(Error::UserNotFound)?;
+    let points_by_day = get_user_points_by_day(&mut transaction, &user.id).await?;
+    let daily_stats = get_daily_stats_by_user_id(&mut transaction, &user.id)
+        .await?
+        .into_iter()
+        .map(|stat| DailyStatForDashboard {
+            tasks_count: stat.tasks_count,
+            uptime: stat.uptime,
+            day: stat.day,
+            points: points_by_day.get(&stat.day).copied().unwrap_or_default(),
+        })
+        .collect();
+    let referrals = get_user_referrals(&mut transaction, user.id).await?;
+    let perks = get_user_perks(&mut transaction, user.id)
+        .await?
+        .into_iter()
+        .map(|perk| PerkUI {
+            id: perk.id,
+            name: perk.name.to_string(),
+            multiplier: perk.multiplier,
+            one_time_bonus: perk.one_time_bonus,
+        })
+        .collect();
+    let ip_history = get_user_ips(&mut transaction, &user.id, i64::MAX).await?;
+    commit_txn(transaction).await?;
+    let export = AccountExport {
+        email: db_user.email,
+        created_at: db_user.created_at,
+        wallet_address: db_user.wallet_address,
+        daily_stats,
+        referrals,
+        perks,
+        ip_history,
+    };
+    match query.format.as_deref().unwrap_or("json") {
+        "json" => Ok(Json(export).into_response()),
+        "csv" => {
+            let dataset = query.dataset.unwrap_or_default();
+            let body = export_csv(export, &dataset)?;
+            Ok((
+                [
+                    (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+                    (
+                        header::CONTENT_DISPOSITION,
+                        format!("attachment; filename=\"blockmesh-{}.csv\"", dataset),
+                    ),
+                ],
+                body,
+            )
+                .into_response())
+        }
+        _ => Err(Error::InvalidAccountRequest(
+            "format must be json or csv".to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use block_mesh_common::interfaces::server_api::{Referral, UserIpInfo};
+    use chrono::{NaiveDate, TimeZone, Utc};
+
+    fn export() -> AccountExport {
+        let at = Utc.with_ymd_and_hms(2024, 11, 1, 12, 0, 0).unwrap();
+        AccountExport {
+            email: "user@example.com".to_string(),
+            created_at: at,
+            wallet_address: None,
+            daily_stats: vec![DailyStatForDashboard {
+                tasks_count: 3,
+                uptime: 3600.5,
+                day: NaiveDate::from_ymd_opt(2024, 11, 1).unwrap(),
+                points: 12.5,
+            }],
+            referrals: vec![Referral {
+                email: "friend, \"best\"@example.com".to_string(),
+                created_at: at,
+                verified_email: true,
+                points_earned: 1.0,
+            }],
+            perks: vec![],
+            ip_history: vec![UserIpInfo {
+                ip: "1.2.3.4".to_string(),
+                country: None,
+                updated_at: at,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_csv_field_quotes_when_needed() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+        assert_eq!(csv_field(""), "");
+    }
+
+    #[test]
+    fn test_export_csv_datasets() {
+        assert_eq!(
+            export_csv(export(), "daily_stats").unwrap(),
+            "day,tasks_count,uptime,points\n2024-11-01,3,3600.5,12.5\n"
+        );
+        assert_eq!(
+            export_csv(export(), "referrals").unwrap(),
+            "email,created_at,verified_email,points_earned\n\"friend, \"\"best\"\"@example.com\",2024-11-01T12:00:00+00:00,true,1\n"
+        );
+        assert_eq!(
+            export_csv(export(), "perks").unwrap(),
+            "name,multiplier,one_time_bonus\n"
+        );
+        assert_eq!(
+            export_csv(export(), "ip_history").unwrap(),
+            "ip,country,last_seen_at\n1.2.3.4,,2024-11-01T12:00:00+00:00\n"
+        );
+    }
+
+    #[test]
+    fn test_export_csv_rejects_unknown_dataset() {
+        assert!(matches!(
+            export_csv(export(), ""),
+            Err(Error::InvalidAccountRequest(_))
+        ));
+        assert!(matches!(
+            export_csv(export(), "users"),
+            Err(Error::InvalidAccountRequest(_))
+        ));
+    }
+}
@@ -0,0 +1,4 @@
+pub mod change_email;
+pub mod delete_account;
+pub mod email_change_confirm;
+pub mod export;
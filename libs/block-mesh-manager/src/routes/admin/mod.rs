@@ -1 +1,3 @@
+pub mod points_ledger;
 pub mod reports_queue;
+pub mod sybil_flags;
@@ -0,0 +1,50 @@
+use crate::database::points_ledger::insert_points_ledger_entry::insert_points_ledger_entry;
+use crate::database::user::get_user_by_email::get_user_opt_by_email;
+use crate::errors::error::Error;
+use crate::middlewares::authentication::Backend;
+use crate::startup::application::AppState;
+use axum::extract::State;
+use axum::response::IntoResponse;
+use axum::{Extension, Json};
+use axum_login::AuthSession;
+use block_mesh_common::interfaces::server_api::PointsAdjustmentRequest;
+use block_mesh_manager_database_domain::domain::get_user_opt_by_id::get_user_opt_by_id;
+use block_mesh_manager_database_domain::domain::points_ledger::PointsSource;
+use block_mesh_manager_database_domain::domain::user::UserRole;
+use chrono::Utc;
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+use http::StatusCode;
+use std::sync::Arc;
+
+#[tracing::instrument(name = "adjust_points", skip_all)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthSession<Backend>>,
+    Json(body): Json<PointsAdjustmentRequest>,
+) -> Result<impl IntoResponse, Error> {
+    let mut transaction = create_txn(&state.pool).await?;
+    let admin = auth.user.ok_or(Error::UserNotFound)?;
+    let admin = get_user_opt_by_id(&mut transaction, &admin.id)
+        .await?
+        .ok_or(Error::UserNotFound)?;
+    if !matches!(admin.role, UserRole::Admin) {
+        return Err(Error::Unauthorized);
+    }
+    let user = get_user_opt_by_email(&mut transaction, &body.email.to_ascii_lowercase())
+        .await?
+        .ok_or(Error::UserNotFound)?;
+    let id = insert_points_ledger_entry(
+        &mut transaction,
+        &user.id,
+        body.day.unwrap_or(Utc::now().date_naive()),
+        PointsSource::ManualAdjustment,
+        None,
+        body.points,
+        1.0,
+        Some(body.note),
+        Some(admin.id),
+    )
+    .await?;
+    commit_txn(transaction).await?;
+    Ok((StatusCode::CREATED, Json(id)).into_response())
+}
@@ -0,0 +1,37 @@
+use crate::database::points_ledger::get_user_points_ledger::get_user_points_ledger;
+use crate::database::user::get_user_by_email::get_user_opt_by_email;
+use crate::errors::error::Error;
+use crate::middlewares::authentication::Backend;
+use crate::startup::application::AppState;
+use axum::extract::{Query, State};
+use axum::response::IntoResponse;
+use axum::{Extension, Json};
+use axum_login::AuthSession;
+use block_mesh_common::interfaces::server_api::PointsLedgerQuery;
+use block_mesh_manager_database_domain::domain::get_user_opt_by_id::get_user_opt_by_id;
+use block_mesh_manager_database_domain::domain::user::UserRole;
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+use std::sync::Arc;
+
+#[tracing::instrument(name = "get_points_ledger", skip_all)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthSession<Backend>>,
+    Query(query): Query<PointsLedgerQuery>,
+) -> Result<impl IntoResponse, Error> {
+    let mut transaction = create_txn(&state.pool).await?;
+    let admin = auth.user.ok_or(Error::UserNotFound)?;
+    let admin = get_user_opt_by_id(&mut transaction, &admin.id)
+        .await?
+        .ok_or(Error::UserNotFound)?;
+    if !matches!(admin.role, UserRole::Admin) {
+        return Err(Error::Unauthorized);
+    }
+    let user = get_user_opt_by_email(&mut transaction, &query.email.to_ascii_lowercase())
+        .await?
+        .ok_or(Error::UserNotFound)?;
+    let entries =
+        get_user_points_ledger(&mut transaction, &user.id, query.limit.unwrap_or(100)).await?;
+    commit_txn(transaction).await?;
+    Ok(Json(entries))
+}
@@ -0,0 +1,3 @@
+pub mod adjust_points;
+pub mod get_ledger;
+pub mod reverse_points;
@@ -0,0 +1,43 @@
+use crate::database::points_ledger::get_points_ledger_entry::get_points_ledger_entry;
+use crate::database::points_ledger::reverse_points_ledger_entry::reverse_points_ledger_entry;
+use crate::errors::error::Error;
+use crate::middlewares::authentication::Backend;
+use crate::startup::application::AppState;
+use axum::extract::State;
+use axum::response::IntoResponse;
+use axum::{Extension, Json};
+use axum_login::AuthSession;
+use block_mesh_common::interfaces::server_api::PointsReversalRequest;
+use block_mesh_manager_database_domain::domain::get_user_opt_by_id::get_user_opt_by_id;
+use block_mesh_manager_database_domain::domain::points_ledger::PointsSource;
+use block_mesh_manager_database_domain::domain::user::UserRole;
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+use http::StatusCode;
+use std::sync::Arc;
+
+#[tracing::instrument(name = "reverse_points", skip_all)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthSession<Backend>>,
+    Json(body): Json<PointsReversalRequest>,
+) -> Result<impl IntoResponse, Error> {
+    let mut transaction = create_txn(&state.pool).await?;
+    let admin = auth.user.ok_or(Error::UserNotFound)?;
+    let admin = get_user_opt_by_id(&mut transaction, &admin.id)
+        .await?
+        .ok_or(Error::UserNotFound)?;
+    if !matches!(admin.role, UserRole::Admin) {
+        return Err(Error::Unauthorized);
+    }
+    let entry = get_points_ledger_entry(&mut transaction, &body.id)
+        .await?
+        .ok_or(Error::PointsLedgerEntryNotFound)?;
+    if entry.source == PointsSource::Reversal {
+        return Err(Error::PointsAlreadyReversed);
+    }
+    let id = reverse_points_ledger_entry(&mut transaction, &entry, body.note, &admin.id)
+        .await?
+        .ok_or(Error::PointsAlreadyReversed)?;
+    commit_txn(transaction).await?;
+    Ok((StatusCode::CREATED, Json(id)).into_response())
+}
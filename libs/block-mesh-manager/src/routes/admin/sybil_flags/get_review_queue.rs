@@ -0,0 +1,38 @@
+use crate::database::sybil_flags::get_sybil_flags_for_review::get_sybil_flags_for_review;
+use crate::errors::error::Error;
+use crate::middlewares::authentication::Backend;
+use crate::startup::application::AppState;
+use axum::extract::{Query, State};
+use axum::response::IntoResponse;
+use axum::{Extension, Json};
+use axum_login::AuthSession;
+use block_mesh_common::interfaces::server_api::SybilFlagsQuery;
+use block_mesh_manager_database_domain::domain::get_user_opt_by_id::get_user_opt_by_id;
+use block_mesh_manager_database_domain::domain::sybil_flag::SybilFlagStatus;
+use block_mesh_manager_database_domain::domain::user::UserRole;
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+use std::sync::Arc;
+
+#[tracing::instrument(name = "get_sybil_review_queue", skip_all)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthSession<Backend>>,
+    Query(query): Query<SybilFlagsQuery>,
+) -> Result<impl IntoResponse, Error> {
+    let mut transaction = create_txn(&state.pool).await?;
+    let admin = auth.user.ok_or(Error::UserNotFound)?;
+    let admin = get_user_opt_by_id(&mut transaction, &admin.id)
+        .await?
+        .ok_or(Error::UserNotFound)?;
+    if !matches!(admin.role, UserRole::Admin) {
+        return Err(Error::Unauthorized);
+    }
+    let status = query
+        .status
+        .map(SybilFlagStatus::from)
+        .unwrap_or(SybilFlagStatus::Pending);
+    let flags =
+        get_sybil_flags_for_review(&mut transaction, status, query.limit.unwrap_or(100)).await?;
+    commit_txn(transaction).await?;
+    Ok(Json(flags))
+}
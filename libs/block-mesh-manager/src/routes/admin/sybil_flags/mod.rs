@@ -0,0 +1,2 @@
+pub mod get_review_queue;
+pub mod review_flag;
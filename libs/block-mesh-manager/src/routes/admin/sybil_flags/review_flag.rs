@@ -0,0 +1,37 @@
+use crate::database::sybil_flags::review_sybil_flag::review_sybil_flag;
+use crate::errors::error::Error;
+use crate::middlewares::authentication::Backend;
+use crate::startup::application::AppState;
+use axum::extract::State;
+use axum::response::IntoResponse;
+use axum::{Extension, Json};
+use axum_login::AuthSession;
+use block_mesh_common::interfaces::server_api::SybilFlagReviewRequest;
+use block_mesh_manager_database_domain::domain::get_user_opt_by_id::get_user_opt_by_id;
+use block_mesh_manager_database_domain::domain::sybil_flag::SybilFlagStatus;
+use block_mesh_manager_database_domain::domain::user::UserRole;
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+use http::StatusCode;
+use std::sync::Arc;
+
+#[tracing::instrument(name = "review_sybil_flag", skip_all)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthSession<Backend>>,
+    Json(body): Json<SybilFlagReviewRequest>,
+) -> Result<impl IntoResponse, Error> {
+    let mut transaction = create_txn(&state.pool).await?;
+    let admin = auth.user.ok_or(Error::UserNotFound)?;
+    let admin = get_user_opt_by_id(&mut transaction, &admin.id)
+        .await?
+        .ok_or(Error::UserNotFound)?;
+    if !matches!(admin.role, UserRole::Admin) {
+        return Err(Error::Unauthorized);
+    }
+    let status = SybilFlagStatus::from(body.status);
+    if !review_sybil_flag(&mut transaction, &body.user_id, status, &admin.id).await? {
+        return Err(Error::SybilFlagNotFound);
+    }
+    commit_txn(transaction).await?;
+    Ok(StatusCode::OK)
+}
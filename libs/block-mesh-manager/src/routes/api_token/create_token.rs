@@ -0,0 +1,64 @@
+use crate::database::api_token::create_named_api_token::create_named_api_token;
+use crate::database::api_token::get_user_api_tokens::get_user_api_tokens;
+use crate::errors::error::Error;
+use crate::middlewares::authentication::Backend;
+use crate::startup::application::AppState;
+use axum::extract::State;
+use axum::{Extension, Json};
+use axum_login::AuthSession;
+use block_mesh_common::interfaces::server_api::{ApiTokenSecretResponse, CreateApiTokenRequest};
+use block_mesh_manager_database_domain::domain::api_token::{max_active_api_tokens, ApiTokenScope};
+use chrono::{Duration, Utc};
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+use std::str::FromStr;
+use std::sync::Arc;
+
+const MAX_NAME_LENGTH: usize = 64;
+const MAX_EXPIRY_DAYS: i64 = 3650;
+
+#[tracing::instrument(name = "create_api_token", skip_all)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthSession<Backend>>,
+    Json(body): Json<CreateApiTokenRequest>,
+) -> Result<Json<ApiTokenSecretResponse>, Error> {
+    let user = auth.user.ok_or(Error::UserNotFound)?;
+    let name = body.name.trim();
+    if name.is_empty() || name.len() > MAX_NAME_LENGTH {
+        return Err(Error::InvalidApiTokenRequest);
+    }
+    let mut scopes = Vec::with_capacity(body.scopes.len());
+    for scope in &body.scopes {
+        let scope = ApiTokenScope::from_str(scope).map_err(|_| Error::InvalidApiTokenRequest)?;
+        if !scopes.contains(&scope) {
+            scopes.push(scope);
+        }
+    }
+    if scopes.is_empty() {
+        return Err(Error::InvalidApiTokenRequest);
+    }
+    let expires_at = match body.expires_in_days {
+        Some(days) if (1..=MAX_EXPIRY_DAYS).contains(&days) => {
+            Some(Utc::now() + Duration::days(days))
+        }
+        Some(_) => return Err(Error::InvalidApiTokenRequest),
+        None => None,
+    };
+
+    let mut transaction = create_txn(&state.pool).await?;
+    let existing = get_user_api_tokens(&mut transaction, &user.id).await?;
+    let now = Utc::now();
+    let active = existing
+        .iter()
+        .filter(|api_token| !api_token.is_expired(now))
+        .count();
+    if existing.iter().any(|api_token| api_token.name == name)
+        || active as i64 >= max_active_api_tokens()
+    {
+        return Err(Error::InvalidApiTokenRequest);
+    }
+    let (id, api_token) =
+        create_named_api_token(&mut transaction, &user.id, name, &scopes, expires_at).await?;
+    commit_txn(transaction).await?;
+    Ok(Json(ApiTokenSecretResponse { id, api_token }))
+}
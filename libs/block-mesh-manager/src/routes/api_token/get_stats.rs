@@ -1,10 +1,10 @@
-use crate::database::api_token::get_api_token_by_user_id_and_status::get_api_token_by_usr_and_status;
 use crate::database::daily_stat::get_daily_stats_by_user_id::get_daily_stats_by_user_id;
 use crate::database::user::get_user_by_email::get_user_opt_by_email;
 use crate::errors::error::Error;
 use axum::{Extension, Json};
 use block_mesh_common::interfaces::server_api::{GetStatsRequest, GetStatsResponse, Stat};
-use block_mesh_manager_database_domain::domain::api_token::ApiTokenStatus;
+use block_mesh_manager_database_domain::domain::api_token::ApiTokenScope;
+use block_mesh_manager_database_domain::domain::find_token::find_token;
 use sqlx::PgPool;
 
 #[tracing::instrument(name = "get_stats", skip_all)]
@@ -17,11 +17,10 @@ pub async fn handler(
     let user = get_user_opt_by_email(&mut transaction, &email)
         .await?
         .ok_or_else(|| Error::UserNotFound)?;
-    let api_token =
-        get_api_token_by_usr_and_status(&mut transaction, &user.id, ApiTokenStatus::Active)
-            .await?
-            .ok_or(Error::ApiTokenNotFound)?;
-    if *api_token.token.as_ref() != body.api_token {
+    let api_token = find_token(&mut transaction, &body.api_token, ApiTokenScope::ReadStats)
+        .await?
+        .ok_or(Error::ApiTokenNotFound)?;
+    if api_token.user_id != user.id {
         return Err(Error::ApiTokenMismatch);
     }
 
@@ -1,4 +1,3 @@
-use crate::database::api_token::get_api_token_by_user_id_and_status::get_api_token_by_usr_and_status;
 use crate::database::user::get_user_by_email::get_user_opt_by_email;
 use crate::errors::error::Error;
 use crate::startup::application::AppState;
@@ -8,7 +7,10 @@ use axum::{Extension, Json};
 use block_mesh_common::interfaces::server_api::{
     GetTokenRequest, GetTokenResponse, GetTokenResponseEnum,
 };
-use block_mesh_manager_database_domain::domain::api_token::ApiTokenStatus;
+use block_mesh_manager_database_domain::domain::api_token::DEFAULT_API_TOKEN_NAME;
+use block_mesh_manager_database_domain::domain::get_or_create_api_token::get_or_create_api_token;
+use block_mesh_manager_database_domain::domain::node_auth::issue_session_ticket;
+use block_mesh_manager_database_domain::domain::siws_auth::get_token_with_siws;
 use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
 use sqlx::PgPool;
 use std::sync::Arc;
@@ -20,69 +22,104 @@ pub async fn handler(
     Json(body): Json<GetTokenRequest>,
 ) -> Result<Json<GetTokenResponse>, Error> {
     let email = body.email.clone().to_ascii_lowercase();
-    let key = (email.clone(), body.password.clone());
     let get_token_map = &state.get_token_map;
-
-    if let Some(entry) = get_token_map.get(&key) {
-        return match entry.value() {
-            GetTokenResponseEnum::GetTokenResponse(r) => Ok(Json(r.clone())),
-            GetTokenResponseEnum::UserNotFound => Err(Error::UserNotFound),
-            GetTokenResponseEnum::PasswordMismatch => Err(Error::PasswordMismatch),
-            GetTokenResponseEnum::ApiTokenNotFound => Err(Error::ApiTokenNotFound),
+    let token_name = body
+        .token_name
+        .as_deref()
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .unwrap_or(DEFAULT_API_TOKEN_NAME)
+        .to_string();
+    if let Some(sign_in) = &body.siws {
+        // signed messages are single use, nothing worth caching
+        let mut transaction = create_txn(&pool).await?;
+        let Some((user_id, api_token)) =
+            get_token_with_siws(&mut transaction, sign_in, &token_name).await?
+        else {
+            commit_txn(transaction).await?;
+            return Err(Error::WalletSignIn("Wallet sign in failed".to_string()));
         };
+        commit_txn(transaction).await?;
+        let (session_ticket, session_ticket_expires_at) =
+            issue_session_ticket(user_id, api_token.id, &api_token.scopes).unzip();
+        return Ok(Json(GetTokenResponse {
+            api_token: Some(*api_token.token.as_ref()),
+            message: None,
+            session_ticket,
+            session_ticket_expires_at,
+        }));
     }
+    let secret = format!("{}\0{}", body.password, token_name);
+    let ticket = match get_token_map.get(&email, secret.as_bytes()) {
+        Ok(cached) => {
+            return match cached {
+                GetTokenResponseEnum::GetTokenResponse {
+                    mut response,
+                    user_id,
+                    token_id,
+                    scopes,
+                } => {
+                    (response.session_ticket, response.session_ticket_expires_at) =
+                        issue_session_ticket(user_id, token_id, &scopes).unzip();
+                    Ok(Json(response))
+                }
+                GetTokenResponseEnum::UserNotFound => Err(Error::UserNotFound),
+                GetTokenResponseEnum::PasswordMismatch => Err(Error::PasswordMismatch),
+                GetTokenResponseEnum::ApiTokenNotFound => Err(Error::ApiTokenNotFound),
+            }
+        }
+        Err(ticket) => ticket,
+    };
     let mut transaction = create_txn(&pool).await?;
 
     let user = match get_user_opt_by_email(&mut transaction, &email).await {
         Ok(user) => match user {
             Some(user) => user,
             None => {
-                get_token_map.insert(key, GetTokenResponseEnum::UserNotFound);
+                get_token_map.insert(ticket, GetTokenResponseEnum::UserNotFound, true);
                 commit_txn(transaction).await?;
                 return Err(Error::UserNotFound);
             }
         },
         Err(_) => {
-            get_token_map.insert(key, GetTokenResponseEnum::UserNotFound);
             commit_txn(transaction).await?;
             return Err(Error::UserNotFound);
         }
     };
 
     if !verify_with_cache(body.password.as_ref(), user.password.as_ref()).await {
-        get_token_map.insert(key, GetTokenResponseEnum::PasswordMismatch);
+        get_token_map.insert(ticket, GetTokenResponseEnum::PasswordMismatch, true);
         commit_txn(transaction).await?;
         return Err(Error::PasswordMismatch);
     }
 
-    let api_token =
-        match get_api_token_by_usr_and_status(&mut transaction, &user.id, ApiTokenStatus::Active)
-            .await
-        {
-            Ok(api_token) => match api_token {
-                Some(api_token) => api_token,
-                None => {
-                    get_token_map.insert(key, GetTokenResponseEnum::ApiTokenNotFound);
-                    commit_txn(transaction).await?;
-                    return Err(Error::ApiTokenNotFound);
-                }
-            },
-            Err(_) => {
-                get_token_map.insert(key, GetTokenResponseEnum::ApiTokenNotFound);
-                commit_txn(transaction).await?;
-                return Err(Error::ApiTokenNotFound);
-            }
-        };
+    let api_token = match get_or_create_api_token(&mut transaction, &user.id, &token_name).await {
+        Ok(api_token) => api_token,
+        Err(_) => {
+            commit_txn(transaction).await?;
+            return Err(Error::ApiTokenNotFound);
+        }
+    };
 
-    let response = GetTokenResponse {
+    let mut response = GetTokenResponse {
         api_token: Some(*api_token.token.as_ref()),
         message: None,
+        session_ticket: None,
+        session_ticket_expires_at: None,
     };
 
     get_token_map.insert(
-        key,
-        GetTokenResponseEnum::GetTokenResponse(response.clone()),
+        ticket,
+        GetTokenResponseEnum::GetTokenResponse {
+            response: response.clone(),
+            user_id: user.id,
+            token_id: api_token.id,
+            scopes: api_token.scopes.clone(),
+        },
+        false,
     );
     commit_txn(transaction).await?;
+    (response.session_ticket, response.session_ticket_expires_at) =
+        issue_session_ticket(user.id, api_token.id, &api_token.scopes).unzip();
     Ok(Json(response))
 }
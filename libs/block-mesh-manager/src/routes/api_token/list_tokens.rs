@@ -0,0 +1,24 @@
+use crate::database::api_token::get_user_api_tokens::get_user_api_tokens;
+use crate::errors::error::Error;
+use crate::middlewares::authentication::Backend;
+use crate::startup::application::AppState;
+use axum::extract::State;
+use axum::{Extension, Json};
+use axum_login::AuthSession;
+use block_mesh_common::interfaces::server_api::ApiTokenInfo;
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+use std::sync::Arc;
+
+#[tracing::instrument(name = "list_api_tokens", skip_all)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthSession<Backend>>,
+) -> Result<Json<Vec<ApiTokenInfo>>, Error> {
+    let user = auth.user.ok_or(Error::UserNotFound)?;
+    let mut transaction = create_txn(&state.pool).await?;
+    let api_tokens = get_user_api_tokens(&mut transaction, &user.id).await?;
+    commit_txn(transaction).await?;
+    Ok(Json(
+        api_tokens.into_iter().map(ApiTokenInfo::from).collect(),
+    ))
+}
@@ -0,0 +1,33 @@
+use crate::database::api_token::revoke_api_token::revoke_api_token;
+use crate::errors::error::Error;
+use crate::middlewares::authentication::Backend;
+use crate::startup::application::AppState;
+use axum::extract::State;
+use axum::response::IntoResponse;
+use axum::{Extension, Json};
+use axum_login::AuthSession;
+use block_mesh_common::interfaces::db_messages::InvalidateApiCache;
+use block_mesh_common::interfaces::server_api::ApiTokenIdRequest;
+use block_mesh_manager_database_domain::domain::notify_api::notify_api;
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+use http::StatusCode;
+use std::sync::Arc;
+
+#[tracing::instrument(name = "revoke_api_token", skip_all)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthSession<Backend>>,
+    Json(body): Json<ApiTokenIdRequest>,
+) -> Result<impl IntoResponse, Error> {
+    let user = auth.user.ok_or(Error::UserNotFound)?;
+    let mut transaction = create_txn(&state.pool).await?;
+    if !revoke_api_token(&mut transaction, &user.id, &body.id).await? {
+        return Err(Error::ApiTokenNotFound);
+    }
+    commit_txn(transaction).await?;
+    let email = user.email.to_ascii_lowercase();
+    state.get_token_map.invalidate_email(&email);
+    state.check_token_map.invalidate_email(&email);
+    let _ = notify_api(&state.pool, InvalidateApiCache { email }).await;
+    Ok(StatusCode::OK)
+}
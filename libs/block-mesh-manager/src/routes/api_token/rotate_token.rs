@@ -0,0 +1,34 @@
+use crate::database::api_token::rotate_api_token::rotate_api_token;
+use crate::errors::error::Error;
+use crate::middlewares::authentication::Backend;
+use crate::startup::application::AppState;
+use axum::extract::State;
+use axum::{Extension, Json};
+use axum_login::AuthSession;
+use block_mesh_common::interfaces::db_messages::InvalidateApiCache;
+use block_mesh_common::interfaces::server_api::{ApiTokenIdRequest, ApiTokenSecretResponse};
+use block_mesh_manager_database_domain::domain::notify_api::notify_api;
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+use std::sync::Arc;
+
+#[tracing::instrument(name = "rotate_api_token", skip_all)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthSession<Backend>>,
+    Json(body): Json<ApiTokenIdRequest>,
+) -> Result<Json<ApiTokenSecretResponse>, Error> {
+    let user = auth.user.ok_or(Error::UserNotFound)?;
+    let mut transaction = create_txn(&state.pool).await?;
+    let api_token = rotate_api_token(&mut transaction, &user.id, &body.id)
+        .await?
+        .ok_or(Error::ApiTokenNotFound)?;
+    commit_txn(transaction).await?;
+    let email = user.email.to_ascii_lowercase();
+    state.get_token_map.invalidate_email(&email);
+    state.check_token_map.invalidate_email(&email);
+    let _ = notify_api(&state.pool, InvalidateApiCache { email }).await;
+    Ok(Json(ApiTokenSecretResponse {
+        id: body.id,
+        api_token,
+    }))
+}
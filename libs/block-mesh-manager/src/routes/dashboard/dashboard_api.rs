@@ -9,6 +9,7 @@ use crate::errors::error::Error;
 use crate::routes::dashboard::dashboard_data_extractor::dashboard_data_extractor;
 use crate::startup::application::AppState;
 use block_mesh_common::interfaces::server_api::{DashboardRequest, DashboardResponse};
+use block_mesh_manager_database_domain::domain::api_token::ApiTokenScope;
 use block_mesh_manager_database_domain::domain::find_token::find_token;
 use block_mesh_manager_database_domain::domain::get_user_opt_by_id::get_user_opt_by_id;
 
@@ -19,7 +20,7 @@ pub async fn handler(
     Json(body): Json<DashboardRequest>,
 ) -> Result<Json<DashboardResponse>, Error> {
     let mut transaction = pool.begin().await.map_err(Error::from)?;
-    let api_token = find_token(&mut transaction, &body.api_token)
+    let api_token = find_token(&mut transaction, &body.api_token, ApiTokenScope::ReadStats)
         .await?
         .ok_or(Error::ApiTokenNotFound)?;
     let user = get_user_opt_by_id(&mut transaction, &api_token.user_id)
@@ -8,24 +8,33 @@ use tracing::Level;
 use uuid::Uuid;
 
 use block_mesh_common::interfaces::server_api::{
-    CallToActionUI, DailyStatForDashboard, DashboardResponse, PerkUI, Referral,
+    ApiTokenInfo, CallToActionUI, DailyStatForDashboard, DashboardResponse, PerkUI, Referral,
 };
 
+use crate::database::api_token::get_user_api_tokens::get_user_api_tokens;
 use crate::database::call_to_action::get_user_calls_to_action::get_user_call_to_action;
 use crate::database::daily_stat::get_daily_stats_by_user_id::get_daily_stats_by_user_id;
 use crate::database::invite_code::get_number_of_users_invited::get_number_of_users_invited;
 use crate::database::invite_code::get_user_latest_invite_code::get_user_latest_invite_code;
 use crate::database::invite_code::get_user_referrals::get_user_referrals;
 use crate::database::perks::get_user_perks::get_user_perks;
+use crate::database::points_ledger::get_user_points_by_day::get_user_points_by_day;
+use crate::database::points_ledger::get_user_points_total::{
+    get_user_points_total, get_user_points_total_by_source,
+};
+use crate::database::sybil_flags::get_user_sybil_flag::get_user_sybil_flag;
 use crate::database::users_ip::get_user_ips::get_user_ips;
 use crate::errors::error::Error;
 use crate::startup::application::AppState;
 use crate::utils::cache_envar::get_envar;
-use crate::utils::points::{calc_one_time_bonus_points, calc_points_daily, calc_total_points};
+use crate::utils::points::calc_live_points;
 use block_mesh_common::feature_flag_client::{get_flag_value_from_map, FlagValue};
 use block_mesh_manager_database_domain::domain::aggregate::AggregateName;
+use block_mesh_manager_database_domain::domain::daily_stat::DailyStatStatus;
 use block_mesh_manager_database_domain::domain::get_or_create_aggregate_by_user_and_name::get_or_create_aggregate_by_user_and_name;
+use block_mesh_manager_database_domain::domain::get_reward_rules::get_reward_rules;
 use block_mesh_manager_database_domain::domain::get_user_opt_by_id::get_user_opt_by_id;
+use block_mesh_manager_database_domain::domain::points_ledger::PointsSource;
 use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
 use regex::Regex;
 
@@ -75,12 +84,38 @@ pub async fn dashboard_data_extractor(
         sec_diff < connected_buffer * ((interval * 2.0) as i64).checked_div(1_000).unwrap_or(240);
     let calls_to_action = get_user_call_to_action(&mut transaction, user_id).await?;
     let perks = get_user_perks(&mut transaction, user_id).await?;
+    let ledger_points_by_day = get_user_points_by_day(&mut transaction, &user_id).await?;
+    let ledger_points = get_user_points_total(&mut transaction, &user_id).await?;
+    let referral_points =
+        get_user_points_total_by_source(&mut transaction, &user_id, PointsSource::Referral).await?;
+    let rules = get_reward_rules(&mut transaction).await?;
+    let sybil_weight = get_user_sybil_flag(&mut transaction, &user_id)
+        .await?
+        .map(|flag| flag.effective_weight())
+        .unwrap_or(1.0);
+    let mut ongoing_points = 0.0;
     let daily_stats: Vec<DailyStatForDashboard> =
         get_daily_stats_by_user_id(&mut transaction, &user_id)
             .await?
             .into_iter()
             .map(|i| {
-                let points = calc_points_daily(i.uptime, i.tasks_count, &perks);
+                // finalized days are credited to the ledger, ongoing days are still accumulating
+                let mut points = ledger_points_by_day
+                    .get(&i.day)
+                    .copied()
+                    .unwrap_or_default();
+                if matches!(i.status, DailyStatStatus::OnGoing) {
+                    let live = calc_live_points(
+                        &rules,
+                        i.day,
+                        i.uptime,
+                        i.tasks_count,
+                        &perks,
+                        sybil_weight,
+                    );
+                    ongoing_points += live;
+                    points += live;
+                }
                 DailyStatForDashboard {
                     tasks_count: i.tasks_count,
                     uptime: i.uptime,
@@ -97,12 +132,7 @@ pub async fn dashboard_data_extractor(
         tasks.value.as_i64().unwrap_or_default(),
         daily_stats.iter().map(|i| i.tasks_count).sum::<i64>(),
     );
-    let one_time_bonus_points =
-        calc_one_time_bonus_points(overall_uptime as f64, overall_task_count, &perks) as u64;
-    let points = max(
-        calc_total_points(overall_uptime as f64, overall_task_count, &perks) as u64,
-        one_time_bonus_points + daily_stats.iter().map(|i| i.points).sum::<f64>() as u64,
-    ) as f64;
+    let points = ledger_points + ongoing_points;
     let download = get_or_create_aggregate_by_user_and_name(
         &mut transaction,
         AggregateName::Download,
@@ -121,8 +151,11 @@ pub async fn dashboard_data_extractor(
     )
     .await?;
 
+    let api_tokens = get_user_api_tokens(&mut transaction, &user_id).await?;
+
     commit_txn(transaction).await?;
     Ok(DashboardResponse {
+        api_tokens: api_tokens.into_iter().map(ApiTokenInfo::from).collect(),
         wallet_address: user.wallet_address,
         user_ips,
         calls_to_action: calls_to_action
@@ -139,6 +172,7 @@ pub async fn dashboard_data_extractor(
             .map(|i| Referral {
                 created_at: i.created_at,
                 verified_email: i.verified_email,
+                points_earned: i.points_earned,
                 email: {
                     let s: Vec<&str> = i.email.split('@').collect();
                     let re = Regex::new(r"[A-Za-z]").unwrap();
@@ -147,6 +181,7 @@ pub async fn dashboard_data_extractor(
                 },
             })
             .collect(),
+        referral_points,
         upload: upload.value.as_f64().unwrap_or_default(),
         download: download.value.as_f64().unwrap_or_default(),
         latency: latency.value.as_f64().unwrap_or_default(),
@@ -1,8 +1,48 @@
+use crate::database::notifications::unsubscribe_notifications::unsubscribe_notifications;
 use crate::errors::error::Error;
-use axum::response::IntoResponse;
+use crate::notification::notification_redirect::NotificationRedirect;
+use crate::startup::application::AppState;
+use axum::extract::{Query, State};
+use axum::response::{IntoResponse, Response};
+use block_mesh_common::interfaces::server_api::UnsubscribeQuery;
+use block_mesh_common::routes_enum::RoutesEnum;
+use block_mesh_manager_database_domain::domain::notification_preferences::NotificationKind;
 use http::StatusCode;
+use std::str::FromStr;
+use std::sync::Arc;
 
+/// Target of the unsubscribe links in notification emails, `kind` turns off a single
+/// notification and without it every email stops. Also accepts one-click POSTs.
 #[tracing::instrument(name = "unsubscribe", skip_all)]
-pub async fn unsubscribe() -> Result<impl IntoResponse, Error> {
-    Ok((StatusCode::OK, "OK"))
+pub async fn unsubscribe(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<UnsubscribeQuery>,
+) -> Result<Response, Error> {
+    let Some(token) = query.token else {
+        return Ok((StatusCode::OK, "OK").into_response());
+    };
+    let kind = match query.kind.as_deref().map(NotificationKind::from_str) {
+        None => None,
+        Some(Ok(kind)) => Some(kind),
+        Some(Err(_)) => return Ok(invalid_link()),
+    };
+    if !unsubscribe_notifications(&state.pool, &token, kind).await? {
+        return Ok(invalid_link());
+    }
+    Ok(NotificationRedirect::redirect(
+        "Unsubscribed",
+        "You can turn notifications back on from the dashboard",
+        RoutesEnum::Static_UnAuth_Login.to_string().as_str(),
+    )
+    .into_response())
+}
+
+fn invalid_link() -> Response {
+    Error::redirect(
+        400,
+        "Invalid link",
+        "This unsubscribe link is not valid",
+        RoutesEnum::Static_UnAuth_Root.to_string().as_str(),
+    )
+    .into_response()
 }
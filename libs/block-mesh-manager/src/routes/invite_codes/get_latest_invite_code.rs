@@ -6,6 +6,7 @@ use crate::errors::error::Error;
 use block_mesh_common::interfaces::server_api::{
     GetLatestInviteCodeRequest, GetLatestInviteCodeResponse,
 };
+use block_mesh_manager_database_domain::domain::api_token::ApiTokenScope;
 use block_mesh_manager_database_domain::domain::find_token::find_token;
 use block_mesh_manager_database_domain::domain::get_user_opt_by_id::get_user_opt_by_id;
 use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
@@ -16,7 +17,7 @@ pub async fn handler(
     Json(body): Json<GetLatestInviteCodeRequest>,
 ) -> Result<Json<GetLatestInviteCodeResponse>, Error> {
     let mut transaction = create_txn(&pool).await?;
-    let api_token = find_token(&mut transaction, &body.api_token)
+    let api_token = find_token(&mut transaction, &body.api_token, ApiTokenScope::ReadStats)
         .await?
         .ok_or(Error::ApiTokenNotFound)?;
     let user = get_user_opt_by_id(&mut transaction, &api_token.user_id)
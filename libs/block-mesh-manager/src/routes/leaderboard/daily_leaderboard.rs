@@ -1,10 +1,10 @@
 use crate::database::leaderboard::get_daily_leaderboard::get_daily_leaderboard;
 use crate::errors::error::Error;
 use crate::middlewares::authentication::Backend;
-use crate::utils::points::{TASKS_FACTOR, UPTIME_FACTOR};
 use axum::{Extension, Json};
 use axum_login::AuthSession;
 use block_mesh_common::interfaces::server_api::{DailyLeaderboard, LeaderBoardUser};
+use block_mesh_manager_database_domain::domain::get_reward_rules::get_reward_rules;
 use chrono::{Duration, NaiveDate, Utc};
 use dashmap::DashMap;
 use sqlx::PgPool;
@@ -33,24 +33,30 @@ pub async fn handler(
     let mut transaction = pool.begin().await.map_err(Error::from)?;
     let user = auth.user.ok_or(Error::UserNotFound)?;
 
-    let leaderboard_users: Vec<LeaderBoardUser> =
-        get_daily_leaderboard(&mut transaction, UPTIME_FACTOR, TASKS_FACTOR, 5)
-            .await?
-            .into_iter()
-            .map(|i| {
-                if user.email == i.email {
-                    LeaderBoardUser {
-                        email: user.email.clone(),
-                        points: i.points,
-                    }
-                } else {
-                    LeaderBoardUser {
-                        email: "***@***".to_string(),
-                        points: i.points,
-                    }
+    if get_reward_rules(&mut transaction)
+        .await?
+        .for_day(day)
+        .is_none()
+    {
+        return Err(Error::RewardRulesNotFound);
+    }
+    let leaderboard_users: Vec<LeaderBoardUser> = get_daily_leaderboard(&mut transaction, day, 5)
+        .await?
+        .into_iter()
+        .map(|i| {
+            if user.email == i.email {
+                LeaderBoardUser {
+                    email: user.email.clone(),
+                    points: i.points,
+                }
+            } else {
+                LeaderBoardUser {
+                    email: "***@***".to_string(),
+                    points: i.points,
                 }
-            })
-            .collect();
+            }
+        })
+        .collect();
     transaction.commit().await.map_err(Error::from)?;
     cache.insert(day, leaderboard_users.clone());
     Ok(Json(DailyLeaderboard {
@@ -1,7 +1,10 @@
 use crate::database::nonce::get_nonce_by_user_id::get_nonce_by_user_id;
+use crate::database::two_factor::get_user_totp::get_user_totp;
 use crate::database::user::get_user_by_email::get_user_opt_by_email;
 use crate::errors::error::Error;
-use crate::middlewares::authentication::{Backend, Credentials};
+use crate::middlewares::authentication::{
+    Backend, Credentials, PendingTwoFactor, PENDING_TWO_FACTOR_KEY,
+};
 use axum::response::Redirect;
 use axum::{Extension, Form};
 use axum_login::AuthSession;
@@ -41,6 +44,21 @@ pub async fn handler(
             ));
         }
     };
+    let two_factor_enabled = get_user_totp(&mut tranaction, &user.id)
+        .await?
+        .map(|user_totp| user_totp.enabled)
+        .unwrap_or(false);
+    if two_factor_enabled {
+        commit_txn(tranaction).await?;
+        // the password checked out, the login itself waits for the second factor
+        auth.session
+            .insert(PENDING_TWO_FACTOR_KEY, PendingTwoFactor::new(session))
+            .await
+            .map_err(|e| Error::Auth(e.to_string()))?;
+        return Ok(Redirect::to(
+            RoutesEnum::Static_UnAuth_TwoFactor.to_string().as_str(),
+        ));
+    }
     match auth.login(&session).await {
         Ok(_) => {}
         Err(e) => {
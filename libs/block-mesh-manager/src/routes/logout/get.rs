@@ -1,14 +1,29 @@
+use crate::database::user_session::revoke_user_session::revoke_user_session;
 use crate::errors::error::Error;
 use crate::middlewares::authentication::Backend;
+use crate::middlewares::user_session::{forget_user_session, USER_SESSION_KEY};
+use crate::startup::application::AppState;
+use axum::extract::State;
 use axum::response::Redirect;
 use axum::Extension;
 use axum_login::AuthSession;
 use block_mesh_common::routes_enum::RoutesEnum;
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+use std::sync::Arc;
+use uuid::Uuid;
 
 #[tracing::instrument(name = "logout", skip_all)]
 pub async fn handler(
+    State(state): State<Arc<AppState>>,
     Extension(mut auth): Extension<AuthSession<Backend>>,
 ) -> Result<Redirect, Error> {
+    let current: Option<Uuid> = auth.session.get(USER_SESSION_KEY).await.ok().flatten();
+    if let (Some(user), Some(id)) = (&auth.user, current) {
+        let mut transaction = create_txn(&state.pool).await?;
+        revoke_user_session(&mut transaction, &user.id, &id).await?;
+        commit_txn(transaction).await?;
+        forget_user_session(&state.redis, &user.id, &id).await;
+    }
     auth.logout()
         .await
         .map_err(|e| Error::Auth(e.to_string()))?;
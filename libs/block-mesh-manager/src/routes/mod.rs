@@ -1,3 +1,4 @@
+pub mod account;
 pub mod api_token;
 pub mod bandwidth;
 pub mod basic_response;
@@ -12,13 +13,18 @@ pub mod login;
 pub mod logout;
 pub mod map;
 pub mod notification;
+pub mod notifications;
 pub mod password;
 pub mod perks;
 pub mod register;
 pub mod rpc;
+pub mod siws;
 pub mod tasks;
+pub mod tg_bot;
 pub mod twitter;
+pub mod two_factor;
 pub mod uptime_report;
+pub mod user_sessions;
 
 pub mod admin;
 pub mod version;
@@ -0,0 +1,35 @@
+use crate::errors::error::Error;
+use crate::middlewares::authentication::Backend;
+use crate::startup::application::AppState;
+use axum::extract::State;
+use axum::{Extension, Json};
+use axum_login::AuthSession;
+use block_mesh_common::interfaces::server_api::NotificationPreferencesResponse;
+use block_mesh_manager_database_domain::domain::notification_preferences::{
+    get_or_create_notification_preferences, NotificationPreferences,
+};
+use std::sync::Arc;
+
+pub fn preferences_response(
+    preferences: &NotificationPreferences,
+) -> NotificationPreferencesResponse {
+    NotificationPreferencesResponse {
+        email_enabled: preferences.email_enabled,
+        telegram_linked: preferences.telegram_chat_id.is_some(),
+        node_offline: preferences.node_offline,
+        new_referral: preferences.new_referral,
+        perk_earned: preferences.perk_earned,
+        daily_summary: preferences.daily_summary,
+        offline_minutes: preferences.offline_minutes,
+    }
+}
+
+#[tracing::instrument(name = "get_notification_preferences", skip_all)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthSession<Backend>>,
+) -> Result<Json<NotificationPreferencesResponse>, Error> {
+    let user = auth.user.ok_or(Error::UserNotFound)?;
+    let preferences = get_or_create_notification_preferences(&state.pool, &user.id).await?;
+    Ok(Json(preferences_response(&preferences)))
+}
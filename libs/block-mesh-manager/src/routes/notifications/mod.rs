@@ -0,0 +1,5 @@
+pub mod get_preferences;
+pub mod telegram_link;
+pub mod telegram_unlink;
+pub mod telegram_webhook;
+pub mod update_preferences;
@@ -0,0 +1,45 @@
+use crate::database::notifications::set_telegram_link_token::set_telegram_link_token;
+use crate::errors::error::Error;
+use crate::middlewares::authentication::Backend;
+use crate::startup::application::AppState;
+use crate::utils::cache_envar::get_envar;
+use axum::extract::State;
+use axum::{Extension, Json};
+use axum_login::AuthSession;
+use block_mesh_common::interfaces::server_api::TelegramLinkResponse;
+use block_mesh_manager_database_domain::domain::nonce::Nonce;
+use block_mesh_manager_database_domain::domain::notification_preferences::get_or_create_notification_preferences;
+use chrono::{Duration, Utc};
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+use std::sync::Arc;
+
+const TELEGRAM_LINK_TTL_MINUTES: i64 = 15;
+
+/// Returns a `t.me` deep link, opening it sends `/start <token>` to the bot
+/// and the webhook ties that chat to the account.
+#[tracing::instrument(name = "telegram_link", skip_all)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthSession<Backend>>,
+) -> Result<Json<TelegramLinkResponse>, Error> {
+    let user = auth.user.ok_or(Error::UserNotFound)?;
+    let bot_username = get_envar("TELEGRAM_NOTIFICATIONS_BOT_USERNAME").await;
+    if bot_username.is_empty() {
+        return Err(Error::TelegramNotificationsDisabled);
+    }
+    // the start parameter only allows up to 64 letters, digits, `_` and `-`
+    let token = Nonce::generate_nonce(32);
+    let mut transaction = create_txn(&state.pool).await?;
+    get_or_create_notification_preferences(&mut transaction, &user.id).await?;
+    set_telegram_link_token(
+        &mut transaction,
+        &user.id,
+        &token,
+        Utc::now() + Duration::minutes(TELEGRAM_LINK_TTL_MINUTES),
+    )
+    .await?;
+    commit_txn(transaction).await?;
+    Ok(Json(TelegramLinkResponse {
+        url: format!("https://t.me/{}?start={}", bot_username, token),
+    }))
+}
@@ -0,0 +1,20 @@
+use crate::database::notifications::unlink_telegram_chat::unlink_telegram_chat;
+use crate::errors::error::Error;
+use crate::middlewares::authentication::Backend;
+use crate::startup::application::AppState;
+use axum::extract::State;
+use axum::response::IntoResponse;
+use axum::Extension;
+use axum_login::AuthSession;
+use http::StatusCode;
+use std::sync::Arc;
+
+#[tracing::instrument(name = "telegram_unlink", skip_all)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthSession<Backend>>,
+) -> Result<impl IntoResponse, Error> {
+    let user = auth.user.ok_or(Error::UserNotFound)?;
+    unlink_telegram_chat(&state.pool, &user.id).await?;
+    Ok(StatusCode::OK)
+}
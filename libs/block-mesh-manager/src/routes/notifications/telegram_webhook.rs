@@ -0,0 +1,84 @@
+use crate::database::notifications::link_telegram_chat::link_telegram_chat;
+use crate::database::notifications::unlink_telegram_chat::unlink_telegram_chat_by_chat_id;
+use crate::errors::error::Error;
+use crate::startup::application::AppState;
+use crate::utils::cache_envar::get_envar;
+use axum::extract::State;
+use axum::http::HeaderMap;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use http::StatusCode;
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+
+/// Header Telegram echoes back with the `secret_token` given to `setWebhook`.
+const SECRET_TOKEN_HEADER: &str = "X-Telegram-Bot-Api-Secret-Token";
+
+#[derive(Deserialize)]
+struct TelegramUpdate {
+    message: Option<TelegramMessage>,
+}
+
+#[derive(Deserialize)]
+struct TelegramMessage {
+    chat: TelegramChat,
+    text: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TelegramChat {
+    id: i64,
+}
+
+/// Webhook of the notifications bot, registered with `setWebhook` and
+/// `TELEGRAM_NOTIFICATIONS_WEBHOOK_SECRET` as the secret token.
+/// Replies are returned as a `sendMessage` call in the webhook response.
+#[tracing::instrument(name = "telegram_webhook", skip_all)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(update): Json<serde_json::Value>,
+) -> Result<Response, Error> {
+    let secret = get_envar("TELEGRAM_NOTIFICATIONS_WEBHOOK_SECRET").await;
+    let provided = headers
+        .get(SECRET_TOKEN_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    if secret.is_empty() || provided != secret {
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    }
+    // anything we don't understand is acknowledged so Telegram doesn't redeliver it
+    let Ok(TelegramUpdate {
+        message: Some(message),
+    }) = serde_json::from_value::<TelegramUpdate>(update)
+    else {
+        return Ok(StatusCode::OK.into_response());
+    };
+    let chat_id = message.chat.id;
+    let text = message.text.unwrap_or_default();
+    let mut parts = text.split_whitespace();
+    let reply = match (parts.next(), parts.next()) {
+        (Some("/start"), Some(token)) => {
+            if link_telegram_chat(&state.pool, token, chat_id).await? {
+                "Your BlockMesh account is linked, notifications will arrive in this chat. Send /stop to unlink."
+            } else {
+                "This link expired, please create a new one from the BlockMesh dashboard."
+            }
+        }
+        (Some("/stop"), _) => {
+            if unlink_telegram_chat_by_chat_id(&state.pool, chat_id).await? {
+                "Notifications are turned off for this chat."
+            } else {
+                "This chat is not linked to a BlockMesh account."
+            }
+        }
+        _ => "Open the notifications page of the BlockMesh dashboard to link this chat.",
+    };
+    Ok(Json(json!({
+        "method": "sendMessage",
+        "chat_id": chat_id,
+        "text": reply,
+    }))
+    .into_response())
+}
@@ -0,0 +1,41 @@
+use crate::database::notifications::update_notification_preferences::update_notification_preferences;
+use crate::errors::error::Error;
+use crate::middlewares::authentication::Backend;
+use crate::routes::notifications::get_preferences::preferences_response;
+use crate::startup::application::AppState;
+use crate::utils::email_locale::email_locale;
+use axum::extract::State;
+use axum::http::HeaderMap;
+use axum::{Extension, Json};
+use axum_login::AuthSession;
+use block_mesh_common::interfaces::server_api::{
+    NotificationPreferencesResponse, UpdateNotificationPreferencesRequest,
+};
+use block_mesh_manager_database_domain::domain::notification_preferences::get_or_create_notification_preferences;
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+use std::sync::Arc;
+
+/// Bounds of the offline alert delay, shorter than a few polling intervals would flap.
+const MIN_OFFLINE_MINUTES: i32 = 5;
+const MAX_OFFLINE_MINUTES: i32 = 24 * 60;
+
+/// Saving also records the browser language, notifications are rendered in it.
+#[tracing::instrument(name = "update_notification_preferences", skip_all)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthSession<Backend>>,
+    headers: HeaderMap,
+    Json(mut body): Json<UpdateNotificationPreferencesRequest>,
+) -> Result<Json<NotificationPreferencesResponse>, Error> {
+    let user = auth.user.ok_or(Error::UserNotFound)?;
+    body.offline_minutes = body
+        .offline_minutes
+        .clamp(MIN_OFFLINE_MINUTES, MAX_OFFLINE_MINUTES);
+    let mut transaction = create_txn(&state.pool).await?;
+    get_or_create_notification_preferences(&mut transaction, &user.id).await?;
+    update_notification_preferences(&mut transaction, &user.id, &body, email_locale(&headers))
+        .await?;
+    let preferences = get_or_create_notification_preferences(&mut transaction, &user.id).await?;
+    commit_txn(transaction).await?;
+    Ok(Json(preferences_response(&preferences)))
+}
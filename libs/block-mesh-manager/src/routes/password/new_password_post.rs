@@ -78,6 +78,8 @@ pub async fn handler(
     };
     del_from_redis_with_pattern(&email, "-*", &mut redis).await?;
     del_from_redis_with_pattern(&user.id.to_string(), "-*", &mut redis).await?;
+    state.get_token_map.invalidate_email(&email);
+    state.check_token_map.invalidate_email(&email);
     let _ = notify_api(&state.pool, InvalidateApiCache { email: user.email }).await;
     Ok(NotificationRedirect::redirect(
         "Password updated",
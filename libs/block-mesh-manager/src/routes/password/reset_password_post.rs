@@ -3,8 +3,9 @@ use crate::database::user::get_user_by_email::get_user_opt_by_email;
 use crate::errors::error::Error;
 use crate::notification::notification_redirect::NotificationRedirect;
 use crate::startup::application::AppState;
-use crate::utils::cache_envar::get_envar;
+use crate::utils::email_locale::email_locale;
 use axum::extract::State;
+use axum::http::HeaderMap;
 use axum::response::Redirect;
 use axum::{Extension, Form};
 use block_mesh_common::interfaces::server_api::ResetPasswordForm;
@@ -14,6 +15,7 @@ use std::sync::Arc;
 pub async fn handler(
     Extension(pool): Extension<PgPool>,
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
     Form(form): Form<ResetPasswordForm>,
 ) -> Result<Redirect, Error> {
     let mut transaction = pool.begin().await?;
@@ -24,18 +26,14 @@ pub async fn handler(
     let nonce = get_nonce_by_user_id(&mut transaction, &user.id)
         .await?
         .ok_or_else(|| Error::NonceNotFound)?;
-    let email_mode = get_envar("EMAIL_MODE").await;
-    if email_mode == "AWS" {
-        let _ = state
-            .email_client
-            .send_reset_password_email_aws(&user.email, nonce.nonce.expose_secret())
-            .await;
-    } else {
-        let _ = state
-            .email_client
-            .send_reset_password_email_gmail(&user.email, nonce.nonce.expose_secret())
-            .await;
-    }
+    let _ = state
+        .email_client
+        .send_reset_password_email(
+            &user.email,
+            nonce.nonce.expose_secret(),
+            email_locale(&headers),
+        )
+        .await;
     transaction.commit().await?;
     Ok(NotificationRedirect::redirect(
         "Email Sent",
@@ -1,45 +1,106 @@
 use std::str::FromStr;
 
+use axum::extract::State;
 use axum::{Extension, Json};
 use axum_login::AuthSession;
+use http::HeaderMap;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::Signature;
-use sqlx::PgPool;
+use std::sync::Arc;
 
 use crate::database::perks::add_perk_to_user::add_perk_to_user;
 use crate::database::user::update_user_wallet::update_user_wallet;
+use crate::database::wallet::insert_wallet_link_event::{
+    insert_wallet_link_event, wallet_bonus_already_granted,
+};
+use crate::database::wallet::use_wallet_challenge::use_wallet_challenge;
 use crate::domain::perk::PerkName;
+use crate::domain::wallet_link_event::WalletLinkAction;
 use crate::errors::error::Error;
 use crate::middlewares::authentication::Backend;
+use crate::startup::application::AppState;
 use block_mesh_common::interfaces::server_api::{ConnectWalletRequest, ConnectWalletResponse};
+use block_mesh_manager_database_domain::domain::get_reward_rules::get_reward_rules;
+use block_mesh_manager_database_domain::domain::get_user_opt_by_id::get_user_opt_by_id;
+use block_mesh_manager_database_domain::domain::get_user_opt_by_wallet::get_user_opt_by_wallet;
+use chrono::Utc;
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
 
-#[tracing::instrument(name = "connect_wallet", skip(pool, auth))]
+#[tracing::instrument(name = "connect_wallet", skip(state, auth, headers))]
 pub async fn handler(
-    Extension(pool): Extension<PgPool>,
+    State(state): State<Arc<AppState>>,
     Extension(auth): Extension<AuthSession<Backend>>,
+    headers: HeaderMap,
     Json(body): Json<ConnectWalletRequest>,
 ) -> Result<Json<ConnectWalletResponse>, Error> {
-    let mut transaction = pool.begin().await?;
     let user = auth.user.ok_or(Error::UserNotFound)?;
     let signature =
-        Signature::try_from(body.signature.as_slice()).map_err(|_| Error::InternalServer)?;
-    let pubkey = Pubkey::from_str(body.pubkey.as_str()).unwrap_or_default();
-    let message = body.message.as_bytes();
-    if signature.verify(pubkey.as_ref(), message) {
-        add_perk_to_user(
+        Signature::try_from(body.signature.as_slice()).map_err(|_| Error::SignatureMismatch)?;
+    let pubkey = Pubkey::from_str(body.pubkey.as_str()).map_err(|_| Error::SignatureMismatch)?;
+    if !signature.verify(pubkey.as_ref(), body.message.as_bytes()) {
+        tracing::error!("Signature verification failed.");
+        return Err(Error::SignatureMismatch);
+    }
+    let mut transaction = create_txn(&state.pool).await?;
+    // only a message issued by wallet_challenge for this user and wallet, and only once
+    if !use_wallet_challenge(&mut transaction, &user.id, &body.pubkey, &body.message).await? {
+        return Err(Error::InvalidWalletChallenge);
+    }
+    let ip = headers
+        .get("cf-connecting-ip")
+        .and_then(|ip| ip.to_str().ok())
+        .map(str::to_string);
+    if let Some(owner) = get_user_opt_by_wallet(&mut transaction, &body.pubkey).await? {
+        if owner.id != user.id {
+            return Err(Error::WalletAlreadyLinked);
+        }
+        commit_txn(transaction).await?;
+        return Ok(Json(ConnectWalletResponse { status: 200 }));
+    }
+    let current = get_user_opt_by_id(&mut transaction, &user.id)
+        .await?
+        .ok_or(Error::UserNotFound)?;
+    if let Some(previous) = current.wallet_address {
+        insert_wallet_link_event(
             &mut transaction,
-            user.id,
-            PerkName::Wallet,
-            1.1,
-            0.0,
-            serde_json::from_str("{}").unwrap(),
+            &user.id,
+            &previous,
+            WalletLinkAction::Unlink,
+            ip.clone(),
         )
         .await?;
-        update_user_wallet(&mut transaction, user.id, body.pubkey).await?
-    } else {
-        tracing::error!("Signature verification failed.");
-        return Err(Error::SignatureMismatch);
     }
-    transaction.commit().await?;
+    let bonus_granted =
+        wallet_bonus_already_granted(&mut transaction, &user.id, &body.pubkey).await?;
+    update_user_wallet(&mut transaction, user.id, body.pubkey.clone())
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(e) if e.is_unique_violation() => Error::WalletAlreadyLinked,
+            e => Error::from(e),
+        })?;
+    insert_wallet_link_event(
+        &mut transaction,
+        &user.id,
+        &body.pubkey,
+        WalletLinkAction::Link,
+        ip,
+    )
+    .await?;
+    let rules = get_reward_rules(&mut transaction).await?;
+    let perk = rules.perk_rule(Utc::now().date_naive(), &PerkName::Wallet.to_string());
+    add_perk_to_user(
+        &mut transaction,
+        user.id,
+        PerkName::Wallet,
+        perk.multiplier,
+        if bonus_granted {
+            0.0
+        } else {
+            perk.one_time_bonus
+        },
+        serde_json::from_str("{}").unwrap(),
+    )
+    .await?;
+    commit_txn(transaction).await?;
     Ok(Json(ConnectWalletResponse { status: 200 }))
 }
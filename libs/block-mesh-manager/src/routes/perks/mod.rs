@@ -1 +1,3 @@
 pub mod connect_wallet;
+pub mod unlink_wallet;
+pub mod wallet_challenge;
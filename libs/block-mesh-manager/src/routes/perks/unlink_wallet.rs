@@ -0,0 +1,47 @@
+use axum::extract::State;
+use axum::{Extension, Json};
+use axum_login::AuthSession;
+use http::HeaderMap;
+use std::sync::Arc;
+
+use crate::database::wallet::insert_wallet_link_event::insert_wallet_link_event;
+use crate::database::wallet::unlink_user_wallet::unlink_user_wallet;
+use crate::domain::wallet_link_event::WalletLinkAction;
+use crate::errors::error::Error;
+use crate::middlewares::authentication::Backend;
+use crate::startup::application::AppState;
+use block_mesh_common::interfaces::server_api::ConnectWalletResponse;
+use block_mesh_common::siws::is_wallet_placeholder_email;
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+
+#[tracing::instrument(name = "unlink_wallet", skip_all)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthSession<Backend>>,
+    headers: HeaderMap,
+) -> Result<Json<ConnectWalletResponse>, Error> {
+    let user = auth.user.ok_or(Error::UserNotFound)?;
+    // a wallet only account would be left without any way to sign in
+    if is_wallet_placeholder_email(&user.email) {
+        return Err(Error::InvalidAccountRequest(
+            "Please link an email to your account before unlinking the wallet".to_string(),
+        ));
+    }
+    let mut transaction = create_txn(&state.pool).await?;
+    if let Some(wallet) = unlink_user_wallet(&mut transaction, &user.id).await? {
+        let ip = headers
+            .get("cf-connecting-ip")
+            .and_then(|ip| ip.to_str().ok())
+            .map(str::to_string);
+        insert_wallet_link_event(
+            &mut transaction,
+            &user.id,
+            &wallet,
+            WalletLinkAction::Unlink,
+            ip,
+        )
+        .await?;
+    }
+    commit_txn(transaction).await?;
+    Ok(Json(ConnectWalletResponse { status: 200 }))
+}
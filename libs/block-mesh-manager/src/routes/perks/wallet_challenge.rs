@@ -0,0 +1,55 @@
+use std::str::FromStr;
+
+use axum::extract::State;
+use axum::{Extension, Json};
+use axum_login::AuthSession;
+use solana_sdk::pubkey::Pubkey;
+use std::sync::Arc;
+
+use crate::database::wallet::create_wallet_challenge::create_wallet_challenge;
+use crate::errors::error::Error;
+use crate::middlewares::authentication::Backend;
+use crate::startup::application::{AppState, ApplicationBaseUrl};
+use block_mesh_common::interfaces::server_api::{WalletChallengeRequest, WalletChallengeResponse};
+use block_mesh_common::siws::SiwsMessage;
+use block_mesh_manager_database_domain::domain::get_user_opt_by_wallet::get_user_opt_by_wallet;
+use block_mesh_manager_database_domain::domain::nonce::Nonce;
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+
+/// Issues the exact message `connect_wallet` will accept, bound to this account, wallet and
+/// domain, valid once and for a few minutes.
+#[tracing::instrument(name = "wallet_challenge", skip(state, auth, base_url))]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthSession<Backend>>,
+    Extension(base_url): Extension<ApplicationBaseUrl>,
+    Json(body): Json<WalletChallengeRequest>,
+) -> Result<Json<WalletChallengeResponse>, Error> {
+    let user = auth.user.ok_or(Error::UserNotFound)?;
+    Pubkey::from_str(&body.pubkey).map_err(|_| Error::InvalidWalletChallenge)?;
+    let mut transaction = create_txn(&state.pool).await?;
+    if let Some(owner) = get_user_opt_by_wallet(&mut transaction, &body.pubkey).await? {
+        if owner.id != user.id {
+            return Err(Error::WalletAlreadyLinked);
+        }
+    }
+    let mut message = SiwsMessage::new(base_url.as_str(), &body.pubkey, &Nonce::generate_nonce(16));
+    message.statement = Some(format!("Link this wallet to BlockMesh account {}", user.id));
+    let expires_at = message
+        .expiration_time
+        .ok_or(Error::InvalidWalletChallenge)?;
+    let message = message.to_string();
+    create_wallet_challenge(
+        &mut transaction,
+        &user.id,
+        &body.pubkey,
+        &message,
+        expires_at,
+    )
+    .await?;
+    commit_txn(transaction).await?;
+    Ok(Json(WalletChallengeResponse {
+        message,
+        expires_at,
+    }))
+}
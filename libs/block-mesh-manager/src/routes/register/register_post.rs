@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use anyhow::anyhow;
 use axum::extract::State;
+use axum::http::HeaderMap;
 use axum::response::Redirect;
 use axum::{Extension, Form};
 use axum_login::AuthSession;
@@ -27,13 +28,14 @@ use crate::database::user::update_user_invited_by::update_user_invited_by;
 use crate::errors::error::Error;
 use crate::middlewares::authentication::{Backend, Credentials};
 use crate::startup::application::AppState;
-use crate::utils::cache_envar::get_envar;
+use crate::utils::email_locale::email_locale;
 
 #[tracing::instrument(name = "register_post", skip_all)]
 pub async fn handler(
     Extension(pool): Extension<PgPool>,
     Extension(mut auth): Extension<AuthSession<Backend>>,
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
     Form(form): Form<RegisterForm>,
 ) -> Result<Redirect, Error> {
     let mut transaction = create_txn(&pool).await?;
@@ -137,17 +139,9 @@ pub async fn handler(
     auth.login(&session)
         .await
         .map_err(|_| Error::Auth(anyhow!("Login failed").to_string()))?;
-    let email_mode = get_envar("EMAIL_MODE").await;
-    if email_mode == "AWS" {
-        let _ = state
-            .email_client
-            .send_confirmation_email_aws(&email, nonce_secret.expose_secret())
-            .await;
-    } else {
-        let _ = state
-            .email_client
-            .send_confirmation_email_gmail(&email, nonce_secret.expose_secret())
-            .await;
-    }
+    let _ = state
+        .email_client
+        .send_confirmation_email(&email, nonce_secret.expose_secret(), email_locale(&headers))
+        .await;
     Ok(Redirect::to("/ui/dashboard"))
 }
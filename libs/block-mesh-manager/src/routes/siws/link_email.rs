@@ -0,0 +1,75 @@
+use crate::database::nonce::get_nonce_by_user_id::get_nonce_by_user_id;
+use crate::database::user::get_user_by_email::get_user_opt_by_email;
+use crate::database::user::update_user_email::update_user_email;
+use crate::errors::error::Error;
+use crate::middlewares::authentication::{Backend, SessionUser};
+use crate::startup::application::AppState;
+use crate::utils::email_locale::email_locale;
+use axum::extract::State;
+use axum::http::HeaderMap;
+use axum::response::IntoResponse;
+use axum::{Extension, Json};
+use axum_login::AuthSession;
+use bcrypt::{hash, DEFAULT_COST};
+use block_mesh_common::interfaces::server_api::LinkEmailRequest;
+use block_mesh_common::siws::is_wallet_placeholder_email;
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+use http::StatusCode;
+use std::sync::Arc;
+use validator::validate_email;
+
+/// Gives an account created by wallet sign in a real email and a password, after which
+/// both login methods work.
+#[tracing::instrument(name = "link_email", skip_all)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Extension(mut auth): Extension<AuthSession<Backend>>,
+    headers: HeaderMap,
+    Json(body): Json<LinkEmailRequest>,
+) -> Result<impl IntoResponse, Error> {
+    let user = auth.user.clone().ok_or(Error::UserNotFound)?;
+    if !is_wallet_placeholder_email(&user.email) {
+        return Err(Error::EmailAlreadyLinked);
+    }
+    let email = body.email.trim().to_ascii_lowercase();
+    if !validate_email(email.clone()) || is_wallet_placeholder_email(&email) {
+        return Err(Error::InvalidLinkEmailRequest(
+            "Please check if email you inserted is correct".to_string(),
+        ));
+    }
+    if body.password.contains(' ')
+        || body.password.chars().all(char::is_alphanumeric)
+        || body.password.len() < 8
+    {
+        return Err(Error::InvalidLinkEmailRequest(
+            "Password must be at least 8 characters long, contain a special character and no spaces"
+                .to_string(),
+        ));
+    }
+    let mut transaction = create_txn(&state.pool).await?;
+    if get_user_opt_by_email(&mut transaction, &email)
+        .await?
+        .is_some()
+    {
+        return Err(Error::EmailAlreadyLinked);
+    }
+    let hashed_password = hash(body.password, DEFAULT_COST)?;
+    update_user_email(&mut transaction, user.id, &email, &hashed_password).await?;
+    let nonce = get_nonce_by_user_id(&mut transaction, &user.id)
+        .await?
+        .ok_or(Error::NonceNotFound)?;
+    commit_txn(transaction).await?;
+    // the session carries the email, refresh it so caches keyed by email line up
+    auth.login(&SessionUser {
+        id: user.id,
+        email: email.clone(),
+        nonce: user.nonce,
+    })
+    .await
+    .map_err(|e| Error::Auth(e.to_string()))?;
+    let _ = state
+        .email_client
+        .send_confirmation_email(&email, nonce.nonce.expose_secret(), email_locale(&headers))
+        .await;
+    Ok(StatusCode::OK)
+}
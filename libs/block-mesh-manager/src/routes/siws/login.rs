@@ -0,0 +1,207 @@
+use crate::database::api_token::create_api_token::create_api_token;
+use crate::database::invite_code::create_invite_code::create_invite_code;
+use crate::database::invite_code::get_user_opt_by_invited_code::get_user_opt_by_invited_code;
+use crate::database::nonce::create_nonce::create_nonce;
+use crate::database::nonce::get_nonce_by_user_id::get_nonce_by_user_id;
+use crate::database::perks::add_perk_to_user::add_perk_to_user;
+use crate::database::two_factor::get_user_totp::get_user_totp;
+use crate::database::uptime_report::create_uptime_report::create_uptime_report;
+use crate::database::user::create_user::create_user;
+use crate::database::user::update_user_invited_by::update_user_invited_by;
+use crate::database::wallet::insert_wallet_link_event::{
+    insert_wallet_link_event, wallet_bonus_already_granted,
+};
+use crate::domain::perk::PerkName;
+use crate::domain::wallet_link_event::WalletLinkAction;
+use crate::errors::error::Error;
+use crate::middlewares::authentication::{
+    Backend, PendingTwoFactor, SessionUser, PENDING_TWO_FACTOR_KEY,
+};
+use crate::startup::application::AppState;
+use axum::extract::State;
+use axum::{Extension, Json};
+use axum_login::AuthSession;
+use bcrypt::{hash, DEFAULT_COST};
+use block_mesh_common::interfaces::server_api::{SiwsLoginRequest, SiwsLoginResponse};
+use block_mesh_common::siws::wallet_placeholder_email;
+use block_mesh_manager_database_domain::domain::get_reward_rules::get_reward_rules;
+use block_mesh_manager_database_domain::domain::get_user_opt_by_id::get_user_opt_by_id;
+use block_mesh_manager_database_domain::domain::get_user_opt_by_wallet::get_user_opt_by_wallet;
+use block_mesh_manager_database_domain::domain::nonce::Nonce;
+use block_mesh_manager_database_domain::domain::prep_user::prep_user;
+use block_mesh_manager_database_domain::domain::siws_auth::verify_siws;
+use chrono::Utc;
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+use secret::Secret;
+use sqlx::{Postgres, Transaction};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Same bootstrap as `register_post`, with the wallet as identity and an unusable password
+/// until an email is linked. A wallet unlinked from another account earns no second bonus.
+#[tracing::instrument(name = "create_wallet_user", skip(transaction))]
+async fn create_wallet_user(
+    transaction: &mut Transaction<'_, Postgres>,
+    pubkey: &str,
+    invited_by: Uuid,
+) -> Result<Uuid, Error> {
+    let password = hash(Nonce::generate_nonce(32), DEFAULT_COST)?;
+    let user_id = create_user(
+        transaction,
+        Some(pubkey.to_string()),
+        &wallet_placeholder_email(pubkey),
+        &password,
+    )
+    .await
+    .map_err(Error::from)?;
+    create_nonce(
+        transaction,
+        &user_id,
+        &Secret::from(Nonce::generate_nonce(16)),
+    )
+    .await?;
+    create_api_token(transaction, user_id).await?;
+    create_invite_code(transaction, user_id, Uuid::new_v4().to_string()).await?;
+    create_uptime_report(transaction, &user_id, &None).await?;
+    prep_user(transaction, &user_id).await?;
+    update_user_invited_by(transaction, user_id, invited_by).await?;
+    let bonus_granted = wallet_bonus_already_granted(transaction, &user_id, pubkey).await?;
+    insert_wallet_link_event(transaction, &user_id, pubkey, WalletLinkAction::Link, None).await?;
+    let rules = get_reward_rules(transaction).await?;
+    let perk = rules.perk_rule(Utc::now().date_naive(), &PerkName::Wallet.to_string());
+    add_perk_to_user(
+        transaction,
+        user_id,
+        PerkName::Wallet,
+        perk.multiplier,
+        if bonus_granted {
+            0.0
+        } else {
+            perk.one_time_bonus
+        },
+        serde_json::from_str("{}").unwrap(),
+    )
+    .await?;
+    Ok(user_id)
+}
+
+#[tracing::instrument(name = "siws_login", skip_all, fields(pubkey = body.pubkey))]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Extension(mut auth): Extension<AuthSession<Backend>>,
+    Json(body): Json<SiwsLoginRequest>,
+) -> Result<Json<SiwsLoginResponse>, Error> {
+    let mut transaction = create_txn(&state.pool).await?;
+    verify_siws(
+        &mut transaction,
+        &body.pubkey,
+        &body.message,
+        &body.signature,
+    )
+    .await?
+    .map_err(|e| Error::WalletSignIn(e.to_string()))?;
+    let (user_id, created) = match get_user_opt_by_wallet(&mut transaction, &body.pubkey).await? {
+        Some(user) => (user.id, false),
+        None => {
+            let invite_code = body
+                .invite_code
+                .as_deref()
+                .map(str::trim)
+                .filter(|code| !code.is_empty())
+                .ok_or(Error::InviteCodeNotFound)?;
+            let invited_by =
+                get_user_opt_by_invited_code(&mut transaction, invite_code.to_string())
+                    .await?
+                    .ok_or(Error::InviteCodeNotFound)?;
+            let user_id =
+                create_wallet_user(&mut transaction, &body.pubkey, invited_by.user_id).await?;
+            (user_id, true)
+        }
+    };
+    let user = get_user_opt_by_id(&mut transaction, &user_id)
+        .await?
+        .ok_or(Error::UserNotFound)?;
+    let nonce = get_nonce_by_user_id(&mut transaction, &user_id)
+        .await?
+        .ok_or(Error::NonceNotFound)?;
+    let two_factor_required = get_user_totp(&mut transaction, &user_id)
+        .await?
+        .map(|user_totp| user_totp.enabled)
+        .unwrap_or(false);
+    // the nonce is burned and the account exists, commit before touching the session
+    commit_txn(transaction).await?;
+    let session_user = SessionUser {
+        id: user.id,
+        email: user.email,
+        nonce: nonce.nonce.as_ref().to_string(),
+    };
+    if two_factor_required {
+        auth.session
+            .insert(PENDING_TWO_FACTOR_KEY, PendingTwoFactor::new(session_user))
+            .await
+            .map_err(|e| Error::Auth(e.to_string()))?;
+    } else {
+        auth.login(&session_user)
+            .await
+            .map_err(|e| Error::Auth(e.to_string()))?;
+    }
+    Ok(Json(SiwsLoginResponse {
+        created,
+        two_factor_required,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::test_support::{insert_user, insert_user_with_wallet};
+    use crate::database::wallet::unlink_user_wallet::unlink_user_wallet;
+    use sqlx::PgPool;
+
+    async fn wallet_bonus(pool: &PgPool, user_id: Uuid) -> anyhow::Result<f64> {
+        Ok(
+            sqlx::query_scalar("SELECT one_time_bonus FROM perks WHERE user_id = $1 AND name = $2")
+                .bind(user_id)
+                .bind(PerkName::Wallet.to_string())
+                .fetch_one(pool)
+                .await?,
+        )
+    }
+
+    #[sqlx::test]
+    async fn test_unlinked_wallet_earns_no_second_bonus(pool: PgPool) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"UPDATE reward_rules SET perks = '{"wallet": {"multiplier": 1.1, "one_time_bonus": 1000.0}}'"#,
+        )
+        .execute(&pool)
+        .await?;
+        let inviter = insert_user(&pool).await?;
+        // an email account links the wallet, then drops it
+        let first = insert_user_with_wallet(&pool, Some("wallet")).await?;
+        let mut transaction = pool.begin().await?;
+        insert_wallet_link_event(
+            &mut transaction,
+            &first,
+            "wallet",
+            WalletLinkAction::Link,
+            None,
+        )
+        .await?;
+        unlink_user_wallet(&mut transaction, &first).await?;
+        insert_wallet_link_event(
+            &mut transaction,
+            &first,
+            "wallet",
+            WalletLinkAction::Unlink,
+            None,
+        )
+        .await?;
+        let second = create_wallet_user(&mut transaction, "wallet", inviter).await?;
+        let fresh = create_wallet_user(&mut transaction, "other", inviter).await?;
+        transaction.commit().await?;
+
+        assert_eq!(wallet_bonus(&pool, second).await?, 0.0);
+        assert_eq!(wallet_bonus(&pool, fresh).await?, 1000.0);
+        Ok(())
+    }
+}
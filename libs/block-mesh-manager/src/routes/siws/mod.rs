@@ -0,0 +1,3 @@
+pub mod link_email;
+pub mod login;
+pub mod nonce;
@@ -0,0 +1,13 @@
+use crate::errors::error::Error;
+use crate::startup::application::AppState;
+use axum::extract::State;
+use axum::Json;
+use block_mesh_common::interfaces::server_api::SiwsNonceResponse;
+use block_mesh_manager_database_domain::domain::siws_nonce::create_siws_nonce;
+use std::sync::Arc;
+
+#[tracing::instrument(name = "siws_nonce", skip_all)]
+pub async fn handler(State(state): State<Arc<AppState>>) -> Result<Json<SiwsNonceResponse>, Error> {
+    let (nonce, expires_at) = create_siws_nonce(&state.pool).await?;
+    Ok(Json(SiwsNonceResponse { nonce, expires_at }))
+}
@@ -2,28 +2,12 @@ use crate::database::task::count_user_tasks_in_period::count_user_tasks_in_perio
 use crate::database::task::create_task::create_task;
 use crate::errors::error::Error;
 use axum::{Extension, Json};
+use block_mesh_common::interfaces::server_api::{CreateTaskRequest, CreateTaskResponse};
+use block_mesh_manager_database_domain::domain::api_token::ApiTokenScope;
 use block_mesh_manager_database_domain::domain::find_token::find_token;
 use block_mesh_manager_database_domain::domain::get_user_opt_by_id::get_user_opt_by_id;
 use block_mesh_manager_database_domain::domain::task::TaskMethod;
-use serde::{Deserialize, Serialize};
-use serde_json::Value;
 use sqlx::PgPool;
-use uuid::Uuid;
-
-#[derive(Serialize, Deserialize, Debug)]
-pub struct CreateTaskRequest {
-    pub url: String,
-    pub method: TaskMethod,
-    pub headers: Option<Value>,
-    pub body: Option<Value>,
-    pub api_token: Uuid,
-    pub email: String,
-}
-
-#[derive(Serialize, Deserialize, Debug)]
-pub struct CreateTaskResponse {
-    pub task_id: Uuid,
-}
 
 #[tracing::instrument(name = "create_task_with_token", skip_all)]
 pub async fn handler(
@@ -31,7 +15,7 @@ pub async fn handler(
     Json(body): Json<CreateTaskRequest>,
 ) -> Result<Json<CreateTaskResponse>, Error> {
     let mut transaction = pool.begin().await.map_err(Error::from)?;
-    let api_token = find_token(&mut transaction, &body.api_token)
+    let api_token = find_token(&mut transaction, &body.api_token, ApiTokenScope::TaskCreate)
         .await?
         .ok_or(Error::ApiTokenNotFound)?;
     let user = get_user_opt_by_id(&mut transaction, &api_token.user_id)
@@ -50,7 +34,7 @@ pub async fn handler(
         &mut transaction,
         &user.id,
         &body.url,
-        &body.method,
+        &TaskMethod::from(body.method),
         body.headers,
         body.body,
     )
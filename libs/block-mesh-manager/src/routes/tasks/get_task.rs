@@ -9,9 +9,11 @@ use anyhow::Context;
 use axum::extract::State;
 use axum::{Extension, Json};
 use block_mesh_common::interfaces::server_api::{GetTaskRequest, GetTaskResponse};
+use block_mesh_manager_database_domain::domain::api_token::ApiTokenScope;
 use block_mesh_manager_database_domain::domain::create_daily_stat::create_daily_stat;
 use block_mesh_manager_database_domain::domain::find_token::find_token;
 use block_mesh_manager_database_domain::domain::get_user_opt_by_id::get_user_opt_by_id;
+use block_mesh_manager_database_domain::domain::task::without_credential_headers;
 use block_mesh_manager_database_domain::domain::task::TaskStatus;
 use block_mesh_manager_database_domain::domain::task_limit::TaskLimit;
 use block_mesh_manager_database_domain::domain::update_task_assigned::update_task_assigned;
@@ -28,7 +30,7 @@ pub async fn handler(
     Json(body): Json<GetTaskRequest>,
 ) -> Result<Json<Option<GetTaskResponse>>, Error> {
     let mut transaction = create_txn(&pool).await?;
-    let api_token = find_token(&mut transaction, &body.api_token)
+    let api_token = find_token(&mut transaction, &body.api_token, ApiTokenScope::NodeReport)
         .await?
         .ok_or(Error::ApiTokenNotFound)?;
     let user = get_user_opt_by_id(&mut transaction, &api_token.user_id)
@@ -68,7 +70,7 @@ pub async fn handler(
             id: task.id,
             url: task.url,
             method: task.method.to_string(),
-            headers: task.headers,
+            headers: without_credential_headers(task.headers),
             body: task.body,
         })));
     }
@@ -87,7 +89,7 @@ pub async fn handler(
         id: task.id,
         url: task.url,
         method: task.method.to_string(),
-        headers: task.headers,
+        headers: without_credential_headers(task.headers),
         body: task.body,
     })))
 }
@@ -0,0 +1,40 @@
+use crate::database::task::get_task_by_id::get_task_by_user_id;
+use crate::errors::error::Error;
+use axum::{Extension, Json};
+use block_mesh_common::interfaces::server_api::{GetTaskResultRequest, GetTaskResultResponse};
+use block_mesh_manager_database_domain::domain::api_token::ApiTokenScope;
+use block_mesh_manager_database_domain::domain::find_token::find_token;
+use block_mesh_manager_database_domain::domain::get_user_opt_by_id::get_user_opt_by_id;
+use sqlx::PgPool;
+
+/// Polled by the creator of a task created with `create_task_with_token` until a node submits it.
+#[tracing::instrument(name = "get_task_result", skip_all)]
+pub async fn handler(
+    Extension(pool): Extension<PgPool>,
+    Json(body): Json<GetTaskResultRequest>,
+) -> Result<Json<GetTaskResultResponse>, Error> {
+    let mut transaction = pool.begin().await.map_err(Error::from)?;
+    let api_token = find_token(&mut transaction, &body.api_token, ApiTokenScope::TaskCreate)
+        .await?
+        .ok_or(Error::ApiTokenNotFound)?;
+    let user = get_user_opt_by_id(&mut transaction, &api_token.user_id)
+        .await?
+        .ok_or_else(|| Error::UserNotFound)?;
+    if user.email.to_ascii_lowercase() != body.email.to_ascii_lowercase() {
+        return Err(Error::UserNotFound);
+    }
+    let task = get_task_by_user_id(&mut transaction, &body.task_id)
+        .await
+        .map_err(Error::from)?
+        .ok_or(Error::TaskNotFound)?;
+    transaction.commit().await.map_err(Error::from)?;
+    if task.user_id != user.id {
+        return Err(Error::NotYourTask);
+    }
+    Ok(Json(GetTaskResultResponse {
+        task_id: task.id,
+        status: task.status.to_string(),
+        response_code: task.response_code,
+        response_raw: task.response_raw,
+    }))
+}
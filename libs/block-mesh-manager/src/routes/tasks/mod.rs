@@ -2,6 +2,7 @@ pub mod create_task;
 pub mod create_task_post;
 pub mod create_task_with_token;
 pub mod get_task;
+pub mod get_task_result;
 pub mod submit_task;
 pub mod tasks_table;
 pub mod view_task;
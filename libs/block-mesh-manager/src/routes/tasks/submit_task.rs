@@ -5,16 +5,30 @@ use axum::Json;
 use block_mesh_common::interfaces::server_api::{
     HandlerMode, SubmitTaskRequest, SubmitTaskResponse,
 };
+use block_mesh_manager_database_domain::domain::api_token::ApiTokenScope;
+use block_mesh_manager_database_domain::domain::node_auth::{authenticate_node, NodeCredentials};
 use block_mesh_manager_database_domain::domain::submit_task_content::submit_task_content;
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
 use std::sync::Arc;
 
 #[tracing::instrument(name = "submit_task", skip_all)]
 pub async fn handler(
     State(state): State<Arc<AppState>>,
+    credentials: NodeCredentials,
     Query(query): Query<SubmitTaskRequest>,
     request: Request,
 ) -> Result<Json<SubmitTaskResponse>, Error> {
-    submit_task_content(&state.pool, query, Some(request), HandlerMode::Http)
-        .await
-        .map_err(Error::from)
+    let mut transaction = create_txn(&state.pool).await?;
+    let identity =
+        authenticate_node(&mut transaction, &credentials, ApiTokenScope::NodeReport).await?;
+    commit_txn(transaction).await?;
+    submit_task_content(
+        &state.pool,
+        &identity,
+        query,
+        Some(request),
+        HandlerMode::Http,
+    )
+    .await
+    .map_err(Error::from)
 }
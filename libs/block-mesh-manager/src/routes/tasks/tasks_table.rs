@@ -10,7 +10,9 @@ use block_mesh_common::constants::{
     BLOCK_MESH_LANDING_PAGE_IMAGE, BLOCK_MESH_LOGO, BLOCK_MESH_SUPPORT_CHAT,
     BLOCK_MESH_SUPPORT_EMAIL, BLOCK_MESH_TWITTER,
 };
-use block_mesh_manager_database_domain::domain::task::{Task, TaskMethod, TaskStatus};
+use block_mesh_manager_database_domain::domain::task::{
+    without_credential_headers, Task, TaskMethod, TaskStatus,
+};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -70,7 +72,7 @@ impl From<Task> for TaskForTemplate {
             user_id: task.user_id,
             url: task.url,
             method: task.method,
-            headers: OptionWrapper(task.headers.unwrap_or(Value::Null)),
+            headers: OptionWrapper(without_credential_headers(task.headers).unwrap_or(Value::Null)),
             body: OptionWrapper(task.body.unwrap_or(Value::Null)),
             assigned_user_id: OptionWrapper(task.assigned_user_id.unwrap_or(Uuid::nil())),
             status: task.status,
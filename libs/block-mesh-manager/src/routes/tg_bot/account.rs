@@ -0,0 +1,27 @@
+use crate::database::tg_bot::get_user_id_by_tg_id::get_user_id_by_tg_id;
+use crate::errors::error::Error;
+use crate::startup::application::AppState;
+use crate::utils::tg_bot_account::{tg_bot_account, verify_tg_bot_secret};
+use axum::extract::State;
+use axum::http::HeaderMap;
+use axum::Json;
+use block_mesh_common::interfaces::server_api::{TgBotAccountRequest, TgBotAccountResponse};
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+use std::sync::Arc;
+
+/// Tier and stats of the account linked to a Telegram user, for the privacy bot's quota and `/stats`.
+#[tracing::instrument(name = "tg_bot_account", skip_all)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(body): Json<TgBotAccountRequest>,
+) -> Result<Json<TgBotAccountResponse>, Error> {
+    verify_tg_bot_secret(&headers).await?;
+    let mut transaction = create_txn(&state.pool).await?;
+    let user_id = get_user_id_by_tg_id(&mut transaction, body.tg_id)
+        .await?
+        .ok_or(Error::TgBotAccountNotLinked)?;
+    let account = tg_bot_account(&mut transaction, &user_id).await?;
+    commit_txn(transaction).await?;
+    Ok(Json(account))
+}
@@ -0,0 +1,31 @@
+use crate::database::tg_bot::link_tg_bot_account::link_tg_bot_account;
+use crate::errors::error::Error;
+use crate::startup::application::AppState;
+use crate::utils::tg_bot_account::{tg_bot_account, verify_tg_bot_secret};
+use axum::extract::State;
+use axum::http::HeaderMap;
+use axum::Json;
+use block_mesh_common::interfaces::server_api::{TgBotAccountResponse, TgBotLinkRequest};
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+use std::sync::Arc;
+
+/// Called by the privacy bot when a user sends `/link <code>`.
+#[tracing::instrument(name = "tg_bot_link", skip_all)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(body): Json<TgBotLinkRequest>,
+) -> Result<Json<TgBotAccountResponse>, Error> {
+    verify_tg_bot_secret(&headers).await?;
+    let mut transaction = create_txn(&state.pool).await?;
+    let user_id = link_tg_bot_account(
+        &mut transaction,
+        &body.code.trim().to_ascii_uppercase(),
+        body.tg_id,
+    )
+    .await?
+    .ok_or(Error::InvalidTgBotLinkCode)?;
+    let account = tg_bot_account(&mut transaction, &user_id).await?;
+    commit_txn(transaction).await?;
+    Ok(Json(account))
+}
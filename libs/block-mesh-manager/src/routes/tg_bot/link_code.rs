@@ -0,0 +1,28 @@
+use crate::database::tg_bot::set_tg_bot_link_code::set_tg_bot_link_code;
+use crate::errors::error::Error;
+use crate::middlewares::authentication::Backend;
+use crate::startup::application::AppState;
+use axum::extract::State;
+use axum::{Extension, Json};
+use axum_login::AuthSession;
+use block_mesh_common::interfaces::server_api::TgBotLinkCodeResponse;
+use block_mesh_common::tg_bot::{TG_BOT_LINK_CODE_LENGTH, TG_BOT_LINK_CODE_TTL_MINUTES};
+use block_mesh_manager_database_domain::domain::nonce::Nonce;
+use chrono::{Duration, Utc};
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+use std::sync::Arc;
+
+/// One-time code for the dashboard, the user sends `/link <code>` to the privacy bot.
+#[tracing::instrument(name = "tg_bot_link_code", skip_all)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthSession<Backend>>,
+) -> Result<Json<TgBotLinkCodeResponse>, Error> {
+    let user = auth.user.ok_or(Error::UserNotFound)?;
+    let code = Nonce::generate_nonce(TG_BOT_LINK_CODE_LENGTH);
+    let expires_at = Utc::now() + Duration::minutes(TG_BOT_LINK_CODE_TTL_MINUTES);
+    let mut transaction = create_txn(&state.pool).await?;
+    set_tg_bot_link_code(&mut transaction, &user.id, &code, expires_at).await?;
+    commit_txn(transaction).await?;
+    Ok(Json(TgBotLinkCodeResponse { code, expires_at }))
+}
@@ -0,0 +1,4 @@
+pub mod account;
+pub mod link;
+pub mod link_code;
+pub mod unlink;
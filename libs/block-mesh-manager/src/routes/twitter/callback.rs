@@ -12,6 +12,8 @@ use block_mesh_common::constants::BLOCKMESH_TWITTER_USER_ID;
 use block_mesh_common::routes_enum::RoutesEnum;
 use block_mesh_manager_database_domain::domain::aggregate::AggregateName;
 use block_mesh_manager_database_domain::domain::get_or_create_aggregate_by_user_and_name::get_or_create_aggregate_by_user_and_name;
+use block_mesh_manager_database_domain::domain::get_reward_rules::get_reward_rules;
+use chrono::Utc;
 use http::StatusCode;
 use reqwest::ClientBuilder;
 use serde::{Deserialize, Serialize};
@@ -92,12 +94,14 @@ pub async fn callback(
         let data = user.into_data().unwrap();
         let follow_data = get_following(data.id.as_u64()).await?;
         if follow_data.following {
+            let rules = get_reward_rules(&mut transaction).await?;
+            let perk = rules.perk_rule(Utc::now().date_naive(), &PerkName::Twitter.to_string());
             add_perk_to_user(
                 &mut transaction,
                 user_id.unwrap(),
                 PerkName::Twitter,
-                1.0,
-                500.0,
+                perk.multiplier,
+                perk.one_time_bonus,
                 serde_json::to_value(&follow_data).unwrap(),
             )
             .await?;
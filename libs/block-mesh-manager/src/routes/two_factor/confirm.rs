@@ -0,0 +1,49 @@
+use crate::database::two_factor::enable_user_totp::enable_user_totp;
+use crate::database::two_factor::get_user_totp::get_user_totp;
+use crate::database::two_factor::replace_recovery_codes::replace_recovery_codes;
+use crate::errors::error::Error;
+use crate::middlewares::authentication::Backend;
+use crate::startup::application::AppState;
+use crate::utils::totp::{generate_recovery_codes, hash_recovery_code, verify_totp};
+use axum::extract::State;
+use axum::{Extension, Json};
+use axum_login::AuthSession;
+use block_mesh_common::interfaces::server_api::{RecoveryCodesResponse, TwoFactorCodeRequest};
+use chrono::Utc;
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+use std::sync::Arc;
+
+/// Enables 2FA once the user proves the authenticator app is set up, recovery codes are only
+/// returned here and when regenerated.
+#[tracing::instrument(name = "two_factor_confirm", skip_all)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthSession<Backend>>,
+    Json(body): Json<TwoFactorCodeRequest>,
+) -> Result<Json<RecoveryCodesResponse>, Error> {
+    let user = auth.user.ok_or(Error::UserNotFound)?;
+    let mut transaction = create_txn(&state.pool).await?;
+    let user_totp = get_user_totp(&mut transaction, &user.id)
+        .await?
+        .ok_or(Error::InvalidTwoFactorRequest)?;
+    if user_totp.enabled {
+        return Err(Error::InvalidTwoFactorRequest);
+    }
+    let step = verify_totp(
+        &user_totp.secret,
+        &body.code,
+        Utc::now().timestamp(),
+        user_totp.last_used_step,
+    )
+    .ok_or(Error::InvalidTwoFactorCode)?;
+    enable_user_totp(&mut transaction, &user.id, step).await?;
+    let recovery_codes = generate_recovery_codes();
+    let pepper = user.id.to_string();
+    let code_hashes: Vec<String> = recovery_codes
+        .iter()
+        .map(|code| hash_recovery_code(code, &pepper))
+        .collect();
+    replace_recovery_codes(&mut transaction, &user.id, &code_hashes).await?;
+    commit_txn(transaction).await?;
+    Ok(Json(RecoveryCodesResponse { recovery_codes }))
+}
@@ -0,0 +1,31 @@
+use crate::database::two_factor::delete_user_totp::delete_user_totp;
+use crate::database::two_factor::verify_second_factor::verify_second_factor;
+use crate::errors::error::Error;
+use crate::middlewares::authentication::Backend;
+use crate::startup::application::AppState;
+use axum::extract::State;
+use axum::response::IntoResponse;
+use axum::{Extension, Json};
+use axum_login::AuthSession;
+use block_mesh_common::interfaces::server_api::TwoFactorCodeRequest;
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+use http::StatusCode;
+use std::sync::Arc;
+
+#[tracing::instrument(name = "two_factor_disable", skip_all)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthSession<Backend>>,
+    Json(body): Json<TwoFactorCodeRequest>,
+) -> Result<impl IntoResponse, Error> {
+    let user = auth.user.ok_or(Error::UserNotFound)?;
+    let mut transaction = create_txn(&state.pool).await?;
+    if !verify_second_factor(&mut transaction, &user.id, &body.code).await? {
+        // keep the failed attempt counted
+        commit_txn(transaction).await?;
+        return Err(Error::InvalidTwoFactorCode);
+    }
+    delete_user_totp(&mut transaction, &user.id).await?;
+    commit_txn(transaction).await?;
+    Ok(StatusCode::OK)
+}
@@ -0,0 +1,31 @@
+use crate::database::two_factor::upsert_user_totp::upsert_user_totp;
+use crate::errors::error::Error;
+use crate::middlewares::authentication::Backend;
+use crate::startup::application::AppState;
+use crate::utils::totp::{generate_secret, otpauth_uri};
+use axum::extract::State;
+use axum::{Extension, Json};
+use axum_login::AuthSession;
+use block_mesh_common::interfaces::server_api::TwoFactorEnrollResponse;
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+use std::sync::Arc;
+
+const ISSUER: &str = "BlockMesh";
+
+#[tracing::instrument(name = "two_factor_enroll", skip_all)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthSession<Backend>>,
+) -> Result<Json<TwoFactorEnrollResponse>, Error> {
+    let user = auth.user.ok_or(Error::UserNotFound)?;
+    let secret = generate_secret();
+    let mut transaction = create_txn(&state.pool).await?;
+    if !upsert_user_totp(&mut transaction, &user.id, &secret).await? {
+        return Err(Error::InvalidTwoFactorRequest);
+    }
+    commit_txn(transaction).await?;
+    Ok(Json(TwoFactorEnrollResponse {
+        otpauth_uri: otpauth_uri(&secret, &user.email, ISSUER),
+        secret,
+    }))
+}
@@ -0,0 +1,7 @@
+pub mod confirm;
+pub mod disable;
+pub mod enroll;
+pub mod regenerate_recovery_codes;
+pub mod status;
+pub mod two_factor_form;
+pub mod two_factor_post;
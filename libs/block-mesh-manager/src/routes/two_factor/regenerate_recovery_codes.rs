@@ -0,0 +1,36 @@
+use crate::database::two_factor::replace_recovery_codes::replace_recovery_codes;
+use crate::database::two_factor::verify_second_factor::verify_second_factor;
+use crate::errors::error::Error;
+use crate::middlewares::authentication::Backend;
+use crate::startup::application::AppState;
+use crate::utils::totp::{generate_recovery_codes, hash_recovery_code};
+use axum::extract::State;
+use axum::{Extension, Json};
+use axum_login::AuthSession;
+use block_mesh_common::interfaces::server_api::{RecoveryCodesResponse, TwoFactorCodeRequest};
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+use std::sync::Arc;
+
+#[tracing::instrument(name = "regenerate_recovery_codes", skip_all)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthSession<Backend>>,
+    Json(body): Json<TwoFactorCodeRequest>,
+) -> Result<Json<RecoveryCodesResponse>, Error> {
+    let user = auth.user.ok_or(Error::UserNotFound)?;
+    let mut transaction = create_txn(&state.pool).await?;
+    if !verify_second_factor(&mut transaction, &user.id, &body.code).await? {
+        // keep the failed attempt counted
+        commit_txn(transaction).await?;
+        return Err(Error::InvalidTwoFactorCode);
+    }
+    let recovery_codes = generate_recovery_codes();
+    let pepper = user.id.to_string();
+    let code_hashes: Vec<String> = recovery_codes
+        .iter()
+        .map(|code| hash_recovery_code(code, &pepper))
+        .collect();
+    replace_recovery_codes(&mut transaction, &user.id, &code_hashes).await?;
+    commit_txn(transaction).await?;
+    Ok(Json(RecoveryCodesResponse { recovery_codes }))
+}
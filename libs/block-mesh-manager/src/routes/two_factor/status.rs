@@ -0,0 +1,29 @@
+use crate::database::two_factor::get_user_totp::{count_recovery_codes, get_user_totp};
+use crate::errors::error::Error;
+use crate::middlewares::authentication::Backend;
+use crate::startup::application::AppState;
+use axum::extract::State;
+use axum::{Extension, Json};
+use axum_login::AuthSession;
+use block_mesh_common::interfaces::server_api::TwoFactorStatusResponse;
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+use std::sync::Arc;
+
+#[tracing::instrument(name = "two_factor_status", skip_all)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthSession<Backend>>,
+) -> Result<Json<TwoFactorStatusResponse>, Error> {
+    let user = auth.user.ok_or(Error::UserNotFound)?;
+    let mut transaction = create_txn(&state.pool).await?;
+    let enabled = get_user_totp(&mut transaction, &user.id)
+        .await?
+        .map(|user_totp| user_totp.enabled)
+        .unwrap_or(false);
+    let recovery_codes_left = count_recovery_codes(&mut transaction, &user.id).await?;
+    commit_txn(transaction).await?;
+    Ok(Json(TwoFactorStatusResponse {
+        enabled,
+        recovery_codes_left,
+    }))
+}
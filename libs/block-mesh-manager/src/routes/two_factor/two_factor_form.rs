@@ -0,0 +1,33 @@
+use crate::middlewares::authentication::{Backend, PendingTwoFactor, PENDING_TWO_FACTOR_KEY};
+use askama::Template;
+use askama_axum::IntoResponse;
+use axum::response::Redirect;
+use axum::Extension;
+use axum_login::AuthSession;
+use block_mesh_common::routes_enum::RoutesEnum;
+use chrono::Utc;
+
+#[derive(Template)]
+#[template(path = "two_factor.html")]
+struct TwoFactorTemplate {}
+
+#[tracing::instrument(name = "two_factor_form", skip_all)]
+pub async fn handler(
+    Extension(auth): Extension<AuthSession<Backend>>,
+) -> Result<impl IntoResponse, Redirect> {
+    if auth.user.is_some() {
+        return Err(Redirect::to("/ui/dashboard"));
+    }
+    let pending: Option<PendingTwoFactor> = auth
+        .session
+        .get(PENDING_TWO_FACTOR_KEY)
+        .await
+        .ok()
+        .flatten();
+    match pending {
+        Some(pending) if pending.is_usable(Utc::now()) => Ok(TwoFactorTemplate {}),
+        _ => Err(Redirect::to(
+            RoutesEnum::Static_UnAuth_Login.to_string().as_str(),
+        )),
+    }
+}
@@ -0,0 +1,71 @@
+use crate::database::two_factor::verify_second_factor::verify_second_factor;
+use crate::errors::error::Error;
+use crate::middlewares::authentication::{Backend, PendingTwoFactor, PENDING_TWO_FACTOR_KEY};
+use axum::response::Redirect;
+use axum::{Extension, Form};
+use axum_login::AuthSession;
+use block_mesh_common::interfaces::server_api::TwoFactorCodeRequest;
+use block_mesh_common::routes_enum::RoutesEnum;
+use chrono::Utc;
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+use sqlx::PgPool;
+
+#[tracing::instrument(name = "two_factor_post", skip_all)]
+pub async fn handler(
+    Extension(pool): Extension<PgPool>,
+    Extension(mut auth): Extension<AuthSession<Backend>>,
+    Form(form): Form<TwoFactorCodeRequest>,
+) -> Result<Redirect, Error> {
+    let pending: Option<PendingTwoFactor> = auth
+        .session
+        .get(PENDING_TWO_FACTOR_KEY)
+        .await
+        .map_err(|e| Error::Auth(e.to_string()))?;
+    let mut pending = match pending {
+        Some(pending) if pending.is_usable(Utc::now()) => pending,
+        _ => {
+            let _ = auth
+                .session
+                .remove::<PendingTwoFactor>(PENDING_TWO_FACTOR_KEY)
+                .await;
+            return Ok(Error::redirect(
+                400,
+                "Login Expired",
+                "The login attempt expired. Please login again.",
+                RoutesEnum::Static_UnAuth_Login.to_string().as_str(),
+            ));
+        }
+    };
+    // the session read-modify-write is not atomic, parallel requests can all pass it,
+    // this only ends the login attempt early, guesses are bounded per user in verify_second_factor
+    pending.attempts += 1;
+    auth.session
+        .insert(PENDING_TWO_FACTOR_KEY, pending.clone())
+        .await
+        .map_err(|e| Error::Auth(e.to_string()))?;
+    let mut transaction = create_txn(&pool).await?;
+    let verified = verify_second_factor(&mut transaction, &pending.user.id, &form.code).await?;
+    commit_txn(transaction).await?;
+    if !verified {
+        return Ok(Error::redirect(
+            400,
+            "Invalid Code",
+            "The code is invalid. Please try again.",
+            RoutesEnum::Static_UnAuth_TwoFactor.to_string().as_str(),
+        ));
+    }
+    let _ = auth
+        .session
+        .remove::<PendingTwoFactor>(PENDING_TWO_FACTOR_KEY)
+        .await;
+    if let Err(e) = auth.login(&pending.user).await {
+        tracing::error!("Login failed: {:?} for user {}", e, pending.user.id);
+        return Ok(Error::redirect(
+            400,
+            "Login Failed",
+            "Login failed. Please try again.",
+            RoutesEnum::Static_UnAuth_Login.to_string().as_str(),
+        ));
+    }
+    Ok(Redirect::to("/ui/dashboard"))
+}
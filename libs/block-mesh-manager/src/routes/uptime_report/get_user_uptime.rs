@@ -3,6 +3,7 @@ use crate::errors::error::Error;
 use axum::extract::Query;
 use axum::{Extension, Json};
 use block_mesh_common::interfaces::server_api::{GetUserUptimeRequest, GetUserUptimeResponse};
+use block_mesh_manager_database_domain::domain::api_token::ApiTokenScope;
 use block_mesh_manager_database_domain::domain::find_token::find_token;
 use block_mesh_manager_database_domain::domain::get_user_opt_by_id::get_user_opt_by_id;
 use http::StatusCode;
@@ -14,7 +15,7 @@ pub async fn handler(
     Query(query): Query<GetUserUptimeRequest>,
 ) -> Result<Json<GetUserUptimeResponse>, Error> {
     let mut transaction = pool.begin().await.map_err(Error::from)?;
-    let api_token = find_token(&mut transaction, &query.api_token)
+    let api_token = find_token(&mut transaction, &query.api_token, ApiTokenScope::ReadStats)
         .await?
         .ok_or(Error::ApiTokenNotFound)?;
     let user = get_user_opt_by_id(&mut transaction, &api_token.user_id)
@@ -2,13 +2,14 @@ use crate::errors::error::Error;
 use crate::startup::application::AppState;
 use crate::utils::cache_envar::get_envar;
 use anyhow::Context;
-use axum::extract::{Query, Request, State};
+use axum::extract::{Request, State};
 use axum::Json;
 use block_mesh_common::feature_flag_client::{get_flag_value_from_map, FlagValue};
-use block_mesh_common::interfaces::server_api::{
-    HandlerMode, ReportUptimeRequest, ReportUptimeResponse,
-};
+use block_mesh_common::interfaces::server_api::{HandlerMode, ReportUptimeResponse};
+use block_mesh_manager_database_domain::domain::api_token::ApiTokenScope;
+use block_mesh_manager_database_domain::domain::node_auth::{authenticate_node, NodeCredentials};
 use block_mesh_manager_database_domain::domain::report_uptime_content::report_uptime_content;
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
 use http::HeaderMap;
 use std::sync::Arc;
 
@@ -16,7 +17,7 @@ use std::sync::Arc;
 pub async fn handler(
     headers: HeaderMap,
     State(state): State<Arc<AppState>>,
-    Query(query): Query<ReportUptimeRequest>,
+    credentials: NodeCredentials,
     request: Request,
 ) -> Result<Json<ReportUptimeResponse>, Error> {
     let app_env = get_envar("APP_ENVIRONMENT").await;
@@ -32,6 +33,11 @@ pub async fn handler(
     }
     .to_string();
 
+    let mut transaction = create_txn(&state.pool).await?;
+    let identity =
+        authenticate_node(&mut transaction, &credentials, ApiTokenScope::NodeReport).await?;
+    commit_txn(transaction).await?;
+
     let polling_interval = get_flag_value_from_map(
         &state.flags,
         "polling_interval",
@@ -43,7 +49,7 @@ pub async fn handler(
     report_uptime_content(
         &state.pool,
         header_ip,
-        query,
+        &identity,
         Some(request),
         HandlerMode::Http,
         polling_interval,
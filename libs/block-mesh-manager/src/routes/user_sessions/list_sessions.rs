@@ -0,0 +1,43 @@
+use crate::database::user_session::get_user_sessions::get_user_sessions;
+use crate::errors::error::Error;
+use crate::middlewares::authentication::{Backend, SESSION_INACTIVITY_DAYS};
+use crate::middlewares::user_session::USER_SESSION_KEY;
+use crate::startup::application::AppState;
+use axum::extract::State;
+use axum::{Extension, Json};
+use axum_login::AuthSession;
+use block_mesh_common::interfaces::server_api::UserSessionInfo;
+use chrono::{Duration, Utc};
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[tracing::instrument(name = "list_user_sessions", skip_all)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthSession<Backend>>,
+) -> Result<Json<Vec<UserSessionInfo>>, Error> {
+    let user = auth.user.ok_or(Error::UserNotFound)?;
+    let current: Option<Uuid> = auth.session.get(USER_SESSION_KEY).await.ok().flatten();
+    let mut transaction = create_txn(&state.pool).await?;
+    let user_sessions = get_user_sessions(
+        &mut transaction,
+        &user.id,
+        Utc::now() - Duration::days(SESSION_INACTIVITY_DAYS),
+    )
+    .await?;
+    commit_txn(transaction).await?;
+    Ok(Json(
+        user_sessions
+            .into_iter()
+            .map(|user_session| UserSessionInfo {
+                current: current == Some(user_session.id),
+                id: user_session.id,
+                ip: user_session.ip,
+                user_agent: user_session.user_agent,
+                created_at: user_session.created_at,
+                last_seen_at: user_session.last_seen_at,
+            })
+            .collect(),
+    ))
+}
@@ -0,0 +1,2 @@
+pub mod list_sessions;
+pub mod revoke_session;
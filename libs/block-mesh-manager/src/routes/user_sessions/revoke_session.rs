@@ -0,0 +1,29 @@
+use crate::database::user_session::revoke_user_session::revoke_user_session;
+use crate::errors::error::Error;
+use crate::middlewares::authentication::Backend;
+use crate::middlewares::user_session::forget_user_session;
+use crate::startup::application::AppState;
+use axum::extract::State;
+use axum::response::IntoResponse;
+use axum::{Extension, Json};
+use axum_login::AuthSession;
+use block_mesh_common::interfaces::server_api::UserSessionIdRequest;
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+use http::StatusCode;
+use std::sync::Arc;
+
+#[tracing::instrument(name = "revoke_user_session", skip_all)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthSession<Backend>>,
+    Json(body): Json<UserSessionIdRequest>,
+) -> Result<impl IntoResponse, Error> {
+    let user = auth.user.ok_or(Error::UserNotFound)?;
+    let mut transaction = create_txn(&state.pool).await?;
+    if !revoke_user_session(&mut transaction, &user.id, &body.id).await? {
+        return Err(Error::UserSessionNotFound);
+    }
+    commit_txn(transaction).await?;
+    forget_user_session(&state.redis, &user.id, &body.id).await;
+    Ok(StatusCode::OK)
+}
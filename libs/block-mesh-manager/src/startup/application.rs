@@ -1,13 +1,15 @@
 use crate::configuration::settings::Settings;
 use crate::emails::email_client::EmailClient;
 use crate::middlewares::authentication::{authentication_layer, Backend};
+use crate::middlewares::rate_limit::rate_limit_layer;
+use crate::middlewares::user_session::track_user_session;
 use crate::routes::twitter::context::Oauth2Ctx;
 use crate::startup::routers::api_router::get_api_router;
 use crate::startup::routers::leptos_router::get_leptos_router;
 use crate::startup::routers::static_auth_router::get_static_auth_router;
 use crate::startup::routers::static_un_auth_router::get_static_un_auth_router;
 use axum::extract::Request;
-use axum::{Extension, Router};
+use axum::{middleware, Extension, Router};
 use axum_login::login_required;
 use block_mesh_common::feature_flag_client::FlagValue;
 use dashmap::DashMap;
@@ -28,7 +30,6 @@ use block_mesh_common::env::env_var;
 use block_mesh_common::env::get_env_var_or_panic::get_env_var_or_panic;
 use block_mesh_common::interfaces::server_api::{CheckTokenResponseMap, GetTokenResponseMap};
 use tokio::sync::Mutex;
-use tower_governor::{governor::GovernorConfigBuilder, GovernorLayer};
 use tower_http::cors::CorsLayer;
 use tower_http::services::ServeDir;
 use tower_http::timeout::TimeoutLayer;
@@ -110,6 +111,11 @@ impl Application {
             .nest("/", un_auth_router);
 
         let backend = backend
+            .layer(middleware::from_fn_with_state(
+                app_state.clone(),
+                track_user_session,
+            ))
+            .layer(rate_limit_layer(app_state.redis.clone()))
             .layer(Extension(application_base_url))
             .layer(Extension(db_pool.clone()))
             .layer(cors)
@@ -146,45 +152,6 @@ impl Application {
         } else {
             app
         };
-        let gov_layer = env::var("GOV_LAYER")
-            .unwrap_or("false".to_string())
-            .parse()
-            .unwrap_or(false);
-
-        let app = if gov_layer {
-            let governor_conf = Arc::new(
-                GovernorConfigBuilder::default()
-                    .per_second(
-                        env::var("REQUEST_PER_SECOND")
-                            .unwrap_or("10".to_string())
-                            .parse()
-                            .unwrap_or(10),
-                    )
-                    .burst_size(
-                        env::var("REQUEST_PER_SECOND_BURST")
-                            .unwrap_or("30".to_string())
-                            .parse()
-                            .unwrap_or(30),
-                    )
-                    .finish()
-                    .unwrap(),
-            );
-            let governor_limiter = governor_conf.limiter().clone();
-            let interval = Duration::from_secs(60);
-            // a separate background task to clean up
-            std::thread::spawn(move || loop {
-                std::thread::sleep(interval);
-                tracing::info!("rate limiting storage size: {}", governor_limiter.len());
-                governor_limiter.retain_recent();
-                governor_limiter.shrink_to_fit();
-            });
-            app.layer(GovernorLayer {
-                config: governor_conf,
-            })
-        } else {
-            app
-        };
-
         let app = app
             .nest("/", leptos_router)
             .nest("/", backend)
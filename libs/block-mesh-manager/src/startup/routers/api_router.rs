@@ -11,6 +11,14 @@ pub fn get_api_router() -> Router<Arc<AppState>> {
             RoutesEnum::Api_ConnectWallet.to_string().as_str(),
             post(routes::perks::connect_wallet::handler),
         )
+        .route(
+            RoutesEnum::Api_WalletChallenge.to_string().as_str(),
+            post(routes::perks::wallet_challenge::handler),
+        )
+        .route(
+            RoutesEnum::Api_WalletUnlink.to_string().as_str(),
+            post(routes::perks::unlink_wallet::handler),
+        )
         .route(
             RoutesEnum::Api_ReportUptime.to_string().as_str(),
             post(routes::uptime_report::report_uptime::handler),
@@ -31,6 +39,14 @@ pub fn get_api_router() -> Router<Arc<AppState>> {
             RoutesEnum::Api_SubmitTask.to_string().as_str(),
             post(routes::tasks::submit_task::handler),
         )
+        .route(
+            RoutesEnum::Api_CreateTaskWithToken.to_string().as_str(),
+            post(routes::tasks::create_task_with_token::handler),
+        )
+        .route(
+            RoutesEnum::Api_GetTaskResult.to_string().as_str(),
+            post(routes::tasks::get_task_result::handler),
+        )
         .route(
             RoutesEnum::Api_GetStats.to_string().as_str(),
             post(routes::api_token::get_stats::handler),
@@ -55,6 +71,118 @@ pub fn get_api_router() -> Router<Arc<AppState>> {
             RoutesEnum::Api_ReportsQueue.to_string().as_str(),
             get(routes::admin::reports_queue::get_stats::handler)
                 .post(routes::admin::reports_queue::change_settings::handler),
+        )
+        .route(
+            RoutesEnum::Api_AdminPointsLedger.to_string().as_str(),
+            get(routes::admin::points_ledger::get_ledger::handler)
+                .post(routes::admin::points_ledger::adjust_points::handler),
+        )
+        .route(
+            RoutesEnum::Api_AdminPointsReversal.to_string().as_str(),
+            post(routes::admin::points_ledger::reverse_points::handler),
+        )
+        .route(
+            RoutesEnum::Api_AdminSybilFlags.to_string().as_str(),
+            get(routes::admin::sybil_flags::get_review_queue::handler)
+                .post(routes::admin::sybil_flags::review_flag::handler),
+        )
+        .route(
+            RoutesEnum::Api_ApiTokens.to_string().as_str(),
+            get(routes::api_token::list_tokens::handler)
+                .post(routes::api_token::create_token::handler),
+        )
+        .route(
+            RoutesEnum::Api_ApiTokensRevoke.to_string().as_str(),
+            post(routes::api_token::revoke_token::handler),
+        )
+        .route(
+            RoutesEnum::Api_ApiTokensRotate.to_string().as_str(),
+            post(routes::api_token::rotate_token::handler),
+        )
+        .route(
+            RoutesEnum::Api_TwoFactor.to_string().as_str(),
+            get(routes::two_factor::status::handler),
+        )
+        .route(
+            RoutesEnum::Api_TwoFactorEnroll.to_string().as_str(),
+            post(routes::two_factor::enroll::handler),
+        )
+        .route(
+            RoutesEnum::Api_TwoFactorConfirm.to_string().as_str(),
+            post(routes::two_factor::confirm::handler),
+        )
+        .route(
+            RoutesEnum::Api_TwoFactorDisable.to_string().as_str(),
+            post(routes::two_factor::disable::handler),
+        )
+        .route(
+            RoutesEnum::Api_TwoFactorRecoveryCodes.to_string().as_str(),
+            post(routes::two_factor::regenerate_recovery_codes::handler),
+        )
+        .route(
+            RoutesEnum::Api_Sessions.to_string().as_str(),
+            get(routes::user_sessions::list_sessions::handler),
+        )
+        .route(
+            RoutesEnum::Api_SessionsRevoke.to_string().as_str(),
+            post(routes::user_sessions::revoke_session::handler),
+        )
+        .route(
+            RoutesEnum::Api_SiwsNonce.to_string().as_str(),
+            get(routes::siws::nonce::handler),
+        )
+        .route(
+            RoutesEnum::Api_SiwsLogin.to_string().as_str(),
+            post(routes::siws::login::handler),
+        )
+        .route(
+            RoutesEnum::Api_LinkEmail.to_string().as_str(),
+            post(routes::siws::link_email::handler),
+        )
+        .route(
+            RoutesEnum::Api_ChangeEmail.to_string().as_str(),
+            post(routes::account::change_email::handler),
+        )
+        .route(
+            RoutesEnum::Api_DeleteAccount.to_string().as_str(),
+            post(routes::account::delete_account::handler),
+        )
+        .route(
+            RoutesEnum::Api_ExportAccount.to_string().as_str(),
+            get(routes::account::export::handler),
+        )
+        .route(
+            RoutesEnum::Api_NotificationPreferences.to_string().as_str(),
+            get(routes::notifications::get_preferences::handler)
+                .post(routes::notifications::update_preferences::handler),
+        )
+        .route(
+            RoutesEnum::Api_NotificationTelegramLink
+                .to_string()
+                .as_str(),
+            post(routes::notifications::telegram_link::handler),
+        )
+        .route(
+            RoutesEnum::Api_NotificationTelegramUnlink
+                .to_string()
+                .as_str(),
+            post(routes::notifications::telegram_unlink::handler),
+        )
+        .route(
+            RoutesEnum::Api_TgBotLinkCode.to_string().as_str(),
+            post(routes::tg_bot::link_code::handler),
+        )
+        .route(
+            RoutesEnum::Api_TgBotUnlink.to_string().as_str(),
+            post(routes::tg_bot::unlink::handler),
+        )
+        .route(
+            RoutesEnum::Api_TgBotLink.to_string().as_str(),
+            post(routes::tg_bot::link::handler),
+        )
+        .route(
+            RoutesEnum::Api_TgBotAccount.to_string().as_str(),
+            post(routes::tg_bot::account::handler),
         );
     api_router
 }
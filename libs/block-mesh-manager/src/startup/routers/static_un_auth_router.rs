@@ -31,6 +31,12 @@ pub fn get_static_un_auth_router() -> Router<Arc<AppState>> {
             RoutesEnum::Static_UnAuth_EmailConfirm.to_string().as_str(),
             get(routes::emails::email_confirm::handler),
         )
+        .route(
+            RoutesEnum::Static_UnAuth_EmailChangeConfirm
+                .to_string()
+                .as_str(),
+            get(routes::account::email_change_confirm::handler),
+        )
         .route(
             RoutesEnum::Static_UnAuth_ResetPassword.to_string().as_str(),
             get(routes::password::reset_password_form::handler)
@@ -52,6 +58,11 @@ pub fn get_static_un_auth_router() -> Router<Arc<AppState>> {
             RoutesEnum::Static_UnAuth_Root.to_string().as_str(),
             get(routes::login::login_form::handler).post(routes::login::login_post::handler),
         )
+        .route(
+            RoutesEnum::Static_UnAuth_TwoFactor.to_string().as_str(),
+            get(routes::two_factor::two_factor_form::handler)
+                .post(routes::two_factor::two_factor_post::handler),
+        )
         .route(
             RoutesEnum::Static_UnAuth_Error.to_string().as_str(),
             get(routes::error::error_page::handler),
@@ -81,7 +92,14 @@ pub fn get_static_un_auth_router() -> Router<Arc<AppState>> {
         )
         .route(
             RoutesEnum::Static_UnAuth_Unsubscribe.to_string().as_str(),
-            get(routes::health_check::unsubscribe::unsubscribe),
+            get(routes::health_check::unsubscribe::unsubscribe)
+                .post(routes::health_check::unsubscribe::unsubscribe),
+        )
+        .route(
+            RoutesEnum::Static_UnAuth_TelegramWebhook
+                .to_string()
+                .as_str(),
+            post(routes::notifications::telegram_webhook::handler),
         )
         .route(
             RoutesEnum::Static_UnAuth_HealthCheck.to_string().as_str(),
@@ -0,0 +1,13 @@
+use axum::http::header::ACCEPT_LANGUAGE;
+use axum::http::HeaderMap;
+use block_mesh_common::email::locale::negotiate_locale;
+
+/// Locale for emails triggered by this request, from its `Accept-Language` header.
+pub fn email_locale(headers: &HeaderMap) -> &'static str {
+    negotiate_locale(
+        headers
+            .get(ACCEPT_LANGUAGE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default(),
+    )
+}
@@ -0,0 +1,19 @@
+use crate::middlewares::authentication::{del_from_redis, del_from_redis_with_pattern};
+use crate::startup::application::AppState;
+use block_mesh_common::interfaces::db_messages::InvalidateApiCache;
+use block_mesh_manager_database_domain::domain::notify_api::notify_api;
+use uuid::Uuid;
+
+/// Drops everything cached about the user under `email` in this process, Redis and manager-api,
+/// for when the email stops pointing at the account.
+#[tracing::instrument(name = "forget_cached_user", skip(state))]
+pub async fn forget_cached_user(state: &AppState, user_id: &Uuid, email: &str) {
+    let email = email.to_ascii_lowercase();
+    let mut redis = state.redis.clone();
+    let _ = del_from_redis_with_pattern(&email, "-*", &mut redis).await;
+    let _ = del_from_redis_with_pattern(&user_id.to_string(), "-*", &mut redis).await;
+    del_from_redis(&user_id.to_string(), &mut redis).await;
+    state.get_token_map.invalidate_email(&email);
+    state.check_token_map.invalidate_email(&email);
+    let _ = notify_api(&state.pool, InvalidateApiCache { email }).await;
+}
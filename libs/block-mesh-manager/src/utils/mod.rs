@@ -1,3 +1,7 @@
 pub mod cache_envar;
+pub mod email_locale;
+pub mod forget_user;
 pub mod points;
+pub mod tg_bot_account;
+pub mod totp;
 pub mod verify_cache;
@@ -1,35 +1,44 @@
 use crate::domain::perk::Perk;
+use block_mesh_manager_database_domain::domain::reward_rules::RewardRules;
+use chrono::NaiveDate;
 
-pub const UPTIME_FACTOR: f64 = 100.0 / (24.0 * 60.0 * 60.0);
-pub const TASKS_FACTOR: f64 = 10.0;
-
-pub fn raw_points(uptime: f64, tasks_count: i64) -> f64 {
-    uptime * UPTIME_FACTOR + tasks_count as f64 * TASKS_FACTOR
-}
-
-pub fn calc_points_daily(uptime: f64, tasks_count: i64, perks: &Vec<Perk>) -> f64 {
-    let mut points = raw_points(uptime, tasks_count);
-    for perk in perks {
-        points *= perk.multiplier;
-    }
-    points
+fn perk_pairs(perks: &[Perk]) -> Vec<(String, f64)> {
+    perks
+        .iter()
+        .map(|perk| (perk.name.to_string(), perk.multiplier))
+        .collect()
 }
 
-pub fn calc_total_points(uptime: f64, tasks_count: i64, perks: &Vec<Perk>) -> f64 {
-    let mut points = raw_points(uptime, tasks_count);
-    for perk in perks {
-        points *= perk.multiplier;
-    }
-    for perk in perks {
-        points += perk.one_time_bonus;
-    }
-    points
+/// Points for a single day, evaluated with the reward rules that were active on `day`.
+pub fn calc_points_daily(
+    rules: &RewardRules,
+    day: NaiveDate,
+    uptime: f64,
+    tasks_count: i64,
+    perks: &[Perk],
+) -> f64 {
+    let perks = perk_pairs(perks);
+    rules
+        .evaluate(
+            day,
+            uptime,
+            tasks_count,
+            perks.iter().map(|(name, m)| (name.as_str(), *m)),
+            1,
+        )
+        .map(|breakdown| breakdown.total())
+        .unwrap_or_default()
 }
 
-pub fn calc_one_time_bonus_points(uptime: f64, tasks_count: i64, perks: &Vec<Perk>) -> f64 {
-    let mut points = raw_points(uptime, tasks_count);
-    for perk in perks {
-        points += perk.one_time_bonus;
-    }
-    points
+/// Points a day that is still accumulating is worth so far, scaled by the user's sybil weight.
+/// The dashboard uses it, `get_daily_leaderboard` evaluates the same formula in SQL.
+pub fn calc_live_points(
+    rules: &RewardRules,
+    day: NaiveDate,
+    uptime: f64,
+    tasks_count: i64,
+    perks: &[Perk],
+    sybil_weight: f64,
+) -> f64 {
+    calc_points_daily(rules, day, uptime, tasks_count, perks) * sybil_weight
 }
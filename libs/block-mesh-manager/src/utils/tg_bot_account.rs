@@ -0,0 +1,57 @@
+use crate::database::daily_stat::get_daily_stats_by_user_id::get_daily_stats_by_user_id;
+use crate::database::points_ledger::get_user_points_by_day::get_user_points_by_day;
+use crate::database::points_ledger::get_user_points_total::get_user_points_total;
+use crate::errors::error::Error;
+use crate::utils::cache_envar::get_envar;
+use axum::http::HeaderMap;
+use block_mesh_common::constant_time::constant_time_eq;
+use block_mesh_common::interfaces::server_api::{DailyStatForDashboard, TgBotAccountResponse};
+use block_mesh_common::tg_bot::{TgBotTier, TG_BOT_SECRET_HEADER, TG_BOT_TIER_WINDOW_DAYS};
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+/// Calls from the Telegram privacy bot carry `TG_BOT_API_SECRET`, unset disables them.
+pub async fn verify_tg_bot_secret(headers: &HeaderMap) -> Result<(), Error> {
+    let secret = get_envar("TG_BOT_API_SECRET").await;
+    if secret.is_empty() {
+        return Err(Error::TgBotDisabled);
+    }
+    let provided = headers
+        .get(TG_BOT_SECRET_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    if !constant_time_eq(provided.as_bytes(), secret.as_bytes()) {
+        return Err(Error::Unauthorized);
+    }
+    Ok(())
+}
+
+/// Tier from the total points and the uptime of the last days, points per day are the
+/// finalized ones from the ledger.
+pub async fn tg_bot_account(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+) -> anyhow::Result<TgBotAccountResponse> {
+    let points = get_user_points_total(transaction, user_id).await?;
+    let points_by_day = get_user_points_by_day(transaction, user_id).await?;
+    let daily_stats: Vec<DailyStatForDashboard> = get_daily_stats_by_user_id(transaction, user_id)
+        .await?
+        .into_iter()
+        .take(TG_BOT_TIER_WINDOW_DAYS as usize)
+        .map(|i| DailyStatForDashboard {
+            tasks_count: i.tasks_count,
+            uptime: i.uptime,
+            points: points_by_day.get(&i.day).copied().unwrap_or_default(),
+            day: i.day,
+        })
+        .collect();
+    let average_uptime =
+        daily_stats.iter().map(|i| i.uptime).sum::<f64>() / TG_BOT_TIER_WINDOW_DAYS as f64;
+    let tier = TgBotTier::from_activity(points, average_uptime);
+    Ok(TgBotAccountResponse {
+        tier,
+        daily_limit: tier.daily_limit(),
+        points,
+        daily_stats,
+    })
+}
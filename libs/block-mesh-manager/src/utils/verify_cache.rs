@@ -1,28 +1,30 @@
 use bcrypt::verify;
-use dashmap::DashMap;
+use block_mesh_common::credential_cache::CredentialCache;
 use std::sync::Arc;
 use tokio::sync::OnceCell;
 
-type VerifyMap = Arc<DashMap<(String, String), bool>>;
+/// Keyed by the bcrypt hash, which changes on every password update,
+/// so results never outlive the password they were computed for.
+type VerifyMap = Arc<CredentialCache<bool>>;
 
 static CACHE: OnceCell<VerifyMap> = OnceCell::const_new();
 
 #[tracing::instrument(name = "get_cache", skip_all)]
 pub async fn get_cache<'a>() -> &'a VerifyMap {
     CACHE
-        .get_or_init(|| async { Arc::new(DashMap::new()) })
+        .get_or_init(|| async { Arc::new(CredentialCache::default()) })
         .await
 }
 
 #[tracing::instrument(name = "verify_with_cache", skip_all)]
 pub async fn verify_with_cache(password: &str, hash: &str) -> bool {
-    let key = (password.to_string(), hash.to_string());
     let cache = get_cache().await;
-    if let Some(entry) = cache.get(&key) {
-        return *entry.value();
-    }
+    let ticket = match cache.get(hash, password.as_bytes()) {
+        Ok(result) => return result,
+        Err(ticket) => ticket,
+    };
     if let Ok(result) = verify::<&str>(password, hash) {
-        cache.insert(key, result);
+        cache.insert(ticket, result, !result);
         return result;
     }
     false
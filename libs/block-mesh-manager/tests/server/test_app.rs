@@ -1,3 +1,4 @@
+use block_mesh_common::credential_cache::CredentialCache;
 use block_mesh_common::env::app_env_var::AppEnvVar;
 use block_mesh_common::env::env_var::EnvVar;
 use block_mesh_common::env::get_env_var_or_panic::get_env_var_or_panic;
@@ -12,7 +13,6 @@ use block_mesh_manager::configuration::settings::Settings;
 use block_mesh_manager::emails::email_client::EmailClient;
 use block_mesh_manager::startup::application::{AppState, Application};
 use block_mesh_manager::startup::get_connection_pool::get_connection_pool;
-use dashmap::DashMap;
 use database_utils::utils::migrate::migrate;
 use logger_general::tracing::setup_tracing_stdout_only;
 use redis;
@@ -58,7 +58,10 @@ pub async fn spawn_app() -> TestApp {
     migrate(&db_pool, "test".to_string())
         .await
         .expect("Failed to migrate database");
-    let email_client = Arc::new(EmailClient::new(configuration.application.base_url.clone()).await);
+    let email_client = Arc::new(EmailClient::new(
+        configuration.application.base_url.clone(),
+        db_pool.clone(),
+    ));
     let client = ClientBuilder::new()
         .timeout(Duration::from_secs(3))
         .build()
@@ -71,8 +74,8 @@ pub async fn spawn_app() -> TestApp {
         .await
         .unwrap();
 
-    let check_token_map: CheckTokenResponseMap = Arc::new(DashMap::new());
-    let get_token_map: GetTokenResponseMap = Arc::new(DashMap::new());
+    let check_token_map: CheckTokenResponseMap = Arc::new(CredentialCache::default());
+    let get_token_map: GetTokenResponseMap = Arc::new(CredentialCache::default());
 
     let app_state = Arc::new(AppState {
         get_token_map,
@@ -111,11 +114,6 @@ impl TestApp {
         format!("{}/ws", s)
     }
 
-    pub fn ws_address_with_auth(&self, email: &str, api_token: &Uuid) -> String {
-        let base = self.ws_address();
-        format!("{base}?email={email}&api_token={api_token}")
-    }
-
     pub async fn register_post(&self, form: &RegisterForm) -> anyhow::Result<()> {
         let response = self
             .client
@@ -25,13 +25,15 @@ async fn test_ws() -> (TestApp, reqwest_websocket::WebSocket) {
         .get_api_token(&GetTokenRequest {
             email: email.clone(),
             password: password.clone(),
+            token_name: None,
         })
         .await
         .unwrap();
     assert!(api_token.api_token.is_some());
     let api_token = api_token.api_token.unwrap();
     let response = Client::default()
-        .get(app.ws_address_with_auth(&email, &api_token))
+        .get(app.ws_address())
+        .bearer_auth(api_token)
         .upgrade() // Prepares the WebSocket upgrade.
         .send()
         .await
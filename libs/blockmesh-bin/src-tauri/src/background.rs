@@ -30,9 +30,11 @@ pub async fn channel_receiver(mut rx: Receiver<ChannelMessage>, app_state: Arc<M
                     }
                     let email = Arc::new(s.config.email.clone().unwrap_or_default());
                     let api_token = Arc::new(s.config.api_token.clone().unwrap_or_default());
+                    let device_id = s.config.device_id;
                     drop(s);
                     loop {
-                        let _ = report_uptime(email.to_string(), api_token.to_string()).await;
+                        let _ = report_uptime(email.to_string(), api_token.to_string(), device_id)
+                            .await;
                         sleep(Duration::from_secs(30)).await;
                     }
                 });
@@ -1,8 +1,8 @@
 use anyhow::anyhow;
-use block_mesh_common::constants::BLOCK_MESH_APP_SERVER;
+use block_mesh_common::constants::{DeviceType, BLOCK_MESH_APP_SERVER};
 use block_mesh_common::interfaces::server_api::{
-    GetTaskRequest, GetTaskResponse, ReportUptimeRequest, ReportUptimeResponse, RunTaskResponse,
-    SubmitTaskRequest, SubmitTaskResponse,
+    ClientsMetadata, GetTaskRequest, GetTaskResponse, ReportUptimeRequest, ReportUptimeResponse,
+    RunTaskResponse, SubmitTaskRequest, SubmitTaskResponse,
 };
 use chrono::Utc;
 use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
@@ -16,13 +16,17 @@ use std::time::Duration;
 use uuid::Uuid;
 
 #[tracing::instrument(name = "report_uptime", skip(api_token), err)]
-pub async fn report_uptime(email: String, api_token: String) -> anyhow::Result<()> {
+pub async fn report_uptime(
+    email: String,
+    api_token: String,
+    device_id: Option<Uuid>,
+) -> anyhow::Result<()> {
     let api_token = Uuid::from_str(&api_token).map_err(|_| anyhow!("Invalid UUID"))?;
     let metadata = fetch_metadata().await.unwrap_or_default();
 
     let query = ReportUptimeRequest {
-        email,
-        api_token,
+        email: None,
+        api_token: None,
         ip: if metadata.ip.is_empty() {
             None
         } else {
@@ -35,7 +39,14 @@ pub async fn report_uptime(email: String, api_token: String) -> anyhow::Result<(
         .build()
         .unwrap_or_default()
         .post(format!("{}/api/report_uptime", BLOCK_MESH_APP_SERVER))
+        .bearer_auth(api_token)
         .query(&query)
+        .json(&ClientsMetadata {
+            depin_aggregator: None,
+            device_type: DeviceType::Desktop,
+            version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            device_id,
+        })
         .send()
         .await
     {
@@ -135,8 +146,8 @@ pub async fn submit_task(
     response_time: f64,
 ) -> anyhow::Result<SubmitTaskResponse> {
     let query: SubmitTaskRequest = SubmitTaskRequest {
-        email: email.to_string(),
-        api_token: *api_token,
+        email: None,
+        api_token: None,
         task_id: *task_id,
         response_code: Some(response_code),
         country: Option::from(metadata.country.clone()),
@@ -150,6 +161,7 @@ pub async fn submit_task(
         .build()
         .unwrap_or_default()
         .post(format!("{}/api/submit_task", base_url))
+        .bearer_auth(api_token)
         .query(&query)
         .body(response_raw)
         .send()
@@ -17,6 +17,9 @@ use speed_test::metadata::fetch_metadata;
 use speed_test::upload::test_upload;
 use speed_test::Metadata;
 use std::cmp;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
 use std::str::FromStr;
 use tracing::Level;
 use uuid::Uuid;
@@ -83,6 +86,31 @@ pub async fn login_to_network(url: &str, login_form: LoginForm) -> anyhow::Resul
     }
 }
 
+/// Device id kept in `~/.blockmesh/device_id` so every run on this machine reports the same device.
+/// Falls back to an id for this run only when there is no home directory to keep it in.
+pub fn get_or_create_device_id() -> Uuid {
+    let Some(home) = env::var_os("HOME").or_else(|| env::var_os("USERPROFILE")) else {
+        return Uuid::new_v4();
+    };
+    let path = PathBuf::from(home).join(".blockmesh").join("device_id");
+    if let Some(device_id) = fs::read_to_string(&path)
+        .ok()
+        .and_then(|device_id| Uuid::from_str(device_id.trim()).ok())
+    {
+        return device_id;
+    }
+    let device_id = Uuid::new_v4();
+    let stored = path
+        .parent()
+        .map(fs::create_dir_all)
+        .unwrap_or(Ok(()))
+        .and_then(|_| fs::write(&path, device_id.to_string()));
+    if let Err(error) = stored {
+        debug!("Failed to store device id in {}: {error}", path.display());
+    }
+    device_id
+}
+
 #[tracing::instrument(name = "report_uptime", skip(api_token), err(level = Level::TRACE))]
 pub async fn report_uptime(
     url: &str,
@@ -94,8 +122,8 @@ pub async fn report_uptime(
     let cloudflare_metadata = fetch_metadata().await.unwrap_or_default();
 
     let query = ReportUptimeRequest {
-        email: email.to_string(),
-        api_token,
+        email: None,
+        api_token: None,
         ip: Some(cloudflare_metadata.ip).filter(|ip| !ip.is_empty()),
     };
 
@@ -103,6 +131,7 @@ pub async fn report_uptime(
     info!("Reporting uptime on {}", &url);
     if let Ok(response) = http_client()
         .post(url)
+        .bearer_auth(api_token)
         .query(&query)
         .json(&session_metadata)
         .send()
@@ -206,8 +235,8 @@ pub async fn submit_task(
         city: _city,
     } = metadata;
     let query: SubmitTaskRequest = SubmitTaskRequest {
-        email: email.to_string(),
-        api_token: *api_token,
+        email: None,
+        api_token: None,
         task_id: *task_id,
         response_code: Some(response_code),
         country: Option::from(country),
@@ -219,6 +248,7 @@ pub async fn submit_task(
     };
     let response = http_client()
         .post(format!("{}/api/submit_task", base_url))
+        .bearer_auth(api_token)
         .query(&query)
         .body(response_raw)
         .send()
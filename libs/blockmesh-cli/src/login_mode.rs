@@ -1,5 +1,6 @@
 use crate::helpers::{
-    get_polling_interval, login_to_network, report_uptime, run_task, submit_bandwidth, task_poller,
+    get_or_create_device_id, get_polling_interval, login_to_network, report_uptime, run_task,
+    submit_bandwidth, task_poller,
 };
 use anyhow::anyhow;
 use block_mesh_common::constants::DeviceType;
@@ -57,6 +58,7 @@ pub async fn login_mode(
         depin_aggregator,
         device_type: DeviceType::Cli,
         version: Some(env!("CARGO_PKG_VERSION").to_string()),
+        device_id: Some(get_or_create_device_id()),
     };
     let mut prev_is_ws_feature: Option<bool> = None;
     let stop_notifier = Arc::new(Notify::new());
@@ -103,9 +105,10 @@ async fn connect_ws(
     let url = url
         .replace("http://", "ws://")
         .replace("https://", "wss://");
-    let url = format!("{url}/ws?email={email}&api_token={api_token}");
+    let url = format!("{url}/ws");
     let ws = client
         .get(&url)
+        .bearer_auth(api_token)
         .upgrade()
         .send()
         .await?
@@ -169,8 +172,8 @@ async fn handle_ws_message(
                     .unwrap();
                 let response_time = Some(std::cmp::max(task_start.elapsed().as_millis(), 1) as f64);
                 let report = SubmitTaskRequest {
-                    email: email.clone(),
-                    api_token,
+                    email: None,
+                    api_token: None,
                     task_id: task.id,
                     response_code: Some(completed_task.status),
                     country: Some(country),
@@ -205,8 +208,8 @@ async fn handle_ws_message(
             WsServerMessage::RequestUptimeReport => {
                 let cf_meta = fetch_metadata().await.unwrap_or_default();
                 let report = ReportUptimeRequest {
-                    email: email.clone(),
-                    api_token,
+                    email: None,
+                    api_token: None,
                     ip: Some(cf_meta.ip).filter(|ip| !ip.is_empty()),
                 };
                 let _ = tx.send(WsClientMessage::ReportUptime(report)).await;
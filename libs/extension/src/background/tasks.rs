@@ -94,8 +94,8 @@ pub async fn submit_task(
     response_time: f64,
 ) -> anyhow::Result<SubmitTaskResponse> {
     let query: SubmitTaskRequest = SubmitTaskRequest {
-        email: email.to_string(),
-        api_token: *api_token,
+        email: None,
+        api_token: None,
         task_id: *task_id,
         response_code: Some(response_code),
         country: Option::from(metadata.country.clone()),
@@ -107,6 +107,7 @@ pub async fn submit_task(
     };
     let response = reqwest::Client::new()
         .post(format!("{}/api/submit_task", base_url))
+        .bearer_auth(api_token)
         .query(&query)
         .body(response_raw)
         .send()
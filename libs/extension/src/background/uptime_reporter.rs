@@ -3,7 +3,9 @@ use crate::utils::connectors::set_panic_hook;
 use crate::utils::extension_wrapper_state::ExtensionWrapperState;
 use block_mesh_common::chrome_storage::AuthStatus;
 use block_mesh_common::constants::DeviceType;
-use block_mesh_common::interfaces::server_api::{ReportUptimeRequest, ReportUptimeResponse};
+use block_mesh_common::interfaces::server_api::{
+    ClientsMetadata, ReportUptimeRequest, ReportUptimeResponse,
+};
 use leptos::*;
 use logger_leptos::leptos_tracing::setup_leptos_tracing;
 use speed_test::metadata::fetch_metadata;
@@ -27,14 +29,23 @@ pub async fn report_uptime() {
     let base_url = app_state.blockmesh_url.get_untracked();
     let email = app_state.email.get_untracked();
     let api_token = app_state.api_token.get_untracked();
+    let device_id = app_state.device_id.get_untracked();
 
-    report_uptime_inner(&base_url, &email, &api_token, OperationMode::Http).await;
+    report_uptime_inner(
+        &base_url,
+        &email,
+        &api_token,
+        &device_id,
+        OperationMode::Http,
+    )
+    .await;
 }
 
 pub async fn report_uptime_inner(
     base_url: &str,
     email: &str,
     api_token: &Uuid,
+    device_id: &Uuid,
     operation_mode: OperationMode,
 ) -> Option<ReportUptimeRequest> {
     let metadata = fetch_metadata().await.unwrap_or_default();
@@ -45,8 +56,8 @@ pub async fn report_uptime_inner(
     };
 
     let query = ReportUptimeRequest {
-        email: email.to_string(),
-        api_token: *api_token,
+        email: None,
+        api_token: None,
         ip: ip.clone(),
     };
 
@@ -54,7 +65,14 @@ pub async fn report_uptime_inner(
         OperationMode::Http => {
             if let Ok(response) = reqwest::Client::new()
                 .post(format!("{}/api/report_uptime", base_url))
+                .bearer_auth(api_token)
                 .query(&query)
+                .json(&ClientsMetadata {
+                    depin_aggregator: None,
+                    device_type: DeviceType::Extension,
+                    version: Some(env!("CARGO_PKG_VERSION").to_string()),
+                    device_id: Some(*device_id),
+                })
                 .send()
                 .await
             {
@@ -63,8 +81,8 @@ pub async fn report_uptime_inner(
             None
         }
         OperationMode::WebSocket => Some(ReportUptimeRequest {
-            email: email.to_string(),
-            api_token: *api_token,
+            email: Some(email.to_string()),
+            api_token: Some(*api_token),
             ip: ip.clone(),
         }),
     }
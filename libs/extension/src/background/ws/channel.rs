@@ -55,15 +55,21 @@ pub fn set_rx(rx: Receiver<WsServerMessage>, ws: WebSocket) {
             let base_url = app_state.blockmesh_url.get_untracked();
             let email = app_state.email.get_untracked();
             let api_token = app_state.api_token.get_untracked();
+            let device_id = app_state.device_id.get_untracked();
 
             match msg {
                 WsServerMessage::Ping => {
                     let _ = ws.clone().send_with_str("pong");
                 }
                 WsServerMessage::RequestUptimeReport => {
-                    if let Some(r) =
-                        report_uptime_inner(&base_url, &email, &api_token, OperationMode::WebSocket)
-                            .await
+                    if let Some(r) = report_uptime_inner(
+                        &base_url,
+                        &email,
+                        &api_token,
+                        &device_id,
+                        OperationMode::WebSocket,
+                    )
+                    .await
                     {
                         let _ = ws.clone().send_with_str(
                             serde_json::to_string(&WsClientMessage::ReportUptime(r))
@@ -104,8 +110,8 @@ pub fn set_rx(rx: Receiver<WsServerMessage>, ws: WebSocket) {
                         let _ = ws.clone().send_with_str(
                             serde_json::to_string(&WsClientMessage::CompleteTask(
                                 SubmitTaskRequest {
-                                    email,
-                                    api_token,
+                                    email: Some(email),
+                                    api_token: Some(api_token),
                                     task_id: task.id,
                                     response_code: Some(completed_task.status),
                                     country: None,
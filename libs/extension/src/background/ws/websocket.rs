@@ -2,9 +2,11 @@ use super::{
     on_close_handler, on_error_handler, on_message_handler, on_open_handler, WebSocketReadyState,
 };
 use crate::background::ws::channel::get_tx;
+use crate::utils::check_token::get_session_ticket;
 use crate::utils::log::log;
 use crate::utils::{connectors::set_panic_hook, extension_wrapper_state::ExtensionWrapperState};
 use block_mesh_common::constants::DeviceType;
+use block_mesh_common::interfaces::server_api::CheckTokenRequest;
 use block_mesh_common::interfaces::ws_api::WsServerMessage;
 use leptos::SignalGetUntracked;
 use logger_leptos::leptos_tracing::setup_leptos_tracing;
@@ -45,14 +47,15 @@ pub async fn start_websocket() -> Result<(), JsValue> {
     if !app_state.has_api_token() {
         return Err(JsValue::from_str("Missing Api Token"));
     }
-    let email = app_state.email.get_untracked();
-    let api_token = app_state.api_token.get_untracked();
+    let credentials = CheckTokenRequest {
+        email: app_state.email.get_untracked(),
+        api_token: app_state.api_token.get_untracked(),
+    };
     let blockmesh_url = app_state
         .blockmesh_ws_url
         .get_untracked()
         .replace("http://", "ws://")
         .replace("https://", "wss://");
-    let api_token = api_token.to_string();
     match get_ws_status() {
         WebSocketReadyState::CLOSED => {}
         WebSocketReadyState::CLOSING => {}
@@ -60,10 +63,11 @@ pub async fn start_websocket() -> Result<(), JsValue> {
         WebSocketReadyState::CONNECTING => return Ok(()),
         WebSocketReadyState::INVALID => return Ok(()),
     }
-    log!("connecting websocket {blockmesh_url}/ws?email={email}&api_token={api_token}");
-    let ws = WebSocket::new(&format!(
-        "{blockmesh_url}/ws?email={email}&api_token={api_token}"
-    ))?;
+    let ticket = get_session_ticket(&app_state.blockmesh_url.get_untracked(), &credentials)
+        .await
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    log!("connecting websocket {blockmesh_url}/ws");
+    let ws = WebSocket::new(&format!("{blockmesh_url}/ws?ticket={ticket}"))?;
 
     let state: WebSocketReadyState = ws.ready_state().into();
     set_ws_status(&state);
@@ -1,16 +1,20 @@
-use block_mesh_common::interfaces::server_api::CheckTokenRequest;
+use block_mesh_common::interfaces::server_api::{CheckTokenRequest, GetTokenResponse};
 use block_mesh_common::routes_enum::RoutesEnum;
 
-pub async fn check_token(
-    blockmesh_url: &str,
-    credentials: &CheckTokenRequest,
-) -> anyhow::Result<()> {
+fn check_token_url(blockmesh_url: &str) -> String {
     let blockmesh_url = if blockmesh_url.contains("app") {
         blockmesh_url.replace("app", "api")
     } else {
         blockmesh_url.to_string()
     };
-    let url = format!("{}/api{}", blockmesh_url, RoutesEnum::Api_CheckToken);
+    format!("{}/api{}", blockmesh_url, RoutesEnum::Api_CheckToken)
+}
+
+pub async fn check_token(
+    blockmesh_url: &str,
+    credentials: &CheckTokenRequest,
+) -> anyhow::Result<()> {
+    let url = check_token_url(blockmesh_url);
     let client = reqwest::Client::new();
     Ok(client
         .post(&url)
@@ -21,3 +25,24 @@ pub async fn check_token(
         .json()
         .await?)
 }
+
+/// Short lived ticket for opening the websocket, so the api token never ends up in a URL.
+pub async fn get_session_ticket(
+    blockmesh_url: &str,
+    credentials: &CheckTokenRequest,
+) -> anyhow::Result<String> {
+    let url = check_token_url(blockmesh_url);
+    let client = reqwest::Client::new();
+    let response: GetTokenResponse = client
+        .post(&url)
+        .header("Content-Type", "application/json")
+        .json(&credentials)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    response
+        .session_ticket
+        .ok_or_else(|| anyhow::anyhow!("Session tickets are disabled"))
+}
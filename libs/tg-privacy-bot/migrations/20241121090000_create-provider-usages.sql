@@ -0,0 +1,20 @@
+CREATE TABLE provider_usages
+(
+    id            uuid PRIMARY KEY,
+    usage_id      uuid             NOT NULL,
+    provider      TEXT             NOT NULL,
+    model_name    TEXT             NOT NULL,
+    requests      BIGINT           NOT NULL,
+    failures      BIGINT           NOT NULL,
+    input_tokens  BIGINT           NOT NULL,
+    output_tokens BIGINT           NOT NULL,
+    cost_usd      DOUBLE PRECISION NOT NULL,
+    created_at    timestamptz      NOT NULL,
+    updated_at    timestamptz      NOT NULL,
+    CONSTRAINT fk_usage FOREIGN KEY (usage_id) REFERENCES usages (id)
+);
+
+CREATE INDEX provider_usages_created_at ON provider_usages (created_at);
+CREATE INDEX provider_usages_usage_id ON provider_usages (usage_id);
+CREATE INDEX provider_usages_provider ON provider_usages (provider);
+CREATE UNIQUE INDEX provider_usages_usage_id_model_name ON provider_usages (usage_id, model_name);
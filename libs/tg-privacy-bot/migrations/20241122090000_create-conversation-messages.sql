@@ -0,0 +1,14 @@
+CREATE TABLE conversation_messages
+(
+    id         uuid PRIMARY KEY,
+    chat_id    BIGINT      NOT NULL,
+    user_id    uuid        NOT NULL,
+    role       TEXT        NOT NULL,
+    content    TEXT        NOT NULL,
+    model_name TEXT        NOT NULL,
+    created_at timestamptz NOT NULL,
+    CONSTRAINT fk_user FOREIGN KEY (user_id) REFERENCES users (id)
+);
+
+CREATE INDEX conversation_messages_chat_id_created_at ON conversation_messages (chat_id, created_at);
+CREATE INDEX conversation_messages_user_id ON conversation_messages (user_id);
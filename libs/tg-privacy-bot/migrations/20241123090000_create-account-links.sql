@@ -0,0 +1,11 @@
+CREATE TABLE account_links
+(
+    user_id      uuid PRIMARY KEY,
+    tier         TEXT        NOT NULL,
+    daily_limit  BIGINT      NOT NULL,
+    refreshed_at timestamptz NOT NULL,
+    created_at   timestamptz NOT NULL,
+    CONSTRAINT fk_user FOREIGN KEY (user_id) REFERENCES users (id)
+);
+
+CREATE INDEX account_links_refreshed_at ON account_links (refreshed_at);
@@ -0,0 +1 @@
+ALTER TABLE user_settings ADD COLUMN redact_pii BOOLEAN NOT NULL DEFAULT TRUE;
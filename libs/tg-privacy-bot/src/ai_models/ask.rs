@@ -0,0 +1,111 @@
+use crate::ai_models::redaction::Redactor;
+use ai_interfaces::clients::bulk::{AIClient, Completion};
+use ai_interfaces::clients::request::CompletionRequest;
+use ai_interfaces::clients::transport::{BlockMeshTransport, Transport};
+use ai_interfaces::models::base::ModelName;
+use anyhow::{anyhow, Context};
+use std::env;
+use std::sync::OnceLock;
+
+static AI_CLIENT: OnceLock<AIClient> = OnceLock::new();
+
+pub fn blockmesh_egress() -> bool {
+    env::var("BLOCKMESH_EGRESS")
+        .unwrap_or("false".to_string())
+        .parse()
+        .unwrap_or(false)
+}
+
+/// Checks the configuration once at startup, see [`get_ai_client`].
+pub fn init_ai_client() -> anyhow::Result<&'static AIClient> {
+    if let Some(client) = AI_CLIENT.get() {
+        return Ok(client);
+    }
+    let client = build_ai_client()?;
+    Ok(AI_CLIENT.get_or_init(|| client))
+}
+
+/// Only the providers with an API key in the environment are available.
+/// With `BLOCKMESH_EGRESS` requests run from BlockMesh nodes, which never get provider API keys,
+/// so only the keyless OpenAI-compatible endpoint at `BLOCKMESH_EGRESS_BASE_URL` is served.
+pub fn get_ai_client() -> &'static AIClient {
+    AI_CLIENT.get_or_init(|| build_ai_client().expect("init_ai_client checks this at startup"))
+}
+
+fn build_ai_client() -> anyhow::Result<AIClient> {
+    if !blockmesh_egress() {
+        return Ok(AIClient::from_available_env());
+    }
+    let base_url = env::var("BLOCKMESH_EGRESS_BASE_URL").map_err(|_| {
+        anyhow!(
+            "BLOCKMESH_EGRESS requires BLOCKMESH_EGRESS_BASE_URL, an OpenAI-compatible endpoint \
+             that takes no API key: nodes never get provider keys, so keyed providers would \
+             reject every request"
+        )
+    })?;
+    let transport = BlockMeshTransport::from_env()
+        .context("BLOCKMESH_EGRESS requires BLOCKMESH_EMAIL and BLOCKMESH_API_TOKEN")?;
+    tracing::warn!(
+        "BLOCKMESH_EGRESS is on: prompts and answers reach third-party BlockMesh nodes in \
+         plaintext, only {} is used and provider API keys are ignored",
+        base_url
+    );
+    Ok(AIClient::keyless_openai_compatible(base_url)
+        .with_transport(Transport::BlockMesh(transport)))
+}
+
+/// With `redact_pii` personal data is replaced before the request leaves the bot and restored
+/// in the answer.
+pub async fn ask(
+    model_name: ModelName,
+    request: CompletionRequest,
+    redact_pii: bool,
+) -> anyhow::Result<Completion> {
+    if !redact_pii {
+        return get_ai_client().completion(model_name, request).await;
+    }
+    let mut redactor = Redactor::new();
+    let request = redactor.redact_request(request);
+    let mut completion = get_ai_client().completion(model_name, request).await?;
+    completion.message = redactor.restore_message(completion.message);
+    Ok(completion)
+}
+
+/// Text sent back to the user, errors included so a failing provider is not silent.
+pub fn answer_text(model_name: &ModelName, result: &anyhow::Result<Completion>) -> String {
+    match result {
+        Ok(completion) => {
+            let text = completion.message.text();
+            if text.trim().is_empty() {
+                format!("{} returned an empty response", model_name)
+            } else {
+                text
+            }
+        }
+        Err(e) => {
+            tracing::error!("{} failed: {:?}", model_name, e);
+            format!("{} failed to answer: {}", model_name, e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ai_interfaces::clients::bulk::ClientKind;
+
+    #[test]
+    fn test_egress_requires_a_keyless_base_url() {
+        env::set_var("BLOCKMESH_EGRESS", "true");
+        env::remove_var("BLOCKMESH_EGRESS_BASE_URL");
+        let error = build_ai_client().err().unwrap();
+        assert!(error.to_string().contains("BLOCKMESH_EGRESS_BASE_URL"));
+
+        env::set_var("BLOCKMESH_EGRESS_BASE_URL", "http://localhost:11434");
+        env::set_var("BLOCKMESH_EMAIL", "node@example.com");
+        env::set_var("BLOCKMESH_API_TOKEN", uuid::Uuid::nil().to_string());
+        let client = build_ai_client().unwrap();
+        assert_eq!(client.client_kinds(), vec![ClientKind::OpenAi]);
+        env::remove_var("BLOCKMESH_EGRESS");
+    }
+}
@@ -0,0 +1,238 @@
+use crate::ai_models::ask::ask;
+use crate::database::models::conversation_message::{ConversationMessage, ConversationRole};
+use crate::database::models::message_mode::MessageMode;
+use ai_interfaces::clients::bulk::{Completion, Message};
+use ai_interfaces::clients::request::CompletionRequest;
+use ai_interfaces::models::base::ModelName;
+use std::env;
+
+const SUMMARY_PROMPT: &str = "Summarize the conversation below in a few sentences. \
+Keep names, facts and decisions the user may refer to later.";
+
+/// Rough estimate, providers tokenize differently and ~4 characters per token is close enough for a budget.
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count() / 4 + 1
+}
+
+pub fn token_budget() -> usize {
+    env::var("CONVERSATION_TOKEN_BUDGET")
+        .unwrap_or("4000".to_string())
+        .parse()
+        .unwrap_or(4000)
+}
+
+/// Whether the stored history has to be cleared before answering with `model_name`.
+pub fn should_reset(
+    message_mode: &MessageMode,
+    model_name: &ModelName,
+    history: &[ConversationMessage],
+) -> bool {
+    match message_mode {
+        MessageMode::ResetOnEachMessage => !history.is_empty(),
+        MessageMode::ResetOnModelChange => history
+            .iter()
+            .any(|message| message.model_name != *model_name),
+        MessageMode::KeepAlways => false,
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Conversation {
+    pub summary: Option<ConversationMessage>,
+    pub messages: Vec<ConversationMessage>,
+    /// Older messages that do not fit the budget, to be summarized or discarded
+    pub overflow: Vec<ConversationMessage>,
+}
+
+impl Conversation {
+    /// Keeps the newest messages that fit in `budget` together with the summary and the question.
+    pub fn fit(history: Vec<ConversationMessage>, question: &str, budget: usize) -> Self {
+        let mut conversation = Self::default();
+        let mut messages = Vec::new();
+        for message in history {
+            match message.role {
+                ConversationRole::Summary => conversation.summary = Some(message),
+                _ => messages.push(message),
+            }
+        }
+        let mut used = estimate_tokens(question)
+            + conversation
+                .summary
+                .as_ref()
+                .map(|summary| estimate_tokens(&summary.content))
+                .unwrap_or_default();
+        let mut split = messages.len();
+        while split > 0 {
+            let tokens = estimate_tokens(&messages[split - 1].content);
+            if used + tokens > budget {
+                break;
+            }
+            used += tokens;
+            split -= 1;
+        }
+        // Providers expect the history to start with a user turn
+        while messages
+            .get(split)
+            .is_some_and(|message| message.role == ConversationRole::Assistant)
+        {
+            split += 1;
+        }
+        conversation.messages = messages.split_off(split);
+        conversation.overflow = messages;
+        conversation
+    }
+
+    pub fn request(&self, question: String) -> CompletionRequest {
+        let mut messages = self
+            .messages
+            .iter()
+            .map(|message| match message.role {
+                ConversationRole::Assistant => Message::assistant(message.content.clone()),
+                _ => Message::user(message.content.clone()),
+            })
+            .collect::<Vec<_>>();
+        messages.push(Message::user(question));
+        let request = CompletionRequest::new(messages);
+        match self.summary {
+            Some(ref summary) => request.with_system(format!(
+                "Summary of the earlier conversation: {}",
+                summary.content
+            )),
+            None => request,
+        }
+    }
+
+    /// Folds the previous summary and the overflow into a new summary.
+    pub async fn summarize(
+        &self,
+        model_name: ModelName,
+        redact_pii: bool,
+    ) -> anyhow::Result<Completion> {
+        let mut transcript = String::new();
+        if let Some(ref summary) = self.summary {
+            transcript.push_str(&format!("Earlier summary: {}\n", summary.content));
+        }
+        for message in &self.overflow {
+            transcript.push_str(&format!("{}: {}\n", message.role, message.content));
+        }
+        let request =
+            CompletionRequest::new(vec![Message::user(transcript)]).with_system(SUMMARY_PROMPT);
+        ask(model_name, request, redact_pii).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ai_interfaces::models::anthropic::AnthropicModels;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    /// 36 characters, estimated at 10 tokens
+    fn message(role: ConversationRole, content: &str) -> ConversationMessage {
+        ConversationMessage {
+            id: Uuid::new_v4(),
+            chat_id: 1,
+            user_id: Uuid::nil(),
+            role,
+            content: format!("{:<36}", content),
+            model_name: ModelName::default(),
+            created_at: Utc::now(),
+        }
+    }
+
+    fn history() -> Vec<ConversationMessage> {
+        vec![
+            message(ConversationRole::User, "u1"),
+            message(ConversationRole::Assistant, "a1"),
+            message(ConversationRole::User, "u2"),
+            message(ConversationRole::Assistant, "a2"),
+        ]
+    }
+
+    fn contents(messages: &[ConversationMessage]) -> Vec<&str> {
+        messages.iter().map(|m| m.content.trim_end()).collect()
+    }
+
+    #[test]
+    fn test_fit_keeps_newest_messages_within_budget() {
+        let conversation = Conversation::fit(history(), "q", 21);
+        assert_eq!(contents(&conversation.messages), vec!["u2", "a2"]);
+        assert_eq!(contents(&conversation.overflow), vec!["u1", "a1"]);
+        assert!(conversation.summary.is_none());
+
+        let conversation = Conversation::fit(history(), "q", 1000);
+        assert_eq!(
+            contents(&conversation.messages),
+            vec!["u1", "a1", "u2", "a2"]
+        );
+        assert!(conversation.overflow.is_empty());
+
+        let conversation = Conversation::fit(history(), "q", 0);
+        assert!(conversation.messages.is_empty());
+        assert_eq!(conversation.overflow.len(), 4);
+    }
+
+    #[test]
+    fn test_fit_starts_history_on_a_user_turn() {
+        // a1 fits the budget, but the kept history may not open with an assistant turn
+        let conversation = Conversation::fit(history(), "q", 31);
+        assert_eq!(contents(&conversation.messages), vec!["u2", "a2"]);
+        assert_eq!(contents(&conversation.overflow), vec!["u1", "a1"]);
+    }
+
+    #[test]
+    fn test_fit_counts_the_summary() {
+        let mut with_summary = vec![message(ConversationRole::Summary, "summary")];
+        with_summary.extend(history());
+        let conversation = Conversation::fit(with_summary, "q", 31);
+        assert_eq!(
+            conversation
+                .summary
+                .as_ref()
+                .map(|summary| summary.content.trim_end()),
+            Some("summary")
+        );
+        assert_eq!(contents(&conversation.messages), vec!["u2", "a2"]);
+        assert_eq!(contents(&conversation.overflow), vec!["u1", "a1"]);
+
+        let request = conversation.request("q".to_string());
+        assert_eq!(request.messages.len(), 3);
+        assert!(request
+            .system
+            .is_some_and(|system| system.contains("summary")));
+        assert!(Conversation::fit(history(), "q", 31)
+            .request("q".to_string())
+            .system
+            .is_none());
+    }
+
+    #[test]
+    fn test_should_reset() {
+        let model = ModelName::default();
+        let other = ModelName::Anthropic(AnthropicModels::Claude35HaikuLatest);
+        let history = history();
+
+        assert!(!should_reset(&MessageMode::ResetOnEachMessage, &model, &[]));
+        assert!(should_reset(
+            &MessageMode::ResetOnEachMessage,
+            &model,
+            &history
+        ));
+
+        assert!(!should_reset(&MessageMode::ResetOnModelChange, &model, &[]));
+        assert!(!should_reset(
+            &MessageMode::ResetOnModelChange,
+            &model,
+            &history
+        ));
+        assert!(should_reset(
+            &MessageMode::ResetOnModelChange,
+            &other,
+            &history
+        ));
+
+        assert!(!should_reset(&MessageMode::KeepAlways, &model, &history));
+        assert!(!should_reset(&MessageMode::KeepAlways, &other, &history));
+    }
+}
@@ -1 +1,3 @@
-pub mod open_ai;
+pub mod ask;
+pub mod conversation;
+pub mod redaction;
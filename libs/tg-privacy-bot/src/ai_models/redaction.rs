@@ -0,0 +1,316 @@
+use ai_interfaces::clients::bulk::Message;
+use ai_interfaces::clients::request::{CompletionRequest, ContentPart};
+use regex::{Captures, Regex};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+const PLACEHOLDER_PROMPT: &str = "Values in square brackets like [EMAIL_1] are placeholders for \
+redacted personal data, repeat them unchanged when they are needed in the answer.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PiiKind {
+    Email,
+    Iban,
+    Wallet,
+    Phone,
+    Name,
+}
+
+impl Display for PiiKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PiiKind::Email => write!(f, "EMAIL"),
+            PiiKind::Iban => write!(f, "IBAN"),
+            PiiKind::Wallet => write!(f, "WALLET"),
+            PiiKind::Phone => write!(f, "PHONE"),
+            PiiKind::Name => write!(f, "NAME"),
+        }
+    }
+}
+
+struct Patterns {
+    email: Regex,
+    iban: Regex,
+    wallet: Regex,
+    phone: Regex,
+    name: Regex,
+    placeholder: Regex,
+}
+
+fn patterns() -> &'static Patterns {
+    static PATTERNS: OnceLock<Patterns> = OnceLock::new();
+    PATTERNS.get_or_init(|| Patterns {
+        email: Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}")
+            .unwrap(),
+        iban: Regex::new(r"(?i)\b[A-Z]{2}\d{2}(?: ?[A-Z0-9]{4}){2,7}(?: ?[A-Z0-9]{1,3})?\b")
+            .unwrap(),
+        // EVM, bech32 and legacy bitcoin, base58 (Solana) addresses
+        wallet: Regex::new(
+            r"\b(?:0x[a-fA-F0-9]{40}|bc1[a-z0-9]{25,59}|[13][1-9A-HJ-NP-Za-km-z]{24,33}|[1-9A-HJ-NP-Za-km-z]{32,44})\b",
+        )
+        .unwrap(),
+        phone: Regex::new(r"\+?\(?\d[\d ().-]{6,}\d").unwrap(),
+        // Names are only recognized after an introduction or a title, "I am" is followed by
+        // too many capitalized words that are not names
+        name: Regex::new(
+            r"(?:\b(?i:my name is|call me)\s+|\b(?:Mr|Mrs|Ms|Miss|Dr|Prof)\.?\s+)([A-Z][a-z]+(?:[ -][A-Z][a-z]+){0,2})",
+        )
+        .unwrap(),
+        placeholder: Regex::new(r"\[(?:EMAIL|IBAN|WALLET|PHONE|NAME)_\d+\]").unwrap(),
+    })
+}
+
+/// ISO 13616 mod 97 check, drops numbers that only look like an IBAN.
+fn is_valid_iban(value: &str) -> bool {
+    let compact = value.replace(' ', "").to_ascii_uppercase();
+    if !(15..=34).contains(&compact.len()) {
+        return false;
+    }
+    let (head, tail) = compact.split_at(4);
+    let mut remainder = 0u32;
+    for c in tail.chars().chain(head.chars()) {
+        let Some(digit) = c.to_digit(36) else {
+            return false;
+        };
+        remainder = if digit < 10 {
+            (remainder * 10 + digit) % 97
+        } else {
+            (remainder * 100 + digit) % 97
+        };
+    }
+    remainder == 1
+}
+
+/// Dates and short numbers are kept, an international prefix allows shorter numbers.
+fn is_phone(value: &str) -> bool {
+    let digits = value.chars().filter(|c| c.is_ascii_digit()).count();
+    (9..=15).contains(&digits) || (value.starts_with('+') && (8..=15).contains(&digits))
+}
+
+/// Replaces personal data with placeholders before a request leaves the bot and puts the
+/// originals back in the answer.
+/// The mapping lives only as long as the redactor, one per request, and is never stored.
+#[derive(Default)]
+pub struct Redactor {
+    placeholders: HashMap<String, String>,
+    originals: HashMap<String, String>,
+    counts: HashMap<PiiKind, usize>,
+}
+
+impl Redactor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.originals.is_empty()
+    }
+
+    /// The same value always gets the same placeholder so the model can relate mentions.
+    fn placeholder(&mut self, kind: PiiKind, value: &str) -> String {
+        if let Some(placeholder) = self.placeholders.get(value) {
+            return placeholder.clone();
+        }
+        let count = self.counts.entry(kind).or_default();
+        *count += 1;
+        let placeholder = format!("[{}_{}]", kind, count);
+        self.placeholders
+            .insert(value.to_string(), placeholder.clone());
+        self.originals
+            .insert(placeholder.clone(), value.to_string());
+        placeholder
+    }
+
+    /// Order matters, IBANs and wallets go before phones so their digits are not split.
+    pub fn redact(&mut self, text: &str) -> String {
+        let patterns = patterns();
+        let text = patterns
+            .email
+            .replace_all(text, |caps: &Captures| {
+                self.placeholder(PiiKind::Email, &caps[0])
+            })
+            .into_owned();
+        let text = patterns
+            .iban
+            .replace_all(&text, |caps: &Captures| {
+                if is_valid_iban(&caps[0]) {
+                    self.placeholder(PiiKind::Iban, &caps[0])
+                } else {
+                    caps[0].to_string()
+                }
+            })
+            .into_owned();
+        let text = patterns
+            .wallet
+            .replace_all(&text, |caps: &Captures| {
+                self.placeholder(PiiKind::Wallet, &caps[0])
+            })
+            .into_owned();
+        let text = patterns
+            .phone
+            .replace_all(&text, |caps: &Captures| {
+                if is_phone(&caps[0]) {
+                    self.placeholder(PiiKind::Phone, &caps[0])
+                } else {
+                    caps[0].to_string()
+                }
+            })
+            .into_owned();
+        patterns
+            .name
+            .replace_all(&text, |caps: &Captures| {
+                let (whole, name) = (caps.get(0).unwrap(), caps.get(1).unwrap());
+                let cue = &whole.as_str()[..name.start() - whole.start()];
+                format!("{}{}", cue, self.placeholder(PiiKind::Name, name.as_str()))
+            })
+            .into_owned()
+    }
+
+    /// Redacts the system prompt and every text part, other parts are sent as they are.
+    pub fn redact_request(&mut self, mut request: CompletionRequest) -> CompletionRequest {
+        request.system = request.system.map(|system| self.redact(&system));
+        for message in &mut request.messages {
+            for part in &mut message.content {
+                if let ContentPart::Text(text) = part {
+                    *text = self.redact(text);
+                }
+            }
+        }
+        if self.is_empty() {
+            return request;
+        }
+        let system = match &request.system {
+            Some(system) => format!("{}\n\n{}", system, PLACEHOLDER_PROMPT),
+            None => PLACEHOLDER_PROMPT.to_string(),
+        };
+        request.with_system(system)
+    }
+
+    /// Single pass, so an original that looks like a placeholder is never replaced again.
+    pub fn restore(&self, text: &str) -> String {
+        patterns()
+            .placeholder
+            .replace_all(text, |caps: &Captures| {
+                self.originals
+                    .get(&caps[0])
+                    .cloned()
+                    .unwrap_or_else(|| caps[0].to_string())
+            })
+            .into_owned()
+    }
+
+    pub fn restore_message(&self, mut message: Message) -> Message {
+        for part in &mut message.content {
+            if let ContentPart::Text(text) = part {
+                *text = self.restore(text);
+            }
+        }
+        message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redact(text: &str) -> (Redactor, String) {
+        let mut redactor = Redactor::new();
+        let redacted = redactor.redact(text);
+        (redactor, redacted)
+    }
+
+    #[test]
+    fn test_is_valid_iban() {
+        assert!(is_valid_iban("DE89 3704 0044 0532 0130 00"));
+        assert!(is_valid_iban("GB82WEST12345698765432"));
+        assert!(is_valid_iban("gb82 west 1234 5698 7654 32"));
+        assert!(!is_valid_iban("DE89 3704 0044 0532 0130 01"));
+        assert!(!is_valid_iban("DE89 3704"));
+        assert!(!is_valid_iban("DE89 3704 0044 0532 0130 0$"));
+    }
+
+    #[test]
+    fn test_redacts_emails() {
+        let (_, redacted) = redact("write to jane.doe@example.co.uk or jane.doe@example.co.uk");
+        assert_eq!(redacted, "write to [EMAIL_1] or [EMAIL_1]");
+    }
+
+    #[test]
+    fn test_redacts_ibans() {
+        let (_, redacted) =
+            redact("pay DE89 3704 0044 0532 0130 00 or gb82west12345698765432, not DE89 3704 0044 0532 0130 01");
+        assert_eq!(
+            redacted,
+            "pay [IBAN_1] or [IBAN_2], not DE89 3704 0044 0532 0130 01"
+        );
+    }
+
+    #[test]
+    fn test_redacts_wallets() {
+        let (_, redacted) = redact(
+            "send to 0x52908400098527886E0F7030069857D2E4169EE7 or bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq",
+        );
+        assert_eq!(redacted, "send to [WALLET_1] or [WALLET_2]");
+    }
+
+    #[test]
+    fn test_redacts_phones() {
+        let (_, redacted) =
+            redact("call +1 (415) 555-2671 or 030 1234 5678 on 2024-11-01 at 10:30");
+        assert_eq!(
+            redacted,
+            "call [PHONE_1] or [PHONE_2] on 2024-11-01 at 10:30"
+        );
+    }
+
+    #[test]
+    fn test_redacts_names_after_a_cue() {
+        let (_, redacted) = redact("Hi, my name is Jane Doe and Dr. Watson said call me Bob");
+        assert_eq!(
+            redacted,
+            "Hi, my name is [NAME_1] and Dr. [NAME_2] said call me [NAME_3]"
+        );
+        let (redactor, redacted) = redact("I am Happy to help, I'm Not sure why");
+        assert_eq!(redacted, "I am Happy to help, I'm Not sure why");
+        assert!(redactor.is_empty());
+    }
+
+    #[test]
+    fn test_restore_round_trip() {
+        let text = "I'm jane@example.com, my name is Jane, IBAN DE89 3704 0044 0532 0130 00";
+        let (redactor, redacted) = redact(text);
+        assert!(!redacted.contains("jane@example.com"));
+        assert_eq!(redactor.restore(&redacted), text);
+        // unknown placeholders are left alone
+        assert_eq!(redactor.restore("[EMAIL_9] [NAME_1]"), "[EMAIL_9] Jane");
+    }
+
+    #[test]
+    fn test_restore_does_not_mix_up_similar_placeholders() {
+        let emails: Vec<String> = (1..=10).map(|i| format!("user{}@example.com", i)).collect();
+        let (redactor, redacted) = redact(&emails.join(" "));
+        assert!(redacted.starts_with("[EMAIL_1] [EMAIL_2]"));
+        assert!(redacted.ends_with("[EMAIL_10]"));
+        assert_eq!(
+            redactor.restore("[EMAIL_1][EMAIL_10] [EMAIL_10]"),
+            "user1@example.comuser10@example.com user10@example.com"
+        );
+    }
+
+    #[test]
+    fn test_redact_request_explains_placeholders() {
+        let request = CompletionRequest::new(vec![Message::user("mail jane@example.com")])
+            .with_system("Be brief.");
+        let mut redactor = Redactor::new();
+        let request = redactor.redact_request(request);
+        assert_eq!(
+            request.system,
+            Some(format!("Be brief.\n\n{}", PLACEHOLDER_PROMPT))
+        );
+        assert_eq!(request.messages[0].text(), "mail [EMAIL_1]");
+
+        let request = CompletionRequest::new(vec![Message::user("hello")]);
+        assert_eq!(Redactor::new().redact_request(request).system, None);
+    }
+}
@@ -9,12 +9,20 @@ pub enum Commands {
     #[default]
     #[command(description = "Start bot")]
     Start,
-    // #[command(description = "select_mode")]
-    // SelectMode,
+    #[command(description = "select_mode")]
+    SelectMode,
     #[command(description = "select_model")]
     SelectModel,
     #[command(description = "info")]
     Info,
+    #[command(description = "Redact personal data before it is sent to the model")]
+    Privacy,
+    #[command(description = "Clear the conversation history")]
+    Reset,
+    #[command(description = "Link your BlockMesh account with the code from the dashboard")]
+    Link(String),
+    #[command(description = "Show your BlockMesh tier and node stats")]
+    Stats,
     #[command(description = "Display Help.")]
     Help,
 }
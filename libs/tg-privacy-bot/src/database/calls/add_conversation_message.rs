@@ -0,0 +1,38 @@
+use crate::database::models::conversation_message::{ConversationMessage, ConversationRole};
+use ai_interfaces::models::base::ModelName;
+use chrono::Utc;
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+pub async fn add_conversation_message(
+    transaction: &mut Transaction<'_, Postgres>,
+    chat_id: i64,
+    user_id: &Uuid,
+    role: &ConversationRole,
+    content: &str,
+    model_name: &ModelName,
+) -> anyhow::Result<ConversationMessage> {
+    let id = Uuid::new_v4();
+    let now = Utc::now();
+    let message = sqlx::query_as!(
+        ConversationMessage,
+        r#"
+        INSERT INTO conversation_messages
+        (id, chat_id, user_id, role, content, model_name, created_at)
+        VALUES
+        ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING id, chat_id, user_id, role AS "role: ConversationRole", content,
+                  model_name AS "model_name: ModelName", created_at
+        "#,
+        id,
+        chat_id,
+        user_id,
+        role.to_string(),
+        content,
+        model_name.to_string(),
+        now
+    )
+    .fetch_one(&mut **transaction)
+    .await?;
+    Ok(message)
+}
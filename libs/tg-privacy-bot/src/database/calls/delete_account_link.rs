@@ -0,0 +1,35 @@
+use chrono::Utc;
+use sqlx::{Postgres, Transaction};
+use std::env;
+use uuid::Uuid;
+
+/// Today's limit goes back to `USAGE_LIMIT`.
+pub async fn delete_account_link(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+) -> anyhow::Result<()> {
+    let usage_limit = env::var("USAGE_LIMIT")
+        .unwrap_or("10".to_string())
+        .parse::<i64>()?;
+    let now = Utc::now();
+    sqlx::query!(
+        r#"
+        DELETE FROM account_links WHERE user_id = $1
+        "#,
+        user_id
+    )
+    .execute(&mut **transaction)
+    .await?;
+    sqlx::query!(
+        r#"
+        UPDATE usages SET usage_limit = $2, updated_at = $3 WHERE user_id = $1 AND day = $4
+        "#,
+        user_id,
+        usage_limit,
+        now,
+        now.date_naive()
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
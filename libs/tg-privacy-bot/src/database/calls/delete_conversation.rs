@@ -0,0 +1,16 @@
+use sqlx::{Postgres, Transaction};
+
+pub async fn delete_conversation(
+    transaction: &mut Transaction<'_, Postgres>,
+    chat_id: i64,
+) -> anyhow::Result<()> {
+    sqlx::query!(
+        r#"
+        DELETE FROM conversation_messages WHERE chat_id = $1
+        "#,
+        chat_id
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
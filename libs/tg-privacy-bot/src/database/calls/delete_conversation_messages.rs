@@ -0,0 +1,17 @@
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+pub async fn delete_conversation_messages(
+    transaction: &mut Transaction<'_, Postgres>,
+    ids: &[Uuid],
+) -> anyhow::Result<()> {
+    sqlx::query!(
+        r#"
+        DELETE FROM conversation_messages WHERE id = ANY($1)
+        "#,
+        ids
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,21 @@
+use crate::database::models::account_link::AccountLink;
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+pub async fn get_account_link(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+) -> anyhow::Result<Option<AccountLink>> {
+    let account_link = sqlx::query_as!(
+        AccountLink,
+        r#"
+        SELECT user_id, tier, daily_limit, refreshed_at, created_at
+        FROM account_links
+        WHERE user_id = $1
+        "#,
+        user_id
+    )
+    .fetch_optional(&mut **transaction)
+    .await?;
+    Ok(account_link)
+}
@@ -0,0 +1,24 @@
+use crate::database::models::conversation_message::{ConversationMessage, ConversationRole};
+use ai_interfaces::models::base::ModelName;
+use sqlx::{Postgres, Transaction};
+
+/// Oldest message first.
+pub async fn get_conversation(
+    transaction: &mut Transaction<'_, Postgres>,
+    chat_id: i64,
+) -> anyhow::Result<Vec<ConversationMessage>> {
+    let messages = sqlx::query_as!(
+        ConversationMessage,
+        r#"
+        SELECT id, chat_id, user_id, role AS "role: ConversationRole", content,
+               model_name AS "model_name: ModelName", created_at
+        FROM conversation_messages
+        WHERE chat_id = $1
+        ORDER BY created_at ASC
+        "#,
+        chat_id
+    )
+    .fetch_all(&mut **transaction)
+    .await?;
+    Ok(messages)
+}
@@ -4,6 +4,7 @@ use sqlx::{Postgres, Transaction};
 use std::env;
 use uuid::Uuid;
 
+/// A new day starts with the tier limit of a linked BlockMesh account, `USAGE_LIMIT` otherwise.
 pub async fn get_or_create_usage(
     transaction: &mut Transaction<'_, Postgres>,
     user_id: &Uuid,
@@ -20,7 +21,7 @@ pub async fn get_or_create_usage(
         INSERT INTO usages
         (id, user_id, usage_limit, usage, created_at, updated_at, day)
         VALUES
-        ($1, $2, $3, $4, $5, $6, $7)
+        ($1, $2, COALESCE((SELECT daily_limit FROM account_links WHERE user_id = $2), $3), $4, $5, $6, $7)
         ON CONFLICT (user_id, day) DO UPDATE SET updated_at = $6
         RETURNING id, user_id, usage_limit, usage, created_at, updated_at, day
         "#,
@@ -29,7 +30,7 @@ pub async fn get_or_create_usage(
         usage_limit,
         0i64,
         now,
-        now.clone(),
+        now,
         day
     )
     .fetch_one(&mut **transaction)
@@ -21,14 +21,15 @@ pub async fn get_or_create_user_settings(
         VALUES
         ($1, $2, $3, $4, $5, $6)
         ON CONFLICT (user_id) DO UPDATE SET updated_at = $6
-        RETURNING id, user_id, message_mode, model_name, created_at, updated_at
+        RETURNING id, user_id, message_mode AS "message_mode: MessageMode",
+                  model_name AS "model_name: ModelName", redact_pii, created_at, updated_at
         "#,
         id,
         user_id,
         message_mode.to_string(),
         model_name.to_string(),
         now,
-        now.clone()
+        now
     )
     .fetch_one(&mut **transaction)
     .await?;
@@ -1,6 +1,15 @@
+pub mod add_conversation_message;
+pub mod delete_account_link;
+pub mod delete_conversation;
+pub mod delete_conversation_messages;
+pub mod get_account_link;
+pub mod get_conversation;
 pub mod get_or_create_usage;
 pub mod get_or_create_user;
 pub mod get_or_create_user_settings;
 pub mod increment_usage;
+pub mod record_provider_usage;
 pub mod update_user_settings_message_mode;
 pub mod update_user_settings_model_name;
+pub mod update_user_settings_redact_pii;
+pub mod upsert_account_link;
@@ -0,0 +1,54 @@
+use ai_interfaces::clients::bulk::Completion;
+use ai_interfaces::models::base::ModelName;
+use chrono::Utc;
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+/// Adds one request to the model's row for the day, `None` records a failed request.
+pub async fn record_provider_usage(
+    transaction: &mut Transaction<'_, Postgres>,
+    usage_id: &Uuid,
+    model_name: &ModelName,
+    completion: Option<&Completion>,
+) -> anyhow::Result<()> {
+    let id = Uuid::new_v4();
+    let now = Utc::now();
+    let tokens = completion.and_then(|completion| completion.usage);
+    let input_tokens = tokens
+        .map(|t| i64::from(t.input_tokens))
+        .unwrap_or_default();
+    let output_tokens = tokens
+        .map(|t| i64::from(t.output_tokens))
+        .unwrap_or_default();
+    let cost_usd = completion
+        .and_then(|completion| completion.cost_usd)
+        .unwrap_or_default();
+    let failures = if completion.is_some() { 0i64 } else { 1i64 };
+    sqlx::query!(
+        r#"
+        INSERT INTO provider_usages
+        (id, usage_id, provider, model_name, requests, failures, input_tokens, output_tokens, cost_usd, created_at, updated_at)
+        VALUES
+        ($1, $2, $3, $4, 1, $5, $6, $7, $8, $9, $9)
+        ON CONFLICT (usage_id, model_name) DO UPDATE SET
+            requests = provider_usages.requests + 1,
+            failures = provider_usages.failures + $5,
+            input_tokens = provider_usages.input_tokens + $6,
+            output_tokens = provider_usages.output_tokens + $7,
+            cost_usd = provider_usages.cost_usd + $8,
+            updated_at = $9
+        "#,
+        id,
+        usage_id,
+        model_name.client_kind().to_string(),
+        model_name.to_string(),
+        failures,
+        input_tokens,
+        output_tokens,
+        cost_usd,
+        now
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
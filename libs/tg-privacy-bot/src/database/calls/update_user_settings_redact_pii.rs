@@ -0,0 +1,22 @@
+use chrono::Utc;
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+pub async fn update_user_settings_redact_pii(
+    transaction: &mut Transaction<'_, Postgres>,
+    id: &Uuid,
+    redact_pii: bool,
+) -> anyhow::Result<()> {
+    let now = Utc::now();
+    sqlx::query!(
+        r#"
+        UPDATE user_settings SET redact_pii = $2, updated_at = $3 WHERE id = $1
+        "#,
+        id,
+        redact_pii,
+        now
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
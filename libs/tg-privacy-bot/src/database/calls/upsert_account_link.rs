@@ -0,0 +1,41 @@
+use block_mesh_common::tg_bot::TgBotTier;
+use chrono::Utc;
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+/// Also moves today's limit to the new tier.
+pub async fn upsert_account_link(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+    tier: &TgBotTier,
+    daily_limit: i64,
+) -> anyhow::Result<()> {
+    let now = Utc::now();
+    sqlx::query!(
+        r#"
+        INSERT INTO account_links
+        (user_id, tier, daily_limit, refreshed_at, created_at)
+        VALUES
+        ($1, $2, $3, $4, $4)
+        ON CONFLICT (user_id) DO UPDATE SET tier = $2, daily_limit = $3, refreshed_at = $4
+        "#,
+        user_id,
+        tier.to_string(),
+        daily_limit,
+        now
+    )
+    .execute(&mut **transaction)
+    .await?;
+    sqlx::query!(
+        r#"
+        UPDATE usages SET usage_limit = $2, updated_at = $3 WHERE user_id = $1 AND day = $4
+        "#,
+        user_id,
+        daily_limit,
+        now,
+        now.date_naive()
+    )
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,24 @@
+use block_mesh_common::tg_bot::TgBotTier;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// BlockMesh account linked through `/link`, the tier is refreshed from the manager.
+#[derive(sqlx::FromRow, Debug, Serialize, Deserialize, Clone)]
+pub struct AccountLink {
+    pub user_id: Uuid,
+    pub tier: String,
+    pub daily_limit: i64,
+    pub refreshed_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl AccountLink {
+    pub fn tier(&self) -> TgBotTier {
+        TgBotTier::from(self.tier.clone())
+    }
+
+    pub fn is_stale(&self) -> bool {
+        Utc::now() - self.refreshed_at > Duration::hours(24)
+    }
+}
@@ -0,0 +1,71 @@
+use ai_interfaces::models::base::ModelName;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Decode, Postgres};
+use std::error::Error;
+use std::fmt::Display;
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum ConversationRole {
+    User,
+    Assistant,
+    /// Replaces the messages that no longer fit the token budget
+    Summary,
+}
+
+impl Display for ConversationRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConversationRole::User => write!(f, "User"),
+            ConversationRole::Assistant => write!(f, "Assistant"),
+            ConversationRole::Summary => write!(f, "Summary"),
+        }
+    }
+}
+
+impl From<String> for ConversationRole {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "Assistant" => ConversationRole::Assistant,
+            "Summary" => ConversationRole::Summary,
+            _ => ConversationRole::User,
+        }
+    }
+}
+
+impl sqlx::Type<Postgres> for ConversationRole {
+    fn type_info() -> sqlx::postgres::PgTypeInfo {
+        <String as sqlx::Type<Postgres>>::type_info()
+    }
+}
+
+impl sqlx::Encode<'_, Postgres> for ConversationRole {
+    fn encode_by_ref(
+        &self,
+        buf: &mut <Postgres as sqlx::database::HasArguments<'_>>::ArgumentBuffer,
+    ) -> sqlx::encode::IsNull {
+        <String as sqlx::Encode<Postgres>>::encode(self.to_string(), buf)
+    }
+}
+
+impl sqlx::Decode<'_, Postgres> for ConversationRole {
+    fn decode(
+        value: <Postgres as sqlx::database::HasValueRef<'_>>::ValueRef,
+    ) -> Result<Self, Box<dyn Error + 'static + Send + Sync>> {
+        let value = <&str as Decode<Postgres>>::decode(value)?;
+        let value = value.to_string();
+        Ok(Self::from(value))
+    }
+}
+
+#[derive(sqlx::FromRow, Debug, Serialize, Deserialize, Clone)]
+pub struct ConversationMessage {
+    pub id: Uuid,
+    pub chat_id: i64,
+    pub user_id: Uuid,
+    pub role: ConversationRole,
+    pub content: String,
+    pub model_name: ModelName,
+    pub created_at: DateTime<Utc>,
+}
@@ -1,3 +1,5 @@
+pub mod account_link;
+pub mod conversation_message;
 pub mod invite_code;
 pub mod message_mode;
 pub mod usage;
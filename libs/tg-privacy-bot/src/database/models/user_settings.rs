@@ -10,6 +10,7 @@ pub struct UserSettings {
     pub user_id: Uuid,
     pub model_name: ModelName,
     pub message_mode: MessageMode,
+    pub redact_pii: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
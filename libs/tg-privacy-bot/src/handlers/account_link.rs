@@ -0,0 +1,49 @@
+use crate::database::calls::delete_account_link::delete_account_link;
+use crate::database::calls::upsert_account_link::upsert_account_link;
+use crate::database::db_utils::get_pool;
+use crate::database::models::account_link::AccountLink;
+use crate::manager_api::get_account;
+use block_mesh_common::interfaces::server_api::TgBotAccountResponse;
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+/// Stores the manager's view of the account, `None` means it was unlinked on the dashboard.
+pub async fn apply_account(
+    transaction: &mut Transaction<'_, Postgres>,
+    user_id: &Uuid,
+    account: Option<&TgBotAccountResponse>,
+) -> anyhow::Result<()> {
+    match account {
+        Some(account) => {
+            upsert_account_link(transaction, user_id, &account.tier, account.daily_limit).await
+        }
+        None => delete_account_link(transaction, user_id).await,
+    }
+}
+
+/// Runs in the background so answering doesn't wait on the manager, the new limit applies from
+/// the next message. The caller reads the link in its own transaction, nothing is spawned while
+/// the link is fresh and the manager is called before the write transaction is opened.
+pub fn refresh_account_link_if_stale(account_link: Option<&AccountLink>, tg_id: i64) {
+    let Some(user_id) = account_link
+        .filter(|link| link.is_stale())
+        .map(|link| link.user_id)
+    else {
+        return;
+    };
+    tokio::spawn(async move {
+        let result: anyhow::Result<()> = async {
+            let account = get_account(tg_id).await?;
+            let pool = get_pool().await;
+            let mut transaction = create_txn(pool).await?;
+            apply_account(&mut transaction, &user_id, account.as_ref()).await?;
+            commit_txn(transaction).await?;
+            Ok(())
+        }
+        .await;
+        if let Err(e) = result {
+            tracing::warn!("Cannot refresh account link of {}: {:?}", user_id, e);
+        }
+    });
+}
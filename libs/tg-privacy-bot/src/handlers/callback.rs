@@ -1,10 +1,13 @@
+use crate::ai_models::ask::get_ai_client;
 use crate::database::calls::get_or_create_usage::get_or_create_usage;
 use crate::database::calls::get_or_create_user::get_or_create_user;
 use crate::database::calls::get_or_create_user_settings::get_or_create_user_settings;
 use crate::database::calls::update_user_settings_message_mode::update_user_settings_message_mode;
 use crate::database::calls::update_user_settings_model_name::update_user_settings_model_name;
+use crate::database::calls::update_user_settings_redact_pii::update_user_settings_redact_pii;
 use crate::database::db_utils::get_pool;
 use crate::database::models::message_mode::MessageMode;
+use crate::handlers::settings_owner::{get_or_create_settings_owner, is_group};
 use crate::HandlerResult;
 use ai_interfaces::models::base::ModelName;
 use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
@@ -27,7 +30,20 @@ pub async fn callback_handler(bot: Bot, query: CallbackQuery) -> HandlerResult {
     let tg_id = from.id.0;
     let user = get_or_create_user(&mut transaction, tg_id as i64, &username).await?;
     let _ = get_or_create_usage(&mut transaction, &user.id).await?;
-    let user_settings = get_or_create_user_settings(&mut transaction, &user.id).await?;
+    let owner = match query.message {
+        Some(ref message) if is_group(message.chat()) => {
+            let member = bot.get_chat_member(message.chat().id, from.id).await?;
+            if !member.is_privileged() {
+                bot.answer_callback_query(query.id)
+                    .text("Only group admins can change the group settings")
+                    .await?;
+                return Ok(());
+            }
+            get_or_create_settings_owner(&mut transaction, message.chat(), &from).await?
+        }
+        _ => user,
+    };
+    let user_settings = get_or_create_user_settings(&mut transaction, &owner.id).await?;
 
     if let Some(data) = query.data {
         let data = data.as_str().to_string();
@@ -45,19 +61,40 @@ pub async fn callback_handler(bot: Bot, query: CallbackQuery) -> HandlerResult {
             }
             // Acknowledge the callback query
             bot.answer_callback_query(query.id).await?;
-        } else if data.starts_with("select_model_") {
-            let model_name = ModelName::from(data.replace("select_model_", "").trim().to_string());
-            update_user_settings_model_name(&mut transaction, &user_settings.id, &model_name)
+        } else if data.starts_with("redact_pii_") {
+            let redact_pii = data.replace("redact_pii_", "").trim() == "true";
+            update_user_settings_redact_pii(&mut transaction, &user_settings.id, redact_pii)
                 .await?;
             if let Some(message) = query.message {
                 bot.send_message(
                     message.chat().id,
-                    format!("Changed to model: {}", model_name),
+                    format!(
+                        "Personal data redaction is {}",
+                        if redact_pii { "on" } else { "off" }
+                    ),
                 )
                 .await?;
             }
             // Acknowledge the callback query
             bot.answer_callback_query(query.id).await?;
+        } else if data.starts_with("select_model_") {
+            let model_name = ModelName::from(data.replace("select_model_", "").trim().to_string());
+            // Keyboards sent before a provider was removed can still offer its models
+            let supported = get_ai_client().supports(&model_name.client_kind());
+            if supported {
+                update_user_settings_model_name(&mut transaction, &user_settings.id, &model_name)
+                    .await?;
+            }
+            if let Some(message) = query.message {
+                let text = if supported {
+                    format!("Changed to model: {}", model_name)
+                } else {
+                    format!("Model {} is not available", model_name)
+                };
+                bot.send_message(message.chat().id, text).await?;
+            }
+            // Acknowledge the callback query
+            bot.answer_callback_query(query.id).await?;
         }
     }
     commit_txn(transaction).await?;
@@ -3,17 +3,10 @@ use teloxide::Bot;
 
 use crate::HandlerResult;
 
-#[tracing::instrument(name = "chosen_inline_result_handler", skip(_bot, _q))]
-pub async fn chosen_inline_result_handler(_bot: Bot, _q: ChosenInlineResult) -> HandlerResult {
-    // let choose_debian_version = InlineQueryResultArticle::new(
-    //     "0",
-    //     "Chose debian version",
-    //     InputMessageContent::Text(InputMessageContentText::new("Debian versions:")),
-    // )
-    // .reply_markup(make_actions_keyboard());
-    //
-    // let choose_debian_version = "11";
-    // bot.answer_inline_query(q.id, vec![choose_debian_version.into()])
-    //     .await?;
+/// Only sent when inline feedback is enabled for the bot, the answer was already charged
+/// when the inline query was answered.
+#[tracing::instrument(name = "chosen_inline_result_handler", skip(_bot, q))]
+pub async fn chosen_inline_result_handler(_bot: Bot, q: ChosenInlineResult) -> HandlerResult {
+    tracing::info!("Inline answer {} chosen by {}", q.result_id, q.from.id);
     Ok(())
 }
@@ -1,15 +1,13 @@
 use crate::{HandlerResult, MyDialogue};
 use teloxide::prelude::*;
 
-// const HELP_TEXT: &str = r#"
-// /select_mode - Select message context mode
-// /select_model - Select a different model
-// /info - Show current settings
-// /help - This message
-// "#;
-
 const HELP_TEXT: &str = r#"
+/select_mode - Select message context mode
 /select_model - Select a different model
+/privacy - Redact personal data before it is sent to the model
+/reset - Clear the conversation history
+/link - Link your BlockMesh account with the code from the dashboard
+/stats - Show your BlockMesh tier and node stats
 /info - Show current settings
 /help - This message
 "#;
@@ -1,7 +1,9 @@
+use crate::database::calls::get_account_link::get_account_link;
 use crate::database::calls::get_or_create_usage::get_or_create_usage;
 use crate::database::calls::get_or_create_user::get_or_create_user;
 use crate::database::calls::get_or_create_user_settings::get_or_create_user_settings;
 use crate::database::db_utils::get_pool;
+use crate::handlers::settings_owner::get_or_create_settings_owner;
 use crate::{HandlerResult, MyDialogue};
 use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
 use teloxide::prelude::*;
@@ -18,19 +20,23 @@ pub async fn info(bot: Bot, _dialogue: MyDialogue, msg: Message) -> HandlerResul
             let _message = msg.text().unwrap_or_default().to_string();
             let user = get_or_create_user(&mut transaction, tg_id as i64, &username).await?;
             let usage = get_or_create_usage(&mut transaction, &user.id).await?;
-            let user_settings = get_or_create_user_settings(&mut transaction, &user.id).await?;
+            let owner = get_or_create_settings_owner(&mut transaction, &msg.chat, from).await?;
+            let user_settings = get_or_create_user_settings(&mut transaction, &owner.id).await?;
+            let tier = get_account_link(&mut transaction, &user.id)
+                .await?
+                .map(|link| link.tier())
+                .unwrap_or_default();
             commit_txn(transaction).await?;
-            // let response = format!(
-            //     r#"
-            //     Mode: {} | Model Name: {}
-            //     "#,
-            //     user_settings.message_mode, user_settings.model_name
-            // );
             let response = format!(
                 r#"
-                Model Name: {} | Usage {} / {}
+                Mode: {} | Model Name: {} | Redact PII: {} | Tier: {} | Usage {} / {}
                 "#,
-                user_settings.model_name, usage.usage, usage.usage_limit
+                user_settings.message_mode,
+                user_settings.model_name,
+                user_settings.redact_pii,
+                tier,
+                usage.usage,
+                usage.usage_limit
             );
             let _r = bot.send_message(msg.chat.id, response).await;
         }
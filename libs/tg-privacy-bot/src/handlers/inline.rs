@@ -1,19 +1,167 @@
+use crate::ai_models::ask::{answer_text, ask};
+use crate::database::calls::get_account_link::get_account_link;
+use crate::database::calls::get_or_create_usage::get_or_create_usage;
+use crate::database::calls::get_or_create_user::get_or_create_user;
+use crate::database::calls::get_or_create_user_settings::get_or_create_user_settings;
+use crate::database::calls::increment_usage::increment_usage;
+use crate::database::calls::record_provider_usage::record_provider_usage;
+use crate::database::db_utils::get_pool;
+use crate::handlers::account_link::refresh_account_link_if_stale;
+use crate::HandlerResult;
+use ai_interfaces::clients::bulk::Message as AiMessage;
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+use std::collections::HashMap;
+use std::env;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
 use teloxide::prelude::*;
+use teloxide::types::{
+    InlineQueryResult, InlineQueryResultArticle, InputMessageContent, InputMessageContentText,
+};
 use teloxide::Bot;
 
-use crate::HandlerResult;
+/// Telegram limit for the text of a message
+const MAX_MESSAGE_LENGTH: usize = 4096;
+
+/// Latest inline query id per user, older queries are superseded while the user is typing.
+static LATEST_QUERIES: OnceLock<Mutex<HashMap<UserId, String>>> = OnceLock::new();
+
+fn latest_queries() -> &'static Mutex<HashMap<UserId, String>> {
+    LATEST_QUERIES.get_or_init(Default::default)
+}
+
+fn debounce_delay() -> Duration {
+    let millis = env::var("INLINE_DEBOUNCE_MS")
+        .unwrap_or("800".to_string())
+        .parse()
+        .unwrap_or(800);
+    Duration::from_millis(millis)
+}
+
+/// Telegram stops accepting an answer to an inline query after about ten seconds.
+fn answer_timeout() -> Duration {
+    let millis = env::var("INLINE_ANSWER_TIMEOUT_MS")
+        .unwrap_or("7000".to_string())
+        .parse()
+        .unwrap_or(7000);
+    Duration::from_millis(millis)
+}
 
-#[tracing::instrument(name = "inline_query_handler", skip(_bot, _q))]
-pub async fn inline_query_handler(_bot: Bot, _q: InlineQuery) -> HandlerResult {
-    // let choose_debian_version = InlineQueryResultArticle::new(
-    //     "0",
-    //     "Chose debian version",
-    //     InputMessageContent::Text(InputMessageContentText::new("Debian versions:")),
-    // )
-    // .reply_markup(make_actions_keyboard());
-    //
-    // let choose_debian_version = "11";
-    // bot.answer_inline_query(q.id, vec![choose_debian_version.into()])
-    //     .await?;
+/// Waits `delay` and returns whether `query_id` is still the latest inline query of the user.
+async fn debounce(user_id: UserId, query_id: &str, delay: Duration) -> bool {
+    latest_queries()
+        .lock()
+        .unwrap()
+        .insert(user_id, query_id.to_string());
+    tokio::time::sleep(delay).await;
+    let mut latest = latest_queries().lock().unwrap();
+    if latest.get(&user_id).map(String::as_str) != Some(query_id) {
+        return false;
+    }
+    latest.remove(&user_id);
+    true
+}
+
+fn article(title: &str, text: String) -> InlineQueryResult {
+    let text = text.chars().take(MAX_MESSAGE_LENGTH).collect::<String>();
+    let description = text.chars().take(100).collect::<String>();
+    InlineQueryResult::Article(
+        InlineQueryResultArticle::new(
+            "answer",
+            title,
+            InputMessageContent::Text(InputMessageContentText::new(text)),
+        )
+        .description(description),
+    )
+}
+
+#[tracing::instrument(name = "inline_query_handler", skip(bot, q))]
+pub async fn inline_query_handler(bot: Bot, q: InlineQuery) -> HandlerResult {
+    let question = q.query.trim().to_string();
+    if question.is_empty() {
+        return Ok(());
+    }
+    if !debounce(q.from.id, &q.id, debounce_delay()).await {
+        return Ok(());
+    }
+
+    let pool = get_pool().await;
+    let mut transaction = create_txn(pool).await?;
+    let username = q.from.username.clone().unwrap_or_default();
+    let user = get_or_create_user(&mut transaction, q.from.id.0 as i64, &username).await?;
+    let usage = get_or_create_usage(&mut transaction, &user.id).await?;
+    let user_settings = get_or_create_user_settings(&mut transaction, &user.id).await?;
+    let account_link = get_account_link(&mut transaction, &user.id).await?;
+    commit_txn(transaction).await?;
+    refresh_account_link_if_stale(account_link.as_ref(), q.from.id.0 as i64);
+    let result = if usage.over_limit() {
+        article("Usage limit exceeded", "Usage limit exceeded".to_string())
+    } else {
+        let model_name = user_settings.model_name;
+        let answer = tokio::time::timeout(
+            answer_timeout(),
+            ask(
+                model_name.clone(),
+                vec![AiMessage::user(&question)].into(),
+                user_settings.redact_pii,
+            ),
+        )
+        .await
+        .ok();
+        let mut transaction = create_txn(pool).await?;
+        record_provider_usage(
+            &mut transaction,
+            &usage.id,
+            &model_name,
+            answer.as_ref().and_then(|answer| answer.as_ref().ok()),
+        )
+        .await?;
+        if answer.as_ref().is_some_and(|answer| answer.is_ok()) {
+            increment_usage(&mut transaction, &usage.id).await?;
+        }
+        commit_txn(transaction).await?;
+        match answer {
+            Some(answer) => article(&question, answer_text(&model_name, &answer)),
+            None => article(
+                "Taking too long",
+                format!(
+                    "{} took too long to answer, ask in a private chat with the bot instead",
+                    model_name
+                ),
+            ),
+        }
+    };
+    bot.answer_inline_query(q.id, vec![result])
+        .is_personal(true)
+        .cache_time(0)
+        .await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_debounce_keeps_only_the_latest_query() {
+        let user_id = UserId(1);
+        let delay = Duration::from_millis(50);
+        let first = debounce(user_id, "first", delay);
+        let second = async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            debounce(user_id, "second", delay).await
+        };
+        assert_eq!(tokio::join!(first, second), (false, true));
+        assert!(!latest_queries().lock().unwrap().contains_key(&user_id));
+    }
+
+    #[tokio::test]
+    async fn test_debounce_is_per_user() {
+        let delay = Duration::from_millis(20);
+        let (first, second) = tokio::join!(
+            debounce(UserId(2), "query", delay),
+            debounce(UserId(3), "query", delay)
+        );
+        assert!(first && second);
+    }
+}
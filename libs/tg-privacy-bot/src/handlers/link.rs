@@ -0,0 +1,62 @@
+use crate::database::calls::get_or_create_user::get_or_create_user;
+use crate::database::db_utils::get_pool;
+use crate::handlers::account_link::apply_account;
+use crate::manager_api::link_account;
+use crate::{HandlerResult, MyDialogue};
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+use teloxide::prelude::*;
+use teloxide::Bot;
+
+#[tracing::instrument(name = "link", skip(bot, _dialogue, code))]
+pub async fn link(bot: Bot, _dialogue: MyDialogue, msg: Message, code: String) -> HandlerResult {
+    let Some(ref from) = msg.from else {
+        bot.send_message(msg.chat.id, "Cannot get user data")
+            .await?;
+        return Ok(());
+    };
+    let code = code.trim();
+    if code.is_empty() {
+        bot.send_message(
+            msg.chat.id,
+            "Send /link <code> with the code from your BlockMesh dashboard",
+        )
+        .await?;
+        return Ok(());
+    }
+    let tg_id = from.id.0 as i64;
+    let account = match link_account(code, tg_id).await {
+        Ok(account) => account,
+        Err(e) => {
+            tracing::error!("Cannot link account: {:?}", e);
+            bot.send_message(
+                msg.chat.id,
+                "Linking is not available right now, try again later",
+            )
+            .await?;
+            return Ok(());
+        }
+    };
+    let Some(account) = account else {
+        bot.send_message(
+            msg.chat.id,
+            "This code is unknown or expired, get a new one from the BlockMesh dashboard",
+        )
+        .await?;
+        return Ok(());
+    };
+    let pool = get_pool().await;
+    let mut transaction = create_txn(pool).await?;
+    let username = from.username.clone().unwrap_or_default();
+    let user = get_or_create_user(&mut transaction, tg_id, &username).await?;
+    apply_account(&mut transaction, &user.id, Some(&account)).await?;
+    commit_txn(transaction).await?;
+    bot.send_message(
+        msg.chat.id,
+        format!(
+            "Your BlockMesh account is linked. Tier: {} | Daily limit: {}",
+            account.tier, account.daily_limit
+        ),
+    )
+    .await?;
+    Ok(())
+}
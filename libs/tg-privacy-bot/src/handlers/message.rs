@@ -1,35 +1,160 @@
-use crate::ai_models::open_ai::ask;
+use crate::ai_models::ask::{answer_text, ask};
+use crate::ai_models::conversation::{should_reset, token_budget, Conversation};
+use crate::database::calls::add_conversation_message::add_conversation_message;
+use crate::database::calls::delete_conversation::delete_conversation;
+use crate::database::calls::delete_conversation_messages::delete_conversation_messages;
+use crate::database::calls::get_account_link::get_account_link;
+use crate::database::calls::get_conversation::get_conversation;
 use crate::database::calls::get_or_create_usage::get_or_create_usage;
 use crate::database::calls::get_or_create_user::get_or_create_user;
 use crate::database::calls::get_or_create_user_settings::get_or_create_user_settings;
 use crate::database::calls::increment_usage::increment_usage;
+use crate::database::calls::record_provider_usage::record_provider_usage;
 use crate::database::db_utils::get_pool;
+use crate::database::models::conversation_message::ConversationRole;
+use crate::database::models::message_mode::MessageMode;
+use crate::handlers::account_link::refresh_account_link_if_stale;
+use crate::handlers::settings_owner::{get_or_create_settings_owner, is_group};
 use crate::HandlerResult;
 use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
 use teloxide::prelude::*;
+use teloxide::types::{Me, ReplyParameters};
 use teloxide::Bot;
 
-#[tracing::instrument(name = "message_handler", skip(bot))]
-pub async fn message_handler(bot: Bot, msg: Message) -> HandlerResult {
+/// In groups only mentions of the bot and replies to it are answered, the mention is stripped.
+fn group_question(msg: &Message, me: &Me) -> Option<String> {
+    let text = msg.text()?;
+    let mention = format!("@{}", me.username());
+    let replied_to_bot = msg
+        .reply_to_message()
+        .and_then(|reply| reply.from.as_ref())
+        .is_some_and(|author| author.id == me.id);
+    if !text.contains(&mention) && !replied_to_bot {
+        return None;
+    }
+    Some(text.replace(&mention, "").trim().to_string())
+}
+
+#[tracing::instrument(name = "message_handler", skip(bot, me))]
+pub async fn message_handler(bot: Bot, me: Me, msg: Message) -> HandlerResult {
+    let message = if is_group(&msg.chat) {
+        match group_question(&msg, &me) {
+            Some(question) => question,
+            None => return Ok(()),
+        }
+    } else {
+        msg.text().unwrap_or_default().to_string()
+    };
     let pool = get_pool().await;
     let mut transaction = create_txn(pool).await?;
     match msg.from {
         Some(ref from) => {
             let username = from.username.clone().unwrap_or_default();
             let tg_id = from.id.0;
-            let message = msg.text().unwrap_or_default().to_string();
+            let chat_id = msg.chat.id.0;
             let user = get_or_create_user(&mut transaction, tg_id as i64, &username).await?;
+            // Usage is charged to the sender, settings come from the group in group chats
             let usage = get_or_create_usage(&mut transaction, &user.id).await?;
-            let user_settings = get_or_create_user_settings(&mut transaction, &user.id).await?;
+            let account_link = get_account_link(&mut transaction, &user.id).await?;
+            let owner = get_or_create_settings_owner(&mut transaction, &msg.chat, from).await?;
+            let user_settings = get_or_create_user_settings(&mut transaction, &owner.id).await?;
             if usage.over_limit() {
+                commit_txn(transaction).await?;
                 bot.send_message(msg.chat.id, "Usage limit exceeded")
                     .await?;
                 return Ok(());
             }
-            increment_usage(&mut transaction, &usage.id).await?;
+            let model_name = user_settings.model_name;
+            let mut history = get_conversation(&mut transaction, chat_id).await?;
+            if should_reset(&user_settings.message_mode, &model_name, &history) {
+                delete_conversation(&mut transaction, chat_id).await?;
+                history.clear();
+            }
             commit_txn(transaction).await?;
-            let response = ask(message, user_settings.model_name).await?;
-            bot.send_message(msg.chat.id, response).await?;
+            refresh_account_link_if_stale(account_link.as_ref(), tg_id as i64);
+
+            let mut conversation = Conversation::fit(history, &message, token_budget());
+            if !conversation.overflow.is_empty() {
+                let summary = conversation
+                    .summarize(model_name.clone(), user_settings.redact_pii)
+                    .await;
+                let mut transaction = create_txn(pool).await?;
+                record_provider_usage(
+                    &mut transaction,
+                    &usage.id,
+                    &model_name,
+                    summary.as_ref().ok(),
+                )
+                .await?;
+                // Without a summary the overflow is dropped, the previous summary is kept
+                let mut replaced = conversation
+                    .overflow
+                    .drain(..)
+                    .map(|message| message.id)
+                    .collect::<Vec<_>>();
+                match summary {
+                    Ok(summary) => {
+                        if let Some(previous) = conversation.summary.take() {
+                            replaced.push(previous.id);
+                        }
+                        conversation.summary = Some(
+                            add_conversation_message(
+                                &mut transaction,
+                                chat_id,
+                                &user.id,
+                                &ConversationRole::Summary,
+                                &summary.message.text(),
+                                &model_name,
+                            )
+                            .await?,
+                        );
+                    }
+                    Err(e) => tracing::warn!("Cannot summarize conversation: {:?}", e),
+                }
+                delete_conversation_messages(&mut transaction, &replaced).await?;
+                commit_txn(transaction).await?;
+            }
+
+            let result = ask(
+                model_name.clone(),
+                conversation.request(message.clone()),
+                user_settings.redact_pii,
+            )
+            .await;
+            // Failed requests are recorded per provider but do not count towards the limit
+            let mut transaction = create_txn(pool).await?;
+            record_provider_usage(
+                &mut transaction,
+                &usage.id,
+                &model_name,
+                result.as_ref().ok(),
+            )
+            .await?;
+            if let Ok(ref completion) = result {
+                increment_usage(&mut transaction, &usage.id).await?;
+                if user_settings.message_mode != MessageMode::ResetOnEachMessage {
+                    for (role, content) in [
+                        (ConversationRole::User, message),
+                        (ConversationRole::Assistant, completion.message.text()),
+                    ] {
+                        add_conversation_message(
+                            &mut transaction,
+                            chat_id,
+                            &user.id,
+                            &role,
+                            &content,
+                            &model_name,
+                        )
+                        .await?;
+                    }
+                }
+            }
+            commit_txn(transaction).await?;
+            let mut response = bot.send_message(msg.chat.id, answer_text(&model_name, &result));
+            if is_group(&msg.chat) {
+                response = response.reply_parameters(ReplyParameters::new(msg.id));
+            }
+            response.await?;
         }
         None => {
             bot.send_message(msg.chat.id, "Cannot get user data")
@@ -38,3 +163,80 @@ pub async fn message_handler(bot: Bot, msg: Message) -> HandlerResult {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const BOT_ID: u64 = 42;
+
+    fn me() -> Me {
+        serde_json::from_value(json!({
+            "id": BOT_ID,
+            "is_bot": true,
+            "first_name": "Privacy",
+            "username": "privacy_bot",
+            "can_join_groups": true,
+            "can_read_all_group_messages": false,
+            "supports_inline_queries": true
+        }))
+        .unwrap()
+    }
+
+    fn group_message(text: &str, reply_author: Option<u64>) -> Message {
+        let mut message = json!({
+            "message_id": 2,
+            "date": 0,
+            "chat": {"id": -100, "type": "supergroup", "title": "group"},
+            "from": {"id": 7, "is_bot": false, "first_name": "User"},
+            "text": text
+        });
+        if let Some(author) = reply_author {
+            message["reply_to_message"] = json!({
+                "message_id": 1,
+                "date": 0,
+                "chat": {"id": -100, "type": "supergroup", "title": "group"},
+                "from": {"id": author, "is_bot": author == BOT_ID, "first_name": "Author"},
+                "text": "earlier"
+            });
+        }
+        serde_json::from_value(message).unwrap()
+    }
+
+    #[test]
+    fn test_group_question_strips_the_mention() {
+        assert_eq!(
+            group_question(&group_message("@privacy_bot what is rust?", None), &me()),
+            Some("what is rust?".to_string())
+        );
+        assert_eq!(
+            group_question(&group_message("what is rust, @privacy_bot", None), &me()),
+            Some("what is rust,".to_string())
+        );
+    }
+
+    #[test]
+    fn test_group_question_answers_replies_to_the_bot() {
+        assert_eq!(
+            group_question(&group_message("and then?", Some(BOT_ID)), &me()),
+            Some("and then?".to_string())
+        );
+        assert_eq!(
+            group_question(&group_message("and then?", Some(8)), &me()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_group_question_ignores_other_messages() {
+        assert_eq!(
+            group_question(&group_message("what is rust?", None), &me()),
+            None
+        );
+        assert_eq!(
+            group_question(&group_message("@other_bot what is rust?", None), &me()),
+            None
+        );
+    }
+}
@@ -1,9 +1,15 @@
+pub mod account_link;
 pub mod callback;
 pub mod chosen_inline_result;
 pub mod help;
 pub mod info;
 pub mod inline;
+pub mod link;
 pub mod message;
+pub mod privacy;
+pub mod reset;
 pub mod select_mode;
 pub mod select_model;
+pub mod settings_owner;
 pub mod start;
+pub mod stats;
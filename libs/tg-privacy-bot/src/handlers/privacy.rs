@@ -0,0 +1,57 @@
+use crate::database::calls::get_or_create_usage::get_or_create_usage;
+use crate::database::calls::get_or_create_user::get_or_create_user;
+use crate::database::calls::get_or_create_user_settings::get_or_create_user_settings;
+use crate::database::db_utils::get_pool;
+use crate::handlers::settings_owner::get_or_create_settings_owner;
+use crate::{HandlerResult, MyDialogue};
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+use teloxide::prelude::*;
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup};
+use teloxide::Bot;
+
+#[tracing::instrument(name = "privacy", skip(bot, _dialogue))]
+pub async fn privacy(bot: Bot, _dialogue: MyDialogue, msg: Message) -> HandlerResult {
+    let pool = get_pool().await;
+    let mut transaction = create_txn(pool).await?;
+
+    let redact_pii = match msg.from {
+        Some(ref from) => {
+            let username = from.username.clone().unwrap_or_default();
+            let tg_id = from.id.0;
+            let user = get_or_create_user(&mut transaction, tg_id as i64, &username).await?;
+            let _ = get_or_create_usage(&mut transaction, &user.id).await?;
+            let owner = get_or_create_settings_owner(&mut transaction, &msg.chat, from).await?;
+            let user_settings = get_or_create_user_settings(&mut transaction, &owner.id).await?;
+            commit_txn(transaction).await?;
+            user_settings.redact_pii
+        }
+        None => {
+            bot.send_message(msg.chat.id, "Cannot get user data")
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let keyboard = InlineKeyboardMarkup::new(vec![
+        vec![InlineKeyboardButton::callback(
+            "Redact personal data",
+            "redact_pii_true",
+        )],
+        vec![InlineKeyboardButton::callback(
+            "Send messages as they are",
+            "redact_pii_false",
+        )],
+    ]);
+
+    bot.send_message(
+        msg.chat.id,
+        format!(
+            "Emails, phone numbers, wallet addresses, IBANs and names are replaced before \
+            messages are sent to the model and restored in the answer.\nRedaction is {}:",
+            if redact_pii { "on" } else { "off" }
+        ),
+    )
+    .reply_markup(keyboard)
+    .await?;
+    Ok(())
+}
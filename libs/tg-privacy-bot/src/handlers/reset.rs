@@ -0,0 +1,17 @@
+use crate::database::calls::delete_conversation::delete_conversation;
+use crate::database::db_utils::get_pool;
+use crate::{HandlerResult, MyDialogue};
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+use teloxide::prelude::*;
+use teloxide::Bot;
+
+#[tracing::instrument(name = "reset", skip(bot, _dialogue))]
+pub async fn reset(bot: Bot, _dialogue: MyDialogue, msg: Message) -> HandlerResult {
+    let pool = get_pool().await;
+    let mut transaction = create_txn(pool).await?;
+    delete_conversation(&mut transaction, msg.chat.id.0).await?;
+    commit_txn(transaction).await?;
+    bot.send_message(msg.chat.id, "Conversation history cleared")
+        .await?;
+    Ok(())
+}
@@ -3,13 +3,13 @@ use crate::database::calls::get_or_create_user::get_or_create_user;
 use crate::database::calls::get_or_create_user_settings::get_or_create_user_settings;
 use crate::database::db_utils::get_pool;
 use crate::database::models::message_mode::MessageMode;
+use crate::handlers::settings_owner::get_or_create_settings_owner;
 use crate::{HandlerResult, MyDialogue};
 use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
 use teloxide::prelude::*;
 use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup};
 use teloxide::Bot;
 
-#[allow(dead_code)]
 #[tracing::instrument(name = "select_mode", skip(bot, _dialogue))]
 pub async fn select_mode(bot: Bot, _dialogue: MyDialogue, msg: Message) -> HandlerResult {
     let pool = get_pool().await;
@@ -22,7 +22,8 @@ pub async fn select_mode(bot: Bot, _dialogue: MyDialogue, msg: Message) -> Handl
             let _message = msg.text().unwrap_or_default().to_string();
             let user = get_or_create_user(&mut transaction, tg_id as i64, &username).await?;
             let _ = get_or_create_usage(&mut transaction, &user.id).await?;
-            let _ = get_or_create_user_settings(&mut transaction, &user.id).await?;
+            let owner = get_or_create_settings_owner(&mut transaction, &msg.chat, from).await?;
+            let _ = get_or_create_user_settings(&mut transaction, &owner.id).await?;
             commit_txn(transaction).await?;
         }
         None => {
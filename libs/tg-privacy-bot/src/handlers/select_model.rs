@@ -1,3 +1,4 @@
+use crate::ai_models::ask::get_ai_client;
 use crate::{HandlerResult, MyDialogue};
 use ai_interfaces::models::base::ModelName;
 use enum_iterator::all;
@@ -7,7 +8,15 @@ use teloxide::Bot;
 
 #[tracing::instrument(name = "select_model", skip(bot, _dialogue))]
 pub async fn select_model(bot: Bot, _dialogue: MyDialogue, msg: Message) -> HandlerResult {
-    let model_names = all::<ModelName>().collect::<Vec<_>>();
+    let ai_client = get_ai_client();
+    let model_names = all::<ModelName>()
+        .filter(|model_name| ai_client.supports(&model_name.client_kind()))
+        .collect::<Vec<_>>();
+    if model_names.is_empty() {
+        bot.send_message(msg.chat.id, "No models are available")
+            .await?;
+        return Ok(());
+    }
 
     let model_names_keyboard = model_names
         .iter()
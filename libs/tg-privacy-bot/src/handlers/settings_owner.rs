@@ -0,0 +1,23 @@
+use crate::database::calls::get_or_create_user::get_or_create_user;
+use crate::database::models::user::User;
+use sqlx::{Postgres, Transaction};
+use teloxide::types::{Chat, User as TgUser};
+
+pub fn is_group(chat: &Chat) -> bool {
+    chat.is_group() || chat.is_supergroup()
+}
+
+/// Groups share one set of settings and one history between members, stored under a user row
+/// keyed by the group's chat id, which Telegram keeps negative so it never collides with a user id.
+pub async fn get_or_create_settings_owner(
+    transaction: &mut Transaction<'_, Postgres>,
+    chat: &Chat,
+    from: &TgUser,
+) -> anyhow::Result<User> {
+    if is_group(chat) {
+        get_or_create_user(transaction, chat.id.0, chat.title().unwrap_or_default()).await
+    } else {
+        let username = from.username.clone().unwrap_or_default();
+        get_or_create_user(transaction, from.id.0 as i64, &username).await
+    }
+}
@@ -0,0 +1,59 @@
+use crate::database::calls::get_or_create_user::get_or_create_user;
+use crate::database::db_utils::get_pool;
+use crate::handlers::account_link::apply_account;
+use crate::manager_api::get_account;
+use crate::{HandlerResult, MyDialogue};
+use database_utils::utils::instrument_wrapper::{commit_txn, create_txn};
+use teloxide::prelude::*;
+use teloxide::Bot;
+
+#[tracing::instrument(name = "stats", skip(bot, _dialogue))]
+pub async fn stats(bot: Bot, _dialogue: MyDialogue, msg: Message) -> HandlerResult {
+    let Some(ref from) = msg.from else {
+        bot.send_message(msg.chat.id, "Cannot get user data")
+            .await?;
+        return Ok(());
+    };
+    let tg_id = from.id.0 as i64;
+    let account = match get_account(tg_id).await {
+        Ok(account) => account,
+        Err(e) => {
+            tracing::error!("Cannot get account: {:?}", e);
+            bot.send_message(
+                msg.chat.id,
+                "Stats are not available right now, try again later",
+            )
+            .await?;
+            return Ok(());
+        }
+    };
+    let pool = get_pool().await;
+    let mut transaction = create_txn(pool).await?;
+    let username = from.username.clone().unwrap_or_default();
+    let user = get_or_create_user(&mut transaction, tg_id, &username).await?;
+    apply_account(&mut transaction, &user.id, account.as_ref()).await?;
+    commit_txn(transaction).await?;
+    let Some(account) = account else {
+        bot.send_message(
+            msg.chat.id,
+            "Link your BlockMesh account first: get a code on the dashboard and send /link <code>",
+        )
+        .await?;
+        return Ok(());
+    };
+    let mut response = format!(
+        "Tier: {} | Daily limit: {} | Points: {:.1}\n",
+        account.tier, account.daily_limit, account.points
+    );
+    for day in &account.daily_stats {
+        response.push_str(&format!(
+            "\n{} | Uptime {:.1}h | Tasks {} | Points {:.1}",
+            day.day,
+            day.uptime / 3_600.0,
+            day.tasks_count,
+            day.points
+        ));
+    }
+    bot.send_message(msg.chat.id, response).await?;
+    Ok(())
+}
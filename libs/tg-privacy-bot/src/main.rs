@@ -3,7 +3,9 @@ mod commands;
 mod database;
 mod error;
 mod handlers;
+mod manager_api;
 
+use crate::ai_models::ask::{blockmesh_egress, init_ai_client};
 use crate::commands::Commands;
 use crate::database::db_utils::get_pool;
 use crate::error::Error;
@@ -45,9 +47,13 @@ fn bot_schema() -> UpdateHandler<Box<dyn std::error::Error + Send + Sync + 'stat
     let command_handler = teloxide::filter_command::<Commands, _>().branch(
         case![State::Start]
             .branch(case![Commands::Help].endpoint(handlers::help::help))
-            // .branch(case![Commands::SelectMode].endpoint(handlers::select_mode::select_mode))
+            .branch(case![Commands::SelectMode].endpoint(handlers::select_mode::select_mode))
             .branch(case![Commands::SelectModel].endpoint(handlers::select_model::select_model))
             .branch(case![Commands::Info].endpoint(handlers::info::info))
+            .branch(case![Commands::Privacy].endpoint(handlers::privacy::privacy))
+            .branch(case![Commands::Reset].endpoint(handlers::reset::reset))
+            .branch(case![Commands::Link(code)].endpoint(handlers::link::link))
+            .branch(case![Commands::Stats].endpoint(handlers::stats::stats))
             .branch(case![Commands::Start].endpoint(handlers::start::start)),
     );
 
@@ -144,6 +150,12 @@ async fn run() -> anyhow::Result<()> {
     let db_pool = get_pool().await;
     let env = env::var("APP_ENVIRONMENT")?;
     migrate(db_pool, env).await?;
+    let ai_client = init_ai_client()?;
+    tracing::info!(
+        "AI providers: {:?}, BlockMesh egress: {}",
+        ai_client.client_kinds(),
+        blockmesh_egress()
+    );
     println!("Dispatching bot");
 
     let router = Router::new()
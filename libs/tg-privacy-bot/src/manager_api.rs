@@ -0,0 +1,61 @@
+use anyhow::anyhow;
+use block_mesh_common::constants::BLOCK_MESH_APP_SERVER;
+use block_mesh_common::interfaces::server_api::{
+    TgBotAccountRequest, TgBotAccountResponse, TgBotLinkRequest,
+};
+use block_mesh_common::routes_enum::RoutesEnum;
+use block_mesh_common::tg_bot::TG_BOT_SECRET_HEADER;
+use http::StatusCode;
+use serde::Serialize;
+use std::env;
+use std::sync::OnceLock;
+
+static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+
+fn client() -> &'static reqwest::Client {
+    CLIENT.get_or_init(reqwest::Client::new)
+}
+
+/// `None` when the manager answers with `missing`, the status it uses for an unknown code or
+/// an unlinked Telegram user.
+async fn post<T: Serialize>(
+    route: RoutesEnum,
+    body: &T,
+    missing: StatusCode,
+) -> anyhow::Result<Option<TgBotAccountResponse>> {
+    let secret =
+        env::var("TG_BOT_API_SECRET").map_err(|_| anyhow!("TG_BOT_API_SECRET is not set"))?;
+    let base_url = env::var("BLOCKMESH_MANAGER_URL").unwrap_or(BLOCK_MESH_APP_SERVER.to_string());
+    let response = client()
+        .post(format!("{}/api{}", base_url, route))
+        .header(TG_BOT_SECRET_HEADER, secret)
+        .json(body)
+        .send()
+        .await?;
+    if response.status() == missing {
+        return Ok(None);
+    }
+    let response = response.error_for_status()?;
+    Ok(Some(response.json().await?))
+}
+
+pub async fn link_account(code: &str, tg_id: i64) -> anyhow::Result<Option<TgBotAccountResponse>> {
+    post(
+        RoutesEnum::Api_TgBotLink,
+        &TgBotLinkRequest {
+            code: code.to_string(),
+            tg_id,
+        },
+        StatusCode::BAD_REQUEST,
+    )
+    .await
+}
+
+pub async fn get_account(tg_id: i64) -> anyhow::Result<Option<TgBotAccountResponse>> {
+    post(
+        RoutesEnum::Api_TgBotAccount,
+        &TgBotAccountRequest { tg_id },
+        StatusCode::NOT_FOUND,
+    )
+    .await
+}